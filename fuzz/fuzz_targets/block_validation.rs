@@ -0,0 +1,41 @@
+// Arbitrary bytes as a synced block, against an in-memory chain with one known
+// leader. Each input is tried as-is (hash/signature checks) and again re-signed
+// by the leader, so the fork choice and commit paths see fuzzed contents too.
+#![no_main]
+use std::sync::{Mutex, OnceLock};
+
+use libfuzzer_sys::fuzz_target;
+use rust_compass::block::Block;
+use rust_compass::chain::Chain;
+use rust_compass::crypto::KeyPair;
+
+fn leader() -> &'static KeyPair {
+    static LEADER: OnceLock<KeyPair> = OnceLock::new();
+    LEADER.get_or_init(|| KeyPair::from_seed("fuzz-leader"))
+}
+
+fn chain() -> &'static Mutex<Chain> {
+    static CHAIN: OnceLock<Mutex<Chain>> = OnceLock::new();
+    CHAIN.get_or_init(|| {
+        let chain = Chain::new_in_memory();
+        let pk = leader().public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        Mutex::new(chain)
+    })
+}
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(mut block) = bincode::deserialize::<Block>(data) else {
+        return;
+    };
+    let mut chain = chain().lock().unwrap_or_else(|e| e.into_inner());
+    let _ = chain.sync_block(block.clone());
+
+    block.header.proposer = leader().public_key_hex();
+    let Ok(hash) = block.header.calculate_hash() else {
+        return;
+    };
+    block.header.signature_hex = leader().sign_hex(&hex::decode(&hash).unwrap_or_default());
+    block.header.hash = hash;
+    let _ = chain.sync_block(block);
+});
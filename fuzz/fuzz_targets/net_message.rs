@@ -0,0 +1,20 @@
+// Arbitrary bytes as a gossipsub payload: decoding under the gossip size budget
+// must never panic, and accepted messages must pass their own structural checks.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_compass::gulf_stream::admission::MAX_TX_SIZE_BYTES;
+use rust_compass::network::{decode_net_message, NetMessage};
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(msg) = decode_net_message(data, MAX_TX_SIZE_BYTES as u64) else {
+        return;
+    };
+    assert!(msg.validate().is_ok());
+    if let NetMessage::SubmitTx(payload) = &msg {
+        let _ = payload.verify();
+        let _ = payload.get_account_id();
+    }
+
+    // The canonical encoding of an accepted message is exactly the input
+    assert_eq!(bincode::serialize(&msg).unwrap(), data);
+});
@@ -0,0 +1,17 @@
+// Arbitrary bytes as a gossiped transaction: decoding, pre-validation and
+// sender derivation must never panic, and anything that decodes must round-trip.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_compass::network::TransactionPayload;
+
+fuzz_target!(|data: &[u8]| {
+    let Ok(payload) = bincode::deserialize::<TransactionPayload>(data) else {
+        return;
+    };
+    let _ = payload.verify();
+    let _ = payload.get_account_id();
+
+    let encoded = bincode::serialize(&payload).expect("decoded payload must re-encode");
+    let again: TransactionPayload = bincode::deserialize(&encoded).expect("re-encoded payload must decode");
+    assert_eq!(encoded, bincode::serialize(&again).unwrap());
+});
@@ -0,0 +1,12 @@
+// Arbitrary bytes as a raw sync-protocol stream, read through the same framing
+// the request/response codec uses: reads stay bounded and never panic.
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use rust_compass::network::{read_sync_message, MAX_SYNC_MESSAGE_BYTES};
+
+fuzz_target!(|data: &[u8]| {
+    let mut stream = futures::io::Cursor::new(data);
+    if futures::executor::block_on(read_sync_message(&mut stream)).is_ok() {
+        assert!(data.len() as u64 <= MAX_SYNC_MESSAGE_BYTES);
+    }
+});
@@ -0,0 +1,158 @@
+// End-to-end devnet tests: a leader, a follower and a compute worker in one
+// process (see `support`), driven over JSON-RPC the way wallets and workers do.
+// Each test uses its own dev accounts so they can run in parallel against the
+// shared devnet.
+//
+//     cargo test --features devnet-it --test devnet
+mod support;
+
+use rust_compass::client::RpcClient;
+use rust_compass::crypto::KeyPair;
+use rust_compass::fixtures::{self, DEV_BALANCE, DEV_MARKET_BALANCE};
+use rust_compass::market::{OrderSide, OrderType, TimeInForce};
+use rust_compass::network::{SignedTransaction, TransactionPayload};
+use support::{devnet, wait_for_tx, wait_until, worker_keypair, CONVERGE_TIMEOUT, ECHO_MODEL};
+
+#[tokio::test]
+async fn test_transfer_converges_on_follower() {
+    let net = devnet();
+    let (leader, follower) = (net.leader(), net.follower());
+    let dave = fixtures::dev_keypair("dave");
+    let carol = fixtures::dev_keypair("carol").public_key_hex();
+
+    // A transfer signs over the head it saw; if a block lands first it is refused
+    // with a prev_hash mismatch and the wallet signs again on the new head
+    let mut status = serde_json::Value::Null;
+    for _ in 0..10 {
+        let tx_hash = rust_compass::cli::tx::submit_transfer(&leader, &dave, &dave.public_key_hex(), &carol, "Compass", 1_000, Some(0))
+            .await
+            .expect("submit transfer");
+        status = wait_for_tx(&leader, &tx_hash).await;
+        if !status["error"].as_str().is_some_and(|e| e.contains("prev_hash")) {
+            break;
+        }
+    }
+    assert_eq!(status["status"], "Confirmed", "{}", status);
+    assert_eq!(leader.get_balance(&carol, "Compass").await.unwrap(), DEV_BALANCE + 1_000);
+
+    // The follower replays the same block and ends up with the same balances and head
+    let height = status["block_height"].as_u64().expect("confirmed transfer has a block height");
+    wait_until("follower to sync the transfer block", CONVERGE_TIMEOUT, || async {
+        follower.get_chain_height().await.ok().filter(|h| *h > height)
+    })
+    .await;
+    for account in [carol.as_str(), dave.public_key_hex().as_str()] {
+        assert_eq!(
+            follower.get_balance(account, "Compass").await.unwrap(),
+            leader.get_balance(account, "Compass").await.unwrap()
+        );
+    }
+    assert_eq!(block_hash(&leader, height).await, block_hash(&follower, height).await);
+}
+
+#[tokio::test]
+async fn test_compute_job_settles_through_worker() {
+    let net = devnet();
+    let leader = net.leader();
+    let worker = worker_keypair().public_key_hex();
+    let before = leader.get_balance(&worker, "COMPUTE").await.unwrap();
+
+    let job_id = format!("IT_ECHO_{}", std::process::id());
+    let params = rust_compass::rpc::types::SubmitComputeParams {
+        job_id: job_id.clone(),
+        model_id: ECHO_MODEL.to_string(),
+        inputs: b"ping".to_vec(),
+        max_compute_units: 1_000,
+        bid_amount: 0,
+        bid_asset: "COMPASS".to_string(),
+        signature: String::new(),
+        owner_id: fixtures::dev_keypair("carol").public_key_hex(),
+    };
+    leader.call_method::<_, serde_json::Value>("submitCompute", &params).await.expect("submit compute job");
+
+    // The worker can only settle once the job's anti-cheat minimum has passed
+    wait_until("the worker to settle the job", CONVERGE_TIMEOUT, || async {
+        let pending = leader.get_pending_compute_jobs(Some(ECHO_MODEL.to_string())).await.ok()?;
+        pending.iter().all(|j| j.job_id != job_id).then_some(())
+    })
+    .await;
+    assert!(leader.get_balance(&worker, "COMPUTE").await.unwrap() > before);
+}
+
+#[tokio::test]
+async fn test_model_nft_mint_lands_in_registry() {
+    let net = devnet();
+    let leader = net.leader();
+    let model_id = "it-model";
+
+    leader
+        .call_method::<_, serde_json::Value>(
+            "configureEpochMinting",
+            serde_json::json!({ "ticker": "SOL", "model_id": model_id, "mint_at_epoch": 0, "min_accuracy_to_mint": 0.0 }),
+        )
+        .await
+        .expect("configure minting");
+    let minted: serde_json::Value = leader
+        .call_method("mintModelNFT", serde_json::json!({ "ticker": "SOL", "model_id": model_id }))
+        .await
+        .expect("mint model NFT");
+    let token_id = minted["token_id"].as_str().unwrap().to_string();
+    let status = wait_for_tx(&leader, minted["tx_hash"].as_str().unwrap()).await;
+    assert_eq!(status["status"], "Confirmed", "{}", status);
+
+    let nfts = leader.get_all_nfts().await.unwrap();
+    assert!(nfts.iter().any(|n| n.token_id == token_id), "{} not in registry", token_id);
+    // The devnet fixtures are still there alongside it
+    assert!(nfts.iter().any(|n| n.token_id == "MODEL-DEV-BTC-alice"));
+}
+
+#[tokio::test]
+async fn test_order_book_trade_settles_with_receipt() {
+    let net = devnet();
+    let leader = net.leader();
+    let (alice, bob) = (fixtures::dev_keypair("alice"), fixtures::dev_keypair("bob"));
+
+    let sell = place_order(&leader, &alice, OrderSide::Sell, 10, 5).await;
+    assert_eq!(wait_for_tx(&leader, &sell).await["status"], "Confirmed");
+    let buy = place_order(&leader, &bob, OrderSide::Buy, 10, 5).await;
+    assert_eq!(wait_for_tx(&leader, &buy).await["status"], "Confirmed");
+
+    // First two orders on a fresh book: bid 2 fills ask 1
+    let receipt: serde_json::Value = leader
+        .call_method("getTradeReceipt", serde_json::json!({ "trade_id": "2-1" }))
+        .await
+        .expect("trade receipt");
+    assert_eq!(receipt["trade"]["buyer"], bob.public_key_hex());
+    assert_eq!(receipt["trade"]["seller"], alice.public_key_hex());
+    assert_eq!(receipt["trade"]["amount"], 10);
+    assert_eq!(receipt["node_pubkey"], fixtures::validator_keypair().public_key_hex());
+
+    // Settled by a Trade block on the ledger: 10 Compass-BTC for 50 Compass
+    assert_eq!(leader.get_balance(&bob.public_key_hex(), "Compass-BTC").await.unwrap(), DEV_MARKET_BALANCE + 10);
+    assert_eq!(leader.get_balance(&alice.public_key_hex(), "Compass").await.unwrap(), DEV_BALANCE + 50);
+}
+
+async fn block_hash(client: &RpcClient, height: u64) -> serde_json::Value {
+    let block: serde_json::Value = client.call_method("getBlock", serde_json::json!({ "height": height })).await.unwrap();
+    block["header"]["hash"].clone()
+}
+
+/// Sign and submit a Compass-BTC/Compass limit order from `keypair`; returns the tx hash
+async fn place_order(client: &RpcClient, keypair: &KeyPair, side: OrderSide, amount: u64, price: u64) -> String {
+    let user = keypair.public_key_hex();
+    let nonce = client.get_nonce(&user).await.unwrap() + 1;
+    let signature = keypair.sign_hex(format!("order:{:?}:Compass-BTC/Compass:{}@{}:{}", side, amount, price, nonce).as_bytes());
+    let payload = TransactionPayload::PlaceOrder {
+        user,
+        side,
+        base: "Compass-BTC".to_string(),
+        quote: "Compass".to_string(),
+        amount,
+        price,
+        signature,
+        order_type: OrderType::Limit,
+        time_in_force: TimeInForce::Gtc,
+    };
+    let tx = SignedTransaction::sign(payload, nonce, fixtures::DEV_CHAIN_ID, keypair);
+    client.submit_signed_transaction(&tx).await.expect("submit order")
+}
@@ -0,0 +1,190 @@
+// In-process devnet for the integration tests
+//
+// One leader, one follower and one compute worker are booted per test binary, on
+// a runtime thread of their own so they outlive every test's runtime. The leader
+// runs the `--dev` fixtures with the devnet validator key; the follower dials it
+// over libp2p and syncs blocks like any other peer. Everything talks over real
+// sockets on free localhost ports.
+#![allow(dead_code)]
+
+use std::future::Future;
+use std::net::TcpListener;
+use std::path::PathBuf;
+use std::sync::{Arc, OnceLock};
+use std::time::{Duration, Instant};
+
+use rust_compass::client::RpcClient;
+use rust_compass::config::CompassConfig;
+use rust_compass::crypto::KeyPair;
+use rust_compass::fixtures;
+
+/// Model id the harness worker serves; its "inference" echoes the job's inputs
+pub const ECHO_MODEL: &str = "devnet-echo";
+
+/// How long any single convergence check may take
+pub const CONVERGE_TIMEOUT: Duration = Duration::from_secs(90);
+
+pub struct Devnet {
+    pub leader_rpc: String,
+    pub follower_rpc: String,
+    pub dir: PathBuf,
+}
+
+static DEVNET: OnceLock<Devnet> = OnceLock::new();
+
+/// The shared devnet, booted on first use
+pub fn devnet() -> &'static Devnet {
+    DEVNET.get_or_init(boot)
+}
+
+impl Devnet {
+    pub fn leader(&self) -> RpcClient {
+        RpcClient::new(self.leader_rpc.clone())
+    }
+
+    pub fn follower(&self) -> RpcClient {
+        RpcClient::new(self.follower_rpc.clone())
+    }
+}
+
+/// Keypair the harness worker submits results with
+pub fn worker_keypair() -> KeyPair {
+    fixtures::dev_keypair("worker")
+}
+
+fn free_port() -> u16 {
+    TcpListener::bind("127.0.0.1:0")
+        .and_then(|l| l.local_addr())
+        .map(|a| a.port())
+        .expect("no free localhost port")
+}
+
+fn node_config(dir: &std::path::Path, name: &str, rpc_port: u16, p2p_port: u16) -> CompassConfig {
+    let mut config = CompassConfig::default();
+    config.node.dev = true;
+    config.node.rpc_port = rpc_port;
+    config.node.p2p_port = p2p_port;
+    config.node.db_path = dir.join(format!("{}-db", name)).to_string_lossy().into_owned();
+    config.node.identity_file = dir.join(format!("{}-identity.json", name)).to_string_lossy().into_owned();
+    // Cheap PoH so blocks keep coming on a loaded CI box
+    config.consensus.vdf_iterations = Some(100);
+    // A single worker settles jobs
+    config.consensus.pouw.required_matches = 1;
+    config
+}
+
+fn boot() -> Devnet {
+    // Node logs are noisy; show them only when asked for
+    if let Ok(filter) = std::env::var("RUST_LOG") {
+        let _ = tracing_subscriber::fmt().with_env_filter(filter).with_test_writer().try_init();
+    }
+    let dir = std::env::temp_dir().join(format!("compass-devnet-it-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).expect("create devnet dir");
+    // Nodes still keep some state (wallets.json, layer2.json, ...) relative to the cwd
+    std::env::set_current_dir(&dir).expect("enter devnet dir");
+
+    let (leader_rpc, leader_p2p) = (free_port(), free_port());
+    let (follower_rpc, follower_p2p) = (free_port(), free_port());
+    let leader = node_config(&dir, "leader", leader_rpc, leader_p2p);
+    let follower = node_config(&dir, "follower", follower_rpc, follower_p2p);
+    let leader_url = format!("http://127.0.0.1:{}", leader_rpc);
+    let follower_url = format!("http://127.0.0.1:{}", follower_rpc);
+
+    let worker_url = leader_url.clone();
+    std::thread::Builder::new()
+        .name("devnet".to_string())
+        .spawn(move || {
+            let rt = tokio::runtime::Builder::new_multi_thread()
+                .enable_all()
+                .build()
+                .expect("devnet runtime");
+            rt.block_on(async move {
+                let leader_node = rust_compass::node::run_node_mode_internal(
+                    leader,
+                    None,
+                    Some(Arc::new(fixtures::validator_keypair())),
+                );
+                let follower_node = async move {
+                    // Let the leader bind before the follower dials it
+                    tokio::time::sleep(Duration::from_secs(2)).await;
+                    rust_compass::node::run_node_mode_internal(
+                        follower,
+                        Some(format!("/ip4/127.0.0.1/tcp/{}", leader_p2p)),
+                        Some(Arc::new(fixtures::dev_keypair("follower"))),
+                    )
+                    .await
+                };
+                tokio::join!(leader_node, follower_node, run_worker(worker_url));
+            });
+        })
+        .expect("spawn devnet thread");
+
+    let devnet = Devnet { leader_rpc: leader_url, follower_rpc: follower_url, dir };
+    // Block until both nodes serve RPC and the follower has synced past genesis. Tests
+    // call in from their own runtime, so the probe gets a thread and runtime of its own.
+    let (leader, follower) = (devnet.leader_rpc.clone(), devnet.follower_rpc.clone());
+    std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread().enable_all().build().expect("probe runtime");
+        rt.block_on(async {
+            let (leader, follower) = (RpcClient::new(leader), RpcClient::new(follower));
+            wait_until("leader producing blocks", CONVERGE_TIMEOUT, || async {
+                leader.get_chain_height().await.ok().filter(|h| *h > 2)
+            })
+            .await;
+            wait_until("follower syncing from the leader", CONVERGE_TIMEOUT, || async {
+                follower.get_chain_height().await.ok().filter(|h| *h > 2)
+            })
+            .await;
+        });
+    })
+    .join()
+    .expect("devnet failed to come up");
+    devnet
+}
+
+/// Minimal compute worker: polls the leader for `ECHO_MODEL` jobs and answers each
+/// with its own inputs. Results sent before the job's anti-cheat minimum are
+/// refused, so a job is simply retried on the next poll until it settles.
+async fn run_worker(rpc_url: String) {
+    let client = RpcClient::new(rpc_url);
+    let worker_id = worker_keypair().public_key_hex();
+    loop {
+        tokio::time::sleep(Duration::from_secs(1)).await;
+        let Ok(jobs) = client.get_pending_compute_jobs(Some(ECHO_MODEL.to_string())).await else {
+            continue;
+        };
+        for job in jobs {
+            let _ = client
+                .submit_result(job.job_id, worker_id.clone(), job.inputs, None, None, 0)
+                .await;
+        }
+    }
+}
+
+/// Poll `check` every 250ms until it yields a value; panics naming `what` on timeout
+pub async fn wait_until<T, F, Fut>(what: &str, timeout: Duration, mut check: F) -> T
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Option<T>>,
+{
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(value) = check().await {
+            return value;
+        }
+        if Instant::now() >= deadline {
+            panic!("timed out after {:?} waiting for {}", timeout, what);
+        }
+        tokio::time::sleep(Duration::from_millis(250)).await;
+    }
+}
+
+/// Wait for `tx_hash` to leave Gulf Stream on `client`; returns its status record
+pub async fn wait_for_tx(client: &RpcClient, tx_hash: &str) -> serde_json::Value {
+    wait_until(&format!("tx {}", tx_hash), CONVERGE_TIMEOUT, || async {
+        let status = client.get_transaction_status(tx_hash).await.ok()?;
+        matches!(status["status"].as_str(), Some("Confirmed") | Some("Failed")).then_some(status)
+    })
+    .await
+}
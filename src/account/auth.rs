@@ -26,7 +26,7 @@ pub enum AuthorizationModel {
     /// Single key authorization
     SingleKey { pubkey: String },
     
-    /// Multi-signature (future use)
+    /// m-of-n multi-signature
     MultiSig { threshold: u8, keys: Vec<String> },
     
     /// Key rotation support
@@ -48,6 +48,32 @@ impl AuthorizationModel {
             }
         }
     }
+
+    /// Signatures needed to authorize a transaction
+    pub fn threshold(&self) -> usize {
+        match self {
+            Self::MultiSig { threshold, .. } => *threshold as usize,
+            _ => 1,
+        }
+    }
+
+    /// Check `approvals` (pubkey, signature) over `message` and return how many distinct
+    /// authorized keys signed validly. Errors if that is below the threshold.
+    pub fn verify_threshold(&self, message: &[u8], approvals: &[(String, String)]) -> Result<usize, String> {
+        let mut signed: Vec<&str> = Vec::new();
+        for (pubkey, signature) in approvals {
+            if signed.contains(&pubkey.as_str()) || !self.can_sign(pubkey) {
+                continue;
+            }
+            if crate::crypto::verify_with_pubkey_hex(message, signature, pubkey) {
+                signed.push(pubkey);
+            }
+        }
+        if signed.len() < self.threshold() {
+            return Err(format!("{} of {} required signatures", signed.len(), self.threshold()));
+        }
+        Ok(signed.len())
+    }
 }
 
 /// Hash a password using Argon2id
@@ -161,4 +187,25 @@ mod tests {
         assert!(auth.can_sign("0x1234"));
         assert!(!auth.can_sign("0x5678"));
     }
+
+    #[test]
+    fn test_multisig_threshold() {
+        use crate::crypto::KeyPair;
+        let keys: Vec<KeyPair> = ["a", "b", "c"].iter().map(|s| KeyPair::from_seed(s)).collect();
+        let auth = AuthorizationModel::MultiSig {
+            threshold: 2,
+            keys: keys.iter().map(|k| k.public_key_hex()).collect(),
+        };
+        let msg = b"pay bob";
+        let approve = |k: &KeyPair| (k.public_key_hex(), k.sign_hex(msg));
+
+        // One approval, even repeated, is not enough
+        assert!(auth.verify_threshold(msg, &[approve(&keys[0]), approve(&keys[0])]).is_err());
+        // Outsiders and bad signatures do not count
+        let outsider = KeyPair::from_seed("mallory");
+        let forged = (keys[1].public_key_hex(), keys[1].sign_hex(b"pay mallory"));
+        assert!(auth.verify_threshold(msg, &[approve(&keys[0]), approve(&outsider), forged]).is_err());
+
+        assert_eq!(auth.verify_threshold(msg, &[approve(&keys[0]), approve(&keys[2])]).unwrap(), 2);
+    }
 }
@@ -124,12 +124,12 @@ impl BalanceStore {
         }
     }
     
-    /// Get total supply of an asset
-    pub fn total_supply(&self, asset: &Asset) -> u64 {
+    /// Get total supply of an asset. Summed as u128: each balance fits a u64, their total need not.
+    pub fn total_supply(&self, asset: &Asset) -> u128 {
         self.balances
             .iter()
             .filter(|((_, ast), _)| ast == asset)
-            .map(|(_, balance)| balance)
+            .map(|(_, balance)| *balance as u128)
             .sum()
     }
     
@@ -187,5 +187,11 @@ mod tests {
         store.credit(&"bob".to_string(), &"COMPASS".to_string(), 500).unwrap();
         
         assert_eq!(store.total_supply(&"COMPASS".to_string()), 1500);
+
+        // Per-account balances cap at u64, the total does not
+        store.set_balance(&"alice".to_string(), &"COMPASS".to_string(), u64::MAX);
+        store.set_balance(&"bob".to_string(), &"COMPASS".to_string(), u64::MAX);
+        assert_eq!(store.total_supply(&"COMPASS".to_string()), 2 * u64::MAX as u128);
+        assert_eq!(store.credit(&"bob".to_string(), &"COMPASS".to_string(), 1), Err(BalanceError::Overflow));
     }
 }
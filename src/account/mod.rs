@@ -11,9 +11,12 @@ pub mod store;
 pub mod balance;
 pub mod auth;
 pub mod recovery;
+pub mod multisig;
+pub mod session;
 
 pub use types::{Account, AccountType, AccountId};
 pub use store::AccountStore;
 pub use balance::BalanceStore;
 pub use auth::AuthorizationModel;
 pub use recovery::RecoveryKey;
+pub use multisig::MultisigProposal;
@@ -0,0 +1,60 @@
+//! Pending transactions for multisig accounts
+//!
+//! A signer proposes a transfer out of a multisig account, the other signers
+//! approve it by signing the same `approval_message`, and once the threshold is
+//! met anyone may execute it. The approvals travel in the block, where the
+//! chain checks them again against the account's signer set.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Message each signer approves. Binding the nonce means an approval is spent
+/// once the account moves past it.
+pub fn approval_message(account: &str, to: &str, asset: &str, amount: u64, nonce: u64) -> String {
+    format!("COMPASS_MULTISIG:{}:{}:{}:{}:{}", account, to, asset, amount, nonce)
+}
+
+/// A proposed transfer collecting approvals
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MultisigProposal {
+    pub proposal_id: String,
+    pub account: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub proposer: String,
+    pub approvals: Vec<(String, String)>, // (signer pubkey, signature)
+    pub created_at: u64,
+}
+
+impl MultisigProposal {
+    pub fn new(account: &str, to: &str, asset: &str, amount: u64, nonce: u64, proposer: &str, created_at: u64) -> Self {
+        let message = approval_message(account, to, asset, amount, nonce);
+        Self {
+            proposal_id: hex::encode(&Sha256::digest(message.as_bytes())[..16]),
+            account: account.to_string(),
+            to: to.to_string(),
+            asset: asset.to_string(),
+            amount,
+            nonce,
+            proposer: proposer.to_string(),
+            approvals: Vec::new(),
+            created_at,
+        }
+    }
+
+    pub fn message(&self) -> String {
+        approval_message(&self.account, &self.to, &self.asset, self.amount, self.nonce)
+    }
+
+    /// Record a signer's approval after checking the signature; re-approving replaces the old one
+    pub fn approve(&mut self, signer: &str, signature: &str) -> Result<(), String> {
+        if !crate::crypto::verify_with_pubkey_hex(self.message().as_bytes(), signature, signer) {
+            return Err("approval signature does not match the proposal".to_string());
+        }
+        self.approvals.retain(|(pk, _)| pk != signer);
+        self.approvals.push((signer.to_string(), signature.to_string()));
+        Ok(())
+    }
+}
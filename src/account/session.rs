@@ -0,0 +1,178 @@
+//! Login sessions for password accounts over RPC
+//!
+//! `login` checks an account's password once and hands back a random token.
+//! Transactions sent from that account must carry the token until it expires
+//! or is revoked. Tokens live in memory only, so a node restart logs everyone out.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use rand::rngs::OsRng;
+use rand::RngCore;
+
+/// How long a session lasts unless the node config says otherwise
+pub const DEFAULT_SESSION_TTL_MS: u64 = 15 * 60 * 1000;
+
+/// Password checks allowed per account, and per caller address, in each window
+pub const MAX_AUTH_ATTEMPTS: u32 = 5;
+
+/// Length of the password check window
+pub const AUTH_WINDOW_MS: u64 = 60 * 1000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionError {
+    /// No token presented for an account that needs one
+    Missing,
+    /// Never issued, revoked, or expired and pruned
+    Unknown,
+    Expired,
+    /// Issued to a different account
+    WrongAccount,
+    /// Too many password checks for the account or from the caller's address
+    Throttled,
+}
+
+impl std::fmt::Display for SessionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match self {
+            SessionError::Missing => "session token required: log in first",
+            SessionError::Unknown => "unknown or revoked session token",
+            SessionError::Expired => "session expired: log in again",
+            SessionError::WrongAccount => "session token belongs to another account",
+            SessionError::Throttled => "too many password attempts: try again later",
+        };
+        f.write_str(message)
+    }
+}
+
+#[derive(Debug, Clone)]
+struct Session {
+    account: String,
+    expires_at_ms: u64,
+}
+
+/// Issued session tokens, shared by every RPC listener of a node
+#[derive(Debug)]
+pub struct SessionStore {
+    ttl_ms: u64,
+    sessions: Mutex<HashMap<String, Session>>,
+    /// Password checks per throttle key: when its window opened and how many since
+    attempts: Mutex<HashMap<String, (u64, u32)>>,
+}
+
+impl Default for SessionStore {
+    fn default() -> Self {
+        Self::new(DEFAULT_SESSION_TTL_MS)
+    }
+}
+
+impl SessionStore {
+    pub fn new(ttl_ms: u64) -> Self {
+        Self { ttl_ms, sessions: Mutex::new(HashMap::new()), attempts: Mutex::new(HashMap::new()) }
+    }
+
+    fn sessions(&self) -> std::sync::MutexGuard<'_, HashMap<String, Session>> {
+        self.sessions.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Start a session for `account` (whose password has been checked); returns
+    /// the token and when it expires
+    pub fn issue(&self, account: &str, now_ms: u64) -> (String, u64) {
+        let mut bytes = [0u8; 32];
+        OsRng.fill_bytes(&mut bytes);
+        let token = hex::encode(bytes);
+        let expires_at_ms = now_ms.saturating_add(self.ttl_ms);
+
+        let mut sessions = self.sessions();
+        sessions.retain(|_, s| s.expires_at_ms > now_ms);
+        sessions.insert(token.clone(), Session { account: account.to_string(), expires_at_ms });
+        (token, expires_at_ms)
+    }
+
+    /// Check that `token` is a live session of `account`
+    pub fn check(&self, token: Option<&str>, account: &str, now_ms: u64) -> Result<(), SessionError> {
+        let token = token.filter(|t| !t.is_empty()).ok_or(SessionError::Missing)?;
+        let sessions = self.sessions();
+        let session = sessions.get(token).ok_or(SessionError::Unknown)?;
+        if session.expires_at_ms <= now_ms {
+            return Err(SessionError::Expired);
+        }
+        if session.account != account {
+            return Err(SessionError::WrongAccount);
+        }
+        Ok(())
+    }
+
+    /// Count a password check against `account` and the caller's `ip`, refusing
+    /// it once either has used up `MAX_AUTH_ATTEMPTS` in the window. Checks are
+    /// counted whether or not the password turns out right, since each costs an
+    /// argon2 hash.
+    pub fn throttle(&self, account: &str, ip: Option<&str>, now_ms: u64) -> Result<(), SessionError> {
+        let mut keys = vec![format!("account:{}", account)];
+        keys.extend(ip.map(|ip| format!("ip:{}", ip)));
+
+        let mut attempts = self.attempts.lock().unwrap_or_else(|e| e.into_inner());
+        attempts.retain(|_, (opened, _)| now_ms.saturating_sub(*opened) < AUTH_WINDOW_MS);
+        if keys.iter().any(|k| attempts.get(k).is_some_and(|(_, n)| *n >= MAX_AUTH_ATTEMPTS)) {
+            return Err(SessionError::Throttled);
+        }
+        for key in keys {
+            attempts.entry(key).or_insert((now_ms, 0)).1 += 1;
+        }
+        Ok(())
+    }
+
+    /// End one session; false if the token wasn't live
+    pub fn revoke(&self, token: &str) -> bool {
+        self.sessions().remove(token).is_some()
+    }
+
+    /// End every session of `account`, returning how many there were
+    pub fn revoke_account(&self, account: &str) -> usize {
+        let mut sessions = self.sessions();
+        let before = sessions.len();
+        sessions.retain(|_, s| s.account != account);
+        before - sessions.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sessions_expire_and_revoke() {
+        let store = SessionStore::new(1_000);
+        let (token, expires) = store.issue("alice", 5_000);
+        assert_eq!(expires, 6_000);
+        assert_eq!(store.check(Some(&token), "alice", 5_999), Ok(()));
+        assert_eq!(store.check(Some(&token), "bob", 5_500), Err(SessionError::WrongAccount));
+        assert_eq!(store.check(Some(&token), "alice", 6_000), Err(SessionError::Expired));
+        assert_eq!(store.check(None, "alice", 5_500), Err(SessionError::Missing));
+        assert_eq!(store.check(Some("forged"), "alice", 5_500), Err(SessionError::Unknown));
+
+        assert!(store.revoke(&token));
+        assert!(!store.revoke(&token));
+        assert_eq!(store.check(Some(&token), "alice", 5_500), Err(SessionError::Unknown));
+
+        let (a, _) = store.issue("alice", 10_000);
+        let (b, _) = store.issue("alice", 10_000);
+        let (c, _) = store.issue("carol", 10_000);
+        assert_ne!(a, b);
+        assert_eq!(store.revoke_account("alice"), 2);
+        assert_eq!(store.check(Some(&c), "carol", 10_001), Ok(()));
+    }
+
+    #[test]
+    fn test_password_checks_throttled_per_account_and_address() {
+        let store = SessionStore::default();
+        for _ in 0..MAX_AUTH_ATTEMPTS {
+            assert_eq!(store.throttle("alice", Some("10.0.0.1"), 1_000), Ok(()));
+        }
+        assert_eq!(store.throttle("alice", Some("10.0.0.2"), 1_000), Err(SessionError::Throttled));
+        // The address is spent too, whichever account it tries next
+        assert_eq!(store.throttle("bob", Some("10.0.0.1"), 1_000), Err(SessionError::Throttled));
+        assert_eq!(store.throttle("bob", Some("10.0.0.2"), 1_000), Ok(()));
+        assert_eq!(store.throttle("alice", Some("10.0.0.1"), 1_000 + AUTH_WINDOW_MS), Ok(()));
+    }
+}
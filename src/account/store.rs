@@ -3,7 +3,7 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
-use super::types::{Account, AccountId, AccountType};
+use super::types::{Account, AccountId, AccountType, MultisigAccountData};
 use super::auth::{hash_password, verify_password, encrypt_data, decrypt_data, AuthError};
 use super::recovery::{RecoveryKey, RecoveryError};
 use crate::storage::Storage;
@@ -69,7 +69,7 @@ impl AccountStore {
         }
         
         // Validate account name (lowercase, alphanumeric + underscore)
-        if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_') {
+        if !valid_account_name(&name) {
             return Err(AccountStoreError::InvalidCredentials);
         }
         
@@ -123,6 +123,51 @@ impl AccountStore {
         Ok(account)
     }
     
+    /// Create an m-of-n multisig account. It has no password or key of its own;
+    /// transfers out of it are authorized by `threshold` of `signers`.
+    pub fn create_multisig_account(
+        &mut self,
+        name: String,
+        threshold: u8,
+        signers: Vec<String>,
+    ) -> Result<Account, AccountStoreError> {
+        if self.accounts.contains_key(&name) {
+            return Err(AccountStoreError::AccountAlreadyExists);
+        }
+        if !valid_account_name(&name) {
+            return Err(AccountStoreError::InvalidCredentials);
+        }
+        let mut distinct = signers.clone();
+        distinct.sort();
+        distinct.dedup();
+        let valid_keys = signers.iter().all(|k| hex::decode(k).map(|b| b.len() == 32).unwrap_or(false));
+        if threshold == 0 || threshold as usize > signers.len() || distinct.len() != signers.len() || !valid_keys {
+            return Err(AccountStoreError::InvalidCredentials);
+        }
+
+        let account = Account {
+            name: name.clone(),
+            account_type: AccountType::Multisig(MultisigAccountData { threshold, signers }),
+            password_hash: String::new(),
+            salt: Vec::new(),
+            backup_seed_encrypted: Vec::new(),
+            recovery_pubkey: String::new(),
+            signing_pubkey: String::new(),
+            signing_privkey_encrypted: Vec::new(),
+            nonce: 0,
+            created_at: current_timestamp(),
+            metadata: HashMap::new(),
+        };
+        self.accounts.insert(name, account.clone());
+
+        if let Some(storage) = &self.storage {
+            storage.save_account(&account)
+                .map_err(|e| AccountStoreError::StorageError(e.to_string()))?;
+        }
+
+        Ok(account)
+    }
+
     /// Authenticate an account with password
     pub fn authenticate(
         &self,
@@ -226,6 +271,20 @@ impl AccountStore {
     pub fn account_names(&self) -> Vec<String> {
         self.accounts.keys().cloned().collect()
     }
+
+    /// Name of the account whose signing key is `pubkey` (hex)
+    pub fn name_for_pubkey(&self, pubkey: &str) -> Option<&str> {
+        self.accounts
+            .values()
+            .find(|a| !a.signing_pubkey.is_empty() && a.signing_pubkey.eq_ignore_ascii_case(pubkey))
+            .map(|a| a.name.as_str())
+    }
+}
+
+/// Lowercase, alphanumeric + underscore, and not mistakable for a `cmps1` address
+fn valid_account_name(name: &str) -> bool {
+    name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+        && !crate::encoding::is_bech32_address(name)
 }
 
 impl Default for AccountStore {
@@ -259,6 +318,15 @@ mod tests {
         
         assert_eq!(account.name, "alice");
         assert!(account.can_sign());
+        assert_eq!(store.name_for_pubkey(&account.signing_pubkey.to_uppercase()), Some("alice"));
+        assert_eq!(store.name_for_pubkey(&"00".repeat(32)), None);
+
+        // Names that read as an address would make `cmps1...` input ambiguous
+        assert!(store.create_account(
+            "cmps1alice".to_string(),
+            "secure_password_123".to_string(),
+            AccountType::User(UserAccountData::default()),
+        ).is_err());
     }
     
     #[test]
@@ -2,6 +2,7 @@
 
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use super::auth::AuthorizationModel;
 
 /// Account identifier - human-readable name
 pub type AccountId = String;
@@ -48,6 +49,9 @@ pub enum AccountType {
     
     /// Admin account (genesis account with special privileges)
     Admin(AdminAccountData),
+
+    /// Shared account whose transfers need `threshold` of `signers` to approve
+    Multisig(MultisigAccountData),
 }
 
 /// User account data
@@ -94,6 +98,13 @@ pub struct AdminAccountData {
     pub permissions: Vec<String>,
 }
 
+/// Multisig account data
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MultisigAccountData {
+    pub threshold: u8,
+    pub signers: Vec<String>, // Ed25519 public keys (hex)
+}
+
 impl Account {
     /// Check if account has admin privileges
     pub fn is_admin(&self) -> bool {
@@ -105,6 +116,22 @@ impl Account {
         matches!(self.account_type, AccountType::Oracle(_))
     }
     
+    /// Check if account is m-of-n multisig
+    pub fn is_multisig(&self) -> bool {
+        matches!(self.account_type, AccountType::Multisig(_))
+    }
+
+    /// Keys allowed to authorize this account's transfers, if it is not single-key
+    pub fn authorization(&self) -> AuthorizationModel {
+        match &self.account_type {
+            AccountType::Multisig(data) => AuthorizationModel::MultiSig {
+                threshold: data.threshold,
+                keys: data.signers.clone(),
+            },
+            _ => AuthorizationModel::SingleKey { pubkey: self.signing_pubkey.clone() },
+        }
+    }
+
     /// Check if account can sign transactions
     pub fn can_sign(&self) -> bool {
         !self.signing_privkey_encrypted.is_empty()
@@ -0,0 +1,105 @@
+//! Constant-product pools that convert non-native fee payments into Compass.
+//!
+//! Each pool pairs one asset (e.g. "COMPUTE" or a Compass-X vault asset) with
+//! Compass. A payer who opts to pay a fee in that asset is charged the oracle
+//! equivalent of the Compass fee plus `FEE_SURCHARGE_BPS`; the asset is swapped
+//! through the pool and the Compass that comes out goes to the fee recipient.
+//! The swap must yield at least the nominal fee, so a thin or skewed pool makes
+//! the transaction fail rather than underpay.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// Premium over the oracle rate charged when a fee is paid in another asset (2%)
+pub const FEE_SURCHARGE_BPS: u64 = 200;
+/// Pool swap fee, left in the reserves (0.3%)
+pub const SWAP_FEE_BPS: u64 = 30;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LiquidityPool {
+    pub asset: String,
+    pub asset_reserve: u64,
+    pub compass_reserve: u64,
+}
+
+impl LiquidityPool {
+    pub fn new(asset: &str) -> Self {
+        Self { asset: asset.to_string(), asset_reserve: 0, compass_reserve: 0 }
+    }
+
+    /// Add reserves on both sides. Liquidity is protocol-owned, so there are no shares.
+    pub fn add_liquidity(&mut self, asset_amount: u64, compass_amount: u64) -> Result<(), String> {
+        if asset_amount == 0 || compass_amount == 0 {
+            return Err("liquidity must be added on both sides".to_string());
+        }
+        self.asset_reserve = self.asset_reserve.checked_add(asset_amount).ok_or("asset reserve overflow")?;
+        self.compass_reserve = self.compass_reserve.checked_add(compass_amount).ok_or("compass reserve overflow")?;
+        Ok(())
+    }
+
+    /// Compass received for `amount_in` of the asset, after the swap fee
+    pub fn quote_compass_out(&self, amount_in: u64) -> u64 {
+        if self.asset_reserve == 0 || self.compass_reserve == 0 {
+            return 0;
+        }
+        let in_after_fee = amount_in as u128 * (10_000 - SWAP_FEE_BPS) as u128;
+        let numerator = in_after_fee * self.compass_reserve as u128;
+        let denominator = self.asset_reserve as u128 * 10_000 + in_after_fee;
+        (numerator / denominator) as u64
+    }
+
+    /// Swap `amount_in` of the asset for at least `min_out` Compass, updating reserves
+    pub fn swap_asset_for_compass(&mut self, amount_in: u64, min_out: u64) -> Result<u64, String> {
+        let out = self.quote_compass_out(amount_in);
+        if out == 0 || out < min_out {
+            return Err(format!(
+                "{} pool too shallow: {} {} buys {} Compass, need {}",
+                self.asset, amount_in, self.asset, out, min_out
+            ));
+        }
+        self.asset_reserve = self.asset_reserve.checked_add(amount_in).ok_or("asset reserve overflow")?;
+        self.compass_reserve -= out;
+        Ok(out)
+    }
+}
+
+/// Amount of the fee asset owed for a Compass fee at `price` (Compass per unit of
+/// the asset), including the surcharge and rounded up.
+pub fn fee_in_asset(fee: u64, price: Decimal) -> Result<u64, String> {
+    if price <= Decimal::ZERO {
+        return Err("invalid conversion rate".to_string());
+    }
+    let surcharge = Decimal::from(10_000 + FEE_SURCHARGE_BPS) / Decimal::from(10_000);
+    (Decimal::from(fee) / price * surcharge)
+        .ceil()
+        .to_u64()
+        .ok_or_else(|| "fee conversion overflow".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    #[test]
+    fn test_fee_conversion_through_pool() {
+        // 1 COMPUTE = 0.5 Compass, pool priced the same way
+        let price = Decimal::from_str("0.5").unwrap();
+        let owed = fee_in_asset(100, price).unwrap();
+        assert_eq!(owed, 204); // 200 at the oracle rate + 2%
+
+        let mut pool = LiquidityPool::new("COMPUTE");
+        pool.add_liquidity(2_000_000, 1_000_000).unwrap();
+        let out = pool.swap_asset_for_compass(owed, 100).unwrap();
+        assert!(out >= 100);
+        assert_eq!(pool.asset_reserve, 2_000_204);
+        assert_eq!(pool.compass_reserve, 1_000_000 - out);
+
+        // A shallow pool cannot cover the fee, and is left untouched
+        let mut shallow = LiquidityPool::new("COMPUTE");
+        shallow.add_liquidity(1_000, 100).unwrap();
+        assert!(shallow.swap_asset_for_compass(owed, 100).is_err());
+        assert_eq!(shallow.compass_reserve, 100);
+    }
+}
@@ -0,0 +1,138 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+/// Privileged actions recorded in the audit log
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum AuditAction {
+    VaultRegistration,
+    RecurringJobCreated,
+    OracleSignature,
+    EscrowRelease,
+    Slash,
+    FreezeAuthoritySet,
+    FeePoolSeeded,
+    OracleCircuitBreaker,
+    RedemptionPayout,
+    L2Rollback,
+}
+
+impl AuditAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AuditAction::VaultRegistration => "VaultRegistration",
+            AuditAction::RecurringJobCreated => "RecurringJobCreated",
+            AuditAction::OracleSignature => "OracleSignature",
+            AuditAction::EscrowRelease => "EscrowRelease",
+            AuditAction::Slash => "Slash",
+            AuditAction::FreezeAuthoritySet => "FreezeAuthoritySet",
+            AuditAction::FeePoolSeeded => "FeePoolSeeded",
+            AuditAction::OracleCircuitBreaker => "OracleCircuitBreaker",
+            AuditAction::RedemptionPayout => "RedemptionPayout",
+            AuditAction::L2Rollback => "L2Rollback",
+        }
+    }
+}
+
+/// One entry of the append-only audit log.
+/// Each entry commits to its predecessor through `prev_hash`, so rewriting
+/// any historical entry breaks every hash after it.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct AuditEntry {
+    pub seq: u64,
+    pub timestamp: u64,
+    pub actor: String,
+    pub action: AuditAction,
+    pub details: String,
+    pub prev_hash: String,
+    pub hash: String,
+}
+
+/// prev_hash of the first entry
+pub const AUDIT_GENESIS_HASH: &str =
+    "0000000000000000000000000000000000000000000000000000000000000000";
+
+impl AuditEntry {
+    pub fn new(
+        seq: u64,
+        prev_hash: String,
+        actor: &str,
+        action: AuditAction,
+        details: String,
+    ) -> Self {
+        let mut entry = Self {
+            seq,
+            timestamp: chrono::Utc::now().timestamp() as u64,
+            actor: actor.to_string(),
+            action,
+            details,
+            prev_hash,
+            hash: String::new(),
+        };
+        entry.hash = entry.calculate_hash();
+        entry
+    }
+
+    pub fn calculate_hash(&self) -> String {
+        let mut hasher = Sha256::new();
+        hasher.update(self.seq.to_be_bytes());
+        hasher.update(self.timestamp.to_be_bytes());
+        hasher.update(self.actor.as_bytes());
+        hasher.update([0u8]);
+        hasher.update(self.action.as_str().as_bytes());
+        hasher.update([0u8]);
+        hasher.update(self.details.as_bytes());
+        hasher.update(self.prev_hash.as_bytes());
+        hex::encode(hasher.finalize())
+    }
+}
+
+/// Check that a contiguous run of entries is internally consistent.
+/// Returns the sequence number of the first broken entry, if any.
+pub fn verify_chain(entries: &[AuditEntry]) -> Result<(), u64> {
+    let mut prev: Option<&AuditEntry> = None;
+    for entry in entries {
+        if entry.hash != entry.calculate_hash() {
+            return Err(entry.seq);
+        }
+        match prev {
+            Some(p) if p.hash != entry.prev_hash || p.seq + 1 != entry.seq => return Err(entry.seq),
+            None if entry.seq == 0 && entry.prev_hash != AUDIT_GENESIS_HASH => return Err(entry.seq),
+            _ => {}
+        }
+        prev = Some(entry);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn build_chain(n: u64) -> Vec<AuditEntry> {
+        let mut entries: Vec<AuditEntry> = Vec::new();
+        for seq in 0..n {
+            let prev = entries
+                .last()
+                .map(|e| e.hash.clone())
+                .unwrap_or_else(|| AUDIT_GENESIS_HASH.to_string());
+            entries.push(AuditEntry::new(seq, prev, "admin", AuditAction::Slash, format!("entry {}", seq)));
+        }
+        entries
+    }
+
+    #[test]
+    fn test_valid_chain_verifies() {
+        assert_eq!(verify_chain(&build_chain(5)), Ok(()));
+    }
+
+    #[test]
+    fn test_tampered_entry_detected() {
+        let mut entries = build_chain(5);
+        entries[2].details = "rewritten".to_string();
+        assert_eq!(verify_chain(&entries), Err(2));
+
+        // Re-hashing the tampered entry still breaks the link to its successor
+        entries[2].hash = entries[2].calculate_hash();
+        assert_eq!(verify_chain(&entries), Err(3));
+    }
+}
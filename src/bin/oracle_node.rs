@@ -22,9 +22,9 @@ struct Args {
     #[clap(long)]
     account: String,
     
-    /// Oracle account password
+    /// Secret key (hex) of the oracle's registered key; submissions are signed with it
     #[clap(long)]
-    password: String,
+    key: String,
     
     /// Price update interval in seconds
     #[clap(long, default_value = "30")]
@@ -43,6 +43,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .init();
     
     let args = Args::parse();
+    let key = rust_compass::crypto::KeyPair::from_secret_hex(&args.key)?;
     
     info!("🔮 Compass Oracle Node v2.0 Starting...");
     info!("Account: {}", args.account);
@@ -74,7 +75,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     if let Err(e) = submit_price_feed(
                         &args.rpc_url,
                         &args.account,
-                        &args.password,
+                        &key,
                         ticker,
                         price,
                     ).await {
@@ -112,11 +113,13 @@ async fn fetch_price(ticker: &str) -> Result<f64, Box<dyn std::error::Error>> {
 async fn submit_price_feed(
     rpc_url: &str,
     account: &str,
-    password: &str,
+    key: &rust_compass::crypto::KeyPair,
     ticker: &str,
     price: f64,
 ) -> Result<(), Box<dyn std::error::Error>> {
     let client = reqwest::Client::new();
+    let timestamp = current_timestamp_ms();
+    let message = rust_compass::oracle::aggregation::price_message(ticker, price, timestamp);
     
     // Prepare RPC request
     let req_body = serde_json::json!({
@@ -124,11 +127,11 @@ async fn submit_price_feed(
         "id": 1,
         "method": "submitOraclePrice",
         "params": {
-            "account": account,
-            "password": password,
+            "oracle_account": account,
             "ticker": ticker,
             "price": price,
-            "timestamp": current_timestamp_ms(),
+            "timestamp": timestamp,
+            "signature": key.sign_hex(message.as_bytes()),
         }
     });
     
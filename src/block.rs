@@ -1,7 +1,7 @@
 use crate::crypto::KeyPair;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use crate::encoding::CanonicalSerialize;
+use crate::encoding::{self, CanonicalSerialize, EncodingError};
 use std::io::Write;
 use tracing::{debug, error};
 
@@ -74,6 +74,279 @@ pub enum BlockType {
         stake_amount: u64,
         signature: String,
     },
+    /// Transfer whose fee is paid by a third party (see `sponsor_message`)
+    SponsoredTransfer {
+        from: String,
+        to: String,
+        asset: String,
+        amount: u64,
+        nonce: u64,
+        fee: u64,
+        fee_payer: String,
+        fee_payer_signature: String,
+    },
+    /// Issuer freeze/thaw of one account's holdings of a vault asset
+    AssetFreeze {
+        compass_asset: String,
+        account: String,
+        frozen: bool,
+        authority: String,
+        reason: String,
+        seq: u64,
+        authority_signature: String,
+    },
+    /// Transfer whose Compass fee is paid in `fee_asset`, converted through its
+    /// fee pool (see `amm`). `max_fee_in` caps what the sender agreed to spend.
+    AssetFeeTransfer {
+        from: String,
+        to: String,
+        asset: String,
+        amount: u64,
+        nonce: u64,
+        fee: u64,
+        fee_asset: String,
+        max_fee_in: u64,
+    },
+    /// Transfer out of a multisig account, authorized by signer approvals of
+    /// `account::multisig::approval_message` rather than a header signature
+    MultisigTransfer {
+        from: String,
+        to: String,
+        asset: String,
+        amount: u64,
+        nonce: u64,
+        approvals: Vec<(String, String)>, // (signer pubkey, signature)
+    },
+    /// Totals for a finished epoch; `report_hash` commits to the full
+    /// `epoch_report::EpochReport` every node tallies for it
+    EpochReport {
+        epoch: u64,
+        fees_collected: u64,
+        compute_emitted: u64,
+        report_hash: String,
+    },
+    /// Validator vote for finality on the canonical block `target_hash`. Unlike the
+    /// governance `Vote`, the authorization is `signature` over
+    /// `finality::vote_message` by the validator's registered key.
+    FinalityVote {
+        voter: String,
+        target_hash: String,
+        target_height: u64,
+        signature: String,
+    },
+    /// Worker moving `amount` of its accrued earnings into its COMPUTE balance,
+    /// authorized by `signature` over `layer3::earnings::claim_message`
+    EarningsClaim {
+        worker: String,
+        amount: u64,
+        nonce: u64,
+        signature: String,
+    },
+    /// Leader releasing `amount` of `account`'s vested genesis allocation, which
+    /// must not exceed what `vesting::VestingSchedule` has vested by the block's
+    /// timestamp
+    Vesting {
+        account: String,
+        amount: u64,
+    },
+    /// Holder handing its governance voting power to `representative` (empty to
+    /// take it back), authorized by `signature` over `governance::delegate_message`
+    Delegate {
+        delegator: String,
+        representative: String,
+        nonce: u64,
+        signature: String,
+    },
+    /// Governance proposal to pay `amount` from the treasury to `recipient`,
+    /// voted on like any proposal until `deadline`
+    TreasuryProposal {
+        id: u64,
+        proposer: String,
+        recipient: String,
+        amount: u64,
+        deadline: u64,
+        text: String,
+    },
+    /// Leader carrying out the passed treasury proposal `proposal_id`
+    TreasurySpend {
+        proposal_id: u64,
+        recipient: String,
+        amount: u64,
+    },
+    /// Leader settling one order-book fill: the taker's `taker_side` order took
+    /// `amount` base units from the maker's resting order at the maker's `price`
+    /// (quote units per base unit). Moves base to the buyer and quote to the seller;
+    /// each side's fee, in quote units, goes to the treasury and the proposer.
+    Trade {
+        trade_id: String,
+        maker: String,
+        taker: String,
+        taker_side: crate::market::OrderSide,
+        base: String,
+        quote: String,
+        amount: u64,
+        price: u64,
+        maker_fee: u64,
+        taker_fee: u64,
+    },
+    /// Leader releasing the vault side of burn `intent_id` once its payout to the
+    /// redeemer, `external_tx`, has confirmed on the collateral's chain
+    RedemptionRelease {
+        intent_id: String,
+        redeemer: String,
+        external_tx: String,
+    },
+    /// Leader committing a hash of Layer 2 state (`layer2::checkpoint`); `state_hash`
+    /// combines the three component hashes
+    L2Checkpoint {
+        sequence: u64,
+        state_hash: String,
+        economics_hash: String,
+        assets_hash: String,
+        collateral_hash: String,
+    },
+    /// `account` parking a transaction until `execute_at_slot` for `fee`. `envelope` is
+    /// the account's signed `Scheduled` transaction, so every node can check it.
+    Schedule {
+        account: String,
+        execute_at_slot: u64,
+        fee: u64,
+        envelope: Vec<u8>,
+    },
+    /// Governance proposal to set the stability fee on vault debt to
+    /// `annual_rate_bps`, voted on like any proposal until `deadline`
+    StabilityFeeProposal {
+        id: u64,
+        proposer: String,
+        annual_rate_bps: u64,
+        deadline: u64,
+        text: String,
+    },
+    /// Leader enacting the passed stability fee proposal `proposal_id`
+    StabilityFeeChange {
+        proposal_id: u64,
+        annual_rate_bps: u64,
+    },
+    /// Entity withdrawing `amount` of collateral that finished unbonding by the
+    /// block's slot into its balance, authorized by `signature` over
+    /// `layer2::collateral::withdraw_message`
+    UnbondedWithdrawal {
+        entity: String,
+        amount: u64,
+        nonce: u64,
+        signature: String,
+    },
+    /// `deployer` publishing a WASM contract at `vm::contract_address(deployer,
+    /// nonce)`, authorized by `signature` over `vm::deploy_message`. It pays
+    /// `gas_price` Compass per unit of gas its `init` uses.
+    ContractDeploy {
+        deployer: String,
+        code: Vec<u8>,
+        gas_limit: u64,
+        gas_price: u64,
+        nonce: u64,
+        signature: String,
+    },
+    /// `caller` running `method` of `contract`, authorized by `signature` over
+    /// `vm::call_message`, at `gas_price` Compass per unit of gas
+    ContractCall {
+        caller: String,
+        contract: String,
+        method: String,
+        input: Vec<u8>,
+        gas_limit: u64,
+        gas_price: u64,
+        nonce: u64,
+        signature: String,
+    },
+    /// Settlement of `caller`'s paid run of model `token_id`, authorized by
+    /// `signature` over `layer3::model_nft::invoke_message`. The fee is metered from
+    /// `inputs`, and each signed request settles once.
+    ModelInvocation {
+        token_id: String,
+        caller: String,
+        inputs: Vec<u8>,
+        timestamp: u64,
+        signature: String,
+    },
+    /// Balance changes (and model rentals) an RPC call settled on the node that
+    /// signed this block: rentals, subscriptions, pool deposits, vault mints, ...
+    /// Everyone else replays them as-is. `memo` says what was paid for.
+    Settlement {
+        legs: Vec<SettlementLeg>,
+        memo: String,
+    },
+    /// Node operator assigning `authority` (issuer public key hex) as the key
+    /// that signs `AssetFreeze` blocks for `compass_asset`
+    FreezeAuthorityChange {
+        compass_asset: String,
+        authority: String,
+    },
+}
+
+/// One balance change in a `Settlement` block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum SettlementLeg {
+    Transfer { from: String, to: String, asset: String, amount: u64 },
+    /// Leaves circulation, e.g. locked as collateral or paid as a burned fee
+    Debit { account: String, asset: String, amount: u64 },
+    /// Enters circulation, e.g. minted against locked collateral
+    Credit { account: String, asset: String, amount: u64 },
+    /// `renter` holds model `token_id` until `expires_at` (unix seconds), which
+    /// entitles it to free invocations
+    Rental { token_id: String, renter: String, expires_at: u64, rate_per_hour: u64 },
+}
+
+impl SettlementLeg {
+    fn accounts(&self) -> Vec<&str> {
+        match self {
+            SettlementLeg::Transfer { from, to, .. } => vec![from.as_str(), to.as_str()],
+            SettlementLeg::Debit { account, .. } | SettlementLeg::Credit { account, .. } => vec![account.as_str()],
+            SettlementLeg::Rental { renter, .. } => vec![renter.as_str()],
+        }
+    }
+}
+
+impl CanonicalSerialize for SettlementLeg {
+    fn canonical_serialize<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        match self {
+            SettlementLeg::Transfer { from, to, asset, amount } => {
+                0u8.canonical_serialize(writer)?;
+                from.canonical_serialize(writer)?;
+                to.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)
+            }
+            SettlementLeg::Debit { account, asset, amount } => {
+                1u8.canonical_serialize(writer)?;
+                account.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)
+            }
+            SettlementLeg::Credit { account, asset, amount } => {
+                2u8.canonical_serialize(writer)?;
+                account.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)
+            }
+            SettlementLeg::Rental { token_id, renter, expires_at, rate_per_hour } => {
+                3u8.canonical_serialize(writer)?;
+                token_id.canonical_serialize(writer)?;
+                renter.canonical_serialize(writer)?;
+                expires_at.canonical_serialize(writer)?;
+                rate_per_hour.canonical_serialize(writer)
+            }
+        }
+    }
+}
+
+/// Message the fee payer signs to sponsor a transfer. It binds every field of the
+/// transfer, so the sponsorship cannot be replayed onto a different payment.
+pub fn sponsor_message(from: &str, to: &str, asset: &str, amount: u64, nonce: u64, fee: u64, fee_payer: &str) -> String {
+    format!(
+        "COMPASS_SPONSOR:{}:{}:{}:{}:{}:{}:{}",
+        from, to, asset, amount, nonce, fee, fee_payer
+    )
 }
 
 impl CanonicalSerialize for BlockType {
@@ -146,6 +419,191 @@ impl CanonicalSerialize for BlockType {
                 stake_amount.canonical_serialize(writer)?;
                 signature.canonical_serialize(writer)?;
             }
+            BlockType::SponsoredTransfer { from, to, asset, amount, nonce, fee, fee_payer, fee_payer_signature } => {
+                11u8.canonical_serialize(writer)?;
+                from.canonical_serialize(writer)?;
+                to.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                fee.canonical_serialize(writer)?;
+                fee_payer.canonical_serialize(writer)?;
+                fee_payer_signature.canonical_serialize(writer)?;
+            }
+            BlockType::AssetFreeze { compass_asset, account, frozen, authority, reason, seq, authority_signature } => {
+                12u8.canonical_serialize(writer)?;
+                compass_asset.canonical_serialize(writer)?;
+                account.canonical_serialize(writer)?;
+                frozen.canonical_serialize(writer)?;
+                authority.canonical_serialize(writer)?;
+                reason.canonical_serialize(writer)?;
+                seq.canonical_serialize(writer)?;
+                authority_signature.canonical_serialize(writer)?;
+            }
+            BlockType::AssetFeeTransfer { from, to, asset, amount, nonce, fee, fee_asset, max_fee_in } => {
+                13u8.canonical_serialize(writer)?;
+                from.canonical_serialize(writer)?;
+                to.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                fee.canonical_serialize(writer)?;
+                fee_asset.canonical_serialize(writer)?;
+                max_fee_in.canonical_serialize(writer)?;
+            }
+            BlockType::MultisigTransfer { from, to, asset, amount, nonce, approvals } => {
+                14u8.canonical_serialize(writer)?;
+                from.canonical_serialize(writer)?;
+                to.canonical_serialize(writer)?;
+                asset.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                (approvals.len() as u64).canonical_serialize(writer)?;
+                for (pubkey, signature) in approvals {
+                    pubkey.canonical_serialize(writer)?;
+                    signature.canonical_serialize(writer)?;
+                }
+            }
+            BlockType::EpochReport { epoch, fees_collected, compute_emitted, report_hash } => {
+                15u8.canonical_serialize(writer)?;
+                epoch.canonical_serialize(writer)?;
+                fees_collected.canonical_serialize(writer)?;
+                compute_emitted.canonical_serialize(writer)?;
+                report_hash.canonical_serialize(writer)?;
+            }
+            BlockType::FinalityVote { voter, target_hash, target_height, signature } => {
+                16u8.canonical_serialize(writer)?;
+                voter.canonical_serialize(writer)?;
+                target_hash.canonical_serialize(writer)?;
+                target_height.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::EarningsClaim { worker, amount, nonce, signature } => {
+                17u8.canonical_serialize(writer)?;
+                worker.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::Vesting { account, amount } => {
+                18u8.canonical_serialize(writer)?;
+                account.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+            }
+            BlockType::Delegate { delegator, representative, nonce, signature } => {
+                19u8.canonical_serialize(writer)?;
+                delegator.canonical_serialize(writer)?;
+                representative.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::TreasuryProposal { id, proposer, recipient, amount, deadline, text } => {
+                20u8.canonical_serialize(writer)?;
+                id.canonical_serialize(writer)?;
+                proposer.canonical_serialize(writer)?;
+                recipient.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                deadline.canonical_serialize(writer)?;
+                text.canonical_serialize(writer)?;
+            }
+            BlockType::TreasurySpend { proposal_id, recipient, amount } => {
+                21u8.canonical_serialize(writer)?;
+                proposal_id.canonical_serialize(writer)?;
+                recipient.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+            }
+            BlockType::Trade { trade_id, maker, taker, taker_side, base, quote, amount, price, maker_fee, taker_fee } => {
+                22u8.canonical_serialize(writer)?;
+                trade_id.canonical_serialize(writer)?;
+                maker.canonical_serialize(writer)?;
+                taker.canonical_serialize(writer)?;
+                (*taker_side == crate::market::OrderSide::Sell).canonical_serialize(writer)?;
+                base.canonical_serialize(writer)?;
+                quote.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                price.canonical_serialize(writer)?;
+                maker_fee.canonical_serialize(writer)?;
+                taker_fee.canonical_serialize(writer)?;
+            }
+            BlockType::RedemptionRelease { intent_id, redeemer, external_tx } => {
+                23u8.canonical_serialize(writer)?;
+                intent_id.canonical_serialize(writer)?;
+                redeemer.canonical_serialize(writer)?;
+                external_tx.canonical_serialize(writer)?;
+            }
+            BlockType::L2Checkpoint { sequence, state_hash, economics_hash, assets_hash, collateral_hash } => {
+                24u8.canonical_serialize(writer)?;
+                sequence.canonical_serialize(writer)?;
+                state_hash.canonical_serialize(writer)?;
+                economics_hash.canonical_serialize(writer)?;
+                assets_hash.canonical_serialize(writer)?;
+                collateral_hash.canonical_serialize(writer)?;
+            }
+            BlockType::Schedule { account, execute_at_slot, fee, envelope } => {
+                25u8.canonical_serialize(writer)?;
+                account.canonical_serialize(writer)?;
+                execute_at_slot.canonical_serialize(writer)?;
+                fee.canonical_serialize(writer)?;
+                envelope.canonical_serialize(writer)?;
+            }
+            BlockType::StabilityFeeProposal { id, proposer, annual_rate_bps, deadline, text } => {
+                26u8.canonical_serialize(writer)?;
+                id.canonical_serialize(writer)?;
+                proposer.canonical_serialize(writer)?;
+                annual_rate_bps.canonical_serialize(writer)?;
+                deadline.canonical_serialize(writer)?;
+                text.canonical_serialize(writer)?;
+            }
+            BlockType::StabilityFeeChange { proposal_id, annual_rate_bps } => {
+                27u8.canonical_serialize(writer)?;
+                proposal_id.canonical_serialize(writer)?;
+                annual_rate_bps.canonical_serialize(writer)?;
+            }
+            BlockType::UnbondedWithdrawal { entity, amount, nonce, signature } => {
+                28u8.canonical_serialize(writer)?;
+                entity.canonical_serialize(writer)?;
+                amount.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::ContractDeploy { deployer, code, gas_limit, gas_price, nonce, signature } => {
+                29u8.canonical_serialize(writer)?;
+                deployer.canonical_serialize(writer)?;
+                code.canonical_serialize(writer)?;
+                gas_limit.canonical_serialize(writer)?;
+                gas_price.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::ContractCall { caller, contract, method, input, gas_limit, gas_price, nonce, signature } => {
+                30u8.canonical_serialize(writer)?;
+                caller.canonical_serialize(writer)?;
+                contract.canonical_serialize(writer)?;
+                method.canonical_serialize(writer)?;
+                input.canonical_serialize(writer)?;
+                gas_limit.canonical_serialize(writer)?;
+                gas_price.canonical_serialize(writer)?;
+                nonce.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::ModelInvocation { token_id, caller, inputs, timestamp, signature } => {
+                31u8.canonical_serialize(writer)?;
+                token_id.canonical_serialize(writer)?;
+                caller.canonical_serialize(writer)?;
+                inputs.canonical_serialize(writer)?;
+                timestamp.canonical_serialize(writer)?;
+                signature.canonical_serialize(writer)?;
+            }
+            BlockType::Settlement { legs, memo } => {
+                32u8.canonical_serialize(writer)?;
+                legs.canonical_serialize(writer)?;
+                memo.canonical_serialize(writer)?;
+            }
+            BlockType::FreezeAuthorityChange { compass_asset, authority } => {
+                33u8.canonical_serialize(writer)?;
+                compass_asset.canonical_serialize(writer)?;
+                authority.canonical_serialize(writer)?;
+            }
         }
         Ok(())
     }
@@ -164,6 +622,296 @@ impl BlockType {
             BlockType::Mint { .. } => 8,
             BlockType::Burn { .. } => 9,
             BlockType::ValidatorRegistration { .. } => 10,
+            BlockType::SponsoredTransfer { .. } => 11,
+            BlockType::AssetFreeze { .. } => 12,
+            BlockType::AssetFeeTransfer { .. } => 13,
+            BlockType::MultisigTransfer { .. } => 14,
+            BlockType::EpochReport { .. } => 15,
+            BlockType::FinalityVote { .. } => 16,
+            BlockType::EarningsClaim { .. } => 17,
+            BlockType::Vesting { .. } => 18,
+            BlockType::Delegate { .. } => 19,
+            BlockType::TreasuryProposal { .. } => 20,
+            BlockType::TreasurySpend { .. } => 21,
+            BlockType::Trade { .. } => 22,
+            BlockType::RedemptionRelease { .. } => 23,
+            BlockType::L2Checkpoint { .. } => 24,
+            BlockType::Schedule { .. } => 25,
+            BlockType::StabilityFeeProposal { .. } => 26,
+            BlockType::StabilityFeeChange { .. } => 27,
+            BlockType::UnbondedWithdrawal { .. } => 28,
+            BlockType::ContractDeploy { .. } => 29,
+            BlockType::ContractCall { .. } => 30,
+            BlockType::ModelInvocation { .. } => 31,
+            BlockType::Settlement { .. } => 32,
+            BlockType::FreezeAuthorityChange { .. } => 33,
+        }
+    }
+
+    /// Compass fee the block charges, in base units
+    pub fn fee(&self) -> u64 {
+        match self {
+            BlockType::Transfer { fee, .. }
+            | BlockType::SponsoredTransfer { fee, .. }
+            | BlockType::AssetFeeTransfer { fee, .. }
+            | BlockType::Mint { fee, .. }
+            | BlockType::Burn { fee, .. }
+            | BlockType::Schedule { fee, .. } => *fee,
+            _ => 0,
+        }
+    }
+
+    /// Variant name, as used by the block type index
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BlockType::PoH { .. } => "PoH",
+            BlockType::Genesis => "Genesis",
+            BlockType::Work => "Work",
+            BlockType::Proposal { .. } => "Proposal",
+            BlockType::Reward { .. } => "Reward",
+            BlockType::Vote { .. } => "Vote",
+            BlockType::Transfer { .. } => "Transfer",
+            BlockType::Mint { .. } => "Mint",
+            BlockType::Burn { .. } => "Burn",
+            BlockType::ValidatorRegistration { .. } => "ValidatorRegistration",
+            BlockType::SponsoredTransfer { .. } => "SponsoredTransfer",
+            BlockType::AssetFreeze { .. } => "AssetFreeze",
+            BlockType::AssetFeeTransfer { .. } => "AssetFeeTransfer",
+            BlockType::MultisigTransfer { .. } => "MultisigTransfer",
+            BlockType::EpochReport { .. } => "EpochReport",
+            BlockType::FinalityVote { .. } => "FinalityVote",
+            BlockType::EarningsClaim { .. } => "EarningsClaim",
+            BlockType::Vesting { .. } => "Vesting",
+            BlockType::Delegate { .. } => "Delegate",
+            BlockType::TreasuryProposal { .. } => "TreasuryProposal",
+            BlockType::TreasurySpend { .. } => "TreasurySpend",
+            BlockType::Trade { .. } => "Trade",
+            BlockType::RedemptionRelease { .. } => "RedemptionRelease",
+            BlockType::L2Checkpoint { .. } => "L2Checkpoint",
+            BlockType::Schedule { .. } => "Schedule",
+            BlockType::StabilityFeeProposal { .. } => "StabilityFeeProposal",
+            BlockType::StabilityFeeChange { .. } => "StabilityFeeChange",
+            BlockType::UnbondedWithdrawal { .. } => "UnbondedWithdrawal",
+            BlockType::ContractDeploy { .. } => "ContractDeploy",
+            BlockType::ContractCall { .. } => "ContractCall",
+            BlockType::ModelInvocation { .. } => "ModelInvocation",
+            BlockType::Settlement { .. } => "Settlement",
+            BlockType::FreezeAuthorityChange { .. } => "FreezeAuthorityChange",
+        }
+    }
+
+    /// Accounts this block moves funds for or acts on, for the account history index
+    pub fn accounts(&self) -> Vec<&str> {
+        let mut accounts = match self {
+            BlockType::PoH { .. }
+            | BlockType::Genesis
+            | BlockType::Work
+            | BlockType::EpochReport { .. }
+            | BlockType::L2Checkpoint { .. }
+            | BlockType::StabilityFeeChange { .. } => vec![],
+            BlockType::Proposal { proposer, .. } => vec![proposer.as_str()],
+            BlockType::Reward { recipient, .. } => vec![recipient.as_str()],
+            BlockType::Vote { voter, .. } => vec![voter.as_str()],
+            BlockType::Transfer { from, to, .. } => vec![from.as_str(), to.as_str()],
+            BlockType::Mint { owner, .. } => vec![owner.as_str()],
+            BlockType::Burn { redeemer, .. } => vec![redeemer.as_str()],
+            BlockType::ValidatorRegistration { validator_id, .. } => vec![validator_id.as_str()],
+            BlockType::SponsoredTransfer { from, to, fee_payer, .. } => vec![from.as_str(), to.as_str(), fee_payer.as_str()],
+            BlockType::AssetFreeze { account, authority, .. } => vec![account.as_str(), authority.as_str()],
+            BlockType::AssetFeeTransfer { from, to, .. } => vec![from.as_str(), to.as_str()],
+            BlockType::MultisigTransfer { from, to, .. } => vec![from.as_str(), to.as_str()],
+            BlockType::FinalityVote { voter, .. } => vec![voter.as_str()],
+            BlockType::EarningsClaim { worker, .. } => vec![worker.as_str()],
+            BlockType::Vesting { account, .. } => vec![account.as_str()],
+            BlockType::Delegate { delegator, representative, .. } => {
+                vec![delegator.as_str(), representative.as_str()].into_iter().filter(|a| !a.is_empty()).collect()
+            }
+            BlockType::TreasuryProposal { proposer, recipient, .. } => vec![proposer.as_str(), recipient.as_str()],
+            BlockType::TreasurySpend { recipient, .. } => vec![crate::treasury::TREASURY_ACCOUNT, recipient.as_str()],
+            BlockType::Trade { maker, taker, .. } => vec![maker.as_str(), taker.as_str()],
+            BlockType::RedemptionRelease { redeemer, .. } => vec![redeemer.as_str()],
+            BlockType::Schedule { account, .. } => vec![account.as_str()],
+            BlockType::StabilityFeeProposal { proposer, .. } => vec![proposer.as_str()],
+            BlockType::UnbondedWithdrawal { entity, .. } => vec![entity.as_str()],
+            BlockType::ContractDeploy { deployer, .. } => vec![deployer.as_str()],
+            BlockType::ContractCall { caller, .. } => vec![caller.as_str()],
+            BlockType::ModelInvocation { caller, .. } => vec![caller.as_str()],
+            BlockType::Settlement { legs, .. } => legs.iter().flat_map(SettlementLeg::accounts).collect(),
+            BlockType::FreezeAuthorityChange { authority, .. } => vec![authority.as_str()],
+        };
+        accounts.sort_unstable();
+        accounts.dedup();
+        accounts
+    }
+}
+
+/// Shorten long keys/addresses for one-line summaries
+fn short_id(id: &str) -> String {
+    if id.chars().count() > 16 {
+        format!("{}...", id.chars().take(8).collect::<String>())
+    } else {
+        id.to_string()
+    }
+}
+
+impl std::fmt::Display for BlockType {
+    /// One-line human-readable summary, e.g. "Transfer 10 Compass from alice to bob, fee 1"
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BlockType::PoH { tick, iterations, .. } => {
+                write!(f, "PoH tick {} ({} iterations)", tick, iterations)
+            }
+            BlockType::Genesis => write!(f, "Genesis"),
+            BlockType::Work => write!(f, "Work"),
+            BlockType::Proposal { id, proposer, text, .. } => {
+                write!(f, "Proposal #{} by {}: \"{}\"", id, short_id(proposer), text)
+            }
+            BlockType::Reward { recipient, amount, asset, reason } => {
+                write!(f, "Reward {} {} to {} ({})", amount, asset, short_id(recipient), reason)
+            }
+            BlockType::Vote { proposal_id, voter, choice } => write!(
+                f,
+                "Vote {} on proposal #{} by {}",
+                if *choice { "YES" } else { "NO" },
+                proposal_id,
+                short_id(voter)
+            ),
+            BlockType::Transfer { from, to, asset, amount, fee, .. } => write!(
+                f,
+                "Transfer {} {} from {} to {}, fee {}",
+                amount,
+                asset,
+                short_id(from),
+                short_id(to),
+                fee
+            ),
+            BlockType::Mint { collateral_asset, collateral_amount, compass_asset, mint_amount, owner, fee, .. } => write!(
+                f,
+                "Mint {} {} to {} against {} {}, fee {}",
+                mint_amount,
+                compass_asset,
+                short_id(owner),
+                collateral_amount,
+                collateral_asset,
+                fee
+            ),
+            BlockType::Burn { collateral_asset, compass_asset, burn_amount, redeemer, destination_address, fee, .. } => write!(
+                f,
+                "Burn {} {} by {}, release {} to {}, fee {}",
+                burn_amount,
+                compass_asset,
+                short_id(redeemer),
+                collateral_asset,
+                short_id(destination_address),
+                fee
+            ),
+            BlockType::ValidatorRegistration { validator_id, stake_amount, .. } => write!(
+                f,
+                "Register validator {} with stake {}",
+                short_id(validator_id),
+                stake_amount
+            ),
+            BlockType::SponsoredTransfer { from, to, asset, amount, fee, fee_payer, .. } => write!(
+                f,
+                "Transfer {} {} from {} to {}, fee {} paid by {}",
+                amount,
+                asset,
+                short_id(from),
+                short_id(to),
+                fee,
+                short_id(fee_payer)
+            ),
+            BlockType::AssetFreeze { compass_asset, account, frozen, reason, .. } => write!(
+                f,
+                "{} {} for {} ({})",
+                if *frozen { "Freeze" } else { "Thaw" },
+                compass_asset,
+                short_id(account),
+                reason
+            ),
+            BlockType::AssetFeeTransfer { from, to, asset, amount, fee, fee_asset, .. } => write!(
+                f,
+                "Transfer {} {} from {} to {}, fee {} paid in {}",
+                amount,
+                asset,
+                short_id(from),
+                short_id(to),
+                fee,
+                fee_asset
+            ),
+            BlockType::MultisigTransfer { from, to, asset, amount, approvals, .. } => write!(
+                f,
+                "Multisig transfer {} {} from {} to {} ({} approvals)",
+                amount,
+                asset,
+                short_id(from),
+                short_id(to),
+                approvals.len()
+            ),
+            BlockType::EpochReport { epoch, fees_collected, compute_emitted, .. } => write!(
+                f,
+                "Epoch {} report: fees {}, COMPUTE emitted {}",
+                epoch, fees_collected, compute_emitted
+            ),
+            BlockType::FinalityVote { voter, target_height, .. } => {
+                write!(f, "Finality vote by {} for height {}", short_id(voter), target_height)
+            }
+            BlockType::EarningsClaim { worker, amount, .. } => {
+                write!(f, "Earnings claim of {} COMPUTE by {}", amount, short_id(worker))
+            }
+            BlockType::Vesting { account, amount } => {
+                write!(f, "Vesting release of {} Compass to {}", amount, short_id(account))
+            }
+            BlockType::Delegate { delegator, representative, .. } if representative.is_empty() => {
+                write!(f, "Delegation revoked by {}", short_id(delegator))
+            }
+            BlockType::Delegate { delegator, representative, .. } => {
+                write!(f, "Delegation from {} to {}", short_id(delegator), short_id(representative))
+            }
+            BlockType::TreasuryProposal { id, recipient, amount, .. } => {
+                write!(f, "Treasury proposal #{}: {} Compass to {}", id, amount, short_id(recipient))
+            }
+            BlockType::TreasurySpend { proposal_id, recipient, amount } => {
+                write!(f, "Treasury spend of {} Compass to {} (proposal #{})", amount, short_id(recipient), proposal_id)
+            }
+            BlockType::Trade { trade_id, base, quote, amount, price, .. } => {
+                write!(f, "Trade {}: {} {} @ {} {}", trade_id, amount, base, price, quote)
+            }
+            BlockType::RedemptionRelease { intent_id, redeemer, external_tx } => {
+                write!(f, "Redemption {} to {} paid by {}", short_id(intent_id), short_id(redeemer), external_tx)
+            }
+            BlockType::L2Checkpoint { sequence, state_hash, .. } => {
+                write!(f, "Layer 2 checkpoint #{} ({})", sequence, short_id(state_hash))
+            }
+            BlockType::Schedule { account, execute_at_slot, fee, .. } => {
+                write!(f, "Schedule transaction by {} for slot {}, fee {}", short_id(account), execute_at_slot, fee)
+            }
+            BlockType::StabilityFeeProposal { id, annual_rate_bps, .. } => {
+                write!(f, "Stability fee proposal #{}: {} bps a year", id, annual_rate_bps)
+            }
+            BlockType::StabilityFeeChange { proposal_id, annual_rate_bps } => {
+                write!(f, "Stability fee set to {} bps a year (proposal #{})", annual_rate_bps, proposal_id)
+            }
+            BlockType::UnbondedWithdrawal { entity, amount, .. } => {
+                write!(f, "Unbonded withdrawal of {} Compass by {}", amount, short_id(entity))
+            }
+            BlockType::ContractDeploy { deployer, code, nonce, .. } => write!(
+                f,
+                "Contract {} deployed by {} ({} bytes)",
+                short_id(&crate::vm::contract_address(deployer, *nonce)),
+                short_id(deployer),
+                code.len()
+            ),
+            BlockType::ContractCall { caller, contract, method, .. } => {
+                write!(f, "Contract call {}::{} by {}", short_id(contract), method, short_id(caller))
+            }
+            BlockType::ModelInvocation { token_id, caller, .. } => {
+                write!(f, "Model {} invoked by {}", token_id, short_id(caller))
+            }
+            BlockType::Settlement { legs, memo } => write!(f, "Settlement of {} ({} balance changes)", memo, legs.len()),
+            BlockType::FreezeAuthorityChange { compass_asset, authority } => {
+                write!(f, "Freeze authority for {} set to {}", compass_asset, short_id(authority))
+            }
         }
     }
 }
@@ -177,6 +925,30 @@ pub struct BlockHeader {
     pub prev_hash: String,
     pub hash: String,
     pub timestamp: u64,
+    /// Merkle root of balances and nonces after this block (`Storage::state_root`).
+    /// Stamped by the chain when it commits a block it executed, so like `hash` and
+    /// `signature_hex` it stays out of the signed bytes: the sender signs before the
+    /// resulting state is known. Empty on blocks from before state roots.
+    #[serde(default)]
+    pub state_root: String,
+    /// Validator signature over `state_root`, made by the node that executed and
+    /// committed the block. The root is outside the signed bytes, so without this
+    /// any relay could rewrite it.
+    #[serde(default)]
+    pub root_attestation: Option<RootAttestation>,
+}
+
+/// A validator vouching for the state a block left behind
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RootAttestation {
+    /// Validator id or public key
+    pub signer: String,
+    pub signature: String,
+}
+
+/// Message a validator signs to attest `state_root` after block `hash`
+pub fn state_root_message(hash: &str, state_root: &str) -> String {
+    format!("COMPASS_STATE_ROOT:{}:{}", hash, state_root)
 }
 
 impl BlockHeader {
@@ -189,11 +961,11 @@ impl BlockHeader {
         // Index, BlockType, Proposer, Timestamp, PrevHash
         // (Signature and SelfHash are excluded)
 
-        self.index.canonical_serialize(&mut buf).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
-        self.block_type.canonical_serialize(&mut buf).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
-        self.proposer.canonical_serialize(&mut buf).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
-        self.timestamp.canonical_serialize(&mut buf).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
-        self.prev_hash.canonical_serialize(&mut buf).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
+        self.index.canonical_serialize(&mut buf).map_err(EncodingError::from)?;
+        self.block_type.canonical_serialize(&mut buf).map_err(EncodingError::from)?;
+        self.proposer.canonical_serialize(&mut buf).map_err(EncodingError::from)?;
+        self.timestamp.canonical_serialize(&mut buf).map_err(EncodingError::from)?;
+        self.prev_hash.canonical_serialize(&mut buf).map_err(EncodingError::from)?;
         
         Ok(buf)
     }
@@ -203,7 +975,7 @@ impl BlockHeader {
         let mut hasher = Sha256::new();
         // Use our new strict serializer
         hasher.update(&self.canonical_bytes()?);
-        Ok(hex::encode(hasher.finalize()))
+        Ok(encoding::hash_to_hex(&hasher.finalize()))
     }
 }
 
@@ -273,6 +1045,8 @@ pub fn create_proposal_block(
         prev_hash,
         hash: String::new(),
         timestamp: now,
+        state_root: String::new(),
+        root_attestation: None,
     };
 
     let pre_sign_hash = header.calculate_hash()?;
@@ -299,8 +1073,8 @@ pub fn create_poh_block(
     admin: &KeyPair,
 ) -> Result<BlockHeader, crate::error::CompassError> {
     // timestamp is passed in
-    let hash_hex = hex::encode(&end_vdf_hash);
-    let proof_hex = hex::encode(&proof_bytes);
+    let hash_hex = encoding::hash_to_hex(&end_vdf_hash);
+    let proof_hex = encoding::to_hex(&proof_bytes);
 
     let mut header = BlockHeader {
         index,
@@ -315,12 +1089,14 @@ pub fn create_poh_block(
         signature_hex: String::new(),
         prev_hash,
         hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
     };
 
     let pre_sign_hash = header.calculate_hash()?;
     
     // Decode to raw bytes for signing (safer/cleaner than signing ASCII hex)
-    let raw_hash = hex::decode(&pre_sign_hash).map_err(|e| crate::error::CompassError::SerializationError(e.to_string()))?;
+    let raw_hash = encoding::hash_from_hex(&pre_sign_hash)?;
 
     // Sign the RAW bytes
     let signature_hex = admin.sign_hex(&raw_hash);
@@ -331,7 +1107,7 @@ pub fn create_poh_block(
          
          // Verify immediately with raw bytes
             let pk_bytes = admin.public_key();
-            let pk_hex = hex::encode(pk_bytes.as_bytes());
+            let pk_hex = encoding::encode_address(pk_bytes.as_bytes());
             
             if !crate::crypto::verify_with_pubkey_hex(&raw_hash, &signature_hex, &pk_hex) {
                 // This is a sanity check panic in development, converting to error log + panic for now as it's critical
@@ -347,7 +1123,8 @@ pub fn create_poh_block(
     Ok(header)
 }
 
-/// Vote block
+/// Vote block. It counts in `Chain::tally_votes` with the voter's collateral stake
+/// and the stake delegated to it.
 pub fn create_vote_block(
     index: u64,
     voter_wallet_id: String,
@@ -368,12 +1145,113 @@ pub fn create_vote_block(
         signature_hex: String::new(),
         prev_hash,
         hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
     };
 
-    let pre_sign_hash = header.calculate_hash()?;
-    let sig_hex = voter.sign_hex(pre_sign_hash.as_bytes());
-    header.signature_hex = sig_hex;
+    // Signed over the raw hash bytes, as `Chain::append_vote` verifies
+    header.hash = header.calculate_hash()?;
+    header.signature_hex = voter.sign_hex(&encoding::hash_from_hex(&header.hash)?);
+
+    Ok(header)
+}
+
+/// Delegation block: `delegator` (a public key) hands its voting power to
+/// `representative`, or takes it back when that is empty
+pub fn create_delegate_block(
+    index: u64,
+    representative: String,
+    nonce: u64,
+    prev_hash: String,
+    delegator: &KeyPair,
+) -> Result<BlockHeader, crate::error::CompassError> {
+    let delegator_id = delegator.public_key_hex();
+    let signature = delegator.sign_hex(crate::governance::delegate_message(&delegator_id, &representative, nonce).as_bytes());
+    let mut header = BlockHeader {
+        index,
+        block_type: BlockType::Delegate { delegator: delegator_id.clone(), representative, nonce, signature },
+        proposer: delegator_id,
+        timestamp: current_unix_timestamp_ms(),
+        signature_hex: String::new(),
+        prev_hash,
+        hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash()?;
+
+    Ok(header)
+}
+
+/// Treasury proposal block, signed by `proposer` over the raw hash bytes as
+/// `Chain::append_treasury_proposal` verifies
+#[allow(clippy::too_many_arguments)]
+pub fn create_treasury_proposal_block(
+    index: u64,
+    id: u64,
+    recipient: String,
+    amount: u64,
+    deadline_ms: u64,
+    text: String,
+    prev_hash: String,
+    proposer: &KeyPair,
+) -> Result<BlockHeader, crate::error::CompassError> {
+    let proposer_id = proposer.public_key_hex();
+    let mut header = BlockHeader {
+        index,
+        block_type: BlockType::TreasuryProposal {
+            id,
+            proposer: proposer_id.clone(),
+            recipient,
+            amount,
+            deadline: deadline_ms,
+            text,
+        },
+        proposer: proposer_id,
+        timestamp: current_unix_timestamp_ms(),
+        signature_hex: String::new(),
+        prev_hash,
+        hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash()?;
+    header.signature_hex = proposer.sign_hex(&encoding::hash_from_hex(&header.hash)?);
+
+    Ok(header)
+}
+
+/// Stability fee proposal block, signed by `proposer` over the raw hash bytes as
+/// `Chain::append_stability_fee_proposal` verifies
+pub fn create_stability_fee_proposal_block(
+    index: u64,
+    id: u64,
+    annual_rate_bps: u64,
+    deadline_ms: u64,
+    text: String,
+    prev_hash: String,
+    proposer: &KeyPair,
+) -> Result<BlockHeader, crate::error::CompassError> {
+    let proposer_id = proposer.public_key_hex();
+    let mut header = BlockHeader {
+        index,
+        block_type: BlockType::StabilityFeeProposal {
+            id,
+            proposer: proposer_id.clone(),
+            annual_rate_bps,
+            deadline: deadline_ms,
+            text,
+        },
+        proposer: proposer_id,
+        timestamp: current_unix_timestamp_ms(),
+        signature_hex: String::new(),
+        prev_hash,
+        hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
+    };
     header.hash = header.calculate_hash()?;
+    header.signature_hex = proposer.sign_hex(&encoding::hash_from_hex(&header.hash)?);
 
     Ok(header)
 }
@@ -402,6 +1280,8 @@ pub fn create_reward_block(
         signature_hex: String::new(),
         prev_hash,
         hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
     };
 
     let pre_sign_hash = header.calculate_hash()?;
@@ -439,6 +1319,8 @@ pub fn create_transfer_block(
         signature_hex: String::new(),
         prev_hash,
         hash: String::new(),
+        state_root: String::new(),
+        root_attestation: None,
     };
 
     let pre_sign_hash = header.calculate_hash()?;
@@ -448,3 +1330,59 @@ pub fn create_transfer_block(
 
     Ok(header)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_block_type_summary() {
+        let transfer = BlockType::Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount: 10,
+            nonce: 0,
+            fee: 1,
+        };
+        assert_eq!(transfer.to_string(), "Transfer 10 Compass from alice to bob, fee 1");
+
+        let sponsored = BlockType::SponsoredTransfer {
+            from: "newbie".to_string(),
+            to: "shop".to_string(),
+            asset: "cLTC".to_string(),
+            amount: 5,
+            nonce: 1,
+            fee: 1,
+            fee_payer: "faucet".to_string(),
+            fee_payer_signature: String::new(),
+        };
+        assert_eq!(sponsored.to_string(), "Transfer 5 cLTC from newbie to shop, fee 1 paid by faucet");
+
+        let vote = BlockType::Vote { proposal_id: 7, voter: "a".repeat(64), choice: true };
+        assert_eq!(vote.to_string(), "Vote YES on proposal #7 by aaaaaaaa...");
+    }
+
+    #[test]
+    fn test_sponsorship_binds_transfer() {
+        let sponsor = KeyPair::generate();
+        let sig = sponsor.sign_hex(sponsor_message("newbie", "shop", "Compass", 5, 1, 1, "faucet").as_bytes());
+        let pk = sponsor.public_key_hex();
+        assert!(crate::crypto::verify_with_pubkey_hex(
+            sponsor_message("newbie", "shop", "Compass", 5, 1, 1, "faucet").as_bytes(),
+            &sig,
+            &pk
+        ));
+        // Same sponsorship cannot cover a larger payment or a replay at the next nonce
+        assert!(!crate::crypto::verify_with_pubkey_hex(
+            sponsor_message("newbie", "shop", "Compass", 500, 1, 1, "faucet").as_bytes(),
+            &sig,
+            &pk
+        ));
+        assert!(!crate::crypto::verify_with_pubkey_hex(
+            sponsor_message("newbie", "shop", "Compass", 5, 2, 1, "faucet").as_bytes(),
+            &sig,
+            &pk
+        ));
+    }
+}
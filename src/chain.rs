@@ -1,6 +1,10 @@
 use crate::block::{BlockHeader, BlockType};
 use crate::crypto::verify_with_pubkey_hex;
-use crate::storage::Storage;
+use crate::encoding;
+use crate::epoch_report::EpochReport;
+use crate::finality::VoteRecord;
+use crate::leader_schedule::{epoch_of, LeaderSchedule, ValidatorStake};
+use crate::storage::{Receipt, Storage, TxRecord, TxStatus};
 use crate::vault::VaultManager;
 use crate::error::CompassError;
 use std::sync::{Arc, Mutex};
@@ -19,6 +23,28 @@ pub enum ForkStatus {
     Gap,         // Missing parent blocks
 }
 
+/// Deepest rollback a reorg may perform; forks that split off further back stay side branches
+pub const MAX_REORG_DEPTH: u64 = 64;
+
+/// Transactions one account may have parked for a later slot at a time
+pub const MAX_SCHEDULED_PER_ACCOUNT: usize = 16;
+
+/// Least a `Schedule` block pays, whatever the fee schedule says: parked transactions
+/// sit in every node's storage until their slot
+pub const MIN_SCHEDULE_FEE: u64 = 1000;
+
+/// Highest stability fee a proposal may set: 100% a year
+pub const MAX_STABILITY_FEE_BPS: u64 = 10_000;
+
+/// Fork-choice weight of a branch: the longer branch wins; between equally long ones, a
+/// latest PoH block made by its slot's scheduled leader wins, then the higher PoH tick
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct ChainWeight {
+    pub height: u64,
+    pub scheduled_leader: bool,
+    pub poh_tick: u64,
+}
+
 pub struct Chain {
     pub storage: Arc<Storage>,
     pub head_hash: Option<String>,
@@ -29,15 +55,47 @@ pub struct Chain {
     pub account_store: Arc<Mutex<AccountStore>>,
     pub balance_store: Arc<Mutex<BalanceStore>>,
     pub oracle_registry: Arc<Mutex<OracleRegistry>>,
+
+    /// Slot length used for liveness accounting (set from consensus config)
+    pub slot_duration_ms: u64,
+
+    /// Slots per leader schedule epoch (set from consensus config)
+    pub slots_per_epoch: u64,
+
+    /// PoUW reward parameters (set from consensus config)
+    pub pouw_curve: crate::layer3::pouw::PouwRewardCurve,
+    /// Minimum fees enforced when executing transactions (set from consensus config)
+    pub fee_schedule: crate::fees::FeeSchedule,
+    /// Smallest earnings claim accepted (set from consensus config)
+    pub min_earnings_payout: u64,
+    /// Treasury's share of fees (set from consensus config)
+    pub treasury: crate::treasury::TreasuryConfig,
+    /// Oracle sanity bands (set from consensus config)
+    pub oracle_guard: crate::oracle::circuit_breaker::OracleGuardConfig,
+    /// Price rounds and outlier cut (set from consensus config)
+    pub oracle_aggregation: crate::oracle::aggregation::OracleAggregationConfig,
+    /// Maker and taker rates Trade blocks must charge (set from consensus config)
+    pub market_fees: crate::market::MarketFeeConfig,
+    /// Interest on vault debt (set from consensus config) until governance sets
+    /// another rate; read it through `stability_fee_config`
+    pub stability_fee: crate::vault::stability::StabilityFeeConfig,
+    /// Block and PoUW reward curve (set from consensus config)
+    pub emission: crate::layer2::economics::EmissionSchedule,
+    /// Integrator callbacks, told of every committed block
+    pub hooks: crate::hooks::HookRegistry,
+    /// Light nodes keep synced headers only and apply none of their effects (set from node config)
+    pub header_only: bool,
+    /// Key that attests the state root of each block this node commits (set from
+    /// the node identity). Without one, committed blocks carry an unattested root.
+    pub root_signer: Option<Arc<crate::node::signer::NodeSigner>>,
+
+    /// Gulf Stream hash of the transaction `apply_tx` is running; `commit_block`
+    /// indexes it against the block it writes
+    indexing_tx: Option<String>,
 }
 
 impl Chain {
     pub fn new(storage: Arc<Storage>) -> Self {
-        // Attempt to load head from storage
-        // Key: "chain_info:head" -> hash
-        let head_hash: Option<String> = storage.get("chain_info:head").unwrap_or(None);
-        let mut height = 0;
-
         // --- Vault Manager (Migrated to Sled) ---
         let mut vault_manager = VaultManager::new_with_storage(storage.clone());
         if vault_manager.vaults.is_empty() && std::path::Path::new("vaults.json").exists() {
@@ -56,6 +114,23 @@ impl Chain {
             info!("Persistence: ✅ Vault Migration Complete.");
         }
 
+        Self::open(storage, vault_manager)
+    }
+
+    /// Empty chain on a throwaway database (no legacy file migration), for unit
+    /// tests and fuzzing. Pair with `KeyPair::from_seed` for reproducible signers.
+    pub fn new_in_memory() -> Self {
+        let storage = Arc::new(Storage::new_in_memory().expect("Failed to open temporary DB"));
+        let vault_manager = VaultManager::new_with_storage(storage.clone());
+        Self::open(storage, vault_manager)
+    }
+
+    fn open(storage: Arc<Storage>, vault_manager: VaultManager) -> Self {
+        // Attempt to load head from storage
+        // Key: "chain_info:head" -> hash
+        let head_hash: Option<String> = storage.get("chain_info:head").unwrap_or(None);
+        let mut height = 0;
+
         if let Some(ref h) = head_hash {
             // Load block to get index/height
             if let Ok(Some(b)) = storage.get_block(h) {
@@ -68,6 +143,14 @@ impl Chain {
             info!("🆕 No existing blockchain found - will initialize genesis");
         }
 
+        storage.set_event_height(height);
+
+        match storage.backfill_block_indexes(height) {
+            Ok(0) => {}
+            Ok(n) => info!("Indexed {} existing blocks by account and proposer", n),
+            Err(e) => warn!("Block index backfill failed: {}", e),
+        }
+
         Chain {
             storage: storage.clone(),
             head_hash,
@@ -78,6 +161,21 @@ impl Chain {
             account_store: Arc::new(Mutex::new(AccountStore::new())),
             balance_store: Arc::new(Mutex::new(BalanceStore::new())),
             oracle_registry: Arc::new(Mutex::new(OracleRegistry::new())),
+            slot_duration_ms: 1000,
+            slots_per_epoch: crate::leader_schedule::DEFAULT_SLOTS_PER_EPOCH,
+            pouw_curve: Default::default(),
+            fee_schedule: Default::default(),
+            min_earnings_payout: 0,
+            treasury: Default::default(),
+            oracle_guard: Default::default(),
+            oracle_aggregation: Default::default(),
+            market_fees: Default::default(),
+            stability_fee: Default::default(),
+            emission: Default::default(),
+            header_only: false,
+            root_signer: None,
+            hooks: Default::default(),
+            indexing_tx: None,
         }
     }
 
@@ -98,6 +196,8 @@ impl Chain {
                 prev_hash: "0000000000000000000000000000000000000000000000000000000000000000".to_string(),
                 hash: "".to_string(), // Calculated below
                 timestamp: config.timestamp,
+                state_root: String::new(),
+                root_attestation: None,
             },
             transactions: vec![],
         };
@@ -120,12 +220,22 @@ impl Chain {
         if let Some(h) = &self.head_hash {
              self.storage.put("chain_info:genesis", h).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
         }
+        self.storage.set_chain_id(&config.chain_id)?;
+        self.storage.set_genesis_config_hash(&config.canonical_hash())?;
 
         // Apply Initial Balances
         for (addr, amount) in &config.initial_balances {
-            self.storage.set_balance(addr, "Compass", *amount).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+            self.storage.writer().set_balance(addr, "Compass", *amount)?;
             info!("Genesis: Credited {} with {}", addr, amount);
         }
+        for allocation in config.allocations.iter().filter(|a| a.vesting.is_none()) {
+            self.storage.writer().credit(&allocation.account, crate::vesting::VESTING_ASSET, allocation.amount)?;
+            info!("Genesis: Credited {} with {}", allocation.account, allocation.amount);
+        }
+        for schedule in config.vesting_schedules() {
+            self.storage.save_vesting_schedule(&schedule)?;
+            info!("Genesis: Locked {} for {}, vesting until {}", schedule.total, schedule.account, schedule.end_ms());
+        }
         
         // Initial Validators (if any)
         if !config.initial_validators.is_empty() {
@@ -202,31 +312,685 @@ impl Chain {
     }
 
     /// Internal helper to save block and update head
-    fn commit_block(&mut self, block: crate::block::Block) -> Result<(), CompassError> {
+    /// Commit a block this node executed, stamping the state root it produced
+    fn commit_block(&mut self, mut block: crate::block::Block) -> Result<(), CompassError> {
+        block.header.state_root = self.storage.state_root()?;
+        if let Some(signer) = &self.root_signer {
+            let message = crate::block::state_root_message(&block.header.hash, &block.header.state_root);
+            match signer.sign_hex(message.as_bytes(), crate::node::signer::SignPurpose::StateRoot) {
+                Ok(signature) => {
+                    block.header.root_attestation = Some(crate::block::RootAttestation {
+                        signer: signer.public_key_hex(),
+                        signature,
+                    });
+                }
+                Err(e) => warn!("State root of block {} left unattested: {}", block.header.index, e),
+            }
+        }
+        self.commit_synced_block(block)
+    }
+
+    /// Check the validator attestation on a peer block's state root. Full nodes
+    /// also recompute the root, so only header-only nodes need one to trust it.
+    fn verify_root_attestation(&self, header: &BlockHeader) -> Result<(), CompassError> {
+        let signer_key = match &header.root_attestation {
+            Some(attestation) => self.proposer_pubkey(&attestation.signer)?,
+            None => None,
+        };
+        match (&header.root_attestation, signer_key) {
+            (Some(attestation), Some(pubkey)) => {
+                let message = crate::block::state_root_message(&header.hash, &header.state_root);
+                if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), &attestation.signature, &pubkey) {
+                    return Err(CompassError::InvalidSignature);
+                }
+                Ok(())
+            }
+            _ if self.header_only && !header.state_root.is_empty() => Err(CompassError::InvalidState(format!(
+                "state root of block {} is not attested by a validator",
+                header.index
+            ))),
+            _ => Ok(()),
+        }
+    }
+
+    /// Commit a peer's block as received, keeping the state root it carries
+    fn commit_synced_block(&mut self, block: crate::block::Block) -> Result<(), CompassError> {
         let hash = block.header.hash.clone();
         if hash.is_empty() {
             return Err(CompassError::InvalidState("No hash".to_string()));
         }
 
-        // Save to DB
-        if let Err(e) = self.storage.save_block(&block) {
-            return Err(CompassError::DatabaseError(e.to_string()));
-        }
+        // The block, its indexes and the new head land together, so a crash can't
+        // leave a head pointing at a block that isn't there
+        let mut batch = self.storage.batch();
+        self.storage.stage_block(&mut batch, &block)?;
+        batch.put("chain_info:head", &hash)?;
+        self.storage.write(batch)?;
 
-        // Update Head
-        if let Err(e) = self.storage.put("chain_info:head", &hash) {
-            return Err(CompassError::DatabaseError(format!("Failed to update head: {}", e)));
+        if let Some(tx_hash) = &self.indexing_tx {
+            let record = TxRecord {
+                status: TxStatus::Confirmed,
+                block_height: Some(block.header.index),
+                block_hash: Some(hash.clone()),
+                error: None,
+                updated_at: crate::block::current_unix_timestamp_ms(),
+            };
+            if let Err(e) = self.storage.index_tx(tx_hash, &record) {
+                warn!("Failed to index tx {}: {}", tx_hash, e);
+            }
+            // Events were recorded at this block's height while it executed
+            let index = block.header.index;
+            let receipt = Receipt {
+                tx_hash: tx_hash.clone(),
+                block_height: Some(index),
+                status: TxStatus::Confirmed,
+                fee_paid: block.header.block_type.fee(),
+                logs: self.storage.get_events_range(index, index).into_iter().map(|e| e.event).collect(),
+                error: None,
+            };
+            if let Err(e) = self.storage.save_receipt(&receipt) {
+                warn!("Failed to save receipt for tx {}: {}", tx_hash, e);
+            }
         }
 
         self.head_hash = Some(hash);
         self.height += 1;
+        self.storage.set_event_height(self.height);
+
+        match &block.header.block_type {
+            BlockType::PoH { .. } => {
+                if let Err(e) = self.record_slot_liveness(&block.header) {
+                    warn!("Liveness accounting failed for block {}: {}", block.header.index, e);
+                }
+                // Fees in the blocks that follow go to the leader producing ticks
+                if let Err(e) = self.storage.set_fee_recipient(&block.header.proposer) {
+                    warn!("Failed to record fee recipient at block {}: {}", block.header.index, e);
+                }
+            }
+            BlockType::EpochReport { epoch, report_hash, .. } => {
+                if let Err(e) = self.record_epoch_report(*epoch, report_hash) {
+                    warn!("Failed to record epoch {} report: {}", epoch, e);
+                }
+            }
+            BlockType::FinalityVote { voter, target_hash, target_height, .. } => {
+                if let Err(e) = self.record_finality_vote(voter, target_hash, *target_height) {
+                    warn!("Finality vote by {} not counted: {}", voter, e);
+                }
+            }
+            _ => {}
+        }
+        self.hooks.on_block(&block);
+        if let Err(e) = self.storage.block_committed() {
+            warn!("Failed to flush after block {}: {}", block.header.index, e);
+        }
+        Ok(())
+    }
+
+    /// Execute the state transition of a block whose checks have passed; see `executor`
+    fn execute(&mut self, header: &BlockHeader) -> Result<crate::executor::Effects, CompassError> {
+        let stability_fee = self.stability_fee_config();
+        let mut state = crate::executor::State {
+            storage: &self.storage,
+            vaults: &mut self.vault_manager,
+            treasury: self.treasury,
+            stability_fee,
+            slot_duration_ms: self.slot_duration_ms,
+            emission: self.emission,
+            slots_per_epoch: self.slots_per_epoch,
+        };
+        crate::executor::apply_block(header, &mut state)
+    }
+
+    /// Execute a peer's block and check it left the state root the peer stamped on it
+    fn execute_synced(&mut self, header: &BlockHeader) -> Result<(), CompassError> {
+        self.execute(header)?;
+        let ours = self.storage.state_root()?;
+        let theirs = &header.state_root;
+        if !theirs.is_empty() && *theirs != ours {
+            warn!("⚠️ State divergence at height {}: local root {}, peer root {}", header.index, ours, theirs);
+            return Err(CompassError::InvalidState(format!(
+                "state root mismatch at height {}: local {}, peer {}",
+                header.index, ours, theirs
+            )));
+        }
+        Ok(())
+    }
+
+    /// Re-execute a block this chain's database accepted before, as `replay` does
+    /// into a scratch database. Signatures and admission checks are not repeated.
+    pub fn replay_block(&mut self, block: crate::block::Block) -> Result<(), CompassError> {
+        if let Some(head) = self.head_hash() {
+            if block.header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+        self.execute(&block.header)?;
+        self.commit_synced_block(block)
+    }
+
+    /// Run one of the `append_*` calls for the Gulf Stream transaction `tx_hash` and
+    /// index the outcome: Confirmed with the committed block, or Failed with the error
+    pub fn apply_tx<T>(
+        &mut self,
+        tx_hash: &str,
+        apply: impl FnOnce(&mut Self) -> Result<T, CompassError>,
+    ) -> Result<T, CompassError> {
+        self.indexing_tx = Some(tx_hash.to_string());
+        let result = apply(self);
+        self.indexing_tx = None;
+        if let Err(e) = &result {
+            self.record_tx_status(tx_hash, TxStatus::Failed, Some(e.to_string()));
+        }
+        result
+    }
+
+    /// Index a transaction that never reaches `commit_block`: rejected, parked for a
+    /// later slot, or applied to L2 state without a block (confirmed at the current
+    /// height). Everything but a parked transaction also gets its receipt.
+    pub fn record_tx_status(&self, tx_hash: &str, status: TxStatus, error: Option<String>) {
+        self.record_tx(tx_hash, status, error, vec![]);
+    }
+
+    /// Confirm a transaction applied without a block (e.g. an order matched
+    /// off-chain), with the events it produced as its receipt logs
+    pub fn record_tx_events(&self, tx_hash: &str, events: Vec<crate::events::StateEvent>) {
+        self.record_tx(tx_hash, TxStatus::Confirmed, None, events);
+    }
+
+    fn record_tx(&self, tx_hash: &str, status: TxStatus, error: Option<String>, logs: Vec<crate::events::StateEvent>) {
+        let record = TxRecord {
+            status,
+            block_height: (status == TxStatus::Confirmed).then_some(self.height),
+            block_hash: None,
+            error,
+            updated_at: crate::block::current_unix_timestamp_ms(),
+        };
+        if let Err(e) = self.storage.index_tx(tx_hash, &record) {
+            warn!("Failed to index tx {}: {}", tx_hash, e);
+        }
+        if status == TxStatus::Pending {
+            return;
+        }
+        let receipt = Receipt {
+            tx_hash: tx_hash.to_string(),
+            block_height: record.block_height,
+            status,
+            fee_paid: 0,
+            logs,
+            error: record.error,
+        };
+        if let Err(e) = self.storage.save_receipt(&receipt) {
+            warn!("Failed to save receipt for tx {}: {}", tx_hash, e);
+        }
+    }
+
+    /// Count a worker's result for a compute job against the PoUW curve. When it
+    /// completes the quorum, every worker that submitted the winning hash is paid.
+    pub fn record_pouw_result(
+        &self,
+        job_id: &str,
+        worker_id: &str,
+        result_hash: &str,
+        now: u64,
+    ) -> Result<crate::layer3::pouw::PouwOutcome, CompassError> {
+        use crate::layer3::pouw::{PouwOutcome, PouwTally};
+
+        let mut tally = match self.storage.get_pouw_tally(job_id)? {
+            Some(tally) => tally,
+            None => {
+                let job = self
+                    .storage
+                    .get_compute_job(job_id)?
+                    .ok_or_else(|| CompassError::InvalidState(format!("unknown compute job {}", job_id)))?;
+                PouwTally::new(&job)
+            }
+        };
+        let outcome = tally
+            .record(&self.pouw_curve, worker_id, result_hash, now)
+            .map_err(CompassError::InvalidState)?;
+        match &outcome {
+            PouwOutcome::Duplicate | PouwOutcome::AlreadySettled => return Ok(outcome),
+            PouwOutcome::Pending { .. } => {}
+            PouwOutcome::Settled { rewards } => {
+                // The emission schedule halves the curve's price and caps the total minted
+                let asset = crate::epoch_report::COMPUTE_ASSET;
+                let epoch = self.epoch_at(now.saturating_mul(1000));
+                let mut emitted = self.storage.get_emitted(asset)?;
+                let paid: Vec<(String, u64)> = rewards
+                    .iter()
+                    .map(|(worker, amount)| {
+                        let amount = self.emission.pouw_reward(*amount, epoch, emitted);
+                        emitted = emitted.saturating_add(amount);
+                        (worker.clone(), amount)
+                    })
+                    .collect();
+                let mut payout = self.storage.writer().batch();
+                for (worker, amount) in &paid {
+                    payout = payout.credit(worker, asset, *amount);
+                }
+                payout.commit()?;
+                if self.emission.max_pouw_emission > 0 {
+                    self.storage.record_emission(asset, paid.iter().map(|(_, amount)| amount).sum())?;
+                }
+                for (worker, amount) in &paid {
+                    self.storage.record_reward(worker, asset, *amount, "pouw")?;
+                }
+                self.storage.save_pouw_tally(&tally)?;
+                return Ok(PouwOutcome::Settled { rewards: paid });
+            }
+        }
+        self.storage.save_pouw_tally(&tally)?;
+        Ok(outcome)
+    }
+
+    /// Slot a block timestamp falls in
+    pub fn slot_at(&self, timestamp_ms: u64) -> u64 {
+        timestamp_ms / self.slot_duration_ms.max(1)
+    }
+
+    /// Slot of the latest committed block (0 before any), for timing that has to
+    /// follow the chain rather than this node's clock
+    pub fn head_slot(&self) -> Result<u64, CompassError> {
+        let Some(head) = self.head_hash() else { return Ok(0) };
+        Ok(self.storage.get_block(&head)?.map_or(0, |block| self.slot_at(block.header.timestamp)))
+    }
+
+    /// Validators that can lead, weighted by their `layer2::collateral` stake (under
+    /// their id or public key). Validators with no registered key can't sign blocks
+    /// and are left out.
+    pub fn validator_stakes(&self) -> Result<Vec<ValidatorStake>, CompassError> {
+        let collateral = self.storage.get_collateral_stakes()?;
+        let mut stakes = Vec::new();
+        for validator in self.storage.get_active_validators()? {
+            let Some(pubkey) = self.storage.get_validator_pubkey(&validator)? else { continue };
+            let stake = collateral.get(&validator).or_else(|| collateral.get(&pubkey)).copied().unwrap_or(0);
+            stakes.push(ValidatorStake { validator, stake });
+        }
+        Ok(stakes)
+    }
+
+    /// Leader schedule for `epoch`. It is computed from the stakes when first asked
+    /// for and kept, so stake changes take effect from the next epoch on.
+    pub fn leader_schedule(&self, epoch: u64) -> Result<LeaderSchedule, CompassError> {
+        if let Some(schedule) = self.storage.get_leader_schedule(epoch)? {
+            if schedule.slots_per_epoch == self.slots_per_epoch {
+                return Ok(schedule);
+            }
+        }
+        let schedule = LeaderSchedule::compute(epoch, self.slots_per_epoch, &self.validator_stakes()?);
+        self.storage.save_leader_schedule(&schedule)?;
+        Ok(schedule)
+    }
+
+    /// Validator scheduled to lead `slot`; `None` when there is no one to schedule
+    pub fn leader_for_slot(&self, slot: u64) -> Result<Option<String>, CompassError> {
+        let schedule = self.leader_schedule(epoch_of(slot, self.slots_per_epoch))?;
+        Ok(schedule.leader_at(slot).map(str::to_string))
+    }
+
+    /// Public key of a block proposer given by validator id or by the key itself
+    fn proposer_pubkey(&self, proposer: &str) -> Result<Option<String>, CompassError> {
+        if let Some(pubkey) = self.storage.get_validator_pubkey(proposer)? {
+            return Ok(Some(pubkey));
+        }
+        for validator in self.storage.get_active_validators()? {
+            if self.storage.get_validator_pubkey(&validator)?.as_deref() == Some(proposer) {
+                return Ok(Some(proposer.to_string()));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Whether `proposer` (validator id or public key) may produce the PoH block for
+    /// `slot`. Anyone may while no validator is scheduled.
+    pub fn may_propose(&self, slot: u64, proposer: &str) -> bool {
+        match self.leader_for_slot(slot) {
+            Ok(Some(leader)) => {
+                leader == proposer || matches!(self.storage.get_validator_pubkey(&leader), Ok(Some(pk)) if pk == proposer)
+            }
+            Ok(None) => true,
+            Err(e) => {
+                warn!("Leader schedule unavailable for slot {}: {}", slot, e);
+                false
+            }
+        }
+    }
+
+    /// The validator scheduled to lead `slot`, by public key where one is registered
+    /// (producers are tracked by the key they sign with). Falls back to the proposer
+    /// of the PoH block that closes the gap when no one is scheduled.
+    fn scheduled_leader(&self, slot: u64, proposer: &str) -> String {
+        match self.leader_for_slot(slot) {
+            Ok(Some(leader)) => self.storage.get_validator_pubkey(&leader).ok().flatten().unwrap_or(leader),
+            _ => proposer.to_string(),
+        }
+    }
+
+    /// Credit the PoH block's slot to its proposer and charge any slots skipped since
+    /// the previous PoH block to their scheduled leader
+    fn record_slot_liveness(&self, header: &BlockHeader) -> Result<(), CompassError> {
+        use crate::liveness::{advance, SkippedSlots, SlotCursor, LIVENESS_ALERT_THRESHOLD};
+
+        let cursor = self.storage.get_slot_cursor()?;
+        let (slot, skipped) = advance(cursor.as_ref(), header.timestamp, self.slot_duration_ms);
+
+        let skipped = skipped.map(|(first_slot, last_slot)| SkippedSlots {
+            first_slot,
+            last_slot,
+            leader: self.scheduled_leader(first_slot, &header.proposer),
+            detected_at_height: header.index,
+        });
+        let leader = match &skipped {
+            Some(run) => {
+                let mut leader = self.storage.get_leader_liveness(&run.leader)?;
+                leader.slots_skipped = leader.slots_skipped.saturating_add(run.count());
+                leader.last_skipped_slot = Some(run.last_slot);
+                Some(leader)
+            }
+            None => None,
+        };
+
+        let mut producer = match &leader {
+            Some(l) if l.validator == header.proposer => l.clone(),
+            _ => self.storage.get_leader_liveness(&header.proposer)?,
+        };
+        producer.slots_produced = producer.slots_produced.saturating_add(1);
+        producer.last_produced_slot = Some(slot);
+
+        let new_cursor = SlotCursor { slot, timestamp_ms: header.timestamp };
+        self.storage.record_slot(&new_cursor, &producer, skipped.as_ref().zip(leader.as_ref()))?;
+
+        if let (Some(run), Some(leader)) = (&skipped, &leader) {
+            let score = if leader.validator == producer.validator { producer.score() } else { leader.score() };
+            warn!(
+                "⏭️  Skipped {} slot(s) {}..={} (leader {}), liveness {:.3}",
+                run.count(),
+                run.first_slot,
+                run.last_slot,
+                run.leader,
+                score
+            );
+            if score < LIVENESS_ALERT_THRESHOLD {
+                warn!("🚨 Validator {} liveness {:.3} is below {:.2}", run.leader, score, LIVENESS_ALERT_THRESHOLD);
+            }
+        }
+        Ok(())
+    }
+
+    /// Epoch a block timestamp falls in
+    pub fn epoch_at(&self, timestamp_ms: u64) -> u64 {
+        epoch_of(self.slot_at(timestamp_ms), self.slots_per_epoch)
+    }
+
+    /// Tally `epoch` from the canonical chain. Blocks count toward the epoch of the
+    /// PoH block they follow, since only PoH timestamps come from the slot clock.
+    pub fn compute_epoch_report(&self, epoch: u64) -> Result<EpochReport, CompassError> {
+        let mut blocks = Vec::new();
+        let mut group = Vec::new();
+        let mut height = self.height;
+        while height > 0 {
+            height -= 1;
+            let Some(block) = self.storage.get_block_by_height(height)? else { break };
+            let marks_slot = height == 0 || matches!(block.header.block_type, BlockType::PoH { .. });
+            let group_epoch = self.epoch_at(block.header.timestamp);
+            group.push(block);
+            if !marks_slot {
+                continue;
+            }
+            if group_epoch < epoch {
+                break;
+            }
+            if group_epoch == epoch {
+                blocks.append(&mut group);
+            } else {
+                group.clear();
+            }
+        }
+        blocks.reverse();
+
+        let events = match (blocks.first(), blocks.last()) {
+            (Some(first), Some(last)) => self.storage.get_events_range(first.header.index, last.header.index),
+            _ => Vec::new(),
+        };
+        Ok(EpochReport::tally(epoch, &blocks, &events))
+    }
+
+    /// Epoch whose report block is due: the head is the first PoH block of a new
+    /// epoch and the epoch of the PoH block before it has no report yet
+    pub fn epoch_report_due(&self) -> Option<u64> {
+        let epoch = self.epoch_just_ended()?;
+        match self.storage.get_epoch_report(epoch) {
+            Ok(None) => Some(epoch),
+            _ => None,
+        }
+    }
+
+    /// Vesting releases due when the head is the first PoH block of a new epoch:
+    /// every account with something vested and unreleased at the head's timestamp
+    pub fn vesting_releases_due(&self) -> Vec<(String, u64)> {
+        if self.epoch_just_ended().is_none() {
+            return Vec::new();
+        }
+        let Ok(Some(head)) = self.storage.get_block(self.head_hash.as_deref().unwrap_or_default()) else {
+            return Vec::new();
+        };
+        self.storage
+            .get_vesting_schedules()
+            .into_iter()
+            .map(|s| {
+                let amount = s.releasable_at(head.header.timestamp);
+                (s.account, amount)
+            })
+            .filter(|(_, amount)| *amount > 0)
+            .collect()
+    }
+
+    /// Epoch of the PoH block before the head, if the head is a PoH block that
+    /// opened a later one
+    fn epoch_just_ended(&self) -> Option<u64> {
+        let head = self.storage.get_block(self.head_hash.as_ref()?).ok().flatten()?;
+        if !matches!(head.header.block_type, BlockType::PoH { .. }) {
+            return None;
+        }
+        let mut height = head.header.index;
+        let previous = loop {
+            height = height.checked_sub(1)?;
+            let block = self.storage.get_block_by_height(height).ok().flatten()?;
+            if height == 0 || matches!(block.header.block_type, BlockType::PoH { .. }) {
+                break block;
+            }
+        };
+        let epoch = self.epoch_at(previous.header.timestamp);
+        (epoch < self.epoch_at(head.header.timestamp)).then_some(epoch)
+    }
+
+    /// Keep our own tally for a report block that was just committed, warning if it
+    /// disagrees with the block
+    fn record_epoch_report(&self, epoch: u64, report_hash: &str) -> Result<(), CompassError> {
+        let report = self.compute_epoch_report(epoch)?;
+        if report.hash() != report_hash {
+            warn!("⚠️ Epoch {} report block hash {} differs from local tally {}", epoch, report_hash, report.hash());
+        }
+        self.storage.save_epoch_report(&report)
+    }
+
+    /// Validator id of `voter` (an id or a registered public key) if it is an active validator
+    fn active_validator_id(&self, voter: &str) -> Result<Option<String>, CompassError> {
+        for validator in self.storage.get_active_validators()? {
+            if validator == voter || self.storage.get_validator_pubkey(&validator)?.as_deref() == Some(voter) {
+                return Ok(Some(validator));
+            }
+        }
+        Ok(None)
+    }
+
+    /// Check that `voter` may vote for the canonical block `target_hash` at `target_height`
+    /// under its lockout; returns its validator id and the vote record it would move to
+    pub fn check_finality_vote(
+        &self,
+        voter: &str,
+        target_hash: &str,
+        target_height: u64,
+    ) -> Result<(String, VoteRecord), CompassError> {
+        let validator = self
+            .active_validator_id(voter)?
+            .ok_or_else(|| CompassError::InvalidState(format!("{} is not an active validator", voter)))?;
+        if !self.is_canonical_at(target_height, target_hash) {
+            return Err(CompassError::InvalidState(format!(
+                "vote target {} is not the canonical block at height {}",
+                target_hash, target_height
+            )));
+        }
+        let previous = self.storage.get_finality_vote(&validator)?;
+        // Both canonical, so the older block is an ancestor of the newer one
+        let extends = previous.as_ref().is_some_and(|p| self.is_canonical_at(p.height, &p.hash));
+        let record = VoteRecord::next(previous.as_ref(), target_hash, target_height, extends).map_err(CompassError::InvalidState)?;
+        Ok((validator, record))
+    }
+
+    /// Count a committed finality vote and move the finalized height up if it now has a supermajority
+    fn record_finality_vote(&self, voter: &str, target_hash: &str, target_height: u64) -> Result<(), CompassError> {
+        let (validator, record) = self.check_finality_vote(voter, target_hash, target_height)?;
+        self.storage.save_finality_vote(&validator, &record)?;
+        self.update_finality()
+    }
+
+    /// Recompute the finalized height from the validators' latest canonical votes,
+    /// weighted like the leader schedule. Finality never moves back.
+    fn update_finality(&self) -> Result<(), CompassError> {
+        let mut stakes = self.validator_stakes()?;
+        if stakes.iter().all(|v| v.stake == 0) {
+            stakes.iter_mut().for_each(|v| v.stake = 1);
+        }
+        let total = stakes.iter().fold(0u64, |sum, v| sum.saturating_add(v.stake));
+        let mut votes = Vec::new();
+        for v in &stakes {
+            if let Some(record) = self.storage.get_finality_vote(&v.validator)? {
+                if self.is_canonical_at(record.height, &record.hash) {
+                    votes.push((v.stake, record.height));
+                }
+            }
+        }
+
+        let Some(height) = crate::finality::finalized_height(&votes, total) else { return Ok(()) };
+        if self.finalized_height().is_some_and(|finalized| finalized >= height) {
+            return Ok(());
+        }
+        let hash: String = self
+            .storage
+            .get(&format!("height:{}", height))?
+            .ok_or_else(|| CompassError::InvalidState(format!("no canonical block at height {}", height)))?;
+        self.storage.set_finalized(height, &hash)?;
+        info!("🏁 Finalized height {} ({}...)", height, &hash[..hash.len().min(12)]);
+        Ok(())
+    }
+
+    /// Highest block height a supermajority of stake has voted for
+    pub fn finalized_height(&self) -> Option<u64> {
+        self.storage.get_finalized().ok().flatten().map(|(height, _)| height)
+    }
+
+    /// Whether the canonical block at `height` is final
+    pub fn is_finalized(&self, height: u64) -> bool {
+        self.finalized_height().is_some_and(|finalized| height <= finalized)
+    }
+
+    /// Merkle root of the current balances and nonces
+    pub fn state_root(&self) -> Result<String, CompassError> {
+        self.storage.state_root()
+    }
+
+    /// Proof of `wallet_id`'s `asset` balance against the current state root
+    pub fn balance_proof(&self, wallet_id: &str, asset: &str) -> Result<Option<crate::merkle::StateProof>, CompassError> {
+        self.storage.state_proof(&format!("bal:{}:{}", wallet_id, asset))
+    }
+
+    /// Light client check of a peer's proof: it must hash up to the state root in
+    /// our own header at `height`. A root that moved on since that block fails too.
+    pub fn verify_state_proof(&self, proof: &crate::merkle::StateProof, height: u64) -> Result<(), CompassError> {
+        let header = self
+            .storage
+            .get_block_by_height(height)?
+            .ok_or_else(|| CompassError::InvalidState(format!("no header at height {}", height)))?
+            .header;
+        if header.state_root.is_empty() || header.state_root != proof.state_root {
+            return Err(CompassError::InvalidState(format!(
+                "proof root {} is not the state root of block {}",
+                proof.state_root, height
+            )));
+        }
+        if !proof.verify() {
+            return Err(CompassError::InvalidState("proof does not hash to its state root".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Snapshot ledger state at the current head
+    pub fn take_snapshot(&self) -> Result<crate::storage::SnapshotManifest, CompassError> {
+        let head = self
+            .head_hash
+            .as_ref()
+            .and_then(|h| self.storage.get_block(h).ok().flatten())
+            .ok_or_else(|| CompassError::InvalidState("no head block to snapshot".to_string()))?;
+        self.storage.create_snapshot(&head)
+    }
+
+    /// Prune blocks older than the last `keep_blocks`, but never past the latest
+    /// snapshot (peers sync from it onward) or the finalized height (anything newer
+    /// may still be reorged). Returns how many blocks were deleted.
+    pub fn prune(&self, keep_blocks: u64) -> Result<u64, CompassError> {
+        let mut below = self.height.saturating_sub(keep_blocks);
+        if let Some(snapshot) = self.storage.latest_snapshot() {
+            below = below.min(snapshot.height);
+        }
+        if let Some(finalized) = self.finalized_height() {
+            below = below.min(finalized + 1);
+        }
+        self.storage.prune_blocks(below)
+    }
+
+    /// Fast sync: adopt a peer's snapshot instead of fetching every block before it.
+    /// The snapshot block must be ahead of us and pass the same signature check as a
+    /// synced block; its chunks must match the manifest.
+    pub fn restore_snapshot(
+        &mut self,
+        manifest: &crate::storage::SnapshotManifest,
+        chunks: &[crate::storage::SnapshotChunk],
+    ) -> Result<(), CompassError> {
+        let block = &manifest.block;
+        if block.header.index != manifest.height || manifest.height < self.height {
+            return Err(CompassError::InvalidState(format!(
+                "snapshot at height {} is not ahead of local height {}",
+                manifest.height, self.height
+            )));
+        }
+        if block.header.hash != block.header.calculate_hash()? {
+            return Err(CompassError::HashMismatch("calculated".to_string(), block.header.hash.clone()));
+        }
+        self.verify_block_signature(block)?;
+
+        self.storage.restore_snapshot(manifest, chunks)?;
+        self.head_hash = Some(block.header.hash.clone());
+        self.height = manifest.height + 1;
+        self.storage.set_event_height(self.height);
+        self.vault_manager = VaultManager::new_with_storage(self.storage.clone());
+        info!("📸 Restored state snapshot at height {} ({} chunks)", manifest.height, chunks.len());
         Ok(())
     }
 
     /// Public method for P2P Sync (Trusts the block verified by peer)
     pub fn sync_block(&mut self, block: crate::block::Block) -> Result<(), CompassError> {
-        // 1. Idempotency
-        if self.storage.get_block(&block.header.hash).map(|o| o.is_some()).unwrap_or(false) {
+        // 1. Idempotency. A block we already executed must have left our state where
+        // it left the peer's; a different root means the two nodes have diverged.
+        if let Ok(Some(local)) = self.storage.get_block(&block.header.hash) {
+            let (ours, theirs) = (&local.header.state_root, &block.header.state_root);
+            if !ours.is_empty() && !theirs.is_empty() && ours != theirs {
+                warn!("⚠️ State divergence at height {}: local root {}, peer root {}", block.header.index, ours, theirs);
+                return Err(CompassError::InvalidState(format!(
+                    "state root mismatch at height {}: local {}, peer {}",
+                    block.header.index, ours, theirs
+                )));
+            }
             return Ok(());
         }
 
@@ -243,45 +1007,179 @@ impl Chain {
             return Err(CompassError::HashMismatch("calculated".to_string(), block.header.hash.clone()));
         }
         self.verify_block_signature(&block)?;
+        self.verify_root_attestation(&block.header)?;
 
-        // 4. Fork Choice Rule (Longest Chain / Heaviest Chain)
-        // Check if this block creates a new Head (Higher Height)
-        let current_head_height = self.height;
-        let new_block_height = block.header.index + 1;
-
-        if new_block_height > current_head_height {
-            // New Heaviest Chain detected!
-            // Case A: Simple Extension (Parent == Current Head)
-            if let Some(h) = &self.head_hash {
-                if &block.header.prev_hash == h {
-                    // Happy path: Append
-                    info!("🔗 Chain Extended: Height {} -> {}", current_head_height, new_block_height);
-                    return self.commit_block(block);
+        // 4. Fork Choice Rule (Longest Chain, PoH tick as tie-break)
+        let extends_head = match &self.head_hash {
+            Some(h) => &block.header.prev_hash == h,
+            None => block.header.index == 0,
+        };
+        if extends_head {
+            info!("🔗 Chain Extended: Height {} -> {} [{}]", self.height, block.header.index + 1, block.header.block_type);
+            // The proposer ran the block's admission checks; a follower only replays its effects
+            if !self.header_only {
+                if let Err(e) = self.execute_synced(&block.header) {
+                    self.storage.revert_events(self.height, self.height + 1)?;
+                    return Err(e);
                 }
-            } else if block.header.index == 0 {
-                 // Genesis case
-                 return self.commit_block(block);
             }
+            return self.commit_synced_block(block);
+        }
 
-            // Case B: Re-Org (Fork with Higher Weight)
-            // Example: We are at Height 10 (Head A). New Block is Height 11 (Head B), Parent is Height 10 (Branch B).
-            // Parent B exists (checked above).
-            // We switch Head pointer. 
-            // WARN: State (Balances) currently reflects Chain A. 
-            // In a full implementation, we must revert A and apply B.
-            // For v1.4, we will Log the Re-Org and update Head, assuming state convergence handling later.
-            warn!("🔀 RE-ORG DETECTED: Switching Head from Height {} to {}", current_head_height, new_block_height);
-            return self.commit_block(block);
+        // Side branch: store it and track its tip in place of its parent's
+        self.storage.save_side_block(&block)?;
+        self.storage.remove_fork_tip(&block.header.prev_hash)?;
+        self.storage.add_fork_tip(&block.header.hash, block.header.index)?;
 
-        } else {
-            // Block is valid but not heavier (Side-chain or old block).
-            // Just store it without updating Head.
-            // This allows us to track forks.
-            info!("🥢 Fork/Stale Block stored: Height {} (Head is {})", new_block_height, current_head_height);
-            if let Err(e) = self.storage.save_block(&block) {
-                return Err(CompassError::DatabaseError(e.to_string()));
+        let candidate = self.branch_weight(&block);
+        let current = self.head_weight();
+        if candidate > current {
+            warn!(
+                "🔀 RE-ORG DETECTED: Switching Head from Height {} to {} (tick {} -> {})",
+                current.height, candidate.height, current.poh_tick, candidate.poh_tick
+            );
+            return self.reorganize(block);
+        }
+
+        info!("🥢 Fork/Stale Block stored: Height {} (Head is {})", block.header.index + 1, self.height);
+        Ok(())
+    }
+
+    /// Weight of the canonical chain
+    pub fn head_weight(&self) -> ChainWeight {
+        match self.head_hash.as_ref().and_then(|h| self.storage.get_block(h).ok().flatten()) {
+            Some(head) => self.branch_weight(&head),
+            None => ChainWeight::default(),
+        }
+    }
+
+    /// Weight of the branch ending at `tip`. The PoH tick is the one of the latest PoH
+    /// block within `MAX_REORG_DEPTH` of the tip, which covers any branch we could switch to.
+    fn branch_weight(&self, tip: &crate::block::Block) -> ChainWeight {
+        let mut poh_tick = 0;
+        let mut scheduled_leader = false;
+        let mut cursor = Some(tip.clone());
+        for _ in 0..=MAX_REORG_DEPTH {
+            let Some(block) = cursor else { break };
+            if let BlockType::PoH { tick, .. } = block.header.block_type {
+                poh_tick = tick;
+                scheduled_leader = self.may_propose(self.slot_at(block.header.timestamp), &block.header.proposer);
+                break;
             }
-            return Ok(());
+            if block.header.index == 0 {
+                break;
+            }
+            cursor = self.storage.get_block(&block.header.prev_hash).ok().flatten();
+        }
+        ChainWeight { height: tip.header.index + 1, scheduled_leader, poh_tick }
+    }
+
+    fn is_canonical(&self, block: &crate::block::Block) -> bool {
+        self.is_canonical_at(block.header.index, &block.header.hash)
+    }
+
+    fn is_canonical_at(&self, height: u64, hash: &str) -> bool {
+        matches!(
+            self.storage.get::<String>(&format!("height:{}", height)),
+            Ok(Some(canonical)) if canonical == hash
+        )
+    }
+
+    /// Switch the canonical chain to the branch ending at `new_tip`: roll back balances
+    /// and nonces written by the blocks above the common ancestor, then execute and
+    /// commit the new branch on top of it the same way `sync_block` does peer blocks.
+    /// The abandoned head is kept as a fork tip.
+    pub fn reorganize(&mut self, new_tip: crate::block::Block) -> Result<(), CompassError> {
+        // Walk back to the first block the canonical chain shares with the new branch
+        let mut branch = vec![new_tip];
+        loop {
+            let oldest = &branch[branch.len() - 1].header;
+            if oldest.index == 0 {
+                return Err(CompassError::InvalidState("Fork does not share our genesis block".to_string()));
+            }
+            let parent = self
+                .storage
+                .get_block(&oldest.prev_hash)?
+                .ok_or_else(|| CompassError::InvalidState(format!("Parent block {} not found (orphan)", oldest.prev_hash)))?;
+            if self.is_canonical(&parent) {
+                break;
+            }
+            if branch.len() as u64 >= MAX_REORG_DEPTH {
+                return Err(CompassError::InvalidState(format!("Reorg deeper than {} blocks refused", MAX_REORG_DEPTH)));
+            }
+            branch.push(parent);
+        }
+        branch.reverse();
+
+        let fork_height = branch[0].header.index;
+        if self.height.saturating_sub(fork_height) > MAX_REORG_DEPTH {
+            return Err(CompassError::InvalidState(format!("Reorg deeper than {} blocks refused", MAX_REORG_DEPTH)));
+        }
+        if let Some(finalized) = self.finalized_height().filter(|f| *f >= fork_height) {
+            return Err(CompassError::InvalidState(format!("Reorg below finalized height {} refused", finalized)));
+        }
+
+        // Keep the blocks being abandoned, to put back if the new branch fails to execute
+        let old_head = self.head_hash.clone();
+        let old_tip_index = self.height.saturating_sub(1);
+        let abandoned = (fork_height..self.height)
+            .map(|height| {
+                self.storage
+                    .get_block_by_height(height)?
+                    .ok_or_else(|| CompassError::InvalidState(format!("Canonical block at height {} missing", height)))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        // Roll the canonical chain back to the common ancestor
+        self.rewind_to(fork_height)?;
+        if let Some(old_head) = &old_head {
+            self.storage.add_fork_tip(old_head, old_tip_index)?;
+        }
+
+        // Apply the new branch
+        let applied = branch.len();
+        for block in branch {
+            let index = block.header.index;
+            self.storage.remove_fork_tip(&block.header.hash)?;
+            let executed = if self.header_only { Ok(()) } else { self.execute_synced(&block.header) };
+            if let Err(e) = executed.and_then(|_| self.commit_synced_block(block)) {
+                warn!("Reorg aborted: block {} of the new branch failed ({}); restoring the previous head", index, e);
+                self.rewind_to(fork_height)?;
+                for block in abandoned {
+                    if !self.header_only {
+                        self.execute(&block.header)?;
+                    }
+                    self.commit_synced_block(block)?;
+                }
+                if let Some(old_head) = &old_head {
+                    self.storage.remove_fork_tip(old_head)?;
+                }
+                return Err(e);
+            }
+        }
+        info!("🔀 Reorg complete: rolled back {} block(s), applied {} (height {})", abandoned.len(), applied, self.height);
+        Ok(())
+    }
+
+    /// Undo the effects of every block from `height` up and make its parent the head
+    fn rewind_to(&mut self, height: u64) -> Result<(), CompassError> {
+        // Events of a block that failed part way through sit at `self.height`
+        let not_reverted = self.storage.revert_events(height, self.height + 1)?;
+        if not_reverted > 0 {
+            warn!("Reorg: {} NFT/vault change(s) above height {} cannot be rolled back", not_reverted, height);
+        }
+        let parent = self
+            .storage
+            .get_block_by_height(height.saturating_sub(1))?
+            .filter(|_| height > 0)
+            .map(|b| b.header.hash);
+        self.storage.truncate_height_index(height, self.height)?;
+        self.head_hash = parent.clone();
+        self.height = height;
+        self.storage.set_event_height(self.height);
+        match parent {
+            Some(hash) => self.storage.put("chain_info:head", &hash),
+            None => self.storage.delete("chain_info:head"),
         }
     }
     
@@ -308,7 +1206,7 @@ impl Chain {
     }
 
     /// Verify block signature based on proposer type
-    fn verify_block_signature(&self, block: &crate::block::Block) -> Result<(), CompassError> {
+    pub(crate) fn verify_block_signature(&self, block: &crate::block::Block) -> Result<(), CompassError> {
         let header = &block.header;
         let recompute = header.calculate_hash()?;
         
@@ -318,15 +1216,23 @@ impl Chain {
         }
 
         match &header.block_type {
-            BlockType::PoH { .. } => {
+            BlockType::PoH { .. }
+            | BlockType::Vesting { .. }
+            | BlockType::TreasurySpend { .. }
+            | BlockType::StabilityFeeChange { .. }
+            | BlockType::Trade { .. }
+            | BlockType::RedemptionRelease { .. }
+            | BlockType::L2Checkpoint { .. }
+            | BlockType::Settlement { .. }
+            | BlockType::FreezeAuthorityChange { .. } => {
                 // Consensus Block: Must be signed by a registered validator (or admin)
                 // 1. Fetch proposer pubkey from storage
-                let pubkey_opt = self.storage.get_validator_pubkey(&header.proposer).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+                let pubkey_opt = self.proposer_pubkey(&header.proposer)?;
                 
                 if let Some(pk) = pubkey_opt {
                      // Decode hex signature
                      // Decode hash to verify RAW bytes
-                     if let Ok(raw_hash) = hex::decode(&recompute) {
+                     if let Ok(raw_hash) = encoding::hash_from_hex(&recompute) {
                          if verify_with_pubkey_hex(&raw_hash, &header.signature_hex, &pk) {
                              // Verified!
                          } else {
@@ -354,6 +1260,103 @@ impl Chain {
                      return Err(CompassError::MissingMetadata("Unknown proposer".to_string()));
                 }
             },
+            BlockType::FinalityVote { voter, target_hash, target_height, signature } => {
+                let pk = self
+                    .proposer_pubkey(voter)?
+                    .ok_or_else(|| CompassError::MissingMetadata(format!("Unknown voter {}", voter)))?;
+                let message = crate::finality::vote_message(target_hash, *target_height);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, &pk) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::Delegate { delegator, representative, nonce, signature } => {
+                let message = crate::governance::delegate_message(delegator, representative, *nonce);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, delegator) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::EarningsClaim { worker, amount, nonce, signature } => {
+                // Workers are identified by their public key
+                let message = crate::layer3::earnings::claim_message(worker, *amount, *nonce);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, worker) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::ContractDeploy { deployer, code, gas_limit, gas_price, nonce, signature } => {
+                let message = crate::vm::deploy_message(deployer, code, *gas_limit, *gas_price, *nonce);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, deployer) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::ContractCall { caller, contract, method, input, gas_limit, gas_price, nonce, signature } => {
+                let message = crate::vm::call_message(caller, contract, method, input, *gas_limit, *gas_price, *nonce);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, caller) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::ModelInvocation { token_id, caller, inputs, timestamp, signature } => {
+                let message = crate::layer3::model_nft::invoke_message(token_id, caller, inputs, *timestamp);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, caller) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::MultisigTransfer { from, to, asset, amount, nonce, approvals } => {
+                // No header signature: the approvals must meet the account's threshold
+                let account = self
+                    .storage
+                    .get_account(from)?
+                    .filter(|a| a.is_multisig())
+                    .ok_or_else(|| CompassError::InvalidState(format!("{} is not a multisig account", from)))?;
+                let message = crate::account::multisig::approval_message(from, to, asset, *amount, *nonce);
+                account
+                    .authorization()
+                    .verify_threshold(message.as_bytes(), approvals)
+                    .map_err(|e| {
+                        warn!("Multisig transfer from {} lacks approvals: {}", from, e);
+                        CompassError::InvalidSignature
+                    })?;
+            },
+            BlockType::AssetFreeze { compass_asset, account, frozen, authority, seq, authority_signature, .. } => {
+                // Only the key a FreezeAuthorityChange block assigned may freeze
+                let registered = self
+                    .storage
+                    .get_freeze_authority(compass_asset)?
+                    .ok_or_else(|| CompassError::InvalidState(format!("{} has no freeze authority", compass_asset)))?;
+                if &registered != authority {
+                    return Err(CompassError::InvalidState(format!(
+                        "{} is not the freeze authority for {}",
+                        authority, compass_asset
+                    )));
+                }
+                if !crate::vault::compliance::verify_freeze(authority, compass_asset, account, *frozen, *seq, authority_signature) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::UnbondedWithdrawal { entity, nonce, signature, .. } => {
+                // Stakers are identified by their public key
+                let message = crate::layer2::collateral::withdraw_message(entity, *nonce);
+                if !verify_with_pubkey_hex(message.as_bytes(), signature, entity) {
+                    return Err(CompassError::InvalidSignature);
+                }
+            },
+            BlockType::Schedule { account, execute_at_slot, fee, envelope } => {
+                // The account signed the envelope, which binds the slot and fee too
+                let tx = crate::network::decode_scheduled_envelope(envelope).map_err(CompassError::InvalidState)?;
+                let chain_id = self.storage.get_chain_id()?;
+                tx.verify(chain_id.as_deref(), Some(&self.storage)).map_err(|e| {
+                    warn!("Schedule envelope rejected: {}", e);
+                    CompassError::InvalidSignature
+                })?;
+                let matches = match &tx.payload {
+                    crate::network::TransactionPayload::Scheduled { execute_at_slot: slot, fee: paid, inner } => {
+                        slot == execute_at_slot && paid == fee && inner.get_account_id().as_ref() == Some(account)
+                    }
+                    _ => false,
+                };
+                if !matches || &header.proposer != account {
+                    return Err(CompassError::InvalidState("Schedule block does not match its envelope".to_string()));
+                }
+            },
             BlockType::ValidatorRegistration { validator_id, pubkey, .. } => {
                  // Self-Signed by the explicit pubkey in the payload
                  // header.proposer should match validator_id
@@ -408,7 +1411,7 @@ impl Chain {
         }
         
         // Decode hash to verify RAW bytes
-        let raw_hash = hex::decode(&recompute).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
         if !verify_with_pubkey_hex(&raw_hash, sig_hex, admin_pubkey_hex) {
             return Err(CompassError::InvalidSignature);
         }
@@ -424,6 +1427,58 @@ impl Chain {
         self.commit_block(full_block)
     }
 
+    /// Append the report block for a finished epoch, signed by `proposer_pubkey_hex`.
+    /// Its totals must match this node's own tally of the epoch.
+    pub fn append_epoch_report(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
+        if header.signature_hex.is_empty() || !verify_with_pubkey_hex(&raw_hash, &header.signature_hex, proposer_pubkey_hex) {
+            return Err(CompassError::InvalidSignature);
+        }
+
+        let BlockType::EpochReport { epoch, fees_collected, compute_emitted, report_hash } = &header.block_type else {
+            return Err(CompassError::InvalidState("not an epoch report block".to_string()));
+        };
+        let report = self.compute_epoch_report(*epoch)?;
+        if (report.fees_collected, report.compute_emitted, report.hash()) != (*fees_collected, *compute_emitted, report_hash.clone()) {
+            return Err(CompassError::InvalidState(format!("epoch {} report does not match the local tally", epoch)));
+        }
+
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
+    /// Append a leader's release of vested genesis funds, signed by
+    /// `proposer_pubkey_hex`. Executing it checks the amount against the schedule.
+    pub fn append_vesting(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
+        if header.signature_hex.is_empty() || !verify_with_pubkey_hex(&raw_hash, &header.signature_hex, proposer_pubkey_hex) {
+            return Err(CompassError::InvalidSignature);
+        }
+        if !matches!(header.block_type, BlockType::Vesting { .. }) {
+            return Err(CompassError::InvalidState("not a vesting block".to_string()));
+        }
+
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
     /// Append a PoH block (admin only)
     pub fn append_poh(
         &mut self,
@@ -442,7 +1497,7 @@ impl Chain {
             return Err(CompassError::InvalidSignature);
         }
 
-        let raw_hash = hex::decode(&recompute).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
         if !verify_with_pubkey_hex(&raw_hash, sig_hex, admin_pubkey_hex) {
             return Err(CompassError::InvalidSignature);
         }
@@ -451,6 +1506,8 @@ impl Chain {
             return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
         }
 
+        // Mints the proposer's block reward, if the emission schedule pays one
+        self.execute(&header)?;
         let full_block = crate::block::Block {
             header: header.clone(),
             transactions: vec![],
@@ -476,7 +1533,7 @@ impl Chain {
             return Err(CompassError::InvalidSignature);
         }
         
-        let raw_hash = hex::decode(&recompute).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
         if !verify_with_pubkey_hex(&raw_hash, sig_hex, admin_pubkey_hex) {
             return Err(CompassError::InvalidSignature);
         }
@@ -510,7 +1567,7 @@ impl Chain {
             return Err(CompassError::InvalidSignature);
         }
         
-        let raw_hash = hex::decode(&recompute).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
         if !verify_with_pubkey_hex(&raw_hash, sig_hex, voter_pubkey_hex) {
             return Err(CompassError::InvalidSignature);
         }
@@ -526,12 +1583,8 @@ impl Chain {
         self.commit_block(full_block)
     }
 
-    /// Append a transfer block (verify signature, balance, nonce)
-    pub fn append_transfer(
-        &mut self,
-        header: BlockHeader,
-        sender_pubkey_hex: &str,
-    ) -> Result<(), CompassError> {
+    /// Check that a user-signed header extends the head and is signed by `sender_pubkey_hex`
+    fn verify_signed_header(&self, header: &BlockHeader, sender_pubkey_hex: &str) -> Result<(), CompassError> {
         // 1. Check prev_hash
         if let Some(head) = self.head_hash() {
             if header.prev_hash != head {
@@ -546,7 +1599,7 @@ impl Chain {
             return Err(CompassError::InvalidSignature);
         }
         
-        let raw_hash = hex::decode(&recompute).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let raw_hash = encoding::hash_from_hex(&recompute)?;
         if !verify_with_pubkey_hex(&raw_hash, sig_hex, sender_pubkey_hex) {
             warn!("Sig Verify Failed!");
             debug!("Hash (Recomputed): {}", recompute);
@@ -559,6 +1612,84 @@ impl Chain {
             return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
         }
 
+        // 3. The key must be the sender's own, or the one a named account registered
+        let sender = match &header.block_type {
+            BlockType::Transfer { from, .. }
+            | BlockType::SponsoredTransfer { from, .. }
+            | BlockType::AssetFeeTransfer { from, .. } => from,
+            _ => &header.proposer,
+        };
+        if self.storage.signing_key(sender)? != sender_pubkey_hex {
+            warn!("Key {} does not sign for {}", sender_pubkey_hex, sender);
+            return Err(CompassError::InvalidSignature);
+        }
+        Ok(())
+    }
+
+    /// Reject movements of an issuer-controlled asset touching a frozen account
+    fn ensure_not_frozen(&self, asset: &str, accounts: &[&String]) -> Result<(), CompassError> {
+        for account in accounts {
+            if self.storage.is_frozen(asset, account) {
+                return Err(CompassError::InvalidState(format!("account {} is frozen for {}", account, asset)));
+            }
+        }
+        Ok(())
+    }
+
+    /// Reject fee-paying blocks that pay less than the fee schedule's minimum
+    fn ensure_min_fee(&self, block_type: &BlockType) -> Result<(), CompassError> {
+        self.fee_schedule.priority_fee(block_type).map(|_| ()).map_err(CompassError::InvalidState)
+    }
+
+    /// Reject single-signature spends from a multisig account
+    fn ensure_single_signer(&self, from: &str) -> Result<(), CompassError> {
+        if let Some(account) = self.storage.get_account(from)? {
+            if account.is_multisig() {
+                return Err(CompassError::InvalidState(format!(
+                    "{} is a multisig account and needs a multisig transfer",
+                    from
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    /// Append an issuer freeze/thaw block. The authority signs `compliance::freeze_message`
+    /// and must be the freeze authority assigned to the asset.
+    pub fn append_asset_freeze(&mut self, header: BlockHeader) -> Result<crate::vault::compliance::FreezeRecord, CompassError> {
+        let BlockType::AssetFreeze { compass_asset, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a freeze block".to_string()));
+        };
+
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+
+        if !self.vault_manager.vaults.contains_key(compass_asset) {
+            return Err(CompassError::InvalidState(format!("{} is not a vault asset", compass_asset)));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+
+        let freeze = self.execute(&header)?.freeze;
+        self.commit_block(block)?;
+        freeze.ok_or_else(|| CompassError::InvalidState("freeze block recorded no freeze".to_string()))
+    }
+
+    /// Append a transfer block (verify signature, balance, nonce)
+    pub fn append_transfer(
+        &mut self,
+        header: BlockHeader,
+        sender_pubkey_hex: &str,
+    ) -> Result<(), CompassError> {
+        self.verify_signed_header(&header, sender_pubkey_hex)?;
+
         // 3. Extract transfer details
         if let BlockType::Transfer {
             from,
@@ -569,6 +1700,10 @@ impl Chain {
             fee,
         } = &header.block_type
         {
+            self.ensure_min_fee(&header.block_type)?;
+            self.ensure_single_signer(from)?;
+            self.ensure_not_frozen(asset, &[from, to])?;
+
             // 4. Check nonce (replay protection)
             let current_nonce = self.storage.get_nonce(from).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
             if *nonce != current_nonce + 1 {
@@ -579,18 +1714,20 @@ impl Chain {
                 )));
             }
 
-            // 5. Check sender balance (Amount + Fee)
+            // 5. Check sender balance (Amount + Fee), less what open orders hold in escrow
             // Fee is always in "Compass" (Native Token). If asset != Compass, we need to check TWO balances.
 
             // Check Fee Balance (Compass)
             let sender_compass_bal = self
                 .storage
-                .get_balance(from, "Compass")
+                .get_free_balance(from, "Compass")
                 .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            let mut required_compass = *fee;
-            if asset == "Compass" {
-                required_compass += amount;
-            }
+            let required_compass = if asset == "Compass" {
+                fee.checked_add(*amount)
+                    .ok_or_else(|| CompassError::Overflow(format!("amount {} plus fee {}", amount, fee)))?
+            } else {
+                *fee
+            };
 
             if sender_compass_bal < required_compass {
                 return Err(CompassError::InvalidState(format!(
@@ -603,46 +1740,16 @@ impl Chain {
             if asset != "Compass" {
                 let sender_asset_bal = self
                     .storage
-                    .get_balance(from, asset)
+                    .get_free_balance(from, asset)
                     .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
                 if sender_asset_bal < *amount {
                     return Err(CompassError::InvalidState(format!("insufficient {} balance", asset)));
                 }
             }
 
-            // 6. Execute transfer
-            // Deduct Fee
-            if *fee > 0 {
-                self.storage
-                    .set_balance(from, "Compass", sender_compass_bal - *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?; // Updates Compass bal
-                                                  // Credit Fee to Foundation (or Admin)
-                let foundation_bal = self
-                    .storage
-                    .get_balance("foundation", "Compass")
-                    .unwrap_or(0);
-                self.storage
-                    .set_balance("foundation", "Compass", foundation_bal + *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            }
-
-            // Deduct Amount & Credit Recipient
-            // Refetch balance if it was updated by fee logic
-            let sender_bal_final = self.storage.get_balance(from, asset).unwrap_or(0);
-            // if asset==Compass, it's (sender_compass_bal - fee).
-            self.storage
-                .set_balance(from, asset, sender_bal_final - amount)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-
-            let recipient_balance = self.storage.get_balance(to, asset).unwrap_or(0);
-            self.storage
-                .set_balance(to, asset, recipient_balance + amount)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-
+            // 6. Execute transfer: fee to the block producer, amount to the recipient
             // 7. Update nonce
-            self.storage
-                .set_nonce(from, *nonce)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+            self.execute(&header)?;
 
             // 8. Commit block
             let full_block = crate::block::Block {
@@ -657,334 +1764,3021 @@ impl Chain {
         }
     }
 
-    // ... (append_mint/burn updates below need to be separate or I can include destructuring fixes here if I replace those methods again, but they were added recently.
-    // I will use multi_replace to fix destructuring in append_mint/burn if needed, or just let compilation fail and fix.
-    // Providing destructuring updates here inside the same file replacement if ranges allow.
-    // The previous replace_file_content targeted append_mint which is further down.
-    // I will do separate edits.
+    /// Append a sponsored transfer: the sender authorizes the payment by signing the
+    /// block, the fee payer authorizes the fee by signing `sponsor_message`, and the
+    /// fee is charged to the fee payer so the sender needs no Compass of their own.
+    pub fn append_sponsored_transfer(
+        &mut self,
+        header: BlockHeader,
+        sender_pubkey_hex: &str,
+        fee_payer_pubkey_hex: &str,
+    ) -> Result<(), CompassError> {
+        self.verify_signed_header(&header, sender_pubkey_hex)?;
 
-    /// Tally votes for a proposal
-    pub fn tally_votes(&self, proposal_id: u64) -> (u64, u64) {
-        let mut yes = 0;
-        let mut no = 0;
+        let BlockType::SponsoredTransfer { from, to, asset, amount, nonce, fee, fee_payer, fee_payer_signature } =
+            &header.block_type
+        else {
+            return Err(CompassError::InvalidState("not a sponsored transfer block".to_string()));
+        };
 
-        // Scan blockchain
-        for i in 0..self.height {
-            if let Ok(Some(block)) = self.storage.get_block_by_height(i) {
-                if let BlockType::Vote {
-                    proposal_id: pid,
-                    choice,
-                    ..
-                } = &block.header.block_type
-                {
-                    if *pid == proposal_id {
-                        if *choice {
-                            yes += 1;
-                        } else {
-                            no += 1;
-                        }
-                    }
-                }
-            }
+        if fee_payer == from {
+            return Err(CompassError::InvalidState("fee payer must differ from sender".to_string()));
+        }
+        self.ensure_min_fee(&header.block_type)?;
+        self.ensure_single_signer(from)?;
+        self.ensure_single_signer(fee_payer)?;
+        self.ensure_not_frozen(asset, &[from, to])?;
+        // The payer's key comes with the payload, so it must be the one that controls `fee_payer`
+        if self.storage.signing_key(fee_payer)? != fee_payer_pubkey_hex {
+            warn!("Fee payer key does not control {} for sponsored transfer from {}", fee_payer, from);
+            return Err(CompassError::InvalidSignature);
+        }
+        let message = crate::block::sponsor_message(from, to, asset, *amount, *nonce, *fee, fee_payer);
+        if !verify_with_pubkey_hex(message.as_bytes(), fee_payer_signature, fee_payer_pubkey_hex) {
+            warn!("Fee payer signature invalid for sponsored transfer from {}", from);
+            return Err(CompassError::InvalidSignature);
+        }
+
+        // Replay protection is on the sender's nonce, which the sponsorship also commits to
+        let current_nonce = self.storage.get_nonce(from).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        if *nonce != current_nonce + 1 {
+            return Err(CompassError::InvalidState(format!(
+                "invalid nonce: expected {}, got {}",
+                current_nonce + 1,
+                nonce
+            )));
+        }
+
+        let payer_bal = self
+            .storage
+            .get_free_balance(fee_payer, "Compass")
+            .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        if payer_bal < *fee {
+            return Err(CompassError::InvalidState(format!(
+                "fee payer has insufficient Compass: has {}, needs {}",
+                payer_bal, fee
+            )));
+        }
+        let sender_bal = self
+            .storage
+            .get_free_balance(from, asset)
+            .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        if sender_bal < *amount {
+            return Err(CompassError::InvalidState(format!("insufficient {} balance", asset)));
         }
 
-        (yes, no)
+        // Fee: payer -> block producer; amount: sender -> recipient
+        self.execute(&header)?;
+
+        self.commit_block(crate::block::Block {
+            header: header.clone(),
+            transactions: vec![],
+        })
     }
 
-    /// Check if a proposal ID already exists
-    pub fn proposal_id_exists(&self, id: u64) -> bool {
-        for i in 0..self.height {
-            if let Ok(Some(block)) = self.storage.get_block_by_height(i) {
-                if let BlockType::Proposal { id: pid, .. } = &block.header.block_type {
-                    if *pid == id {
-                        return true;
-                    }
-                }
-            }
+    /// Compass per unit of `asset` for fee conversion: a fresh oracle quote, or the 1:1
+    /// peg for vault-issued assets that have no quote of their own.
+    pub fn fee_conversion_rate(&self, asset: &str) -> Result<rust_decimal::Decimal, CompassError> {
+        self.ensure_oracle_live(asset)?;
+        if let Some((price, _)) = self.vault_manager.oracle_prices.get(asset) {
+            return Ok(*price);
         }
-        false
+        if self.vault_manager.vaults.contains_key(asset) {
+            return Ok(rust_decimal::Decimal::ONE);
+        }
+        Err(CompassError::InvalidState(format!("no conversion rate for {}", asset)))
     }
 
-    pub fn calculate_reward_amount(&self) -> u64 {
-        let base_reward = 50_000_000;
-        let halvings = self.height / 10_000;
-        base_reward >> halvings
+    /// Refuse to act on `ticker` while its oracle circuit breaker is tripped or its
+    /// published price is stale
+    pub fn ensure_oracle_live(&self, ticker: &str) -> Result<(), CompassError> {
+        if self.storage.is_oracle_paused(ticker) {
+            return Err(CompassError::InvalidState(format!("{} is paused by the oracle circuit breaker", ticker)));
+        }
+        let now = crate::block::current_unix_timestamp_ms() / 1000;
+        if let Some(reason) = self.vault_manager.price_staleness(ticker, now, &self.oracle_guard) {
+            return Err(CompassError::InvalidState(format!("oracle price for {} is stale: {}", ticker, reason)));
+        }
+        Ok(())
     }
 
-    /// Append a Mint block (Vault logic)
-    pub fn append_mint(
+    /// Amount of `fee_asset` charged for a Compass fee, surcharge included
+    pub fn quote_asset_fee(&self, fee_asset: &str, fee: u64) -> Result<u64, CompassError> {
+        let rate = self.fee_conversion_rate(fee_asset)?;
+        crate::amm::fee_in_asset(fee, rate).map_err(CompassError::InvalidState)
+    }
+
+    /// Move foundation-held Compass and `asset` into the asset's fee pool
+    pub fn seed_fee_pool(&mut self, asset: &str, asset_amount: u64, compass_amount: u64) -> Result<crate::amm::LiquidityPool, CompassError> {
+        if asset == "Compass" {
+            return Err(CompassError::InvalidState("Compass needs no fee pool".to_string()));
+        }
+        let asset_bal = self.storage.get_balance("foundation", asset)?;
+        let compass_bal = self.storage.get_balance("foundation", "Compass")?;
+        if asset_bal < asset_amount || compass_bal < compass_amount {
+            return Err(CompassError::InvalidState(format!(
+                "foundation holds {} {} and {} Compass",
+                asset_bal, asset, compass_bal
+            )));
+        }
+
+        let mut pool = self.storage.get_amm_pool(asset)?.unwrap_or_else(|| crate::amm::LiquidityPool::new(asset));
+        pool.add_liquidity(asset_amount, compass_amount).map_err(CompassError::InvalidState)?;
+        self.storage
+            .writer()
+            .batch()
+            .debit("foundation", asset, asset_amount)
+            .debit("foundation", "Compass", compass_amount)
+            .commit()?;
+        self.storage.save_amm_pool(&pool)?;
+        Ok(pool)
+    }
+
+    /// Append a transfer whose fee is paid in another asset. The sender is charged the
+    /// oracle-priced equivalent plus surcharge (at most `max_fee_in`), which is swapped
+    /// through the fee pool; the Compass it yields goes to the block producer.
+    pub fn append_asset_fee_transfer(
         &mut self,
         header: BlockHeader,
-        oracle_pubkey_hex: &str, // Admin or Oracle key acting as authority
+        sender_pubkey_hex: &str,
     ) -> Result<(), CompassError> {
-        // 1. Check prev_hash
-        if let Some(head) = self.head_hash() {
-            if header.prev_hash != head {
-                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
-            }
-        }
+        self.verify_signed_header(&header, sender_pubkey_hex)?;
 
-        // 2. Verify signature
-        let recompute = header.calculate_hash()?;
-        let sig_hex = &header.signature_hex;
-        if sig_hex.is_empty() {
-             return Err(CompassError::InvalidSignature);
-        }
+        let BlockType::AssetFeeTransfer { from, to, asset, amount, nonce, fee, fee_asset, max_fee_in } = &header.block_type
+        else {
+            return Err(CompassError::InvalidState("not an asset-fee transfer block".to_string()));
+        };
 
-        // Verify user signature on the header first
-        if header.hash != recompute {
-             return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        if fee_asset == "Compass" {
+            return Err(CompassError::InvalidState("Compass fees use a plain transfer".to_string()));
         }
+        self.ensure_min_fee(&header.block_type)?;
+        self.ensure_single_signer(from)?;
+        self.ensure_not_frozen(asset, &[from, to])?;
+        self.ensure_not_frozen(fee_asset, &[from])?;
 
-        if let BlockType::Mint {
-            vault_id: _,
-            collateral_asset,
-            collateral_amount,
-            compass_asset: _,
-            mint_amount,
-            owner,
-            tx_proof,
-            oracle_signature,
-            fee,
-        } = &header.block_type
-        {
-            // 4. Delegate to VaultManager (Verifies Oracle Sig + updates Vault State)
-            // Returns (correct_asset_name, minted_amount)
-            let (asset_name, minted) = self.vault_manager.deposit_and_mint(
-                collateral_asset,
-                *collateral_amount,
-                *mint_amount,
-                owner,
-                tx_proof,
-                oracle_signature,
-                oracle_pubkey_hex,
-            ).map_err(|e| CompassError::TransactionError(e.to_string()))?;
-
-            // Save Vault state to DB
-            if let Err(e) = self.vault_manager.save("") {
-                 return Err(CompassError::DatabaseError(e.to_string()));
-            }
-
-            // 5. Deduct Fee (if any)
-            if *fee > 0 {
-                let user_native_bal = self.storage.get_balance(owner, "Compass").unwrap_or(0);
-                if user_native_bal < *fee {
-                    return Err(CompassError::InvalidState("insufficient Compass balance for network fee".to_string()));
-                }
-                self.storage
-                    .set_balance(owner, "Compass", user_native_bal - *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-                let foundation = self
-                    .storage
-                    .get_balance("foundation", "Compass")
-                    .unwrap_or(0);
-                self.storage
-                    .set_balance("foundation", "Compass", foundation + *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            }
+        let current_nonce = self.storage.get_nonce(from)?;
+        if *nonce != current_nonce + 1 {
+            return Err(CompassError::InvalidState(format!(
+                "invalid nonce: expected {}, got {}",
+                current_nonce + 1,
+                nonce
+            )));
+        }
 
-            // 6. Credit Minted Asset to User
-            let current_bal = self.storage.get_balance(owner, &asset_name).unwrap_or(0);
-            self.storage
-                .set_balance(owner, &asset_name, current_bal + minted)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        let fee_in = self.quote_asset_fee(fee_asset, *fee)?;
+        if fee_in > *max_fee_in {
+            return Err(CompassError::InvalidState(format!(
+                "fee costs {} {}, above the {} allowed",
+                fee_in, fee_asset, max_fee_in
+            )));
+        }
+        let mut pool = self
+            .storage
+            .get_amm_pool(fee_asset)?
+            .ok_or_else(|| CompassError::InvalidState(format!("no fee pool for {}", fee_asset)))?;
+        let compass_out = pool.swap_asset_for_compass(fee_in, *fee).map_err(CompassError::InvalidState)?;
 
-            let full_block = crate::block::Block {
-                header: header.clone(),
-                transactions: vec![],
-            };
-            self.commit_block(full_block)?;
-            Ok(())
+        // Check both balances before touching either; they may be the same asset
+        let fee_asset_bal = self.storage.get_free_balance(from, fee_asset)?;
+        let required_fee_asset = if fee_asset == asset {
+            fee_in
+                .checked_add(*amount)
+                .ok_or_else(|| CompassError::Overflow(format!("amount {} plus fee {}", amount, fee_in)))?
         } else {
-             Err(CompassError::InvalidState("not a mint block".to_string()))
+            fee_in
+        };
+        if fee_asset_bal < required_fee_asset {
+            return Err(CompassError::InvalidState(format!(
+                "insufficient {} balance: has {}, needs {} (incl fee)",
+                fee_asset, fee_asset_bal, required_fee_asset
+            )));
         }
+        if fee_asset != asset && self.storage.get_free_balance(from, asset)? < *amount {
+            return Err(CompassError::InvalidState(format!("insufficient {} balance", asset)));
+        }
+
+        // Fee: sender's asset into the pool, pool's Compass to the block producer.
+        // Followers can't reprice it, so it is charged here rather than by `execute`.
+        let fee_to = self.storage.get_fee_recipient()?;
+        let treasury_share = self.treasury.fee_share(compass_out);
+        let mut fee_leg = self
+            .storage
+            .writer()
+            .batch()
+            .debit(from, fee_asset, fee_in)
+            .credit(&fee_to, "Compass", compass_out - treasury_share);
+        if treasury_share > 0 {
+            fee_leg = fee_leg.credit(crate::treasury::TREASURY_ACCOUNT, "Compass", treasury_share);
+        }
+        fee_leg.commit()?;
+        self.storage.save_amm_pool(&pool)?;
+        self.execute(&header)?;
+
+        self.commit_block(crate::block::Block {
+            header: header.clone(),
+            transactions: vec![],
+        })
     }
 
-    /// Append a Burn block
-    pub fn append_burn(
+    /// Register an m-of-n multisig account
+    pub fn create_multisig_account(
         &mut self,
-        header: BlockHeader,
-        redeemer_pubkey_hex: &str,
-    ) -> Result<(), CompassError> {
+        name: &str,
+        threshold: u8,
+        signers: Vec<String>,
+    ) -> Result<crate::account::Account, CompassError> {
+        if self.storage.get_account(name)?.is_some() {
+            return Err(CompassError::InvalidState(format!("account {} already exists", name)));
+        }
+        let account = self
+            .account_store
+            .lock()
+            .map_err(|_| CompassError::InvalidState("account store lock poisoned".to_string()))?
+            .create_multisig_account(name.to_string(), threshold, signers)
+            .map_err(|e| CompassError::InvalidState(format!("invalid multisig account: {:?}", e)))?;
+        self.storage.save_account(&account)?;
+        Ok(account)
+    }
+
+    /// Append a transfer out of a multisig account. There is no header signature;
+    /// instead the block carries approvals that must meet the account's threshold.
+    pub fn append_multisig_transfer(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::MultisigTransfer { from, to, asset, amount, nonce, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a multisig transfer block".to_string()));
+        };
+
         if let Some(head) = self.head_hash() {
             if header.prev_hash != head {
                 return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
             }
         }
-
         let recompute = header.calculate_hash()?;
-        let sig_hex = &header.signature_hex;
-        if sig_hex.is_empty() {
-            return Err(CompassError::InvalidSignature);
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
         }
-        if !verify_with_pubkey_hex(recompute.as_bytes(), sig_hex, redeemer_pubkey_hex) {
-            return Err(CompassError::InvalidSignature);
+
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+
+        self.ensure_not_frozen(asset, &[from, to])?;
+        let current_nonce = self.storage.get_nonce(from)?;
+        if *nonce != current_nonce + 1 {
+            return Err(CompassError::InvalidState(format!(
+                "invalid nonce: expected {}, got {}",
+                current_nonce + 1,
+                nonce
+            )));
+        }
+        let sender_bal = self.storage.get_free_balance(from, asset)?;
+        if sender_bal < *amount {
+            return Err(CompassError::InvalidState(format!("insufficient {} balance", asset)));
         }
 
-        if let BlockType::Burn {
-            vault_id: _,
-            collateral_asset: _,
-            compass_asset,
-            burn_amount,
-            redeemer,
-            destination_address,
-            fee,
-        } = &header.block_type
-        {
-            // Check Fee
-            if *fee > 0 {
-                let user_native_bal = self.storage.get_balance(redeemer, "Compass").unwrap_or(0);
-                if user_native_bal < *fee {
-                    return Err(CompassError::InvalidState("insufficient Compass balance for fee".to_string()));
-                }
-                self.storage
-                    .set_balance(redeemer, "Compass", user_native_bal - *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-                let foundation = self
-                    .storage
-                    .get_balance("foundation", "Compass")
-                    .unwrap_or(0);
-                self.storage
-                    .set_balance("foundation", "Compass", foundation + *fee)
-                    .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        self.execute(&header)?;
+        self.commit_block(block)
+    }
+
+    /// Append a validator's finality vote. Like a multisig transfer there is no header
+    /// signature; the vote signature in the block authorizes it.
+    pub fn append_finality_vote(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::FinalityVote { voter, target_hash, target_height, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a finality vote block".to_string()));
+        };
+
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
             }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+        self.check_finality_vote(voter, target_hash, *target_height)?;
+        self.commit_block(block)
+    }
 
-            // Check balance of Compass-Asset
-            let current_bal = self
-                .storage
-                .get_balance(redeemer, compass_asset)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            if current_bal < *burn_amount {
-                return Err(CompassError::InvalidState("insufficient balance to burn".to_string()));
+    // ... (append_mint/burn updates below need to be separate or I can include destructuring fixes here if I replace those methods again, but they were added recently.
+    // I will use multi_replace to fix destructuring in append_mint/burn if needed, or just let compilation fail and fix.
+    // Providing destructuring updates here inside the same file replacement if ranges allow.
+    // The previous replace_file_content targeted append_mint which is further down.
+    // I will do separate edits.
+
+    /// Append a worker's earnings claim: signed by the worker, within its claimable
+    /// balance and the minimum payout, and spending its next account nonce
+    pub fn append_earnings_claim(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::EarningsClaim { worker, amount, nonce, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not an earnings claim block".to_string()));
+        };
+
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
             }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
 
-            // 1. Burn (reduce balance on Chain)
-            self.storage
-                .set_balance(redeemer, compass_asset, current_bal - burn_amount)
-                .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        let expected = self.storage.get_nonce(worker)? + 1;
+        if *nonce != expected {
+            return Err(CompassError::InvalidState(format!("invalid nonce: expected {}, got {}", expected, nonce)));
+        }
+        let mut earnings = self.storage.get_worker_earnings(worker)?;
+        earnings.claim(*amount, self.min_earnings_payout).map_err(CompassError::InvalidState)?;
 
-            // 2. Update Vault State (Calculate collateral to release)
-            let released_collateral = self
-                .vault_manager
-                .burn_and_redeem(compass_asset, *burn_amount)
-                .map_err(|e| CompassError::TransactionError(e.to_string()))?;
-            
-            // Save Vault state to DB
-            if let Err(e) = self.vault_manager.save("") {
-                 return Err(CompassError::DatabaseError(e.to_string()));
-            };
+        self.execute(&header)?;
+        self.storage.save_worker_earnings(&earnings)?;
+        self.commit_block(block)
+    }
 
-            // Log for external watchers (Bridge)
-            info!("EVENT: Withdrawal Authorized. {} burnt. Release {} collateral to {} on External Chain.", 
-                compass_asset, released_collateral, destination_address);
+    /// Append an entity's withdrawal of its unbonded collateral: signed by the
+    /// entity, spending its next account nonce, and for exactly what has finished
+    /// unbonding by the block's slot
+    pub fn append_unbonded_withdrawal(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::UnbondedWithdrawal { entity, amount, nonce, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not an unbonded withdrawal block".to_string()));
+        };
 
-            let full_block = crate::block::Block {
-                header: header.clone(),
-                transactions: vec![],
-            };
-            self.commit_block(full_block)?;
-            Ok(())
-        } else {
-            Err(CompassError::InvalidState("not a burn block".to_string()))
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+
+        let expected = self.storage.get_nonce(entity)? + 1;
+        if *nonce != expected {
+            return Err(CompassError::InvalidState(format!("invalid nonce: expected {}, got {}", expected, nonce)));
         }
+        if *amount == 0 {
+            return Err(CompassError::InvalidState("nothing has finished unbonding".to_string()));
+        }
+
+        self.execute(&header)?;
+        self.commit_block(block)
     }
 
-    // 4. Validator Stats
-    pub fn update_validator_stats(&self, validator: &str, reward: u64, block_time_ms: u64) -> Result<(), CompassError> {
-        let mut stats = self.storage.get_validator_stats(validator).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-        stats.blocks_produced += 1;
-        stats.compute_earned += reward;
-        // Simple moving average for block time
-        if stats.blocks_produced > 1 {
-            stats.avg_block_time_ms = (stats.avg_block_time_ms + block_time_ms) / 2;
-        } else {
-            stats.avg_block_time_ms = block_time_ms;
+    /// Append the settlement of a paid model invocation: signed by the caller and
+    /// charged at most once per signed request
+    pub fn append_model_invocation(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::ModelInvocation { token_id, caller, inputs, timestamp, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a model invocation block".to_string()));
+        };
+
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
         }
-        // Uptime logic: For now, assuming 1 block = 1 second of uptime approx
-        // better would be tracking timestamps.
-        // Let's just approximate uptime hours based on block count for now (assuming 1 block/sec)
-        // 3600 blocks = 1 hour
-        stats.uptime_hours = stats.blocks_produced / 3600;
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
 
-        self.storage.set_validator_stats(validator, &stats).map_err(|e| CompassError::DatabaseError(e.to_string()))
+        let request_id = crate::layer3::model_nft::invoke_request_id(token_id, caller, inputs, *timestamp);
+        if self.storage.is_invocation_settled(&request_id)? {
+            return Err(CompassError::InvalidState(format!("invocation {} is already settled", request_id)));
+        }
+
+        self.execute(&header)?;
+        self.commit_block(block)
     }
 
-    // 5. Validator Management
-    pub fn append_validator_registration(
-        &mut self,
-        header: BlockHeader,
-    ) -> Result<(), CompassError> {
+    /// Commit balance changes an RPC call made on this node as a `Settlement` block
+    /// signed with the node's key, so followers make them too rather than finding
+    /// their state root off by them
+    pub fn settle(&mut self, legs: Vec<crate::block::SettlementLeg>, memo: &str) -> Result<(), CompassError> {
+        let signer = self
+            .root_signer
+            .clone()
+            .ok_or_else(|| CompassError::InvalidState("this node has no key to sign settlements with".to_string()))?;
+        let block_type = BlockType::Settlement { legs, memo: memo.to_string() };
+        let head_hash = self.head_hash().unwrap_or_default();
+        let header = crate::node::poh_service::build_leader_header(
+            block_type,
+            self.height,
+            head_hash,
+            &signer,
+            crate::node::signer::SignPurpose::Settlement,
+        )
+        .map_err(|e| CompassError::InvalidState(e.to_string()))?;
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+        self.execute(&header)?;
+        self.commit_block(block)
+    }
+
+    /// Assign `authority` (issuer public key hex) as the freeze authority for
+    /// vault asset `compass_asset` in a block signed with this node's key, so
+    /// every node checks later `AssetFreeze` blocks against the same key
+    pub fn set_freeze_authority(&mut self, compass_asset: &str, authority: &str) -> Result<(), CompassError> {
+        if !self.vault_manager.vaults.contains_key(compass_asset) {
+            return Err(CompassError::InvalidState(format!("{} is not a vault asset", compass_asset)));
+        }
+        let signer = self
+            .root_signer
+            .clone()
+            .ok_or_else(|| CompassError::InvalidState("this node has no key to sign freeze authorities with".to_string()))?;
+        let block_type = BlockType::FreezeAuthorityChange {
+            compass_asset: compass_asset.to_string(),
+            authority: authority.to_string(),
+        };
+        let head_hash = self.head_hash().unwrap_or_default();
+        let header = crate::node::poh_service::build_leader_header(
+            block_type,
+            self.height,
+            head_hash,
+            &signer,
+            crate::node::signer::SignPurpose::FreezeAuthorityChange,
+        )
+        .map_err(|e| CompassError::InvalidState(e.to_string()))?;
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+        self.execute(&header)?;
+        self.commit_block(block)
+    }
+
+    /// Append a contract deployment or call: signed by its sender, spending the
+    /// sender's next account nonce, within the gas limits and at no less than the
+    /// schedule's gas price. The block commits and its gas is paid even if the
+    /// contract fails; the inner result says how the run went.
+    pub fn append_contract(&mut self, header: BlockHeader) -> Result<Result<crate::vm::ContractOutcome, String>, CompassError> {
+        let (sender, gas_limit, gas_price, nonce) = match &header.block_type {
+            BlockType::ContractDeploy { deployer, code, gas_limit, gas_price, nonce, .. } => {
+                crate::vm::check_code(code)?;
+                (deployer, gas_limit, gas_price, nonce)
+            }
+            BlockType::ContractCall { caller, gas_limit, gas_price, nonce, .. } => (caller, gas_limit, gas_price, nonce),
+            _ => return Err(CompassError::InvalidState("not a contract block".to_string())),
+        };
+
         if let Some(head) = self.head_hash() {
             if header.prev_hash != head {
                 return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
             }
         }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
+
+        let expected = self.storage.get_nonce(sender)? + 1;
+        if *nonce != expected {
+            return Err(CompassError::InvalidState(format!("invalid nonce: expected {}, got {}", expected, nonce)));
+        }
+        crate::vm::check_gas(*gas_limit)?;
+        if *gas_price < self.fee_schedule.gas_price {
+            return Err(CompassError::InvalidState(format!(
+                "gas price {} is below the minimum of {}",
+                gas_price, self.fee_schedule.gas_price
+            )));
+        }
+
+        let contract = self.execute(&header)?.contract;
+        self.commit_block(block)?;
+        contract.ok_or_else(|| CompassError::InvalidState("contract block ran no contract".to_string()))
+    }
+
+    /// Append a holder's delegation of its voting power: signed by the delegator
+    /// and spending its next account nonce
+    pub fn append_delegate(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::Delegate { delegator, representative, nonce, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a delegation block".to_string()));
+        };
 
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
         let recompute = header.calculate_hash()?;
         if header.hash != recompute {
             return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
         }
-        
-        // Check Header Sig (Proposer integrity) - Usually same as validator
-        // We skip explicit header sig check here if we trust the payload sig check below, 
-        // but for full security we should check both.
-        // Assuming header.signature_hex is valid for header.proposer.
+        if delegator == representative {
+            return Err(CompassError::InvalidState("cannot delegate to yourself".to_string()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
 
-        if let BlockType::ValidatorRegistration {
-            validator_id,
-            pubkey,
-            stake_amount,
-            signature,
-        } = &header.block_type
-        {
-            // 1. Verify Payload Signature (Proof of possession of Validator Key)
-            // Message signed should be something binding, e.g. "REGISTER:<validator_id>" or just the whole block?
-            // For simplicity, let's say they sign their own validator_id.
-            if !verify_with_pubkey_hex(validator_id.as_bytes(), signature, pubkey) {
-                return Err(CompassError::InvalidSignature);
+        let expected = self.storage.get_nonce(delegator)? + 1;
+        if *nonce != expected {
+            return Err(CompassError::InvalidState(format!("invalid nonce: expected {}, got {}", expected, nonce)));
+        }
+
+        self.execute(&header)?;
+        self.commit_block(block)
+    }
+
+    /// Append a block parking `account`'s signed transaction until its slot. The
+    /// account pays the fee now; the transaction's own nonce and fee are checked
+    /// when it is released.
+    pub fn append_schedule(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::Schedule { account, execute_at_slot, fee, envelope } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a schedule block".to_string()));
+        };
+
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
             }
+        }
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        let block = crate::block::Block { header: header.clone(), transactions: vec![] };
+        self.verify_block_signature(&block)?;
 
-            // 2. Check Stake (Compass Balance)
-            let current_bal = self.storage.get_balance(validator_id, "Compass").unwrap_or(0);
-            if current_bal < *stake_amount {
-                return Err(CompassError::InvalidState(format!("Insufficient Compass balance for stake. Has {}, needs {}", current_bal, stake_amount)));
+        if *execute_at_slot <= self.height {
+            return Err(CompassError::InvalidState(format!("slot {} has already passed", execute_at_slot)));
+        }
+        if *fee < MIN_SCHEDULE_FEE {
+            return Err(CompassError::InvalidState(format!("fee {} is below the minimum of {} to schedule", fee, MIN_SCHEDULE_FEE)));
+        }
+        self.ensure_min_fee(&header.block_type)?;
+        if self.storage.get_free_balance(account, "Compass")? < *fee {
+            return Err(CompassError::InvalidState(format!("insufficient Compass balance for the {} fee", fee)));
+        }
+        if self.storage.scheduled_count(account) >= MAX_SCHEDULED_PER_ACCOUNT {
+            return Err(CompassError::InvalidState(format!(
+                "{} already has {} transactions scheduled",
+                account, MAX_SCHEDULED_PER_ACCOUNT
+            )));
+        }
+        let tx_hash = encoding::hash_to_hex(&encoding::sha256(envelope));
+        if self.storage.is_scheduled(*execute_at_slot, &tx_hash) {
+            return Err(CompassError::InvalidState("transaction is already scheduled".to_string()));
+        }
+        // A replayed envelope can't charge the fee again once its transaction has run
+        let tx = crate::network::decode_scheduled_envelope(envelope).map_err(CompassError::InvalidState)?;
+        if let Some(nonce) = tx.payload.get_nonce() {
+            if nonce <= self.storage.get_nonce(account)? {
+                return Err(CompassError::InvalidState(format!("nonce {} is already used", nonce)));
             }
+        }
+
+        self.execute(&header)?;
+        self.commit_block(block)
+    }
+
+    /// Tally votes for a proposal, as (yes, no) stake: each voter's `layer2::collateral`
+    /// stake plus the stake delegated to it (see `governance::tally`)
+    pub fn tally_votes(&self, proposal_id: u64) -> (u64, u64) {
+        let tally = self.tally_until(proposal_id, u64::MAX);
+        (tally.yes, tally.no)
+    }
+
+    /// Tally of `proposal_id` counting only blocks stamped at or before `until_ms`
+    fn tally_until(&self, proposal_id: u64, until_ms: u64) -> crate::governance::VoteTally {
+        let stakes = self.storage.get_collateral_stakes().unwrap_or_default();
+        let blocks: Vec<BlockType> = (0..self.height)
+            .filter_map(|i| self.storage.get_block_by_height(i).ok().flatten())
+            .filter(|block| block.header.timestamp <= until_ms)
+            .map(|block| block.header.block_type)
+            .collect();
+        crate::governance::tally(&blocks, proposal_id, &stakes)
+    }
 
-            // 3. Deduct Stake (Lock it)
-            // We just remove it from circulating balance. 
-            // In future, move to "StakedCompass" balance.
-            self.storage.set_balance(validator_id, "Compass", current_bal - stake_amount).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+    /// Blocks of `kind`, oldest first, from the block type index
+    fn blocks_of_kind(&self, kind: &str) -> Result<Vec<crate::block::Block>, CompassError> {
+        let (heights, _) = self.storage.get_type_block_heights(kind, 0, usize::MAX);
+        let mut blocks = Vec::with_capacity(heights.len());
+        for height in heights.into_iter().rev() {
+            blocks.extend(self.storage.get_block_by_height(height)?);
+        }
+        Ok(blocks)
+    }
+
+    /// Every treasury proposal with its tally and status at `now_ms`, oldest first
+    pub fn treasury_proposals(&self, now_ms: u64) -> Result<Vec<crate::treasury::TreasuryProposalInfo>, CompassError> {
+        let spent: std::collections::HashSet<u64> = self
+            .blocks_of_kind("TreasurySpend")?
+            .into_iter()
+            .filter_map(|b| match b.header.block_type {
+                BlockType::TreasurySpend { proposal_id, .. } => Some(proposal_id),
+                _ => None,
+            })
+            .collect();
+        let mut proposals = Vec::new();
+        for block in self.blocks_of_kind("TreasuryProposal")? {
+            let BlockType::TreasuryProposal { id, proposer, recipient, amount, deadline, text } = block.header.block_type else {
+                continue;
+            };
+            let tally = self.tally_until(id, deadline);
+            proposals.push(crate::treasury::TreasuryProposalInfo {
+                status: crate::treasury::ProposalStatus::at(deadline, now_ms, tally, spent.contains(&id)),
+                id,
+                proposer,
+                recipient,
+                amount,
+                deadline,
+                text,
+                yes: tally.yes,
+                no: tally.no,
+            });
+        }
+        Ok(proposals)
+    }
 
-            // 4. Add to Validator List
-            let mut validators = self.storage.get_active_validators().map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            if !validators.contains(validator_id) {
-                validators.push(validator_id.clone());
-                self.storage.set_active_validators(&validators).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-                println!("Validator Registered: {} (Total: {})", validator_id, validators.len());
+    /// Spends due when the head is the first PoH block of a new epoch: passed
+    /// treasury proposals not carried out yet, as far as the treasury can pay them
+    pub fn treasury_spends_due(&self) -> Vec<BlockType> {
+        if self.epoch_just_ended().is_none() {
+            return Vec::new();
+        }
+        let Ok(Some(head)) = self.storage.get_block(self.head_hash.as_deref().unwrap_or_default()) else {
+            return Vec::new();
+        };
+        let Ok(proposals) = self.treasury_proposals(head.header.timestamp) else { return Vec::new() };
+        let mut available = self
+            .storage
+            .get_balance(crate::treasury::TREASURY_ACCOUNT, crate::treasury::TREASURY_ASSET)
+            .unwrap_or(0);
+        let mut due = Vec::new();
+        for p in proposals.into_iter().filter(|p| p.status == crate::treasury::ProposalStatus::Passed) {
+            if p.amount > available {
+                warn!("Treasury proposal #{} passed but the treasury holds {} of its {}", p.id, available, p.amount);
+                continue;
             }
-            
-            // 5. Save Pubkey Mapping
-            self.storage.set_validator_pubkey(validator_id, pubkey).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-            // Note: Validators list already saved above via set_active_validators
+            available -= p.amount;
+            due.push(BlockType::TreasurySpend { proposal_id: p.id, recipient: p.recipient, amount: p.amount });
+        }
+        due
+    }
 
-            let full_block = crate::block::Block {
-                header: header.clone(),
-                transactions: vec![],
+    /// Append a treasury proposal, signed by its proposer's key over the raw hash.
+    /// Its id must be new and its deadline ahead of the block.
+    pub fn append_treasury_proposal(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::TreasuryProposal { id, proposer, amount, deadline, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a treasury proposal block".to_string()));
+        };
+        if *amount == 0 {
+            return Err(CompassError::InvalidState("treasury proposal spends nothing".to_string()));
+        }
+        if *deadline <= header.timestamp {
+            return Err(CompassError::InvalidState("deadline in past".to_string()));
+        }
+        if self.proposal_id_exists(*id) {
+            return Err(CompassError::InvalidState(format!("proposal {} already exists", id)));
+        }
+        let proposer = proposer.clone();
+        self.append_proposal(header, &proposer)
+    }
+
+    /// Append a leader's `TreasurySpend`, signed by `proposer_pubkey_hex`. It must
+    /// carry out a passed proposal, as proposed, exactly once.
+    pub fn append_treasury_spend(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        let BlockType::TreasurySpend { proposal_id, recipient, amount } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a treasury spend block".to_string()));
+        };
+        let proposal = self
+            .treasury_proposals(header.timestamp)?
+            .into_iter()
+            .find(|p| p.id == *proposal_id)
+            .ok_or_else(|| CompassError::InvalidState(format!("no treasury proposal {}", proposal_id)))?;
+        if proposal.status != crate::treasury::ProposalStatus::Passed {
+            return Err(CompassError::InvalidState(format!("treasury proposal {} is {:?}", proposal_id, proposal.status)));
+        }
+        if (&proposal.recipient, proposal.amount) != (recipient, *amount) {
+            return Err(CompassError::InvalidState(format!("spend does not match treasury proposal {}", proposal_id)));
+        }
+
+        self.verify_signed_header(&header, proposer_pubkey_hex)?;
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
+    /// Stability fee rate in force: the configured one until governance changes it
+    pub fn stability_fee_config(&self) -> crate::vault::stability::StabilityFeeConfig {
+        let governed = self.storage.get_stability_fee_rate().unwrap_or_else(|e| {
+            warn!("Governed stability fee unreadable, using the configured rate: {}", e);
+            None
+        });
+        self.stability_fee.governed(governed)
+    }
+
+    /// Every stability fee proposal with its tally and status at `now_ms`, oldest first
+    pub fn stability_fee_proposals(&self, now_ms: u64) -> Result<Vec<crate::vault::stability::StabilityFeeProposalInfo>, CompassError> {
+        let enacted: std::collections::HashSet<u64> = self
+            .blocks_of_kind("StabilityFeeChange")?
+            .into_iter()
+            .filter_map(|b| match b.header.block_type {
+                BlockType::StabilityFeeChange { proposal_id, .. } => Some(proposal_id),
+                _ => None,
+            })
+            .collect();
+        let mut proposals = Vec::new();
+        for block in self.blocks_of_kind("StabilityFeeProposal")? {
+            let BlockType::StabilityFeeProposal { id, proposer, annual_rate_bps, deadline, text } = block.header.block_type else {
+                continue;
             };
-            self.commit_block(full_block)?;
-            Ok(())
-        } else {
-            Err(CompassError::InvalidState("Not a validator registration block".to_string()))
+            let tally = self.tally_until(id, deadline);
+            proposals.push(crate::vault::stability::StabilityFeeProposalInfo {
+                status: crate::treasury::ProposalStatus::at(deadline, now_ms, tally, enacted.contains(&id)),
+                id,
+                proposer,
+                annual_rate_bps,
+                deadline,
+                text,
+                yes: tally.yes,
+                no: tally.no,
+            });
+        }
+        Ok(proposals)
+    }
+
+    /// Rate changes due when the head is the first PoH block of a new epoch:
+    /// passed stability fee proposals not enacted yet, in the order proposed
+    pub fn stability_fee_changes_due(&self) -> Vec<BlockType> {
+        if self.epoch_just_ended().is_none() {
+            return Vec::new();
+        }
+        let Ok(Some(head)) = self.storage.get_block(self.head_hash.as_deref().unwrap_or_default()) else {
+            return Vec::new();
+        };
+        let Ok(proposals) = self.stability_fee_proposals(head.header.timestamp) else { return Vec::new() };
+        proposals
+            .into_iter()
+            .filter(|p| p.status == crate::treasury::ProposalStatus::Passed)
+            .map(|p| BlockType::StabilityFeeChange { proposal_id: p.id, annual_rate_bps: p.annual_rate_bps })
+            .collect()
+    }
+
+    /// Append a stability fee proposal, signed by its proposer's key over the raw
+    /// hash. Its id must be new, its rate at most `MAX_STABILITY_FEE_BPS` and its
+    /// deadline ahead of the block.
+    pub fn append_stability_fee_proposal(&mut self, header: BlockHeader) -> Result<(), CompassError> {
+        let BlockType::StabilityFeeProposal { id, proposer, annual_rate_bps, deadline, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a stability fee proposal block".to_string()));
+        };
+        if *annual_rate_bps > MAX_STABILITY_FEE_BPS {
+            return Err(CompassError::InvalidState(format!(
+                "stability fee of {} bps is above the {} bps cap",
+                annual_rate_bps, MAX_STABILITY_FEE_BPS
+            )));
+        }
+        if *deadline <= header.timestamp {
+            return Err(CompassError::InvalidState("deadline in past".to_string()));
+        }
+        if self.proposal_id_exists(*id) {
+            return Err(CompassError::InvalidState(format!("proposal {} already exists", id)));
+        }
+        let proposer = proposer.clone();
+        self.append_proposal(header, &proposer)
+    }
+
+    /// Append a leader's `StabilityFeeChange`, signed by `proposer_pubkey_hex`. It
+    /// must enact a passed proposal, at its rate, exactly once.
+    pub fn append_stability_fee_change(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        let BlockType::StabilityFeeChange { proposal_id, annual_rate_bps } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a stability fee change block".to_string()));
+        };
+        let proposal = self
+            .stability_fee_proposals(header.timestamp)?
+            .into_iter()
+            .find(|p| p.id == *proposal_id)
+            .ok_or_else(|| CompassError::InvalidState(format!("no stability fee proposal {}", proposal_id)))?;
+        if proposal.status != crate::treasury::ProposalStatus::Passed {
+            return Err(CompassError::InvalidState(format!("stability fee proposal {} is {:?}", proposal_id, proposal.status)));
+        }
+        if proposal.annual_rate_bps != *annual_rate_bps {
+            return Err(CompassError::InvalidState(format!("rate does not match stability fee proposal {}", proposal_id)));
+        }
+
+        self.verify_signed_header(&header, proposer_pubkey_hex)?;
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
+    /// Append a leader's settlement of an order-book fill, signed by
+    /// `proposer_pubkey_hex`. Its fees must be the ones `market_fees` charges, and
+    /// executing it fails unless both sides still hold what the trade moves.
+    pub fn append_trade(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        let BlockType::Trade { maker, taker, base, quote, amount, price, maker_fee, taker_fee, .. } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a trade block".to_string()));
+        };
+        if *amount == 0 {
+            return Err(CompassError::InvalidState("trade moves nothing".to_string()));
         }
+        if base == quote {
+            return Err(CompassError::InvalidState(format!("trade of {} for itself", base)));
+        }
+        let expected = self.market_fees.fees(*price, *amount);
+        if (*maker_fee, *taker_fee) != expected {
+            return Err(CompassError::InvalidState(format!(
+                "trade fees {}/{} don't match the market fee rates ({}/{})",
+                maker_fee, taker_fee, expected.0, expected.1
+            )));
+        }
+        self.ensure_not_frozen(base, &[maker, taker])?;
+        self.ensure_not_frozen(quote, &[maker, taker])?;
+
+        self.verify_signed_header(&header, proposer_pubkey_hex)?;
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
+    /// Append a leader's release of a queued redemption whose payout has
+    /// confirmed on the collateral's chain, signed by `proposer_pubkey_hex`
+    pub fn append_redemption_release(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        let BlockType::RedemptionRelease { intent_id, redeemer, external_tx } = &header.block_type else {
+            return Err(CompassError::InvalidState("not a redemption release block".to_string()));
+        };
+        let intent = self
+            .storage
+            .get_redemption(intent_id)?
+            .ok_or_else(|| CompassError::InvalidState(format!("unknown redemption {}", intent_id)))?;
+        if !intent.is_queued() {
+            return Err(CompassError::InvalidState(format!("redemption {} already released", intent_id)));
+        }
+        if &intent.redeemer != redeemer {
+            return Err(CompassError::InvalidState(format!("redemption {} belongs to {}", intent_id, intent.redeemer)));
+        }
+        if external_tx.is_empty() {
+            return Err(CompassError::InvalidState("release names no payout transaction".to_string()));
+        }
+        if let Some(paid) = self.storage.get_payout_tx_redemption(external_tx)? {
+            return Err(CompassError::InvalidState(format!("payout {} already released redemption {}", external_tx, paid)));
+        }
+
+        self.verify_signed_header(&header, proposer_pubkey_hex)?;
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
     }
 
-    pub fn get_leader(&self, tick: u64) -> Result<String, CompassError> {
-        let validators = self.storage.get_active_validators().map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-        if validators.is_empty() {
-            return Ok("admin".to_string());
+    /// Append a leader-signed Layer 2 checkpoint; its sequence must follow the last one
+    pub fn append_l2_checkpoint(&mut self, header: BlockHeader, proposer_pubkey_hex: &str) -> Result<(), CompassError> {
+        if !matches!(header.block_type, BlockType::L2Checkpoint { .. }) {
+            return Err(CompassError::InvalidState("not a layer 2 checkpoint block".to_string()));
         }
-        // Round Robin
-        let index = (tick as usize) % validators.len();
-        Ok(validators[index].clone())
+        self.verify_signed_header(&header, proposer_pubkey_hex)?;
+        self.execute(&header)?;
+        self.commit_block(crate::block::Block { header, transactions: vec![] })
+    }
+
+    /// Check if a proposal ID already exists
+    pub fn proposal_id_exists(&self, id: u64) -> bool {
+        for i in 0..self.height {
+            if let Ok(Some(block)) = self.storage.get_block_by_height(i) {
+                if let BlockType::Proposal { id: pid, .. }
+                | BlockType::TreasuryProposal { id: pid, .. }
+                | BlockType::StabilityFeeProposal { id: pid, .. } = &block.header.block_type
+                {
+                    if *pid == id {
+                        return true;
+                    }
+                }
+            }
+        }
+        false
+    }
+
+    pub fn calculate_reward_amount(&self) -> u64 {
+        let base_reward = 50_000_000;
+        let halvings = self.height / 10_000;
+        base_reward >> halvings
+    }
+
+    /// Append a Mint block (Vault logic)
+    pub fn append_mint(
+        &mut self,
+        header: BlockHeader,
+        oracle_pubkey_hex: &str, // Admin or Oracle key acting as authority
+    ) -> Result<(), CompassError> {
+        // 1. Check prev_hash
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+
+        // 2. Verify signature
+        let recompute = header.calculate_hash()?;
+        let sig_hex = &header.signature_hex;
+        if sig_hex.is_empty() {
+             return Err(CompassError::InvalidSignature);
+        }
+
+        // Verify user signature on the header first
+        if header.hash != recompute {
+             return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+
+        if let BlockType::Mint {
+            vault_id: _,
+            collateral_asset,
+            collateral_amount,
+            compass_asset: _,
+            mint_amount,
+            owner,
+            tx_proof,
+            oracle_signature,
+            fee: _,
+        } = &header.block_type
+        {
+            self.ensure_oracle_live(collateral_asset)?;
+
+            // 4. The oracle attested the deposit and it hasn't been claimed
+            self.vault_manager
+                .verify_deposit(
+                    collateral_asset,
+                    *collateral_amount,
+                    *mint_amount,
+                    owner,
+                    tx_proof,
+                    oracle_signature,
+                    oracle_pubkey_hex,
+                )
+                .map_err(|e| CompassError::TransactionError(e.to_string()))?;
+
+            // 5. Update the vault, deduct the fee (if any), credit the minted asset
+            self.execute(&header)?;
+
+            let full_block = crate::block::Block {
+                header: header.clone(),
+                transactions: vec![],
+            };
+            self.commit_block(full_block)?;
+            Ok(())
+        } else {
+             Err(CompassError::InvalidState("not a mint block".to_string()))
+        }
+    }
+
+    /// Append a Burn block
+    pub fn append_burn(
+        &mut self,
+        header: BlockHeader,
+        redeemer_pubkey_hex: &str,
+    ) -> Result<(), CompassError> {
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+
+        let recompute = header.calculate_hash()?;
+        let sig_hex = &header.signature_hex;
+        if sig_hex.is_empty() {
+            return Err(CompassError::InvalidSignature);
+        }
+        if !verify_with_pubkey_hex(recompute.as_bytes(), sig_hex, redeemer_pubkey_hex) {
+            return Err(CompassError::InvalidSignature);
+        }
+        if let BlockType::Burn { redeemer, .. } = &header.block_type {
+            if self.storage.signing_key(redeemer)? != redeemer_pubkey_hex {
+                return Err(CompassError::InvalidSignature);
+            }
+        }
+
+        if let BlockType::Burn {
+            vault_id: _,
+            collateral_asset,
+            compass_asset,
+            burn_amount,
+            redeemer,
+            destination_address,
+            fee: _,
+        } = &header.block_type
+        {
+            self.ensure_oracle_live(collateral_asset)?;
+            // Funds locked by open orders can't be burnt
+            let free = self.storage.get_free_balance(redeemer, compass_asset)?;
+            if free < *burn_amount {
+                return Err(CompassError::InvalidState(format!(
+                    "insufficient free {} balance: has {}, needs {}",
+                    compass_asset, free, burn_amount
+                )));
+            }
+
+            let vault = self
+                .vault_manager
+                .vaults
+                .get(compass_asset)
+                .ok_or_else(|| CompassError::InvalidState(format!("no vault for {}", compass_asset)))?;
+            if &vault.collateral_asset != collateral_asset {
+                return Err(CompassError::InvalidState(format!(
+                    "{} is backed by {}, not {}",
+                    compass_asset, vault.collateral_asset, collateral_asset
+                )));
+            }
+            if destination_address.is_empty() {
+                return Err(CompassError::InvalidState("burn has no destination address".to_string()));
+            }
+
+            // Fee, then burn (reduce balance on Chain) and queue the payout; the vault
+            // releases the collateral once the payout confirms
+            let intent = self
+                .execute(&header)?
+                .redemption
+                .ok_or_else(|| CompassError::InvalidState("burn queued no redemption".to_string()))?;
+
+            // Log for external watchers (Bridge)
+            info!("EVENT: Withdrawal Queued. {} {} burnt. Pay {} {} to {} on External Chain (redemption {}).",
+                burn_amount, compass_asset, intent.quote.payout, collateral_asset, destination_address, intent.id);
+
+            let full_block = crate::block::Block {
+                header: header.clone(),
+                transactions: vec![],
+            };
+            self.commit_block(full_block)?;
+            Ok(())
+        } else {
+            Err(CompassError::InvalidState("not a burn block".to_string()))
+        }
+    }
+
+    // 4. Validator Stats
+    pub fn update_validator_stats(&self, validator: &str, reward: u64, block_time_ms: u64) -> Result<(), CompassError> {
+        let mut stats = self.storage.get_validator_stats(validator).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        stats.blocks_produced += 1;
+        stats.compute_earned += reward;
+        // Simple moving average for block time
+        if stats.blocks_produced > 1 {
+            stats.avg_block_time_ms = (stats.avg_block_time_ms + block_time_ms) / 2;
+        } else {
+            stats.avg_block_time_ms = block_time_ms;
+        }
+        // Uptime logic: For now, assuming 1 block = 1 second of uptime approx
+        // better would be tracking timestamps.
+        // Let's just approximate uptime hours based on block count for now (assuming 1 block/sec)
+        // 3600 blocks = 1 hour
+        stats.uptime_hours = stats.blocks_produced / 3600;
+
+        self.storage.set_validator_stats(validator, &stats).map_err(|e| CompassError::DatabaseError(e.to_string()))
+    }
+
+    // 5. Validator Management
+    pub fn append_validator_registration(
+        &mut self,
+        header: BlockHeader,
+    ) -> Result<(), CompassError> {
+        if let Some(head) = self.head_hash() {
+            if header.prev_hash != head {
+                return Err(CompassError::InvalidState("prev_hash mismatch".to_string()));
+            }
+        }
+
+        let recompute = header.calculate_hash()?;
+        if header.hash != recompute {
+            return Err(CompassError::HashMismatch("calculated".to_string(), header.hash.clone()));
+        }
+        
+        // Check Header Sig (Proposer integrity) - Usually same as validator
+        // We skip explicit header sig check here if we trust the payload sig check below, 
+        // but for full security we should check both.
+        // Assuming header.signature_hex is valid for header.proposer.
+
+        if let BlockType::ValidatorRegistration {
+            validator_id,
+            pubkey,
+            stake_amount,
+            signature,
+        } = &header.block_type
+        {
+            // 1. Verify Payload Signature (Proof of possession of Validator Key)
+            // Message signed should be something binding, e.g. "REGISTER:<validator_id>" or just the whole block?
+            // For simplicity, let's say they sign their own validator_id.
+            if !verify_with_pubkey_hex(validator_id.as_bytes(), signature, pubkey) {
+                return Err(CompassError::InvalidSignature);
+            }
+
+            // 2. Check Stake (Compass Balance)
+            let current_bal = self.storage.get_free_balance(validator_id, "Compass").unwrap_or(0);
+            if current_bal < *stake_amount {
+                return Err(CompassError::InvalidState(format!("Insufficient Compass balance for stake. Has {}, needs {}", current_bal, stake_amount)));
+            }
+
+            // 3. Deduct Stake (Lock it), add to the validator list, save the pubkey
+            self.execute(&header)?;
+
+            let full_block = crate::block::Block {
+                header: header.clone(),
+                transactions: vec![],
+            };
+            self.commit_block(full_block)?;
+            Ok(())
+        } else {
+            Err(CompassError::InvalidState("Not a validator registration block".to_string()))
+        }
+    }
+
+    pub fn get_leader(&self, slot: u64) -> Result<String, CompassError> {
+        Ok(self.leader_for_slot(slot)?.unwrap_or_else(|| "admin".to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+    use crate::block::Block;
+    use crate::events::StateEvent;
+    use crate::node::signer::NodeSigner;
+
+    fn poh_header(chain: &Chain, proposer: &KeyPair, tick: u64, timestamp: u64) -> BlockHeader {
+        poh_child(chain.height, &chain.head_hash().unwrap_or_default(), proposer, tick, timestamp)
+    }
+
+    fn poh_child(index: u64, prev_hash: &str, proposer: &KeyPair, tick: u64, timestamp: u64) -> BlockHeader {
+        let mut header = BlockHeader {
+            index,
+            timestamp,
+            prev_hash: prev_hash.to_string(),
+            hash: String::new(),
+            proposer: proposer.public_key_hex(),
+            signature_hex: String::new(),
+            block_type: BlockType::PoH { tick, iterations: 1, hash: String::new(), proof: String::new() },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = proposer.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+        header
+    }
+
+    #[test]
+    fn test_in_memory_chain_appends_poh() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+
+        for tick in 1..=3 {
+            let header = poh_header(&chain, &leader, tick, 1_000_000 + tick * 1000);
+            chain.append_poh(header, &pk).unwrap();
+        }
+        assert_eq!(chain.height, 3);
+
+        // A gap of three slots before the next block is charged to the leader
+        let header = poh_header(&chain, &leader, 4, 1_000_000 + 7000);
+        chain.append_poh(header, &pk).unwrap();
+        let liveness = chain.storage.get_leader_liveness(&pk).unwrap();
+        assert_eq!((liveness.slots_produced, liveness.slots_skipped), (4, 3));
+    }
+
+    #[test]
+    fn test_poh_blocks_mint_halving_capped_rewards_to_their_proposer() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 2;
+        chain.emission = crate::layer2::economics::EmissionSchedule {
+            initial_block_reward: 100,
+            halving_interval_epochs: 1,
+            max_block_emission: 180,
+            max_pouw_emission: 0,
+        };
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+
+        // One block per epoch: 100, 50, 25, then only the 5 left under the cap
+        let mut totals = Vec::new();
+        for tick in 0..5 {
+            let header = poh_header(&chain, &leader, tick, tick * 2000);
+            chain.append_poh(header, &pk).unwrap();
+            totals.push(chain.storage.get_balance(&pk, "Compass").unwrap());
+        }
+        assert_eq!(totals, vec![100, 150, 175, 180, 180]);
+        assert_eq!(chain.storage.get_emitted("Compass").unwrap(), 180);
+
+        // A reorg dropping the last blocks takes their rewards back out of the total
+        chain.storage.revert_events(3, 5).unwrap();
+        assert_eq!(chain.storage.get_emitted("Compass").unwrap(), 175);
+        assert_eq!(chain.storage.get_balance(&pk, "Compass").unwrap(), 175);
+    }
+
+    #[test]
+    fn test_in_memory_chain_rejects_foreign_signer() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let intruder = KeyPair::from_seed("intruder");
+
+        let header = poh_header(&chain, &intruder, 1, 1_000_000);
+        assert!(chain.append_poh(header, &leader.public_key_hex()).is_err());
+        assert_eq!(chain.height, 0);
+    }
+
+    #[test]
+    fn test_apply_tx_indexes_confirmed_and_failed() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let intruder = KeyPair::from_seed("intruder");
+        let pk = leader.public_key_hex();
+
+        let header = poh_header(&chain, &leader, 1, 1_000_000);
+        let block_hash = header.hash.clone();
+        chain.apply_tx("aa", |c| c.append_poh(header, &pk)).unwrap();
+        let confirmed = chain.storage.get_tx_record("aa").unwrap().unwrap();
+        assert_eq!(confirmed.status, TxStatus::Confirmed);
+        assert_eq!((confirmed.block_height, confirmed.block_hash), (Some(0), Some(block_hash)));
+
+        let header = poh_header(&chain, &intruder, 2, 1_001_000);
+        assert!(chain.apply_tx("bb", |c| c.append_poh(header, &pk)).is_err());
+        let failed = chain.storage.get_tx_record("bb").unwrap().unwrap();
+        assert_eq!(failed.status, TxStatus::Failed);
+        assert!(failed.error.is_some() && failed.block_height.is_none());
+    }
+
+    #[test]
+    fn test_executed_transactions_get_receipts() {
+        let mut chain = Chain::new_in_memory();
+        let alice = KeyPair::from_seed("receipt-alice");
+        let from = alice.public_key_hex();
+        chain.storage.writer().set_balance(&from, "Compass", 100).unwrap();
+
+        let mut header = BlockHeader {
+            index: 0,
+            timestamp: 1_000,
+            prev_hash: String::new(),
+            hash: String::new(),
+            proposer: from.clone(),
+            signature_hex: String::new(),
+            block_type: BlockType::Transfer { from: from.clone(), to: "bob".to_string(), asset: "Compass".to_string(), amount: 10, nonce: 1, fee: 2 },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+        chain.apply_tx("aa", |c| c.append_transfer(header, &from)).unwrap();
+
+        let receipt = chain.storage.get_receipt("aa").unwrap().unwrap();
+        assert_eq!((receipt.status, receipt.block_height, receipt.fee_paid), (TxStatus::Confirmed, Some(0), 2));
+        assert!(receipt.logs.iter().any(|e| matches!(
+            e,
+            crate::events::StateEvent::BalanceChanged { account, new: 10, .. } if account == "bob"
+        )));
+
+        chain.record_tx_status("bb", TxStatus::Failed, Some("insufficient funds".to_string()));
+        let failed = chain.storage.get_receipt("bb").unwrap().unwrap();
+        assert_eq!((failed.status, failed.block_height, failed.error.as_deref()), (TxStatus::Failed, None, Some("insufficient funds")));
+
+        // Parked transactions only get one once they run
+        chain.record_tx_status("cc", TxStatus::Pending, None);
+        assert!(chain.storage.get_receipt("cc").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_synced_transfer_updates_follower_state() {
+        let alice = KeyPair::from_seed("sync-alice");
+        let from = alice.public_key_hex();
+        let mut leader = Chain::new_in_memory();
+        let mut follower = Chain::new_in_memory();
+        let mut light = Chain::new_in_memory();
+        light.header_only = true;
+        let validator = KeyPair::from_seed("sync-validator");
+        light.storage.set_validator_pubkey(&validator.public_key_hex(), &validator.public_key_hex()).unwrap();
+        leader.root_signer = Some(Arc::new(NodeSigner::Local(Arc::new(validator))));
+        for chain in [&leader, &follower, &light] {
+            chain.storage.writer().set_balance(&from, "Compass", 100).unwrap();
+        }
+
+        let mut header = BlockHeader {
+            index: 0,
+            timestamp: 1_000,
+            prev_hash: String::new(),
+            hash: String::new(),
+            proposer: from.clone(),
+            signature_hex: String::new(),
+            block_type: BlockType::Transfer { from: from.clone(), to: "bob".to_string(), asset: "Compass".to_string(), amount: 10, nonce: 1, fee: 2 },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+        // Funds an open order holds in escrow can't be sent; the escrow stays out of the state root
+        let escrow = crate::market::OrderEscrow { order_id: 1, account: from.clone(), asset: "Compass".to_string(), amount: 95 };
+        leader.storage.lock_escrow(&escrow).unwrap();
+        assert!(leader.append_transfer(header.clone(), &from).is_err());
+        assert_eq!(leader.storage.release_escrow(1, 90).unwrap(), 90);
+        leader.append_transfer(header, &from).unwrap();
+
+        let block = leader.storage.get_block_by_height(0).unwrap().unwrap();
+        follower.sync_block(block.clone()).unwrap();
+        assert_eq!(follower.storage.get_balance("bob", "Compass").unwrap(), 10);
+        assert_eq!(follower.storage.get_balance(&from, "Compass").unwrap(), 88);
+        assert_eq!(follower.storage.get_nonce(&from).unwrap(), 1);
+        assert_eq!(follower.storage.state_root().unwrap(), block.header.state_root);
+        // Already applied: syncing it again changes nothing
+        follower.sync_block(block.clone()).unwrap();
+        assert_eq!(follower.storage.get_balance("bob", "Compass").unwrap(), 10);
+
+        light.sync_block(block).unwrap();
+        assert_eq!((light.height, light.storage.get_balance("bob", "Compass").unwrap()), (1, 0));
+    }
+
+    #[test]
+    fn test_fees_meet_schedule_and_go_to_leader() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("fee-leader");
+        let alice = KeyPair::from_seed("fee-alice");
+        let from = alice.public_key_hex();
+        chain.storage.writer().set_balance(&from, "Compass", 1_000).unwrap();
+        chain.fee_schedule.base_fees.insert("Transfer".to_string(), 50);
+
+        let transfer = |chain: &Chain, fee: u64| {
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 2_000_000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: from.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::Transfer { from: from.clone(), to: "bob".to_string(), asset: "Compass".to_string(), amount: 10, nonce: 1, fee },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000_000), &leader.public_key_hex()).unwrap();
+        assert!(chain.append_transfer(transfer(&chain, 49), &from).is_err());
+        chain.append_transfer(transfer(&chain, 60), &from).unwrap();
+
+        assert_eq!(chain.storage.get_balance(&leader.public_key_hex(), "Compass").unwrap(), 60);
+        assert_eq!(chain.storage.get_balance(&from, "Compass").unwrap(), 1_000 - 70);
+    }
+
+    #[test]
+    fn test_sponsored_fee_payer_key_must_control_the_payer() {
+        let mut chain = Chain::new_in_memory();
+        let (alice, sponsor, thief) = (KeyPair::from_seed("sp-alice"), KeyPair::from_seed("sp-sponsor"), KeyPair::from_seed("sp-thief"));
+        let (from, payer) = (alice.public_key_hex(), sponsor.public_key_hex());
+        chain.storage.writer().set_balance(&from, "COMPUTE", 100).unwrap();
+        chain.storage.writer().set_balance(&payer, "Compass", 1_000).unwrap();
+
+        let sponsored = |chain: &Chain, payer_key: &KeyPair| {
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 1_000_000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: from.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::SponsoredTransfer {
+                    from: from.clone(),
+                    to: "bob".to_string(),
+                    asset: "COMPUTE".to_string(),
+                    amount: 10,
+                    nonce: 1,
+                    fee: 5,
+                    fee_payer: payer.clone(),
+                    fee_payer_signature: payer_key
+                        .sign_hex(crate::block::sponsor_message(&from, "bob", "COMPUTE", 10, 1, 5, &payer).as_bytes()),
+                },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        // Signing with your own key can't spend someone else's Compass on fees
+        let forged = sponsored(&chain, &thief);
+        assert!(chain.append_sponsored_transfer(forged, &from, &thief.public_key_hex()).is_err());
+        assert_eq!(chain.storage.get_balance(&payer, "Compass").unwrap(), 1_000);
+
+        chain.append_sponsored_transfer(sponsored(&chain, &sponsor), &from, &payer).unwrap();
+        assert_eq!(chain.storage.get_balance(&payer, "Compass").unwrap(), 995);
+        assert_eq!(chain.storage.get_balance("bob", "COMPUTE").unwrap(), 10);
+    }
+
+    #[test]
+    fn test_asset_fee_transfer_converts_through_pool() {
+        let mut chain = Chain::new_in_memory();
+        let worker = KeyPair::from_seed("worker");
+        let from = worker.public_key_hex();
+        chain.storage.writer().set_balance(&from, "COMPUTE", 1_000).unwrap();
+        chain.storage.writer().set_balance("foundation", "COMPUTE", 2_000_000).unwrap();
+        chain.storage.writer().set_balance("foundation", "Compass", 1_000_000).unwrap();
+        chain.seed_fee_pool("COMPUTE", 2_000_000, 1_000_000).unwrap();
+        let now = crate::block::current_unix_timestamp_ms() / 1000;
+        chain.vault_manager.oracle_prices.insert("COMPUTE".to_string(), (rust_decimal::Decimal::new(5, 1), now));
+
+        let transfer = |chain: &Chain, max_fee_in: u64| {
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 1_000_000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: from.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::AssetFeeTransfer {
+                    from: from.clone(),
+                    to: "bob".to_string(),
+                    asset: "COMPUTE".to_string(),
+                    amount: 500,
+                    nonce: 1,
+                    fee: 100,
+                    fee_asset: "COMPUTE".to_string(),
+                    max_fee_in,
+                },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = worker.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        // 100 Compass at 0.5 Compass/COMPUTE plus 2% is 204 COMPUTE; a lower cap refuses it
+        assert!(chain.append_asset_fee_transfer(transfer(&chain, 203), &from).is_err());
+        assert_eq!(chain.storage.get_balance(&from, "COMPUTE").unwrap(), 1_000);
+
+        chain.append_asset_fee_transfer(transfer(&chain, 204), &from).unwrap();
+        assert_eq!(chain.storage.get_balance(&from, "COMPUTE").unwrap(), 1_000 - 204 - 500);
+        assert_eq!(chain.storage.get_balance("bob", "COMPUTE").unwrap(), 500);
+        let pool = chain.storage.get_amm_pool("COMPUTE").unwrap().unwrap();
+        let foundation = chain.storage.get_balance("foundation", "Compass").unwrap();
+        assert!(foundation >= 100);
+        assert_eq!(pool.compass_reserve + foundation, 1_000_000);
+        assert_eq!(chain.storage.get_nonce(&from).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_stale_oracle_price_blocks_fee_conversion() {
+        let mut chain = Chain::new_in_memory();
+        let now = crate::block::current_unix_timestamp_ms() / 1000;
+        chain.vault_manager.publish_price("COMPUTE", rust_decimal::Decimal::new(5, 1), now);
+        assert_eq!(chain.fee_conversion_rate("COMPUTE").unwrap(), rust_decimal::Decimal::new(5, 1));
+
+        // +20% in one update is held until a follow-up confirms it
+        chain.vault_manager.publish_price("COMPUTE", rust_decimal::Decimal::new(6, 1), now);
+        let err = chain.fee_conversion_rate("COMPUTE").unwrap_err().to_string();
+        assert!(err.contains("stale"), "{}", err);
+        assert!(chain.ensure_oracle_live("COMPUTE").is_err());
+        chain.vault_manager.publish_price("COMPUTE", rust_decimal::Decimal::new(61, 2), now);
+        assert!(chain.ensure_oracle_live("COMPUTE").is_ok());
+
+        // An old price is stale however small its last move
+        chain.vault_manager.publish_price("COMPUTE", rust_decimal::Decimal::new(61, 2), now - 7_200);
+        assert!(chain.fee_conversion_rate("COMPUTE").is_err());
+        let status = chain.vault_manager.price_status("COMPUTE", now, &chain.oracle_guard).unwrap();
+        assert!(matches!(status.stale, Some(crate::oracle::freshness::Staleness::TooOld { .. })));
+    }
+
+    #[test]
+    fn test_multisig_transfer_needs_threshold() {
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        let keys: Vec<KeyPair> = ["s1", "s2", "s3"].iter().map(|s| KeyPair::from_seed(s)).collect();
+        let (mut chain, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for c in [&mut chain, &mut follower] {
+            c.create_multisig_account("team", 2, keys.iter().map(|k| k.public_key_hex()).collect()).unwrap();
+            c.storage.writer().set_balance("team", "Compass", 1_000).unwrap();
+            c.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        }
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000), &pk).unwrap();
+        follower.sync_block(chain.storage.get_block_by_height(0).unwrap().unwrap()).unwrap();
+
+        let message = crate::account::multisig::approval_message("team", "bob", "Compass", 400, 1);
+        let transfer = |chain: &Chain, signers: &[&KeyPair]| {
+            let approvals = signers
+                .iter()
+                .map(|k| (k.public_key_hex(), k.sign_hex(message.as_bytes())))
+                .collect();
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 1_000_000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: "team".to_string(),
+                signature_hex: String::new(),
+                block_type: BlockType::MultisigTransfer {
+                    from: "team".to_string(),
+                    to: "bob".to_string(),
+                    asset: "Compass".to_string(),
+                    amount: 400,
+                    nonce: 1,
+                    approvals,
+                },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        };
+
+        assert!(chain.append_multisig_transfer(transfer(&chain, &[&keys[0]])).is_err());
+        // A relayed block short of the threshold is refused too
+        let short = crate::block::Block { header: transfer(&follower, &[&keys[0]]), transactions: vec![] };
+        assert!(follower.sync_block(short).is_err());
+        assert_eq!(follower.storage.get_balance("team", "Compass").unwrap(), 1_000);
+        chain.append_multisig_transfer(transfer(&chain, &[&keys[0], &keys[2]])).unwrap();
+        assert_eq!(chain.storage.get_balance("team", "Compass").unwrap(), 600);
+        assert_eq!(chain.storage.get_balance("bob", "Compass").unwrap(), 400);
+        // The same approvals cannot be replayed
+        assert!(chain.append_multisig_transfer(transfer(&chain, &[&keys[0], &keys[2]])).is_err());
+    }
+
+    #[test]
+    fn test_state_root_stamped_and_divergence_detected() {
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        let mut ours = Chain::new_in_memory();
+        let mut theirs = Chain::new_in_memory();
+        for chain in [&mut ours, &mut theirs] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.append_poh(poh_header(chain, &leader, 1, 1_000_000), &pk).unwrap();
+        }
+        let genesis = ours.storage.get_block_by_height(0).unwrap().unwrap();
+        assert_eq!(genesis.header.state_root, ours.state_root().unwrap());
+
+        // Same block, executed on top of different balances
+        ours.storage.writer().set_balance("alice", "Compass", 10).unwrap();
+        theirs.storage.writer().set_balance("alice", "Compass", 11).unwrap();
+        let next = poh_header(&ours, &leader, 2, 1_001_000);
+        ours.append_poh(next.clone(), &pk).unwrap();
+        theirs.append_poh(next.clone(), &pk).unwrap();
+
+        let local = ours.storage.get_block(&next.hash).unwrap().unwrap();
+        let peer = theirs.storage.get_block(&next.hash).unwrap().unwrap();
+        assert_eq!(local.header.hash, peer.header.hash);
+        assert_ne!(local.header.state_root, peer.header.state_root);
+        assert!(ours.sync_block(peer).is_err());
+        assert!(ours.sync_block(local).is_ok());
+
+        // A balance set back to zero hashes like one never written
+        let before = ours.state_root().unwrap();
+        ours.storage.writer().set_balance("carol", "Compass", 5).unwrap();
+        assert_ne!(ours.state_root().unwrap(), before);
+        ours.storage.writer().set_balance("carol", "Compass", 0).unwrap();
+        assert_eq!(ours.state_root().unwrap(), before);
+    }
+
+    #[test]
+    fn test_settlement_moves_balances_on_followers_too() {
+        use crate::block::SettlementLeg;
+
+        let leader_key = KeyPair::from_seed("leader");
+        let pk = leader_key.public_key_hex();
+        let mut leader = Chain::new_in_memory();
+        let mut follower = Chain::new_in_memory();
+        for chain in [&leader, &follower] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.storage.writer().set_balance("alice", "COMPASS", 50).unwrap();
+        }
+        leader.append_poh(poh_header(&leader, &leader_key, 1, 1000), &pk).unwrap();
+        follower.sync_block(leader.get_blocks_range(0, 0).remove(0)).unwrap();
+
+        let legs = vec![
+            SettlementLeg::Transfer { from: "alice".into(), to: "bob".into(), asset: "COMPASS".into(), amount: 20 },
+            SettlementLeg::Debit { account: "alice".into(), asset: "COMPASS".into(), amount: 5 },
+        ];
+        // Only a node with a key can settle
+        assert!(leader.settle(legs.clone(), "rent").is_err());
+        leader.root_signer = Some(Arc::new(NodeSigner::Local(Arc::new(KeyPair::from_seed("leader")))));
+        let overdrawn = vec![SettlementLeg::Debit { account: "alice".into(), asset: "COMPASS".into(), amount: 51 }];
+        assert!(leader.settle(overdrawn, "rent").is_err());
+        leader.settle(legs, "rent").unwrap();
+        assert_eq!(leader.storage.get_balance("alice", "COMPASS").unwrap(), 25);
+
+        let block = leader.storage.get_block_by_height(1).unwrap().unwrap();
+        assert_eq!(block.header.block_type.kind(), "Settlement");
+        follower.sync_block(block).unwrap();
+        assert_eq!(follower.storage.get_balance("alice", "COMPASS").unwrap(), 25);
+        assert_eq!(follower.storage.get_balance("bob", "COMPASS").unwrap(), 20);
+        assert_eq!(follower.state_root().unwrap(), leader.state_root().unwrap());
+    }
+
+    #[test]
+    fn test_freeze_authority_is_assigned_on_chain_and_checked_on_sync() {
+        let leader_key = KeyPair::from_seed("leader");
+        let (issuer, mallory) = (KeyPair::from_seed("issuer"), KeyPair::from_seed("mallory"));
+        let pk = leader_key.public_key_hex();
+        let asset = "Compass:issuer:LTC".to_string();
+        let (mut leader, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for chain in [&mut leader, &mut follower] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.vault_manager.vaults.insert(asset.clone(), crate::vault::Vault {
+                collateral_asset: "LTC".to_string(),
+                compass_asset: asset.clone(),
+                vault_address: String::new(),
+                exchange_rate: rust_decimal::Decimal::ONE,
+                collateral_balance: 0,
+                minted_supply: 0,
+                accumulated_fees: 0,
+                mint_fee_rate: rust_decimal::Decimal::ZERO,
+                redeem_fee_rate: rust_decimal::Decimal::ZERO,
+                derivation_path: String::new(),
+            });
+        }
+        leader.append_poh(poh_header(&leader, &leader_key, 1, 1_000), &pk).unwrap();
+        leader.root_signer = Some(Arc::new(NodeSigner::Local(Arc::new(KeyPair::from_seed("leader")))));
+        leader.set_freeze_authority(&asset, &issuer.public_key_hex()).unwrap();
+
+        let freeze = |chain: &Chain, signer: &KeyPair| {
+            let mut header = poh_header(chain, signer, 0, 2_000);
+            header.block_type = BlockType::AssetFreeze {
+                compass_asset: asset.clone(),
+                account: "bob".to_string(),
+                frozen: true,
+                authority: signer.public_key_hex(),
+                reason: "court order".to_string(),
+                seq: 0,
+                authority_signature: signer.sign_hex(crate::vault::compliance::freeze_message(&asset, "bob", true, 0).as_bytes()),
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        };
+        // Anyone can sign a freeze for themselves, but only the assigned key counts
+        assert!(leader.append_asset_freeze(freeze(&leader, &mallory)).is_err());
+        leader.append_asset_freeze(freeze(&leader, &issuer)).unwrap();
+        assert!(leader.storage.is_frozen(&asset, "bob"));
+
+        for block in leader.get_blocks_range(0, 2) {
+            follower.sync_block(block).unwrap();
+        }
+        assert_eq!(follower.storage.get_freeze_authority(&asset).unwrap(), Some(issuer.public_key_hex()));
+        assert!(follower.storage.is_frozen(&asset, "bob"));
+
+        // A relayed freeze by a key the chain never assigned is refused
+        let mut forged = freeze(&follower, &mallory);
+        if let BlockType::AssetFreeze { seq, authority_signature, .. } = &mut forged.block_type {
+            *seq = 1;
+            *authority_signature = mallory.sign_hex(crate::vault::compliance::freeze_message(&asset, "bob", true, 1).as_bytes());
+        }
+        forged.hash = forged.calculate_hash().unwrap();
+        assert!(follower.sync_block(crate::block::Block { header: forged, transactions: vec![] }).is_err());
+        assert_eq!(follower.storage.get_freeze_seq(&asset), 1);
+    }
+
+    #[test]
+    fn test_state_root_follows_writes_like_a_full_rescan() {
+        fn rescanned(chain: &Chain) -> String {
+            let leaves: Vec<_> = chain.storage.state_leaves().unwrap().iter().map(|(k, v)| crate::merkle::leaf_hash(k, v)).collect();
+            encoding::hash_to_hex(&crate::merkle::merkle_root(&leaves))
+        }
+
+        let chain = Chain::new_in_memory();
+        assert_eq!(chain.state_root().unwrap(), rescanned(&chain));
+        chain.storage.writer().set_balance("alice", "Compass", 10).unwrap();
+        chain.storage.writer().set_balance("bob", "Compass", 20).unwrap();
+        assert_eq!(chain.state_root().unwrap(), rescanned(&chain));
+
+        // Changed values, a new key, a key zeroed out, then a rollback of all of it
+        chain.storage.set_event_height(1);
+        chain.storage.writer().batch().transfer("alice", "bob", "Compass", 4).set_nonce("alice", 1).commit().unwrap();
+        assert_eq!(chain.state_root().unwrap(), rescanned(&chain));
+        chain.storage.writer().transfer("alice", "carol", "Compass", 6).unwrap();
+        assert_eq!(chain.state_root().unwrap(), rescanned(&chain));
+        chain.storage.revert_events(1, 2).unwrap();
+        assert_eq!(chain.state_root().unwrap(), rescanned(&chain));
+        assert_eq!(chain.storage.get_balance("alice", "Compass").unwrap(), 10);
+    }
+
+    #[test]
+    fn test_relayed_state_root_must_match_execution_and_attestation() {
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        let mut source = Chain::new_in_memory();
+        let mut full = Chain::new_in_memory();
+        let mut light = Chain::new_in_memory();
+        light.header_only = true;
+        for chain in [&mut source, &mut full, &mut light] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        }
+        source.root_signer = Some(Arc::new(NodeSigner::Local(Arc::new(KeyPair::from_seed("leader")))));
+        source.append_poh(poh_header(&source, &leader, 1, 1_000_000), &pk).unwrap();
+        source.append_poh(poh_header(&source, &leader, 2, 1_001_000), &pk).unwrap();
+        let blocks = source.get_blocks_range(0, 1);
+        full.sync_block(blocks[0].clone()).unwrap();
+        light.sync_block(blocks[0].clone()).unwrap();
+
+        // A relay swaps the root; the hash and proposer signature still check out
+        let mut forged = blocks[1].clone();
+        forged.header.state_root = "ab".repeat(32);
+        assert!(full.sync_block(forged.clone()).is_err());
+        assert!(light.sync_block(forged.clone()).is_err());
+        assert_eq!((full.height, light.height), (1, 1));
+
+        // Without the attestation, a full node still recomputes the root and a light node has no root to trust
+        forged.header.root_attestation = None;
+        assert!(full.sync_block(forged.clone()).is_err());
+        assert!(light.sync_block(forged).is_err());
+        assert!(full.storage.get_block_by_height(1).unwrap().is_none());
+
+        full.sync_block(blocks[1].clone()).unwrap();
+        light.sync_block(blocks[1].clone()).unwrap();
+        assert_eq!((full.height, light.height), (2, 2));
+    }
+
+    #[test]
+    fn test_light_client_verifies_balance_proof_against_header() {
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        let mut full = Chain::new_in_memory();
+        let mut light = Chain::new_in_memory();
+        light.header_only = true;
+        full.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        light.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        full.root_signer = Some(Arc::new(NodeSigner::Local(Arc::new(KeyPair::from_seed("leader")))));
+
+        full.append_poh(poh_header(&full, &leader, 1, 1_000_000), &pk).unwrap();
+        full.storage.writer().set_balance("alice", "Compass", 500).unwrap();
+        full.storage.writer().set_balance("bob", "Compass", 20).unwrap();
+        full.storage.writer().set_nonce("alice", 3).unwrap();
+        full.append_poh(poh_header(&full, &leader, 2, 1_001_000), &pk).unwrap();
+
+        // The light chain holds headers only and no balances
+        for block in full.get_blocks_range(0, 1) {
+            light.sync_block(Block { header: block.header, transactions: vec![] }).unwrap();
+        }
+        assert_eq!(light.height, 2);
+        assert_eq!(light.storage.get_balance("alice", "Compass").unwrap(), 0);
+
+        let proof = full.balance_proof("alice", "Compass").unwrap().unwrap();
+        assert_eq!(proof.balance(), Some(500));
+        light.verify_state_proof(&proof, 1).unwrap();
+        // Block 0 was stamped before the balances existed
+        assert!(light.verify_state_proof(&proof, 0).is_err());
+
+        let mut forged = proof.clone();
+        forged.value = hex::encode(5_000u64.to_be_bytes());
+        assert!(light.verify_state_proof(&forged, 1).is_err());
+        assert!(full.balance_proof("carol", "Compass").unwrap().is_none());
+    }
+
+    #[test]
+    fn test_snapshot_restores_state_on_fresh_chain() {
+        let mut source = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        source.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        source.append_poh(poh_header(&source, &leader, 1, 1_000_000), &pk).unwrap();
+        source.storage.writer().set_balance("alice", "Compass", 750).unwrap();
+        source.storage.writer().set_nonce("alice", 4).unwrap();
+        source.append_poh(poh_header(&source, &leader, 2, 1_001_000), &pk).unwrap();
+
+        let manifest = source.take_snapshot().unwrap();
+        assert_eq!(source.storage.latest_snapshot().unwrap().height, 1);
+        let chunks: Vec<_> = (0..manifest.chunk_hashes.len() as u32)
+            .map(|i| source.storage.get_snapshot_chunk(manifest.height, i).unwrap().unwrap())
+            .collect();
+
+        let mut fresh = Chain::new_in_memory();
+        fresh.storage.set_validator_pubkey(&pk, &pk).unwrap();
+
+        // A tampered chunk is refused before anything is written
+        let mut tampered = chunks.clone();
+        tampered[0].push(("bal:mallory:Compass".to_string(), 1_000_000u64.to_le_bytes().to_vec()));
+        assert!(fresh.restore_snapshot(&manifest, &tampered).is_err());
+        assert_eq!(fresh.height, 0);
+
+        fresh.restore_snapshot(&manifest, &chunks).unwrap();
+        assert_eq!((fresh.height, fresh.head_hash()), (2, source.head_hash()));
+        assert_eq!(fresh.storage.get_balance("alice", "Compass").unwrap(), 750);
+        assert_eq!(fresh.storage.get_nonce("alice").unwrap(), 4);
+
+        // Blocks after the snapshot sync on top of it
+        let next = poh_header(&source, &leader, 3, 1_002_000);
+        fresh.sync_block(Block { header: next, transactions: vec![] }).unwrap();
+        assert_eq!(fresh.height, 3);
+    }
+
+    #[test]
+    fn test_pruning_keeps_recent_blocks_and_stops_at_snapshot() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        for tick in 1..=10 {
+            chain.append_poh(poh_header(&chain, &leader, tick, 1_000_000 + tick * 1000), &pk).unwrap();
+        }
+        chain.storage.writer().set_balance("alice", "Compass", 5).unwrap();
+
+        // Keeping 4 of 10 drops heights 1..6; genesis and state stay
+        assert_eq!(chain.prune(4).unwrap(), 5);
+        assert_eq!(chain.storage.pruned_below(), 6);
+        assert!(chain.storage.get_block_by_height(0).unwrap().is_some());
+        assert!(chain.storage.get_block_by_height(5).unwrap().is_none());
+        assert!(chain.storage.get_block_by_height(6).unwrap().is_some());
+        assert_eq!(chain.storage.get_balance("alice", "Compass").unwrap(), 5);
+        assert_eq!(chain.prune(4).unwrap(), 0);
+
+        // A snapshot holds pruning back so peers can sync from it
+        chain.storage.create_snapshot(&chain.storage.get_block_by_height(7).unwrap().unwrap()).unwrap();
+        assert_eq!(chain.prune(0).unwrap(), 1);
+        assert!(chain.storage.get_block_by_height(7).unwrap().is_some());
+    }
+
+    /// Set in the child process `test_committed_blocks_survive_a_crash` spawns
+    const CRASH_CHILD_DIR: &str = "COMPASS_CRASH_CHILD_DIR";
+
+    fn open_on_disk(path: &std::path::Path, every_blocks: u64) -> Chain {
+        let policy = crate::storage::FlushPolicy { every_blocks, ..Default::default() };
+        let storage = Arc::new(Storage::new(path.to_str().unwrap()).unwrap().with_flush_policy(policy));
+        Chain::open(storage.clone(), VaultManager::new_with_storage(storage))
+    }
+
+    #[test]
+    fn test_committed_blocks_survive_a_crash() {
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        if let Ok(dir) = std::env::var(CRASH_CHILD_DIR) {
+            // Child: note each block as committed once append returns, then die
+            // without any shutdown or final flush
+            let dir = std::path::PathBuf::from(dir);
+            let mut chain = open_on_disk(&dir.join("db"), 1);
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            for tick in 1..=25 {
+                chain.append_poh(poh_header(&chain, &leader, tick, 1_000_000 + tick * 1000), &pk).unwrap();
+                std::fs::write(dir.join("committed"), chain.height.to_string()).unwrap();
+            }
+            std::process::abort();
+        }
+
+        let dir = std::env::temp_dir().join(format!("compass-crash-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let status = std::process::Command::new(std::env::current_exe().unwrap())
+            .args(["--exact", "chain::tests::test_committed_blocks_survive_a_crash", "--test-threads=1"])
+            .env(CRASH_CHILD_DIR, &dir)
+            .stdout(std::process::Stdio::null())
+            .stderr(std::process::Stdio::null())
+            .status()
+            .unwrap();
+        assert!(!status.success(), "the child should have crashed");
+        let committed: u64 = std::fs::read_to_string(dir.join("committed")).unwrap().parse().unwrap();
+        assert_eq!(committed, 25);
+
+        // Every block the child saw committed is back, linked up to the head
+        let chain = open_on_disk(&dir.join("db"), 1);
+        assert!(chain.height >= committed);
+        let mut prev_hash = String::new();
+        for height in 0..chain.height {
+            let block = chain.storage.get_block_by_height(height).unwrap().expect("committed block lost");
+            if height > 0 {
+                assert_eq!(block.header.prev_hash, prev_hash);
+            }
+            prev_hash = block.header.hash;
+        }
+        assert_eq!(chain.head_hash(), Some(prev_hash));
+        drop(chain);
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_sync_block_reorgs_to_longer_fork() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+
+        let genesis = poh_header(&chain, &leader, 1, 1_000_000);
+        let genesis_hash = genesis.hash.clone();
+        chain.append_poh(genesis, &pk).unwrap();
+
+        // Block 1 on the current branch moves a balance and a nonce
+        chain.storage.writer().set_balance("alice", "Compass", 50).unwrap();
+        chain.storage.writer().set_nonce("alice", 1).unwrap();
+        let a1 = poh_header(&chain, &leader, 2, 1_001_000);
+        let a1_hash = a1.hash.clone();
+        chain.append_poh(a1, &pk).unwrap();
+
+        // Same height, same tick: kept as a side branch
+        let b1 = poh_child(1, &genesis_hash, &leader, 2, 1_001_500);
+        chain.sync_block(Block { header: b1.clone(), transactions: vec![] }).unwrap();
+        assert_eq!(chain.head_hash(), Some(a1_hash.clone()));
+
+        // The side branch grows past the head and takes over
+        let b2 = poh_child(2, &b1.hash, &leader, 3, 1_002_000);
+        chain.sync_block(Block { header: b2.clone(), transactions: vec![] }).unwrap();
+        assert_eq!((chain.head_hash(), chain.height), (Some(b2.hash.clone()), 3));
+        assert_eq!(chain.storage.get_block_by_height(1).unwrap().unwrap().header.hash, b1.hash);
+        assert_eq!(chain.storage.get_balance("alice", "Compass").unwrap(), 0);
+        assert_eq!(chain.storage.get_nonce("alice").unwrap(), 0);
+        assert_eq!(chain.storage.get_fork_tips(), vec![(a1_hash, 1)]);
+        assert_eq!(chain.head_weight(), ChainWeight { height: 3, scheduled_leader: true, poh_tick: 3 });
+    }
+
+    #[test]
+    fn test_failed_reorg_keeps_the_old_branch_and_a_good_one_abandons_its_transfer() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let alice = KeyPair::from_seed("alice");
+        let (pk, alice_pk) = (leader.public_key_hex(), alice.public_key_hex());
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        chain.storage.writer().set_balance(&alice_pk, "Compass", 1_000).unwrap();
+        let transfer = |index: u64, prev_hash: &str, to: &str, amount: u64| {
+            let mut header = BlockHeader {
+                index,
+                timestamp: 1_001_000,
+                prev_hash: prev_hash.to_string(),
+                hash: String::new(),
+                proposer: alice_pk.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::Transfer { from: alice_pk.clone(), to: to.to_string(), asset: "Compass".to_string(), amount, nonce: 1, fee: 10 },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        let genesis = poh_header(&chain, &leader, 1, 1_000_000);
+        let genesis_hash = genesis.hash.clone();
+        chain.append_poh(genesis, &pk).unwrap();
+        let paid = transfer(1, &genesis_hash, "bob", 100);
+        chain.append_transfer(paid.clone(), &alice_pk).unwrap();
+        let head = chain.head_hash();
+        let root = chain.state_root().unwrap();
+
+        // A longer branch whose transfer alice can't cover fails part way and changes nothing
+        let overdrawn = transfer(1, &genesis_hash, "carol", 5_000);
+        chain.sync_block(Block { header: overdrawn.clone(), transactions: vec![] }).unwrap();
+        let b2 = poh_child(2, &overdrawn.hash, &leader, 3, 1_002_000);
+        assert!(chain.sync_block(Block { header: b2, transactions: vec![] }).is_err());
+        assert_eq!((chain.head_hash(), chain.height), (head, 2));
+        assert_eq!(chain.state_root().unwrap(), root);
+        assert_eq!(chain.storage.get_balance("bob", "Compass").unwrap(), 100);
+        assert_eq!(chain.storage.get_block_by_height(1).unwrap().unwrap().header.hash, paid.hash);
+
+        // A valid longer branch drops the transfer: bob's payment and alice's nonce roll back
+        let c1 = poh_child(1, &genesis_hash, &leader, 2, 1_001_500);
+        chain.sync_block(Block { header: c1.clone(), transactions: vec![] }).unwrap();
+        let c2 = poh_child(2, &c1.hash, &leader, 3, 1_002_500);
+        chain.sync_block(Block { header: c2.clone(), transactions: vec![] }).unwrap();
+        assert_eq!((chain.head_hash(), chain.height), (Some(c2.hash), 3));
+        assert_eq!(chain.storage.get_balance("bob", "Compass").unwrap(), 0);
+        assert_eq!(chain.storage.get_balance(&alice_pk, "Compass").unwrap(), 1_000);
+        assert_eq!(chain.storage.get_nonce(&alice_pk).unwrap(), 0);
+        assert!(chain.storage.get_fork_tips().contains(&(paid.hash, 1)));
+    }
+
+    #[test]
+    fn test_leader_follower_and_reorg_execute_alike() {
+        let leader = KeyPair::from_seed("leader");
+        let (alice, oracle) = (KeyPair::from_seed("alice"), KeyPair::from_seed("oracle"));
+        let (pk, alice_pk) = (leader.public_key_hex(), alice.public_key_hex());
+        let fresh = || {
+            let chain = Chain::new_in_memory();
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.storage.writer().set_balance(&alice_pk, "Compass", 1_000).unwrap();
+            chain
+        };
+        let signed = |chain: &Chain, block_type: BlockType| {
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 1_000_000 + chain.height * 1000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: alice_pk.clone(),
+                signature_hex: String::new(),
+                block_type,
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = alice.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        // Leader: genesis tick, a transfer paying the leader a fee, a vault mint, a tick
+        let mut live = fresh();
+        live.append_poh(poh_header(&live, &leader, 1, 1_000_000), &pk).unwrap();
+        let transfer = BlockType::Transfer {
+            from: alice_pk.clone(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount: 100,
+            nonce: 1,
+            fee: 10,
+        };
+        live.append_transfer(signed(&live, transfer), &alice_pk).unwrap();
+        let deposit = format!("DEPOSIT:LTC:1000:ltc-tx:500:{}", alice_pk);
+        let mint = BlockType::Mint {
+            vault_id: "v1".to_string(),
+            collateral_asset: "LTC".to_string(),
+            collateral_amount: 1_000,
+            compass_asset: String::new(),
+            mint_amount: 500,
+            owner: alice_pk.clone(),
+            tx_proof: "ltc-tx".to_string(),
+            oracle_signature: oracle.sign_hex(deposit.as_bytes()),
+            fee: 0,
+        };
+        live.append_mint(signed(&live, mint), &oracle.public_key_hex()).unwrap();
+        live.append_poh(poh_header(&live, &leader, 3, 1_004_000), &pk).unwrap();
+        let blocks: Vec<Block> = (0..live.height).map(|h| live.storage.get_block_by_height(h).unwrap().unwrap()).collect();
+
+        // Follower: syncs the blocks in order
+        let mut follower = fresh();
+        for block in &blocks {
+            follower.sync_block(block.clone()).unwrap();
+        }
+
+        // Reorg: first on its own branch at height 1, then switches to the leader's
+        let mut reorged = fresh();
+        reorged.sync_block(blocks[0].clone()).unwrap();
+        reorged.append_poh(poh_header(&reorged, &leader, 2, 1_001_500), &pk).unwrap();
+        for block in &blocks[1..] {
+            reorged.sync_block(block.clone()).unwrap();
+        }
+        assert_eq!(reorged.head_hash(), live.head_hash());
+
+        let vault = crate::vault::vault_asset_name(&alice_pk, "LTC");
+        for chain in [&follower, &reorged] {
+            assert_eq!(chain.state_root().unwrap(), live.state_root().unwrap());
+            assert_eq!(chain.storage.get_balance(&pk, "Compass").unwrap(), 10);
+            assert_eq!(chain.storage.get_balance(&alice_pk, &vault).unwrap(), 500);
+            assert_eq!(chain.vault_manager.vaults[&vault].minted_supply, 500);
+        }
+    }
+
+    #[test]
+    fn test_fork_choice_favors_scheduled_leader() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 16;
+        let (v1, v2) = (KeyPair::from_seed("v1"), KeyPair::from_seed("v2"));
+        chain.storage.set_active_validators(&["v1".to_string(), "v2".to_string()]).unwrap();
+        chain.storage.set_validator_pubkey("v1", &v1.public_key_hex()).unwrap();
+        chain.storage.set_validator_pubkey("v2", &v2.public_key_hex()).unwrap();
+
+        // A slot v2 leads
+        let schedule = chain.leader_schedule(0).unwrap();
+        let slot = (1..16).find(|s| schedule.leader_at(*s) == Some("v2")).expect("v2 leads some slot");
+        assert!(chain.may_propose(slot, "v2") && chain.may_propose(slot, &v2.public_key_hex()));
+        assert!(!chain.may_propose(slot, &v1.public_key_hex()));
+
+        let genesis = poh_header(&chain, &v1, 1, 0);
+        let genesis_hash = genesis.hash.clone();
+        chain.append_poh(genesis, &v1.public_key_hex()).unwrap();
+
+        // v1 fills v2's slot first; v2's block for the same slot replaces it
+        let stolen = poh_header(&chain, &v1, 2, slot * 1000);
+        chain.append_poh(stolen.clone(), &v1.public_key_hex()).unwrap();
+        assert!(!chain.head_weight().scheduled_leader);
+
+        let led = poh_child(1, &genesis_hash, &v2, 2, slot * 1000);
+        chain.sync_block(Block { header: led.clone(), transactions: vec![] }).unwrap();
+        assert_eq!(chain.head_hash(), Some(led.hash));
+        assert_eq!(chain.head_weight(), ChainWeight { height: 2, scheduled_leader: true, poh_tick: 2 });
+        assert_eq!(chain.storage.get_fork_tips(), vec![(stolen.hash, 1)]);
+    }
+
+    #[test]
+    fn test_epoch_report_block_closes_epoch() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 4;
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+
+        // Epoch 0: slots 1 and 2, with a PoUW payout while building the second block
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000), &pk).unwrap();
+        chain.storage.record_reward("worker", "COMPUTE", 40, "pouw").unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 2, 2_000), &pk).unwrap();
+        assert_eq!(chain.epoch_report_due(), None);
+
+        // The first PoH block of epoch 1 makes epoch 0's report due
+        chain.append_poh(poh_header(&chain, &leader, 3, 5_000), &pk).unwrap();
+        assert_eq!(chain.epoch_report_due(), Some(0));
+        let report = chain.compute_epoch_report(0).unwrap();
+        assert_eq!(report.heights, Some((0, 1)));
+        assert_eq!(report.compute_emitted, 40);
+
+        let mut header = poh_header(&chain, &leader, 0, 5_100);
+        header.block_type = report.block_type();
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = leader.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+
+        // A block claiming other totals is refused
+        let mut forged = header.clone();
+        forged.block_type = crate::epoch_report::EpochReport { fees_collected: 1, ..report.clone() }.block_type();
+        forged.hash = forged.calculate_hash().unwrap();
+        forged.signature_hex = leader.sign_hex(&encoding::hash_from_hex(&forged.hash).unwrap());
+        assert!(chain.append_epoch_report(forged, &pk).is_err());
+
+        chain.append_epoch_report(header, &pk).unwrap();
+        assert_eq!(chain.storage.get_epoch_report(0).unwrap(), Some(report));
+        assert_eq!(chain.epoch_report_due(), None);
+    }
+
+    #[test]
+    fn test_vesting_block_releases_only_what_has_vested() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 4;
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        let terms = crate::vesting::VestingTerms { start_ms: Some(0), cliff_ms: 2_000, duration_ms: 10_000 };
+        chain.storage.save_vesting_schedule(&terms.schedule("team", 1_000, 0)).unwrap();
+
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000), &pk).unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 2, 2_000), &pk).unwrap();
+        assert!(chain.vesting_releases_due().is_empty());
+        chain.append_poh(poh_header(&chain, &leader, 3, 5_000), &pk).unwrap();
+        assert_eq!(chain.vesting_releases_due(), vec![("team".to_string(), 500)]);
+
+        let release = |chain: &Chain, amount: u64| {
+            let mut header = poh_header(chain, &leader, 0, 5_000);
+            header.block_type = BlockType::Vesting { account: "team".to_string(), amount };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = leader.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+        assert!(chain.append_vesting(release(&chain, 501), &pk).is_err());
+        let height = chain.height;
+        chain.append_vesting(release(&chain, 500), &pk).unwrap();
+        assert_eq!(chain.storage.get_balance("team", "Compass").unwrap(), 500);
+        assert_eq!(chain.storage.get_vesting_schedule("team").unwrap().unwrap().released, 500);
+        assert!(chain.append_vesting(release(&chain, 1), &pk).is_err());
+
+        // A reorg abandoning the block takes the release back with the credit
+        chain.storage.revert_events(height, height + 1).unwrap();
+        assert_eq!(chain.storage.get_balance("team", "Compass").unwrap(), 0);
+        assert_eq!(chain.storage.get_vesting_schedule("team").unwrap().unwrap().released, 0);
+    }
+
+    #[test]
+    fn test_trade_block_settles_on_every_ledger() {
+        let leader_key = KeyPair::from_seed("leader");
+        let pk = leader_key.public_key_hex();
+        let (mut leader, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for chain in [&mut leader, &mut follower] {
+            chain.market_fees = crate::market::MarketFeeConfig { maker_fee_bps: 100, taker_fee_bps: 200 };
+            chain.treasury = crate::treasury::TreasuryConfig { fee_percent: 50, slash_percent: 0 };
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.storage.writer().credit("alice", "LTC", 10).unwrap();
+            chain.storage.writer().credit("bob", "Compass", 1_000).unwrap();
+        }
+        leader.append_poh(poh_header(&leader, &leader_key, 1, 1_000), &pk).unwrap();
+
+        // bob's buy took 4 LTC from alice's resting ask at 50: 200 Compass, plus 1% from alice and 2% from bob
+        let trade = |chain: &Chain, amount: u64, fees: (u64, u64)| {
+            let mut header = poh_header(chain, &leader_key, 0, 2_000);
+            header.block_type = BlockType::Trade {
+                trade_id: "2-1".to_string(),
+                maker: "alice".to_string(),
+                taker: "bob".to_string(),
+                taker_side: crate::market::OrderSide::Buy,
+                base: "LTC".to_string(),
+                quote: "Compass".to_string(),
+                amount,
+                price: 50,
+                maker_fee: fees.0,
+                taker_fee: fees.1,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = leader_key.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+        assert!(leader.append_trade(trade(&leader, 11, (5, 11)), &pk).is_err());
+        assert!(leader.append_trade(trade(&leader, 4, (2, 4)), "someone-else").is_err());
+        assert!(leader.append_trade(trade(&leader, 4, (0, 0)), &pk).is_err());
+        leader.append_trade(trade(&leader, 4, (2, 4)), &pk).unwrap();
+        let balances = [
+            ("alice", "LTC", 6),
+            ("alice", "Compass", 198),
+            ("bob", "LTC", 4),
+            ("bob", "Compass", 796),
+            (crate::treasury::TREASURY_ACCOUNT, "Compass", 3),
+            (pk.as_str(), "Compass", 3),
+        ];
+        for (account, asset, balance) in balances {
+            assert_eq!(leader.storage.get_balance(account, asset).unwrap(), balance, "{} {}", account, asset);
+        }
+
+        for height in 0..leader.height {
+            follower.sync_block(leader.storage.get_block_by_height(height).unwrap().unwrap()).unwrap();
+        }
+        assert_eq!(follower.storage.get_balance("alice", "Compass").unwrap(), 198);
+        assert_eq!(follower.storage.state_root().unwrap(), leader.storage.state_root().unwrap());
+
+        // Both index the fill for market data
+        let fills = follower.storage.get_pair_trades("LTC/Compass", 0);
+        assert_eq!(fills.len(), 1);
+        assert_eq!((fills[0].height, fills[0].timestamp, fills[0].price, fills[0].amount), (1, 2_000, 50, 4));
+        assert_eq!((fills[0].maker_fee, fills[0].taker_fee), (2, 4));
+        assert!(follower.storage.get_pair_trades("LTC/Compass", 2_001).is_empty());
+        assert_eq!(leader.storage.get_last_pair_trade("LTC/Compass"), Some(fills[0].clone()));
+    }
+
+    #[test]
+    fn test_burn_holds_collateral_until_payout_is_released() {
+        let leader_key = KeyPair::from_seed("leader");
+        let alice = KeyPair::from_seed("alice");
+        let (pk, alice_pk) = (leader_key.public_key_hex(), alice.public_key_hex());
+        let asset = crate::vault::vault_asset_name(&alice_pk, "LTC");
+        let (mut leader, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for chain in [&mut leader, &mut follower] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.storage.writer().credit(&alice_pk, &asset, 500).unwrap();
+            chain.vault_manager.vaults.insert(asset.clone(), crate::vault::Vault {
+                collateral_asset: "LTC".to_string(),
+                compass_asset: asset.clone(),
+                vault_address: String::new(),
+                exchange_rate: rust_decimal::Decimal::new(5, 1),
+                collateral_balance: 1_000,
+                minted_supply: 500,
+                accumulated_fees: 0,
+                mint_fee_rate: rust_decimal::Decimal::ZERO,
+                redeem_fee_rate: rust_decimal::Decimal::new(5, 3),
+                derivation_path: String::new(),
+            });
+        }
+        leader.append_poh(poh_header(&leader, &leader_key, 1, 1_000), &pk).unwrap();
+
+        let burn = |chain: &mut Chain, burn_amount: u64, timestamp: u64| {
+            let mut burn = poh_header(chain, &alice, 0, timestamp);
+            burn.proposer = alice_pk.clone();
+            burn.block_type = BlockType::Burn {
+                vault_id: asset.clone(),
+                collateral_asset: "LTC".to_string(),
+                compass_asset: asset.clone(),
+                burn_amount,
+                redeemer: alice_pk.clone(),
+                destination_address: "ltc1qalice".to_string(),
+                fee: 0,
+            };
+            burn.hash = burn.calculate_hash().unwrap();
+            burn.signature_hex = alice.sign_hex(burn.hash.as_bytes());
+            let intent_id = burn.hash.clone();
+            chain.append_burn(burn, &alice_pk).unwrap();
+            intent_id
+        };
+        let intent_id = burn(&mut leader, 200, 2_000);
+
+        // Burnt, but the vault still holds the collateral: 400 LTC less a 2 LTC fee is owed
+        let intent = leader.storage.get_redemption(&intent_id).unwrap().unwrap();
+        assert!(intent.is_queued());
+        assert_eq!((intent.quote.gross, intent.quote.fee, intent.quote.payout), (400, 2, 398));
+        assert_eq!(leader.storage.get_balance(&alice_pk, &asset).unwrap(), 300);
+        let vault = &leader.vault_manager.vaults[&asset];
+        assert_eq!((vault.collateral_balance, vault.minted_supply), (1_000, 500));
+
+        let release_of = |chain: &Chain, intent_id: &str, redeemer: &str, external_tx: &str| {
+            let mut header = poh_header(chain, &leader_key, 0, 3_000);
+            header.block_type = BlockType::RedemptionRelease {
+                intent_id: intent_id.to_string(),
+                redeemer: redeemer.to_string(),
+                external_tx: external_tx.to_string(),
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = leader_key.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+        let release = |chain: &Chain, redeemer: &str| release_of(chain, &intent_id, redeemer, "ltc-payout");
+        assert!(leader.append_redemption_release(release(&leader, "mallory"), &pk).is_err());
+        assert!(leader.append_redemption_release(release(&leader, &alice_pk), "someone-else").is_err());
+        leader.append_redemption_release(release(&leader, &alice_pk), &pk).unwrap();
+        assert!(leader.append_redemption_release(release(&leader, &alice_pk), &pk).is_err());
+
+        for height in 0..leader.height {
+            follower.sync_block(leader.storage.get_block_by_height(height).unwrap().unwrap()).unwrap();
+        }
+        for chain in [&leader, &follower] {
+            let vault = &chain.vault_manager.vaults[&asset];
+            assert_eq!((vault.collateral_balance, vault.minted_supply, vault.accumulated_fees), (600, 300, 2));
+            let intent = chain.storage.get_redemption(&intent_id).unwrap().unwrap();
+            assert_eq!(intent.status, crate::vault::redemption::RedemptionStatus::Released { external_tx: "ltc-payout".to_string(), height: 2 });
+        }
+        assert_eq!(follower.storage.state_root().unwrap(), leader.storage.state_root().unwrap());
+
+        // A payout that already released one redemption can't release another
+        let second = burn(&mut leader, 100, 4_000);
+        assert!(leader.append_redemption_release(release_of(&leader, &second, &alice_pk, "ltc-payout"), &pk).is_err());
+        let mut replayed = release_of(&leader, &second, &alice_pk, "ltc-payout");
+        assert!(leader.sync_block(crate::block::Block { header: replayed.clone(), transactions: vec![] }).is_err());
+        replayed = release_of(&leader, &second, &alice_pk, "ltc-payout-2");
+        leader.append_redemption_release(replayed, &pk).unwrap();
+        assert_eq!(leader.storage.get_payout_tx_redemption("ltc-payout-2").unwrap(), Some(second));
+    }
+
+    #[test]
+    fn test_vault_debt_accrues_stability_fee_to_treasury() {
+        let leader_key = KeyPair::from_seed("leader");
+        let alice = KeyPair::from_seed("alice");
+        let (pk, alice_pk) = (leader_key.public_key_hex(), alice.public_key_hex());
+        let asset = crate::vault::vault_asset_name(&alice_pk, "LTC");
+        let half_year_ms = 365 * 24 * 3600 * 1000 / 2;
+        let (mut leader, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for chain in [&mut leader, &mut follower] {
+            chain.stability_fee = crate::vault::stability::StabilityFeeConfig { annual_rate_bps: 1_000 };
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain.storage.writer().credit(&alice_pk, &asset, 1_000).unwrap();
+            chain.vault_manager.vaults.insert(asset.clone(), crate::vault::Vault {
+                collateral_asset: "LTC".to_string(),
+                compass_asset: asset.clone(),
+                vault_address: String::new(),
+                exchange_rate: rust_decimal::Decimal::ONE,
+                collateral_balance: 2_000,
+                minted_supply: 1_000,
+                accumulated_fees: 0,
+                mint_fee_rate: rust_decimal::Decimal::ZERO,
+                redeem_fee_rate: rust_decimal::Decimal::ZERO,
+                derivation_path: String::new(),
+            });
+        }
+
+        let burn = |chain: &Chain, amount: u64, timestamp: u64| {
+            let mut header = poh_header(chain, &alice, 0, timestamp);
+            header.proposer = alice_pk.clone();
+            header.block_type = BlockType::Burn {
+                vault_id: asset.clone(),
+                collateral_asset: "LTC".to_string(),
+                compass_asset: asset.clone(),
+                burn_amount: amount,
+                redeemer: alice_pk.clone(),
+                destination_address: "ltc1qalice".to_string(),
+                fee: 0,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = alice.sign_hex(header.hash.as_bytes());
+            header
+        };
+        // The first block touching the vault starts the clock; half a year at 10% is 5% more debt
+        leader.append_burn(burn(&leader, 100, 1_000), &alice_pk).unwrap();
+        assert_eq!(leader.vault_manager.vaults[&asset].minted_supply, 1_000);
+        leader.append_burn(burn(&leader, 100, 1_000 + half_year_ms), &alice_pk).unwrap();
+
+        for height in 0..leader.height {
+            follower.sync_block(leader.storage.get_block_by_height(height).unwrap().unwrap()).unwrap();
+        }
+        for chain in [&leader, &follower] {
+            assert_eq!(chain.vault_manager.vaults[&asset].minted_supply, 1_050);
+            assert_eq!(chain.storage.get_balance(crate::treasury::TREASURY_ACCOUNT, &asset).unwrap(), 50);
+            assert_eq!(chain.storage.get_vault_accrual(&asset).unwrap().unwrap().charged, 50);
+        }
+        assert_eq!(follower.storage.state_root().unwrap(), leader.storage.state_root().unwrap());
+    }
+
+    #[test]
+    fn test_l2_checkpoints_follow_in_sequence_and_sync() {
+        let leader_key = KeyPair::from_seed("leader");
+        let pk = leader_key.public_key_hex();
+        let (mut leader, mut follower) = (Chain::new_in_memory(), Chain::new_in_memory());
+        for chain in [&mut leader, &mut follower] {
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        }
+        let mut l2 = crate::layer2::Layer2State::new(None);
+        l2.collateral.stake("alice".to_string(), 10);
+        let commitment = l2.commitment().unwrap();
+
+        let checkpoint = |chain: &Chain, sequence: u64, state_hash: String| {
+            let mut header = poh_header(chain, &leader_key, 0, 1_000);
+            header.block_type = BlockType::L2Checkpoint {
+                sequence,
+                state_hash,
+                economics_hash: commitment.economics_hash.clone(),
+                assets_hash: commitment.assets_hash.clone(),
+                collateral_hash: commitment.collateral_hash.clone(),
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = leader_key.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+        // A hash that doesn't combine the components, or a skipped sequence, is refused
+        assert!(leader.append_l2_checkpoint(checkpoint(&leader, 1, "00".repeat(32)), &pk).is_err());
+        assert!(leader.append_l2_checkpoint(checkpoint(&leader, 2, commitment.state_hash()), &pk).is_err());
+        leader.append_l2_checkpoint(checkpoint(&leader, 1, commitment.state_hash()), &pk).unwrap();
+        assert!(leader.append_l2_checkpoint(checkpoint(&leader, 1, commitment.state_hash()), &pk).is_err());
+
+        follower.sync_block(leader.storage.get_block_by_height(0).unwrap().unwrap()).unwrap();
+        let recorded = follower.storage.get_latest_l2_checkpoint().unwrap();
+        assert_eq!((recorded.sequence, recorded.height), (1, 0));
+        assert_eq!(recorded.commitment, commitment);
+    }
+
+    #[test]
+    fn test_votes_are_weighted_by_stake_and_delegation() {
+        let mut chain = Chain::new_in_memory();
+        let (alice, bob, carol) = (KeyPair::from_seed("alice"), KeyPair::from_seed("bob"), KeyPair::from_seed("carol"));
+        let (alice_pk, bob_pk, carol_pk) = (alice.public_key_hex(), bob.public_key_hex(), carol.public_key_hex());
+        let mut collateral = crate::layer2::collateral::CollateralManager::new();
+        collateral.stake(alice_pk.clone(), 30);
+        collateral.stake(bob_pk.clone(), 20);
+        collateral.stake(carol_pk.clone(), 40);
+        chain.storage.put("l2:collateral", &collateral).unwrap();
+
+        let delegate = |chain: &Chain, nonce: u64| {
+            crate::block::create_delegate_block(chain.height, bob_pk.clone(), nonce, chain.head_hash().unwrap_or_default(), &alice)
+                .unwrap()
+        };
+        assert!(chain.append_delegate(delegate(&chain, 2)).is_err());
+        chain.append_delegate(delegate(&chain, 1)).unwrap();
+        assert_eq!(chain.storage.get_nonce(&alice_pk).unwrap(), 1);
+
+        for (voter, pk, choice) in [(&bob, &bob_pk, true), (&carol, &carol_pk, false)] {
+            let head = chain.head_hash().unwrap_or_default();
+            let vote = crate::block::create_vote_block(chain.height, pk.clone(), 7, choice, head, voter).unwrap();
+            chain.append_vote(vote, pk).unwrap();
+        }
+        assert_eq!(chain.tally_votes(7), (50, 40));
+    }
+
+    #[test]
+    fn test_treasury_collects_fees_and_spends_passed_proposals() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 4;
+        chain.treasury = crate::treasury::TreasuryConfig { fee_percent: 10, slash_percent: 0 };
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        let (alice, bob, carol, dave) =
+            (KeyPair::from_seed("alice"), KeyPair::from_seed("bob"), KeyPair::from_seed("carol"), KeyPair::from_seed("dave"));
+        let mut collateral = crate::layer2::collateral::CollateralManager::new();
+        collateral.stake(bob.public_key_hex(), 20);
+        collateral.stake(carol.public_key_hex(), 10);
+        collateral.stake(dave.public_key_hex(), 50);
+        chain.storage.put("l2:collateral", &collateral).unwrap();
+        chain.storage.writer().set_balance(&alice.public_key_hex(), "Compass", 1_000).unwrap();
+
+        // Headers restamped to the test's clock and signed again
+        let at = |mut header: BlockHeader, timestamp: u64, signer: &KeyPair| {
+            header.timestamp = timestamp;
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = signer.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000), &pk).unwrap();
+        let mut transfer = poh_header(&chain, &alice, 0, 0);
+        transfer.block_type = BlockType::Transfer {
+            from: alice.public_key_hex(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount: 10,
+            nonce: 1,
+            fee: 100,
+        };
+        chain.append_transfer(at(transfer, 1_100, &alice), &alice.public_key_hex()).unwrap();
+        assert_eq!(chain.storage.get_balance(crate::treasury::TREASURY_ACCOUNT, "Compass").unwrap(), 10);
+        assert_eq!(chain.storage.get_balance(&pk, "Compass").unwrap(), 90);
+
+        let proposal = |chain: &Chain, amount: u64, deadline: u64| {
+            let header = crate::block::create_treasury_proposal_block(
+                chain.height,
+                1,
+                "grants".to_string(),
+                amount,
+                deadline,
+                "fund grants".to_string(),
+                chain.head_hash().unwrap_or_default(),
+                &alice,
+            )
+            .unwrap();
+            at(header, 1_200, &alice)
+        };
+        assert!(chain.append_treasury_proposal(proposal(&chain, 0, 3_000)).is_err());
+        assert!(chain.append_treasury_proposal(proposal(&chain, 8, 1_200)).is_err());
+        chain.append_treasury_proposal(proposal(&chain, 8, 3_000)).unwrap();
+        assert!(chain.append_treasury_proposal(proposal(&chain, 8, 3_000)).is_err());
+
+        // Dave votes after the deadline, so his weight doesn't count
+        for (voter, choice, timestamp) in [(&bob, true, 1_300), (&carol, false, 1_300), (&dave, false, 3_500)] {
+            let voter_pk = voter.public_key_hex();
+            let head = chain.head_hash().unwrap_or_default();
+            let vote = crate::block::create_vote_block(chain.height, voter_pk.clone(), 1, choice, head, voter).unwrap();
+            chain.append_vote(at(vote, timestamp, voter), &voter_pk).unwrap();
+        }
+
+        let spend = |chain: &Chain, amount: u64, timestamp: u64| {
+            let mut header = poh_header(chain, &leader, 0, 0);
+            header.block_type = BlockType::TreasurySpend { proposal_id: 1, recipient: "grants".to_string(), amount };
+            at(header, timestamp, &leader)
+        };
+        // Still open
+        assert!(chain.append_treasury_spend(spend(&chain, 8, 2_000), &pk).is_err());
+
+        chain.append_poh(poh_header(&chain, &leader, 2, 2_000), &pk).unwrap();
+        assert!(chain.treasury_spends_due().is_empty());
+        chain.append_poh(poh_header(&chain, &leader, 3, 5_000), &pk).unwrap();
+        let due = chain.treasury_spends_due();
+        assert!(matches!(
+            due.as_slice(),
+            [BlockType::TreasurySpend { proposal_id: 1, recipient, amount: 8 }] if recipient == "grants"
+        ));
+
+        assert!(chain.append_treasury_spend(spend(&chain, 9, 5_000), &pk).is_err());
+        chain.append_treasury_spend(spend(&chain, 8, 5_000), &pk).unwrap();
+        assert_eq!(chain.storage.get_balance("grants", "Compass").unwrap(), 8);
+        assert_eq!(chain.storage.get_balance(crate::treasury::TREASURY_ACCOUNT, "Compass").unwrap(), 2);
+        assert!(chain.append_treasury_spend(spend(&chain, 8, 5_000), &pk).is_err());
+
+        let info = &chain.treasury_proposals(5_000).unwrap()[0];
+        assert_eq!((info.yes, info.no, info.status), (20, 10, crate::treasury::ProposalStatus::Spent));
+    }
+
+    #[test]
+    fn test_stability_fee_rate_changes_by_passed_proposal() {
+        let mut chain = Chain::new_in_memory();
+        chain.slots_per_epoch = 4;
+        chain.stability_fee = crate::vault::stability::StabilityFeeConfig { annual_rate_bps: 500 };
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        let (alice, bob, carol) = (KeyPair::from_seed("alice"), KeyPair::from_seed("bob"), KeyPair::from_seed("carol"));
+        let mut collateral = crate::layer2::collateral::CollateralManager::new();
+        collateral.stake(bob.public_key_hex(), 20);
+        collateral.stake(carol.public_key_hex(), 10);
+        chain.storage.put("l2:collateral", &collateral).unwrap();
+        let at = |mut header: BlockHeader, timestamp: u64, signer: &KeyPair| {
+            header.timestamp = timestamp;
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = signer.sign_hex(&encoding::hash_from_hex(&header.hash).unwrap());
+            header
+        };
+
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000), &pk).unwrap();
+        let proposal = |chain: &Chain, rate: u64| {
+            let head = chain.head_hash().unwrap_or_default();
+            let header = crate::block::create_stability_fee_proposal_block(chain.height, 1, rate, 3_000, "cut the fee".into(), head, &alice).unwrap();
+            at(header, 1_200, &alice)
+        };
+        assert!(chain.append_stability_fee_proposal(proposal(&chain, MAX_STABILITY_FEE_BPS + 1)).is_err());
+        chain.append_stability_fee_proposal(proposal(&chain, 200)).unwrap();
+        for (voter, choice) in [(&bob, true), (&carol, false)] {
+            let voter_pk = voter.public_key_hex();
+            let head = chain.head_hash().unwrap_or_default();
+            let vote = crate::block::create_vote_block(chain.height, voter_pk.clone(), 1, choice, head, voter).unwrap();
+            chain.append_vote(at(vote, 1_300, voter), &voter_pk).unwrap();
+        }
+
+        let change = |chain: &Chain, rate: u64| {
+            let mut header = poh_header(chain, &leader, 0, 0);
+            header.block_type = BlockType::StabilityFeeChange { proposal_id: 1, annual_rate_bps: rate };
+            at(header, 5_000, &leader)
+        };
+        chain.append_poh(poh_header(&chain, &leader, 3, 5_000), &pk).unwrap();
+        assert!(matches!(
+            chain.stability_fee_changes_due().as_slice(),
+            [BlockType::StabilityFeeChange { proposal_id: 1, annual_rate_bps: 200 }]
+        ));
+        assert!(chain.append_stability_fee_change(change(&chain, 0), &pk).is_err());
+        let height = chain.height;
+        chain.append_stability_fee_change(change(&chain, 200), &pk).unwrap();
+        assert_eq!(chain.stability_fee_config().annual_rate_bps, 200);
+        assert!(chain.append_stability_fee_change(change(&chain, 200), &pk).is_err());
+
+        // A reorg past the change puts the configured rate back
+        chain.storage.revert_events(height, chain.height).unwrap();
+        assert_eq!(chain.stability_fee_config().annual_rate_bps, 500);
+    }
+
+    #[test]
+    fn test_finality_votes_finalize_and_pin_the_chain() {
+        fn vote(chain: &Chain, voter: &KeyPair, target_hash: &str, target_height: u64) -> BlockHeader {
+            let signature = voter.sign_hex(crate::finality::vote_message(target_hash, target_height).as_bytes());
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 0,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: voter.public_key_hex(),
+                signature_hex: String::new(),
+                block_type: BlockType::FinalityVote {
+                    voter: voter.public_key_hex(),
+                    target_hash: target_hash.to_string(),
+                    target_height,
+                    signature,
+                },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let mut chain = Chain::new_in_memory();
+        let validators: Vec<KeyPair> = ["v1", "v2", "v3"].iter().map(|s| KeyPair::from_seed(s)).collect();
+        chain.storage.set_active_validators(&["v1".to_string(), "v2".to_string(), "v3".to_string()]).unwrap();
+        for (id, kp) in ["v1", "v2", "v3"].iter().zip(&validators) {
+            chain.storage.set_validator_pubkey(id, &kp.public_key_hex()).unwrap();
+        }
+        let v1 = &validators[0];
+        let genesis = poh_header(&chain, v1, 1, 1_000);
+        let genesis_hash = genesis.hash.clone();
+        chain.append_poh(genesis, &v1.public_key_hex()).unwrap();
+        let target = poh_header(&chain, v1, 2, 2_000);
+        let target_hash = target.hash.clone();
+        chain.append_poh(target, &v1.public_key_hex()).unwrap();
+
+        // Outsiders, forged signatures and off-chain targets don't count
+        let outsider = KeyPair::from_seed("outsider");
+        assert!(chain.append_finality_vote(vote(&chain, &outsider, &target_hash, 1)).is_err());
+        let mut forged = vote(&chain, &validators[1], &target_hash, 1);
+        if let BlockType::FinalityVote { signature, .. } = &mut forged.block_type {
+            *signature = v1.sign_hex(b"something else");
+        }
+        forged.hash = forged.calculate_hash().unwrap();
+        assert!(chain.append_finality_vote(forged).is_err());
+        assert!(chain.append_finality_vote(vote(&chain, v1, &genesis_hash, 1)).is_err());
+
+        // Two of three validators is not a supermajority; the third vote finalizes
+        for voter in &validators[..2] {
+            chain.append_finality_vote(vote(&chain, voter, &target_hash, 1)).unwrap();
+        }
+        assert_eq!(chain.finalized_height(), None);
+        assert!(chain.append_finality_vote(vote(&chain, v1, &target_hash, 1)).is_err());
+        chain.append_finality_vote(vote(&chain, &validators[2], &target_hash, 1)).unwrap();
+        assert_eq!(chain.finalized_height(), Some(1));
+        assert!(chain.is_finalized(1) && !chain.is_finalized(2));
+
+        // A longer fork that would replace the finalized block is refused
+        let head = chain.head_hash();
+        let mut prev = genesis_hash;
+        let tip = chain.height;
+        for index in 1..=tip {
+            let block = poh_child(index, &prev, v1, 10 + index, 10_000 + index * 1000);
+            prev = block.hash.clone();
+            let synced = chain.sync_block(Block { header: block, transactions: vec![] });
+            if index == tip {
+                assert!(synced.unwrap_err().to_string().contains("finalized"));
+            }
+        }
+        assert_eq!(chain.head_hash(), head);
+    }
+
+    #[test]
+    fn test_earnings_claim_pays_out_accrued_compute() {
+        use crate::layer3::earnings::{claim_message, EarningReason};
+
+        fn claim(chain: &Chain, worker: &KeyPair, amount: u64, nonce: u64) -> BlockHeader {
+            let signature = worker.sign_hex(claim_message(&worker.public_key_hex(), amount, nonce).as_bytes());
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 0,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: worker.public_key_hex(),
+                signature_hex: String::new(),
+                block_type: BlockType::EarningsClaim { worker: worker.public_key_hex(), amount, nonce, signature },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let mut chain = Chain::new_in_memory();
+        chain.min_earnings_payout = 10;
+        let worker = KeyPair::from_seed("earner");
+        let id = worker.public_key_hex();
+        chain.storage.accrue_earnings(&id, EarningReason::Job, 25, "job-1").unwrap();
+        chain.storage.accrue_earnings(&id, EarningReason::PoolDividend, 5, "pool-1").unwrap();
+
+        // Below the minimum, above the balance, or with a stale nonce: refused
+        assert!(chain.append_earnings_claim(claim(&chain, &worker, 5, 1)).is_err());
+        assert!(chain.append_earnings_claim(claim(&chain, &worker, 31, 1)).is_err());
+        assert!(chain.append_earnings_claim(claim(&chain, &worker, 20, 2)).is_err());
+
+        chain.append_earnings_claim(claim(&chain, &worker, 20, 1)).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, "COMPUTE").unwrap(), 20);
+        let earnings = chain.storage.get_worker_earnings(&id).unwrap();
+        assert_eq!((earnings.claimable, earnings.total_claimed), (10, 20));
+        assert!(chain.append_earnings_claim(claim(&chain, &worker, 20, 1)).is_err());
+    }
+
+    #[test]
+    fn test_unbonded_withdrawal_credits_matured_collateral_once() {
+        use crate::layer2::collateral::{withdraw_message, Unbonding, COLLATERAL_ASSET};
+
+        fn withdraw(chain: &Chain, signer: &KeyPair, entity: &str, amount: u64, nonce: u64, timestamp: u64) -> BlockHeader {
+            let signature = signer.sign_hex(withdraw_message(entity, nonce).as_bytes());
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: entity.to_string(),
+                signature_hex: String::new(),
+                block_type: BlockType::UnbondedWithdrawal { entity: entity.to_string(), amount, nonce, signature },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let mut chain = Chain::new_in_memory();
+        chain.slot_duration_ms = 1000;
+        let staker = KeyPair::from_seed("staker");
+        let id = staker.public_key_hex();
+        let queue = vec![Unbonding { amount: 30, release_slot: 5 }, Unbonding { amount: 10, release_slot: 9 }];
+        chain.storage.save_l2_stake(&id, 0, &queue, 0).unwrap();
+        let leader = KeyPair::from_seed("leader");
+        chain.storage.set_validator_pubkey(&leader.public_key_hex(), &leader.public_key_hex()).unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 1, 1000), &leader.public_key_hex()).unwrap();
+
+        // Before the block's slot, more than has matured, or signed by someone else: refused
+        assert!(chain.append_unbonded_withdrawal(withdraw(&chain, &staker, &id, 30, 1, 4000)).is_err());
+        assert!(chain.append_unbonded_withdrawal(withdraw(&chain, &staker, &id, 40, 1, 6000)).is_err());
+        let other = KeyPair::from_seed("other");
+        assert!(chain.append_unbonded_withdrawal(withdraw(&chain, &other, &id, 30, 1, 6000)).is_err());
+        assert_eq!(chain.storage.get_l2_unbonding_of(&id).unwrap(), queue);
+
+        let height = chain.height;
+        chain.append_unbonded_withdrawal(withdraw(&chain, &staker, &id, 30, 1, 6000)).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, COLLATERAL_ASSET).unwrap(), 30);
+        assert_eq!(chain.storage.get_l2_unbonding_of(&id).unwrap(), queue[1..]);
+        // The same signature cannot be replayed
+        assert!(chain.append_unbonded_withdrawal(withdraw(&chain, &staker, &id, 10, 1, 9000)).is_err());
+
+        // A reorg puts the withdrawal back in the queue
+        chain.storage.revert_events(height, height + 1).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, COLLATERAL_ASSET).unwrap(), 0);
+        assert_eq!(chain.storage.get_l2_unbonding_of(&id).unwrap(), queue);
+    }
+
+    #[test]
+    fn test_contract_blocks_charge_gas_and_revert_with_the_chain() {
+        use crate::vm::{call_message, contract_address, deploy_message};
+
+        fn deploy(chain: &Chain, signer: &KeyPair, code: &[u8], gas_limit: u64, gas_price: u64, nonce: u64) -> BlockHeader {
+            let deployer = signer.public_key_hex();
+            let signature = signer.sign_hex(deploy_message(&deployer, code, gas_limit, gas_price, nonce).as_bytes());
+            let block_type = BlockType::ContractDeploy { deployer, code: code.to_vec(), gas_limit, gas_price, nonce, signature };
+            contract_header(chain, signer, block_type)
+        }
+        fn call(chain: &Chain, signer: &KeyPair, contract: &str, method: &str, gas_limit: u64, nonce: u64) -> BlockHeader {
+            let caller = signer.public_key_hex();
+            let signature = signer.sign_hex(call_message(&caller, contract, method, &[], gas_limit, 2, nonce).as_bytes());
+            let block_type = BlockType::ContractCall {
+                caller,
+                contract: contract.to_string(),
+                method: method.to_string(),
+                input: vec![],
+                gas_limit,
+                gas_price: 2,
+                nonce,
+                signature,
+            };
+            contract_header(chain, signer, block_type)
+        }
+        fn contract_header(chain: &Chain, signer: &KeyPair, block_type: BlockType) -> BlockHeader {
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 2000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: signer.public_key_hex(),
+                signature_hex: String::new(),
+                block_type,
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let code = wat::parse_str(
+            r#"(module
+                (import "env" "storage_write" (func $write (param i32 i32 i32 i32)))
+                (memory (export "memory") 1)
+                (data (i32.const 0) "k")
+                (func (export "set") (call $write (i32.const 0) (i32.const 1) (i32.const 0) (i32.const 1)))
+                (func (export "spin") (loop (br 0))))"#,
+        )
+        .unwrap();
+        let mut chain = Chain::new_in_memory();
+        chain.fee_schedule.gas_price = 2;
+        let leader = KeyPair::from_seed("leader");
+        chain.storage.set_validator_pubkey(&leader.public_key_hex(), &leader.public_key_hex()).unwrap();
+        let sender = KeyPair::from_seed("contract sender");
+        let id = sender.public_key_hex();
+        chain.storage.writer().set_balance(&id, "Compass", 1_000_000).unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 1, 1000), &leader.public_key_hex()).unwrap();
+
+        // Below the gas price, more gas than the sender can pay for, or signed by someone else: refused
+        assert!(chain.append_contract(deploy(&chain, &sender, &code, 100_000, 1, 1)).is_err());
+        assert!(chain.append_contract(deploy(&chain, &sender, &code, 1_000_000, 2, 1)).is_err());
+        let mut forged = deploy(&chain, &sender, &code, 100_000, 2, 1);
+        if let BlockType::ContractDeploy { signature, .. } = &mut forged.block_type {
+            *signature = KeyPair::from_seed("other").sign_hex(b"forged");
+        }
+        forged.hash = forged.calculate_hash().unwrap();
+        assert!(chain.append_contract(forged).is_err());
+        assert_eq!(chain.storage.get_nonce(&id).unwrap(), 0);
+
+        let deployed_at = chain.height;
+        let address = contract_address(&id, 1);
+        assert_eq!(chain.append_contract(deploy(&chain, &sender, &code, 100_000, 2, 1)).unwrap().unwrap().contract, address);
+        let set = chain.append_contract(call(&chain, &sender, &address, "set", 100_000, 2)).unwrap().unwrap();
+        assert!(set.gas_used > 0);
+        assert_eq!(chain.storage.get_balance(&id, "Compass").unwrap(), 1_000_000 - 2 * set.gas_used);
+        assert_eq!(chain.storage.get_contract_state(&address, b"k").unwrap(), Some(b"k".to_vec()));
+
+        // A failed run still commits, spends the nonce and pays its whole gas limit
+        assert!(chain.append_contract(call(&chain, &sender, &address, "spin", 10_000, 3)).unwrap().is_err());
+        assert_eq!(chain.storage.get_nonce(&id).unwrap(), 3);
+        assert_eq!(chain.storage.get_balance(&id, "Compass").unwrap(), 1_000_000 - 2 * set.gas_used - 20_000);
+        assert!(chain.append_contract(call(&chain, &sender, &address, "set", 100_000, 3)).is_err(), "a nonce runs once");
+
+        // A reorg removes the contract, its state and the gas paid
+        chain.storage.revert_events(deployed_at, chain.height).unwrap();
+        assert!(chain.storage.get_contract(&address).unwrap().is_none());
+        assert!(chain.storage.get_contract_code(&address).unwrap().is_none());
+        assert_eq!(chain.storage.get_contract_state(&address, b"k").unwrap(), None);
+        assert_eq!(chain.storage.get_balance(&id, "Compass").unwrap(), 1_000_000);
+    }
+
+    #[test]
+    fn test_model_invocation_is_charged_once_per_signed_request() {
+        use crate::layer3::model_nft::{invoke_message, ModelNFT, ModelStats};
+
+        fn invocation(chain: &Chain, signer: &KeyPair, caller: &str, token_id: &str, inputs: &[u8], timestamp: u64) -> BlockHeader {
+            let signature = signer.sign_hex(invoke_message(token_id, caller, inputs, timestamp).as_bytes());
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 2000,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: caller.to_string(),
+                signature_hex: String::new(),
+                block_type: BlockType::ModelInvocation {
+                    token_id: token_id.to_string(),
+                    caller: caller.to_string(),
+                    inputs: inputs.to_vec(),
+                    timestamp,
+                    signature,
+                },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let mut chain = Chain::new_in_memory();
+        let stats = ModelStats {
+            accuracy: 0.6, win_rate: 0.5, total_predictions: 0, profitable_predictions: 0, total_profit: 0,
+            training_samples: 1, training_epochs: 1, final_loss: 0.1, training_duration: 1, data_hash: String::new(),
+        };
+        let mut nft = ModelNFT::from_job("job", "BTC", "creator".to_string(), &stats);
+        nft.current_owner = "owner".to_string();
+        chain.storage.save_model_nft(&nft).unwrap();
+        let caller = KeyPair::from_seed("caller");
+        let id = caller.public_key_hex();
+        chain.storage.writer().set_balance(&id, "COMPASS", 100).unwrap();
+        let leader = KeyPair::from_seed("leader");
+        chain.storage.set_validator_pubkey(&leader.public_key_hex(), &leader.public_key_hex()).unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 1, 1000), &leader.public_key_hex()).unwrap();
+
+        let other = KeyPair::from_seed("other");
+        assert!(chain.append_model_invocation(invocation(&chain, &other, &id, &nft.token_id, b"[1]", 1500)).is_err());
+
+        let height = chain.height;
+        chain.append_model_invocation(invocation(&chain, &caller, &id, &nft.token_id, b"[1]", 1500)).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, "COMPASS").unwrap(), 90);
+        assert_eq!(chain.storage.get_balance("creator", "COMPASS").unwrap(), 1);
+        assert_eq!(chain.storage.get_balance("owner", "COMPASS").unwrap(), 9);
+        // The same signed request cannot be charged again; a fresh one can
+        assert!(chain.append_model_invocation(invocation(&chain, &caller, &id, &nft.token_id, b"[1]", 1500)).is_err());
+        chain.append_model_invocation(invocation(&chain, &caller, &id, &nft.token_id, b"[1]", 1600)).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, "COMPASS").unwrap(), 80);
+
+        // A reorg refunds the calls and lets the request settle again
+        chain.storage.revert_events(height, chain.height).unwrap();
+        assert_eq!(chain.storage.get_balance(&id, "COMPASS").unwrap(), 100);
+        assert_eq!(chain.storage.get_balance("owner", "COMPASS").unwrap(), 0);
+        let request_id = crate::layer3::model_nft::invoke_request_id(&nft.token_id, &id, b"[1]", 1500);
+        assert!(!chain.storage.is_invocation_settled(&request_id).unwrap());
+    }
+
+    #[test]
+    fn test_schedule_block_charges_its_fee_and_parks_the_signed_transaction() {
+        use crate::network::{SignedTransaction, TransactionPayload};
+
+        fn schedule(chain: &Chain, account: &KeyPair, fee: u64, block_fee: u64) -> BlockHeader {
+            let delegator = account.public_key_hex();
+            let signature = account.sign_hex(crate::governance::delegate_message(&delegator, "bob", 1).as_bytes());
+            let inner = TransactionPayload::Delegate { delegator: delegator.clone(), representative: "bob".to_string(), nonce: 1, signature };
+            let payload = TransactionPayload::Scheduled { execute_at_slot: 5, fee, inner: Box::new(inner) };
+            let envelope = encoding::canonical_bytes(&SignedTransaction::sign(payload, 1, "compass-test", account)).unwrap();
+            let mut header = BlockHeader {
+                index: chain.height,
+                timestamp: 0,
+                prev_hash: chain.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: delegator.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::Schedule { account: delegator, execute_at_slot: 5, fee: block_fee, envelope },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header
+        }
+
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        chain.append_poh(poh_header(&chain, &leader, 1, 1_000_000), &leader.public_key_hex()).unwrap();
+        let alice = KeyPair::from_seed("alice");
+        let pk = alice.public_key_hex();
+        chain.storage.writer().credit(&pk, "Compass", 5000).unwrap();
+
+        // Too cheap, or a block that charges other than what the account signed
+        assert!(chain.append_schedule(schedule(&chain, &alice, MIN_SCHEDULE_FEE - 1, MIN_SCHEDULE_FEE - 1)).is_err());
+        assert!(chain.append_schedule(schedule(&chain, &alice, MIN_SCHEDULE_FEE, MIN_SCHEDULE_FEE + 1)).is_err());
+
+        chain.append_schedule(schedule(&chain, &alice, MIN_SCHEDULE_FEE, MIN_SCHEDULE_FEE)).unwrap();
+        assert_eq!(chain.storage.get_balance(&pk, "Compass").unwrap(), 5000 - MIN_SCHEDULE_FEE);
+        let parked = chain.storage.get_scheduled_txs();
+        assert_eq!(parked.len(), 1);
+        assert!(matches!(parked[0].2, TransactionPayload::Delegate { .. }));
+
+        // The same envelope can't be parked (and charged) twice
+        assert!(chain.append_schedule(schedule(&chain, &alice, MIN_SCHEDULE_FEE, MIN_SCHEDULE_FEE)).is_err());
+        assert_eq!(chain.storage.scheduled_count(&pk), 1);
+    }
+
+    #[test]
+    fn test_block_indexes_follow_canonical_chain() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+
+        let genesis = poh_header(&chain, &leader, 1, 1_000_000);
+        let genesis_hash = genesis.hash.clone();
+        chain.append_poh(genesis, &pk).unwrap();
+        chain.append_poh(poh_header(&chain, &leader, 2, 1_001_000), &pk).unwrap();
+        assert_eq!(chain.storage.get_proposer_block_heights(&pk, 0, 10), (vec![1, 0], 2));
+
+        // Reorg: height 1 is replaced, height 2 is new; the index still lists each height once
+        let b1 = poh_child(1, &genesis_hash, &leader, 2, 1_001_500);
+        chain.sync_block(Block { header: b1.clone(), transactions: vec![] }).unwrap();
+        let b2 = poh_child(2, &b1.hash, &leader, 3, 1_002_000);
+        chain.sync_block(Block { header: b2, transactions: vec![] }).unwrap();
+        assert_eq!(chain.storage.get_proposer_block_heights(&pk, 0, 10), (vec![2, 1, 0], 3));
+        assert_eq!(chain.storage.get_proposer_block_heights(&pk, 1, 1), (vec![1], 3));
+
+        // Both sides of a transfer see it; cutting the block back removes it again
+        let mut transfer = poh_child(3, "prev", &leader, 0, 1_003_000);
+        transfer.block_type = BlockType::Transfer {
+            from: "alice".into(), to: "bob".into(), asset: "Compass".into(), amount: 5, nonce: 1, fee: 0,
+        };
+        transfer.hash = transfer.calculate_hash().unwrap();
+        chain.storage.save_block(&Block { header: transfer, transactions: vec![] }).unwrap();
+        assert_eq!(chain.storage.get_account_block_heights("bob", 0, 10), (vec![3], 1));
+        assert_eq!(chain.storage.get_account_block_heights("alice", 0, 10), (vec![3], 1));
+        assert_eq!(chain.storage.get_asset_transfer_heights("Compass", 0, 10), (vec![3], 1));
+        assert_eq!(chain.storage.get_type_block_heights("Transfer", 0, 10), (vec![3], 1));
+        assert_eq!(chain.storage.get_type_block_heights("PoH", 0, 10).1, 3);
+        chain.storage.truncate_height_index(3, 4).unwrap();
+        assert_eq!(chain.storage.get_account_block_heights("bob", 0, 10), (vec![], 0));
+        assert_eq!(chain.storage.get_asset_transfer_heights("Compass", 0, 10), (vec![], 0));
+        assert_eq!(chain.storage.get_type_block_heights("Transfer", 0, 10), (vec![], 0));
+
+        let mut mint = poh_child(3, "prev", &leader, 0, 1_003_000);
+        mint.block_type = BlockType::Mint {
+            vault_id: "ltc-vault".into(), collateral_asset: "LTC".into(), collateral_amount: 10,
+            compass_asset: "cLTC".into(), mint_amount: 5, owner: "alice".into(),
+            tx_proof: String::new(), oracle_signature: String::new(), fee: 0,
+        };
+        mint.hash = mint.calculate_hash().unwrap();
+        chain.storage.save_block(&Block { header: mint, transactions: vec![] }).unwrap();
+        assert_eq!(chain.storage.get_vault_mint_heights("ltc-vault", 0, 10), (vec![3], 1));
+        assert_eq!(chain.storage.get_vault_mint_heights("ltc", 0, 10), (vec![], 0));
+    }
+
+    #[test]
+    fn test_nft_events_indexed_by_token_until_reverted() {
+        use crate::layer3::model_nft::{ModelNFT, ModelStats};
+
+        let chain = Chain::new_in_memory();
+        let stats = ModelStats {
+            accuracy: 0.6, win_rate: 0.5, total_predictions: 1, profitable_predictions: 1, total_profit: 0,
+            training_samples: 1, training_epochs: 1, final_loss: 0.1, training_duration: 1, data_hash: String::new(),
+        };
+        let mut nft = ModelNFT::from_job("job", "LTC", "alice".to_string(), &stats);
+        let other = ModelNFT::from_job("job2", "BTC", "carol".to_string(), &stats);
+
+        chain.storage.set_event_height(4);
+        chain.storage.save_model_nft(&nft).unwrap();
+        chain.storage.save_model_nft(&other).unwrap();
+        chain.storage.set_event_height(7);
+        nft.current_owner = "bob".to_string();
+        chain.storage.save_model_nft(&nft).unwrap();
+
+        let (events, total) = chain.storage.get_nft_events(&nft.token_id, 0, 10);
+        assert_eq!(total, 2);
+        assert_eq!(events.iter().map(|e| e.height).collect::<Vec<_>>(), vec![7, 4]);
+        assert!(matches!(&events[0].event, StateEvent::NftOwnerChanged { to, .. } if to == "bob"));
+        assert_eq!(chain.storage.get_nft_events(&nft.token_id, 1, 10).0.len(), 1);
+
+        // A reorg above height 5 drops the transfer from the index
+        chain.storage.revert_events(5, 8).unwrap();
+        assert_eq!(chain.storage.get_nft_events(&nft.token_id, 0, 10).1, 1);
+        assert_eq!(chain.storage.get_nft_events(&other.token_id, 0, 10).1, 1);
     }
 }
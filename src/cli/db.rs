@@ -0,0 +1,66 @@
+use clap::Subcommand;
+
+use crate::storage::{store, DbBackend};
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum DbCommands {
+    /// Copy a node database into another storage engine (stop the node first), e.g.
+    /// a sled `db_path` into a new RocksDB directory before setting `db_backend`
+    Migrate {
+        /// Database to read (default: `db_path` from config.toml)
+        #[arg(long)]
+        from: Option<String>,
+        #[arg(long, value_enum, default_value = "sled")]
+        from_backend: DbBackend,
+        /// Directory for the new database; must not exist yet
+        #[arg(long)]
+        to: String,
+        #[arg(long, value_enum, default_value = "rocksdb")]
+        to_backend: DbBackend,
+    },
+}
+
+/// Entries written per batch while migrating
+const MIGRATE_BATCH: usize = 10_000;
+
+/// `compass db ...`. Returns whether it succeeded.
+pub fn handle_db_command(config: &crate::config::CompassConfig, cmd: DbCommands) -> bool {
+    let DbCommands::Migrate { from, from_backend, to, to_backend } = cmd;
+    let from = from.unwrap_or_else(|| config.node.db_path.clone());
+    // Opening a missing sled path would create an empty database and "migrate" that
+    if !std::path::Path::new(&from).exists() {
+        println!("❌ No database at '{}'", from);
+        return false;
+    }
+    if std::path::Path::new(&to).exists() {
+        println!("❌ '{}' already exists; migrate into a fresh directory", to);
+        return false;
+    }
+
+    let source = match store::open(&from, from_backend, None) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("❌ Cannot open '{}': {} (is the node still running?)", from, e);
+            return false;
+        }
+    };
+    let target = match store::open(&to, to_backend, None) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("❌ Cannot create '{}': {}", to, e);
+            return false;
+        }
+    };
+
+    println!("Copying {} database '{}' into {} at '{}'...", from_backend, from, to_backend, to);
+    match store::copy_all(&source, &target, MIGRATE_BATCH, |n| println!("  {} entries", n)) {
+        Ok(n) => {
+            println!("✅ Copied {} entries. Point `db_path` at '{}' and set `db_backend = \"{}\"`", n, to, to_backend);
+            true
+        }
+        Err(e) => {
+            println!("❌ Migration failed: {}", e);
+            false
+        }
+    }
+}
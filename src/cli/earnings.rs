@@ -0,0 +1,69 @@
+use clap::Subcommand;
+
+use crate::client::rpc_client::RpcClient;
+use crate::layer3::earnings::claim_message;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum EarningsCommands {
+    /// Show a worker's claimable balance and recent accruals
+    Show {
+        /// Worker public key
+        worker: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+    /// Pay claimable earnings into the worker's wallet
+    Claim {
+        /// Amount to claim; everything claimable when omitted
+        amount: Option<u64>,
+        /// Wallet of the worker
+        #[arg(long)]
+        wallet: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+}
+
+async fn run(cmd: EarningsCommands) -> Result<(), String> {
+    match cmd {
+        EarningsCommands::Show { worker, node_url } => {
+            let earnings = RpcClient::new(node_url).get_earnings(&worker).await?;
+            println!("Earnings of {}", worker);
+            println!(
+                "  claimable: {} {} (minimum payout {})",
+                earnings["claimable"], earnings["asset"].as_str().unwrap_or("COMPUTE"), earnings["min_payout"]
+            );
+            println!("  accrued: {} / claimed: {}", earnings["total_accrued"], earnings["total_claimed"]);
+            let entries = earnings["entries"].as_array().cloned().unwrap_or_default();
+            for e in entries.iter().rev().take(10) {
+                println!(
+                    "  {} {} from {}",
+                    e["reason"].as_str().unwrap_or("?"),
+                    e["amount"],
+                    e["reference"].as_str().unwrap_or("")
+                );
+            }
+        }
+        EarningsCommands::Claim { amount, wallet, node_url } => {
+            let keypair = crate::cli::tx::load_wallet_keypair("wallets.json", &wallet)?;
+            let worker = keypair.public_key_hex();
+            let client = RpcClient::new(node_url);
+            let earnings = client.get_earnings(&worker).await?;
+            let amount = match amount {
+                Some(amount) => amount,
+                None => earnings["claimable"].as_u64().ok_or("Node did not report a claimable balance")?,
+            };
+            let nonce = earnings["next_nonce"].as_u64().ok_or("Node did not report the next nonce")?;
+            let signature = keypair.sign_hex(claim_message(&worker, amount, nonce).as_bytes());
+            let res = client.claim_earnings(&worker, amount, nonce, &signature).await?;
+            println!("✅ Claim of {} submitted ({})", amount, res["tx_hash"].as_str().unwrap_or("?"));
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle_earnings_command(cmd: EarningsCommands) {
+    if let Err(e) = run(cmd).await {
+        println!("❌ {}", e);
+    }
+}
@@ -0,0 +1,187 @@
+use clap::{Subcommand, ValueEnum};
+
+use crate::export::BlockRange;
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum DataFormat {
+    /// One JSON object per block per line (schema in `crate::export`)
+    Jsonl,
+    /// Length-prefixed bincode blocks; smaller and faster, for backups
+    Bin,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ChainCommands {
+    /// Write the canonical chain from the node database (stop the node first)
+    Export {
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: DataFormat,
+        /// Heights to write: `0..N` (N excluded), `0..=N`, `N..` or a single height
+        /// (default: all)
+        #[arg(long, default_value = "0..")]
+        range: BlockRange,
+        /// File to write (default: stdout)
+        #[arg(long)]
+        out: Option<String>,
+        #[arg(long)]
+        db_path: Option<String>,
+    },
+    /// Verify and execute exported blocks into a node database (stop the node first)
+    Import {
+        /// File written by `chain export`
+        file: String,
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: DataFormat,
+        #[arg(long)]
+        db_path: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ExportCommands {
+    /// Write the canonical chain from the node database (stop the node first)
+    Blocks {
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: DataFormat,
+        /// File to write (default: stdout)
+        #[arg(long)]
+        out: Option<String>,
+        #[arg(long, default_value_t = 0)]
+        from: u64,
+        /// Last height to export (default: the head)
+        #[arg(long)]
+        to: Option<u64>,
+        #[arg(long)]
+        db_path: Option<String>,
+    },
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum ImportCommands {
+    /// Execute exported blocks into a node database, checking every hash,
+    /// signature and state root
+    Blocks {
+        /// File written by `export blocks`
+        file: String,
+        #[arg(long, value_enum, default_value = "jsonl")]
+        format: DataFormat,
+        #[arg(long)]
+        db_path: Option<String>,
+    },
+}
+
+/// `compass export ...`. Returns whether it succeeded.
+pub fn handle_export_command(config: &crate::config::CompassConfig, cmd: ExportCommands) -> bool {
+    let ExportCommands::Blocks { format, out, from, to, db_path } = cmd;
+    export_blocks(config, format, BlockRange { from, to }, out, db_path)
+}
+
+/// `compass chain ...`. Returns whether it succeeded.
+pub fn handle_chain_command(config: &crate::config::CompassConfig, cmd: ChainCommands) -> bool {
+    match cmd {
+        ChainCommands::Export { format, range, out, db_path } => export_blocks(config, format, range, out, db_path),
+        ChainCommands::Import { file, format, db_path } => import_blocks(config, file, format, db_path),
+    }
+}
+
+fn export_blocks(
+    config: &crate::config::CompassConfig,
+    format: DataFormat,
+    range: BlockRange,
+    out: Option<String>,
+    db_path: Option<String>,
+) -> bool {
+    let db_path = db_path.unwrap_or_else(|| config.node.db_path.clone());
+    let storage = match crate::storage::Storage::open(&db_path, config.node.db_backend, None) {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("❌ Cannot open '{}': {} (is the node still running?)", db_path, e);
+            return false;
+        }
+    };
+
+    let (from, to) = (range.from, range.to.unwrap_or(u64::MAX));
+    let write = |mut writer: &mut dyn std::io::Write| match format {
+        DataFormat::Jsonl => crate::export::export_jsonl(&storage, from, to, &mut writer),
+        DataFormat::Bin => crate::export::export_bin(&storage, from, to, &mut writer),
+    };
+    let result = match &out {
+        Some(path) => std::fs::File::create(path)
+            .map_err(|e| crate::error::CompassError::DatabaseError(format!("{}: {}", path, e)))
+            .and_then(|file| {
+                let mut writer = std::io::BufWriter::new(file);
+                let n = write(&mut writer)?;
+                std::io::Write::flush(&mut writer)
+                    .map_err(|e| crate::error::CompassError::DatabaseError(format!("{}: {}", path, e)))?;
+                Ok(n)
+            }),
+        None => write(&mut std::io::stdout().lock()),
+    };
+    match result {
+        // Progress goes to stderr so stdout carries only the export
+        Ok(n) => {
+            eprintln!("✅ Exported {} blocks{}", n, out.map(|p| format!(" to {}", p)).unwrap_or_default());
+            true
+        }
+        Err(e) => {
+            eprintln!("❌ Export failed: {}", e);
+            false
+        }
+    }
+}
+
+/// `compass import ...`: start the database from the configured genesis if it is
+/// empty, then execute the file's blocks on top. Returns whether it succeeded.
+pub fn handle_import_command(config: &crate::config::CompassConfig, cmd: ImportCommands) -> bool {
+    let ImportCommands::Blocks { file, format, db_path } = cmd;
+    import_blocks(config, file, format, db_path)
+}
+
+fn import_blocks(
+    config: &crate::config::CompassConfig,
+    file: String,
+    format: DataFormat,
+    db_path: Option<String>,
+) -> bool {
+    let db_path = db_path.unwrap_or_else(|| config.node.db_path.clone());
+    let input = match std::fs::File::open(&file) {
+        Ok(f) => std::io::BufReader::new(f),
+        Err(e) => {
+            println!("❌ Cannot read '{}': {}", file, e);
+            return false;
+        }
+    };
+    let genesis = match crate::genesis::GenesisConfig::load(&config.node.genesis_file) {
+        Ok(g) => g,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let result = crate::storage::Storage::open(&db_path, config.node.db_backend, None).and_then(|storage| {
+        let mut chain = crate::chain::Chain::new(std::sync::Arc::new(storage));
+        chain.slot_duration_ms = config.consensus.slot_duration_ms;
+        chain.slots_per_epoch = config.consensus.slots_per_epoch;
+        chain.pouw_curve = config.consensus.pouw.clone();
+        chain.initialize_genesis(&genesis)?;
+        let report = match format {
+            DataFormat::Jsonl => crate::export::import_jsonl(&mut chain, input)?,
+            DataFormat::Bin => crate::export::import_bin(&mut chain, input)?,
+        };
+        Ok((report, chain.height))
+    });
+    match result {
+        Ok((report, height)) => {
+            println!(
+                "✅ Imported {} blocks ({} already present); '{}' is now at height {}",
+                report.imported, report.skipped, db_path, height
+            );
+            true
+        }
+        Err(e) => {
+            println!("❌ Import failed: {}", e);
+            false
+        }
+    }
+}
@@ -0,0 +1,46 @@
+use clap::Subcommand;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum FaucetCommands {
+    /// Ask a node's faucet for testnet Compass
+    Request {
+        /// Address (public key hex) to fund
+        address: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+        /// Captcha token, if the faucet asks for one
+        #[arg(long)]
+        captcha: Option<String>,
+    },
+}
+
+pub async fn handle_faucet_command(cmd: FaucetCommands) {
+    match cmd {
+        FaucetCommands::Request { address, node_url, captcha } => {
+            let url = format!("{}/faucet", node_url.trim_end_matches('/'));
+            let body = crate::rpc::faucet::FaucetRequest { address, captcha };
+            let resp = match reqwest::Client::new().post(&url).json(&body).send().await {
+                Ok(resp) => resp,
+                Err(e) => {
+                    println!("❌ Could not reach {}: {}", url, e);
+                    return;
+                }
+            };
+            let status = resp.status();
+            let json: serde_json::Value = resp.json().await.unwrap_or_default();
+            if status.is_success() {
+                let grant = &json["grant"];
+                let amount = grant["amount"].as_u64().unwrap_or(0);
+                println!(
+                    "✅ Sent {} Compass to {} in block {} ({})",
+                    crate::encoding::format_units(amount, crate::encoding::asset_decimals("Compass")),
+                    grant["address"].as_str().unwrap_or_default(),
+                    grant["height"],
+                    grant["block_hash"].as_str().unwrap_or_default()
+                );
+            } else {
+                println!("❌ Faucet refused ({}): {}", status, json["error"].as_str().unwrap_or("no reason given"));
+            }
+        }
+    }
+}
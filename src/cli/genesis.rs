@@ -0,0 +1,66 @@
+use clap::Subcommand;
+
+use crate::genesis::GenesisConfig;
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum GenesisCommands {
+    /// Check a genesis file before launch: allocations, duplicate accounts and
+    /// validators, key formats, and the pinned genesis hash
+    Validate {
+        /// Genesis file (default: `genesis_file` from config.toml)
+        file: Option<String>,
+        /// Write the canonical hash into the file once it passes
+        #[arg(long)]
+        pin: bool,
+    },
+}
+
+/// `compass genesis ...`. Returns whether it succeeded.
+pub fn handle_genesis_command(config: &crate::config::CompassConfig, cmd: GenesisCommands) -> bool {
+    let GenesisCommands::Validate { file, pin } = cmd;
+    let path = file.unwrap_or_else(|| config.node.genesis_file.clone());
+    let content = match std::fs::read_to_string(&path) {
+        Ok(c) => c,
+        Err(e) => {
+            println!("❌ Cannot read '{}': {}", path, e);
+            return false;
+        }
+    };
+    let (genesis, problems) = match GenesisConfig::check(&content) {
+        Ok(checked) => checked,
+        Err(e) => {
+            println!("❌ '{}' is not a genesis file: {}", path, e);
+            return false;
+        }
+    };
+
+    let hash = genesis.canonical_hash();
+    println!("Chain id:     {}", genesis.chain_id);
+    println!("Accounts:     {}", genesis.initial_balances.len());
+    if let Some(total) = genesis.total_allocation() {
+        println!("Allocated:    {}", total);
+    }
+    let schedules = genesis.vesting_schedules();
+    if !schedules.is_empty() {
+        println!("Vesting:      {} allocation(s), {} locked", schedules.len(), schedules.iter().fold(0u64, |t, s| t.saturating_add(s.total)));
+    }
+    println!("Validators:   {}", genesis.initial_validators.len());
+    println!("Genesis hash: {}{}", hash, if genesis.genesis_hash.is_some() { " (pinned)" } else { "" });
+
+    if !problems.is_empty() {
+        for problem in &problems {
+            println!("  ✗ {}", problem);
+        }
+        println!("❌ {} problem(s) in '{}'", problems.len(), path);
+        return false;
+    }
+    if pin && genesis.genesis_hash.is_none() {
+        if let Err(e) = genesis.pinned().save(&path) {
+            println!("❌ Cannot write '{}': {}", path, e);
+            return false;
+        }
+        println!("📌 Pinned the genesis hash in '{}'", path);
+    }
+    println!("✅ '{}' is ready to launch", path);
+    true
+}
@@ -106,6 +106,7 @@ pub fn handle_keys_command(cmd: KeysCommands) {
                      println!("Name: {}", id.name);
                      println!("Role: {}", id.role);
                      println!("PubKey: {}", id.public_key);
+                     println!("Format: v{}", id.version);
                  },
                  Err(e) => println!("Error: {}", e),
              }
@@ -4,6 +4,12 @@ pub mod tx;
 pub mod wallet;
 pub mod keys; // New Key Manager
 pub mod session; // RBAC Session Management
+pub mod faucet;
+pub mod export;
+pub mod pool;
+pub mod earnings;
+pub mod db;
+pub mod genesis;
 
 use clap::{Parser, Subcommand};
 
@@ -17,6 +23,11 @@ pub struct Cli {
 
 #[derive(Subcommand)]
 pub enum Commands {
+    /// First-run setup: identity keystore, genesis, config, registration and bootnode check
+    Init {
+        #[arg(long, default_value = "config.toml")]
+        config: String,
+    },
     /// Key management (Identity)
     Keys {
         #[command(subcommand)]
@@ -43,10 +54,40 @@ pub enum Commands {
         from: String,
         #[arg(long)]
         to: String,
+        /// Whole tokens, e.g. 1.5 or "1.5 Compass"
         #[arg(long)]
-        amount: u64,
+        amount: String,
         #[arg(long)]
         asset: String,
+        /// Sign with an external signer instead of the stored key,
+        /// e.g. external://127.0.0.1:7070?key=ledger or external:///run/signer.sock
+        #[arg(long)]
+        signer: Option<String>,
+        /// Compass base units to pay above the minimum fee (default: the node's
+        /// median pending priority fee)
+        #[arg(long)]
+        priority_fee: Option<u64>,
+        /// Send even if the recipient looks like a typo of a saved contact
+        #[arg(long)]
+        force: bool,
+    },
+    /// Start an RPC session for a password account; transfers from it use the
+    /// saved token until it expires
+    Login {
+        #[arg(long)]
+        account: String,
+        #[arg(long, default_value = "http://localhost:8899")]
+        rpc_url: String,
+    },
+    /// End an account's saved RPC session
+    Logout {
+        #[arg(long)]
+        account: String,
+    },
+    /// Address book of labelled recipients
+    Contacts {
+        #[command(subcommand)]
+        cmd: wallet::ContactCommands,
     },
 
     /// Run as AI Worker
@@ -61,6 +102,83 @@ pub enum Commands {
         wallet: String,
     },
     
+    /// Watchtower: follow headers from a node and alert on consensus or reserve violations
+    Watchtower {
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+
+        /// Seconds between polls
+        #[arg(long, default_value_t = 10)]
+        interval: u64,
+
+        /// POST a JSON alert to this URL on every violation (alerts always go to stderr)
+        #[arg(long)]
+        webhook: Option<String>,
+
+        /// Public key (hex) allowed to sign PoH blocks; repeat for multiple validators
+        #[arg(long = "proposer")]
+        proposers: Vec<String>,
+
+        /// Height to start following from (default: recent headers)
+        #[arg(long)]
+        from_height: Option<u64>,
+    },
+    
+    /// Re-execute the chain from genesis into a scratch database and diff the state
+    /// against the live one (stop the node first)
+    Replay {
+        /// Last height to replay (default: the live head)
+        #[arg(long)]
+        to: Option<u64>,
+        #[arg(long)]
+        db_path: Option<String>,
+    },
+
+    /// Export chain data for external analysis or migration
+    Export {
+        #[command(subcommand)]
+        cmd: export::ExportCommands,
+    },
+    /// Import chain data written by `export`
+    Import {
+        #[command(subcommand)]
+        cmd: export::ImportCommands,
+    },
+    /// Back up, inspect and restore the chain as JSONL or binary block files
+    Chain {
+        #[command(subcommand)]
+        cmd: export::ChainCommands,
+    },
+    /// Node database maintenance
+    Db {
+        #[command(subcommand)]
+        cmd: db::DbCommands,
+    },
+
+    /// Testnet faucet
+    Faucet {
+        #[command(subcommand)]
+        cmd: faucet::FaucetCommands,
+    },
+
+    /// Model pool governance: parameter proposals and stake-weighted votes
+    Pool {
+        #[command(subcommand)]
+        cmd: pool::PoolCommands,
+    },
+
+    /// Worker earnings: claimable balance and payouts to the wallet
+    Earnings {
+        #[command(subcommand)]
+        cmd: earnings::EarningsCommands,
+    },
+
+    /// Browse blocks, accounts, NFTs and jobs on a node, with search over token and NFT names
+    Explorer {
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+
     /// Interactive Mode (Default)
     Interactive,
     Balance {
@@ -71,13 +189,13 @@ pub enum Commands {
         #[arg(long)]
         vault_id: String,
         #[arg(long)]
-        amount: u64,
+        amount: String, // Whole tokens, e.g. 100.5
         #[arg(long)]
         asset: String, // Compass Asset to mint
         #[arg(long)]
         collateral_asset: String,
         #[arg(long)]
-        collateral_amount: u64,
+        collateral_amount: String, // Whole units of the collateral, e.g. 0.001
         #[arg(long)]
         proof: String, // External transaction hash
         #[arg(long)]
@@ -90,7 +208,7 @@ pub enum Commands {
         #[arg(long)]
         vault_id: String,
         #[arg(long)]
-        amount: u64,
+        amount: String, // Whole tokens
         #[arg(long)]
         asset: String, // Asset to burn
         #[arg(long)]
@@ -106,6 +224,11 @@ pub enum Commands {
     AdminGen,
     /// Calculate Genesis Hash (Offline)
     GenesisHash,
+    /// Genesis file checks
+    Genesis {
+        #[command(subcommand)]
+        cmd: genesis::GenesisCommands,
+    },
     
     /// List an NFT for sale
     ListNFT {
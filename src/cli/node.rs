@@ -13,9 +13,27 @@ pub enum NodeCommands {
         peer: Option<String>,
         #[arg(long, default_value = "false")]
         ephemeral: bool,
+        /// Follow block headers only and verify balances with getProof
+        #[arg(long)]
+        light: bool,
+        /// Throwaway devnet with fixed, funded keys (see `fixtures`); uses its own database
+        #[arg(long)]
+        dev: bool,
     },
     Status,
     Peers,
+    /// Check the database from genesis: block hashes, signatures, links, the height
+    /// index and head, orphaned blocks, then balances by replay (stop the node first)
+    VerifyDb {
+        #[arg(long)]
+        db_path: Option<String>,
+        /// Restore height entries from stored blocks and delete orphaned blocks
+        #[arg(long)]
+        repair: bool,
+        /// Skip re-executing the chain to check balances
+        #[arg(long)]
+        skip_state: bool,
+    },
     Wipe {
         #[arg(long)]
         db_path: Option<String>,
@@ -48,8 +66,137 @@ pub async fn handle_node_command(cmd: NodeCommands) {
                 Err(e) => println!("Failed to get peers: {}", e),
             }
         }
-        NodeCommands::Wipe { .. } => {
+        NodeCommands::Wipe { .. } | NodeCommands::VerifyDb { .. } => {
             // Handled in main.rs
         }
     }
 }
+
+/// `compass replay`: rebuild state from genesis in `<db_path>.replay` and print
+/// where it parts from the live database. Returns whether everything matched.
+pub fn handle_replay(config: &crate::config::CompassConfig, db_path: &str, to: Option<u64>) -> bool {
+    use std::sync::Arc;
+
+    let live = match crate::storage::Storage::open(db_path, config.node.db_backend, None) {
+        Ok(s) => s,
+        Err(e) => {
+            println!("❌ Cannot open '{}': {} (is the node still running?)", db_path, e);
+            return false;
+        }
+    };
+    if live.pruned_below() > 0 {
+        println!("❌ '{}' is pruned below height {}; replay needs an archive database", db_path, live.pruned_below());
+        return false;
+    }
+    let genesis = match crate::genesis::GenesisConfig::load(&config.node.genesis_file) {
+        Ok(g) => g,
+        Err(e) => {
+            println!("❌ {}", e);
+            return false;
+        }
+    };
+
+    let scratch_path = format!("{}.replay", db_path);
+    let _ = std::fs::remove_dir_all(&scratch_path);
+    let result = crate::storage::Storage::open(&scratch_path, config.node.db_backend, None).and_then(|scratch_db| {
+        let mut scratch = crate::chain::Chain::new(Arc::new(scratch_db));
+        scratch.slot_duration_ms = config.consensus.slot_duration_ms;
+        scratch.slots_per_epoch = config.consensus.slots_per_epoch;
+        scratch.pouw_curve = config.consensus.pouw.clone();
+        scratch.initialize_genesis(&genesis)?;
+        crate::replay::replay(&live, &mut scratch, to.unwrap_or(u64::MAX))
+    });
+    let _ = std::fs::remove_dir_all(&scratch_path);
+
+    let report = match result {
+        Ok(r) => r,
+        Err(e) => {
+            println!("❌ Replay failed: {}", e);
+            return false;
+        }
+    };
+
+    println!("Replayed {} blocks up to height {} (live chain has {})", report.blocks, report.height, report.live_height);
+    if let Some((height, error)) = &report.failed {
+        println!("❌ Stopped at block {}: {}", height, error);
+    }
+    if let Some(m) = &report.first_root_mismatch {
+        println!(
+            "❌ State root diverges from block {} on ({} blocks differ): header {}, replayed {}",
+            m.height, report.root_mismatches, m.header_root, m.replayed_root
+        );
+    }
+    match &report.divergences {
+        Some(divergences) => {
+            for d in divergences {
+                let show = |v: Option<u64>| v.map_or("-".to_string(), |v| v.to_string());
+                println!("  {}: live {}, replayed {}", d.key, show(d.live), show(d.replayed));
+            }
+            println!("{} state entries differ from the live database", divergences.len());
+        }
+        None if report.failed.is_none() => {
+            println!("Live state not compared: it includes blocks after height {}", report.height)
+        }
+        None => {}
+    }
+    if report.is_clean() {
+        println!("✅ Replay matches the live chain");
+    }
+    report.is_clean()
+}
+
+/// `compass node verify-db`: check block structure and, unless `skip_state`, replay
+/// balances. Returns whether the database is consistent (after repair, if asked).
+pub fn handle_verify_db(config: &crate::config::CompassConfig, db_path: &str, repair: bool, skip_state: bool) -> bool {
+    use std::sync::Arc;
+
+    let storage = match crate::storage::Storage::open(db_path, config.node.db_backend, None) {
+        Ok(s) => Arc::new(s),
+        Err(e) => {
+            println!("❌ Cannot open '{}': {} (is the node still running?)", db_path, e);
+            return false;
+        }
+    };
+    let chain = crate::chain::Chain::new(storage);
+    let report = match crate::integrity::check(&chain) {
+        Ok(r) => r,
+        Err(e) => {
+            println!("❌ Check failed: {}", e);
+            return false;
+        }
+    };
+    println!("Walked {} canonical blocks ({} stored in total)", report.blocks, report.stored);
+    for issue in &report.issues {
+        let tag = if issue.is_repairable() { "repairable" } else { "needs re-sync" };
+        println!("  ⚠️  {} [{}]", issue, tag);
+    }
+    let mut clean = report.is_clean();
+    if !clean && repair {
+        match crate::integrity::repair(&chain, &report) {
+            Ok(n) => {
+                println!("🔧 Repaired {} of {} issues", n, report.issues.len());
+                clean = n == report.issues.len();
+            }
+            Err(e) => {
+                println!("❌ Repair failed: {}", e);
+                return false;
+            }
+        }
+    } else if !clean {
+        let repairable = report.issues.iter().filter(|i| i.is_repairable()).count();
+        if repairable > 0 {
+            println!("{} of {} issues can be fixed with --repair", repairable, report.issues.len());
+        }
+    }
+    if report.is_clean() {
+        println!("✅ Blocks, links and indexes are consistent");
+    }
+    drop(chain);
+
+    if skip_state {
+        return clean;
+    }
+    // Balances: re-execute everything and compare, as `compass replay` does
+    println!("Replaying the chain to check balances...");
+    handle_replay(config, db_path, None) && clean
+}
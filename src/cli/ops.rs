@@ -1,191 +1,209 @@
 use crate::block::{BlockHeader, BlockType};
+use crate::cli::tx::{fetch_chain_tip, load_wallet_keypair, report_receipt};
 use crate::client::rpc_client::RpcClient;
 use crate::crypto::KeyPair;
+use crate::encoding::Amount;
 use crate::rpc::types::{SubmitBurnParams, SubmitMintParams};
-use crate::wallet::WalletManager;
 use chrono::Utc;
 
+/// What a vault owner asks to mint against deposited collateral
+pub struct MintRequest {
+    pub vault_id: String,
+    pub collateral_asset: String,
+    pub collateral_amount: u64,
+    pub compass_asset: String,
+    pub mint_amount: u64,
+    pub owner: String,
+    pub tx_proof: String, // External deposit transaction hash
+    pub oracle_signature: String,
+}
+
+/// What a holder asks to burn and redeem to an external address
+pub struct BurnRequest {
+    pub vault_id: String,
+    pub collateral_asset: String,
+    pub compass_asset: String,
+    pub burn_amount: u64,
+    pub redeemer: String,
+    pub destination_address: String,
+}
+
+/// Sign a mint with the owner's key and submit it. Returns the tx hash.
+pub async fn submit_mint(client: &RpcClient, keypair: &KeyPair, req: MintRequest) -> Result<String, String> {
+    let tip = fetch_chain_tip(client).await?;
+
+    let mut header = BlockHeader {
+        index: tip.height,
+        block_type: BlockType::Mint {
+            vault_id: req.vault_id.clone(),
+            collateral_asset: req.collateral_asset.clone(),
+            collateral_amount: req.collateral_amount,
+            compass_asset: req.compass_asset.clone(),
+            mint_amount: req.mint_amount,
+            owner: req.owner.clone(),
+            tx_proof: req.tx_proof.clone(),
+            oracle_signature: req.oracle_signature.clone(),
+            fee: 0, // Default fee
+        },
+        proposer: req.owner.clone(),
+        signature_hex: String::new(),
+        prev_hash: tip.head_hash.clone(),
+        hash: String::new(),
+        timestamp: Utc::now().timestamp() as u64,
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash().map_err(|e| e.to_string())?;
+    let signature = keypair.sign_hex(header.hash.as_bytes());
+
+    let params = SubmitMintParams {
+        vault_id: req.vault_id,
+        collateral_asset: req.collateral_asset,
+        collateral_amount: req.collateral_amount,
+        compass_asset: req.compass_asset,
+        mint_amount: req.mint_amount,
+        owner: req.owner,
+        tx_proof: req.tx_proof,
+        oracle_signature: req.oracle_signature,
+        fee: 0,
+        signature,
+        prev_hash: Some(tip.head_hash),
+        timestamp: Some(header.timestamp),
+        public_key: keypair.public_key_hex(),
+    };
+    client.submit_mint(params).await
+}
+
+/// Sign a burn with the redeemer's key and submit it. Returns the tx hash.
+pub async fn submit_burn(client: &RpcClient, keypair: &KeyPair, req: BurnRequest) -> Result<String, String> {
+    let tip = fetch_chain_tip(client).await?;
+
+    let mut header = BlockHeader {
+        index: tip.height,
+        block_type: BlockType::Burn {
+            vault_id: req.vault_id.clone(),
+            collateral_asset: req.collateral_asset.clone(),
+            compass_asset: req.compass_asset.clone(),
+            burn_amount: req.burn_amount,
+            redeemer: req.redeemer.clone(),
+            destination_address: req.destination_address.clone(),
+            fee: 0,
+        },
+        proposer: req.redeemer.clone(),
+        signature_hex: String::new(),
+        prev_hash: tip.head_hash.clone(),
+        hash: String::new(),
+        timestamp: Utc::now().timestamp() as u64,
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash().map_err(|e| e.to_string())?;
+    let signature = keypair.sign_hex(header.hash.as_bytes());
+
+    let params = SubmitBurnParams {
+        vault_id: req.vault_id,
+        collateral_asset: req.collateral_asset,
+        compass_asset: req.compass_asset,
+        burn_amount: req.burn_amount,
+        redeemer: req.redeemer,
+        destination_address: req.destination_address,
+        fee: 0,
+        signature,
+        prev_hash: tip.head_hash,
+        timestamp: header.timestamp,
+        public_key: keypair.public_key_hex(),
+    };
+    client.submit_burn(params).await
+}
+
 pub async fn handle_mint_command(
     vault_id: String,
-    amount: u64,
+    amount: String,
     asset: String,
     collateral_asset: String,
-    collateral_amount: u64,
+    collateral_amount: String,
     proof: String,
     oracle_sig: String,
     owner: String,
     rpc_url: Option<String>,
 ) {
-    // 1. Setup RPC
+    let (amount, collateral_amount) = match (Amount::parse(&amount, &asset), Amount::parse(&collateral_amount, &collateral_asset)) {
+        (Ok(amount), Ok(collateral_amount)) => (amount, collateral_amount),
+        (Err(e), _) | (_, Err(e)) => {
+            println!("Error: {}", e);
+            return;
+        }
+    };
     let url = rpc_url.unwrap_or_else(|| "http://localhost:8899".to_string());
     let client = RpcClient::new(url);
 
-    // 2. Get Wallet for Signing (Header integrity)
     // We assume the local user "owner" is signing the request.
-    let manager = WalletManager::load("client_wallets.json");
-    let wallet = match manager.get_wallet(&owner) {
-        Some(w) => w,
-        None => {
-            println!("Error: Wallet '{}' not found", owner);
-            return;
-        }
-    };
-    let mnemonic = match &wallet.mnemonic {
-        Some(m) => m,
-        None => {
-            println!("Error: Wallet has no mnemonic");
-            return;
-        }
-    };
-    let keypair = match KeyPair::from_mnemonic(mnemonic) {
+    let keypair = match load_wallet_keypair("client_wallets.json", &owner) {
         Ok(kp) => kp,
         Err(e) => {
-            println!("Error restoring keys: {}", e);
-            return;
-        }
-    };
-
-    // 3. Get Chain State
-    let node_info = match client.get_node_info().await {
-        Ok(info) => info,
-        Err(e) => {
-            println!("Error fetching node info: {}", e);
+            println!("Error: {}", e);
             return;
         }
     };
-    let head_hash = node_info["head_hash"]
-        .as_str()
-        .map(|s| s.to_string())
-        .unwrap_or_default();
-    let height = node_info["height"].as_u64().unwrap_or(0);
-
-    // 4. Construct Header to Sign
-    let mut header = BlockHeader {
-        index: height,
-        block_type: BlockType::Mint {
-            vault_id: vault_id.clone(),
-            collateral_asset: collateral_asset.clone(),
-            collateral_amount,
-            compass_asset: asset.clone(),
-            mint_amount: amount,
-            owner: owner.clone(),
-            tx_proof: proof.clone(),
-            oracle_signature: oracle_sig.clone(),
-            fee: 0, // Default fee
-        },
-        proposer: owner.clone(),
-        signature_hex: String::new(),
-        prev_hash: head_hash.clone(),
-        hash: String::new(),
-        timestamp: Utc::now().timestamp() as u64,
-    };
 
-    header.hash = header.calculate_hash().expect("Failed to calculate hash");
-    let signature = keypair.sign_hex(header.hash.as_bytes());
-
-    // 5. Submit
-    let params = SubmitMintParams {
+    let req = MintRequest {
         vault_id,
         collateral_asset,
-        collateral_amount,
+        collateral_amount: collateral_amount.units,
         compass_asset: asset,
-        mint_amount: amount,
+        mint_amount: amount.units,
         owner,
         tx_proof: proof,
         oracle_signature: oracle_sig,
-        fee: 0,
-        signature,
-        prev_hash: Some(head_hash),
-        timestamp: Some(header.timestamp),
-        public_key: keypair.public_key_hex(),
     };
-
-    match client.submit_mint(params).await {
-        Ok(tx_hash) => println!("Mint Submitted! Tx Hash: {}", tx_hash),
+    match submit_mint(&client, &keypair, req).await {
+        Ok(tx_hash) => {
+            println!("Mint Submitted! Tx Hash: {}", tx_hash);
+            report_receipt(&client, &tx_hash).await;
+        }
         Err(e) => println!("Mint Failed: {}", e),
     }
 }
 
 pub async fn handle_burn_command(
     vault_id: String,
-    amount: u64,
+    amount: String,
     asset: String,
     dest_addr: String,
     from: String,
     rpc_url: Option<String>,
 ) {
-    let url = rpc_url.unwrap_or_else(|| "http://localhost:8899".to_string());
-    let client = RpcClient::new(url);
-
-    let manager = WalletManager::load("client_wallets.json");
-    let wallet = match manager.get_wallet(&from) {
-        Some(w) => w,
-        None => {
-            println!("Error: Wallet '{}' not found", from);
-            return;
-        }
-    };
-    let mnemonic = match &wallet.mnemonic {
-        Some(m) => m,
-        None => {
-            println!("Error: Wallet has no mnemonic");
-            return;
-        }
-    };
-    let keypair = match KeyPair::from_mnemonic(mnemonic) {
-        Ok(kp) => kp,
+    let amount = match Amount::parse(&amount, &asset) {
+        Ok(amount) => amount,
         Err(e) => {
-            println!("Error restoring keys: {}", e);
+            println!("Error: {}", e);
             return;
         }
     };
+    let url = rpc_url.unwrap_or_else(|| "http://localhost:8899".to_string());
+    let client = RpcClient::new(url);
 
-    let node_info = match client.get_node_info().await {
-        Ok(info) => info,
+    let keypair = match load_wallet_keypair("client_wallets.json", &from) {
+        Ok(kp) => kp,
         Err(e) => {
-            println!("Error fetching node info: {}", e);
+            println!("Error: {}", e);
             return;
         }
     };
-    let head_hash = node_info["head_hash"]
-        .as_str()
-        .map(|s| s.to_string())
-        .unwrap_or_default();
-    let height = node_info["height"].as_u64().unwrap_or(0);
 
-    let mut header = BlockHeader {
-        index: height,
-        block_type: BlockType::Burn {
-            vault_id: vault_id.clone(),
-            collateral_asset: "Legacy".to_string(), // Placeholder
-            compass_asset: asset.clone(),
-            burn_amount: amount,
-            redeemer: from.clone(),
-            destination_address: dest_addr.clone(),
-            fee: 0,
-        },
-        proposer: from.clone(),
-        signature_hex: String::new(),
-        prev_hash: head_hash,
-        hash: String::new(),
-        timestamp: Utc::now().timestamp() as u64,
-    };
-
-    header.hash = header.calculate_hash().expect("Failed to calculate hash");
-    let signature = keypair.sign_hex(header.hash.as_bytes());
-
-    let params = SubmitBurnParams {
+    let req = BurnRequest {
         vault_id,
+        collateral_asset: crate::vault::collateral_ticker(&asset).to_string(),
         compass_asset: asset,
-        burn_amount: amount,
+        burn_amount: amount.units,
         redeemer: from,
         destination_address: dest_addr,
-        fee: 0,
-        signature,
     };
-
-    match client.submit_burn(params).await {
-        Ok(tx_hash) => println!("Burn Submitted! Tx Hash: {}", tx_hash),
+    match submit_burn(&client, &keypair, req).await {
+        Ok(tx_hash) => {
+            println!("Burn Submitted! Tx Hash: {}", tx_hash);
+            report_receipt(&client, &tx_hash).await;
+        }
         Err(e) => println!("Burn Failed: {}", e),
     }
 }
@@ -0,0 +1,104 @@
+use clap::{Subcommand, ValueEnum};
+
+use crate::client::rpc_client::RpcClient;
+use crate::layer3::collective::{pool_propose_message, pool_vote_message, PoolParamChange};
+
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum PoolParam {
+    /// Epochs between retraining runs
+    TrainingCadence,
+    /// Basis points of the vault contributors can claim
+    RevenueSplit,
+    /// Listing price of the pool's ensemble signal, in COMPASS
+    SignalPrice,
+}
+
+#[derive(Subcommand, Debug, Clone)]
+pub enum PoolCommands {
+    /// Show a pool's parameters and proposals
+    Show {
+        pool_id: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+    /// Propose a parameter change (contributors only)
+    Propose {
+        pool_id: String,
+        #[arg(value_enum)]
+        param: PoolParam,
+        value: u64,
+        /// Wallet holding stake in the pool
+        #[arg(long)]
+        wallet: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+    /// Vote on a proposal with your stake
+    Vote {
+        pool_id: String,
+        proposal_id: u64,
+        /// Vote against instead of for
+        #[arg(long)]
+        reject: bool,
+        #[arg(long)]
+        wallet: String,
+        #[arg(long, default_value = "http://127.0.0.1:9000")]
+        node_url: String,
+    },
+}
+
+fn change_for(param: PoolParam, value: u64) -> Result<PoolParamChange, String> {
+    Ok(match param {
+        PoolParam::TrainingCadence => PoolParamChange::TrainingCadenceEpochs(value),
+        PoolParam::RevenueSplit => PoolParamChange::ContributorShareBps(
+            u16::try_from(value).map_err(|_| "Revenue split is in basis points (at most 10000)".to_string())?,
+        ),
+        PoolParam::SignalPrice => PoolParamChange::SignalPrice(value),
+    })
+}
+
+async fn run(cmd: PoolCommands) -> Result<(), String> {
+    match cmd {
+        PoolCommands::Show { pool_id, node_url } => {
+            let gov = RpcClient::new(node_url).get_pool_governance(&pool_id).await?;
+            println!("Pool {}", pool_id);
+            println!("  params: {}", gov["params"]);
+            let proposals = gov["proposals"].as_array().cloned().unwrap_or_default();
+            let tallies = gov["tallies"].as_array().cloned().unwrap_or_default();
+            if proposals.is_empty() {
+                println!("  no proposals");
+            }
+            for (p, t) in proposals.iter().zip(tallies.iter()) {
+                println!(
+                    "  #{} {} {}: yes {} / no {} of {} staked",
+                    p["id"], p["status"].as_str().unwrap_or("?"), p["change"], t["yes"], t["no"], t["total_staked"]
+                );
+            }
+        }
+        PoolCommands::Propose { pool_id, param, value, wallet, node_url } => {
+            let change = change_for(param, value)?;
+            let keypair = crate::cli::tx::load_wallet_keypair("wallets.json", &wallet)?;
+            let client = RpcClient::new(node_url);
+            let gov = client.get_pool_governance(&pool_id).await?;
+            let id = gov["next_proposal_id"].as_u64().ok_or("Node did not report the next proposal id")?;
+            let signature = keypair.sign_hex(pool_propose_message(&pool_id, id, &change).as_bytes());
+            let res = client.propose_pool_change(&pool_id, &keypair.public_key_hex(), &change, &signature).await?;
+            println!("✅ Proposal #{} open until {}", res["proposal_id"], res["closes_at"]);
+        }
+        PoolCommands::Vote { pool_id, proposal_id, reject, wallet, node_url } => {
+            let keypair = crate::cli::tx::load_wallet_keypair("wallets.json", &wallet)?;
+            let signature = keypair.sign_hex(pool_vote_message(&pool_id, proposal_id, !reject).as_bytes());
+            let res = RpcClient::new(node_url)
+                .vote_pool_proposal(&pool_id, proposal_id, &keypair.public_key_hex(), !reject, &signature)
+                .await?;
+            println!("✅ Vote recorded; proposal is {} ({})", res["status"].as_str().unwrap_or("?"), res["tally"]);
+        }
+    }
+    Ok(())
+}
+
+pub async fn handle_pool_command(cmd: PoolCommands) {
+    if let Err(e) = run(cmd).await {
+        println!("❌ {}", e);
+    }
+}
@@ -1,9 +1,82 @@
+use std::collections::HashMap;
 use std::io::{self, Write};
 use std::path::Path;
 use crate::identity::{Identity, NodeRole};
 use crate::crypto::KeyPair;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 
+/// RPC sessions from `compass login`, kept beside `wallets.json`
+pub const SESSION_PATH: &str = "sessions.json";
+
+/// A login to a node's RPC for a password account
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct RpcSession {
+    pub rpc_url: String,
+    pub token: String,
+    pub expires_at: u64,
+}
+
+/// Saved RPC sessions, by account
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct SessionFile {
+    sessions: HashMap<String, RpcSession>,
+}
+
+impl SessionFile {
+    pub fn load(path: &str) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &str) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        std::fs::write(path, json)
+    }
+
+    pub fn insert(&mut self, account: &str, session: RpcSession) {
+        self.sessions.insert(account.to_string(), session);
+    }
+
+    pub fn remove(&mut self, account: &str) -> Option<RpcSession> {
+        self.sessions.remove(account)
+    }
+
+    /// `account`'s session with the node at `rpc_url`, unless it has expired by `now_ms`
+    pub fn live(&self, account: &str, rpc_url: &str, now_ms: u64) -> Option<&RpcSession> {
+        self.sessions
+            .get(account)
+            .filter(|s| s.rpc_url == rpc_url && s.expires_at > now_ms)
+    }
+}
+
+/// Log `account` in at `rpc_url`, asking for its password, and save the session
+pub async fn login(account: &str, rpc_url: String) -> Result<RpcSession, String> {
+    print!("Password for '{}': ", account);
+    io::stdout().flush().map_err(|e| e.to_string())?;
+    let mut password = String::new();
+    io::stdin().read_line(&mut password).map_err(|e| e.to_string())?;
+
+    let client = crate::client::RpcClient::new(rpc_url.clone());
+    let (token, expires_at) = client.login(account, password.trim()).await?;
+    let session = RpcSession { rpc_url, token, expires_at };
+    let mut file = SessionFile::load(SESSION_PATH);
+    file.insert(account, session.clone());
+    file.save(SESSION_PATH).map_err(|e| e.to_string())?;
+    Ok(session)
+}
+
+/// End `account`'s saved session on its node and forget it
+pub async fn logout(account: &str) -> Result<(), String> {
+    let mut file = SessionFile::load(SESSION_PATH);
+    let session = file.remove(account).ok_or_else(|| format!("No saved session for '{}'", account))?;
+    file.save(SESSION_PATH).map_err(|e| e.to_string())?;
+    crate::client::RpcClient::new(session.rpc_url).logout(&session.token).await?;
+    Ok(())
+}
+
 /// User Role for CLI Access Control
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum UserRole {
@@ -248,3 +321,19 @@ impl Session {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_saved_sessions_match_node_and_expiry() {
+        let mut file = SessionFile::default();
+        let session = RpcSession { rpc_url: "http://a:9000".to_string(), token: "t".to_string(), expires_at: 1_000 };
+        file.insert("alice", session.clone());
+        assert_eq!(file.live("alice", "http://a:9000", 999), Some(&session));
+        assert_eq!(file.live("alice", "http://a:9000", 1_000), None);
+        assert_eq!(file.live("alice", "http://b:9000", 999), None);
+        assert_eq!(file.live("bob", "http://a:9000", 999), None);
+    }
+}
@@ -1,110 +1,241 @@
 use crate::block::{BlockHeader, BlockType};
 use crate::client::rpc_client::RpcClient;
-use crate::crypto::KeyPair;
-use crate::wallet::WalletManager;
+use crate::crypto::{KeyPair, Signer};
+use crate::encoding::Amount;
+use crate::wallet::{AddressBook, RecipientCheck, WalletManager};
 use chrono::Utc;
 
+/// Chain position a client-side transaction is signed against
+pub struct ChainTip {
+    pub height: u64,
+    pub head_hash: String,
+}
+
+pub async fn fetch_chain_tip(client: &RpcClient) -> Result<ChainTip, String> {
+    let node_info = client
+        .get_node_info()
+        .await
+        .map_err(|e| format!("Error fetching node info: {}", e))?;
+    Ok(ChainTip {
+        height: node_info["height"].as_u64().unwrap_or(0),
+        head_hash: node_info["head_hash"]
+            .as_str()
+            .map(|s| s.to_string())
+            .unwrap_or_default(),
+    })
+}
+
+/// Poll for a submitted transaction's receipt for up to ~10 seconds and report
+/// whether it executed
+pub async fn report_receipt(client: &RpcClient, tx_hash: &str) {
+    for _ in 0..20 {
+        match client.get_transaction_receipt(tx_hash).await {
+            Ok(Some(receipt)) => {
+                match (receipt.status, receipt.block_height) {
+                    (crate::storage::TxStatus::Confirmed, Some(height)) => println!(
+                        "✅ Executed at height {} (fee {} Compass, {} state changes)",
+                        height,
+                        crate::encoding::format_units(receipt.fee_paid, crate::encoding::asset_decimals("Compass")),
+                        receipt.logs.len()
+                    ),
+                    _ => println!("❌ Not executed: {}", receipt.error.unwrap_or_else(|| "unknown error".to_string())),
+                }
+                return;
+            }
+            Ok(None) => tokio::time::sleep(std::time::Duration::from_millis(500)).await,
+            Err(e) => {
+                println!("Could not fetch receipt: {}", e);
+                return;
+            }
+        }
+    }
+    println!("Still pending; check later with getTransactionReceipt");
+}
+
+/// Restore the signing key of a wallet stored in `wallets_file`
+pub fn load_wallet_keypair(wallets_file: &str, name: &str) -> Result<KeyPair, String> {
+    let manager = WalletManager::load(wallets_file);
+    let wallet = manager
+        .get_wallet(name)
+        .ok_or_else(|| format!("Wallet '{}' not found in {}", name, wallets_file))?;
+    let mnemonic = wallet
+        .mnemonic
+        .as_ref()
+        .ok_or_else(|| format!("Wallet '{}' does not have a mnemonic (cannot sign)", name))?;
+    KeyPair::from_mnemonic(mnemonic).map_err(|e| format!("Error restoring keys: {}", e))
+}
+
+/// Map a recipient typed into a send flow to the address to pay (a contact label
+/// resolves to its address) and what the address book knows about it.
+pub fn resolve_recipient(book: &AddressBook, to: &str) -> (String, RecipientCheck) {
+    let check = book.check_recipient(to);
+    let address = match &check {
+        RecipientCheck::Known(contact) => contact.address.clone(),
+        RecipientCheck::Unknown { .. } => to.trim().to_string(),
+    };
+    (address, check)
+}
+
+/// Warning to show before paying a recipient, if any
+pub fn recipient_warning(to: &str, check: &RecipientCheck) -> Option<String> {
+    match check {
+        RecipientCheck::Known(contact) if contact.verified => None,
+        RecipientCheck::Known(contact) => Some(format!("Contact '{}' has not been verified against the name registry.", contact.label)),
+        RecipientCheck::Unknown { similar } if similar.is_empty() => Some(format!("{} is not in your address book.", to)),
+        RecipientCheck::Unknown { similar } => {
+            let names: Vec<String> = similar.iter().map(|c| format!("'{}' ({})", c.label, c.address)).collect();
+            Some(format!("{} is not in your address book but closely resembles {}. Possible typo!", to, names.join(", ")))
+        }
+    }
+}
+
+/// Ask the name registry whether the account `label` names is `address`: its
+/// signing key, or the account name itself. A registered address under some other
+/// label does not count.
+pub async fn verify_contact(client: &RpcClient, label: &str, address: &str) -> Result<bool, String> {
+    let by_label = client.resolve_name(label).await?;
+    Ok(by_label["registered"].as_bool().unwrap_or(false)
+        && (by_label["signing_pubkey"].as_str() == Some(address) || by_label["name"].as_str() == Some(address)))
+}
+
+/// Sign a transfer from `from` with `signer` and submit it, paying the node's
+/// minimum fee plus `priority_fee` (default: the median pending one). Returns the tx hash.
+pub async fn submit_transfer(
+    client: &RpcClient,
+    signer: &dyn Signer,
+    from: &str,
+    to: &str,
+    asset: &str,
+    amount: u64,
+    priority_fee: Option<u64>,
+) -> Result<String, String> {
+    let nonce = client
+        .get_nonce(&signer.public_key_hex())
+        .await
+        .map_err(|e| format!("Error fetching nonce: {}", e))?
+        + 1;
+    let tip = fetch_chain_tip(client).await?;
+
+    let transfer = |fee| BlockType::Transfer {
+        from: from.to_string(),
+        to: to.to_string(),
+        asset: asset.to_string(),
+        amount,
+        nonce,
+        fee,
+    };
+    let estimate = client
+        .estimate_fee(&transfer(0))
+        .await
+        .map_err(|e| format!("Error estimating fee: {}", e))?;
+    let fee = estimate["min_fee"]
+        .as_u64()
+        .unwrap_or(0)
+        .saturating_add(priority_fee.unwrap_or_else(|| estimate["priority_fee"].as_u64().unwrap_or(0)));
+
+    // In this "Tx = Block" model, we construct the header to sign it.
+    let mut header = BlockHeader {
+        index: tip.height, // May be stale by the time it lands; the signature covers content, not position
+        block_type: transfer(fee),
+        proposer: from.to_string(),
+        signature_hex: String::new(), // To be filled
+        prev_hash: tip.head_hash, // This creates the race condition, but it's what we have.
+        hash: String::new(),
+        timestamp: Utc::now().timestamp() as u64,
+        state_root: String::new(),
+        root_attestation: None,
+    };
+
+    // Calculate Hash (Pre-signature), then sign the digest the chain verifies against
+    header.hash = header.calculate_hash().map_err(|e| e.to_string())?;
+    let digest = crate::encoding::hash_from_hex(&header.hash).map_err(|e| e.to_string())?;
+    let signature = signer.sign_hex(&digest)?;
+
+    client
+        .submit_transaction(
+            from,
+            to,
+            asset,
+            amount,
+            nonce,
+            fee,
+            &signature,
+            Some(header.prev_hash),
+            Some(header.timestamp),
+            &signer.public_key_hex(),
+        )
+        .await
+}
+
 pub async fn handle_transfer_command(
     from: String,
     to: String,
-    amount: u64,
+    amount: String,
     asset: String,
     rpc_url: Option<String>,
+    signer_url: Option<String>,
+    priority_fee: Option<u64>,
+    force: bool,
 ) {
-    // 1. Get Wallet / Keys
-    let manager = WalletManager::load("wallets.json");
-    let wallet = match manager.get_wallet(&from) {
-        Some(w) => w,
-        None => {
-            println!("Error: Wallet '{}' not found in client_wallets.json", from);
-            return;
-        }
-    };
-
-    let mnemonic = match &wallet.mnemonic {
-        Some(m) => m,
-        None => {
-            println!(
-                "Error: Wallet '{}' does not have a mnemonic (cannot sign)",
-                from
-            );
+    let amount = match Amount::parse(&amount, &asset) {
+        Ok(amount) => amount,
+        Err(e) => {
+            println!("Error: {}", e);
             return;
         }
     };
-
-    let keypair = match KeyPair::from_mnemonic(mnemonic) {
-        Ok(kp) => kp,
+    // A mistyped cmps1 address fails its checksum here, before anything is signed
+    let to = match crate::encoding::parse_account_id(&to) {
+        Ok(to) => to,
         Err(e) => {
-            println!("Error restoring keys: {}", e);
+            println!("Error: recipient {}: {}", to, e);
             return;
         }
     };
-
-    // 2. Setup RPC
-    let url = rpc_url.unwrap_or_else(|| "http://localhost:8899".to_string());
-    let client = RpcClient::new(url);
-
-    // 3. Get Nonce
-    println!("Fetching nonce...");
-    let nonce = match client.get_nonce(&wallet.public_key).await {
-        Ok(n) => n + 1, // Next nonce
-        Err(e) => {
-            println!("Error fetching nonce: {}", e);
+    let (to, check) = resolve_recipient(&AddressBook::load(crate::wallet::ADDRESS_BOOK_PATH), &to);
+    if let Some(warning) = recipient_warning(&to, &check) {
+        println!("Warning: {}", warning);
+        if matches!(&check, RecipientCheck::Unknown { similar } if !similar.is_empty()) && !force {
+            println!("Refusing to send; re-run with --force if {} is correct.", to);
             return;
         }
-    };
+    }
 
-    // 4. Fetch Chain State (Head Hash)
-    println!("Fetching chain state...");
-    let node_info = match client.get_node_info().await {
-        Ok(info) => info,
+    let mut wallets = WalletManager::load("wallets.json");
+    if let Some(url) = signer_url {
+        println!("Connecting to external signer...");
+        // The signer client blocks on its service; keep it off the async runtime
+        match tokio::task::spawn_blocking(move || crate::wallet::connect_external_signer(&url)).await {
+            Ok(Ok(signer)) => wallets.attach_signer(&from, signer),
+            Ok(Err(e)) => {
+                println!("Error: {}", e);
+                return;
+            }
+            Err(e) => {
+                println!("Error: {}", e);
+                return;
+            }
+        }
+    }
+    let signer = match wallets.signer_for(&from) {
+        Ok(signer) => signer,
         Err(e) => {
-            println!("Error fetching node info: {}", e);
+            println!("Error: {}", e);
             return;
         }
     };
 
-    let head_hash = node_info["head_hash"]
-        .as_str()
-        .map(|s| s.to_string())
-        .unwrap_or_default();
-    let height = node_info["height"].as_u64().unwrap_or(0);
-
-    // 5. Construct Block Header (Intent)
-    // In this "Tx = Block" model, we construct the header to sign it.
-    let mut header = BlockHeader {
-        index: height, // Note: This might be slightly off if block produced since. But sig verifies content.
-        // Actually, checking chain.rs: verify uses header.index? No.
-        // It mainly verifies signature against hash.
-        block_type: BlockType::Transfer {
-            from: from.clone(),
-            to: to.clone(),
-            asset: asset.clone(),
-            amount,
-            nonce,
-            fee: 0, // Default fee 0 for now in CLI
-        },
-        proposer: from.clone(),
-        signature_hex: String::new(), // To be filled
-        prev_hash: head_hash,         // This creates the race condition, but it's what we have.
-        hash: String::new(),
-        timestamp: Utc::now().timestamp() as u64,
-    };
-
-    // Calculate Hash (Pre-signature)
-    header.hash = header.calculate_hash().expect("Failed to calculate hash");
-
-    // 6. Sign Hash
-    let signature = keypair.sign_hex(header.hash.as_bytes());
+    let url = rpc_url.unwrap_or_else(|| "http://localhost:8899".to_string());
+    let sessions = crate::cli::session::SessionFile::load(crate::cli::session::SESSION_PATH);
+    let session = sessions.live(&from, &url, crate::block::current_unix_timestamp_ms()).map(|s| s.token.clone());
+    let client = RpcClient::new(url);
+    client.set_session_token(session);
 
-    // 7. Submit
-    println!("Submitting transfer...");
-    match client
-        .submit_transaction(&from, &to, &asset, amount, nonce, &signature, Some(header.prev_hash), Some(header.timestamp), &keypair.public_key_hex())
-        .await
-    {
+    println!("Submitting transfer of {}...", amount);
+    match submit_transfer(&client, signer.as_ref(), &from, &to, &asset, amount.units, priority_fee).await {
         Ok(tx_hash) => {
             println!("Success! Tx Hash: {}", tx_hash);
+            report_receipt(&client, &tx_hash).await;
         }
         Err(e) => {
             println!("Transaction failed: {}", e);
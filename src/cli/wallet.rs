@@ -1,5 +1,5 @@
 use crate::crypto::KeyPair;
-use crate::wallet::{Wallet, WalletManager, WalletType};
+use crate::wallet::{AddressBook, Wallet, WalletManager, WalletType, ADDRESS_BOOK_PATH};
 use clap::Subcommand;
 
 #[derive(Subcommand)]
@@ -15,9 +15,44 @@ pub enum WalletCommands {
         mnemonic: String,
         #[arg(long)]
         name: String,
+        /// Look for used HD accounts on chain and add them
+        #[arg(long)]
+        scan: bool,
+        /// Unused accounts in a row after which the scan stops
+        #[arg(long, default_value_t = crate::wallet::DEFAULT_GAP_LIMIT)]
+        gap_limit: u32,
+        #[arg(long, default_value = "http://localhost:8899")]
+        rpc_url: String,
+    },
+    /// Derive another account from a wallet's mnemonic (m/44'/7777'/N');
+    /// sign as it with `--from <name>/<N>`
+    Derive {
+        #[arg(long)]
+        name: String,
+        #[arg(long)]
+        index: u32,
     },
     /// List all wallets
     List,
+    /// Write an account's transactions as CSV for bookkeeping and tax reporting
+    ExportHistory {
+        /// Wallet name or address as it appears in blocks
+        #[arg(long)]
+        wallet: String,
+        #[arg(long, value_enum, default_value = "csv")]
+        format: HistoryFormat,
+        /// File to write (default: stdout)
+        #[arg(long)]
+        out: Option<String>,
+        #[arg(long, default_value = "http://localhost:8899")]
+        rpc_url: String,
+    },
+}
+
+#[derive(Clone, Copy, Debug, clap::ValueEnum)]
+pub enum HistoryFormat {
+    /// Columns described in `crate::export`
+    Csv,
 }
 
 #[derive(Subcommand)]
@@ -34,7 +69,75 @@ pub enum AccountCommands {
     },
 }
 
-pub fn handle_wallet_command(cmd: WalletCommands) {
+#[derive(Subcommand)]
+pub enum ContactCommands {
+    /// Save a recipient under a label
+    Add {
+        #[arg(long)]
+        label: String,
+        #[arg(long)]
+        address: String,
+        #[arg(long, default_value = "")]
+        note: String,
+        /// Check the address against the node's name registry and mark it verified
+        #[arg(long)]
+        verify: bool,
+        #[arg(long, default_value = "http://localhost:8899")]
+        rpc_url: String,
+    },
+    /// Delete a contact
+    Remove {
+        #[arg(long)]
+        label: String,
+    },
+    /// List saved contacts
+    List,
+}
+
+/// Fetch `wallet`'s whole activity from the account history index, oldest first
+async fn fetch_activity(client: &crate::client::RpcClient, wallet: &str) -> Result<Vec<crate::export::ActivityRecord>, String> {
+    const PAGE: usize = 100;
+    let mut records = Vec::new();
+    let mut page = 0;
+    loop {
+        let (mut rows, total) = client.get_account_activity(wallet, page, PAGE).await?;
+        records.append(&mut rows);
+        page += 1;
+        if page * PAGE >= total {
+            break;
+        }
+    }
+    records.reverse();
+    Ok(records)
+}
+
+/// Used HD accounts of `mnemonic`: those with any activity on the node at `rpc_url`
+async fn scan_accounts(mnemonic: &str, gap_limit: u32, rpc_url: String) -> Result<Vec<crate::wallet::DerivedAccount>, String> {
+    let client = crate::client::RpcClient::new(rpc_url);
+    crate::wallet::scan_used_accounts(mnemonic, gap_limit, |address| {
+        let client = &client;
+        async move {
+            let (_, total) = client.get_account_activity(&address, 0, 1).await?;
+            Ok(total > 0)
+        }
+    })
+    .await
+}
+
+async fn export_history(wallet: &str, out: Option<String>, rpc_url: String) -> Result<usize, String> {
+    let records = fetch_activity(&crate::client::RpcClient::new(rpc_url), wallet).await?;
+    let written = match &out {
+        Some(path) => std::fs::File::create(path).and_then(|file| {
+            let mut writer = std::io::BufWriter::new(file);
+            crate::export::write_activity_csv(&records, &mut writer)
+        }),
+        None => crate::export::write_activity_csv(&records, &mut std::io::stdout().lock()),
+    };
+    written.map_err(|e| e.to_string())?;
+    Ok(records.len())
+}
+
+pub async fn handle_wallet_command(cmd: WalletCommands) {
     // For now, load/save from local file "wallets.json" in current dir
     // This is distinct from the Node's wallet manager, but sharing struct for now.
     let mut manager = WalletManager::load("wallets.json");
@@ -46,19 +149,29 @@ pub fn handle_wallet_command(cmd: WalletCommands) {
                 println!("Wallet '{}' created.", name);
                 println!("Mnemonic: {}", mnemonic);
                 println!("Public Key: {}", wallet.public_key);
+                println!("Address: {}", address_of(&wallet.public_key));
                 println!("KEEP THIS SAFE!");
             }
             manager.wallets.insert(wallet.owner.clone(), wallet);
             let _ = manager.save("wallets.json");
         }
-        WalletCommands::Import { mnemonic, name } => {
+        WalletCommands::Import { mnemonic, name, scan, gap_limit, rpc_url } => {
             // Validate mnemonic
             match KeyPair::from_mnemonic(&mnemonic) {
                 Ok(kp) => {
                     let mut wallet = Wallet::new(&name, WalletType::User);
                     // Overwrite with imported keys
-                    wallet.mnemonic = Some(mnemonic);
+                    wallet.mnemonic = Some(mnemonic.clone());
                     wallet.public_key = kp.public_key_hex();
+                    if scan {
+                        match scan_accounts(&mnemonic, gap_limit, rpc_url).await {
+                            Ok(accounts) => {
+                                println!("Found {} used HD accounts.", accounts.len());
+                                wallet.accounts = accounts;
+                            }
+                            Err(e) => println!("Account scan failed ({}); import continues without it.", e),
+                        }
+                    }
                     manager.wallets.insert(wallet.owner.clone(), wallet);
                     let _ = manager.save("wallets.json");
                     println!("Wallet '{}' imported successfully.", name);
@@ -66,9 +179,34 @@ pub fn handle_wallet_command(cmd: WalletCommands) {
                 Err(e) => println!("Failed to import: {}", e),
             }
         }
+        WalletCommands::Derive { name, index } => {
+            let Some(wallet) = manager.get_wallet_mut(&name) else {
+                println!("Wallet '{}' not found", name);
+                return;
+            };
+            match wallet.derive_account(index) {
+                Ok(account) => {
+                    println!("Account {}/{} ({})", name, index, crate::crypto::derivation_path(index));
+                    println!("Public Key: {}", account.public_key);
+                    println!("Address: {}", address_of(&account.public_key));
+                    let _ = manager.save("wallets.json");
+                }
+                Err(e) => println!("Failed to derive: {}", e),
+            }
+        }
         WalletCommands::List => {
             for w in manager.wallets.values() {
-                println!("Name: {}\tAddress: {}", w.owner, w.public_key);
+                println!("Name: {}\tAddress: {}\tPK: {}", w.owner, address_of(&w.public_key), w.public_key);
+                for account in &w.accounts {
+                    println!("  {}/{}\tAddress: {}\tPK: {}", w.owner, account.index, address_of(&account.public_key), account.public_key);
+                }
+            }
+        }
+        WalletCommands::ExportHistory { wallet, format: HistoryFormat::Csv, out, rpc_url } => {
+            // Progress goes to stderr so stdout stays pure CSV
+            match export_history(&wallet, out, rpc_url).await {
+                Ok(n) => eprintln!("✅ Exported {} activity rows for {}", n, wallet),
+                Err(e) => eprintln!("❌ Export failed: {}", e),
             }
         }
     }
@@ -96,3 +234,61 @@ pub fn handle_account_command(cmd: AccountCommands) {
         }
     }
 }
+
+/// `cmps1` form of a wallet's hex public key, for display
+fn address_of(public_key: &str) -> String {
+    crate::encoding::account_bech32_address(public_key).unwrap_or_else(|| public_key.to_string())
+}
+
+pub async fn handle_contact_command(cmd: ContactCommands) {
+    let mut book = AddressBook::load(ADDRESS_BOOK_PATH);
+
+    match cmd {
+        ContactCommands::Add { label, address, note, verify, rpc_url } => {
+            let address = match crate::encoding::parse_account_id(&address) {
+                Ok(address) => address,
+                Err(e) => {
+                    println!("Error: {}", e);
+                    return;
+                }
+            };
+            let verified = if verify {
+                let client = crate::client::RpcClient::new(rpc_url);
+                match crate::cli::tx::verify_contact(&client, &label, &address).await {
+                    Ok(true) => true,
+                    Ok(false) => {
+                        println!("Warning: the name registry does not link '{}' to {}; saving unverified.", label, address);
+                        false
+                    }
+                    Err(e) => {
+                        println!("Could not reach the name registry ({}); saving unverified.", e);
+                        false
+                    }
+                }
+            } else {
+                false
+            };
+            match book.add(&label, &address, &note, verified) {
+                Ok(_) => {
+                    let _ = book.save(ADDRESS_BOOK_PATH);
+                    println!("Contact '{}' saved{}.", label, if verified { " (verified)" } else { "" });
+                }
+                Err(e) => println!("Error: {}", e),
+            }
+        }
+        ContactCommands::Remove { label } => {
+            if book.remove(&label).is_some() {
+                let _ = book.save(ADDRESS_BOOK_PATH);
+                println!("Contact '{}' removed.", label);
+            } else {
+                println!("No contact named '{}'", label);
+            }
+        }
+        ContactCommands::List => {
+            for c in book.contacts() {
+                let mark = if c.verified { "✓" } else { " " };
+                println!("{} {}\t{}\t{}", mark, c.label, c.address, c.note);
+            }
+        }
+    }
+}
@@ -0,0 +1,282 @@
+// Explorer: interactive terminal browser over a node's RPC
+use crate::client::RpcClient;
+use serde_json::{json, Value};
+use std::io::{self, Write};
+
+const HELP: &str = "\
+Commands:
+  blocks [n]          latest n blocks (default 10)
+  block <height>      one block with its transactions
+  account <address>   balances, nonce and recent history
+  nfts                all model NFTs
+  nft <token_id>      one NFT with its metadata pointer
+  jobs                pending compute jobs and active recurring jobs
+  search <text>       blocks, accounts, token and NFT names
+  audit [n]           latest n audit log entries (default 20) and whether the log verifies
+  help | quit";
+
+/// One line typed at the explorer prompt
+#[derive(Debug, PartialEq)]
+pub enum ExplorerCommand {
+    Blocks(u32),
+    Block(u64),
+    Account(String),
+    Nfts,
+    Nft(String),
+    Jobs,
+    Search(String),
+    Audit(u32),
+    Help,
+    Quit,
+}
+
+impl ExplorerCommand {
+    pub fn parse(line: &str) -> Result<Self, String> {
+        let line = line.trim();
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let rest = rest.trim();
+        let required = |what: &str| {
+            if rest.is_empty() { Err(format!("Usage: {} <{}>", word, what)) } else { Ok(rest.to_string()) }
+        };
+
+        match word.to_lowercase().as_str() {
+            "blocks" | "b" if rest.is_empty() => Ok(Self::Blocks(10)),
+            "blocks" | "b" => rest.parse().map(Self::Blocks).map_err(|_| format!("Not a count: {}", rest)),
+            "block" => required("height")?.parse().map(Self::Block).map_err(|_| format!("Not a height: {}", rest)),
+            "account" | "a" => required("address").map(Self::Account),
+            "nfts" => Ok(Self::Nfts),
+            "nft" => required("token_id").map(Self::Nft),
+            "jobs" | "j" => Ok(Self::Jobs),
+            "search" | "s" | "/" => required("text").map(Self::Search),
+            "audit" if rest.is_empty() => Ok(Self::Audit(20)),
+            "audit" => rest.parse().map(Self::Audit).map_err(|_| format!("Not a count: {}", rest)),
+            "help" | "h" | "?" | "" => Ok(Self::Help),
+            "quit" | "q" | "exit" => Ok(Self::Quit),
+            other => Err(format!("Unknown command '{}' (try 'help')", other)),
+        }
+    }
+}
+
+pub struct Explorer {
+    client: RpcClient,
+}
+
+impl Explorer {
+    pub fn new(rpc_url: String) -> Self {
+        Self { client: RpcClient::new(rpc_url) }
+    }
+
+    /// Read commands until `quit` or end of input
+    pub async fn run(&self) {
+        println!("🔎 Compass Explorer ({})", self.client.url);
+        match self.client.call_method::<Value, Value>("getChainHeight", json!(null)).await {
+            Ok(v) => println!("   Chain height: {}", v["height"]),
+            Err(e) => println!("⚠️  Node not reachable: {}", e),
+        }
+        println!("{}", HELP);
+
+        loop {
+            print!("\nexplorer> ");
+            io::stdout().flush().ok();
+            let mut line = String::new();
+            if io::stdin().read_line(&mut line).unwrap_or(0) == 0 {
+                break;
+            }
+            match ExplorerCommand::parse(&line) {
+                Ok(ExplorerCommand::Quit) => break,
+                Ok(ExplorerCommand::Help) => println!("{}", HELP),
+                Ok(cmd) => {
+                    if let Err(e) = self.execute(cmd).await {
+                        println!("❌ {}", e);
+                    }
+                }
+                Err(e) => println!("❌ {}", e),
+            }
+        }
+    }
+
+    async fn execute(&self, cmd: ExplorerCommand) -> Result<(), String> {
+        match cmd {
+            ExplorerCommand::Blocks(count) => {
+                let blocks: Value = self.client.call_method("getLatestBlocks", json!({ "count": count })).await?;
+                println!("{:>8}  {:<16}  {:<12}  {:>4}  proposer", "height", "hash", "type", "txs");
+                for block in blocks.as_array().into_iter().flatten() {
+                    print_block_row(block);
+                }
+            }
+            ExplorerCommand::Block(height) => {
+                let block: Value = self.client.call_method("getBlock", json!({ "height": height })).await?;
+                print_block(&block);
+            }
+            ExplorerCommand::Account(address) => {
+                // Balances, nonce and recent history in one round trip
+                let mut results = self.client.call_batch(&[
+                    ("getAccountInfo", json!({ "wallet_id": address })),
+                    ("getNonce", json!(address)),
+                    ("getAccountHistory", json!({ "wallet_id": address, "limit": 10 })),
+                ]).await?.into_iter();
+                let info = results.next().unwrap_or(Err("No response".to_string()))?;
+                println!("Account {}", address);
+                if let Ok(nonce) = results.next().unwrap_or(Err(String::new())) {
+                    println!("  nonce: {}", nonce["nonce"]);
+                }
+                match info.get("balances").and_then(|b| b.as_object()) {
+                    Some(balances) if !balances.is_empty() => {
+                        for (asset, amount) in balances {
+                            println!("  {:<24} {}", asset, amount);
+                        }
+                    }
+                    _ => println!("  (no balances)"),
+                }
+                for tx in info["pending_transactions"].as_array().into_iter().flatten() {
+                    println!("  pending  {}  nonce {}", short(str_field(tx, "tx_hash")), tx["nonce"]);
+                }
+                if let Ok(history) = results.next().unwrap_or(Err(String::new())) {
+                    println!("  history ({} blocks):", history["total"]);
+                    for entry in history["entries"].as_array().into_iter().flatten() {
+                        println!("    {:>8}  {}", entry["height"], str_field(entry, "summary"));
+                    }
+                }
+            }
+            ExplorerCommand::Nfts => {
+                let nfts: Value = self.client.call_method("getAllNFTs", json!(null)).await?;
+                let nfts = nfts.as_array().cloned().unwrap_or_default();
+                println!("{} NFTs", nfts.len());
+                for nft in &nfts {
+                    println!("  {:<36}  {:<28}  owner {}", str_field(nft, "token_id"), str_field(nft, "name"), short(str_field(nft, "current_owner")));
+                }
+            }
+            ExplorerCommand::Nft(token_id) => {
+                let mut results = self.client.call_batch(&[
+                    ("getAllNFTs", json!(null)),
+                    ("getNFTMetadata", json!({ "token_id": token_id })),
+                ]).await?.into_iter();
+                let all = results.next().unwrap_or(Err("No response".to_string()))?;
+                let nft = all.as_array().into_iter().flatten()
+                    .find(|n| n["token_id"] == token_id.as_str())
+                    .ok_or_else(|| format!("NFT {} not found", token_id))?;
+                println!("{}", serde_json::to_string_pretty(nft).unwrap_or_default());
+                if let Ok(meta) = results.next().unwrap_or(Err(String::new())) {
+                    println!("metadata: {}", meta);
+                }
+            }
+            ExplorerCommand::Jobs => {
+                let mut results = self.client.call_batch(&[
+                    ("getPendingComputeJobs", json!({})),
+                    ("getRecurringJobs", json!(null)),
+                ]).await?.into_iter();
+                let compute = results.next().unwrap_or(Err("No response".to_string()))?;
+                let compute = compute.as_array().cloned().unwrap_or_default();
+                println!("Pending compute jobs: {}", compute.len());
+                for job in &compute {
+                    println!("  {:<36}  {:<24}  reward {}", str_field(job, "job_id"), str_field(job, "model_id"), job["reward_amount"]);
+                }
+                let recurring = results.next().unwrap_or(Err("No response".to_string()))?;
+                let recurring = recurring.as_array().cloned().unwrap_or_default();
+                println!("Active recurring jobs: {}", recurring.len());
+                for job in &recurring {
+                    println!(
+                        "  {:<36}  {:<12}  {}/{} updates  worker {}",
+                        str_field(job, "job_id"), str_field(job, "ticker"),
+                        job["completed_updates"], job["total_updates_required"],
+                        short(job["assigned_worker"].as_str().unwrap_or("-")),
+                    );
+                }
+            }
+            ExplorerCommand::Search(text) => {
+                let found: Value = self.client.call_method("search", json!({ "query": text, "limit": 50 })).await?;
+                let hits = found["results"].as_array().cloned().unwrap_or_default();
+                println!("{} matches (showing {})", found["total"], hits.len());
+                for hit in &hits {
+                    println!("  {:<10}  {:<36}  {}", str_field(hit, "kind"), str_field(hit, "id"), str_field(hit, "label"));
+                }
+            }
+            ExplorerCommand::Audit(count) => {
+                let log: Value = self.client.call_method("getAuditLog", json!({ "count": count })).await?;
+                let entries = log["entries"].as_array().cloned().unwrap_or_default();
+                match log["first_invalid_seq"].as_u64() {
+                    Some(seq) => println!("⚠️  Audit log broken at entry {} ({} entries)", seq, log["total"]),
+                    None => println!("Audit log: {} entries, hash chain intact", log["total"]),
+                }
+                println!("{:>6}  {:<20}  {:<20}  {:<16}  details", "seq", "time", "action", "actor");
+                for entry in &entries {
+                    let time = entry["timestamp"]
+                        .as_i64()
+                        .and_then(|t| chrono::DateTime::from_timestamp(t, 0))
+                        .map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+                        .unwrap_or_else(|| "-".to_string());
+                    println!(
+                        "{:>6}  {:<20}  {:<20}  {:<16}  {}",
+                        entry["seq"], time, str_field(entry, "action"), short(str_field(entry, "actor")), str_field(entry, "details"),
+                    );
+                }
+            }
+            ExplorerCommand::Help | ExplorerCommand::Quit => {}
+        }
+        Ok(())
+    }
+}
+
+fn str_field<'a>(v: &'a Value, key: &str) -> &'a str {
+    v.get(key).and_then(|f| f.as_str()).unwrap_or("-")
+}
+
+/// First 16 characters of a hash or key
+fn short(s: &str) -> &str {
+    s.get(..16).unwrap_or(s)
+}
+
+fn block_type(header: &Value) -> String {
+    match header.get("block_type") {
+        Some(Value::Object(map)) => map.keys().next().cloned().unwrap_or_default(),
+        Some(Value::String(s)) => s.clone(),
+        _ => "-".to_string(),
+    }
+}
+
+fn print_block_row(block: &Value) {
+    let header = &block["header"];
+    println!(
+        "{:>8}  {:<16}  {:<12}  {:>4}  {}",
+        header["index"], short(str_field(header, "hash")), block_type(header),
+        block["transactions"].as_array().map(|t| t.len()).unwrap_or(0),
+        short(str_field(header, "proposer")),
+    );
+}
+
+fn print_block(block: &Value) {
+    let header = &block["header"];
+    println!("Block {}", header["index"]);
+    println!("  hash:      {}", str_field(header, "hash"));
+    println!("  prev_hash: {}", str_field(header, "prev_hash"));
+    println!("  proposer:  {}", str_field(header, "proposer"));
+    println!("  timestamp: {}", header["timestamp"]);
+    println!("  type:      {}", block_type(header));
+    let txs = block["transactions"].as_array().cloned().unwrap_or_default();
+    println!("  transactions: {}", txs.len());
+    for raw in &txs {
+        // Raw canonical bytes; the tx hash is what getTransactionStatus takes
+        let bytes: Vec<u8> = serde_json::from_value(raw.clone()).unwrap_or_default();
+        println!("    {}  ({} bytes)", crate::encoding::hash_to_hex(&crate::encoding::sha256(&bytes)), bytes.len());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_explorer_commands() {
+        assert_eq!(ExplorerCommand::parse("blocks"), Ok(ExplorerCommand::Blocks(10)));
+        assert_eq!(ExplorerCommand::parse(" b 25 "), Ok(ExplorerCommand::Blocks(25)));
+        assert_eq!(ExplorerCommand::parse("block 7"), Ok(ExplorerCommand::Block(7)));
+        assert_eq!(ExplorerCommand::parse("search Compass:Alice"), Ok(ExplorerCommand::Search("Compass:Alice".into())));
+        assert_eq!(ExplorerCommand::parse("s btc model"), Ok(ExplorerCommand::Search("btc model".into())));
+        assert_eq!(ExplorerCommand::parse("audit"), Ok(ExplorerCommand::Audit(20)));
+        assert_eq!(ExplorerCommand::parse("audit 5"), Ok(ExplorerCommand::Audit(5)));
+        assert_eq!(ExplorerCommand::parse("QUIT"), Ok(ExplorerCommand::Quit));
+        assert!(ExplorerCommand::parse("block tip").is_err());
+        assert!(ExplorerCommand::parse("account").is_err());
+        assert!(ExplorerCommand::parse("frobnicate").is_err());
+    }
+}
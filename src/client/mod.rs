@@ -2,6 +2,9 @@
 pub mod rpc_client;
 pub mod worker;
 pub mod price_fetcher;
+pub mod watchtower;
+pub mod explorer;
+pub mod scheduler;
 mod oracle_rpc; // Oracle verification RPC extensions
 
 pub use rpc_client::RpcClient;
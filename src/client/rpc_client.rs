@@ -7,6 +7,8 @@ pub struct RpcClient {
     pub(super) url: String,
     pub(super) client: Client,
     pub(super) request_id: AtomicU64,
+    /// Sent with `submitTransaction`; set by `login`
+    session_token: std::sync::Mutex<Option<String>>,
 }
 
 impl RpcClient {
@@ -15,9 +17,41 @@ impl RpcClient {
             url,
             client: Client::new(),
             request_id: AtomicU64::new(1),
+            session_token: std::sync::Mutex::new(None),
         }
     }
 
+    /// Present `token` (from an earlier `login`) with transfers
+    pub fn set_session_token(&self, token: Option<String>) {
+        *self.session_token.lock().unwrap_or_else(|e| e.into_inner()) = token;
+    }
+
+    fn session_token(&self) -> Option<String> {
+        self.session_token.lock().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Start a session for a password account; returns (token, expires_at ms) and
+    /// uses the token for this client's transfers
+    pub async fn login(&self, account: &str, password: &str) -> Result<(String, u64), String> {
+        let res = self.send_request("login", json!({ "account": account, "password": password })).await?;
+        let token = res["token"].as_str().ok_or("login returned no token")?.to_string();
+        let expires_at = res["expires_at"].as_u64().unwrap_or_default();
+        self.set_session_token(Some(token.clone()));
+        Ok((token, expires_at))
+    }
+
+    /// End the session `token`; false if it had already ended
+    pub async fn logout(&self, token: &str) -> Result<bool, String> {
+        let res = self.send_request("logout", json!({ "token": token })).await?;
+        Ok(res["revoked"].as_bool().unwrap_or(false))
+    }
+
+    /// End every session of `account`; returns how many there were
+    pub async fn revoke_sessions(&self, account: &str, password: &str) -> Result<u64, String> {
+        let res = self.send_request("revokeSessions", json!({ "account": account, "password": password })).await?;
+        Ok(res["revoked"].as_u64().unwrap_or(0))
+    }
+
     pub async fn get_balance(&self, wallet_id: &str, asset: &str) -> Result<u64, String> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst);
 
@@ -60,7 +94,7 @@ impl RpcClient {
         let request = json!({
             "jsonrpc": "2.0",
             "method": "getNonce",
-            "params": wallet_id,
+            "params": { "wallet_id": wallet_id },
             "id": id,
         });
 
@@ -154,6 +188,68 @@ impl RpcClient {
         }
     }
 
+    /// Send several calls in one JSON-RPC batch. Results come back in call order,
+    /// each either the call's `result` or its error message.
+    pub async fn call_batch(
+        &self,
+        calls: &[(&str, serde_json::Value)],
+    ) -> Result<Vec<Result<serde_json::Value, String>>, String> {
+        let first_id = self.request_id.fetch_add(calls.len() as u64, Ordering::SeqCst);
+        let request: Vec<serde_json::Value> = calls
+            .iter()
+            .enumerate()
+            .map(|(i, (method, params))| json!({
+                "jsonrpc": "2.0",
+                "method": method,
+                "params": params,
+                "id": first_id + i as u64,
+            }))
+            .collect();
+
+        let response = self.client.post(&self.url)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| format!("Network error: {}", e))?;
+
+        let json: serde_json::Value = response.json().await
+            .map_err(|e| format!("Parse error: {}", e))?;
+
+        // The whole batch was refused (empty, too large, or an older node)
+        let responses = match json {
+            serde_json::Value::Array(responses) => responses,
+            other => return Err(format!("RPC Error: {}", other.get("error").unwrap_or(&other))),
+        };
+
+        let mut results = vec![Err("No response for call".to_string()); calls.len()];
+        for resp in responses {
+            let slot = resp["id"].as_u64()
+                .and_then(|id| id.checked_sub(first_id))
+                .map(|i| i as usize)
+                .filter(|i| *i < calls.len());
+            if let Some(i) = slot {
+                results[i] = match resp.get("error") {
+                    Some(err) => Err(format!("RPC Error: {}", err)),
+                    None => resp.get("result").cloned().ok_or_else(|| "No result in response".to_string()),
+                };
+            }
+        }
+        Ok(results)
+    }
+
+    /// Look up a registered account name: `registered`, `signing_pubkey`, `multisig`
+    pub async fn resolve_name(&self, name: &str) -> Result<serde_json::Value, String> {
+        self.call_method("resolveName", json!({ "name": name })).await
+    }
+
+    /// Balance proof and the block height it was taken at
+    pub async fn get_proof(&self, wallet_id: &str, asset: &str) -> Result<(crate::merkle::StateProof, u64), String> {
+        let resp: serde_json::Value = self.call_method("getProof", json!({ "wallet_id": wallet_id, "asset": asset })).await?;
+        let proof = serde_json::from_value(resp["proof"].clone()).map_err(|e| format!("Bad proof: {}", e))?;
+        let height = resp["height"].as_u64().ok_or("Missing proof height")?;
+        Ok((proof, height))
+    }
+
     pub async fn get_account_info(&self, wallet_id: &str) -> Result<serde_json::Value, String> {
         let id = self.request_id.fetch_add(1, Ordering::SeqCst);
 
@@ -194,6 +290,7 @@ impl RpcClient {
         asset: &str,
         amount: u64,
         nonce: u64,
+        fee: u64,
         signature: &str,
         prev_hash: Option<String>,
         timestamp: Option<u64>,
@@ -210,10 +307,12 @@ impl RpcClient {
                 "asset": asset,
                 "amount": amount,
                 "nonce": nonce,
+                "fee": fee,
                 "signature": signature,
                 "prev_hash": prev_hash,
                 "timestamp": timestamp,
-                "public_key": public_key
+                "public_key": public_key,
+                "session_token": self.session_token()
             },
             "id": id,
         });
@@ -388,11 +487,248 @@ impl RpcClient {
         serde_json::from_value(res).map_err(|e| format!("Parse error: {}", e))
     }
 
-    pub async fn get_oracle_prices(&self) -> Result<std::collections::HashMap<String, f64>, String> {
+    /// Submit a transfer whose fee is paid by a sponsor. Returns the tx hash.
+    pub async fn submit_sponsored_transaction(
+        &self,
+        params: &crate::rpc::types::SubmitSponsoredTransferParams,
+    ) -> Result<String, String> {
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        let res = self.send_request("submitSponsoredTransaction", params).await?;
+        Ok(res["tx_hash"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Submit a signed envelope. Returns the tx hash.
+    pub async fn submit_signed_transaction(&self, tx: &crate::network::SignedTransaction) -> Result<String, String> {
+        let params = serde_json::to_value(tx).map_err(|e| e.to_string())?;
+        let res = self.send_request("submitSignedTransaction", params).await?;
+        Ok(res["tx_hash"].as_str().unwrap_or("").to_string())
+    }
+
+    pub async fn register_validator(
+        &self,
+        params: &crate::rpc::types::RegisterValidatorParams,
+    ) -> Result<String, String> {
+        let params = serde_json::to_value(params).map_err(|e| e.to_string())?;
+        let res = self.send_request("registerValidator", params).await?;
+        Ok(res["tx_hash"].as_str().unwrap_or("").to_string())
+    }
+
+    /// Pending / Confirmed / Failed (with block info) or Unknown for a submitted tx hash
+    pub async fn get_transaction_status(&self, tx_hash: &str) -> Result<serde_json::Value, String> {
+        self.send_request("getTransactionStatus", json!({ "tx_hash": tx_hash })).await
+    }
+
+    /// Minimum fee for `block_type` under the node's fee schedule, with the median
+    /// pending priority fee as `priority_fee`
+    pub async fn estimate_fee(&self, block_type: &crate::block::BlockType) -> Result<serde_json::Value, String> {
+        self.send_request("estimateFee", json!({ "block_type": block_type })).await
+    }
+
+    /// One page of an account's activity rows, newest first, and the number of
+    /// blocks in its history
+    pub async fn get_account_activity(
+        &self,
+        wallet_id: &str,
+        page: usize,
+        limit: usize,
+    ) -> Result<(Vec<crate::export::ActivityRecord>, usize), String> {
+        let res = self
+            .send_request("getAccountActivity", json!({ "wallet_id": wallet_id, "page": page, "limit": limit }))
+            .await?;
+        let records = serde_json::from_value(res["records"].clone()).map_err(|e| format!("Parse error: {}", e))?;
+        Ok((records, res["total"].as_u64().unwrap_or(0) as usize))
+    }
+
+    /// A model pool's parameters, proposals and tallies
+    pub async fn get_pool_governance(&self, pool_id: &str) -> Result<serde_json::Value, String> {
+        self.send_request("getPoolGovernance", json!({ "pool_id": pool_id })).await
+    }
+
+    pub async fn propose_pool_change(
+        &self,
+        pool_id: &str,
+        proposer: &str,
+        change: &crate::layer3::collective::PoolParamChange,
+        signature: &str,
+    ) -> Result<serde_json::Value, String> {
+        let params = json!({ "pool_id": pool_id, "proposer": proposer, "change": change, "signature": signature });
+        self.send_request("proposePoolChange", params).await
+    }
+
+    pub async fn vote_pool_proposal(
+        &self,
+        pool_id: &str,
+        proposal_id: u64,
+        voter: &str,
+        approve: bool,
+        signature: &str,
+    ) -> Result<serde_json::Value, String> {
+        let params = json!({
+            "pool_id": pool_id,
+            "proposal_id": proposal_id,
+            "voter": voter,
+            "approve": approve,
+            "signature": signature
+        });
+        self.send_request("votePoolProposal", params).await
+    }
+
+    pub async fn get_earnings(&self, worker: &str) -> Result<serde_json::Value, String> {
+        self.send_request("getEarnings", json!({ "worker": worker })).await
+    }
+
+    /// An account's vesting schedule, or every schedule when `account` is None
+    pub async fn get_vesting_schedule(&self, account: Option<&str>) -> Result<serde_json::Value, String> {
+        self.send_request("getVestingSchedule", json!({ "account": account })).await
+    }
+
+    /// Circulating, staked, escrowed and burned supply, and the emission schedule's progress
+    pub async fn get_supply_info(&self) -> Result<crate::rpc::types::SupplyInfo, String> {
+        self.call_method("getSupplyInfo", json!({})).await
+    }
+
+    /// The treasury's balance, fee and slash shares, proposals and spends
+    pub async fn get_treasury(&self) -> Result<serde_json::Value, String> {
+        self.send_request("getTreasury", json!({})).await
+    }
+
+    /// Resting bids and asks of `pair` ("BASE/QUOTE"), `depth` price levels per side
+    pub async fn get_order_book(&self, pair: &str, depth: Option<usize>) -> Result<serde_json::Value, String> {
+        self.send_request("getOrderBook", json!({ "pair": pair, "depth": depth })).await
+    }
+
+    /// Last price and 24h volume, high and low of `pair`
+    pub async fn get_ticker(&self, pair: &str) -> Result<crate::market::Ticker, String> {
+        self.call_method("getTicker", json!({ "pair": pair })).await
+    }
+
+    /// OHLCV candles of `pair`, e.g. `interval` "1h" over `range` "7d"
+    pub async fn get_candles(&self, pair: &str, interval: &str, range: Option<&str>) -> Result<serde_json::Value, String> {
+        self.send_request("getCandles", json!({ "pair": pair, "interval": interval, "range": range })).await
+    }
+
+    /// Maker and taker fee rates, and the volume and fees of `pair` or of every traded pair
+    pub async fn get_market_stats(&self, pair: Option<&str>) -> Result<serde_json::Value, String> {
+        self.send_request("getMarketStats", json!({ "pair": pair })).await
+    }
+
+    pub async fn claim_earnings(&self, worker: &str, amount: u64, nonce: u64, signature: &str) -> Result<serde_json::Value, String> {
+        let params = json!({ "worker": worker, "amount": amount, "nonce": nonce, "signature": signature });
+        self.send_request("claimEarnings", params).await
+    }
+
+    /// Receipt of an executed transaction; `None` while it hasn't run
+    pub async fn get_transaction_receipt(&self, tx_hash: &str) -> Result<Option<crate::storage::Receipt>, String> {
+        let res = self.send_request("getTransactionReceipt", json!({ "tx_hash": tx_hash })).await?;
+        serde_json::from_value(res).map_err(|e| format!("Parse error: {}", e))
+    }
+
+    pub async fn get_headers(&self, start: Option<u64>, count: Option<u64>) -> Result<Vec<crate::block::BlockHeader>, String> {
+        let params = serde_json::json!({
+            "start": start,
+            "count": count
+        });
+        let res = self.send_request("getHeaders", params).await?;
+        serde_json::from_value(res).map_err(|e| format!("Parse error: {}", e))
+    }
+
+    pub async fn get_proof_of_reserve(&self) -> Result<crate::rpc::types::ProofOfReserve, String> {
+        let res = self.send_request("getProofOfReserve", serde_json::json!(null)).await?;
+        serde_json::from_value(res).map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// Layer 2 checkpoint `sequence` (latest when None), with whether the node's state still matches it
+    pub async fn get_l2_checkpoint(&self, sequence: Option<u64>) -> Result<serde_json::Value, String> {
+        self.call_method("getL2Checkpoint", json!({ "sequence": sequence })).await
+    }
+
+    /// Collateral `entity` has staked and its withdrawals still unbonding
+    pub async fn get_stake_info(&self, entity: &str) -> Result<crate::rpc::types::StakeInfo, String> {
+        self.call_method("getStakeInfo", json!({ "entity": entity })).await
+    }
+
+    /// Release `entity`'s unstaked collateral whose unbonding period has ended;
+    /// `signature` is over `layer2::collateral::withdraw_message(entity, nonce)`
+    pub async fn withdraw_unbonded(&self, entity: &str, nonce: u64, signature: &str) -> Result<serde_json::Value, String> {
+        self.call_method("withdrawUnbonded", json!({ "entity": entity, "nonce": nonce, "signature": signature })).await
+    }
+
+    /// Queue deployment of a WASM contract; `signature` is over `vm::deploy_message`.
+    /// The result carries the address it will have.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn deploy_contract(
+        &self,
+        deployer: &str,
+        code: &[u8],
+        gas_limit: u64,
+        gas_price: u64,
+        nonce: u64,
+        signature: &str,
+    ) -> Result<serde_json::Value, String> {
+        let params = json!({
+            "deployer": deployer, "code": hex::encode(code), "gas_limit": gas_limit, "gas_price": gas_price,
+            "nonce": nonce, "signature": signature
+        });
+        self.call_method("deployContract", params).await
+    }
+
+    /// Queue a call of `method` on `contract`; `signature` is over `vm::call_message`
+    #[allow(clippy::too_many_arguments)]
+    pub async fn call_contract(
+        &self,
+        caller: &str,
+        contract: &str,
+        method: &str,
+        input: &[u8],
+        gas_limit: u64,
+        gas_price: u64,
+        nonce: u64,
+        signature: &str,
+    ) -> Result<serde_json::Value, String> {
+        let params = json!({
+            "caller": caller, "contract": contract, "method": method, "input": hex::encode(input),
+            "gas_limit": gas_limit, "gas_price": gas_price, "nonce": nonce, "signature": signature
+        });
+        self.call_method("callContract", params).await
+    }
+
+    /// Run `method` on `contract` read-only and return its hex encoded output and gas used
+    pub async fn query_contract(&self, contract: &str, method: &str, input: &[u8], gas_limit: Option<u64>) -> Result<serde_json::Value, String> {
+        let params = json!({ "contract": contract, "method": method, "input": hex::encode(input), "gas_limit": gas_limit });
+        self.call_method("queryContract", params).await
+    }
+
+    /// Debt (stability fee included), collateral ratio and fees of one vault
+    pub async fn get_vault_state(&self, vault_id: &str) -> Result<crate::rpc::types::VaultState, String> {
+        self.call_method("getVaultState", json!({ "vault_id": vault_id })).await
+    }
+
+    pub async fn get_oracle_prices(&self) -> Result<std::collections::HashMap<String, crate::oracle::freshness::OraclePriceStatus>, String> {
         let res = self.send_request("getOraclePrices", serde_json::json!(null)).await?;
         serde_json::from_value(res).map_err(|e| format!("Parse error: {}", e))
     }
 
+    /// Submissions and aggregated price of `ticker`'s price round `round`, or of its latest published one
+    pub async fn get_price_round(&self, ticker: &str, round: Option<u64>) -> Result<crate::oracle::aggregation::PriceRound, String> {
+        self.call_method("getPriceRound", json!({ "ticker": ticker, "round": round })).await
+    }
+
+    /// Node-signed price of `ticker`'s round `round`, or of its latest attested one
+    pub async fn get_price_attestation(&self, ticker: &str, round: Option<u64>) -> Result<crate::oracle::feed::PriceAttestation, String> {
+        let res: serde_json::Value = self.call_method("getPriceAttestation", json!({ "ticker": ticker, "round": round })).await?;
+        serde_json::from_value(res["attestation"].clone()).map_err(|e| format!("Parse error: {}", e))
+    }
+
+    /// Withdrawal intents, optionally only "queued" or "released" ones
+    pub async fn get_redemptions(&self, status: Option<&str>, redeemer: Option<&str>) -> Result<serde_json::Value, String> {
+        self.send_request("getRedemptions", json!({ "status": status, "redeemer": redeemer })).await
+    }
+
+    pub async fn submit_redemption_payment(&self, intent_id: &str, external_tx: &str, submitter: &str) -> Result<serde_json::Value, String> {
+        let params = json!({ "intent_id": intent_id, "external_tx": external_tx, "submitter": submitter });
+        self.send_request("submitRedemptionPayment", params).await
+    }
+
     pub async fn get_latest_signal(&self, ticker: &str) -> Result<serde_json::Value, String> {
         let params = serde_json::json!({
             "ticker": ticker,
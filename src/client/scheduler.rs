@@ -0,0 +1,277 @@
+// Worker job scheduler
+//
+// Tracks which jobs this worker has claimed, how many may run at once, and which
+// job classes are paused. The worker console and the dispatcher share one
+// `WorkerScheduler`; both can change limits while jobs are running. Jobs that
+// already ran are remembered so a job still listed as pending by the node (its
+// result not yet in a block) is not picked up twice.
+use std::collections::{BTreeSet, HashMap, HashSet};
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Kinds of work a worker can take on, each pausable on its own
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum JobClass {
+    Oracle,
+    Recurring,
+    Compute,
+}
+
+impl JobClass {
+    pub const ALL: [JobClass; 3] = [JobClass::Oracle, JobClass::Recurring, JobClass::Compute];
+}
+
+impl fmt::Display for JobClass {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JobClass::Oracle => write!(f, "oracle"),
+            JobClass::Recurring => write!(f, "recurring"),
+            JobClass::Compute => write!(f, "compute"),
+        }
+    }
+}
+
+/// Why a job could not be claimed
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClaimError {
+    Paused(JobClass),
+    AtCapacity(usize),
+    AlreadyClaimed,
+}
+
+impl fmt::Display for ClaimError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClaimError::Paused(class) => write!(f, "{} jobs are paused", class),
+            ClaimError::AtCapacity(max) => write!(f, "all {} job slots are busy", max),
+            ClaimError::AlreadyClaimed => write!(f, "job already claimed by this worker"),
+        }
+    }
+}
+
+/// A job currently running on this worker
+#[derive(Debug, Clone)]
+pub struct ActiveJob {
+    pub job_id: String,
+    pub class: JobClass,
+    pub label: String,
+    pub started_at: Instant,
+    /// (done, total) for jobs made of several updates
+    pub progress: Option<(u32, u32)>,
+}
+
+impl ActiveJob {
+    pub fn elapsed(&self) -> Duration {
+        self.started_at.elapsed()
+    }
+}
+
+/// Point-in-time view for display
+#[derive(Debug, Clone)]
+pub struct SchedulerSnapshot {
+    pub active: Vec<ActiveJob>,
+    pub paused: Vec<JobClass>,
+    pub max_concurrency: usize,
+    pub completed: u64,
+    pub failed: u64,
+}
+
+struct Inner {
+    max_concurrency: usize,
+    paused: BTreeSet<JobClass>,
+    active: HashMap<String, ActiveJob>,
+    finished: HashSet<String>,
+    completed: u64,
+    failed: u64,
+}
+
+#[derive(Clone)]
+pub struct WorkerScheduler {
+    inner: Arc<Mutex<Inner>>,
+}
+
+impl WorkerScheduler {
+    pub fn new(max_concurrency: usize) -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Inner {
+                max_concurrency: max_concurrency.max(1),
+                paused: BTreeSet::new(),
+                active: HashMap::new(),
+                finished: HashSet::new(),
+                completed: 0,
+                failed: 0,
+            })),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Inner> {
+        // A panicking job must not take the console down with it
+        self.inner.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Lowering the limit never interrupts running jobs; it only stops new claims
+    pub fn set_concurrency(&self, max: usize) {
+        self.lock().max_concurrency = max.max(1);
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.lock().max_concurrency
+    }
+
+    pub fn pause(&self, class: JobClass) {
+        self.lock().paused.insert(class);
+    }
+
+    pub fn resume(&self, class: JobClass) {
+        self.lock().paused.remove(&class);
+    }
+
+    pub fn is_paused(&self, class: JobClass) -> bool {
+        self.lock().paused.contains(&class)
+    }
+
+    /// Free slots for new jobs
+    pub fn available(&self) -> usize {
+        let inner = self.lock();
+        inner.max_concurrency.saturating_sub(inner.active.len())
+    }
+
+    /// True if the job is running or already ran on this worker
+    pub fn has_seen(&self, job_id: &str) -> bool {
+        let inner = self.lock();
+        inner.active.contains_key(job_id) || inner.finished.contains(job_id)
+    }
+
+    /// Reserve a slot for a job. The slot is released when the handle is finished or dropped.
+    pub fn try_claim(&self, job_id: &str, class: JobClass, label: &str) -> Result<JobHandle, ClaimError> {
+        let mut inner = self.lock();
+        if inner.paused.contains(&class) {
+            return Err(ClaimError::Paused(class));
+        }
+        if inner.active.contains_key(job_id) || inner.finished.contains(job_id) {
+            return Err(ClaimError::AlreadyClaimed);
+        }
+        if inner.active.len() >= inner.max_concurrency {
+            return Err(ClaimError::AtCapacity(inner.max_concurrency));
+        }
+
+        inner.active.insert(
+            job_id.to_string(),
+            ActiveJob {
+                job_id: job_id.to_string(),
+                class,
+                label: label.to_string(),
+                started_at: Instant::now(),
+                progress: None,
+            },
+        );
+        Ok(JobHandle {
+            scheduler: self.clone(),
+            job_id: job_id.to_string(),
+            class,
+            done: false,
+        })
+    }
+
+    pub fn snapshot(&self) -> SchedulerSnapshot {
+        let inner = self.lock();
+        let mut active: Vec<ActiveJob> = inner.active.values().cloned().collect();
+        active.sort_by_key(|j| j.started_at);
+        SchedulerSnapshot {
+            active,
+            paused: inner.paused.iter().copied().collect(),
+            max_concurrency: inner.max_concurrency,
+            completed: inner.completed,
+            failed: inner.failed,
+        }
+    }
+
+    fn release(&self, job_id: &str, succeeded: bool) {
+        let mut inner = self.lock();
+        if inner.active.remove(job_id).is_some() {
+            inner.finished.insert(job_id.to_string());
+            if succeeded {
+                inner.completed += 1;
+            } else {
+                inner.failed += 1;
+            }
+        }
+    }
+}
+
+/// A claimed job slot. Dropping it without `finish` counts the job as failed.
+pub struct JobHandle {
+    scheduler: WorkerScheduler,
+    job_id: String,
+    class: JobClass,
+    done: bool,
+}
+
+impl JobHandle {
+    pub fn job_id(&self) -> &str {
+        &self.job_id
+    }
+
+    pub fn set_progress(&self, done: u32, total: u32) {
+        if let Some(job) = self.scheduler.lock().active.get_mut(&self.job_id) {
+            job.progress = Some((done, total));
+        }
+    }
+
+    /// Whether this job's class has been paused since it was claimed
+    pub fn is_paused(&self) -> bool {
+        self.scheduler.is_paused(self.class)
+    }
+
+    pub fn finish(mut self, succeeded: bool) {
+        self.done = true;
+        self.scheduler.release(&self.job_id, succeeded);
+    }
+}
+
+impl Drop for JobHandle {
+    fn drop(&mut self) {
+        if !self.done {
+            self.scheduler.release(&self.job_id, false);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_claims_respect_capacity_and_pause() {
+        let s = WorkerScheduler::new(1);
+        let a = s.try_claim("a", JobClass::Compute, "model-a").unwrap();
+        assert_eq!(s.try_claim("b", JobClass::Compute, "model-b").err(), Some(ClaimError::AtCapacity(1)));
+
+        s.set_concurrency(2);
+        s.pause(JobClass::Oracle);
+        assert_eq!(s.try_claim("c", JobClass::Oracle, "BTC").err(), Some(ClaimError::Paused(JobClass::Oracle)));
+        let b = s.try_claim("b", JobClass::Compute, "model-b").unwrap();
+        assert_eq!(s.available(), 0);
+
+        a.finish(true);
+        drop(b); // dropped without finishing counts as failed
+        let snap = s.snapshot();
+        assert!(snap.active.is_empty());
+        assert_eq!((snap.completed, snap.failed), (1, 1));
+        assert_eq!(snap.paused, vec![JobClass::Oracle]);
+    }
+
+    #[test]
+    fn test_finished_jobs_are_not_reclaimed() {
+        let s = WorkerScheduler::new(4);
+        let h = s.try_claim("job-1", JobClass::Recurring, "ETH").unwrap();
+        h.set_progress(3, 10);
+        assert_eq!(s.snapshot().active[0].progress, Some((3, 10)));
+        assert_eq!(s.try_claim("job-1", JobClass::Recurring, "ETH").err(), Some(ClaimError::AlreadyClaimed));
+
+        h.finish(true);
+        assert!(s.has_seen("job-1"));
+        assert_eq!(s.try_claim("job-1", JobClass::Recurring, "ETH").err(), Some(ClaimError::AlreadyClaimed));
+    }
+}
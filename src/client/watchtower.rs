@@ -0,0 +1,362 @@
+// Watchtower: header-only follower that independently polices a Compass node
+use crate::block::{BlockHeader, BlockType};
+use crate::client::RpcClient;
+use crate::rpc::types::ProofOfReserve;
+use std::collections::HashMap;
+use std::time::Duration;
+
+pub struct WatchtowerConfig {
+    pub rpc_url: String,
+    pub poll_interval_secs: u64,
+    pub webhook_url: Option<String>,
+    /// Proposer public keys allowed to sign PoH blocks. Empty = accept any key that verifies.
+    pub trusted_proposers: Vec<String>,
+    pub start_height: Option<u64>,
+}
+
+/// Something the watched node did that it should not have
+#[derive(Debug, Clone, PartialEq)]
+pub enum Violation {
+    BrokenLink { index: u64, expected_prev: String, actual_prev: String },
+    HeightGap { expected: u64, actual: u64 },
+    HashMismatch { index: u64, claimed: String, computed: String },
+    BadSignature { index: u64, proposer: String },
+    UntrustedProposer { index: u64, proposer: String },
+    PohTickRegression { index: u64, last_tick: u64, tick: u64 },
+    TimestampRegression { index: u64, last: u64, current: u64 },
+    UnbackedSupply { asset: String, minted: u64 },
+    CollateralWithdrawn { asset: String, before: u64, after: u64, minted_before: u64, minted_after: u64 },
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::BrokenLink { index, expected_prev, actual_prev } => write!(
+                f,
+                "block {} prev_hash {} does not link to previous header {}",
+                index, actual_prev, expected_prev
+            ),
+            Violation::HeightGap { expected, actual } => {
+                write!(f, "expected block {} but received {}", expected, actual)
+            }
+            Violation::HashMismatch { index, claimed, computed } => write!(
+                f,
+                "block {} claims hash {} but header hashes to {}",
+                index, claimed, computed
+            ),
+            Violation::BadSignature { index, proposer } => {
+                write!(f, "block {} signature does not verify for proposer {}", index, proposer)
+            }
+            Violation::UntrustedProposer { index, proposer } => {
+                write!(f, "block {} proposed by untrusted key {}", index, proposer)
+            }
+            Violation::PohTickRegression { index, last_tick, tick } => write!(
+                f,
+                "block {} PoH tick {} does not advance past {}",
+                index, tick, last_tick
+            ),
+            Violation::TimestampRegression { index, last, current } => write!(
+                f,
+                "block {} timestamp {} is earlier than previous {}",
+                index, current, last
+            ),
+            Violation::UnbackedSupply { asset, minted } => {
+                write!(f, "vault {} reports {} minted with zero collateral", asset, minted)
+            }
+            Violation::CollateralWithdrawn { asset, before, after, minted_before, minted_after } => write!(
+                f,
+                "vault {} collateral fell {} -> {} while minted supply went {} -> {}",
+                asset, before, after, minted_before, minted_after
+            ),
+        }
+    }
+}
+
+/// Verification state carried between polls. Pure logic, no I/O.
+#[derive(Default)]
+pub struct HeaderVerifier {
+    pub trusted_proposers: Vec<String>,
+    last_header: Option<BlockHeader>,
+    last_poh_tick: Option<u64>,
+    last_reserves: HashMap<String, (u64, u64)>, // asset -> (collateral, minted)
+}
+
+impl HeaderVerifier {
+    pub fn new(trusted_proposers: Vec<String>) -> Self {
+        Self {
+            trusted_proposers,
+            ..Default::default()
+        }
+    }
+
+    /// Height of the next header we expect to see
+    pub fn next_height(&self) -> Option<u64> {
+        self.last_header.as_ref().map(|h| h.index + 1)
+    }
+
+    pub fn check_header(&mut self, header: &BlockHeader) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        // 1. Continuity with the previous header
+        if let Some(last) = &self.last_header {
+            if header.index != last.index + 1 {
+                violations.push(Violation::HeightGap { expected: last.index + 1, actual: header.index });
+            }
+            if header.prev_hash != last.hash {
+                violations.push(Violation::BrokenLink {
+                    index: header.index,
+                    expected_prev: last.hash.clone(),
+                    actual_prev: header.prev_hash.clone(),
+                });
+            }
+            if header.timestamp < last.timestamp {
+                violations.push(Violation::TimestampRegression {
+                    index: header.index,
+                    last: last.timestamp,
+                    current: header.timestamp,
+                });
+            }
+        }
+
+        // 2. Header integrity
+        match header.calculate_hash() {
+            Ok(computed) if computed != header.hash => violations.push(Violation::HashMismatch {
+                index: header.index,
+                claimed: header.hash.clone(),
+                computed,
+            }),
+            Err(_) => violations.push(Violation::HashMismatch {
+                index: header.index,
+                claimed: header.hash.clone(),
+                computed: String::new(),
+            }),
+            _ => {}
+        }
+
+        // 3. PoH blocks: proposer signature and tick progression
+        if let BlockType::PoH { tick, .. } = &header.block_type {
+            if !self.trusted_proposers.is_empty() && !self.trusted_proposers.contains(&header.proposer) {
+                violations.push(Violation::UntrustedProposer {
+                    index: header.index,
+                    proposer: header.proposer.clone(),
+                });
+            }
+
+            let verified = hex::decode(&header.hash)
+                .map(|raw| crate::crypto::verify_with_pubkey_hex(&raw, &header.signature_hex, &header.proposer))
+                .unwrap_or(false);
+            if !verified {
+                violations.push(Violation::BadSignature {
+                    index: header.index,
+                    proposer: header.proposer.clone(),
+                });
+            }
+
+            if let Some(last_tick) = self.last_poh_tick {
+                if *tick <= last_tick {
+                    violations.push(Violation::PohTickRegression { index: header.index, last_tick, tick: *tick });
+                }
+            }
+            self.last_poh_tick = Some(*tick);
+        }
+
+        self.last_header = Some(header.clone());
+        violations
+    }
+
+    pub fn check_reserves(&mut self, report: &ProofOfReserve) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        for vault in &report.vaults {
+            if vault.minted_supply > 0 && vault.collateral_balance == 0 {
+                violations.push(Violation::UnbackedSupply {
+                    asset: vault.compass_asset.clone(),
+                    minted: vault.minted_supply,
+                });
+            }
+
+            // Collateral may only leave a vault through redemptions, which also shrink supply
+            if let Some((before, minted_before)) = self.last_reserves.get(&vault.compass_asset) {
+                if vault.collateral_balance < *before && vault.minted_supply >= *minted_before {
+                    violations.push(Violation::CollateralWithdrawn {
+                        asset: vault.compass_asset.clone(),
+                        before: *before,
+                        after: vault.collateral_balance,
+                        minted_before: *minted_before,
+                        minted_after: vault.minted_supply,
+                    });
+                }
+            }
+
+            self.last_reserves.insert(
+                vault.compass_asset.clone(),
+                (vault.collateral_balance, vault.minted_supply),
+            );
+        }
+
+        violations
+    }
+}
+
+pub struct Watchtower {
+    config: WatchtowerConfig,
+    client: RpcClient,
+    http: reqwest::Client,
+    verifier: HeaderVerifier,
+}
+
+impl Watchtower {
+    pub fn new(config: WatchtowerConfig) -> Self {
+        let client = RpcClient::new(config.rpc_url.clone());
+        let verifier = HeaderVerifier::new(config.trusted_proposers.clone());
+        Self {
+            config,
+            client,
+            http: reqwest::Client::new(),
+            verifier,
+        }
+    }
+
+    pub async fn run(&mut self) {
+        println!("🗼 Watchtower following {}", self.config.rpc_url);
+        if self.config.trusted_proposers.is_empty() {
+            println!("   ⚠️ No trusted proposers configured: any validly signed PoH block is accepted");
+        }
+
+        let mut next = self.config.start_height;
+        loop {
+            match self.client.get_headers(next, Some(200)).await {
+                Ok(headers) => {
+                    for header in &headers {
+                        for v in self.verifier.check_header(header) {
+                            self.raise(&v).await;
+                        }
+                    }
+                    if let Some(h) = self.verifier.next_height() {
+                        next = Some(h);
+                    }
+                    // Keep paging without sleeping while we are behind
+                    if headers.len() == 200 {
+                        continue;
+                    }
+                }
+                Err(e) => eprintln!("[watchtower] header poll failed: {}", e),
+            }
+
+            match self.client.get_proof_of_reserve().await {
+                Ok(report) => {
+                    for v in self.verifier.check_reserves(&report) {
+                        self.raise(&v).await;
+                    }
+                }
+                Err(e) => eprintln!("[watchtower] reserve poll failed: {}", e),
+            }
+
+            tokio::time::sleep(Duration::from_secs(self.config.poll_interval_secs)).await;
+        }
+    }
+
+    async fn raise(&self, violation: &Violation) {
+        eprintln!("[watchtower] 🚨 VIOLATION: {}", violation);
+
+        if let Some(url) = &self.config.webhook_url {
+            let body = serde_json::json!({
+                "source": "compass-watchtower",
+                "node": self.config.rpc_url,
+                "violation": format!("{:?}", violation),
+                "message": violation.to_string(),
+            });
+            if let Err(e) = self.http.post(url).json(&body).send().await {
+                eprintln!("[watchtower] webhook delivery failed: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+    use crate::rpc::types::VaultReserve;
+
+    fn poh_header(kp: &KeyPair, index: u64, prev_hash: &str, tick: u64) -> BlockHeader {
+        let mut header = BlockHeader {
+            index,
+            block_type: BlockType::PoH {
+                tick,
+                iterations: 1,
+                hash: "00".to_string(),
+                proof: String::new(),
+            },
+            proposer: kp.public_key_hex(),
+            signature_hex: String::new(),
+            prev_hash: prev_hash.to_string(),
+            hash: String::new(),
+            timestamp: 1_000 + index,
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = kp.sign_hex(&hex::decode(&header.hash).unwrap());
+        header
+    }
+
+    #[test]
+    fn test_valid_poh_chain_has_no_violations() {
+        let kp = KeyPair::generate();
+        let mut v = HeaderVerifier::new(vec![kp.public_key_hex()]);
+        let h1 = poh_header(&kp, 1, "genesis", 1);
+        let h2 = poh_header(&kp, 2, &h1.hash, 2);
+        assert!(v.check_header(&h1).is_empty());
+        assert!(v.check_header(&h2).is_empty());
+    }
+
+    #[test]
+    fn test_detects_broken_link_and_foreign_signer() {
+        let kp = KeyPair::generate();
+        let rogue = KeyPair::generate();
+        let mut v = HeaderVerifier::new(vec![kp.public_key_hex()]);
+        let h1 = poh_header(&kp, 1, "genesis", 1);
+        let h2 = poh_header(&rogue, 2, "not-h1", 1);
+        v.check_header(&h1);
+        let violations = v.check_header(&h2);
+        assert!(violations.iter().any(|x| matches!(x, Violation::BrokenLink { .. })));
+        assert!(violations.iter().any(|x| matches!(x, Violation::UntrustedProposer { .. })));
+        assert!(violations.iter().any(|x| matches!(x, Violation::PohTickRegression { .. })));
+    }
+
+    #[test]
+    fn test_detects_collateral_drain() {
+        let mut v = HeaderVerifier::default();
+        let report = |collateral, minted| ProofOfReserve {
+            height: 0,
+            head_hash: None,
+            vaults: vec![VaultReserve {
+                compass_asset: "Compass-LTC".to_string(),
+                collateral_asset: "LTC".to_string(),
+                vault_address: String::new(),
+                collateral_balance: collateral,
+                minted_supply: minted,
+                exchange_rate: "1".to_string(),
+            }],
+        };
+        assert!(v.check_reserves(&report(100, 100)).is_empty());
+        // Redemption: both shrink
+        assert!(v.check_reserves(&report(50, 50)).is_empty());
+        // Collateral leaves without burning supply
+        assert_eq!(v.check_reserves(&report(10, 50)).len(), 1);
+        assert_eq!(
+            v.check_reserves(&report(0, 50)),
+            vec![
+                Violation::UnbackedSupply { asset: "Compass-LTC".to_string(), minted: 50 },
+                Violation::CollateralWithdrawn {
+                    asset: "Compass-LTC".to_string(),
+                    before: 10,
+                    after: 0,
+                    minted_before: 50,
+                    minted_after: 50
+                },
+            ]
+        );
+    }
+}
@@ -4,6 +4,28 @@ use serde::{Deserialize, Serialize};
 pub struct CompassConfig {
     pub node: NodeConfig,
     pub consensus: ConsensusConfig,
+    /// Sign blocks and oracle results through a signing service instead of admin.json
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signer: Option<RemoteSignerConfig>,
+    /// Health thresholds that raise operator alerts
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub alerts: Option<AlertsConfig>,
+    /// Testnet faucet on the RPC port
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub faucet: Option<FaucetConfig>,
+    /// RPC listeners, each with its own method allowlist. When empty the node
+    /// serves every method on `0.0.0.0:<rpc_port>`
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rpc_listeners: Vec<RpcListenerConfig>,
+    /// Certificate for serving RPC over HTTPS; plain HTTP when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tls: Option<TlsConfig>,
+    /// Keep only recent blocks; archive nodes (the default) keep everything
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pruning: Option<PruningConfig>,
+    /// Ethereum endpoint the oracle verifies ETH and ERC-20 vault deposits against
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ethereum: Option<crate::oracle::chains::ethereum::EvmConfig>,
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -16,6 +38,56 @@ pub struct NodeConfig {
     pub identity_file: String,
     #[serde(default = "default_bootnodes")]
     pub bootnodes: Vec<String>,
+    #[serde(default = "default_genesis_file")]
+    pub genesis_file: String,
+    /// Storage engine under `db_path`: "sled" (default) or "rocksdb" (needs the
+    /// `rocksdb` feature; move an existing database over with `compass db migrate`)
+    #[serde(default)]
+    pub db_backend: crate::storage::DbBackend,
+    /// Page cache cap in MiB: sled's page cache or RocksDB's block cache (sled's own
+    /// default is 1 GiB; lower it on SBCs)
+    #[serde(default)]
+    pub db_cache_mb: Option<u64>,
+    /// Flush the database every this many committed blocks (default 0: on the
+    /// interval only; 1 makes every committed block durable before it is announced)
+    #[serde(default)]
+    pub db_flush_every_blocks: Option<u64>,
+    /// Flush the database at least this often, in milliseconds (default 1000)
+    #[serde(default)]
+    pub db_flush_interval_ms: Option<u64>,
+    /// How many nonces past an account's next one the mempool holds (default 16)
+    #[serde(default)]
+    pub future_nonce_window: Option<u64>,
+    /// How long completed job outputs stay readable via getJobResult (default 168 hours)
+    #[serde(default)]
+    pub job_result_retention_hours: Option<u64>,
+    /// Blocks between state snapshots served to syncing peers (default 3600, 0 disables)
+    #[serde(default)]
+    pub snapshot_interval_blocks: Option<u64>,
+    /// Seconds between Layer 2 checkpoints the leader commits (default 600, 0 disables)
+    #[serde(default)]
+    pub l2_checkpoint_interval_secs: Option<u64>,
+    /// Light client: sync block headers only, never execute or produce blocks (`--light`)
+    #[serde(default)]
+    pub light: bool,
+    /// Devnet: deterministic genesis, validator key and fixtures from `crate::fixtures` (`--dev`)
+    #[serde(default)]
+    pub dev: bool,
+    /// Offer TLS 1.3 next to Noise on the p2p port. Certificates come from the node's
+    /// libp2p identity, not `[tls]`; peers without TLS still connect over Noise.
+    #[serde(default)]
+    pub p2p_tls: bool,
+    /// How long an RPC login session lasts, in seconds (default 900)
+    #[serde(default)]
+    pub session_ttl_secs: Option<u64>,
+    /// Deprecated: admit bare payloads (compute jobs, results, orders, ...) that carry
+    /// no signature the node checks. Transfers and other self-signed payloads are
+    /// admitted bare either way; everything else needs a signed envelope.
+    #[serde(default)]
+    pub accept_bare_transactions: bool,
+    /// Gateway `ipfs://` NFT metadata is fetched through (default https://ipfs.io/ipfs/)
+    #[serde(default)]
+    pub ipfs_gateway: Option<String>,
 }
 
 fn default_identity_file() -> String {
@@ -26,9 +98,190 @@ fn default_bootnodes() -> Vec<String> {
     vec![]
 }
 
+fn default_genesis_file() -> String {
+    "genesis.json".to_string()
+}
+
 #[derive(Debug, Deserialize, Serialize, Clone)]
 pub struct ConsensusConfig {
     pub slot_duration_ms: u64,
+    /// Fixed VDF iterations per PoH tick; calibrated to this CPU at startup when unset
+    #[serde(default)]
+    pub vdf_iterations: Option<u64>,
+    /// How compute results are rewarded
+    #[serde(default)]
+    pub pouw: crate::layer3::pouw::PouwRewardCurve,
+    /// Slots per leader schedule epoch; every validator must use the same value
+    #[serde(default = "default_slots_per_epoch")]
+    pub slots_per_epoch: u64,
+    /// Minimum fees by block type and size
+    #[serde(default)]
+    pub fees: crate::fees::FeeSchedule,
+    /// Smallest earnings claim accepted, in COMPUTE base units (0 accepts any)
+    #[serde(default)]
+    pub min_earnings_payout: u64,
+    /// Sanity bands for oracle prices and when they pause a feed
+    #[serde(default)]
+    pub oracle_guard: crate::oracle::circuit_breaker::OracleGuardConfig,
+    /// Oracles allowed to submit prices and how their prices are combined
+    #[serde(default)]
+    pub oracle_aggregation: crate::oracle::aggregation::OracleAggregationConfig,
+    /// Treasury's share of fees and slashed collateral
+    #[serde(default)]
+    pub treasury: crate::treasury::TreasuryConfig,
+    /// Maker and taker rates charged on order-book fills
+    #[serde(default)]
+    pub market_fees: crate::market::MarketFeeConfig,
+    /// Interest charged on vault debt
+    #[serde(default)]
+    pub stability_fee: crate::vault::stability::StabilityFeeConfig,
+    /// Slots unstaked collateral waits before it can be withdrawn
+    #[serde(default = "default_unbonding_slots")]
+    pub unbonding_slots: u64,
+    /// Block and PoUW reward curve
+    #[serde(default)]
+    pub emission: crate::layer2::economics::EmissionSchedule,
+}
+
+fn default_unbonding_slots() -> u64 {
+    crate::layer2::collateral::DEFAULT_UNBONDING_SLOTS
+}
+
+fn default_slots_per_epoch() -> u64 {
+    crate::leader_schedule::DEFAULT_SLOTS_PER_EPOCH
+}
+
+/// `[signer]`: where the block-producing key lives when it is not on this host
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RemoteSignerConfig {
+    /// `http(s)://host:port` or `unix:///path/to/socket`
+    pub url: String,
+    /// Which key the service should use
+    pub key_id: String,
+    /// File holding the hex HMAC secret shared with the service
+    pub auth_secret_file: String,
+    /// Expected public key; startup fails if the service reports another
+    #[serde(default)]
+    pub public_key: Option<String>,
+    #[serde(default = "default_signer_timeout_ms")]
+    pub timeout_ms: u64,
+}
+
+fn default_signer_timeout_ms() -> u64 {
+    2000
+}
+
+/// `[alerts]`: rules checked by the node's monitor task, e.g.
+///
+/// ```toml
+/// [alerts]
+/// webhook_url = "https://example.com/hooks/compass"
+/// [[alerts.rules]]
+/// metric = "peer_count"
+/// below = 3
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct AlertsConfig {
+    #[serde(default = "default_alert_interval_secs")]
+    pub interval_secs: u64,
+    /// Firing and resolved alerts are POSTed here as JSON; they are only logged when unset
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub webhook_url: Option<String>,
+    #[serde(default)]
+    pub rules: Vec<crate::node::monitor::AlertRule>,
+}
+
+fn default_alert_interval_secs() -> u64 {
+    30
+}
+
+/// `[faucet]`: serve `POST /faucet` from a funded wallet in the node's wallet store
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct FaucetConfig {
+    #[serde(default = "default_faucet_wallet")]
+    pub wallet: String,
+    /// Whole Compass paid per request
+    #[serde(default = "default_faucet_amount")]
+    pub amount: String,
+    /// How long an address, and separately a client IP, waits between grants
+    #[serde(default = "default_faucet_cooldown_secs")]
+    pub cooldown_secs: u64,
+    /// Each request is POSTed here first as `{"address", "ip", "captcha"}`; any
+    /// reply other than 2xx turns it down
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub verify_url: Option<String>,
+}
+
+fn default_faucet_wallet() -> String {
+    "faucet".to_string()
+}
+
+fn default_faucet_amount() -> String {
+    "10".to_string()
+}
+
+fn default_faucet_cooldown_secs() -> u64 {
+    24 * 3600
+}
+
+/// `[[rpc_listeners]]`: one RPC socket and the methods it serves, e.g.
+///
+/// ```toml
+/// [[rpc_listeners]]
+/// bind = "0.0.0.0:9000"
+/// access = "public"
+///
+/// [[rpc_listeners]]
+/// bind = "127.0.0.1:9001"
+/// access = "admin"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct RpcListenerConfig {
+    /// `host:port`
+    pub bind: String,
+    /// "public" (chain reads and signed submissions, the default) or "admin" (every method)
+    #[serde(default)]
+    pub access: crate::rpc::access::RpcAccess,
+    /// Methods a public listener serves on top of the public set
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allow: Vec<String>,
+}
+
+/// `[tls]`: PEM files every RPC listener serves HTTPS with, e.g.
+///
+/// ```toml
+/// [tls]
+/// cert_path = "/etc/compass/tls/fullchain.pem"
+/// key_path = "/etc/compass/tls/privkey.pem"
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct TlsConfig {
+    /// Certificate chain, leaf first
+    pub cert_path: String,
+    /// PKCS#8, PKCS#1 or SEC1 private key for the leaf certificate
+    pub key_path: String,
+}
+
+/// `[pruning]`: drop block bodies older than the last `keep_blocks`, e.g.
+///
+/// ```toml
+/// [pruning]
+/// keep_blocks = 100000
+/// ```
+///
+/// State (balances, nonces, accounts) is never pruned. Blocks past the latest
+/// snapshot or the finalized height are kept whatever `keep_blocks` says, so peers
+/// can still sync from this node and reorgs stay possible.
+#[derive(Debug, Deserialize, Serialize, Clone)]
+pub struct PruningConfig {
+    pub keep_blocks: u64,
+    /// How often the pruning pass runs
+    #[serde(default = "default_prune_interval_secs")]
+    pub interval_secs: u64,
+}
+
+fn default_prune_interval_secs() -> u64 {
+    600
 }
 
 impl Default for CompassConfig {
@@ -41,15 +294,54 @@ impl Default for CompassConfig {
                 log_level: "info".to_string(),
                 identity_file: "identity.json".to_string(),
                 bootnodes: vec![],
+                genesis_file: "genesis.json".to_string(),
+                db_backend: Default::default(),
+                db_cache_mb: None,
+                db_flush_every_blocks: None,
+                db_flush_interval_ms: None,
+                future_nonce_window: None,
+                job_result_retention_hours: None,
+                snapshot_interval_blocks: None,
+                l2_checkpoint_interval_secs: None,
+                light: false,
+                dev: false,
+                p2p_tls: false,
+                session_ttl_secs: None,
+                accept_bare_transactions: false,
+                ipfs_gateway: None,
             },
             consensus: ConsensusConfig {
                 slot_duration_ms: 1000,
+                vdf_iterations: None,
+                pouw: Default::default(),
+                slots_per_epoch: default_slots_per_epoch(),
+                fees: Default::default(),
+                min_earnings_payout: 0,
+                oracle_guard: Default::default(),
+                oracle_aggregation: Default::default(),
+                treasury: Default::default(),
+                market_fees: Default::default(),
+                stability_fee: Default::default(),
+                unbonding_slots: default_unbonding_slots(),
+                emission: Default::default(),
             },
+            signer: None,
+            alerts: None,
+            faucet: None,
+            rpc_listeners: vec![],
+            tls: None,
+            pruning: None,
+            ethereum: None,
         }
     }
 }
 
 impl CompassConfig {
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let s = toml::to_string_pretty(self).map_err(|e| e.to_string())?;
+        std::fs::write(path, s).map_err(|e| e.to_string())
+    }
+
     pub fn load_or_default(path: &str) -> Self {
         if std::path::Path::new(path).exists() {
              match std::fs::read_to_string(path) {
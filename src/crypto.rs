@@ -1,12 +1,50 @@
-use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer, Verifier};
+use ed25519_dalek::{SigningKey, VerifyingKey, Signature, Signer as _, Verifier};
 use bip39::{Language, Mnemonic};
-use hex;
+use crate::encoding;
 use rand::rngs::OsRng;
 use rand::RngCore;
 
 // Using VerifyingKey as PublicKey in API
 pub type PublicKey = VerifyingKey;
 
+/// BIP44 coin type in Compass derivation paths. Not registered in SLIP-0044.
+pub const COMPASS_COIN_TYPE: u32 = 7777;
+
+const HARDENED: u32 = 0x8000_0000;
+
+/// BIP44 path of HD account `index`: m/44'/7777'/index'
+pub fn derivation_path(index: u32) -> String {
+    format!("m/44'/{}'/{}'", COMPASS_COIN_TYPE, index)
+}
+
+/// SLIP-0010 Ed25519 secret key at `path` below `seed`. Ed25519 only has
+/// hardened children, so every index is hardened here.
+fn slip10_ed25519(seed: &[u8], path: &[u32]) -> [u8; 32] {
+    use hmac::{Hmac, Mac};
+    use sha2::Sha512;
+
+    let hmac = |key: &[u8], data: &[u8]| -> [u8; 64] {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC takes keys of any length");
+        mac.update(data);
+        mac.finalize().into_bytes().into()
+    };
+    let mut node = hmac(b"ed25519 seed", seed);
+    for index in path {
+        let mut data = Vec::with_capacity(37);
+        data.push(0);
+        data.extend_from_slice(&node[..32]);
+        data.extend_from_slice(&(index | HARDENED).to_be_bytes());
+        node = hmac(&node[32..], &data);
+    }
+    node[..32].try_into().expect("64-byte HMAC output")
+}
+
+fn mnemonic_seed(phrase: &str) -> Result<[u8; 64], String> {
+    let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)
+        .map_err(|e| format!("Invalid mnemonic: {}", e))?;
+    Ok(mnemonic.to_seed(""))
+}
+
 pub struct KeyPair {
     pub signing_key: SigningKey,
 }
@@ -19,18 +57,27 @@ impl KeyPair {
         KeyPair { signing_key }
     }
 
+    /// Deterministic keypair derived from a label. For tests and fuzzing only:
+    /// anyone who knows the label can derive the secret key.
+    pub fn from_seed(label: &str) -> Self {
+        use sha2::{Digest, Sha256};
+        let secret: [u8; 32] = Sha256::digest(label.as_bytes()).into();
+        KeyPair { signing_key: SigningKey::from_bytes(&secret) }
+    }
+
     /// Generate a new 12-word mnemonic
     pub fn generate_mnemonic() -> String {
         let mut entropy = [0u8; 16]; // 128 bits = 12 words
         OsRng.fill_bytes(&mut entropy);
-        Mnemonic::from_entropy(&entropy).unwrap().to_string()
+        Mnemonic::from_entropy(&entropy)
+            .expect("16 bytes is valid BIP39 entropy")
+            .to_string()
     }
 
-    /// Restore keypair from mnemonic phrase
+    /// Restore keypair from mnemonic phrase. This is a wallet's own key, not on
+    /// any HD path; see `derive_from_mnemonic` for its other accounts.
     pub fn from_mnemonic(phrase: &str) -> Result<Self, String> {
-        let mnemonic = Mnemonic::parse_in_normalized(Language::English, phrase)
-            .map_err(|e| format!("Invalid mnemonic: {}", e))?;
-        let seed = mnemonic.to_seed("");
+        let seed = mnemonic_seed(phrase)?;
 
         // Use first 32 bytes as secret key
         let secret_bytes: [u8; 32] = seed[0..32]
@@ -43,6 +90,16 @@ impl KeyPair {
         Ok(KeyPair { signing_key })
     }
 
+    /// HD account `index` of a mnemonic, at `derivation_path(index)`
+    pub fn derive_from_mnemonic(phrase: &str, index: u32) -> Result<Self, String> {
+        if index >= HARDENED {
+            return Err(format!("Account index {} is out of range", index));
+        }
+        let seed = mnemonic_seed(phrase)?;
+        let secret = slip10_ed25519(&seed, &[44, COMPASS_COIN_TYPE, index]);
+        Ok(KeyPair { signing_key: SigningKey::from_bytes(&secret) })
+    }
+
     /// Sign a message with the private key
     pub fn sign(&self, message: &[u8]) -> Signature {
         self.signing_key.sign(message)
@@ -51,7 +108,7 @@ impl KeyPair {
     /// Sign and return hex-encoded signature
     pub fn sign_hex(&self, message: &[u8]) -> String {
         let sig = self.sign(message);
-        hex::encode(sig.to_bytes())
+        encoding::to_hex(sig.to_bytes())
     }
     
     /// Get the public key
@@ -61,19 +118,15 @@ impl KeyPair {
 
     /// Get public key as hex string
     pub fn public_key_hex(&self) -> String {
-        hex::encode(self.signing_key.verifying_key().to_bytes())
+        encoding::encode_address(&self.signing_key.verifying_key().to_bytes())
     }
 
     pub fn secret_key_hex(&self) -> String {
-        hex::encode(self.signing_key.to_bytes())
+        encoding::to_hex(self.signing_key.to_bytes())
     }
 
     pub fn from_secret_hex(hex_str: &str) -> Result<Self, String> {
-        let bytes = hex::decode(hex_str).map_err(|e| e.to_string())?;
-        if bytes.len() != 32 {
-            return Err("Invalid secret key length".to_string());
-        }
-        let arr: [u8; 32] = bytes.try_into().map_err(|_| "Invalid length".to_string())?;
+        let arr: [u8; 32] = encoding::from_hex_array(hex_str, "secret key").map_err(|e| e.to_string())?;
         let signing_key = SigningKey::from_bytes(&arr);
         Ok(KeyPair { signing_key })
     }
@@ -94,60 +147,45 @@ impl KeyPair {
 
     /// Verify a hex-encoded signature
     pub fn verify_hex(&self, message: &[u8], signature_hex: &str) -> bool {
-        if let Ok(sig_bytes) = hex::decode(signature_hex) {
-            if sig_bytes.len() == 64 {
-                if let Ok(sig_array) = TryInto::<[u8; 64]>::try_into(sig_bytes) {
-                    if let Ok(signature) = Signature::try_from(&sig_array[..]) {
-                        return self.verify(message, &signature);
-                    }
-                }
-            }
+        match encoding::signature_from_hex(signature_hex) {
+            Ok(sig_array) => self.verify(message, &Signature::from_bytes(&sig_array)),
+            Err(_) => false,
         }
-        false
+    }
+}
+
+/// Anything that can sign for an account: an in-memory `KeyPair`, or an external
+/// device or daemon holding the key (see `wallet::connect_external_signer`)
+pub trait Signer: Send + Sync {
+    fn public_key_hex(&self) -> String;
+
+    /// Hex signature over `message`
+    fn sign_hex(&self, message: &[u8]) -> Result<String, String>;
+}
+
+impl Signer for KeyPair {
+    fn public_key_hex(&self) -> String {
+        KeyPair::public_key_hex(self)
+    }
+
+    fn sign_hex(&self, message: &[u8]) -> Result<String, String> {
+        Ok(KeyPair::sign_hex(self, message))
     }
 }
 
 /// Verify a signature with a public key (both hex-encoded)
 pub fn verify_with_pubkey_hex(message: &[u8], signature_hex: &str, pubkey_hex: &str) -> bool {
-    // Decode public key
-    let pubkey_bytes = match hex::decode(pubkey_hex) {
-        Ok(b) => b,
-        Err(_) => return false,
-    };
-    
-    if pubkey_bytes.len() != 32 {
+    let (Ok(pub_array), Ok(sig_array)) = (
+        encoding::pubkey_from_hex(pubkey_hex),
+        encoding::signature_from_hex(signature_hex),
+    ) else {
         return false;
-    }
-    
-    let pub_array: [u8; 32] = match pubkey_bytes.as_slice().try_into() {
-        Ok(arr) => arr,
-        Err(_) => return false,
     };
-    
     let pubkey = match VerifyingKey::from_bytes(&pub_array) {
         Ok(pk) => pk,
         Err(_) => return false,
     };
-
-    // Decode signature
-    let sig_bytes = match hex::decode(signature_hex) {
-        Ok(b) => b,
-        Err(_) => return false,
-    };
-    
-    if sig_bytes.len() != 64 {
-        return false;
-    }
-    
-    let sig_array: [u8; 64] = match sig_bytes.as_slice().try_into() {
-        Ok(arr) => arr,
-        Err(_) => return false,
-    };
-    
-    let signature = match Signature::try_from(&sig_array[..]) {
-        Ok(sig) => sig,
-        Err(_) => return false,
-    };
+    let signature = Signature::from_bytes(&sig_array);
 
     // Verify
     pubkey.verify(message, &signature).is_ok()
@@ -160,11 +198,7 @@ pub fn verify_with_pubkey_hex_bytes(
     pubkey_hex: &str,
 ) -> Result<bool, String> {
     // Decode public key
-    let pubkey_bytes = hex::decode(pubkey_hex).map_err(|_| "Invalid pubkey hex")?;
-    let pub_array: [u8; 32] = pubkey_bytes
-        .as_slice()
-        .try_into()
-        .map_err(|_| "Invalid pubkey length")?;
+    let pub_array = encoding::pubkey_from_hex(pubkey_hex).map_err(|e| e.to_string())?;
     let verifying_key = VerifyingKey::from_bytes(&pub_array).map_err(|_| "Invalid pubkey bytes")?;
 
     // Parse signature
@@ -182,3 +216,26 @@ pub fn verify_with_pubkey_hex_bytes(
         Err(_) => Ok(false),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_slip10_ed25519_vectors() {
+        // SLIP-0010 test vector 1 for ed25519
+        let seed = encoding::from_hex("000102030405060708090a0b0c0d0e0f").unwrap();
+        let key = |path: &[u32]| encoding::to_hex(slip10_ed25519(&seed, path));
+        assert_eq!(key(&[]), "2b4be7f19ee27bbf30c667b642d5f4aa69fd169872f8fc3059c08ebae2eb19e7");
+        assert_eq!(key(&[0]), "68e0fe46dfb67e368c75379acec591dad19df3cde26e63b93a8e704f1dade7a3");
+        assert_eq!(key(&[0, 1]), "b1d0bad404bf35da785a64ca1ac54b2617211d2777696fbffaf208f746ae84f2");
+
+        let phrase = KeyPair::generate_mnemonic();
+        let first = KeyPair::derive_from_mnemonic(&phrase, 0).unwrap().public_key_hex();
+        assert_eq!(KeyPair::derive_from_mnemonic(&phrase, 0).unwrap().public_key_hex(), first);
+        assert_ne!(KeyPair::derive_from_mnemonic(&phrase, 1).unwrap().public_key_hex(), first);
+        assert_ne!(KeyPair::from_mnemonic(&phrase).unwrap().public_key_hex(), first);
+        assert!(KeyPair::derive_from_mnemonic(&phrase, HARDENED).is_err());
+        assert_eq!(derivation_path(3), "m/44'/7777'/3'");
+    }
+}
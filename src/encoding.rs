@@ -1,5 +1,462 @@
 #![allow(dead_code)]
+// Canonical encodings
+//
+// Every hex, base64 and bech32 conversion, and the binary form payloads are
+// hashed and signed over, goes through this module so nodes agree byte-for-byte
+// and malformed input surfaces as an `EncodingError` instead of a panic.
+use base64::Engine;
+use serde::{de::DeserializeOwned, Serialize};
+use sha2::{Digest, Sha256};
 use std::io::{self, Write};
+use thiserror::Error;
+
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum EncodingError {
+    #[error("invalid hex: {0}")]
+    InvalidHex(String),
+    #[error("invalid {what} length: expected {expected} bytes, got {actual}")]
+    InvalidLength {
+        what: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("invalid base64: {0}")]
+    InvalidBase64(String),
+    #[error("invalid bech32: {0}")]
+    InvalidBech32(String),
+    #[error("serialization failed: {0}")]
+    Serialize(String),
+    #[error("deserialization failed: {0}")]
+    Deserialize(String),
+    #[error("{size} bytes exceeds limit of {limit} bytes")]
+    TooLarge { size: usize, limit: u64 },
+    #[error("invalid amount: {0}")]
+    InvalidAmount(String),
+}
+
+impl From<io::Error> for EncodingError {
+    fn from(e: io::Error) -> Self {
+        EncodingError::Serialize(e.to_string())
+    }
+}
+
+impl From<EncodingError> for crate::error::CompassError {
+    fn from(e: EncodingError) -> Self {
+        match e {
+            EncodingError::Serialize(msg) => crate::error::CompassError::SerializationError(msg),
+            EncodingError::Deserialize(msg) => crate::error::CompassError::DeserializationError(msg),
+            other => crate::error::CompassError::SerializationError(other.to_string()),
+        }
+    }
+}
+
+// --- Hex ---
+
+/// Lowercase hex, the only form hashes, keys and signatures are displayed in
+pub fn to_hex(bytes: impl AsRef<[u8]>) -> String {
+    hex::encode(bytes)
+}
+
+pub fn from_hex(s: &str) -> Result<Vec<u8>, EncodingError> {
+    hex::decode(s).map_err(|e| EncodingError::InvalidHex(e.to_string()))
+}
+
+/// Decode hex that must be exactly `N` bytes; `what` names the value in the error
+pub fn from_hex_array<const N: usize>(s: &str, what: &'static str) -> Result<[u8; N], EncodingError> {
+    let bytes = from_hex(s)?;
+    let actual = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| EncodingError::InvalidLength { what, expected: N, actual })
+}
+
+// --- Hashes, keys, addresses ---
+
+pub const HASH_LEN: usize = 32;
+pub const PUBKEY_LEN: usize = 32;
+pub const SIGNATURE_LEN: usize = 64;
+
+pub fn sha256(data: impl AsRef<[u8]>) -> [u8; HASH_LEN] {
+    Sha256::digest(data.as_ref()).into()
+}
+
+/// Canonical display form of a hash (block hashes, tx ids)
+pub fn hash_to_hex(hash: &[u8]) -> String {
+    to_hex(hash)
+}
+
+pub fn hash_from_hex(s: &str) -> Result<[u8; HASH_LEN], EncodingError> {
+    from_hex_array(s, "hash")
+}
+
+pub fn pubkey_from_hex(s: &str) -> Result<[u8; PUBKEY_LEN], EncodingError> {
+    from_hex_array(s, "public key")
+}
+
+pub fn signature_from_hex(s: &str) -> Result<[u8; SIGNATURE_LEN], EncodingError> {
+    from_hex_array(s, "signature")
+}
+
+/// Account address of an ed25519 public key. Addresses are the hex-encoded key.
+pub fn encode_address(pubkey: &[u8; PUBKEY_LEN]) -> String {
+    to_hex(pubkey)
+}
+
+pub fn decode_address(address: &str) -> Result<[u8; PUBKEY_LEN], EncodingError> {
+    from_hex_array(address, "address")
+}
+
+// --- Base64 ---
+
+pub fn to_base64(bytes: impl AsRef<[u8]>) -> String {
+    base64::engine::general_purpose::STANDARD.encode(bytes)
+}
+
+pub fn from_base64(s: &str) -> Result<Vec<u8>, EncodingError> {
+    base64::engine::general_purpose::STANDARD
+        .decode(s)
+        .map_err(|e| EncodingError::InvalidBase64(e.to_string()))
+}
+
+// --- Bech32 (BIP-173) ---
+
+const BECH32_CHARSET: &[u8; 32] = b"qpzry9x8gf2tvdw0s3jn54khce6mua7l";
+const BECH32_MAX_LEN: usize = 90;
+
+fn bech32_polymod(values: &[u8]) -> u32 {
+    const GEN: [u32; 5] = [0x3b6a57b2, 0x26508e6d, 0x1ea119fa, 0x3d4233dd, 0x2a1462b3];
+    let mut chk: u32 = 1;
+    for v in values {
+        let top = chk >> 25;
+        chk = ((chk & 0x1ffffff) << 5) ^ u32::from(*v);
+        for (i, g) in GEN.iter().enumerate() {
+            if (top >> i) & 1 == 1 {
+                chk ^= g;
+            }
+        }
+    }
+    chk
+}
+
+fn bech32_hrp_expand(hrp: &str) -> Vec<u8> {
+    let bytes = hrp.as_bytes();
+    let mut out: Vec<u8> = bytes.iter().map(|b| b >> 5).collect();
+    out.push(0);
+    out.extend(bytes.iter().map(|b| b & 31));
+    out
+}
+
+/// Regroup bits, e.g. 8-bit bytes into the 5-bit words bech32 carries
+fn convert_bits(data: &[u8], from: u32, to: u32, pad: bool) -> Option<Vec<u8>> {
+    let mut acc: u32 = 0;
+    let mut bits: u32 = 0;
+    let max = (1u32 << to) - 1;
+    let mut out = Vec::with_capacity(data.len() * from as usize / to as usize + 1);
+    for value in data {
+        let v = u32::from(*value);
+        if v >> from != 0 {
+            return None;
+        }
+        acc = (acc << from) | v;
+        bits += from;
+        while bits >= to {
+            bits -= to;
+            out.push(((acc >> bits) & max) as u8);
+        }
+    }
+    if pad {
+        if bits > 0 {
+            out.push(((acc << (to - bits)) & max) as u8);
+        }
+    } else if bits >= from || (acc << (to - bits)) & max != 0 {
+        return None;
+    }
+    Some(out)
+}
+
+fn check_hrp(hrp: &str) -> Result<(), EncodingError> {
+    if hrp.is_empty() || hrp.len() > 83 {
+        return Err(EncodingError::InvalidBech32("prefix must be 1-83 characters".into()));
+    }
+    if !hrp.bytes().all(|b| (33..=126).contains(&b)) {
+        return Err(EncodingError::InvalidBech32("prefix has invalid characters".into()));
+    }
+    Ok(())
+}
+
+/// Encode bytes as bech32 with human-readable prefix `hrp` (lowercased)
+pub fn bech32_encode(hrp: &str, data: &[u8]) -> Result<String, EncodingError> {
+    check_hrp(hrp)?;
+    let hrp = hrp.to_ascii_lowercase();
+    let words = convert_bits(data, 8, 5, true).expect("8-bit input always converts");
+
+    let mut values = bech32_hrp_expand(&hrp);
+    values.extend_from_slice(&words);
+    values.extend_from_slice(&[0; 6]);
+    let pm = bech32_polymod(&values) ^ 1;
+    let checksum = (0..6).map(|i| ((pm >> (5 * (5 - i))) & 31) as u8);
+
+    let mut out = hrp;
+    out.push('1');
+    out.extend(words.iter().copied().chain(checksum).map(|w| BECH32_CHARSET[w as usize] as char));
+    if out.len() > BECH32_MAX_LEN {
+        return Err(EncodingError::InvalidBech32(format!("longer than {} characters", BECH32_MAX_LEN)));
+    }
+    Ok(out)
+}
+
+/// Decode a bech32 string into its prefix and payload bytes, validating the checksum
+pub fn bech32_decode(s: &str) -> Result<(String, Vec<u8>), EncodingError> {
+    let invalid = |msg: &str| EncodingError::InvalidBech32(msg.to_string());
+    if s.len() > BECH32_MAX_LEN {
+        return Err(invalid("too long"));
+    }
+    if s.chars().any(|c| c.is_ascii_lowercase()) && s.chars().any(|c| c.is_ascii_uppercase()) {
+        return Err(invalid("mixed case"));
+    }
+    let s = s.to_ascii_lowercase();
+    let sep = s.rfind('1').ok_or_else(|| invalid("missing separator"))?;
+    let (hrp, rest) = (&s[..sep], &s[sep + 1..]);
+    check_hrp(hrp)?;
+    if rest.len() < 6 {
+        return Err(invalid("checksum too short"));
+    }
+
+    let words = rest
+        .bytes()
+        .map(|b| BECH32_CHARSET.iter().position(|c| *c == b).map(|p| p as u8))
+        .collect::<Option<Vec<u8>>>()
+        .ok_or_else(|| invalid("invalid character"))?;
+    let mut values = bech32_hrp_expand(hrp);
+    values.extend_from_slice(&words);
+    if bech32_polymod(&values) != 1 {
+        return Err(invalid("checksum mismatch"));
+    }
+
+    let data = convert_bits(&words[..words.len() - 6], 5, 8, false).ok_or_else(|| invalid("invalid padding"))?;
+    Ok((hrp.to_string(), data))
+}
+
+// --- Bech32 account addresses ---
+
+/// Prefix of user-facing account addresses (`cmps1...`)
+pub const ADDRESS_HRP: &str = "cmps";
+
+/// Whether `s` is meant as a bech32 address, valid or not
+pub fn is_bech32_address(s: &str) -> bool {
+    s.get(..ADDRESS_HRP.len() + 1).is_some_and(|p| p.eq_ignore_ascii_case("cmps1"))
+}
+
+/// `cmps1...` form of a public key. The hex key stays the on-chain account id;
+/// this is only the checksummed form users copy around.
+pub fn encode_bech32_address(pubkey: &[u8; PUBKEY_LEN]) -> String {
+    bech32_encode(ADDRESS_HRP, pubkey).expect("32-byte payload fits in a bech32 string")
+}
+
+pub fn decode_bech32_address(address: &str) -> Result<[u8; PUBKEY_LEN], EncodingError> {
+    let (hrp, data) = bech32_decode(address.trim())?;
+    if hrp != ADDRESS_HRP {
+        return Err(EncodingError::InvalidBech32(format!("expected prefix '{}', got '{}'", ADDRESS_HRP, hrp)));
+    }
+    let actual = data.len();
+    data.try_into()
+        .map_err(|_| EncodingError::InvalidLength { what: "address", expected: PUBKEY_LEN, actual })
+}
+
+/// Normalize an account given as a `cmps1` address, a hex public key or a name.
+/// Addresses become the hex key (after checksum validation), hex keys are
+/// lowercased, and anything else is returned trimmed as an account name.
+pub fn parse_account_id(input: &str) -> Result<String, EncodingError> {
+    let s = input.trim();
+    if is_bech32_address(s) {
+        return decode_bech32_address(s).map(|key| encode_address(&key));
+    }
+    if s.len() == PUBKEY_LEN * 2 && s.bytes().all(|b| b.is_ascii_hexdigit()) {
+        return Ok(s.to_ascii_lowercase());
+    }
+    Ok(s.to_string())
+}
+
+/// `cmps1` address for an account id, if it is a hex public key (names have none)
+pub fn account_bech32_address(account: &str) -> Option<String> {
+    decode_address(account).ok().map(|key| encode_bech32_address(&key))
+}
+
+// --- Token amounts ---
+
+/// Decimal places of an asset's base unit, until the token registry records its own
+pub const DEFAULT_DECIMALS: u32 = 8;
+
+pub fn asset_decimals(_asset: &str) -> u32 {
+    DEFAULT_DECIMALS
+}
+
+/// Whole-token decimal string to base units: "1.5" at 8 decimals is 150_000_000.
+/// Exact, so digits beyond `decimals` are an error rather than rounded away.
+pub fn parse_units(input: &str, decimals: u32) -> Result<u64, EncodingError> {
+    let s = input.trim();
+    let invalid = || EncodingError::InvalidAmount(format!("'{}' is not a decimal number", s));
+    let (whole, frac) = s.split_once('.').unwrap_or((s, ""));
+    let digits = |part: &str| part.bytes().all(|b| b.is_ascii_digit());
+    if (whole.is_empty() && frac.is_empty()) || !digits(whole) || !digits(frac) {
+        return Err(invalid());
+    }
+    if frac.len() > decimals as usize {
+        return Err(EncodingError::InvalidAmount(format!("{} has more than {} decimal places", s, decimals)));
+    }
+    let too_large = || EncodingError::InvalidAmount(format!("{} is too large", s));
+    let whole: u64 = if whole.is_empty() { 0 } else { whole.parse().map_err(|_| too_large())? };
+    let frac: u64 = if frac.is_empty() { 0 } else { format!("{:0<width$}", frac, width = decimals as usize).parse().map_err(|_| invalid())? };
+    10u64
+        .checked_pow(decimals)
+        .and_then(|scale| whole.checked_mul(scale))
+        .and_then(|units| units.checked_add(frac))
+        .ok_or_else(too_large)
+}
+
+/// Base units to the shortest whole-token string: 150_000_000 at 8 decimals is "1.5"
+pub fn format_units(units: u64, decimals: u32) -> String {
+    let Some(scale) = 10u64.checked_pow(decimals) else {
+        return units.to_string();
+    };
+    let (whole, frac) = (units / scale, units % scale);
+    if frac == 0 {
+        return whole.to_string();
+    }
+    let frac = format!("{:0width$}", frac, width = decimals as usize);
+    format!("{}.{}", whole, frac.trim_end_matches('0'))
+}
+
+/// An amount of one asset in base units. Parsed from and displayed as whole
+/// tokens ("1.5 Compass"), which is how the CLI, the interactive client and RPC
+/// responses all present it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Amount {
+    pub units: u64,
+    pub asset: String,
+}
+
+impl Amount {
+    pub fn new(units: u64, asset: &str) -> Self {
+        Self { units, asset: asset.to_string() }
+    }
+
+    /// "1.5" or "1.5 Compass"; a unit after the number must be `asset`
+    pub fn parse(input: &str, asset: &str) -> Result<Self, EncodingError> {
+        let s = input.trim();
+        let number = match s.split_once(char::is_whitespace) {
+            Some((number, unit)) if unit.trim() == asset => number,
+            Some((_, unit)) => {
+                return Err(EncodingError::InvalidAmount(format!("'{}' is not {}", unit.trim(), asset)));
+            }
+            None => s,
+        };
+        Ok(Self::new(parse_units(number, asset_decimals(asset))?, asset))
+    }
+
+    pub fn decimals(&self) -> u32 {
+        asset_decimals(&self.asset)
+    }
+
+    /// The number alone, e.g. "1.5"
+    pub fn to_decimal_string(&self) -> String {
+        format_units(self.units, self.decimals())
+    }
+}
+
+impl std::fmt::Display for Amount {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {}", self.to_decimal_string(), self.asset)
+    }
+}
+
+// --- Stored decimals ---
+
+/// Decimals as their exact string, the form `Decimal` serializes to. Its own
+/// reader goes through `deserialize_any`, which bincode (Sled) cannot decode, so
+/// stored records name this module in `#[serde(with = ...)]`.
+pub mod decimal_str {
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+    use std::str::FromStr;
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&value.to_string())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        parse(&String::deserialize(deserializer)?)
+    }
+
+    pub(crate) fn parse<E: serde::de::Error>(value: &str) -> Result<Decimal, E> {
+        Decimal::from_str(value).map_err(|_| E::custom(format!("{} is not a valid decimal", value)))
+    }
+
+    /// `(timestamp, price)` series
+    pub mod timed {
+        use super::*;
+        use serde::ser::SerializeSeq;
+
+        pub fn serialize<S: Serializer>(values: &[(u64, Decimal)], serializer: S) -> Result<S::Ok, S::Error> {
+            let mut seq = serializer.serialize_seq(Some(values.len()))?;
+            for (at, value) in values {
+                seq.serialize_element(&(at, value.to_string()))?;
+            }
+            seq.end()
+        }
+
+        pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<(u64, Decimal)>, D::Error> {
+            Vec::<(u64, String)>::deserialize(deserializer)?
+                .into_iter()
+                .map(|(at, value)| Ok((at, parse(&value)?)))
+                .collect()
+        }
+    }
+}
+
+// --- Payloads ---
+
+/// Bytes a payload is hashed and signed over: bincode with fixed-width
+/// little-endian integers, the same on every platform
+pub fn canonical_bytes<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, EncodingError> {
+    use bincode::Options;
+    // Spelled out rather than `bincode::serialize` so a bincode default change cannot move signed bytes
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .allow_trailing_bytes()
+        .serialize(value)
+        .map_err(|e| EncodingError::Serialize(e.to_string()))
+}
+
+/// What a signature over `value` covers: a domain tag, a zero byte, then its canonical
+/// bytes, so a signature for one kind of message never verifies as another
+pub fn signing_bytes<T: Serialize + ?Sized>(domain: &str, value: &T) -> Result<Vec<u8>, EncodingError> {
+    let mut bytes = domain.as_bytes().to_vec();
+    bytes.push(0);
+    bytes.extend(canonical_bytes(value)?);
+    Ok(bytes)
+}
+
+pub fn from_canonical_bytes<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, EncodingError> {
+    bincode::deserialize(bytes).map_err(|e| EncodingError::Deserialize(e.to_string()))
+}
+
+/// `from_canonical_bytes` for untrusted input: a byte budget (so a forged length
+/// prefix cannot trigger a huge allocation) and no trailing garbage
+pub fn from_canonical_bytes_bounded<T: DeserializeOwned>(bytes: &[u8], limit: u64) -> Result<T, EncodingError> {
+    use bincode::Options;
+    if bytes.len() as u64 > limit {
+        return Err(EncodingError::TooLarge { size: bytes.len(), limit });
+    }
+    bincode::DefaultOptions::new()
+        .with_fixint_encoding()
+        .with_limit(limit)
+        .reject_trailing_bytes()
+        .deserialize(bytes)
+        .map_err(|e| EncodingError::Deserialize(e.to_string()))
+}
+
+// --- Hand-written canonical form for block headers ---
 
 /// Trait for objects that have a canonical binary representation for Hashing/Signing.
 /// careful: This must be deterministic across platforms/versions.
@@ -58,3 +515,101 @@ impl<T: CanonicalSerialize> CanonicalSerialize for Vec<T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hex_lengths_are_checked() {
+        let key = [7u8; 32];
+        assert_eq!(pubkey_from_hex(&to_hex(key)), Ok(key));
+        assert_eq!(decode_address(&encode_address(&key)), Ok(key));
+        assert_eq!(
+            hash_from_hex("abcd"),
+            Err(EncodingError::InvalidLength { what: "hash", expected: 32, actual: 2 })
+        );
+        assert!(matches!(signature_from_hex("zz"), Err(EncodingError::InvalidHex(_))));
+        assert_eq!(from_base64(&to_base64(b"compass")).unwrap(), b"compass");
+        assert!(from_base64("not base64!").is_err());
+    }
+
+    #[test]
+    fn test_bech32_vectors_and_roundtrip() {
+        // BIP-173 test vectors
+        assert_eq!(bech32_decode("A12UEL5L"), Ok(("a".to_string(), vec![])));
+        assert!(bech32_decode("an83characterlonghumanreadablepartthatcontainsthenumber1andtheexcludedcharactersbio1tt5tgs").is_ok());
+        assert!(bech32_decode("A12uEL5L").is_err()); // mixed case
+        assert!(bech32_decode("a12uel5m").is_err()); // bad checksum
+
+        let encoded = bech32_encode("cmps", &[0xde, 0xad, 0xbe, 0xef]).unwrap();
+        assert!(encoded.starts_with("cmps1"));
+        assert_eq!(bech32_decode(&encoded), Ok(("cmps".to_string(), vec![0xde, 0xad, 0xbe, 0xef])));
+    }
+
+    #[test]
+    fn test_account_ids_accept_addresses_keys_and_names() {
+        let key = [0xabu8; 32];
+        let address = encode_bech32_address(&key);
+        assert!(address.starts_with("cmps1"));
+        assert_eq!(decode_bech32_address(&address), Ok(key));
+        assert_eq!(parse_account_id(&address), Ok(to_hex(key)));
+        assert_eq!(parse_account_id(&address.to_ascii_uppercase()), Ok(to_hex(key)));
+        assert_eq!(parse_account_id(&to_hex(key).to_ascii_uppercase()), Ok(to_hex(key)));
+        assert_eq!(parse_account_id(" alice "), Ok("alice".to_string()));
+        assert_eq!(account_bech32_address(&to_hex(key)), Some(address.clone()));
+        assert_eq!(account_bech32_address("alice"), None);
+
+        // One flipped character breaks the checksum
+        let mut typo = address.clone().into_bytes();
+        let last = typo.len() - 1;
+        typo[last] = if typo[last] == b'q' { b'p' } else { b'q' };
+        assert!(parse_account_id(std::str::from_utf8(&typo).unwrap()).is_err());
+
+        // Right checksum, wrong prefix or length
+        assert!(decode_bech32_address(&bech32_encode("other", &key).unwrap()).is_err());
+        assert!(matches!(
+            parse_account_id(&bech32_encode("cmps", &[1, 2, 3]).unwrap()),
+            Err(EncodingError::InvalidLength { what: "address", .. })
+        ));
+    }
+
+    #[test]
+    fn test_canonical_bytes_are_fixed_width_le() {
+        let bytes = canonical_bytes(&(1u64, "ab".to_string())).unwrap();
+        assert_eq!(bytes, [1, 0, 0, 0, 0, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+        let back: (u64, String) = from_canonical_bytes(&bytes).unwrap();
+        assert_eq!(back, (1, "ab".to_string()));
+        assert!(from_canonical_bytes::<(u64, String)>(&bytes[..4]).is_err());
+
+        let mut trailing = bytes.clone();
+        trailing.push(0);
+        assert!(from_canonical_bytes_bounded::<(u64, String)>(&trailing, 64).is_err());
+        assert_eq!(
+            from_canonical_bytes_bounded::<(u64, String)>(&bytes, 8),
+            Err(EncodingError::TooLarge { size: 18, limit: 8 })
+        );
+    }
+
+    #[test]
+    fn test_amounts_parse_exactly_and_round_trip() {
+        assert_eq!(Amount::parse("1.5 Compass", "Compass").unwrap().units, 150_000_000);
+        assert_eq!(Amount::parse(" 1.5 ", "Compass").unwrap().units, 150_000_000);
+        assert_eq!(Amount::parse(".00000001", "Compass").unwrap().units, 1);
+        assert_eq!(Amount::parse("3.", "Compass").unwrap().units, 300_000_000);
+        assert!(Amount::parse("1.5 COMPUTE", "Compass").is_err());
+
+        for bad in ["", ".", "-1", "1e8", "NaN", "1.000000001", "1,5", "184467440738"] {
+            assert!(Amount::parse(bad, "Compass").is_err(), "{}", bad);
+        }
+        assert_eq!(parse_units("184467440737.09551615", 8), Ok(u64::MAX));
+        assert_eq!(parse_units("42", 0), Ok(42));
+
+        assert_eq!(Amount::new(150_000_000, "Compass").to_string(), "1.5 Compass");
+        assert_eq!(format_units(1, 8), "0.00000001");
+        assert_eq!(format_units(0, 8), "0");
+        for units in [0, 1, 10, 100_000_000, 123_456_789, u64::MAX] {
+            assert_eq!(parse_units(&format_units(units, 8), 8), Ok(units));
+        }
+    }
+}
@@ -0,0 +1,148 @@
+// Epoch reports
+//
+// Once an epoch is over, the first leader of the next one appends an
+// `EpochReport` block. The block carries the headline totals and a hash of the
+// full report; every node tallies the same canonical blocks and events, keeps
+// the report, and can check it against the hash. Pool operators reconcile
+// payouts from `rewards` without replaying the epoch themselves.
+use crate::block::{Block, BlockType};
+use crate::events::{IndexedEvent, StateEvent};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Asset PoUW rewards are paid in
+pub const COMPUTE_ASSET: &str = "COMPUTE";
+
+/// What happened during one epoch
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct EpochReport {
+    pub epoch: u64,
+    /// Block heights covered; `None` when no block fell in the epoch
+    pub heights: Option<(u64, u64)>,
+    /// Blocks produced, by proposer
+    pub blocks_by_proposer: BTreeMap<String, u64>,
+    /// Compass fees charged (they go to the foundation account)
+    pub fees_collected: u64,
+    /// COMPUTE paid out as rewards
+    pub compute_emitted: u64,
+    /// Payouts by recipient, then asset
+    pub rewards: BTreeMap<String, BTreeMap<String, u64>>,
+    /// Vault assets burned, by asset
+    pub burns: BTreeMap<String, u64>,
+}
+
+impl EpochReport {
+    /// Tally `blocks` (the epoch's canonical blocks) and the events recorded at their heights
+    pub fn tally(epoch: u64, blocks: &[Block], events: &[IndexedEvent]) -> Self {
+        let mut report = EpochReport { epoch, ..Default::default() };
+        for block in blocks {
+            let header = &block.header;
+            report.heights = Some(match report.heights {
+                Some((lo, hi)) => (lo.min(header.index), hi.max(header.index)),
+                None => (header.index, header.index),
+            });
+            *report.blocks_by_proposer.entry(header.proposer.clone()).or_default() += 1;
+
+            match &header.block_type {
+                BlockType::Transfer { fee, .. }
+                | BlockType::SponsoredTransfer { fee, .. }
+                | BlockType::AssetFeeTransfer { fee, .. }
+                | BlockType::Mint { fee, .. } => report.fees_collected = report.fees_collected.saturating_add(*fee),
+                BlockType::Burn { compass_asset, burn_amount, fee, .. } => {
+                    report.fees_collected = report.fees_collected.saturating_add(*fee);
+                    let burned = report.burns.entry(compass_asset.clone()).or_default();
+                    *burned = burned.saturating_add(*burn_amount);
+                }
+                BlockType::Reward { recipient, amount, asset, .. } => report.add_reward(recipient, asset, *amount),
+                _ => {}
+            }
+        }
+        for indexed in events {
+            if let StateEvent::RewardPaid { account, asset, amount, .. } = &indexed.event {
+                report.add_reward(account, asset, *amount);
+            }
+        }
+        report
+    }
+
+    fn add_reward(&mut self, account: &str, asset: &str, amount: u64) {
+        let paid = self.rewards.entry(account.to_string()).or_default().entry(asset.to_string()).or_default();
+        *paid = paid.saturating_add(amount);
+        if asset == COMPUTE_ASSET {
+            self.compute_emitted = self.compute_emitted.saturating_add(amount);
+        }
+    }
+
+    /// Hex SHA-256 of the report's bincode encoding, as carried by the report block
+    pub fn hash(&self) -> String {
+        let bytes = bincode::serialize(self).unwrap_or_default();
+        crate::encoding::hash_to_hex(&crate::encoding::sha256(bytes))
+    }
+
+    /// The block type that records this report on chain
+    pub fn block_type(&self) -> BlockType {
+        BlockType::EpochReport {
+            epoch: self.epoch,
+            fees_collected: self.fees_collected,
+            compute_emitted: self.compute_emitted,
+            report_hash: self.hash(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::BlockHeader;
+
+    fn block(index: u64, proposer: &str, block_type: BlockType) -> Block {
+        let header = BlockHeader {
+            index,
+            timestamp: 0,
+            prev_hash: String::new(),
+            hash: String::new(),
+            proposer: proposer.to_string(),
+            signature_hex: String::new(),
+            block_type,
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        Block { header, transactions: vec![] }
+    }
+
+    #[test]
+    fn test_tally_sums_fees_rewards_and_burns() {
+        let blocks = vec![
+            block(10, "v1", BlockType::PoH { tick: 1, iterations: 1, hash: String::new(), proof: String::new() }),
+            block(11, "alice", BlockType::Transfer {
+                from: "alice".into(), to: "bob".into(), asset: "Compass".into(), amount: 50, nonce: 1, fee: 3,
+            }),
+            block(12, "bob", BlockType::Burn {
+                vault_id: "v".into(), collateral_asset: "BTC".into(), compass_asset: "cBTC".into(),
+                burn_amount: 20, redeemer: "bob".into(), destination_address: "addr".into(), fee: 2,
+            }),
+            block(13, "v1", BlockType::Reward {
+                recipient: "v1".into(), amount: 7, asset: "Compass".into(), reason: "block".into(),
+            }),
+        ];
+        let events = vec![
+            IndexedEvent { height: 12, event: StateEvent::RewardPaid {
+                account: "worker".into(), asset: COMPUTE_ASSET.into(), amount: 40, reason: "pouw".into(),
+            } },
+            IndexedEvent { height: 12, event: StateEvent::NonceChanged { account: "bob".into(), old: 0, new: 1 } },
+        ];
+
+        let report = EpochReport::tally(3, &blocks, &events);
+        assert_eq!(report.heights, Some((10, 13)));
+        assert_eq!(report.blocks_by_proposer["v1"], 2);
+        assert_eq!(report.fees_collected, 5);
+        assert_eq!(report.compute_emitted, 40);
+        assert_eq!(report.rewards["worker"][COMPUTE_ASSET], 40);
+        assert_eq!(report.rewards["v1"]["Compass"], 7);
+        assert_eq!(report.burns["cBTC"], 20);
+
+        let BlockType::EpochReport { report_hash, .. } = report.block_type() else { panic!("not a report block") };
+        assert_eq!(report_hash, EpochReport::tally(3, &blocks, &events).hash());
+        assert_eq!(EpochReport::tally(4, &[], &[]).heights, None);
+    }
+}
@@ -20,6 +20,8 @@ pub enum CompassError {
     MissingMetadata(String),
     #[error("Transaction failed: {0}")]
     TransactionError(String),
+    #[error("Arithmetic overflow: {0}")]
+    Overflow(String),
     #[error("Unknown error: {0}")]
     Unknown(String),
 }
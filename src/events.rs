@@ -0,0 +1,265 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// A single state mutation, recorded by Storage as it happens and tagged with
+/// the chain height at that moment (the index of the block being built).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub enum StateEvent {
+    BalanceChanged {
+        account: String,
+        asset: String,
+        old: u64,
+        new: u64,
+    },
+    NftOwnerChanged {
+        token_id: String,
+        from: Option<String>,
+        to: String,
+    },
+    VaultUpdated {
+        compass_asset: String,
+        collateral_balance: u64,
+        minted_supply: u64,
+    },
+    NonceChanged {
+        account: String,
+        old: u64,
+        new: u64,
+    },
+    /// A protocol payout (e.g. a PoUW settlement); the balance change itself is
+    /// recorded separately, this only says why it happened
+    RewardPaid {
+        account: String,
+        asset: String,
+        amount: u64,
+        reason: String,
+    },
+    /// Released amount of a vesting allocation moved from `old` to `new`; the
+    /// credit is recorded separately as a `BalanceChanged`
+    VestingReleased {
+        account: String,
+        old: u64,
+        new: u64,
+    },
+    /// Total `asset` minted by the emission schedule moved from `old` to `new`; the
+    /// credit is recorded separately as a `BalanceChanged`
+    Emitted {
+        asset: String,
+        old: u64,
+        new: u64,
+    },
+    /// Governance moved the stability fee rate from `old` (`None`: the configured
+    /// rate applied) to `new`
+    StabilityFeeSet {
+        old: Option<u64>,
+        new: u64,
+    },
+    /// `entity` withdrew collateral that had finished unbonding; `old` is its
+    /// unbonding queue before. The credit is recorded separately as a `BalanceChanged`
+    UnbondingReleased {
+        entity: String,
+        old: Vec<crate::layer2::collateral::Unbonding>,
+    },
+    /// A `ContractDeploy` block stored the contract at `address`
+    ContractDeployed {
+        address: String,
+    },
+    /// A contract run wrote the hex encoded state `key` of `address`, which held
+    /// `old` before (`None`: unset)
+    ContractStateChanged {
+        address: String,
+        key: String,
+        old: Option<Vec<u8>>,
+    },
+    /// A `ModelInvocation` block settled the signed request `request_id`
+    InvocationSettled {
+        request_id: String,
+    },
+    /// A `RedemptionRelease` block spent payout transaction `external_tx`
+    PayoutTxUsed {
+        external_tx: String,
+    },
+    /// One side of a market trade: `amount` base units of `order_id` filled at `price`
+    OrderFilled {
+        order_id: u64,
+        account: String,
+        trade_id: String,
+        price: u64,
+        amount: u64,
+    },
+    /// The unfilled rest of an IOC or market order was dropped
+    OrderCancelled {
+        order_id: u64,
+        account: String,
+        filled: u64,
+        cancelled: u64,
+    },
+    /// A `Schedule` block parked `tx_hash` until `execute_at_slot`
+    TxScheduled {
+        execute_at_slot: u64,
+        tx_hash: String,
+    },
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct IndexedEvent {
+    pub height: u64,
+    pub event: StateEvent,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BalanceDiff {
+    pub account: String,
+    pub asset: String,
+    pub before: u64,
+    pub after: u64,
+    pub delta: i128,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NftOwnershipDiff {
+    pub token_id: String,
+    pub from: Option<String>,
+    pub to: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct VaultDiff {
+    pub compass_asset: String,
+    pub collateral_balance: u64,
+    pub minted_supply: u64,
+}
+
+/// Net effect of all events in a height range
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct StateDiff {
+    pub from_height: u64,
+    pub to_height: u64,
+    pub balances: Vec<BalanceDiff>,
+    pub nft_ownership: Vec<NftOwnershipDiff>,
+    pub vaults: Vec<VaultDiff>,
+}
+
+impl StateDiff {
+    /// Collapse an ordered event stream into net changes.
+    /// Intermediate values are dropped; entries that end where they started are omitted.
+    pub fn from_events(from_height: u64, to_height: u64, events: &[IndexedEvent]) -> Self {
+        let mut balances: BTreeMap<(String, String), (u64, u64)> = BTreeMap::new();
+        let mut nfts: BTreeMap<String, (Option<String>, String)> = BTreeMap::new();
+        let mut vaults: BTreeMap<String, (u64, u64)> = BTreeMap::new();
+
+        for indexed in events {
+            match &indexed.event {
+                StateEvent::BalanceChanged { account, asset, old, new } => {
+                    balances
+                        .entry((account.clone(), asset.clone()))
+                        .and_modify(|(_, after)| *after = *new)
+                        .or_insert((*old, *new));
+                }
+                StateEvent::NftOwnerChanged { token_id, from, to } => {
+                    nfts.entry(token_id.clone())
+                        .and_modify(|(_, after)| *after = to.clone())
+                        .or_insert((from.clone(), to.clone()));
+                }
+                StateEvent::VaultUpdated { compass_asset, collateral_balance, minted_supply } => {
+                    vaults.insert(compass_asset.clone(), (*collateral_balance, *minted_supply));
+                }
+                // Kept for reorg rollback; nonces and vesting are not part of the published diff
+                StateEvent::NonceChanged { .. }
+                | StateEvent::RewardPaid { .. }
+                | StateEvent::VestingReleased { .. }
+                | StateEvent::Emitted { .. }
+                | StateEvent::StabilityFeeSet { .. }
+                | StateEvent::UnbondingReleased { .. }
+                | StateEvent::ContractDeployed { .. }
+                | StateEvent::ContractStateChanged { .. }
+                | StateEvent::InvocationSettled { .. }
+                | StateEvent::PayoutTxUsed { .. }
+                | StateEvent::OrderFilled { .. }
+                | StateEvent::OrderCancelled { .. }
+                | StateEvent::TxScheduled { .. } => {}
+            }
+        }
+
+        StateDiff {
+            from_height,
+            to_height,
+            balances: balances
+                .into_iter()
+                .filter(|(_, (before, after))| before != after)
+                .map(|((account, asset), (before, after))| BalanceDiff {
+                    account,
+                    asset,
+                    before,
+                    after,
+                    delta: after as i128 - before as i128,
+                })
+                .collect(),
+            nft_ownership: nfts
+                .into_iter()
+                .filter(|(_, (from, to))| from.as_deref() != Some(to.as_str()))
+                .map(|(token_id, (from, to))| NftOwnershipDiff { token_id, from, to })
+                .collect(),
+            vaults: vaults
+                .into_iter()
+                .map(|(compass_asset, (collateral_balance, minted_supply))| VaultDiff {
+                    compass_asset,
+                    collateral_balance,
+                    minted_supply,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bal(height: u64, account: &str, old: u64, new: u64) -> IndexedEvent {
+        IndexedEvent {
+            height,
+            event: StateEvent::BalanceChanged {
+                account: account.to_string(),
+                asset: "COMPASS".to_string(),
+                old,
+                new,
+            },
+        }
+    }
+
+    #[test]
+    fn test_balance_changes_collapse_to_net() {
+        let events = vec![bal(1, "alice", 100, 50), bal(2, "alice", 50, 80), bal(2, "bob", 0, 20), bal(3, "bob", 20, 0)];
+        let diff = StateDiff::from_events(0, 3, &events);
+
+        // bob ends where he started and is omitted
+        assert_eq!(
+            diff.balances,
+            vec![BalanceDiff {
+                account: "alice".to_string(),
+                asset: "COMPASS".to_string(),
+                before: 100,
+                after: 80,
+                delta: -20,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_nft_transfer_chain() {
+        let transfer = |from: Option<&str>, to: &str| IndexedEvent {
+            height: 1,
+            event: StateEvent::NftOwnerChanged {
+                token_id: "NFT-1".to_string(),
+                from: from.map(|s| s.to_string()),
+                to: to.to_string(),
+            },
+        };
+        let diff = StateDiff::from_events(0, 1, &[transfer(Some("a"), "b"), transfer(Some("b"), "c")]);
+        assert_eq!(
+            diff.nft_ownership,
+            vec![NftOwnershipDiff { token_id: "NFT-1".to_string(), from: Some("a".to_string()), to: "c".to_string() }]
+        );
+    }
+}
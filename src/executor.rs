@@ -0,0 +1,409 @@
+// Block execution
+//
+// `apply_block` is the one place a block's state transition is written. Every
+// path that moves the chain forward runs it once the block has been admitted:
+// - a leader's `Chain::append_*` checks signatures, nonces, balances and oracle
+//   attestations, then executes;
+// - `Chain::sync_block` checks a peer block's hash and signature, then executes;
+// - `Chain::reorganize` rolls back the abandoned blocks, then executes the new
+//   branch block by block;
+// - `Chain::replay_block` (replay, import) executes blocks accepted before.
+// Execution reads only the header and the state it is handed, so nodes that
+// apply the same blocks to the same state reach the same state root.
+//
+// Two things stay with the leader's admission because the header doesn't carry
+// what they need: an asset-fee transfer's fee leg, priced by the leader's oracle
+// quote, and a worker's earnings ledger, which accrues on the node that ran the
+// jobs. Recording blocks (PoH liveness, epoch reports, finality votes) are
+// handled when the block is committed.
+use tracing::info;
+
+use crate::block::{BlockHeader, BlockType, SettlementLeg};
+use crate::error::CompassError;
+use crate::layer2::checkpoint::{L2CheckpointRecord, L2Commitment};
+use crate::layer2::economics::EmissionSchedule;
+use crate::storage::{StateBatch, Storage};
+use crate::treasury::{TreasuryConfig, TREASURY_ACCOUNT};
+use crate::vault::compliance::FreezeRecord;
+use crate::vault::redemption::{RedemptionStatus, WithdrawalIntent};
+use crate::vault::stability::{StabilityFeeConfig, VaultAccrual};
+use crate::vault::VaultManager;
+
+/// What executing a block may change
+pub struct State<'a> {
+    pub storage: &'a Storage,
+    pub vaults: &'a mut VaultManager,
+    /// Treasury's share of fees (from consensus config)
+    pub treasury: TreasuryConfig,
+    /// Interest on vault debt, paid to the treasury (from consensus config)
+    pub stability_fee: StabilityFeeConfig,
+    /// Slot length the stability fee is charged by
+    pub slot_duration_ms: u64,
+    /// Block and PoUW reward curve (from consensus config)
+    pub emission: EmissionSchedule,
+    /// Epoch length the emission schedule halves by
+    pub slots_per_epoch: u64,
+}
+
+/// What a block changed besides balances and nonces, for the caller to report
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct Effects {
+    /// Set by an `AssetFreeze` block
+    pub freeze: Option<FreezeRecord>,
+    /// Withdrawal intent a `Burn` queued or a `RedemptionRelease` settled
+    pub redemption: Option<WithdrawalIntent>,
+    /// How a `ContractDeploy` or `ContractCall` run went; its gas is paid either way
+    pub contract: Option<Result<crate::vm::ContractOutcome, String>>,
+}
+
+/// Apply the state transition of `header`, a block whose admission checks have
+/// passed. Block types that only record something change nothing here.
+pub fn apply_block(header: &BlockHeader, state: &mut State<'_>) -> Result<Effects, CompassError> {
+    let storage = state.storage;
+    let writer = storage.writer();
+    let fee_to = storage.get_fee_recipient()?;
+    let treasury = state.treasury;
+    let mut effects = Effects::default();
+    match &header.block_type {
+        BlockType::Transfer { from, to, asset, amount, nonce, fee } => {
+            pay_fee(writer.batch(), from, &fee_to, *fee, &treasury)
+                .transfer(from, to, asset, *amount)
+                .set_nonce(from, *nonce)
+                .commit()?
+        }
+        BlockType::SponsoredTransfer { from, to, asset, amount, nonce, fee, fee_payer, .. } => {
+            pay_fee(writer.batch(), fee_payer, &fee_to, *fee, &treasury)
+                .transfer(from, to, asset, *amount)
+                .set_nonce(from, *nonce)
+                .commit()?
+        }
+        // The fee leg was charged at admission, at the leader's quote
+        BlockType::AssetFeeTransfer { from, to, asset, amount, nonce, .. } => {
+            writer.batch().transfer(from, to, asset, *amount).set_nonce(from, *nonce).commit()?
+        }
+        BlockType::MultisigTransfer { from, to, asset, amount, nonce, .. } => {
+            writer.batch().transfer(from, to, asset, *amount).set_nonce(from, *nonce).commit()?
+        }
+        BlockType::Mint { collateral_asset, collateral_amount, mint_amount, owner, tx_proof, fee, .. } => {
+            charge_stability_fee(state, &crate::vault::vault_asset_name(owner, collateral_asset), header.timestamp)?;
+            state
+                .vaults
+                .apply_deposit(collateral_asset, *collateral_amount, *mint_amount, owner, tx_proof)
+                .map_err(CompassError::TransactionError)?;
+            pay_fee(writer.batch(), owner, &fee_to, *fee, &treasury)
+                .credit(owner, &crate::vault::vault_asset_name(owner, collateral_asset), *mint_amount)
+                .commit()?
+        }
+        BlockType::Burn { collateral_asset, compass_asset, burn_amount, redeemer, destination_address, fee, .. } => {
+            // The vault keeps the collateral until the payout confirms (`RedemptionRelease`)
+            charge_stability_fee(state, compass_asset, header.timestamp)?;
+            let quote = state
+                .vaults
+                .quote_redemption(compass_asset, *burn_amount)
+                .map_err(CompassError::TransactionError)?;
+            pay_fee(writer.batch(), redeemer, &fee_to, *fee, &treasury)
+                .debit(redeemer, compass_asset, *burn_amount)
+                .commit()?;
+            let intent = WithdrawalIntent {
+                id: header.hash.clone(),
+                compass_asset: compass_asset.clone(),
+                collateral_asset: collateral_asset.clone(),
+                redeemer: redeemer.clone(),
+                destination_address: destination_address.clone(),
+                quote,
+                signature: header.signature_hex.clone(),
+                burn_height: header.index,
+                status: RedemptionStatus::Queued,
+                payout_tx: None,
+            };
+            storage.save_redemption(&intent)?;
+            effects.redemption = Some(intent);
+        }
+        BlockType::RedemptionRelease { intent_id, external_tx, .. } => {
+            let mut intent = storage
+                .get_redemption(intent_id)?
+                .filter(WithdrawalIntent::is_queued)
+                .ok_or_else(|| CompassError::InvalidState(format!("no queued redemption {}", intent_id)))?;
+            if let Some(paid) = storage.get_payout_tx_redemption(external_tx)? {
+                return Err(CompassError::InvalidState(format!("payout {} already released redemption {}", external_tx, paid)));
+            }
+            charge_stability_fee(state, &intent.compass_asset, header.timestamp)?;
+            state
+                .vaults
+                .release_redemption(&intent.compass_asset, &intent.quote)
+                .map_err(CompassError::TransactionError)?;
+            intent.status = RedemptionStatus::Released { external_tx: external_tx.clone(), height: header.index };
+            storage.mark_payout_tx(external_tx, intent_id)?;
+            storage.save_redemption(&intent)?;
+            effects.redemption = Some(intent);
+        }
+        BlockType::L2Checkpoint { sequence, state_hash, economics_hash, assets_hash, collateral_hash } => {
+            let commitment = L2Commitment {
+                economics_hash: economics_hash.clone(),
+                assets_hash: assets_hash.clone(),
+                collateral_hash: collateral_hash.clone(),
+            };
+            if &commitment.state_hash() != state_hash {
+                return Err(CompassError::InvalidState("checkpoint state hash does not match its components".to_string()));
+            }
+            let next = storage.get_latest_l2_checkpoint().map_or(1, |c| c.sequence + 1);
+            if *sequence != next {
+                return Err(CompassError::InvalidState(format!("expected checkpoint #{}, got #{}", next, sequence)));
+            }
+            storage.save_l2_checkpoint(&L2CheckpointRecord {
+                sequence: *sequence,
+                height: header.index,
+                state_hash: state_hash.clone(),
+                commitment,
+            })?;
+        }
+        BlockType::AssetFreeze { compass_asset, account, frozen, authority, reason, seq, .. } => {
+            if storage.get_freeze_authority(compass_asset)?.as_ref() != Some(authority) {
+                return Err(CompassError::InvalidState(format!("{} is not the freeze authority for {}", authority, compass_asset)));
+            }
+            let expected_seq = storage.get_freeze_seq(compass_asset);
+            if *seq != expected_seq {
+                return Err(CompassError::InvalidState(format!(
+                    "invalid freeze sequence: expected {}, got {}",
+                    expected_seq, seq
+                )));
+            }
+            let record = FreezeRecord {
+                compass_asset: compass_asset.clone(),
+                account: account.clone(),
+                frozen: *frozen,
+                authority: authority.clone(),
+                reason: reason.clone(),
+                seq: *seq,
+                height: header.index,
+                timestamp: header.timestamp,
+            };
+            storage.apply_freeze(&record)?;
+            effects.freeze = Some(record);
+        }
+        BlockType::FreezeAuthorityChange { compass_asset, authority } => storage.set_freeze_authority(compass_asset, authority)?,
+        BlockType::ValidatorRegistration { validator_id, pubkey, stake_amount, .. } => {
+            // The stake just leaves circulation; there is no staked balance yet
+            writer.debit(validator_id, "Compass", *stake_amount)?;
+            let mut validators = storage.get_active_validators()?;
+            if !validators.contains(validator_id) {
+                validators.push(validator_id.clone());
+                storage.set_active_validators(&validators)?;
+                info!("Validator Registered: {} (Total: {})", validator_id, validators.len());
+            }
+            storage.set_validator_pubkey(validator_id, pubkey)?
+        }
+        BlockType::EarningsClaim { worker, amount, nonce, .. } => writer
+            .batch()
+            .credit(worker, crate::layer3::earnings::EARNINGS_ASSET, *amount)
+            .set_nonce(worker, *nonce)
+            .commit()?,
+        BlockType::TreasurySpend { recipient, amount, .. } => {
+            writer.transfer(TREASURY_ACCOUNT, recipient, crate::treasury::TREASURY_ASSET, *amount)?
+        }
+        // Each vault's next charge, back to its last one, is at the new rate
+        BlockType::StabilityFeeChange { annual_rate_bps, .. } => storage.set_stability_fee_rate(*annual_rate_bps)?,
+        BlockType::ContractDeploy { deployer, code, gas_limit, gas_price, nonce, .. } => {
+            let max_cost = reserve_gas(storage, deployer, *gas_limit, *gas_price)?;
+            let run = crate::vm::deploy(storage, header.index, deployer, code, *gas_limit, *nonce);
+            effects.contract = Some(pay_gas(writer.batch(), deployer, &fee_to, run, max_cost, *gas_price, &treasury, *nonce)?);
+        }
+        BlockType::ContractCall { caller, contract, method, input, gas_limit, gas_price, nonce, .. } => {
+            let max_cost = reserve_gas(storage, caller, *gas_limit, *gas_price)?;
+            let run = crate::vm::call(storage, caller, contract, method, input, *gas_limit);
+            effects.contract = Some(pay_gas(writer.batch(), caller, &fee_to, run, max_cost, *gas_price, &treasury, *nonce)?);
+        }
+        // Paid out at the block's own slot, so every node releases the same withdrawals
+        BlockType::UnbondedWithdrawal { entity, amount, nonce, .. } => {
+            let slot = header.timestamp / state.slot_duration_ms.max(1);
+            let matured = crate::layer2::collateral::matured(&storage.get_l2_unbonding_of(entity)?, slot);
+            if matured != *amount {
+                return Err(CompassError::InvalidState(format!(
+                    "{} has {} unbonded by slot {}, block withdraws {}",
+                    entity, matured, slot, amount
+                )));
+            }
+            storage.release_unbonded(entity, slot)?;
+            writer
+                .batch()
+                .credit(entity, crate::layer2::collateral::COLLATERAL_ASSET, *amount)
+                .set_nonce(entity, *nonce)
+                .commit()?
+        }
+        // Metered and priced at the block's time; the request id stands in for a nonce
+        BlockType::ModelInvocation { token_id, caller, inputs, timestamp, .. } => {
+            let request_id = crate::layer3::model_nft::invoke_request_id(token_id, caller, inputs, *timestamp);
+            if storage.is_invocation_settled(&request_id)? {
+                return Err(CompassError::InvalidState(format!("invocation {} is already settled", request_id)));
+            }
+            let nft = storage
+                .get_model_nft(token_id)?
+                .ok_or_else(|| CompassError::InvalidState(format!("model {} not found", token_id)))?;
+            let (_, fee) = nft
+                .invocation_fee(caller, inputs.len(), header.timestamp / 1000)
+                .map_err(CompassError::InvalidState)?;
+            if fee > 0 {
+                let (royalty, owner_share) = nft.split_revenue(fee);
+                writer
+                    .batch()
+                    .debit(caller, "COMPASS", fee)
+                    .credit(&nft.creator, "COMPASS", royalty)
+                    .credit(&nft.current_owner, "COMPASS", owner_share)
+                    .commit()?;
+            }
+            storage.mark_invocation_settled(&request_id, header.index)?
+        }
+        // Models are looked up before any balance moves, so a bad rental leaves nothing half done
+        BlockType::Settlement { legs, .. } => {
+            let mut rented = Vec::new();
+            for leg in legs {
+                if let SettlementLeg::Rental { token_id, renter, expires_at, rate_per_hour } = leg {
+                    let mut nft = storage
+                        .get_model_nft(token_id)?
+                        .ok_or_else(|| CompassError::InvalidState(format!("model {} not found", token_id)))?;
+                    nft.rental_status = Some(crate::layer3::model_nft::RentalAgreement {
+                        renter: renter.clone(),
+                        expires_at: *expires_at,
+                        rate_per_hour: *rate_per_hour,
+                    });
+                    rented.push(nft);
+                }
+            }
+            let batch = legs.iter().fold(writer.batch(), |batch, leg| match leg {
+                SettlementLeg::Transfer { from, to, asset, amount } => batch.transfer(from, to, asset, *amount),
+                SettlementLeg::Debit { account, asset, amount } => batch.debit(account, asset, *amount),
+                SettlementLeg::Credit { account, asset, amount } => batch.credit(account, asset, *amount),
+                SettlementLeg::Rental { .. } => batch,
+            });
+            batch.commit()?;
+            for nft in rented {
+                storage.save_model_nft(&nft)?;
+            }
+        }
+        BlockType::Trade { maker, taker, taker_side, base, quote, amount, price, maker_fee, taker_fee, .. } => {
+            let (buyer, seller) = taker_side.buyer_seller(taker, maker);
+            let (buyer_fee, seller_fee) = match taker_side {
+                crate::market::OrderSide::Buy => (*taker_fee, *maker_fee),
+                crate::market::OrderSide::Sell => (*maker_fee, *taker_fee),
+            };
+            let cost = crate::market::notional(*price, *amount)
+                .ok_or_else(|| CompassError::Overflow(format!("trade value {} x {}", amount, price)))?;
+            let mut batch = writer
+                .batch()
+                .transfer(seller, buyer, base, *amount)
+                .transfer(buyer, seller, quote, cost);
+            // Fee-free fills leave the proposer's balances alone
+            for (payer, fee) in [(buyer, buyer_fee), (seller, seller_fee)] {
+                if fee > 0 {
+                    batch = pay_fee_in(batch, payer, &header.proposer, quote, fee, &treasury);
+                }
+            }
+            batch.commit()?
+        }
+        // Delegations are read back from the blocks by `governance::tally`
+        BlockType::Delegate { delegator, nonce, .. } => writer.set_nonce(delegator, *nonce)?,
+        BlockType::Vesting { account, amount } => {
+            storage.release_vesting(account, *amount, header.timestamp)?;
+            writer.credit(account, crate::vesting::VESTING_ASSET, *amount)?
+        }
+        BlockType::PoH { .. } => mint_block_reward(state, &header.proposer, header.timestamp)?,
+        BlockType::Schedule { account, execute_at_slot, fee, envelope } => {
+            let tx = crate::network::decode_scheduled_envelope(envelope).map_err(CompassError::InvalidState)?;
+            let crate::network::TransactionPayload::Scheduled { inner, .. } = tx.payload else {
+                return Err(CompassError::InvalidState("not a scheduled transaction".to_string()));
+            };
+            pay_fee(writer.batch(), account, &fee_to, *fee, &treasury).commit()?;
+            let tx_hash = crate::encoding::hash_to_hex(&crate::encoding::sha256(envelope));
+            storage.save_scheduled_tx(*execute_at_slot, &tx_hash, &inner)?
+        }
+        _ => {}
+    }
+    Ok(effects)
+}
+
+/// Check `sender` can pay for all of `gas_limit` before its contract runs, so the
+/// run's writes never land without its gas. Returns that most it can cost.
+fn reserve_gas(storage: &Storage, sender: &str, gas_limit: u64, gas_price: u64) -> Result<u64, CompassError> {
+    let max_cost = crate::vm::gas_cost(gas_limit, gas_price)
+        .ok_or_else(|| CompassError::Overflow(format!("gas {} x {}", gas_limit, gas_price)))?;
+    let balance = storage.get_balance(sender, "Compass")?;
+    if balance < max_cost {
+        return Err(CompassError::InvalidState(format!("{} has {} Compass, gas may cost {}", sender, balance, max_cost)));
+    }
+    Ok(max_cost)
+}
+
+/// Charge `sender` for a contract run and spend its nonce: the gas it used at
+/// `gas_price`, or `max_cost` if it failed
+#[allow(clippy::too_many_arguments)]
+fn pay_gas(
+    batch: StateBatch<'_>,
+    sender: &str,
+    fee_to: &str,
+    run: Result<crate::vm::ContractOutcome, CompassError>,
+    max_cost: u64,
+    gas_price: u64,
+    treasury: &TreasuryConfig,
+    nonce: u64,
+) -> Result<Result<crate::vm::ContractOutcome, String>, CompassError> {
+    let cost = match &run {
+        Ok(outcome) => crate::vm::gas_cost(outcome.gas_used, gas_price).unwrap_or(max_cost).min(max_cost),
+        Err(_) => max_cost,
+    };
+    pay_fee(batch, sender, fee_to, cost, treasury).set_nonce(sender, nonce).commit()?;
+    Ok(run.map_err(|e| e.to_string()))
+}
+
+/// Mint the emission schedule's reward for a PoH block to its proposer
+fn mint_block_reward(state: &State<'_>, proposer: &str, timestamp_ms: u64) -> Result<(), CompassError> {
+    let asset = crate::layer2::economics::BLOCK_REWARD_ASSET;
+    let slot = timestamp_ms / state.slot_duration_ms.max(1);
+    let epoch = crate::leader_schedule::epoch_of(slot, state.slots_per_epoch);
+    let reward = state.emission.block_reward(epoch, state.storage.get_emitted(asset)?);
+    if reward == 0 {
+        return Ok(());
+    }
+    state.storage.record_emission(asset, reward)?;
+    state.storage.writer().credit(proposer, asset, reward)?;
+    state.storage.record_reward(proposer, asset, reward, "block")
+}
+
+/// Charge `compass_asset`'s vault the stability fee since it was last charged, up to
+/// the slot of `timestamp_ms`: its debt grows by the fee, which is minted to the treasury
+fn charge_stability_fee(state: &mut State<'_>, compass_asset: &str, timestamp_ms: u64) -> Result<(), CompassError> {
+    if state.stability_fee.annual_rate_bps == 0 {
+        return Ok(());
+    }
+    let Some(vault) = state.vaults.vaults.get_mut(compass_asset) else {
+        return Ok(());
+    };
+    let slot = timestamp_ms / state.slot_duration_ms.max(1);
+    let mut accrual = state.storage.get_vault_accrual(compass_asset)?.unwrap_or_else(|| VaultAccrual::starting_at(slot));
+    let fee = accrual.charge(vault.minted_supply, slot, &state.stability_fee, state.slot_duration_ms);
+    if fee > 0 {
+        vault.minted_supply = vault
+            .minted_supply
+            .checked_add(fee)
+            .ok_or_else(|| CompassError::InvalidState("Minted supply overflow".to_string()))?;
+        state.storage.writer().credit(TREASURY_ACCOUNT, compass_asset, fee)?;
+        state.storage.save_vault(compass_asset, vault)?;
+    }
+    state.storage.save_vault_accrual(compass_asset, &accrual)
+}
+
+/// Add a block's Compass fee to `batch`: the treasury's share to the treasury and
+/// the rest to `fee_to`. A zero share adds no leg, so the treasury account only
+/// appears in state once it is paid something.
+pub fn pay_fee<'a>(batch: StateBatch<'a>, payer: &str, fee_to: &str, fee: u64, treasury: &TreasuryConfig) -> StateBatch<'a> {
+    pay_fee_in(batch, payer, fee_to, "Compass", fee, treasury)
+}
+
+/// `pay_fee` for a fee charged in `asset`, such as a trade's quote asset
+pub fn pay_fee_in<'a>(batch: StateBatch<'a>, payer: &str, fee_to: &str, asset: &str, fee: u64, treasury: &TreasuryConfig) -> StateBatch<'a> {
+    let share = treasury.fee_share(fee);
+    let batch = batch.transfer(payer, fee_to, asset, fee - share);
+    if share == 0 {
+        return batch;
+    }
+    batch.transfer(payer, TREASURY_ACCOUNT, asset, share)
+}
@@ -0,0 +1,593 @@
+// Chain data export and import
+//
+// `compass chain export` (or `compass export blocks`) writes the canonical chain
+// in height order, either as JSON Lines, one block per line, so it can be loaded
+// into pandas, Spark or jq without any Compass code, or as a compact binary file
+// for backups. `compass chain import` reads either back into a database (e.g. one
+// on another storage backend). Every block's hash and signature are checked
+// before it is written, then it is re-executed and its state root compared.
+//
+// JSONL schema, one JSON object per line:
+//
+// | field          | type            | meaning                                              |
+// |----------------|-----------------|------------------------------------------------------|
+// | `v`            | number          | schema version, currently 1                          |
+// | `height`       | number          | block index                                          |
+// | `hash`         | string          | block hash (hex), same as `header.hash`              |
+// | `type`         | string          | block type name, e.g. `Transfer` (`BlockType::kind`) |
+// | `header`       | object          | the full `BlockHeader`, as returned by `getBlock`    |
+// | `transactions` | array of string | raw transaction payloads, hex encoded                |
+//
+// `header.block_type` is externally tagged: `{"Transfer": {"from": ..., ...}}`,
+// or a bare string for variants without fields such as `"Genesis"`.
+//
+// The binary format is the 8 bytes `BIN_MAGIC`, then per block a little-endian
+// u32 length followed by that many bytes of bincode `Block`, the encoding blocks
+// are stored in.
+use std::io::{BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::{Block, BlockHeader, BlockType};
+use crate::chain::Chain;
+use crate::error::CompassError;
+use crate::storage::Storage;
+
+/// Current JSONL schema version
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// First bytes of a binary export; the digit is the format version
+pub const BIN_MAGIC: &[u8; 8] = b"CMPSBLK1";
+
+/// Largest block record a binary import will read, so a corrupt length can't
+/// ask for gigabytes
+const MAX_BIN_RECORD: usize = 64 * 1024 * 1024;
+
+/// Heights to export, parsed from `a..b` (end exclusive), `a..=b`, `a..`, `..b`
+/// or a single height
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BlockRange {
+    pub from: u64,
+    /// Last height, inclusive; `None` runs to the head
+    pub to: Option<u64>,
+}
+
+impl std::str::FromStr for BlockRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let height = |part: &str| part.trim().parse::<u64>().map_err(|_| format!("'{}' is not a block height", part));
+        let Some((start, end)) = s.split_once("..") else {
+            let at = height(s)?;
+            return Ok(Self { from: at, to: Some(at) });
+        };
+        let from = if start.trim().is_empty() { 0 } else { height(start)? };
+        let to = match end.strip_prefix('=') {
+            Some(last) => Some(height(last)?),
+            None if end.trim().is_empty() => None,
+            None => match height(end)?.checked_sub(1) {
+                Some(last) => Some(last),
+                None => return Err(format!("'{}' is an empty range", s)),
+            },
+        };
+        if to.is_some_and(|to| to < from) {
+            return Err(format!("'{}' is an empty range", s));
+        }
+        Ok(Self { from, to })
+    }
+}
+
+/// One line of an export
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BlockRecord {
+    pub v: u32,
+    pub height: u64,
+    pub hash: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub header: BlockHeader,
+    pub transactions: Vec<String>,
+}
+
+impl BlockRecord {
+    pub fn from_block(block: &Block) -> Self {
+        Self {
+            v: SCHEMA_VERSION,
+            height: block.header.index,
+            hash: block.header.hash.clone(),
+            kind: block.header.block_type.kind().to_string(),
+            header: block.header.clone(),
+            transactions: block.transactions.iter().map(hex::encode).collect(),
+        }
+    }
+
+    /// The block this line describes, after checking the line agrees with itself.
+    /// Its hash is checked by the import, like a binary record's.
+    pub fn into_block(self) -> Result<Block, CompassError> {
+        if self.v != SCHEMA_VERSION {
+            return Err(CompassError::DeserializationError(format!("unsupported schema version {}", self.v)));
+        }
+        if self.height != self.header.index || self.hash != self.header.hash {
+            return Err(CompassError::InvalidState(format!(
+                "record for block {} disagrees with its header",
+                self.height
+            )));
+        }
+        let transactions = self
+            .transactions
+            .iter()
+            .map(|tx| hex::decode(tx).map_err(|e| CompassError::DeserializationError(e.to_string())))
+            .collect::<Result<_, _>>()?;
+        Ok(Block { header: self.header, transactions })
+    }
+}
+
+/// Call `f` with `storage`'s canonical blocks `from..=to` (capped at its head),
+/// in order. Returns how many there were.
+fn for_each_block(
+    storage: &Storage,
+    from: u64,
+    to: u64,
+    mut f: impl FnMut(&Block) -> Result<(), CompassError>,
+) -> Result<u64, CompassError> {
+    let height = crate::replay::chain_height(storage)?;
+    if height == 0 {
+        return Ok(0);
+    }
+    let mut written = 0;
+    for index in from..=to.min(height - 1) {
+        let block = storage
+            .get_block_by_height(index)?
+            .ok_or_else(|| CompassError::InvalidState(format!("block {} missing from the database", index)))?;
+        f(&block)?;
+        written += 1;
+    }
+    Ok(written)
+}
+
+/// Write `storage`'s canonical blocks `from..=to` (capped at its head) to `out`,
+/// one record per line. Returns how many were written.
+pub fn export_jsonl(storage: &Storage, from: u64, to: u64, out: &mut impl Write) -> Result<u64, CompassError> {
+    for_each_block(storage, from, to, |block| {
+        let line = serde_json::to_string(&BlockRecord::from_block(block))
+            .map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        writeln!(out, "{}", line).map_err(|e| CompassError::DatabaseError(e.to_string()))
+    })
+}
+
+/// Write `storage`'s canonical blocks `from..=to` (capped at its head) to `out`
+/// in the binary format. Returns how many were written.
+pub fn export_bin(storage: &Storage, from: u64, to: u64, out: &mut impl Write) -> Result<u64, CompassError> {
+    let io = |e: std::io::Error| CompassError::DatabaseError(e.to_string());
+    out.write_all(BIN_MAGIC).map_err(io)?;
+    for_each_block(storage, from, to, |block| {
+        let bytes = bincode::serialize(block).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        out.write_all(&(bytes.len() as u32).to_le_bytes()).map_err(io)?;
+        out.write_all(&bytes).map_err(io)
+    })
+}
+
+/// What an import did
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    /// Blocks executed and stored
+    pub imported: u64,
+    /// Records for blocks `chain` already had (e.g. genesis, or an earlier partial import)
+    pub skipped: u64,
+}
+
+/// The checks a block must pass before an import writes it: its header hashes to
+/// its hash, and it carries the signature a synced block would need
+fn verify_block(chain: &Chain, block: &Block) -> Result<(), CompassError> {
+    let calculated = block.header.calculate_hash()?;
+    if calculated != block.header.hash {
+        return Err(CompassError::HashMismatch(calculated, block.header.hash.clone()));
+    }
+    chain.verify_block_signature(block)
+}
+
+/// Verify and execute one imported block, or skip it if `chain` already has it
+fn import_block(chain: &mut Chain, block: Block, report: &mut ImportReport) -> Result<(), CompassError> {
+    let index = block.header.index;
+    if index < chain.height {
+        let local = chain.storage.get_block_by_height(index)?.map(|b| b.header.hash);
+        if local.as_deref() != Some(block.header.hash.as_str()) {
+            return Err(CompassError::InvalidState(format!(
+                "block {} differs from the one already in the database",
+                index
+            )));
+        }
+        report.skipped += 1;
+        return Ok(());
+    }
+    if index != chain.height {
+        return Err(CompassError::InvalidState(format!("expected block {}, found {}", chain.height, index)));
+    }
+    verify_block(chain, &block)?;
+
+    let header_root = block.header.state_root.clone();
+    chain.replay_block(block)?;
+    // Blocks from before state roots were stamped carry none
+    if !header_root.is_empty() {
+        let root = chain.state_root()?;
+        if root != header_root {
+            return Err(CompassError::InvalidState(format!(
+                "state root after block {} is {}, header says {}",
+                index, root, header_root
+            )));
+        }
+    }
+    report.imported += 1;
+    Ok(())
+}
+
+/// Execute the JSONL blocks in `input` on top of `chain`. Records below
+/// `chain.height` must match the blocks already there; the rest must follow on
+/// without gaps, pass their hash and signature checks, and each must leave the
+/// state root its header carries.
+pub fn import_jsonl(chain: &mut Chain, input: impl BufRead) -> Result<ImportReport, CompassError> {
+    let mut report = ImportReport::default();
+    for (n, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let at_line = |e: CompassError| CompassError::InvalidState(format!("line {}: {}", n + 1, e));
+        let record: BlockRecord =
+            serde_json::from_str(&line).map_err(|e| at_line(CompassError::DeserializationError(e.to_string())))?;
+        let block = record.into_block().map_err(at_line)?;
+        import_block(chain, block, &mut report).map_err(at_line)?;
+    }
+    Ok(report)
+}
+
+/// `import_jsonl` for a binary export
+pub fn import_bin(chain: &mut Chain, mut input: impl BufRead) -> Result<ImportReport, CompassError> {
+    let io = |e: std::io::Error| CompassError::DeserializationError(e.to_string());
+    let mut magic = [0u8; 8];
+    input.read_exact(&mut magic).map_err(io)?;
+    if &magic != BIN_MAGIC {
+        return Err(CompassError::DeserializationError("not a Compass binary block export".to_string()));
+    }
+    let mut report = ImportReport::default();
+    for n in 1.. {
+        if input.fill_buf().map_err(io)?.is_empty() {
+            break;
+        }
+        let at_record = |e: CompassError| CompassError::InvalidState(format!("record {}: {}", n, e));
+        let mut len = [0u8; 4];
+        input.read_exact(&mut len).map_err(|e| at_record(io(e)))?;
+        let len = u32::from_le_bytes(len) as usize;
+        if len > MAX_BIN_RECORD {
+            return Err(at_record(CompassError::DeserializationError(format!("record length {} is too large", len))));
+        }
+        let mut bytes = vec![0u8; len];
+        input.read_exact(&mut bytes).map_err(|e| at_record(io(e)))?;
+        let block: Block = bincode::deserialize(&bytes)
+            .map_err(|e| at_record(CompassError::DeserializationError(e.to_string())))?;
+        import_block(chain, block, &mut report).map_err(at_record)?;
+    }
+    Ok(report)
+}
+
+// --- Wallet activity ---
+//
+// `compass wallet export-history --format csv` writes one row per value movement
+// an account took part in, oldest first, for bookkeeping and tax reporting:
+//
+// | column         | meaning                                                        |
+// |----------------|----------------------------------------------------------------|
+// | `timestamp`    | block time, RFC 3339 UTC                                       |
+// | `type`         | send, receive, fee, mint, burn, reward or stake                |
+// | `asset`        | asset moved                                                    |
+// | `amount`       | whole units moved, always positive; `type` gives the direction |
+// | `counterparty` | other account, vault or external address, if any               |
+// | `fee`          | Compass fee this account paid, in whole units                  |
+// | `block`        | block height                                                   |
+// | `block_hash`   | block hash (hex)                                               |
+//
+// An asset-fee transfer's fee is reported as the Compass it bought; what the
+// sender paid in the fee asset depended on that block's oracle quote.
+
+/// One row of a wallet activity export
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ActivityRecord {
+    pub timestamp: String,
+    #[serde(rename = "type")]
+    pub kind: String,
+    pub asset: String,
+    pub amount: String,
+    pub counterparty: String,
+    pub fee: String,
+    pub block: u64,
+    pub block_hash: String,
+}
+
+/// CSV header matching `ActivityRecord::csv_row`
+pub const ACTIVITY_CSV_HEADER: &str = "timestamp,type,asset,amount,counterparty,fee,block,block_hash";
+
+/// Header timestamps are seconds in some blocks and milliseconds in others;
+/// anything past year 33658 in seconds is taken as milliseconds.
+fn block_time(timestamp: u64) -> String {
+    let secs = if timestamp >= 1_000_000_000_000 { timestamp / 1000 } else { timestamp };
+    chrono::DateTime::from_timestamp(secs as i64, 0)
+        .map(|t| t.to_rfc3339_opts(chrono::SecondsFormat::Secs, true))
+        .unwrap_or_default()
+}
+
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+impl ActivityRecord {
+    /// Rows `account` gets for one block of its history; none for blocks that
+    /// moved no value of its own (votes, freezes, ...)
+    pub fn from_block(account: &str, height: u64, hash: &str, timestamp: u64, block_type: &BlockType) -> Vec<Self> {
+        let row = |kind: &str, asset: &str, amount: u64, counterparty: &str, fee: u64| ActivityRecord {
+            timestamp: block_time(timestamp),
+            kind: kind.to_string(),
+            asset: asset.to_string(),
+            amount: crate::encoding::format_units(amount, crate::encoding::asset_decimals(asset)),
+            counterparty: counterparty.to_string(),
+            fee: crate::encoding::format_units(fee, crate::encoding::asset_decimals("Compass")),
+            block: height,
+            block_hash: hash.to_string(),
+        };
+        let transfer = |from: &str, to: &str, asset: &str, amount: u64, fee: u64, fee_payer: &str| {
+            let mut rows = Vec::new();
+            if from == account {
+                rows.push(row("send", asset, amount, to, if fee_payer == account { fee } else { 0 }));
+            } else if fee_payer == account && fee > 0 {
+                rows.push(row("fee", "Compass", 0, from, fee));
+            }
+            if to == account {
+                rows.push(row("receive", asset, amount, from, 0));
+            }
+            rows
+        };
+
+        match block_type {
+            BlockType::Transfer { from, to, asset, amount, fee, .. }
+            | BlockType::AssetFeeTransfer { from, to, asset, amount, fee, .. } => {
+                transfer(from, to, asset, *amount, *fee, from)
+            }
+            BlockType::SponsoredTransfer { from, to, asset, amount, fee, fee_payer, .. } => {
+                transfer(from, to, asset, *amount, *fee, fee_payer)
+            }
+            BlockType::MultisigTransfer { from, to, asset, amount, .. } => transfer(from, to, asset, *amount, 0, from),
+            BlockType::TreasurySpend { recipient, amount, .. } => transfer(
+                crate::treasury::TREASURY_ACCOUNT,
+                recipient,
+                crate::treasury::TREASURY_ASSET,
+                *amount,
+                0,
+                crate::treasury::TREASURY_ACCOUNT,
+            ),
+            BlockType::Trade { trade_id, maker, taker, taker_side, base, quote, amount, price, maker_fee, taker_fee } => {
+                let (buyer, seller) = taker_side.buyer_seller(taker, maker);
+                let cost = crate::market::notional(*price, *amount).unwrap_or_default();
+                let mut rows = transfer(seller, buyer, base, *amount, 0, seller);
+                rows.extend(transfer(buyer, seller, quote, cost, 0, buyer));
+                // Trade fees are charged in the quote asset, so they get rows of their own
+                for (payer, fee) in [(maker, *maker_fee), (taker, *taker_fee)] {
+                    if payer == account && fee > 0 {
+                        rows.push(row("fee", quote, fee, trade_id, 0));
+                    }
+                }
+                rows
+            }
+            BlockType::Mint { vault_id, collateral_asset, mint_amount, owner, fee, .. } if owner == account => {
+                let asset = crate::vault::vault_asset_name(owner, collateral_asset);
+                vec![row("mint", &asset, *mint_amount, vault_id, *fee)]
+            }
+            BlockType::Burn { compass_asset, burn_amount, redeemer, destination_address, fee, .. } if redeemer == account => {
+                vec![row("burn", compass_asset, *burn_amount, destination_address, *fee)]
+            }
+            BlockType::Reward { recipient, amount, asset, .. } if recipient == account => {
+                vec![row("reward", asset, *amount, "", 0)]
+            }
+            BlockType::EarningsClaim { worker, amount, .. } if worker == account => {
+                vec![row("earnings", crate::layer3::earnings::EARNINGS_ASSET, *amount, "", 0)]
+            }
+            BlockType::Vesting { account: beneficiary, amount } if beneficiary == account => {
+                vec![row("vesting", crate::vesting::VESTING_ASSET, *amount, "", 0)]
+            }
+            BlockType::ValidatorRegistration { validator_id, stake_amount, .. } if validator_id == account => {
+                vec![row("stake", "Compass", *stake_amount, "", 0)]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    pub fn csv_row(&self) -> String {
+        [
+            self.timestamp.as_str(),
+            &self.kind,
+            &self.asset,
+            &self.amount,
+            &self.counterparty,
+            &self.fee,
+            &self.block.to_string(),
+            &self.block_hash,
+        ]
+        .map(csv_field)
+        .join(",")
+    }
+}
+
+/// Write `records` as CSV with a header line
+pub fn write_activity_csv(records: &[ActivityRecord], out: &mut impl Write) -> std::io::Result<()> {
+    writeln!(out, "{}", ACTIVITY_CSV_HEADER)?;
+    for record in records {
+        writeln!(out, "{}", record.csv_row())?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+
+    fn transfer(chain: &mut Chain, sender: &KeyPair, nonce: u64) {
+        let mut header = BlockHeader {
+            index: chain.height,
+            timestamp: 1_000 + nonce,
+            prev_hash: chain.head_hash().unwrap_or_default(),
+            hash: String::new(),
+            proposer: sender.public_key_hex(),
+            signature_hex: String::new(),
+            block_type: BlockType::Transfer {
+                from: sender.public_key_hex(),
+                to: "bob".to_string(),
+                asset: "Compass".to_string(),
+                amount: 10,
+                nonce,
+                fee: 0,
+            },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = sender.sign_hex(&crate::encoding::hash_from_hex(&header.hash).unwrap());
+        chain.append_transfer(header, &sender.public_key_hex()).unwrap();
+    }
+
+    #[test]
+    fn test_jsonl_round_trips_the_chain() {
+        let alice = KeyPair::from_seed("export-alice");
+        let fresh = || {
+            let chain = Chain::new_in_memory();
+            chain.storage.writer().set_balance(&alice.public_key_hex(), "Compass", 100).unwrap();
+            chain
+        };
+        let mut live = fresh();
+        for nonce in 1..=3 {
+            transfer(&mut live, &alice, nonce);
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(export_jsonl(&live.storage, 0, u64::MAX, &mut out).unwrap(), 3);
+        let text = String::from_utf8(out.clone()).unwrap();
+        let first: serde_json::Value = serde_json::from_str(text.lines().next().unwrap()).unwrap();
+        assert_eq!((first["v"].as_u64(), first["type"].as_str()), (Some(1), Some("Transfer")));
+
+        let mut imported = fresh();
+        let report = import_jsonl(&mut imported, out.as_slice()).unwrap();
+        assert_eq!(report, ImportReport { imported: 3, skipped: 0 });
+        assert_eq!(imported.head_hash(), live.head_hash());
+        assert_eq!(imported.storage.get_balance("bob", "Compass").unwrap(), 30);
+
+        // Importing again only confirms what is already there
+        let again = import_jsonl(&mut imported, out.as_slice()).unwrap();
+        assert_eq!(again, ImportReport { imported: 0, skipped: 3 });
+
+        // A tampered record fails its hash check
+        let tampered = text.replacen("\"amount\":10", "\"amount\":99", 1);
+        assert!(import_jsonl(&mut fresh(), tampered.as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_bin_import_verifies_before_writing() {
+        let leader = KeyPair::from_seed("export-leader");
+        let pk = leader.public_key_hex();
+        let fresh = || {
+            let chain = Chain::new_in_memory();
+            chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+            chain
+        };
+        let mut live = fresh();
+        for tick in 1..=3 {
+            let mut header = BlockHeader {
+                index: live.height,
+                timestamp: 1_000 + tick,
+                prev_hash: live.head_hash().unwrap_or_default(),
+                hash: String::new(),
+                proposer: pk.clone(),
+                signature_hex: String::new(),
+                block_type: BlockType::PoH { tick, iterations: 1, hash: String::new(), proof: String::new() },
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            header.hash = header.calculate_hash().unwrap();
+            header.signature_hex = leader.sign_hex(&crate::encoding::hash_from_hex(&header.hash).unwrap());
+            live.append_poh(header, &pk).unwrap();
+        }
+
+        let mut out = Vec::new();
+        assert_eq!(export_bin(&live.storage, 1, 2, &mut out).unwrap(), 2);
+        let mut all = Vec::new();
+        export_bin(&live.storage, 0, u64::MAX, &mut all).unwrap();
+        let mut imported = fresh();
+        assert_eq!(import_bin(&mut imported, all.as_slice()).unwrap(), ImportReport { imported: 3, skipped: 0 });
+        assert_eq!(imported.head_hash(), live.head_hash());
+        assert!(import_bin(&mut fresh(), &b"not an export"[..]).is_err());
+
+        // Re-signed by someone else: the hash still matches, the signature doesn't
+        let mut forged = live.storage.get_block_by_height(2).unwrap().unwrap();
+        forged.header.signature_hex =
+            KeyPair::from_seed("mallory").sign_hex(&crate::encoding::hash_from_hex(&forged.header.hash).unwrap());
+        let mut file = BIN_MAGIC.to_vec();
+        let stored = |height| live.storage.get_block_by_height(height).unwrap().unwrap();
+        for block in [stored(0), stored(1), forged] {
+            let bytes = bincode::serialize(&block).unwrap();
+            file.extend((bytes.len() as u32).to_le_bytes());
+            file.extend(bytes);
+        }
+        let mut target = fresh();
+        let err = import_bin(&mut target, file.as_slice()).unwrap_err();
+        assert!(err.to_string().contains("record 3"), "{}", err);
+        assert_eq!(target.height, 2);
+        assert!(target.storage.get_block(&live.head_hash().unwrap()).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_block_range_parses_rust_style() {
+        let range = |s: &str| s.parse::<BlockRange>();
+        assert_eq!(range("0..10"), Ok(BlockRange { from: 0, to: Some(9) }));
+        assert_eq!(range("5..=7"), Ok(BlockRange { from: 5, to: Some(7) }));
+        assert_eq!(range("5.."), Ok(BlockRange { from: 5, to: None }));
+        assert_eq!(range("..3"), Ok(BlockRange { from: 0, to: Some(2) }));
+        assert_eq!(range("4"), Ok(BlockRange { from: 4, to: Some(4) }));
+        assert!(range("3..3").is_err());
+        assert!(range("0..x").is_err());
+    }
+
+    #[test]
+    fn test_activity_rows_follow_the_account() {
+        let sponsored = BlockType::SponsoredTransfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount: 250_000_000,
+            nonce: 1,
+            fee: 100_000,
+            fee_payer: "carol".to_string(),
+            fee_payer_signature: String::new(),
+        };
+        let rows = |account| ActivityRecord::from_block(account, 7, "ab", 1_700_000_000_000, &sponsored);
+
+        let alice = rows("alice");
+        assert_eq!(alice.len(), 1);
+        assert_eq!((alice[0].kind.as_str(), alice[0].counterparty.as_str()), ("send", "bob"));
+        assert_eq!((alice[0].amount.as_str(), alice[0].fee.as_str()), ("2.5", "0"));
+        assert_eq!(alice[0].timestamp, "2023-11-14T22:13:20Z");
+        assert_eq!(rows("bob")[0].kind, "receive");
+        assert_eq!((rows("carol")[0].kind.as_str(), rows("carol")[0].fee.as_str()), ("fee", "0.001"));
+
+        // Seconds and milliseconds land on the same time; awkward fields are quoted
+        let mut record = ActivityRecord::from_block("bob", 7, "ab", 1_700_000_000, &sponsored).remove(0);
+        assert_eq!(record.timestamp, alice[0].timestamp);
+        record.counterparty = "a,\"b\"".to_string();
+        let mut out = Vec::new();
+        write_activity_csv(&[record], &mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!("{}\n2023-11-14T22:13:20Z,receive,Compass,2.5,\"a,\"\"b\"\"\",0,7,ab\n", ACTIVITY_CSV_HEADER)
+        );
+    }
+}
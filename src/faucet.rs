@@ -0,0 +1,173 @@
+// Testnet faucet
+//
+// Hands out a fixed amount of Compass from a faucet wallet to anyone who asks
+// through `POST /faucet`, at most once per `cooldown_secs` for each address and
+// for each client IP. An optional `verify_url` hook sees every request first (a
+// captcha check, an allowlist) and can turn it down. Grants are ordinary
+// Transfer blocks signed by the faucet key, so explorers and wallets see them
+// like any other payment.
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::block::{BlockHeader, BlockType};
+use crate::chain::Chain;
+use crate::config::FaucetConfig;
+use crate::crypto::KeyPair;
+use crate::encoding;
+
+/// Why a faucet request was not served
+#[derive(Debug, PartialEq)]
+pub enum FaucetError {
+    /// The address or IP was served less than `cooldown_secs` ago
+    RateLimited { retry_after_secs: u64 },
+    /// Malformed request, or the verify hook turned it down
+    Denied(String),
+    /// The transfer failed, e.g. because the faucet ran dry
+    Failed(String),
+}
+
+impl std::fmt::Display for FaucetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FaucetError::RateLimited { retry_after_secs } => write!(f, "rate limited; try again in {}s", retry_after_secs),
+            FaucetError::Denied(reason) => write!(f, "request denied: {}", reason),
+            FaucetError::Failed(reason) => write!(f, "transfer failed: {}", reason),
+        }
+    }
+}
+
+/// A served request
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct FaucetGrant {
+    pub address: String,
+    pub amount: u64,
+    pub block_hash: String,
+    pub height: u64,
+}
+
+pub struct Faucet {
+    keypair: KeyPair,
+    amount: u64,
+    cooldown_secs: u64,
+    verify_url: Option<String>,
+    http: reqwest::Client,
+}
+
+impl Faucet {
+    pub fn new(config: &FaucetConfig, keypair: KeyPair) -> Result<Self, String> {
+        let amount = encoding::parse_units(&config.amount, encoding::asset_decimals("Compass"))
+            .map_err(|e| format!("faucet amount: {}", e))?;
+        Ok(Self {
+            keypair,
+            amount,
+            cooldown_secs: config.cooldown_secs,
+            verify_url: config.verify_url.clone(),
+            http: reqwest::Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default(),
+        })
+    }
+
+    /// Account the faucet pays from
+    pub fn address(&self) -> String {
+        self.keypair.public_key_hex()
+    }
+
+    /// Base units paid per request
+    pub fn amount(&self) -> u64 {
+        self.amount
+    }
+
+    /// Let the `verify_url` hook, if any, accept or refuse a request
+    pub async fn verify(&self, address: &str, ip: &str, captcha: Option<&str>) -> Result<(), FaucetError> {
+        let Some(url) = &self.verify_url else { return Ok(()) };
+        let body = serde_json::json!({ "address": address, "ip": ip, "captcha": captcha });
+        match self.http.post(url).json(&body).send().await {
+            Ok(resp) if resp.status().is_success() => Ok(()),
+            Ok(resp) => Err(FaucetError::Denied(format!("verification failed ({})", resp.status()))),
+            Err(e) => Err(FaucetError::Failed(format!("verification service unreachable: {}", e))),
+        }
+    }
+
+    /// Seconds until the limiter key can be served again
+    fn wait_secs(&self, chain: &Chain, key: &str, now: u64) -> u64 {
+        match chain.storage.get_faucet_grant(key) {
+            Ok(Some(last)) => (last + self.cooldown_secs).saturating_sub(now),
+            _ => 0,
+        }
+    }
+
+    /// Check the cooldowns, then append a transfer of `amount` from the faucet to
+    /// `address` and start both cooldowns
+    pub fn dispense(&self, chain: &mut Chain, address: &str, ip: &str, now: u64) -> Result<FaucetGrant, FaucetError> {
+        let address = address.trim();
+        if address.is_empty() || address.len() > 128 {
+            return Err(FaucetError::Denied("invalid address".to_string()));
+        }
+        let limits = [format!("addr:{}", address), format!("ip:{}", ip)];
+        let wait = limits.iter().map(|key| self.wait_secs(chain, key, now)).max().unwrap_or(0);
+        if wait > 0 {
+            return Err(FaucetError::RateLimited { retry_after_secs: wait });
+        }
+
+        let from = self.address();
+        let nonce = chain.storage.get_nonce(&from).map_err(|e| FaucetError::Failed(e.to_string()))? + 1;
+        let mut header = BlockHeader {
+            index: chain.height,
+            timestamp: now,
+            prev_hash: chain.head_hash().unwrap_or_default(),
+            hash: String::new(),
+            proposer: from.clone(),
+            signature_hex: String::new(),
+            block_type: BlockType::Transfer {
+                from: from.clone(),
+                to: address.to_string(),
+                asset: "Compass".to_string(),
+                amount: self.amount,
+                nonce,
+                fee: 0,
+            },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        let failed = |e: crate::error::CompassError| FaucetError::Failed(e.to_string());
+        header.hash = header.calculate_hash().map_err(failed)?;
+        let digest = encoding::hash_from_hex(&header.hash).map_err(|e| FaucetError::Failed(e.to_string()))?;
+        header.signature_hex = self.keypair.sign_hex(&digest);
+        let (block_hash, height) = (header.hash.clone(), header.index);
+        chain.append_transfer(header, &from).map_err(failed)?;
+
+        for key in &limits {
+            chain.storage.record_faucet_grant(key, now).map_err(failed)?;
+        }
+        Ok(FaucetGrant { address: address.to_string(), amount: self.amount, block_hash, height })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_faucet_pays_once_per_cooldown_per_address_and_ip() {
+        let config: FaucetConfig = toml::from_str("amount = \"2.5\"\ncooldown_secs = 60").unwrap();
+        let faucet = Faucet::new(&config, KeyPair::from_seed("faucet")).unwrap();
+        let mut chain = Chain::new_in_memory();
+        chain.storage.writer().set_balance(&faucet.address(), "Compass", 800_000_000).unwrap();
+
+        let grant = faucet.dispense(&mut chain, "alice", "10.0.0.1", 1_000).unwrap();
+        assert_eq!(grant.amount, 250_000_000);
+        assert_eq!(chain.storage.get_balance("alice", "Compass").unwrap(), 250_000_000);
+
+        // Same address from elsewhere, or another address from the same IP
+        assert_eq!(
+            faucet.dispense(&mut chain, "alice", "10.0.0.2", 1_010),
+            Err(FaucetError::RateLimited { retry_after_secs: 50 })
+        );
+        assert!(matches!(faucet.dispense(&mut chain, "bob", "10.0.0.1", 1_010), Err(FaucetError::RateLimited { .. })));
+        assert!(faucet.dispense(&mut chain, "bob", "10.0.0.2", 1_010).is_ok());
+
+        // After the cooldown the faucet pays again, until it runs dry
+        assert!(faucet.dispense(&mut chain, "alice", "10.0.0.1", 1_060).is_ok());
+        assert!(matches!(faucet.dispense(&mut chain, "carol", "10.0.0.3", 1_060), Err(FaucetError::Failed(_))));
+        assert_eq!(chain.storage.get_faucet_grant("addr:carol").unwrap(), None);
+    }
+}
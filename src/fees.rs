@@ -0,0 +1,103 @@
+// Fee schedule
+//
+// Transfers (plain, sponsored and asset-fee) must carry at least `min_fee`: a base
+// fee for their block type plus a per-byte fee on the canonical encoding. Whatever
+// they pay above that is their priority fee, which Gulf Stream ranks the queue by.
+// Contract deploys and calls instead offer a gas price, at least `gas_price`.
+// Fees are credited to the proposer of the latest PoH block. The schedule is
+// checked when a transaction is executed, not when a block is replayed or synced,
+// so changing it never invalidates history.
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::block::BlockType;
+use crate::encoding;
+
+/// `[consensus.fees]`, e.g.
+///
+/// ```toml
+/// [consensus.fees]
+/// per_byte_fee = 1
+/// [consensus.fees.base_fees]
+/// Transfer = 1000
+/// SponsoredTransfer = 1500
+/// ```
+#[derive(Debug, Deserialize, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct FeeSchedule {
+    /// Compass base units by block type name (`BlockType::kind`); absent types pay none
+    #[serde(default)]
+    pub base_fees: BTreeMap<String, u64>,
+    /// Compass base units per byte of the block type's canonical encoding
+    #[serde(default)]
+    pub per_byte_fee: u64,
+    /// Least Compass base units per unit of gas a contract deploy or call may offer
+    #[serde(default)]
+    pub gas_price: u64,
+}
+
+/// How a block's fee breaks down under a schedule
+#[derive(Debug, Serialize, Clone, PartialEq, Eq)]
+pub struct FeeBreakdown {
+    pub base_fee: u64,
+    pub size_bytes: u64,
+    pub byte_fee: u64,
+    /// `base_fee + byte_fee`
+    pub min_fee: u64,
+}
+
+impl FeeSchedule {
+    /// Minimum fee for a block of `block_type`. The fee field itself is part of the
+    /// encoding, but as a fixed-width integer its value never changes the size.
+    pub fn breakdown(&self, block_type: &BlockType) -> FeeBreakdown {
+        let base_fee = self.base_fees.get(block_type.kind()).copied().unwrap_or(0);
+        let size_bytes = encoding::canonical_bytes(block_type).map(|b| b.len() as u64).unwrap_or(0);
+        let byte_fee = size_bytes.saturating_mul(self.per_byte_fee);
+        FeeBreakdown { base_fee, size_bytes, byte_fee, min_fee: base_fee.saturating_add(byte_fee) }
+    }
+
+    pub fn min_fee(&self, block_type: &BlockType) -> u64 {
+        self.breakdown(block_type).min_fee
+    }
+
+    /// What `block_type` pays above the minimum, or an error if it pays less
+    pub fn priority_fee(&self, block_type: &BlockType) -> Result<u64, String> {
+        let min_fee = self.min_fee(block_type);
+        let fee = block_type.fee();
+        fee.checked_sub(min_fee)
+            .ok_or_else(|| format!("fee {} is below the minimum of {} for a {} block", fee, min_fee, block_type.kind()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transfer(fee: u64) -> BlockType {
+        BlockType::Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount: 10,
+            nonce: 1,
+            fee,
+        }
+    }
+
+    #[test]
+    fn test_min_fee_is_base_plus_bytes() {
+        let schedule = FeeSchedule { base_fees: BTreeMap::from([("Transfer".to_string(), 100)]), per_byte_fee: 2, ..Default::default() };
+        let b = schedule.breakdown(&transfer(0));
+        assert_eq!(b.min_fee, 100 + 2 * b.size_bytes);
+        // The fee amount doesn't move the size it is priced on
+        assert_eq!(schedule.breakdown(&transfer(u64::MAX)), b);
+
+        assert!(schedule.priority_fee(&transfer(b.min_fee - 1)).is_err());
+        assert_eq!(schedule.priority_fee(&transfer(b.min_fee + 7)), Ok(7));
+
+        // Block types without a base fee still pay for their bytes
+        let vote = BlockType::Vote { proposal_id: 1, voter: "alice".to_string(), choice: true };
+        assert_eq!(schedule.min_fee(&vote), 2 * schedule.breakdown(&vote).size_bytes);
+        assert_eq!(FeeSchedule::default().min_fee(&transfer(0)), 0);
+    }
+}
@@ -0,0 +1,112 @@
+// Vote-based finality
+//
+// Validators sign votes on canonical block hashes. Each validator keeps a
+// Tower-style record of its last vote: a vote that builds on the previous one
+// doubles the lockout, and switching to a block that does not descend from it
+// is only allowed once the lockout has expired (measured in block heights). A
+// block is final once validators holding more than two thirds of the stake
+// have a canonical vote at or above its height; a vote for a block covers all
+// of its ancestors.
+use serde::{Deserialize, Serialize};
+
+/// Lockout of a vote that does not build on an earlier one
+pub const INITIAL_LOCKOUT: u64 = 2;
+
+/// Lockouts stop doubling here, so a validator is never locked out of switching for good
+pub const MAX_LOCKOUT: u64 = 1 << 12;
+
+/// Message a validator signs to vote for block `hash` at `height`
+pub fn vote_message(hash: &str, height: u64) -> String {
+    format!("COMPASS_VOTE:{}:{}", hash, height)
+}
+
+/// Whether `voted` out of `total` stake is more than two thirds
+pub fn supermajority(voted: u64, total: u64) -> bool {
+    total > 0 && (voted as u128) * 3 > (total as u128) * 2
+}
+
+/// A validator's latest vote and how long it stays locked to that fork
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VoteRecord {
+    pub hash: String,
+    pub height: u64,
+    pub lockout: u64,
+}
+
+impl VoteRecord {
+    /// Record after voting for `hash` at `height`, given the previous record and whether
+    /// the new block descends from the block it voted for
+    pub fn next(previous: Option<&VoteRecord>, hash: &str, height: u64, extends: bool) -> Result<Self, String> {
+        let lockout = match previous {
+            None => INITIAL_LOCKOUT,
+            Some(prev) if height <= prev.height => {
+                return Err(format!("vote at height {} does not advance past {}", height, prev.height));
+            }
+            Some(prev) if extends => prev.lockout.saturating_mul(2).min(MAX_LOCKOUT),
+            Some(prev) if height > prev.height.saturating_add(prev.lockout) => INITIAL_LOCKOUT,
+            Some(prev) => {
+                return Err(format!(
+                    "locked out: vote at height {} switches forks before height {}",
+                    height,
+                    prev.height.saturating_add(prev.lockout) + 1
+                ));
+            }
+        };
+        Ok(Self { hash: hash.to_string(), height, lockout })
+    }
+}
+
+/// Highest height that `(stake, height of last canonical vote)` pairs finalize out of
+/// `total` stake, if any
+pub fn finalized_height(votes: &[(u64, u64)], total: u64) -> Option<u64> {
+    let mut by_height = votes.to_vec();
+    by_height.sort_by_key(|&(_, height)| std::cmp::Reverse(height));
+    let mut voted = 0u64;
+    for (stake, height) in by_height {
+        voted = voted.saturating_add(stake);
+        if supermajority(voted, total) {
+            return Some(height);
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lockout_doubles_and_blocks_early_fork_switch() {
+        let first = VoteRecord::next(None, "a", 10, false).unwrap();
+        assert_eq!(first.lockout, INITIAL_LOCKOUT);
+        let second = VoteRecord::next(Some(&first), "b", 11, true).unwrap();
+        let third = VoteRecord::next(Some(&second), "c", 12, true).unwrap();
+        assert_eq!(third.lockout, 8);
+
+        // A fork block within the lockout is refused; past it the lockout resets
+        assert!(VoteRecord::next(Some(&third), "x", 20, false).is_err());
+        let switched = VoteRecord::next(Some(&third), "x", 21, false).unwrap();
+        assert_eq!(switched.lockout, INITIAL_LOCKOUT);
+        assert!(VoteRecord::next(Some(&switched), "y", 21, true).is_err());
+
+        let mut record = switched;
+        for h in 22..60 {
+            record = VoteRecord::next(Some(&record), "z", h, true).unwrap();
+        }
+        assert_eq!(record.lockout, MAX_LOCKOUT);
+    }
+
+    #[test]
+    fn test_finalized_height_needs_two_thirds_of_stake() {
+        assert!(supermajority(67, 100));
+        assert!(!supermajority(66, 99));
+        assert!(!supermajority(0, 0));
+
+        // 50 at height 9, 30 at height 7, 20 at height 12
+        let votes = [(50, 9), (30, 7), (20, 12)];
+        assert_eq!(finalized_height(&votes, 100), Some(9));
+        assert_eq!(finalized_height(&votes[..2], 100), Some(7));
+        assert_eq!(finalized_height(&votes[..1], 100), None);
+        assert_eq!(finalized_height(&[], 0), None);
+    }
+}
@@ -0,0 +1,158 @@
+// Devnet fixtures
+//
+// `compass node start --dev` boots a throwaway chain with the same keys, balances,
+// vaults and NFTs every time, so integration tests and demos can hard-code the
+// accounts below instead of bootstrapping their own. Keys are derived from fixed
+// seeds and are public knowledge: never fund them on a real network.
+use std::collections::HashMap;
+
+use crate::chain::Chain;
+use crate::crypto::KeyPair;
+use crate::error::CompassError;
+use crate::genesis::{GenesisConfig, GenesisValidator, GENESIS_TIMESTAMP_MS};
+use crate::layer3::model_nft::{ModelNFT, ModelStats};
+
+pub const DEV_CHAIN_ID: &str = "compass-devnet";
+
+/// Database used by `--dev` unless `--db-path` says otherwise
+pub const DEV_DB_PATH: &str = "compass-dev-db";
+
+/// Funded accounts, in the order they are printed at startup
+pub const DEV_ACCOUNTS: [&str; 4] = ["alice", "bob", "carol", "dave"];
+
+/// Compass each account starts with, in base units (1,000,000 Compass)
+pub const DEV_BALANCE: u64 = 100_000_000_000_000;
+
+/// Collateral tickers with a pre-registered shared vault (`Compass-<ticker>`)
+pub const DEV_VAULTS: [&str; 3] = ["BTC", "LTC", "SOL"];
+
+/// Order-book balance of each `Compass-<ticker>` asset per account
+pub const DEV_MARKET_BALANCE: u64 = 1_000_000_000;
+
+/// Keypair of a devnet account or role, e.g. `dev_keypair("alice")`
+pub fn dev_keypair(name: &str) -> KeyPair {
+    KeyPair::from_seed(&format!("compass-dev-{}", name))
+}
+
+/// Identity that produces PoH blocks on the devnet
+pub fn validator_keypair() -> KeyPair {
+    dev_keypair("validator")
+}
+
+/// Devnet genesis: every account in `DEV_ACCOUNTS` funded, and the dev validator
+/// as the only validator
+pub fn genesis() -> GenesisConfig {
+    let initial_balances: HashMap<String, u64> = DEV_ACCOUNTS
+        .iter()
+        .map(|name| (dev_keypair(name).public_key_hex(), DEV_BALANCE))
+        .collect();
+    GenesisConfig {
+        chain_id: DEV_CHAIN_ID.to_string(),
+        timestamp: GENESIS_TIMESTAMP_MS,
+        initial_balances,
+        allocations: vec![],
+        initial_validators: vec![GenesisValidator {
+            id: "dev-validator".to_string(),
+            public_key: validator_keypair().public_key_hex(),
+            stake: 0,
+        }],
+        genesis_hash: None,
+    }
+    .pinned()
+}
+
+/// One sample model NFT per account, with fixed ids and stats
+pub fn sample_nfts() -> Vec<ModelNFT> {
+    DEV_ACCOUNTS
+        .iter()
+        .zip(["BTC", "ETH", "SOL", "LTC"])
+        .enumerate()
+        .map(|(i, (name, ticker))| {
+            let stats = ModelStats {
+                accuracy: 0.55 + 0.05 * i as f64,
+                win_rate: 0.5 + 0.05 * i as f64,
+                total_predictions: 100,
+                profitable_predictions: 50 + 5 * i,
+                total_profit: 0,
+                training_samples: 10_000,
+                training_epochs: 20,
+                final_loss: 0.1,
+                training_duration: 60,
+                data_hash: String::new(),
+            };
+            let mut nft = ModelNFT::from_job(&format!("dev-{}", i), ticker, dev_keypair(name).public_key_hex(), &stats);
+            // from_job stamps the wall clock into the id and weights hash
+            nft.token_id = format!("MODEL-DEV-{}-{}", ticker, name);
+            nft.weights_hash = format!("dev-weights-{}", i);
+            nft.minted_at = GENESIS_TIMESTAMP_MS / 1000;
+            nft.last_updated = nft.minted_at;
+            nft
+        })
+        .collect()
+}
+
+/// Initialize `chain` with the devnet genesis, register the fixture vaults and
+/// NFTs and give every account `DEV_MARKET_BALANCE` of each vault asset to trade.
+/// Does nothing to a chain that already has blocks.
+pub fn install(chain: &mut Chain) -> Result<(), CompassError> {
+    if chain.height > 0 || chain.head_hash.is_some() {
+        return Ok(());
+    }
+    chain.initialize_genesis(&genesis())?;
+
+    let validator = validator_keypair().public_key_hex();
+    for ticker in DEV_VAULTS {
+        let asset = format!("Compass-{}", ticker);
+        let address = format!("dev-vault-{}", ticker.to_lowercase());
+        if !chain.vault_manager.vaults.contains_key(&asset) {
+            chain.vault_manager.register_vault(ticker, &asset, &address, 1, &validator).map_err(CompassError::InvalidState)?;
+        }
+        for name in DEV_ACCOUNTS {
+            chain.storage.writer().credit(&dev_keypair(name).public_key_hex(), &asset, DEV_MARKET_BALANCE)?;
+        }
+    }
+    for nft in sample_nfts() {
+        chain.storage.save_model_nft(&nft)?;
+    }
+    Ok(())
+}
+
+/// Startup banner listing the devnet keys
+pub fn describe() -> String {
+    let mut out = format!("Devnet '{}' (keys are public, never use them elsewhere)\n", DEV_CHAIN_ID);
+    out += &format!("  validator  {}\n", validator_keypair().public_key_hex());
+    for name in DEV_ACCOUNTS {
+        out += &format!("  {:<10} {}  (seed \"compass-dev-{}\")\n", name, dev_keypair(name).public_key_hex(), name);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dev_fixtures_are_deterministic_and_funded() {
+        assert_eq!(dev_keypair("alice").public_key_hex(), dev_keypair("alice").public_key_hex());
+        assert_ne!(dev_keypair("alice").public_key_hex(), dev_keypair("bob").public_key_hex());
+
+        let mut chain = Chain::new_in_memory();
+        install(&mut chain).unwrap();
+        install(&mut chain).unwrap();
+        assert_eq!(chain.height, 1);
+        assert_eq!(chain.storage.get_chain_id().unwrap().as_deref(), Some(DEV_CHAIN_ID));
+
+        for name in DEV_ACCOUNTS {
+            let address = dev_keypair(name).public_key_hex();
+            assert_eq!(chain.storage.get_balance(&address, "Compass").unwrap(), DEV_BALANCE);
+            assert_eq!(chain.storage.get_balance(&address, "Compass-BTC").unwrap(), DEV_MARKET_BALANCE);
+            assert_eq!(chain.storage.get_nfts_by_owner(&address).len(), 1);
+        }
+        assert_eq!(
+            chain.storage.get_validator_pubkey("dev-validator").unwrap(),
+            Some(validator_keypair().public_key_hex())
+        );
+        assert_eq!(chain.storage.get_all_vaults().len(), DEV_VAULTS.len());
+        assert!(chain.storage.get_model_nft("MODEL-DEV-BTC-alice").unwrap().is_some());
+    }
+}
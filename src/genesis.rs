@@ -1,15 +1,41 @@
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fs;
 use std::path::Path;
 
+use crate::vesting::VestingTerms;
+
+/// Timestamp of the genesis block. Its hash is pinned in `Chain::initialize_genesis`
+/// and depends only on this value, so every network must use it.
+pub const GENESIS_TIMESTAMP_MS: u64 = 1_700_000_000_000;
+
+/// Domain the canonical genesis hash is computed under
+const GENESIS_HASH_DOMAIN: &str = "COMPASS_GENESIS_V1";
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct GenesisConfig {
     pub chain_id: String,
     pub timestamp: u64,
     pub initial_balances: HashMap<String, u64>,
+    /// Grants on top of `initial_balances`. One with vesting terms is locked at
+    /// genesis and released to the account as it vests.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub allocations: Vec<GenesisAllocation>,
     #[serde(default)]
     pub initial_validators: Vec<GenesisValidator>,
+    /// `canonical_hash` of the fields above, pinned when the network is set up.
+    /// A file whose contents no longer hash to it is refused.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub genesis_hash: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct GenesisAllocation {
+    pub account: String,
+    pub amount: u64,
+    /// Left out for an allocation credited in full at genesis
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vesting: Option<VestingTerms>,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -25,7 +51,198 @@ impl GenesisConfig {
             return Err(format!("Genesis file not found: {}", path));
         }
         let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
-        serde_json::from_str(&content).map_err(|e| e.to_string())
+        let genesis: Self = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        genesis.verify_pin()?;
+        Ok(genesis)
+    }
+
+    /// Hash identifying this genesis: chain id, timestamp, balances (in account
+    /// order), validators (in file order, which the leader schedule follows) and
+    /// allocations (in file order). Peers exchange it, so two networks that share a
+    /// chain id still can't mix. Allocations only join the hash when there are
+    /// some, so genesis files written before them keep their hash.
+    pub fn canonical_hash(&self) -> String {
+        let balances: BTreeMap<&str, u64> = self.initial_balances.iter().map(|(k, v)| (k.as_str(), *v)).collect();
+        let validators: Vec<(&str, &str, u64)> = self
+            .initial_validators
+            .iter()
+            .map(|v| (v.id.as_str(), v.public_key.as_str(), v.stake))
+            .collect();
+        let base = (&self.chain_id, self.timestamp, &balances, &validators);
+        let bytes = if self.allocations.is_empty() {
+            crate::encoding::signing_bytes(GENESIS_HASH_DOMAIN, &base)
+        } else {
+            crate::encoding::signing_bytes(GENESIS_HASH_DOMAIN, &(base, &self.allocations))
+        }
+        .unwrap_or_default();
+        crate::encoding::hash_to_hex(&crate::encoding::sha256(bytes))
+    }
+
+    /// This genesis with its current `canonical_hash` pinned
+    pub fn pinned(mut self) -> Self {
+        self.genesis_hash = Some(self.canonical_hash());
+        self
+    }
+
+    /// Err if a hash is pinned and the contents no longer match it
+    pub fn verify_pin(&self) -> Result<(), String> {
+        match &self.genesis_hash {
+            Some(pinned) if *pinned != self.canonical_hash() => Err(format!(
+                "genesis contents hash to {}, but the file pins {}",
+                self.canonical_hash(),
+                pinned
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Everything wrong with this genesis that would stop a network launching
+    /// cleanly from it; empty when there is nothing
+    pub fn problems(&self) -> Vec<String> {
+        let mut problems = Vec::new();
+        if self.chain_id.is_empty() {
+            problems.push("chain_id is empty".to_string());
+        } else if !self.chain_id.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c)) {
+            problems.push(format!("chain_id '{}' may only use letters, digits, '-', '_' and '.'", self.chain_id));
+        }
+        if self.timestamp != GENESIS_TIMESTAMP_MS {
+            problems.push(format!(
+                "timestamp is {}, but the pinned genesis block needs {}",
+                self.timestamp, GENESIS_TIMESTAMP_MS
+            ));
+        }
+
+        if self.initial_balances.is_empty() && self.allocations.iter().all(|a| a.vesting.is_some()) {
+            problems.push("no account is credited at genesis: none could pay a fee".to_string());
+        }
+        let mut accounts: Vec<(&String, &u64)> = self.initial_balances.iter().collect();
+        accounts.sort();
+        for (account, amount) in accounts {
+            if !valid_account_name(account) {
+                problems.push(format!("account '{}' is not a valid account name", account));
+            }
+            if *amount == 0 {
+                problems.push(format!("account '{}' is allocated 0", account));
+            }
+        }
+        let mut vesting_accounts = HashSet::new();
+        for allocation in &self.allocations {
+            let account = &allocation.account;
+            if !valid_account_name(account) {
+                problems.push(format!("allocation account '{}' is not a valid account name", account));
+            }
+            if allocation.amount == 0 {
+                problems.push(format!("allocation to '{}' is 0", account));
+            }
+            if allocation.vesting.is_some() && !vesting_accounts.insert(account.as_str()) {
+                problems.push(format!("account '{}' has more than one vesting allocation", account));
+            }
+        }
+        if self.total_allocation().is_none() {
+            problems.push("allocations add up to more than a u64 can hold".to_string());
+        }
+
+        let (mut ids, mut keys) = (HashSet::new(), HashSet::new());
+        for v in &self.initial_validators {
+            if !ids.insert(v.id.as_str()) {
+                problems.push(format!("validator '{}' is listed more than once", v.id));
+            }
+            if !keys.insert(v.public_key.as_str()) {
+                problems.push(format!("validator '{}' reuses public key {}", v.id, v.public_key));
+            }
+            let valid_key = crate::encoding::pubkey_from_hex(&v.public_key)
+                .is_ok_and(|bytes| ed25519_dalek::VerifyingKey::from_bytes(&bytes).is_ok());
+            if !valid_key {
+                problems.push(format!(
+                    "validator '{}' public key '{}' is not a 64-character hex ed25519 key",
+                    v.id, v.public_key
+                ));
+            }
+        }
+        if let Err(e) = self.verify_pin() {
+            problems.push(e);
+        }
+        problems
+    }
+
+    /// Sum of `initial_balances` and `allocations`, vesting or not, if it fits
+    pub fn total_allocation(&self) -> Option<u64> {
+        self.initial_balances
+            .values()
+            .chain(self.allocations.iter().map(|a| &a.amount))
+            .try_fold(0u64, |total, amount| total.checked_add(*amount))
+    }
+
+    /// Allocations locked at genesis, as the schedules that release them
+    pub fn vesting_schedules(&self) -> Vec<crate::vesting::VestingSchedule> {
+        self.allocations
+            .iter()
+            .filter_map(|a| a.vesting.as_ref().map(|terms| terms.schedule(&a.account, a.amount, self.timestamp)))
+            .collect()
+    }
+
+    /// Parse a genesis file for validation, reporting accounts written more than
+    /// once in `initial_balances` (parsing into a map keeps only the last) along
+    /// with `problems`. Unlike `load`, a mismatched pin is reported, not refused.
+    pub fn check(content: &str) -> Result<(Self, Vec<String>), String> {
+        let genesis: Self = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let written: WrittenAccounts = serde_json::from_str(content).map_err(|e| e.to_string())?;
+        let mut seen = HashSet::new();
+        let mut problems: Vec<String> = written
+            .initial_balances
+            .0
+            .iter()
+            .filter(|account| !seen.insert(account.as_str()))
+            .map(|account| format!("account '{}' appears more than once in initial_balances", account))
+            .collect();
+        problems.dedup();
+        problems.extend(genesis.problems());
+        Ok((genesis, problems))
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(self).map_err(|e| e.to_string())?;
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+fn valid_account_name(account: &str) -> bool {
+    !account.is_empty() && !account.chars().any(|c| c.is_whitespace() || c == ':')
+}
+
+/// What peers compare when they connect: the chain id and the canonical genesis
+/// hash. A peer advertising anything else is on another network.
+pub fn network_id(chain_id: &str, genesis_hash: &str) -> String {
+    format!("/compass/{}/{}", chain_id, genesis_hash)
+}
+
+/// `initial_balances` keys as written, duplicates included
+#[derive(Deserialize)]
+struct WrittenAccounts {
+    initial_balances: AccountKeys,
+}
+
+struct AccountKeys(Vec<String>);
+
+impl<'de> Deserialize<'de> for AccountKeys {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct Keys;
+        impl<'de> serde::de::Visitor<'de> for Keys {
+            type Value = AccountKeys;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a map of account to balance")
+            }
+
+            fn visit_map<A: serde::de::MapAccess<'de>>(self, mut map: A) -> Result<AccountKeys, A::Error> {
+                let mut keys = Vec::new();
+                while let Some((key, _)) = map.next_entry::<String, serde::de::IgnoredAny>()? {
+                    keys.push(key);
+                }
+                Ok(AccountKeys(keys))
+            }
+        }
+        deserializer.deserialize_map(Keys)
     }
 }
 
@@ -41,7 +258,9 @@ pub fn generate_admin_config() {
         chain_id: "compass-mainnet".to_string(),
         timestamp: 0, // In real world use Utc::now()
         initial_balances,
+        allocations: vec![],
         initial_validators: vec![],
+        genesis_hash: None,
     };
 
     let json = serde_json::to_string_pretty(&config).unwrap();
@@ -51,3 +270,78 @@ pub fn generate_admin_config() {
         println!("Created 'genesis.json'.");
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_reports_what_would_break_a_launch() {
+        let keypair = crate::crypto::KeyPair::from_seed("genesis-validator");
+        let genesis = crate::init::local_genesis("compass-test", &keypair);
+        let content = serde_json::to_string(&genesis).unwrap();
+        let (parsed, problems) = GenesisConfig::check(&content).unwrap();
+        assert!(problems.is_empty(), "{:?}", problems);
+        assert_eq!(parsed.genesis_hash, Some(genesis.canonical_hash()));
+
+        // Independent of map order, but not of any balance
+        let mut other = genesis.clone();
+        let mut accounts: Vec<_> = genesis.initial_balances.clone().into_iter().collect();
+        accounts.reverse();
+        other.initial_balances = HashMap::with_capacity(accounts.len());
+        other.initial_balances.extend(accounts);
+        assert_eq!(other.canonical_hash(), genesis.canonical_hash());
+        other.initial_balances.insert("admin".to_string(), 1);
+        assert_ne!(other.canonical_hash(), genesis.canonical_hash());
+        assert!(other.verify_pin().is_err());
+
+        let broken = format!(
+            r#"{{"chain_id": "compass/test", "timestamp": 0,
+                "initial_balances": {{"alice": 1, "alice": 2, "bob": {}, "carol": 0}},
+                "initial_validators": [
+                    {{"id": "v1", "public_key": "{}", "stake": 0}},
+                    {{"id": "v1", "public_key": "abcd", "stake": 0}}
+                ]}}"#,
+            u64::MAX,
+            keypair.public_key_hex()
+        );
+        let (_, problems) = GenesisConfig::check(&broken).unwrap();
+        let expected = [
+            "account 'alice' appears more than once",
+            "chain_id 'compass/test'",
+            "timestamp is 0",
+            "account 'carol' is allocated 0",
+            "add up to more than a u64",
+            "validator 'v1' is listed more than once",
+            "public key 'abcd' is not a 64-character hex ed25519 key",
+        ];
+        for needle in expected {
+            assert!(problems.iter().any(|p| p.contains(needle)), "missing '{}' in {:?}", needle, problems);
+        }
+        assert_eq!(problems.len(), expected.len(), "{:?}", problems);
+    }
+
+    #[test]
+    fn test_vesting_allocations_are_hashed_and_checked() {
+        let keypair = crate::crypto::KeyPair::from_seed("genesis-validator");
+        let mut genesis = crate::init::local_genesis("compass-test", &keypair);
+        let without = genesis.canonical_hash();
+        let terms = VestingTerms { start_ms: None, cliff_ms: 1_000, duration_ms: 4_000 };
+        genesis.allocations = vec![
+            GenesisAllocation { account: "team".to_string(), amount: 500, vesting: Some(terms.clone()) },
+            GenesisAllocation { account: "advisor".to_string(), amount: 50, vesting: None },
+        ];
+        genesis.genesis_hash = None;
+        assert_ne!(genesis.canonical_hash(), without);
+        assert!(genesis.problems().is_empty(), "{:?}", genesis.problems());
+        assert_eq!(genesis.total_allocation(), Some(2_000_000_000_550));
+        let schedules = genesis.vesting_schedules();
+        assert_eq!(schedules.len(), 1);
+        assert_eq!(schedules[0].start_ms, GENESIS_TIMESTAMP_MS);
+
+        genesis.allocations.push(GenesisAllocation { account: "team".to_string(), amount: 0, vesting: Some(terms) });
+        let problems = genesis.problems();
+        assert!(problems.iter().any(|p| p.contains("allocation to 'team' is 0")), "{:?}", problems);
+        assert!(problems.iter().any(|p| p.contains("more than one vesting allocation")), "{:?}", problems);
+    }
+}
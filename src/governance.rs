@@ -0,0 +1,108 @@
+//! Stake-weighted governance tallies.
+//!
+//! A `Vote` counts with the voter's `layer2::collateral` stake. Holders who
+//! don't want to vote themselves can hand their weight to a representative
+//! with a `Delegate` block; their stake then counts with the representative's
+//! vote. Delegation is one level deep (a representative's own delegation does
+//! not pass on what was delegated to it), and a holder who votes directly
+//! counts for itself whatever it delegated. Only each account's latest vote on
+//! a proposal and latest delegation count.
+
+use std::collections::HashMap;
+
+use crate::block::BlockType;
+
+/// What a holder signs to delegate its voting power to `representative` with its
+/// account `nonce`; an empty representative takes the power back
+pub fn delegate_message(delegator: &str, representative: &str, nonce: u64) -> String {
+    format!("COMPASS_DELEGATE:{}:{}:{}", delegator, representative, nonce)
+}
+
+/// Stake for and against a proposal
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct VoteTally {
+    pub yes: u64,
+    pub no: u64,
+}
+
+/// Tally `proposal_id` over the chain's blocks, in chain order, weighting each vote
+/// by `stakes` (collateral by entity)
+pub fn tally<'a>(blocks: impl IntoIterator<Item = &'a BlockType>, proposal_id: u64, stakes: &HashMap<String, u64>) -> VoteTally {
+    let mut delegations: HashMap<&str, &str> = HashMap::new();
+    let mut votes: HashMap<&str, bool> = HashMap::new();
+    for block_type in blocks {
+        match block_type {
+            BlockType::Delegate { delegator, representative, .. } if representative.is_empty() => {
+                delegations.remove(delegator.as_str());
+            }
+            BlockType::Delegate { delegator, representative, .. } => {
+                delegations.insert(delegator, representative);
+            }
+            BlockType::Vote { proposal_id: pid, voter, choice } if *pid == proposal_id => {
+                votes.insert(voter, *choice);
+            }
+            _ => {}
+        }
+    }
+
+    let stake = |account: &str| stakes.get(account).copied().unwrap_or(0);
+    let mut weights: HashMap<&str, u64> = votes.keys().map(|voter| (*voter, stake(voter))).collect();
+    for (delegator, representative) in delegations {
+        if votes.contains_key(delegator) {
+            continue;
+        }
+        if let Some(weight) = weights.get_mut(representative) {
+            *weight = weight.saturating_add(stake(delegator));
+        }
+    }
+
+    let mut tally = VoteTally::default();
+    for (voter, choice) in votes {
+        let side = if choice { &mut tally.yes } else { &mut tally.no };
+        *side = side.saturating_add(weights[voter]);
+    }
+    tally
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vote(voter: &str, choice: bool) -> BlockType {
+        BlockType::Vote { proposal_id: 1, voter: voter.to_string(), choice }
+    }
+
+    fn delegate(delegator: &str, representative: &str) -> BlockType {
+        BlockType::Delegate {
+            delegator: delegator.to_string(),
+            representative: representative.to_string(),
+            nonce: 1,
+            signature: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_votes_count_with_own_and_delegated_stake() {
+        let stakes: HashMap<String, u64> =
+            [("whale", 100), ("rep", 10), ("small1", 5), ("small2", 7), ("small3", 3)].map(|(k, v)| (k.to_string(), v)).into();
+        let blocks = vec![
+            delegate("small1", "rep"),
+            delegate("small2", "rep"),
+            delegate("small3", "rep"),
+            // Taken back: counts for nobody
+            delegate("small3", ""),
+            vote("whale", false),
+            vote("rep", true),
+            // Voting directly overrides the delegation
+            vote("small2", false),
+            // Only the latest vote counts
+            vote("whale", true),
+            vote("whale", false),
+            BlockType::Vote { proposal_id: 2, voter: "rep".to_string(), choice: false },
+        ];
+        assert_eq!(tally(&blocks, 1, &stakes), VoteTally { yes: 15, no: 107 });
+
+        // Power delegated to someone who didn't vote is not counted
+        assert_eq!(tally(&[delegate("small1", "whale"), vote("rep", true)], 1, &stakes), VoteTally { yes: 10, no: 0 });
+    }
+}
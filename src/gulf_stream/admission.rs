@@ -0,0 +1,197 @@
+// src/gulf_stream/admission.rs
+
+use crate::network::{TransactionPayload, WireTransaction};
+use crate::storage::Storage;
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+/// Maximum size of a bincode-serialized transaction accepted by Gulf Stream (1 MiB).
+pub const MAX_TX_SIZE_BYTES: usize = 1024 * 1024;
+
+/// Maximum number of pending transactions a single account may hold in Gulf Stream.
+pub const MAX_PENDING_PER_ACCOUNT: usize = 64;
+
+/// How far past an account's next nonce Gulf Stream will hold transactions.
+/// They wait in the pool until the nonces before them arrive.
+pub const DEFAULT_FUTURE_NONCE_WINDOW: u64 = 16;
+
+/// Rough execution cost of a payload, used to rank transactions by fee per unit.
+/// Signature checks and state writes dominate, so the count follows those.
+pub fn compute_units(payload: &TransactionPayload) -> u64 {
+    match payload {
+        TransactionPayload::Transfer { .. } => 1,
+        TransactionPayload::CancelOrder { .. } => 1,
+        TransactionPayload::SetFrozen { .. } => 1,
+        TransactionPayload::FinalityVote { .. } => 1,
+        TransactionPayload::ClaimEarnings { .. } => 1,
+        TransactionPayload::WithdrawUnbonded { .. } => 1,
+        TransactionPayload::Delegate { .. } => 1,
+        TransactionPayload::SponsoredTransfer { .. } => 2,
+        TransactionPayload::InvokeModel { .. } => 2,
+        TransactionPayload::AssetFeeTransfer { .. } => 2,
+        TransactionPayload::MultisigTransfer { approvals, .. } => 1 + approvals.len() as u64,
+        TransactionPayload::PlaceOrder { .. } => 2,
+        TransactionPayload::RegisterValidator(_) => 2,
+        TransactionPayload::Stake(_) => 2,
+        TransactionPayload::Unstake(_) => 2,
+        TransactionPayload::Mint { .. } => 3,
+        TransactionPayload::Burn { .. } => 3,
+        TransactionPayload::Result(_) => 3,
+        TransactionPayload::OracleVerification(_) => 3,
+        TransactionPayload::MintModelNFT(_) => 3,
+        TransactionPayload::ComputeJob { .. } => 5,
+        TransactionPayload::DeployContract { code, .. } => 5 + code.len() as u64 / 1024,
+        TransactionPayload::CallContract { gas_limit, .. } => 1 + gas_limit / 1_000_000,
+        TransactionPayload::Scheduled { inner, .. } => compute_units(inner) + 1,
+    }
+}
+
+/// Why Gulf Stream refused to admit a transaction.
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum AdmissionError {
+    #[error("Transaction too large: {size} bytes exceeds limit of {limit} bytes")]
+    TooLarge { size: usize, limit: usize },
+
+    #[error("Account {account} has {pending} pending transactions (limit {limit})")]
+    AccountQuotaExceeded {
+        account: String,
+        pending: usize,
+        limit: usize,
+    },
+
+    #[error("Mempool full: {capacity} pending transactions")]
+    MempoolFull { capacity: usize },
+
+    #[error("Transaction already exists")]
+    Duplicate,
+
+    #[error("Malformed transaction")]
+    Malformed,
+
+    #[error("Invalid signature")]
+    InvalidSignature,
+
+    #[error("Transaction must be sent in a signed envelope")]
+    Unsigned,
+
+    #[error("Nonce {nonce} for {account} is already used (next is {next})")]
+    NonceTooLow { account: String, nonce: u64, next: u64 },
+
+    #[error("Nonce {nonce} for {account} is too far ahead (next is {next}, window {window})")]
+    NonceTooFarAhead {
+        account: String,
+        nonce: u64,
+        next: u64,
+        window: u64,
+    },
+
+    #[error("Replacement for nonce {nonce} must pay more than the pending fee of {pending_fee} (offered {offered_fee})")]
+    ReplacementUnderpriced {
+        nonce: u64,
+        pending_fee: u64,
+        offered_fee: u64,
+    },
+
+    #[error("Unknown asset {asset}: no vault issues it and no account holds it")]
+    UnknownAsset { asset: String },
+
+    #[error("Insufficient {asset} balance for {account}: has {balance}, needs {required}")]
+    InsufficientBalance {
+        account: String,
+        asset: String,
+        balance: u64,
+        required: u64,
+    },
+
+    #[error("Rejected by {check} check: {reason}")]
+    Rejected { check: String, reason: String },
+}
+
+/// A stage of the admission pipeline. Checks run on decoded, signature-checked
+/// transactions whose nonce is in range, against confirmed state, so anything they
+/// refuse would otherwise have failed at execution.
+pub trait AdmissionCheck: Send {
+    /// Short name for logs and `AdmissionError::Rejected`
+    fn name(&self) -> &'static str;
+
+    fn check(&self, tx: &WireTransaction, storage: &Storage) -> Result<(), AdmissionError>;
+}
+
+/// Checks every Gulf Stream starts with
+pub fn default_checks() -> Vec<Box<dyn AdmissionCheck>> {
+    vec![Box::new(KnownAssetCheck), Box::new(BalanceCheck)]
+}
+
+/// What a payload takes out of each (account, asset) when it executes. Scheduled
+/// payloads run against a later state and are left to execution.
+fn spends(payload: &TransactionPayload) -> Result<BTreeMap<(&str, &str), u64>, AdmissionError> {
+    let debits: Vec<(&str, &str, u64)> = match payload {
+        TransactionPayload::Transfer { from, asset, amount, .. }
+        | TransactionPayload::AssetFeeTransfer { from, asset, amount, .. }
+        | TransactionPayload::MultisigTransfer { from, asset, amount, .. } => vec![(from, asset, *amount)],
+        TransactionPayload::SponsoredTransfer { from, asset, amount, fee, fee_payer, .. } => {
+            vec![(from, asset, *amount), (fee_payer, "Compass", *fee)]
+        }
+        _ => vec![],
+    };
+    let mut totals = BTreeMap::new();
+    for (account, asset, amount) in debits {
+        let total: &mut u64 = totals.entry((account, asset)).or_default();
+        *total = total.checked_add(amount).ok_or_else(|| AdmissionError::Rejected {
+            check: "balance".to_string(),
+            reason: format!("{} spent by {} overflows", asset, account),
+        })?;
+    }
+    Ok(totals)
+}
+
+/// Refuses transfers of an asset nothing on chain knows about (usually a typo)
+pub struct KnownAssetCheck;
+
+impl AdmissionCheck for KnownAssetCheck {
+    fn name(&self) -> &'static str {
+        "known-asset"
+    }
+
+    fn check(&self, tx: &WireTransaction, storage: &Storage) -> Result<(), AdmissionError> {
+        let mut assets: Vec<&str> = spends(tx.payload())?.into_keys().map(|(_, asset)| asset).collect();
+        if let TransactionPayload::AssetFeeTransfer { fee_asset, .. } = tx.payload() {
+            assets.push(fee_asset);
+        }
+        for asset in assets {
+            if asset == "Compass" {
+                continue;
+            }
+            let known = storage.get_all_vaults().iter().any(|v| v.compass_asset == asset)
+                || storage.get_all_assets().iter().any(|a| a == asset);
+            if !known {
+                return Err(AdmissionError::UnknownAsset { asset: asset.to_string() });
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Refuses spends the account's confirmed balance can't cover
+pub struct BalanceCheck;
+
+impl AdmissionCheck for BalanceCheck {
+    fn name(&self) -> &'static str {
+        "balance"
+    }
+
+    fn check(&self, tx: &WireTransaction, storage: &Storage) -> Result<(), AdmissionError> {
+        for ((account, asset), required) in spends(tx.payload())? {
+            let balance = storage.get_balance(account, asset).unwrap_or(0);
+            if balance < required {
+                return Err(AdmissionError::InsufficientBalance {
+                    account: account.to_string(),
+                    asset: asset.to_string(),
+                    balance,
+                    required,
+                });
+            }
+        }
+        Ok(())
+    }
+}
@@ -1,30 +1,65 @@
+use crate::gulf_stream::admission::{
+    compute_units, default_checks, AdmissionCheck, AdmissionError, DEFAULT_FUTURE_NONCE_WINDOW, MAX_PENDING_PER_ACCOUNT,
+    MAX_TX_SIZE_BYTES,
+};
 use crate::gulf_stream::stats::{GulfStreamStats, QueueSizes};
 use crate::gulf_stream::transactions::CompassGulfStreamTransaction;
 use crate::gulf_stream::utils::now_ms;
 use crate::gulf_stream::validator::ValidatorSlot;
-use std::collections::{HashMap, VecDeque};
+use std::cmp::Ordering;
+use crate::storage::Storage;
+use std::collections::{BinaryHeap, HashMap};
+use std::sync::Arc;
 
-#[derive(Debug, Clone)]
-pub struct HighPrioItem {
+/// Queue entry ranked by fee per compute unit, then age. Entries whose transaction
+/// has left `pending_transactions` (replaced, rejected, expired) are skipped on pop.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReadyItem {
     pub priority_fee: u64,
+    pub compute_units: u64,
     pub timestamp_ms: u128,
     pub tx_hash: Vec<u8>,
 }
 
+impl Ord for ReadyItem {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let mine = self.priority_fee as u128 * other.compute_units as u128;
+        let theirs = other.priority_fee as u128 * self.compute_units as u128;
+        mine.cmp(&theirs)
+            .then_with(|| other.timestamp_ms.cmp(&self.timestamp_ms))
+            .then_with(|| other.tx_hash.cmp(&self.tx_hash))
+    }
+}
+
+impl PartialOrd for ReadyItem {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
 pub struct CompassGulfStreamManager {
     pub node_id: String,
     pub capacity: usize,
+    pub max_tx_size: usize,
+    pub max_pending_per_account: usize,
+    /// Nonces accepted beyond an account's next one
+    pub future_nonce_window: u64,
+    /// Admit bare payloads that don't carry a signature of their own (deprecated)
+    pub accept_bare_transactions: bool,
+    /// Confirmed account nonces; without it nonces are only ordered, not checked
+    nonce_source: Option<Arc<Storage>>,
+    /// Admission pipeline run against the nonce source's state (skipped without one)
+    checks: Vec<Box<dyn AdmissionCheck>>,
     pub pending_transactions: HashMap<Vec<u8>, CompassGulfStreamTransaction>,
     pub processing_transactions: HashMap<Vec<u8>, CompassGulfStreamTransaction>,
-    pub high_priority_queue: Vec<HighPrioItem>,
-    pub normal_priority_queue: VecDeque<Vec<u8>>,
-    pub low_priority_queue: VecDeque<Vec<u8>>,
+    pub ready_queue: BinaryHeap<ReadyItem>,
     pub validator_schedule: Vec<ValidatorSlot>,
     pub current_slot: Option<ValidatorSlot>,
     pub next_leader: Option<String>,
     pub transactions_received: u64,
     pub transactions_confirmed: u64,
     pub transactions_rejected: u64,
+    pub transactions_replaced: u64,
 }
 
 impl CompassGulfStreamManager {
@@ -33,17 +68,22 @@ impl CompassGulfStreamManager {
         CompassGulfStreamManager {
             node_id,
             capacity,
+            max_tx_size: MAX_TX_SIZE_BYTES,
+            max_pending_per_account: MAX_PENDING_PER_ACCOUNT,
+            future_nonce_window: DEFAULT_FUTURE_NONCE_WINDOW,
+            accept_bare_transactions: false,
+            nonce_source: None,
+            checks: default_checks(),
             pending_transactions: HashMap::new(),
             processing_transactions: HashMap::new(),
-            high_priority_queue: Vec::new(),
-            normal_priority_queue: VecDeque::new(),
-            low_priority_queue: VecDeque::new(),
+            ready_queue: BinaryHeap::new(),
             validator_schedule: Vec::new(),
             current_slot: None,
             next_leader: None,
             transactions_received: 0,
             transactions_confirmed: 0,
             transactions_rejected: 0,
+            transactions_replaced: 0,
         }
     }
 
@@ -54,41 +94,214 @@ impl CompassGulfStreamManager {
         raw_tx: Vec<u8>,
         priority_fee: u64,
     ) -> bool {
+        match self.try_add_transaction(tx_hash, raw_tx, priority_fee) {
+            Ok(()) => true,
+            Err(e) => {
+                println!("GulfStream: REJECTED transaction: {}", e);
+                false
+            }
+        }
+    }
+
+    /// Check nonces against the confirmed ones in `storage`
+    pub fn set_nonce_source(&mut self, storage: Arc<Storage>) {
+        self.nonce_source = Some(storage);
+    }
+
+    /// Append a stage to the admission pipeline
+    pub fn add_check(&mut self, check: Box<dyn AdmissionCheck>) {
+        self.checks.push(check);
+    }
+
+    /// Names of the admission pipeline's stages, in order
+    pub fn check_names(&self) -> Vec<&'static str> {
+        self.checks.iter().map(|c| c.name()).collect()
+    }
+
+    /// Next nonce `account` may use, if a nonce source is set
+    fn next_nonce(&self, account: &str) -> Option<u64> {
+        let storage = self.nonce_source.as_ref()?;
+        Some(storage.get_nonce(account).unwrap_or(0) + 1)
+    }
+
+    /// Chain id recorded at genesis, which signed envelopes must name
+    fn chain_id(&self) -> Option<String> {
+        self.nonce_source.as_ref()?.get_chain_id().ok().flatten()
+    }
+
+    /// Number of pending transactions submitted by `account`
+    pub fn pending_count_for(&self, account: &str) -> usize {
+        self.pending_transactions
+            .values()
+            .filter(|tx| tx.sender.as_deref() == Some(account))
+            .count()
+    }
+
+    /// Median priority fee of the pending queue (0 when it is empty), as a hint for
+    /// what a new transaction should offer
+    pub fn median_priority_fee(&self) -> u64 {
+        let mut fees: Vec<u64> = self.pending_transactions.values().map(|tx| tx.priority_fee).collect();
+        fees.sort_unstable();
+        fees.get(fees.len() / 2).copied().unwrap_or(0)
+    }
+
+    /// Add a transaction into the Gulf Stream queues, reporting why it was refused
+    pub fn try_add_transaction(
+        &mut self,
+        tx_hash: Vec<u8>,
+        raw_tx: Vec<u8>,
+        priority_fee: u64,
+    ) -> Result<(), AdmissionError> {
+        if raw_tx.len() > self.max_tx_size {
+            self.transactions_rejected += 1;
+            return Err(AdmissionError::TooLarge {
+                size: raw_tx.len(),
+                limit: self.max_tx_size,
+            });
+        }
+
         if self.pending_transactions.contains_key(&tx_hash) {
-            println!("Transaction already exists");
-            return false;
+            return Err(AdmissionError::Duplicate);
         }
 
         // 1. Pre-Validate Signature (Defense against DoS)
-        if let Ok(payload) = bincode::deserialize::<crate::network::TransactionPayload>(&raw_tx) {
-            if !payload.verify() {
-                 println!("GulfStream: REJECTED invalid signature for tx {:?}", hex::encode(&tx_hash));
-                 self.transactions_rejected += 1;
-                 return false;
+        let tx = match crate::network::decode_transaction(&raw_tx) {
+            Ok(tx) => tx,
+            Err(_) => {
+                self.transactions_rejected += 1;
+                return Err(AdmissionError::Malformed);
+            }
+        };
+        if let crate::network::WireTransaction::Bare(payload) = &tx {
+            if !self.accept_bare_transactions && !payload.self_authenticating() {
+                self.transactions_rejected += 1;
+                return Err(AdmissionError::Unsigned);
             }
-        } else {
-             println!("GulfStream: REJECTED malformed transaction");
-             self.transactions_rejected += 1;
-             return false;
         }
+        if tx.verify(self.chain_id().as_deref(), self.nonce_source.as_deref()).is_err() {
+            self.transactions_rejected += 1;
+            return Err(AdmissionError::InvalidSignature);
+        }
+        let sender = tx.sender();
+        let nonce = tx.nonce();
 
-        let gs_tx = CompassGulfStreamTransaction::new(tx_hash.clone(), raw_tx, priority_fee);
+        // 2. Nonce must be unused and within the window past the account's next one
+        if let (Some(account), Some(nonce)) = (&sender, nonce) {
+            if let Some(next) = self.next_nonce(account) {
+                if nonce < next {
+                    self.transactions_rejected += 1;
+                    return Err(AdmissionError::NonceTooLow { account: account.clone(), nonce, next });
+                }
+                if nonce - next > self.future_nonce_window {
+                    self.transactions_rejected += 1;
+                    return Err(AdmissionError::NonceTooFarAhead {
+                        account: account.clone(),
+                        nonce,
+                        next,
+                        window: self.future_nonce_window,
+                    });
+                }
+            }
+        }
 
-        if priority_fee > 1000 {
-            self.high_priority_queue.push(HighPrioItem {
-                priority_fee,
-                timestamp_ms: gs_tx.timestamp_ms,
-                tx_hash: tx_hash.clone(),
-            });
-        } else if priority_fee > 100 {
-            self.normal_priority_queue.push_back(tx_hash.clone());
+        // 3. Admission pipeline: would the transaction execute against confirmed state?
+        if let Some(storage) = &self.nonce_source {
+            if let Some(err) = self.checks.iter().find_map(|check| check.check(&tx, storage).err()) {
+                self.transactions_rejected += 1;
+                return Err(err);
+            }
+        }
+
+        // 4. Replace-by-fee: same sender and nonce as a pending transaction
+        let replaces = match (&sender, nonce) {
+            (Some(account), Some(nonce)) => self.pending_by_nonce(account, nonce),
+            _ => None,
+        };
+        if let Some(old_hash) = &replaces {
+            let pending_fee = self.pending_transactions[old_hash].priority_fee;
+            if priority_fee <= pending_fee {
+                self.transactions_rejected += 1;
+                return Err(AdmissionError::ReplacementUnderpriced {
+                    nonce: nonce.unwrap_or_default(),
+                    pending_fee,
+                    offered_fee: priority_fee,
+                });
+            }
         } else {
-            self.low_priority_queue.push_back(tx_hash.clone());
+            if self.pending_transactions.len() >= self.capacity {
+                self.transactions_rejected += 1;
+                return Err(AdmissionError::MempoolFull { capacity: self.capacity });
+            }
+
+            // 5. Per-account quota (a replacement keeps the count unchanged)
+            if let Some(account) = &sender {
+                let pending = self.pending_count_for(account);
+                if pending >= self.max_pending_per_account {
+                    self.transactions_rejected += 1;
+                    return Err(AdmissionError::AccountQuotaExceeded {
+                        account: account.clone(),
+                        pending,
+                        limit: self.max_pending_per_account,
+                    });
+                }
+            }
+        }
+
+        if let Some(old_hash) = replaces {
+            self.pending_transactions.remove(&old_hash);
+            self.ready_queue.retain(|item| item.tx_hash != old_hash);
+            self.transactions_replaced += 1;
         }
 
-        self.pending_transactions.insert(tx_hash.clone(), gs_tx);
+        let mut gs_tx = CompassGulfStreamTransaction::new(tx_hash.clone(), raw_tx, priority_fee);
+        gs_tx.sender = sender;
+        gs_tx.nonce = nonce;
+        gs_tx.compute_units = compute_units(tx.payload());
+
+        self.ready_queue.push(ReadyItem {
+            priority_fee,
+            compute_units: gs_tx.compute_units,
+            timestamp_ms: gs_tx.timestamp_ms,
+            tx_hash: tx_hash.clone(),
+        });
+        self.pending_transactions.insert(tx_hash, gs_tx);
         self.transactions_received += 1;
-        true
+        Ok(())
+    }
+
+    /// Hash of the pending transaction `account` sent with `nonce`, if any
+    fn pending_by_nonce(&self, account: &str, nonce: u64) -> Option<Vec<u8>> {
+        self.pending_transactions
+            .values()
+            .find(|tx| tx.nonce == Some(nonce) && tx.sender.as_deref() == Some(account))
+            .map(|tx| tx.tx_hash.clone())
+    }
+
+    /// Whether `tx` can run now given the nonces taken so far in this batch (`expected`,
+    /// seeded from the nonce source): `Some(true)` ready, `Some(false)` waiting on an
+    /// earlier nonce, `None` if its nonce was used in the meantime
+    fn nonce_ready(&self, tx: &CompassGulfStreamTransaction, expected: &mut HashMap<String, u64>) -> Option<bool> {
+        let (Some(sender), Some(nonce)) = (&tx.sender, tx.nonce) else {
+            return Some(true);
+        };
+        let next = match expected.get(sender) {
+            Some(next) => *next,
+            None => match self.next_nonce(sender) {
+                Some(next) => *expected.entry(sender.clone()).or_insert(next),
+                // No confirmed nonces to go by: just keep the sender's own order
+                None => {
+                    let waits = self.pending_transactions.values().any(|other| {
+                        other.sender.as_ref() == Some(sender) && other.nonce.is_some_and(|n| n < nonce)
+                    });
+                    return Some(!waits);
+                }
+            },
+        };
+        match nonce.cmp(&next) {
+            Ordering::Less => None,
+            Ordering::Equal => Some(true),
+            Ordering::Greater => Some(false),
+        }
     }
 
     /// Confirm a transaction (move from pending → confirmed)
@@ -123,6 +336,8 @@ impl CompassGulfStreamManager {
             .retain(|_, tx| tx.timestamp_ms >= cutoff);
         self.processing_transactions
             .retain(|_, tx| tx.timestamp_ms >= cutoff);
+        let pending = &self.pending_transactions;
+        self.ready_queue.retain(|item| pending.contains_key(&item.tx_hash));
     }
 
     /// Update current slot based on validator schedule
@@ -147,68 +362,48 @@ impl CompassGulfStreamManager {
         }
     }
 
-    /// Retrieve a batch of transactions to forward/process, prioritized by fee
+    /// Retrieve a batch of transactions to forward/process, highest fee per compute unit
+    /// first. A sender's transactions leave in nonce order with no gaps; later nonces stay
+    /// queued until the ones before them arrive.
     pub fn pop_ready_transactions(&mut self, limit: usize) -> Vec<CompassGulfStreamTransaction> {
         let mut result = Vec::with_capacity(limit);
-        let mut count = 0;
-
-        // Helper to process a queue
-        // We can't easily capture 'self' in a closure that modifies 'self', so we do it iteratively.
-        // 1. High Priority
-        while count < limit && !self.high_priority_queue.is_empty() {
-             // Removing from Vec (swap_remove is O(1) but changes order, remove(0) is O(N).
-             // Since it's a priority queue (sorted implied? No, just > 1000 fee).
-             // Actually, the current implementation uses `push` for high prio, so it's a stack or we should treat it as queue?
-             // `high_priority_queue` is `Vec<HighPrioItem>`. 
-             // Ideally we want the highest fees. For now, let's just take from the "front" if we treat it as queue,
-             // or "back" if we treat it as stack.
-             // Given it is `Vec` and others are `VecDeque` (back/front), let's assume we want FIFO or simply high fee.
-             // If we just `pop()` we get the last added. To be a queue we need `remove(0)` which is slow.
-             // BUT, `high_priority_queue` is a `Vec`, likely small.
-             // Let's sort it by priority before popping? That's expensive every time.
-             // For this step, let's just pop from back (LIFO) or remove(0).
-             // Let's use `remove(0)` for FIFO behavior on the Vec, accepting O(N) for now since high prio queue shouldn't be massive.
-             // Or better, change `high_priority_queue` to `VecDeque` for O(1) pop_front?
-             // The struct definition has `high_priority_queue: Vec<HighPrioItem>`.
-             // Changing struct diffs is annoying. I will use `remove(0)`.
-             
-             let item = self.high_priority_queue.remove(0);
-             if let Some(mut tx) = self.pending_transactions.remove(&item.tx_hash) {
-                 tx.status = crate::gulf_stream::transactions::TransactionStatus::Processing;
-                 self.processing_transactions.insert(item.tx_hash.clone(), tx.clone());
-                 result.push(tx);
-                 count += 1;
-             }
-        }
-
-        // 2. Normal Priority (VecDeque)
-        while count < limit {
-            if let Some(tx_hash) = self.normal_priority_queue.pop_front() {
-                if let Some(mut tx) = self.pending_transactions.remove(&tx_hash) {
-                    tx.status = crate::gulf_stream::transactions::TransactionStatus::Processing;
-                    self.processing_transactions.insert(tx_hash.clone(), tx.clone());
-                    result.push(tx);
-                    count += 1;
+        let mut deferred = Vec::new();
+        let mut expected: HashMap<String, u64> = HashMap::new();
+
+        while result.len() < limit {
+            let Some(item) = self.ready_queue.pop() else { break };
+            let Some(tx) = self.pending_transactions.get(&item.tx_hash) else {
+                continue; // replaced, rejected or expired
+            };
+            match self.nonce_ready(tx, &mut expected) {
+                Some(true) => {}
+                Some(false) => {
+                    deferred.push(item);
+                    continue;
+                }
+                None => {
+                    // Its nonce was confirmed by another transaction since admission
+                    self.pending_transactions.remove(&item.tx_hash);
+                    self.transactions_rejected += 1;
+                    continue;
                 }
-            } else {
-                break;
             }
-        }
 
-        // 3. Low Priority (VecDeque)
-        while count < limit {
-            if let Some(tx_hash) = self.low_priority_queue.pop_front() {
-                 if let Some(mut tx) = self.pending_transactions.remove(&tx_hash) {
-                    tx.status = crate::gulf_stream::transactions::TransactionStatus::Processing;
-                    self.processing_transactions.insert(tx_hash.clone(), tx.clone());
-                    result.push(tx);
-                    count += 1;
-                 }
-            } else {
-                break;
+            let Some(mut tx) = self.pending_transactions.remove(&item.tx_hash) else { continue };
+            if let (Some(sender), Some(nonce)) = (&tx.sender, tx.nonce) {
+                if let Some(next) = expected.get_mut(sender) {
+                    *next = nonce + 1;
+                }
             }
+            tx.status = crate::gulf_stream::transactions::TransactionStatus::Processing;
+            self.processing_transactions.insert(item.tx_hash.clone(), tx.clone());
+            result.push(tx);
+
+            // Taking this one may have unblocked a deferred follow-up nonce
+            self.ready_queue.extend(deferred.drain(..));
         }
 
+        self.ready_queue.extend(deferred);
         result
     }
 
@@ -226,11 +421,212 @@ impl CompassGulfStreamManager {
             avg_confirmation_time_ms: 0.0,
             current_slot: self.current_slot.as_ref().map(|s| s.validator_id.clone()),
             next_leader: self.next_leader.clone(),
-            queue_sizes: QueueSizes {
-                high_priority: self.high_priority_queue.len() as u64,
-                normal_priority: self.normal_priority_queue.len() as u64,
-                low_priority: self.low_priority_queue.len() as u64,
-            },
+            queue_sizes: QueueSizes::from_fees(self.pending_transactions.values().map(|tx| tx.priority_fee)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::TransactionPayload;
+
+    fn cancel_tx(user: &str, order_id: u64) -> (Vec<u8>, Vec<u8>) {
+        let payload = TransactionPayload::CancelOrder {
+            user: user.to_string(),
+            order_id,
+            signature: "sig".to_string(),
+        };
+        let raw = bincode::serialize(&payload).unwrap();
+        use sha2::Digest;
+        (sha2::Sha256::digest(&raw).to_vec(), raw)
+    }
+
+    fn transfer_tx(from: &str, nonce: u64, amount: u64) -> (Vec<u8>, Vec<u8>) {
+        let payload = TransactionPayload::Transfer {
+            from: from.to_string(),
+            to: "bob".to_string(),
+            asset: "Compass".to_string(),
+            amount,
+            nonce,
+            fee: 0,
+            signature: "sig".to_string(),
+            public_key: String::new(),
+            timestamp: 0,
+            prev_hash: String::new(),
+        };
+        let raw = bincode::serialize(&payload).unwrap();
+        use sha2::Digest;
+        (sha2::Sha256::digest(&raw).to_vec(), raw)
+    }
+
+    #[test]
+    fn test_replace_by_fee() {
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        let (first, raw) = transfer_tx("alice", 1, 10);
+        gs.try_add_transaction(first.clone(), raw, 50).unwrap();
+
+        let (hash, raw) = transfer_tx("alice", 1, 11);
+        assert_eq!(
+            gs.try_add_transaction(hash, raw, 50),
+            Err(AdmissionError::ReplacementUnderpriced { nonce: 1, pending_fee: 50, offered_fee: 50 })
+        );
+
+        let (bumped, raw) = transfer_tx("alice", 1, 12);
+        gs.try_add_transaction(bumped.clone(), raw, 80).unwrap();
+        assert_eq!(gs.pending_count_for("alice"), 1);
+        assert!(!gs.pending_transactions.contains_key(&first));
+
+        let popped = gs.pop_ready_transactions(10);
+        assert_eq!(popped.len(), 1);
+        assert_eq!(popped[0].tx_hash, bumped);
+    }
+
+    #[test]
+    fn test_pops_by_fee_per_unit_in_nonce_order() {
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        gs.accept_bare_transactions = true;
+        // Both cost one compute unit, so the higher fee leaves first
+        let (cheap, raw) = transfer_tx("carol", 1, 5);
+        gs.try_add_transaction(cheap.clone(), raw, 20).unwrap();
+        let (cancel, raw) = cancel_tx("dave", 1);
+        gs.try_add_transaction(cancel.clone(), raw, 30).unwrap();
+
+        // alice's second nonce pays the most but cannot overtake her first
+        let (a1, raw) = transfer_tx("alice", 1, 5);
+        gs.try_add_transaction(a1.clone(), raw, 10).unwrap();
+        let (a2, raw) = transfer_tx("alice", 2, 5);
+        gs.try_add_transaction(a2.clone(), raw, 500).unwrap();
+
+        let order: Vec<Vec<u8>> = gs.pop_ready_transactions(10).into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(order, vec![cancel, cheap, a1, a2]);
+    }
+
+    #[test]
+    fn test_nonce_window_and_gaps() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        storage.writer().set_nonce("alice", 4).unwrap();
+        storage.writer().set_balance("alice", "Compass", 100).unwrap();
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        gs.future_nonce_window = 3;
+        gs.set_nonce_source(storage.clone());
+
+        let (hash, raw) = transfer_tx("alice", 4, 1);
+        assert!(matches!(gs.try_add_transaction(hash, raw, 0), Err(AdmissionError::NonceTooLow { next: 5, .. })));
+        let (hash, raw) = transfer_tx("alice", 9, 1);
+        assert!(matches!(gs.try_add_transaction(hash, raw, 0), Err(AdmissionError::NonceTooFarAhead { .. })));
+
+        // 7 waits behind the missing 6
+        let (n5, raw) = transfer_tx("alice", 5, 1);
+        gs.try_add_transaction(n5.clone(), raw, 0).unwrap();
+        let (n7, raw) = transfer_tx("alice", 7, 1);
+        gs.try_add_transaction(n7.clone(), raw, 0).unwrap();
+        let popped: Vec<Vec<u8>> = gs.pop_ready_transactions(10).into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(popped, vec![n5]);
+        assert!(gs.pending_transactions.contains_key(&n7));
+
+        storage.writer().set_nonce("alice", 5).unwrap();
+        let (n6, raw) = transfer_tx("alice", 6, 1);
+        gs.try_add_transaction(n6.clone(), raw, 0).unwrap();
+        let popped: Vec<Vec<u8>> = gs.pop_ready_transactions(10).into_iter().map(|tx| tx.tx_hash).collect();
+        assert_eq!(popped, vec![n6, n7]);
+    }
+
+    #[test]
+    fn test_admission_checks_run_against_confirmed_state() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        storage.writer().set_balance("alice", "Compass", 5).unwrap();
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        gs.set_nonce_source(storage.clone());
+        assert_eq!(gs.check_names(), vec!["known-asset", "balance"]);
+
+        let (hash, raw) = transfer_tx("alice", 1, 6);
+        assert_eq!(
+            gs.try_add_transaction(hash, raw, 0),
+            Err(AdmissionError::InsufficientBalance { account: "alice".into(), asset: "Compass".into(), balance: 5, required: 6 })
+        );
+        let (hash, raw) = transfer_tx("alice", 1, 5);
+        gs.try_add_transaction(hash, raw, 0).unwrap();
+
+        let mut payload = crate::network::decode_transaction_payload(&transfer_tx("alice", 2, 1).1).unwrap();
+        if let TransactionPayload::Transfer { asset, .. } = &mut payload {
+            *asset = "Compas".to_string();
         }
+        let raw = bincode::serialize(&payload).unwrap();
+        assert_eq!(
+            gs.try_add_transaction(vec![9], raw, 0),
+            Err(AdmissionError::UnknownAsset { asset: "Compas".into() })
+        );
+
+        // Extra stages run after the built-in ones
+        struct NoCancels;
+        impl AdmissionCheck for NoCancels {
+            fn name(&self) -> &'static str {
+                "no-cancels"
+            }
+            fn check(&self, tx: &crate::network::WireTransaction, _: &Storage) -> Result<(), AdmissionError> {
+                match tx.payload() {
+                    TransactionPayload::CancelOrder { .. } => {
+                        Err(AdmissionError::Rejected { check: self.name().into(), reason: "cancels are paused".into() })
+                    }
+                    _ => Ok(()),
+                }
+            }
+        }
+        gs.add_check(Box::new(NoCancels));
+        gs.accept_bare_transactions = true;
+        let (hash, raw) = cancel_tx("bob", 1);
+        assert!(matches!(gs.try_add_transaction(hash, raw, 0), Err(AdmissionError::Rejected { .. })));
+    }
+
+    #[test]
+    fn test_bare_payloads_need_their_own_signature() {
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        let (hash, raw) = cancel_tx("alice", 1);
+        assert_eq!(gs.try_add_transaction(hash.clone(), raw.clone(), 0), Err(AdmissionError::Unsigned));
+        let (transfer, transfer_raw) = transfer_tx("alice", 1, 5);
+        gs.try_add_transaction(transfer, transfer_raw, 0).unwrap();
+
+        gs.accept_bare_transactions = true;
+        gs.try_add_transaction(hash, raw, 0).unwrap();
+    }
+
+    #[test]
+    fn test_rejects_oversized_transaction() {
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        gs.max_tx_size = 16;
+        let (hash, raw) = cancel_tx("alice", 1);
+        let size = raw.len();
+        assert_eq!(
+            gs.try_add_transaction(hash, raw, 0),
+            Err(AdmissionError::TooLarge { size, limit: 16 })
+        );
+    }
+
+    #[test]
+    fn test_per_account_quota() {
+        let mut gs = CompassGulfStreamManager::new("test".to_string(), 100);
+        gs.max_pending_per_account = 2;
+        gs.accept_bare_transactions = true;
+
+        for i in 0..2 {
+            let (hash, raw) = cancel_tx("alice", i);
+            assert!(gs.try_add_transaction(hash, raw, 0).is_ok());
+        }
+
+        let (hash, raw) = cancel_tx("alice", 2);
+        assert!(matches!(
+            gs.try_add_transaction(hash, raw, 0),
+            Err(AdmissionError::AccountQuotaExceeded { pending: 2, limit: 2, .. })
+        ));
+
+        // Other accounts are unaffected
+        let (hash, raw) = cancel_tx("bob", 0);
+        assert!(gs.try_add_transaction(hash, raw, 0).is_ok());
+
+        // Draining the queue frees the quota
+        gs.pop_ready_transactions(10);
+        let (hash, raw) = cancel_tx("alice", 3);
+        assert!(gs.try_add_transaction(hash, raw, 0).is_ok());
     }
 }
@@ -1,5 +1,6 @@
 // src/gulf_stream/mod.rs
 
+pub mod admission;
 pub mod manager;
 pub mod stats;
 pub mod transactions;
@@ -7,5 +8,6 @@ pub mod utils;
 pub mod validator;
 
 // Re‑export commonly used types so you can `use gulf_stream::...` in main.rs
+pub use admission::AdmissionError;
 pub use manager::CompassGulfStreamManager;
 pub use utils::now_ms;
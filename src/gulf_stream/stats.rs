@@ -22,3 +22,18 @@ pub struct QueueSizes {
     pub normal_priority: u64,
     pub low_priority: u64,
 }
+
+impl QueueSizes {
+    /// Bucket pending fees into the reporting tiers (> 1000 high, > 100 normal, else low)
+    pub fn from_fees(fees: impl Iterator<Item = u64>) -> Self {
+        let mut sizes = QueueSizes { high_priority: 0, normal_priority: 0, low_priority: 0 };
+        for fee in fees {
+            match fee {
+                f if f > 1000 => sizes.high_priority += 1,
+                f if f > 100 => sizes.normal_priority += 1,
+                _ => sizes.low_priority += 1,
+            }
+        }
+        sizes
+    }
+}
@@ -15,6 +15,11 @@ pub struct CompassGulfStreamTransaction {
     pub raw_tx: Vec<u8>,
     pub priority_fee: u64,
     pub timestamp_ms: u128,
+    pub sender: Option<String>,
+    /// Sender nonce, for payloads that carry one
+    pub nonce: Option<u64>,
+    /// Estimated execution cost; `priority_fee / compute_units` orders the queue
+    pub compute_units: u64,
     pub status: TransactionStatus,
     pub processing_node: Option<String>,
     pub confirmation_time_ms: Option<u128>,
@@ -31,6 +36,9 @@ impl CompassGulfStreamTransaction {
             raw_tx,
             priority_fee,
             timestamp_ms,
+            sender: None,
+            nonce: None,
+            compute_units: 1,
             status: TransactionStatus::Pending,
             processing_node: None,
             confirmation_time_ms: None,
@@ -0,0 +1,126 @@
+// Execution hooks
+//
+// Integrators attach custom indexing, compliance checks or notifications by
+// implementing `Hook` and registering it before the node starts, instead of
+// forking the execution loop. `on_block` sees every block the chain commits,
+// whether produced here or synced from a peer; `on_tx` sees every transaction
+// payload the node is about to execute. Hooks run inline while the chain is
+// locked, so anything slow (HTTP calls, disk-heavy indexing) should hand the
+// data to a channel or task of its own. A hook that panics is logged and
+// skipped; it never takes the execution loop down with it.
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::Arc;
+use tracing::warn;
+
+use crate::block::Block;
+use crate::network::TransactionPayload;
+
+/// Callbacks into block commits and transaction execution. Both default to doing
+/// nothing, so a hook implements only what it needs.
+pub trait Hook: Send + Sync {
+    /// Shown in logs when the hook fails
+    fn name(&self) -> &str {
+        "hook"
+    }
+
+    /// A block was committed at `block.header.index`
+    fn on_block(&self, _block: &Block) {}
+
+    /// A transaction payload is about to execute
+    fn on_tx(&self, _payload: &TransactionPayload) {}
+}
+
+/// Hooks in registration order
+#[derive(Clone, Default)]
+pub struct HookRegistry {
+    hooks: Vec<Arc<dyn Hook>>,
+}
+
+impl HookRegistry {
+    pub fn register(&mut self, hook: Arc<dyn Hook>) {
+        self.hooks.push(hook);
+    }
+
+    pub fn len(&self) -> usize {
+        self.hooks.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.hooks.is_empty()
+    }
+
+    pub fn on_block(&self, block: &Block) {
+        self.each(|hook| hook.on_block(block));
+    }
+
+    pub fn on_tx(&self, payload: &TransactionPayload) {
+        self.each(|hook| hook.on_tx(payload));
+    }
+
+    fn each(&self, call: impl Fn(&dyn Hook)) {
+        for hook in &self.hooks {
+            if catch_unwind(AssertUnwindSafe(|| call(hook.as_ref()))).is_err() {
+                warn!("Hook '{}' panicked; skipped", hook.name());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct Recorder {
+        seen: Mutex<Vec<String>>,
+    }
+
+    impl Hook for Recorder {
+        fn on_block(&self, block: &Block) {
+            self.seen.lock().unwrap().push(format!("block {}", block.header.index));
+        }
+
+        fn on_tx(&self, payload: &TransactionPayload) {
+            self.seen.lock().unwrap().push(format!("tx {}", payload.get_account_id().unwrap_or_default()));
+        }
+    }
+
+    struct Faulty;
+
+    impl Hook for Faulty {
+        fn on_block(&self, _block: &Block) {
+            panic!("indexer crashed");
+        }
+    }
+
+    #[test]
+    fn test_hooks_see_committed_blocks_and_survive_a_panicking_peer() {
+        let recorder = Arc::new(Recorder::default());
+        let mut chain = crate::chain::Chain::new_in_memory();
+        chain.hooks.register(Arc::new(Faulty));
+        chain.hooks.register(recorder.clone());
+
+        let leader = Arc::new(crate::crypto::KeyPair::from_seed("leader"));
+        let signer = crate::node::signer::NodeSigner::Local(leader.clone());
+        let tick = crate::node::poh_service::PohTick { tick: 1, iterations: 10, end_hash: vec![1], vdf_time: Default::default() };
+        let header = crate::node::poh_service::build_poh_header(&tick, 0, "0".repeat(64), &signer).unwrap();
+        chain.append_poh(header, &leader.public_key_hex()).unwrap();
+        chain.hooks.on_tx(&TransactionPayload::Transfer {
+            from: "alice".to_string(),
+            to: "bob".to_string(),
+            asset: "COMPASS".to_string(),
+            amount: 1,
+            nonce: 1,
+            fee: 0,
+            signature: String::new(),
+            public_key: String::new(),
+            timestamp: 0,
+            prev_hash: String::new(),
+        });
+
+        let seen = recorder.seen.lock().unwrap();
+        assert_eq!(seen.first().map(String::as_str), Some("block 0"));
+        assert_eq!(seen.last().map(String::as_str), Some("tx alice"));
+    }
+}
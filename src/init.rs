@@ -1,10 +1,20 @@
 #![allow(dead_code)]
-/// First-time initialization module
-/// Automatically generates admin keys, verifier keys, and worker wallets
+//! First-time initialization module
+//! Automatically generates admin keys, verifier keys, and worker wallets,
+//! and runs the `compass init` first-run wizard
 
+use crate::config::{CompassConfig, NodeConfig};
 use crate::crypto::KeyPair;
+use crate::genesis::{GenesisConfig, GenesisValidator};
+use crate::identity::{Identity, NodeRole};
+use crate::interactive::prompt::{self, confirm, print_header, prompt, prompt_or};
+use std::collections::HashMap;
 use std::fs;
 use std::path::Path;
+use std::time::Duration;
+
+/// How long the wizard waits for each bootnode to accept a TCP connection
+pub const BOOTNODE_DIAL_TIMEOUT: Duration = Duration::from_secs(3);
 
 pub struct InitializedKeys {
     pub admin_keypair: KeyPair,
@@ -181,3 +191,287 @@ pub fn generate_worker_wallet(worker_id: &str) -> (KeyPair, String, String) {
     
     (keypair, address, mnemonic)
 }
+
+// ========== NODE IDENTITY ==========
+
+/// Resolve the node's signing key from disk: the configured identity file, then
+/// `admin.json`, then the legacy `admin_key.mnemonic`. `Ok(None)` means none exist.
+pub fn load_node_identity(node: &NodeConfig) -> Result<Option<KeyPair>, String> {
+    for path in [Path::new(&node.identity_file), Path::new("admin.json")] {
+        if path.exists() {
+            println!("Found Identity File: {:?}", path);
+            return crate::interactive::unlock_node_identity(path).map(Some);
+        }
+    }
+
+    if Path::new("admin_key.mnemonic").exists() {
+        println!("Loading Legacy Admin Key from 'admin_key.mnemonic'");
+        let phrase = fs::read_to_string("admin_key.mnemonic").map_err(|e| e.to_string())?;
+        return KeyPair::from_mnemonic(phrase.trim())
+            .map(Some)
+            .map_err(|e| format!("Invalid mnemonic in admin_key.mnemonic: {}", e));
+    }
+
+    Ok(None)
+}
+
+// ========== FIRST-RUN WIZARD ==========
+
+/// `compass init`: create the identity keystore, obtain genesis, write the config,
+/// optionally register with a running network, and check the bootnodes.
+pub async fn run_wizard(config_path: &str) {
+    print_header("COMPASS FIRST-RUN SETUP");
+    let mut config = if Path::new(config_path).exists() {
+        CompassConfig::load_or_default(config_path)
+    } else {
+        CompassConfig::default()
+    };
+
+    println!("[1/5] Identity");
+    let keypair = match setup_identity(&mut config.node) {
+        Ok(kp) => kp,
+        Err(e) => {
+            println!("❌ {}", e);
+            return;
+        }
+    };
+
+    println!("\n[2/5] Genesis");
+    if let Err(e) = setup_genesis(&config.node, &keypair).await {
+        println!("❌ {}", e);
+        return;
+    }
+
+    println!("\n[3/5] Node configuration");
+    setup_node_config(&mut config);
+    match config.save(config_path) {
+        Ok(()) => println!("✅ Wrote {}", config_path),
+        Err(e) => {
+            println!("❌ Failed to write {}: {}", config_path, e);
+            return;
+        }
+    }
+
+    println!("\n[4/5] Registration");
+    register_node(&config.node, &keypair).await;
+
+    println!("\n[5/5] Connectivity");
+    check_bootnodes(&config.node.bootnodes).await;
+
+    println!("\nSetup complete. Start the node with: compass node start");
+}
+
+fn setup_identity(node: &mut NodeConfig) -> Result<KeyPair, String> {
+    node.identity_file = prompt_or("Identity keystore", &node.identity_file);
+    let path = Path::new(&node.identity_file);
+
+    if path.exists() {
+        if confirm(&format!("'{}' already exists. Use it?", node.identity_file), true) {
+            return crate::interactive::unlock_node_identity(path);
+        }
+        if !confirm("Replace it with a new identity? The old key is lost unless you have its mnemonic", false) {
+            return Err("Keeping the existing identity; nothing was changed.".to_string());
+        }
+    }
+
+    let name = prompt_or("Identity name", "admin");
+    let role: NodeRole = prompt_or("Role (admin/verifier/user)", "admin").parse()?;
+    let pass = prompt::password("Set password (empty allows unattended start): ");
+    if !pass.is_empty() && prompt::password("Confirm password: ") != pass {
+        return Err("Passwords do not match.".to_string());
+    }
+
+    let (identity, mnemonic) = Identity::new(&name, role, &pass)?;
+    identity.save(path)?;
+    println!("\n⚠️  CRITICAL: Write down your mnemonic. It is the only way to recover this key.");
+    println!("📝 Mnemonic: {}", mnemonic);
+    println!("🔑 Address:  {}", identity.public_key);
+    println!("💾 Saved to {}", node.identity_file);
+    identity.into_keypair()
+}
+
+async fn setup_genesis(node: &NodeConfig, keypair: &KeyPair) -> Result<(), String> {
+    let path = &node.genesis_file;
+    if Path::new(path).exists() {
+        match GenesisConfig::load(path) {
+            Ok(genesis) => {
+                println!("✅ Using existing {} (chain '{}')", path, genesis.chain_id);
+                return Ok(());
+            }
+            Err(e) => println!("⚠️  {} is unreadable ({}); replacing it", path, e),
+        }
+    }
+
+    println!("1. Generate a local genesis (new network, this identity as validator)");
+    println!("2. Fetch genesis from a URL (join an existing network)");
+    let genesis = match prompt("Select [1]: ").as_str() {
+        "2" => fetch_genesis(&prompt("Genesis URL: ")).await?,
+        _ => local_genesis(&prompt_or("Chain ID", "compass-devnet"), keypair),
+    };
+    genesis.save(path)?;
+    println!("✅ Wrote {} (chain '{}')", path, genesis.chain_id);
+    Ok(())
+}
+
+/// Genesis for a fresh network led by `keypair`. Uses the pinned genesis timestamp,
+/// since the genesis block hash is checked against it on startup.
+pub fn local_genesis(chain_id: &str, keypair: &KeyPair) -> GenesisConfig {
+    let mut initial_balances = HashMap::new();
+    initial_balances.insert("admin".to_string(), 1_000_000_000_000);
+    initial_balances.insert("foundation".to_string(), 1_000_000_000_000);
+
+    GenesisConfig {
+        chain_id: chain_id.to_string(),
+        timestamp: crate::genesis::GENESIS_TIMESTAMP_MS,
+        initial_balances,
+        allocations: vec![],
+        initial_validators: vec![GenesisValidator {
+            id: "admin".to_string(),
+            public_key: keypair.public_key_hex(),
+            stake: 0,
+        }],
+        genesis_hash: None,
+    }
+    .pinned()
+}
+
+async fn fetch_genesis(url: &str) -> Result<GenesisConfig, String> {
+    let resp = reqwest::get(url).await.map_err(|e| format!("Failed to fetch genesis: {}", e))?;
+    if !resp.status().is_success() {
+        return Err(format!("Failed to fetch genesis: HTTP {}", resp.status()));
+    }
+    let genesis: GenesisConfig = resp.json().await.map_err(|e| format!("Invalid genesis file: {}", e))?;
+    genesis.verify_pin()?;
+    if genesis.timestamp != crate::genesis::GENESIS_TIMESTAMP_MS {
+        return Err(format!(
+            "Genesis timestamp {} does not match this build's pinned genesis ({})",
+            genesis.timestamp,
+            crate::genesis::GENESIS_TIMESTAMP_MS
+        ));
+    }
+    Ok(genesis)
+}
+
+fn setup_node_config(config: &mut CompassConfig) {
+    let node = &mut config.node;
+    node.p2p_port = prompt_or("P2P port", &node.p2p_port.to_string()).parse().unwrap_or(node.p2p_port);
+    node.rpc_port = prompt_or("RPC port", &node.rpc_port.to_string()).parse().unwrap_or(node.rpc_port);
+    node.db_path = prompt_or("Database path", &node.db_path);
+
+    let current = if node.bootnodes.is_empty() { "-".to_string() } else { node.bootnodes.join(",") };
+    node.bootnodes = parse_bootnodes(&prompt_or("Bootnodes, comma-separated multiaddrs (- for none)", &current));
+}
+
+/// Comma-separated multiaddrs; "-" clears the list
+pub fn parse_bootnodes(input: &str) -> Vec<String> {
+    if input.trim() == "-" {
+        return vec![];
+    }
+    input
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect()
+}
+
+async fn register_node(node: &NodeConfig, keypair: &KeyPair) {
+    println!("1. Validator (stake Compass on a running network)");
+    println!("2. Worker (run compute jobs)");
+    println!("3. Skip");
+    match prompt("Select [3]: ").as_str() {
+        "1" => register_validator(keypair).await,
+        "2" => {
+            // Workers need no on-chain registration; rewards go to the key they sign results with
+            let url = crate::interactive::prompt_node_url("http://127.0.0.1:9000");
+            match crate::client::RpcClient::new(url.clone()).get_node_info().await {
+                Ok(_) => println!("✅ Node at {} is reachable", url),
+                Err(e) => println!("⚠️  Node at {} is not reachable yet: {}", url, e),
+            }
+            let wallet = Path::new(&node.identity_file)
+                .file_stem()
+                .and_then(|s| s.to_str())
+                .unwrap_or("identity");
+            println!("Start the worker with: compass worker --node-url {} --wallet {}", url, wallet);
+        }
+        _ => println!("Skipped."),
+    }
+}
+
+async fn register_validator(keypair: &KeyPair) {
+    let client = crate::client::RpcClient::new(crate::interactive::prompt_node_url("http://127.0.0.1:9000"));
+    let validator_id = prompt_or("Validator account (pays the stake)", "admin");
+    let Some(stake_amount) = prompt::parse_amount(&prompt_or("Stake (Compass)", "1000")) else {
+        println!("❌ Invalid stake amount");
+        return;
+    };
+
+    let params = crate::rpc::types::RegisterValidatorParams {
+        signature: keypair.sign_hex(validator_id.as_bytes()),
+        validator_id,
+        pubkey: keypair.public_key_hex(),
+        stake_amount,
+    };
+    match client.register_validator(&params).await {
+        Ok(tx_hash) => println!("✅ Registration submitted! Tx: {}", tx_hash),
+        Err(e) => println!("❌ Registration failed: {}", e),
+    }
+}
+
+async fn check_bootnodes(bootnodes: &[String]) {
+    if bootnodes.is_empty() {
+        println!("No bootnodes configured; this node will start its own network.");
+        return;
+    }
+    for addr in bootnodes {
+        let Some(target) = bootnode_tcp_target(addr) else {
+            println!("⚠️  {}: not an /ip4, /ip6 or /dns TCP multiaddr", addr);
+            continue;
+        };
+        match tokio::time::timeout(BOOTNODE_DIAL_TIMEOUT, tokio::net::TcpStream::connect(&target)).await {
+            Ok(Ok(_)) => println!("✅ {} reachable", addr),
+            Ok(Err(e)) => println!("❌ {} unreachable: {}", addr, e),
+            Err(_) => println!("❌ {} timed out after {:?}", addr, BOOTNODE_DIAL_TIMEOUT),
+        }
+    }
+}
+
+/// `host:port` to dial for a multiaddr like `/ip4/1.2.3.4/tcp/19000/p2p/...`
+pub fn bootnode_tcp_target(addr: &str) -> Option<String> {
+    let parts: Vec<&str> = addr.trim_start_matches('/').split('/').collect();
+    match parts.as_slice() {
+        ["ip4" | "dns" | "dns4" | "dns6", host, "tcp", port, ..] => {
+            port.parse::<u16>().ok().map(|p| format!("{}:{}", host, p))
+        }
+        ["ip6", host, "tcp", port, ..] => port.parse::<u16>().ok().map(|p| format!("[{}]:{}", host, p)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bootnode_tcp_target() {
+        assert_eq!(bootnode_tcp_target("/ip4/10.0.0.5/tcp/19000/p2p/12D3KooW").as_deref(), Some("10.0.0.5:19000"));
+        assert_eq!(bootnode_tcp_target("/dns4/seed.example.org/tcp/19000").as_deref(), Some("seed.example.org:19000"));
+        assert_eq!(bootnode_tcp_target("/ip6/::1/tcp/19000").as_deref(), Some("[::1]:19000"));
+        assert_eq!(bootnode_tcp_target("/ip4/10.0.0.5/udp/19000/quic"), None);
+        assert_eq!(bootnode_tcp_target("10.0.0.5:19000"), None);
+    }
+
+    #[test]
+    fn test_parse_bootnodes() {
+        assert_eq!(parse_bootnodes("-"), Vec::<String>::new());
+        assert_eq!(parse_bootnodes(" /ip4/1.1.1.1/tcp/1 , ,/ip4/2.2.2.2/tcp/2"), vec!["/ip4/1.1.1.1/tcp/1", "/ip4/2.2.2.2/tcp/2"]);
+    }
+
+    #[test]
+    fn test_local_genesis_matches_pinned_hash() {
+        // initialize_genesis panics if the genesis block hash differs from the pinned one
+        let kp = KeyPair::from_seed("init-test");
+        let mut chain = crate::chain::Chain::new_in_memory();
+        chain.initialize_genesis(&local_genesis("compass-devnet", &kp)).unwrap();
+        assert_eq!(chain.height, 1);
+    }
+}
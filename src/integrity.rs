@@ -0,0 +1,294 @@
+// Database integrity check
+//
+// `compass node verify-db` walks the canonical chain from genesis and checks
+// what the node relied on when it stored each block:
+// - the stored hash is the header's hash;
+// - signatures verify;
+// - each block links to the one before it;
+// - the height index and the head agree with the blocks.
+// Stored blocks that are neither canonical nor on a known fork branch are
+// orphans. Balances are checked separately, by replay.
+//
+// Repair only rewrites what the stored blocks themselves determine: height
+// entries are restored from a block that links into place, and orphans are
+// deleted. A block that is gone can't be rebuilt here; the report names its
+// height so it can be re-synced or re-imported.
+use std::collections::{HashMap, HashSet};
+
+use crate::block::Block;
+use crate::chain::Chain;
+use crate::error::CompassError;
+
+/// One inconsistency found by `check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Issue {
+    /// No stored block links into this height of the canonical chain
+    MissingBlock { height: u64 },
+    /// The height index is missing or names the wrong block, but `block` links
+    /// into place (repairable)
+    HeightIndex { height: u64, indexed: Option<String>, block: String },
+    /// The header no longer hashes to the hash it is stored under
+    HashMismatch { height: u64, stored: String, computed: String },
+    /// The block's prev_hash is not the hash of the block below it
+    BrokenLink { height: u64, prev_hash: String, expected: String },
+    BadSignature { height: u64, error: String },
+    /// `chain_info:head` is not the last block of the canonical walk
+    HeadMismatch { head: Option<String>, expected: String },
+    /// Stored, but neither canonical nor on a fork branch (repairable)
+    Orphan { hash: String, index: u64 },
+}
+
+impl Issue {
+    pub fn is_repairable(&self) -> bool {
+        matches!(self, Issue::HeightIndex { .. } | Issue::Orphan { .. })
+    }
+}
+
+impl std::fmt::Display for Issue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Issue::MissingBlock { height } => write!(f, "height {}: block missing", height),
+            Issue::HeightIndex { height, indexed, block } => write!(
+                f,
+                "height {}: index points at {}, block {} links here",
+                height,
+                indexed.as_deref().unwrap_or("nothing"),
+                block
+            ),
+            Issue::HashMismatch { height, stored, computed } => {
+                write!(f, "height {}: stored as {} but hashes to {}", height, stored, computed)
+            }
+            Issue::BrokenLink { height, prev_hash, expected } => {
+                write!(f, "height {}: prev_hash {} should be {}", height, prev_hash, expected)
+            }
+            Issue::BadSignature { height, error } => write!(f, "height {}: {}", height, error),
+            Issue::HeadMismatch { head, expected } => {
+                write!(f, "head is {} but the chain ends at {}", head.as_deref().unwrap_or("unset"), expected)
+            }
+            Issue::Orphan { hash, index } => write!(f, "orphaned block {} (index {})", hash, index),
+        }
+    }
+}
+
+/// What a check found
+#[derive(Debug, Default)]
+pub struct IntegrityReport {
+    /// Canonical blocks walked
+    pub blocks: u64,
+    /// Blocks stored in total, canonical or not
+    pub stored: u64,
+    pub issues: Vec<Issue>,
+}
+
+impl IntegrityReport {
+    pub fn is_clean(&self) -> bool {
+        self.issues.is_empty()
+    }
+}
+
+/// Index, parent and hash of every stored block, read once up front
+struct StoredBlock {
+    index: u64,
+    prev_hash: String,
+}
+
+fn stored_blocks(chain: &Chain) -> Result<HashMap<String, StoredBlock>, CompassError> {
+    let mut blocks = HashMap::new();
+    for item in chain.storage.db.scan_prefix("block:") {
+        let (key, value) = item?;
+        let hash = String::from_utf8_lossy(&key["block:".len()..]).into_owned();
+        match bincode::deserialize::<Block>(&value) {
+            Ok(block) => blocks.insert(hash, StoredBlock { index: block.header.index, prev_hash: block.header.prev_hash }),
+            // Undecodable: treat it as stored at an unknown height so it shows up as an orphan
+            Err(_) => blocks.insert(hash, StoredBlock { index: u64::MAX, prev_hash: String::new() }),
+        };
+    }
+    Ok(blocks)
+}
+
+/// Walk `chain`'s database from genesis (or the pruning horizon) to the furthest
+/// block that links up, reporting every inconsistency
+pub fn check(chain: &Chain) -> Result<IntegrityReport, CompassError> {
+    let storage = &chain.storage;
+    let stored = stored_blocks(chain)?;
+    let mut children: HashMap<&str, Vec<&str>> = HashMap::new();
+    for (hash, block) in &stored {
+        children.entry(block.prev_hash.as_str()).or_default().push(hash.as_str());
+    }
+    let mut report = IntegrityReport { stored: stored.len() as u64, ..Default::default() };
+
+    let head: Option<String> = storage.get("chain_info:head")?;
+    let head_index = head.as_ref().and_then(|h| stored.get(h)).map(|b| b.index);
+    let indexed_top = (0..).take_while(|h| storage.get::<String>(&format!("height:{}", h)).ok().flatten().is_some()).last();
+    let top = head_index.into_iter().chain(indexed_top).max().unwrap_or(0);
+    let start = storage.pruned_below();
+
+    let mut canonical: HashSet<String> = HashSet::new();
+    // Hash of the last block walked; `None` after a gap, where links can't be checked
+    let mut expected_prev: Option<String> = None;
+    for height in std::iter::once(0).chain(start.max(1)..=top) {
+        // The first block after pruned history links to a block that is gone
+        if height == start && start > 1 {
+            expected_prev = None;
+        }
+        let indexed: Option<String> = storage.get(&format!("height:{}", height))?;
+        let indexed_fits = indexed.as_ref().and_then(|h| stored.get(h)).is_some_and(|b| {
+            b.index == height && expected_prev.as_ref().is_none_or(|prev| &b.prev_hash == prev)
+        });
+        let hash = if indexed_fits {
+            indexed.clone()
+        } else {
+            // A stored block with the right index whose parent is the block below
+            let linking: Vec<&str> = match &expected_prev {
+                Some(prev) => children
+                    .get(prev.as_str())
+                    .map(|c| c.iter().copied().filter(|h| stored[*h].index == height).collect())
+                    .unwrap_or_default(),
+                None => Vec::new(),
+            };
+            match linking.as_slice() {
+                [only] => {
+                    report.issues.push(Issue::HeightIndex { height, indexed: indexed.clone(), block: only.to_string() });
+                    Some(only.to_string())
+                }
+                // Nothing links in, or several forks do and the index has to decide
+                _ => indexed.filter(|h| stored.contains_key(h)),
+            }
+        };
+        let Some(hash) = hash else {
+            report.issues.push(Issue::MissingBlock { height });
+            expected_prev = None;
+            continue;
+        };
+        let Some(block) = storage.get_block(&hash)? else {
+            report.issues.push(Issue::MissingBlock { height });
+            expected_prev = None;
+            continue;
+        };
+
+        let computed = block.header.calculate_hash()?;
+        if computed != hash {
+            report.issues.push(Issue::HashMismatch { height, stored: hash.clone(), computed });
+        }
+        if let Some(prev) = &expected_prev {
+            if &block.header.prev_hash != prev {
+                report.issues.push(Issue::BrokenLink { height, prev_hash: block.header.prev_hash.clone(), expected: prev.clone() });
+            }
+        }
+        if let Err(e) = chain.verify_block_signature(&block) {
+            report.issues.push(Issue::BadSignature { height, error: e.to_string() });
+        }
+        report.blocks += 1;
+        canonical.insert(hash.clone());
+        expected_prev = Some(hash);
+    }
+
+    if let Some(last) = &expected_prev {
+        if head.as_ref() != Some(last) {
+            report.issues.push(Issue::HeadMismatch { head: head.clone(), expected: last.clone() });
+        }
+    }
+
+    // Side branches hang off fork tips and run back into the canonical chain
+    let mut on_branch: HashSet<&str> = HashSet::new();
+    for (tip, _) in storage.get_fork_tips() {
+        let mut cursor = stored.get_key_value(&tip);
+        while let Some((hash, block)) = cursor {
+            if canonical.contains(hash) || !on_branch.insert(hash.as_str()) {
+                break;
+            }
+            cursor = stored.get_key_value(&block.prev_hash);
+        }
+    }
+    let mut orphans: Vec<Issue> = stored
+        .iter()
+        .filter(|(hash, _)| !canonical.contains(*hash) && !on_branch.contains(hash.as_str()))
+        .map(|(hash, block)| Issue::Orphan { hash: hash.clone(), index: block.index })
+        .collect();
+    orphans.sort_by_key(|issue| match issue {
+        Issue::Orphan { index, .. } => *index,
+        _ => 0,
+    });
+    report.issues.extend(orphans);
+    Ok(report)
+}
+
+/// Fix the repairable issues in `report`; returns how many were fixed
+pub fn repair(chain: &Chain, report: &IntegrityReport) -> Result<usize, CompassError> {
+    let mut fixed = 0;
+    for issue in &report.issues {
+        match issue {
+            Issue::HeightIndex { block, .. } => {
+                let block = chain
+                    .storage
+                    .get_block(block)?
+                    .ok_or_else(|| CompassError::DatabaseError(format!("block {} vanished during repair", block)))?;
+                chain.storage.save_block(&block)?;
+            }
+            Issue::Orphan { hash, .. } => chain.storage.delete(&format!("block:{}", hash))?,
+            _ => continue,
+        }
+        fixed += 1;
+    }
+    chain.storage.flush()?;
+    Ok(fixed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockHeader, BlockType};
+    use crate::crypto::KeyPair;
+
+    fn poh(chain: &Chain, leader: &KeyPair, tick: u64, prev_hash: Option<&str>) -> BlockHeader {
+        let mut header = BlockHeader {
+            index: chain.height,
+            timestamp: 1_000_000 + tick * 1000,
+            prev_hash: prev_hash.map(str::to_string).unwrap_or_else(|| chain.head_hash().unwrap_or_default()),
+            hash: String::new(),
+            proposer: leader.public_key_hex(),
+            signature_hex: String::new(),
+            block_type: BlockType::PoH { tick, iterations: 1, hash: String::new(), proof: String::new() },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = leader.sign_hex(&crate::encoding::hash_from_hex(&header.hash).unwrap());
+        header
+    }
+
+    #[test]
+    fn test_check_finds_and_repairs_gaps_and_orphans() {
+        let mut chain = Chain::new_in_memory();
+        let leader = KeyPair::from_seed("leader");
+        let pk = leader.public_key_hex();
+        chain.storage.set_validator_pubkey(&pk, &pk).unwrap();
+        for tick in 1..=6 {
+            chain.append_poh(poh(&chain, &leader, tick, None), &pk).unwrap();
+        }
+        assert!(check(&chain).unwrap().is_clean());
+
+        // A lost height entry, a block nothing points to, and a tampered block
+        chain.storage.delete("height:3").unwrap();
+        let stray = Block { header: poh(&chain, &leader, 99, Some("feed")), transactions: vec![] };
+        chain.storage.save_side_block(&stray).unwrap();
+        let report = check(&chain).unwrap();
+        let block3 = chain.storage.get_block_by_height(2).unwrap().unwrap();
+        assert_eq!(report.blocks, 6);
+        assert!(matches!(&report.issues[0], Issue::HeightIndex { height: 3, indexed: None, .. }));
+        assert_eq!(report.issues[1], Issue::Orphan { hash: stray.header.hash.clone(), index: 6 });
+        assert_eq!(report.issues.len(), 2);
+
+        assert_eq!(repair(&chain, &report).unwrap(), 2);
+        assert!(check(&chain).unwrap().is_clean());
+        assert!(chain.storage.get_block(&stray.header.hash).unwrap().is_none());
+        assert_eq!(chain.storage.get_block_by_height(3).unwrap().unwrap().header.prev_hash, block3.header.hash);
+
+        let mut forged = chain.storage.get_block_by_height(4).unwrap().unwrap();
+        forged.header.timestamp += 1;
+        chain.storage.save_block(&forged).unwrap();
+        let issues = check(&chain).unwrap().issues;
+        assert!(issues.iter().any(|i| matches!(i, Issue::HashMismatch { height: 4, .. })));
+        assert!(issues.iter().all(|i| !i.is_repairable()));
+    }
+}
@@ -0,0 +1,371 @@
+use crate::cli::session::{Session, UserRole};
+use crate::cli::keys::KeysCommands;
+use std::sync::Arc;
+
+pub mod prompt;
+pub mod screens;
+
+use prompt::{confirm, pause, print_header, prompt, prompt_or};
+
+/// PRODUCTION ENTRY POINT - Single Authentication, Role-Locked Menus
+pub async fn start() {
+    // ===== AUTHENTICATION GATE =====
+    let session = match Session::authenticate() {
+        Ok(s) => s,
+        Err(e) => {
+            eprintln!("\n❌ {}", e);
+            eprintln!("🔒 ACCESS DENIED. Exiting...\n");
+            std::thread::sleep(std::time::Duration::from_secs(1));
+            std::process::exit(1);
+        }
+    };
+    
+    println!("\n✅ Authenticated as: {:?} ({})", session.role, session.user_name);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━\n");
+    
+    // ===== ROLE-SPECIFIC MENU (NO SWITCHING) =====
+    match session.role {
+        UserRole::Admin => admin_menu(session).await,
+        UserRole::Worker => worker_menu(session).await,
+        UserRole::Client => client_menu(session).await,
+    }
+    
+    // ===== CLEAN EXIT =====
+    println!("\nSession ended. Goodbye.");
+    std::process::exit(0);
+}
+
+// ========== ADMIN MENU ==========
+async fn admin_menu(session: Session) {
+    loop {
+        print_header("ADMIN CONTROL PANEL");
+        println!("1. Start Admin Node (Leader)");
+        println!("2. Tools (Wipe DB, Init Oracle)");
+        println!("3. View System Wallets (Debug)");
+        println!("4. Generate Keys");
+        println!("5. Exit");
+        let choice = prompt("\nSelect: ");
+        
+        match choice.as_str() {
+            "1" => run_admin_node(session.identity.clone().expect("Admin must have identity")).await,
+            "2" => tools_menu().await,
+            "3" => {
+                println!("\n[System Wallets - Admin View]");
+                println!("(This would call RPC to list all wallets)");
+                pause();
+            },
+            "4" => key_menu(),
+            "5" => break,
+            _ => println!("Invalid option."),
+        }
+    }
+}
+
+// ========== WORKER MENU ==========
+async fn worker_menu(session: Session) {
+    print_header("WORKER / VERIFIER PANEL");
+    println!("Worker Role: Compute jobs only");
+    println!("🔒 Wallet operations DISABLED for security\n");
+    
+    println!("1. Worker Console (jobs, earnings, controls)");
+    println!("2. Start AI Compute Worker");
+    println!("3. Exit");
+    let choice = prompt("\nSelect: ");
+    
+    match choice.as_str() {
+        "1" => run_worker_console().await,
+        "2" => run_ai_worker().await,
+        "3" => {},
+        _ => println!("Invalid option."),
+    }
+}
+
+// ========== CLIENT MENU ==========
+async fn client_menu(session: Session) {
+    let client = crate::client::RpcClient::new(prompt_node_url("http://127.0.0.1:9000"));
+
+    loop {
+        print_header(&format!("CLIENT WALLET - {}", session.user_name));
+        
+        if !session.authenticated {
+            println!("⚠️  Read-only mode (not authenticated)");
+        }
+        
+        println!("1. View Balance");
+        println!("2. Transfer Funds");
+        println!("3. Mint Compass (Collateral)");
+        println!("4. Burn Compass (Redeem)");
+        println!("5. Get Vault Deposit Address");
+        println!("6. Buy Neural Network");
+        println!("7. View All NFTs (Debug)");
+        println!("8. Submit Compute Job");
+        println!("9. Validator Dashboard");
+        println!("10. Exit");
+
+        match prompt("\nSelect: ").as_str() {
+            "1" => screens::balances(&client, &session).await,
+            "2" => screens::transfer(&client, &session).await,
+            "3" => screens::mint(&client, &session).await,
+            "4" => screens::burn(&client, &session).await,
+            "5" => screens::vault_address(&session),
+            "6" => crate::layer3::user_ops::run_user_ai_menu().await,
+            "7" => screens::all_nfts(&client).await,
+            "8" => screens::submit_compute_job(&client).await,
+            "9" => screens::validator_dashboard(&client, &session).await,
+            "10" => break,
+            _ => println!("Invalid option."),
+        }
+    }
+}
+
+async fn run_admin_node(identity: Arc<crate::crypto::KeyPair>) {
+    println!("\n🚀 Starting Admin Node...");
+    
+    let mut leader_config = crate::config::CompassConfig::default();
+    leader_config.node.rpc_port = 9000;
+    leader_config.node.db_path = "compass_db_leader".to_string();
+    
+    crate::node::run_node_mode_internal(
+        leader_config,
+        None,
+        Some(identity),
+    ).await;
+}
+
+async fn run_worker_console() {
+    println!("\n🔍 Starting Worker Console...\n");
+    
+    // Use new robust helper
+    let node_url = prompt_node_url("http://localhost:9000");
+    
+    if let Err(e) = crate::worker_menu::worker_job_menu(&node_url).await {
+        println!("Worker error: {}", e);
+        pause();
+    }
+}
+
+async fn run_ai_worker() {
+    println!("\n🤖 Starting AI Worker...\n");
+    
+    let node_url = prompt_node_url("http://127.0.0.1:9000");
+    
+    // Load Identity
+    let identity = match load_and_maybe_create_identity("worker") { // Default to 'worker' wallet
+        Some(id) => id,
+        None => return,
+    };
+    
+    // Decrypt
+    let keypair = match identity.into_keypair() {
+        Ok(kp) => kp,
+        Err(e) => {
+            print!("❌ Failed to decrypt key: {}", e);
+            return;
+        }
+    };
+    
+    // Create dummy channel for standalone worker (won't receive network jobs)
+    // TODO: Connect this to real P2P if "Interactive Mode" becomes a full node
+    let (tx, _rx) = tokio::sync::broadcast::channel(100);
+    
+    let mut worker = crate::client::AiWorker::new(node_url, keypair, tx);
+    worker.start().await;
+}
+
+fn key_menu() {
+    println!("\n--- Key Management ---");
+    println!("1. Generate Admin Key");
+    println!("2. Generate Verifier Key");
+    println!("3. Generate User Key");
+    println!("4. Export Public Key");
+    println!("5. Back");
+    let choice = prompt("Select: ");
+    
+    match choice.as_str() {
+        "1" => crate::cli::keys::handle_keys_command(KeysCommands::Generate { 
+            role: "admin".to_string(), 
+            name: "admin".to_string() 
+        }),
+        "2" => crate::cli::keys::handle_keys_command(KeysCommands::Generate { 
+            role: "verifier".to_string(), 
+            name: "verifier".to_string() 
+        }),
+        "3" => {
+            let name = prompt("Enter user name: ");
+            crate::cli::keys::handle_keys_command(KeysCommands::Generate { 
+                role: "user".to_string(), 
+                name 
+            });
+        },
+        "4" => {
+            let name = prompt("Enter identity name: ");
+            crate::cli::keys::handle_keys_command(KeysCommands::ExportPub { 
+                name 
+            });
+        },
+        _ => {},
+    }
+    pause();
+}
+
+async fn tools_menu() {
+    println!("\n--- Admin Tools ---");
+    println!("1. Wipe Leader DB");
+    println!("2. Wipe Verifier DB");
+    println!("3. Generate Admin Genesis Config");
+    println!("4. Init Finance Oracle");
+    println!("5. Back");
+    let choice = prompt("Select: ");
+
+    match choice.as_str() {
+        "1" => {
+            println!("⚠️  Database deletion disabled for safety.");
+            println!("⚠️  To reset manually, delete the 'compass_db_leader' folder.");
+            // SAFETY: Commented out to prevent accidental data loss
+            // let _ = std::fs::remove_dir_all("compass_db_leader");
+        },
+        "2" => {
+            println!("⚠️  Database deletion disabled for safety.");
+            println!("⚠️  To reset manually, delete the 'compass_db_verifier' folder.");
+            // SAFETY: Commented out to prevent accidental data loss
+            // let _ = std::fs::remove_dir_all("compass_db_verifier");
+        },
+        "3" => crate::genesis::generate_admin_config(),
+        "4" => {
+            println!("\n🚀 Initializing Layer 3 Finance Oracle...");
+            let client = crate::client::RpcClient::new("http://127.0.0.1:9000".to_string());
+            match crate::layer3::finance_oracle::init_finance_oracle_job(&client).await {
+                Ok(_) => println!("✓ Initialization complete."),
+                Err(e) => println!("❌ Error: {}", e),
+            }
+        },
+        _ => {},
+    }
+    pause();
+}
+
+// ========== PUBLIC UTILITY FUNCTIONS ==========
+// (Used by worker_menu.rs and layer3/user_ops.rs)
+
+/// Load identity for backward compatibility with other modules
+
+
+pub fn load_identity(name: &str) -> Option<crate::identity::Identity> {
+    use std::path::Path;
+    
+    // 1. Try standalone identity file first
+    let filename = format!("{}.json", name);
+    if Path::new(&filename).exists() {
+        let pass = prompt::password(&format!("Enter password for '{}': ", name));
+        
+        match crate::identity::Identity::load_and_decrypt(Path::new(&filename), &pass) {
+            Ok(id) => return Some(id),
+            Err(e) => {
+                println!("Authentication Failed: {}", e);
+                return None
+            }
+        }
+    }
+
+    // 2. Fallback to WalletManager (wallets.json)
+    // This is needed because `wallet create` stores keys here, but Worker expects Identity.
+    // We can reconstruct a transient Identity from the Wallet if it exists.
+    let manager = crate::wallet::WalletManager::load("wallets.json");
+    if let Some(wallet) = manager.get_wallet(name) {
+        // Must have private key (mnemonic)
+        if let Some(mnemonic) = &wallet.mnemonic {
+             // Create Identity from Mnemonic
+             // NOTE: WalletType::User maps to NodeRole::User
+             // We use a dummy password or "none" since we are loading uncrypted from json (for now)
+             // Real implementation should support encrypted wallets in json too.
+             match crate::identity::Identity::from_mnemonic(name, crate::identity::NodeRole::User, mnemonic, "") {
+                 Ok((id, _)) => {
+                     println!("Loaded Identity '{}' from wallets.json", name);
+                     return Some(id);
+                 },
+                 Err(e) => println!("Failed to convert wallet to identity: {}", e),
+             }
+        } else {
+             println!("Wallet found in wallets.json but missing mnemonic/private key.");
+        }
+    }
+
+    None
+}
+
+/// Robustly prompt for Node URL with defaults and validation
+pub fn prompt_node_url(default: &str) -> String {
+    let url = prompt_or("Node URL", default);
+    // Basic fixup
+    if !url.starts_with("http") {
+         format!("http://{}", url)
+    } else {
+         url
+    }
+}
+
+/// Unlock the node identity used by `compass node start`.
+///
+/// An empty password is tried first so unattended testnet nodes can boot. Backup
+/// recovery and format upgrades happen inside `Identity::load_and_decrypt`.
+pub fn unlock_node_identity(path: &std::path::Path) -> Result<crate::crypto::KeyPair, String> {
+    use crate::identity::Identity;
+
+    if let Ok(id) = Identity::load_and_decrypt(path, "") {
+        println!("Identity '{}' unlocked (Passwordless) ({})", id.name, id.public_key);
+        return id.into_keypair();
+    }
+
+    let pass = prompt::password("Enter password to unlock Node Identity: ");
+    match Identity::load_and_decrypt(path, &pass) {
+        Ok(id) => {
+            println!("Identity '{}' unlocked ({})", id.name, id.public_key);
+            id.into_keypair()
+        }
+        Err(e) => Err(format!("Failed to unlock identity: {}", e)),
+    }
+}
+
+/// Load identity or prompt to create one if it doesn't exist
+pub fn load_and_maybe_create_identity(default_name: &str) -> Option<crate::identity::Identity> {
+    let name = prompt_or("   Enter wallet name", default_name);
+    let name = name.as_str();
+    
+    if let Some(id) = load_identity(name) {
+        return Some(id);
+    }
+    
+    // Not found - Prompt to create
+    println!("⚠️  Wallet '{}' not found.", name);
+    if !confirm(&format!("   Create new wallet '{}'?", name), true) {
+        return None;
+    }
+    
+    // Create logic
+    println!("   Creating new wallet '{}'...", name);
+    // Let's prompt for password to be secure
+    let pass = prompt::password("   Set password: ");
+    let pass = pass.as_str();
+    
+    // Create new identity (User role by default for workers)
+    let (id, mnemonic) = match crate::identity::Identity::new(name, crate::identity::NodeRole::User, pass) {
+        Ok(v) => v,
+        Err(e) => {
+            println!("❌ Failed to generate identity: {}", e);
+            return None;
+        }
+    };
+    
+    println!("📝 Write down your mnemonic phrase (SAFE KEEPING in admin_key.mnemonic style):");
+    println!("{}", mnemonic); // Production should handle this better, but improved for now.
+
+    let path_str = format!("{}.json", name);
+    if let Err(e) = id.save(std::path::Path::new(&path_str)) {
+        println!("❌ Failed to save wallet: {}", e);
+        return None;
+    }
+    
+    println!("✅ Wallet '{}' created!", name);
+    println!("   Address: {}", id.public_key);
+    Some(id)
+}
@@ -0,0 +1,81 @@
+// Shared stdin/stdout helpers for interactive screens
+use crate::encoding;
+use std::io::{self, Write};
+
+/// Print `label`, read one line, and return it trimmed
+pub fn prompt(label: &str) -> String {
+    print!("{}", label);
+    io::stdout().flush().unwrap();
+    let mut input = String::new();
+    io::stdin().read_line(&mut input).unwrap();
+    input.trim().to_string()
+}
+
+/// Prompt showing `[default]`; an empty answer takes the default
+pub fn prompt_or(label: &str, default: &str) -> String {
+    let input = prompt(&format!("{} [{}]: ", label, default));
+    if input.is_empty() {
+        default.to_string()
+    } else {
+        input
+    }
+}
+
+/// Prompt for a whole number; None if the answer doesn't parse
+pub fn prompt_u64(label: &str) -> Option<u64> {
+    prompt(label).parse().ok()
+}
+
+/// Prompt for a decimal token amount (e.g. "0.5") and convert to base units
+pub fn prompt_amount(label: &str) -> Option<u64> {
+    parse_amount(&prompt(label))
+}
+
+/// Read a password. Every secret prompt goes through here so masking can be added in one place.
+pub fn password(label: &str) -> String {
+    prompt(label)
+}
+
+/// Yes/no question; an empty answer takes `default`
+pub fn confirm(label: &str, default: bool) -> bool {
+    let hint = if default { "[Y/n]" } else { "[y/N]" };
+    match prompt(&format!("{} {}: ", label, hint)).to_lowercase().as_str() {
+        "" => default,
+        answer => answer == "y" || answer == "yes",
+    }
+}
+
+pub fn print_header(title: &str) {
+    print!("\x1B[2J\x1B[1;1H");
+    println!("========================================");
+    println!("  {}", title);
+    println!("========================================\n");
+}
+
+pub fn pause() {
+    println!("\nPress Enter to continue...");
+    let mut _pause = String::new();
+    io::stdin().read_line(&mut _pause).unwrap();
+}
+
+/// "1.5" -> 150_000_000, exactly as `encoding::Amount` parses it. Rejects zero.
+pub fn parse_amount(input: &str) -> Option<u64> {
+    encoding::parse_units(input, encoding::DEFAULT_DECIMALS).ok().filter(|units| *units > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_amount() {
+        assert_eq!(parse_amount("1"), Some(100_000_000));
+        assert_eq!(parse_amount(" 0.00000001 "), Some(1));
+        assert_eq!(parse_amount("0.1"), Some(10_000_000));
+        assert_eq!(parse_amount("0"), None);
+        assert_eq!(parse_amount("-3"), None);
+        assert_eq!(parse_amount("abc"), None);
+        assert_eq!(parse_amount("NaN"), None);
+        assert_eq!(parse_amount("1.000000001"), None);
+    }
+}
@@ -0,0 +1,312 @@
+// Client screens
+//
+// Each screen gathers input with the `prompt` helpers and then calls the same
+// client/tx-builder functions as the matching CLI subcommand, so signing and
+// submission logic lives in one place (`cli::tx`, `cli::ops`, `RpcClient`).
+use super::prompt::{confirm, pause, prompt, prompt_amount, prompt_or};
+use crate::cli::ops::{submit_burn, submit_mint, BurnRequest, MintRequest};
+use crate::cli::session::Session;
+use crate::cli::tx::{recipient_warning, resolve_recipient, submit_transfer};
+use crate::client::RpcClient;
+use crate::crypto::KeyPair;
+use crate::encoding::Amount;
+use crate::wallet::{AddressBook, RecipientCheck, ADDRESS_BOOK_PATH};
+
+/// The session's signing key, or a message explaining why there isn't one
+fn signer<'a>(session: &'a Session, action: &str) -> Option<&'a KeyPair> {
+    if !session.can_make_transfers() {
+        println!("\n❌ You must be authenticated to {}.", action);
+        return None;
+    }
+    session.identity.as_deref()
+}
+
+/// On-chain balances for the session's account (or an entered address)
+pub async fn balances(client: &RpcClient, session: &Session) {
+    let address = if session.authenticated {
+        session.user_name.clone()
+    } else {
+        prompt("Enter Wallet Address: ")
+    };
+    if address.is_empty() {
+        println!("❌ No address provided.");
+        pause();
+        return;
+    }
+
+    println!("\n📡 Connecting to network...");
+    match client.get_account_info(&address).await {
+        Ok(info) => print_account_info(&info),
+        Err(e) => {
+            println!("⚠️  Could not fetch blockchain balances: {}", e);
+            println!("   (Make sure the node is running)");
+        }
+    }
+    pause();
+}
+
+fn print_account_info(info: &serde_json::Value) {
+    let vault_info = info.get("vault_info").and_then(|v| v.as_object());
+    println!("\n=== Blockchain Balances (On-Chain) ===");
+
+    match info.get("balances").and_then(|b| b.as_object()) {
+        Some(balances) if !balances.is_empty() => {
+            for (asset, amount) in balances {
+                let amount_display = Amount::new(amount.as_u64().unwrap_or(0), asset).to_decimal_string();
+
+                // Vault-backed assets also show what they are redeemable for
+                match vault_info.and_then(|v| v.get(asset)) {
+                    Some(vault) => {
+                        let collateral = vault.get("collateral_balance").and_then(|v| v.as_u64()).unwrap_or(0);
+                        let ratio = vault.get("backing_ratio").and_then(|v| v.as_f64()).unwrap_or(0.0);
+                        let collateral_asset = vault.get("collateral_asset").and_then(|v| v.as_str()).unwrap_or("?");
+                        let inverse_ratio = if ratio > 0.0 { 1.0 / ratio } else { 0.0 };
+                        println!(
+                            " - {}: {} ({:.2} Compass per 1 {}, backed by {}, 1 Compass = {:.8} {})",
+                            asset,
+                            amount_display,
+                            ratio,
+                            collateral_asset,
+                            Amount::new(collateral, collateral_asset),
+                            inverse_ratio,
+                            collateral_asset
+                        );
+                    }
+                    None => println!(" - {}: {}", asset, amount_display),
+                }
+            }
+        }
+        _ => println!(" (No assets found on blockchain)"),
+    }
+    if let Some(nonce) = info.get("nonce").and_then(|n| n.as_u64()) {
+        println!("Nonce: {}", nonce);
+    }
+}
+
+/// Signed transfer, same path as `compass transfer`
+pub async fn transfer(client: &RpcClient, session: &Session) {
+    let Some(keypair) = signer(session, "make transfers") else {
+        pause();
+        return;
+    };
+
+    println!("\n--- Transfer Funds ---");
+    let book = AddressBook::load(ADDRESS_BOOK_PATH);
+    let input = prompt("Recipient (address or contact label): ");
+    if input.is_empty() {
+        pause();
+        return;
+    }
+    let (to, check) = resolve_recipient(&book, &input);
+    if let RecipientCheck::Known(contact) = &check {
+        println!("Contact '{}': {}", contact.label, contact.address);
+    }
+    // Anything the address book doesn't vouch for defaults to "no"
+    let warning = recipient_warning(&input, &check);
+    if let Some(w) = &warning {
+        println!("⚠️  {}", w);
+    }
+    let asset = prompt_or("Asset", "Compass");
+    let amount = match Amount::parse(&prompt("Amount: "), &asset) {
+        Ok(amount) if amount.units > 0 => amount,
+        Ok(_) => {
+            println!("❌ Invalid amount");
+            pause();
+            return;
+        }
+        Err(e) => {
+            println!("❌ {}", e);
+            pause();
+            return;
+        }
+    };
+    if !confirm(&format!("Send {} to {}?", amount, to), warning.is_none()) {
+        pause();
+        return;
+    }
+
+    println!("Submitting transfer...");
+    match submit_transfer(client, keypair, &session.user_name, &to, &asset, amount.units, None).await {
+        Ok(tx_hash) => println!("✅ Success! Tx Hash: {}", tx_hash),
+        Err(e) => println!("❌ Transaction failed: {}", e),
+    }
+    pause();
+}
+
+/// Mint Compass against a collateral deposit, same path as `compass mint`
+pub async fn mint(client: &RpcClient, session: &Session) {
+    let Some(keypair) = signer(session, "mint") else {
+        pause();
+        return;
+    };
+
+    println!("\n--- Mint Contract ---");
+    println!("Note: Oracle will auto-sign your mint request.");
+    let collateral_asset = prompt("Collateral Asset (LTC/SOL/etc): ").to_uppercase();
+    let tx_proof = prompt("TX Hash (Deposit Proof): ");
+    let collateral_amount = prompt_amount("Collateral Amount (e.g., 0.001): ");
+    let mint_amount = prompt_amount("Requested Compass Amount (e.g., 100.5): ");
+    let (Some(collateral_amount), Some(mint_amount)) = (collateral_amount, mint_amount) else {
+        println!("❌ Invalid amounts");
+        pause();
+        return;
+    };
+
+    let req = MintRequest {
+        vault_id: format!("Compass-{}", collateral_asset),
+        compass_asset: format!("Compass-{}", collateral_asset),
+        collateral_asset,
+        collateral_amount,
+        mint_amount,
+        owner: session.user_name.clone(),
+        tx_proof,
+        oracle_signature: String::new(),
+    };
+    match submit_mint(client, keypair, req).await {
+        Ok(tx_hash) => println!("✅ Mint Submitted! Tx: {}", tx_hash),
+        Err(e) => println!("❌ Mint Failed: {}", e),
+    }
+    pause();
+}
+
+/// Burn Compass and redeem collateral externally, same path as `compass burn`
+pub async fn burn(client: &RpcClient, session: &Session) {
+    let Some(keypair) = signer(session, "burn") else {
+        pause();
+        return;
+    };
+
+    println!("\n--- Redeem (Burn) ---");
+    let collateral_asset = prompt("Collateral Asset (LTC/SOL/etc): ").to_uppercase();
+    let Some(burn_amount) = prompt_amount("Compass Amount to burn: ") else {
+        println!("❌ Invalid amount");
+        pause();
+        return;
+    };
+    let destination_address = prompt(&format!("Destination {} Address: ", collateral_asset));
+    if destination_address.is_empty() {
+        println!("❌ Destination address required");
+        pause();
+        return;
+    }
+
+    let req = BurnRequest {
+        vault_id: format!("Compass-{}", collateral_asset),
+        compass_asset: format!("Compass-{}", collateral_asset),
+        collateral_asset,
+        burn_amount,
+        redeemer: session.user_name.clone(),
+        destination_address,
+    };
+    match submit_burn(client, keypair, req).await {
+        Ok(tx_hash) => println!("✅ Burn Submitted! Tx: {}", tx_hash),
+        Err(e) => println!("❌ Burn Failed: {}", e),
+    }
+    pause();
+}
+
+/// Deterministic deposit address for the user's vault
+pub fn vault_address(session: &Session) {
+    println!("\n=== Get Vault Deposit Address ===");
+    let collateral_asset = prompt("Collateral Asset (BTC/LTC/SOL): ").to_uppercase();
+
+    let vault_keys = crate::vault::VaultKeyManager::load_or_generate("vault_master.seed");
+    let (vault_address, derivation_path) = crate::vault::VaultManager::generate_vault_address(
+        &session.user_name,
+        &collateral_asset,
+        vault_keys.get_seed(),
+    );
+
+    println!("\n✓ Vault Address Generated");
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("Vault ID:        Compass:{}:{}", session.user_name, collateral_asset);
+    println!("Deposit Address: {}", vault_address);
+    println!("Derivation Path: {}", derivation_path);
+    println!("━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━━");
+    println!("\n📝 Instructions:");
+    println!("1. Send {} to the address above", collateral_asset);
+    println!("2. Wait for confirmations (BTC: 6+, LTC: 12+, SOL: 32+)");
+    println!("3. Use 'Mint Compass' with your TX hash");
+    println!("4. Oracle will verify and mint Compass tokens");
+    pause();
+}
+
+/// Block production stats and slot liveness for a validator
+pub async fn validator_dashboard(client: &RpcClient, session: &Session) {
+    let validator = match &session.identity {
+        Some(kp) => kp.public_key_hex(),
+        None => prompt("Validator public key: "),
+    };
+    println!("\n=== Validator Dashboard ===");
+
+    let stats = client
+        .call_method::<_, serde_json::Value>("getValidatorStats", serde_json::json!({ "validator": validator }))
+        .await;
+    match stats {
+        Ok(stats) => {
+            let field = |name: &str| stats.get(name).and_then(|v| v.as_u64()).unwrap_or(0);
+            println!("Blocks Produced: {}", field("blocks_produced"));
+            println!("COMPUTE Earned:  {}", Amount::new(field("compute_earned"), "COMPUTE").to_decimal_string());
+            println!("Uptime:          {}h approx", field("uptime_hours"));
+            println!("Avg Block Time:  {:.2}s", field("avg_block_time_ms") as f64 / 1000.0);
+        }
+        Err(e) => println!("❌ Could not fetch stats: {}", e),
+    }
+
+    let liveness = client
+        .call_method::<_, serde_json::Value>("getValidatorLiveness", serde_json::json!({ "validator": validator }))
+        .await;
+    if let Ok(liveness) = liveness {
+        if let Some(v) = liveness["validators"].get(0) {
+            println!(
+                "Slot Liveness:   {:.1}% ({} of {} slots, {} skipped)",
+                v["score"].as_f64().unwrap_or(1.0) * 100.0,
+                v["slots_produced"].as_u64().unwrap_or(0),
+                v["slots_expected"].as_u64().unwrap_or(0),
+                v["slots_skipped"].as_u64().unwrap_or(0)
+            );
+            if v["below_threshold"].as_bool().unwrap_or(false) {
+                println!("⚠️  Below the liveness alert threshold");
+            }
+        }
+    }
+    pause();
+}
+
+/// Collateralized AI compute job
+pub async fn submit_compute_job(client: &RpcClient) {
+    println!("\n🧠 Submit Compute Job");
+    let model = prompt_or("Model ID", "gpt-4o-mini");
+    let inputs = prompt("Input Text: ").into_bytes();
+    let Some(bid_amount) = prompt("Bid Amount (COMPASS): ").parse::<u64>().ok().filter(|b| *b > 0) else {
+        println!("❌ Bid amount must be > 0");
+        pause();
+        return;
+    };
+
+    let job_id = uuid::Uuid::new_v4().to_string();
+    println!("Submitting job {}...", job_id);
+    match client.submit_compute(job_id, model, inputs, 1000, bid_amount, "COMPASS".to_string()).await {
+        Ok(tx) => println!("✅ Job Submitted! Tx: {}", tx),
+        Err(e) => println!("❌ Error: {}", e),
+    }
+    pause();
+}
+
+/// Every model NFT persisted on the node
+pub async fn all_nfts(client: &RpcClient) {
+    match client.get_all_nfts().await {
+        Ok(nfts) => {
+            println!("\n🎨 --- ALL PERSISTED NFTS ({}) ---", nfts.len());
+            for nft in nfts {
+                println!("- [{}] {}", nft.minted_at, nft.token_id);
+                println!("  Name: {}", nft.name);
+                println!("  Owner: {}", nft.current_owner);
+                println!("  Accuracy: {:.2}%", nft.accuracy * 100.0);
+                println!("--------------------------------------------------");
+            }
+        }
+        Err(e) => println!("❌ Error fetching NFTs: {}", e),
+    }
+    pause();
+}
@@ -29,6 +29,15 @@ impl AssetManager {
         self.storage = Some(storage);
     }
 
+    /// Rebuild the manager from its per-asset entries, writing through to `storage`
+    pub fn load(storage: Arc<Storage>) -> Self {
+        Self {
+            registry: storage.get_l2_assets(),
+            ownership: storage.get_l2_asset_owners(),
+            storage: Some(storage),
+        }
+    }
+
     /// Register a mint event (Called after checks pass)
     pub fn register_mint(&mut self, nft: ModelNFT, owner: String) {
         let id = nft.token_id.clone();
@@ -37,9 +46,9 @@ impl AssetManager {
         self.registry.insert(id.clone(), nft.clone());
 
         // Add to ownership
-        self.ownership.entry(owner).or_default().push(id);
+        self.ownership.entry(owner.clone()).or_default().push(id);
         
-        // Persist individual NFT to Sled (in addition to AssetManager blob)
+        // Persist individual NFT to Sled (in addition to its Layer 2 entry)
         if let Some(db) = &self.storage {
             if let Err(e) = db.save_model_nft(&nft) {
                 tracing::error!("Failed to save Model NFT to Sled: {}", e);
@@ -48,15 +57,19 @@ impl AssetManager {
             }
         }
         
-        // Persist entire AssetManager state
-        self.persist();
+        self.persist(&[&nft], &[&owner]);
     }
     
-    fn persist(&self) {
+    /// Write through the entries of the given assets and owners only
+    fn persist(&self, assets: &[&ModelNFT], owners: &[&str]) {
         if let Some(db) = &self.storage {
-            // Save entire manager as blob "l2:assets" for simple v1.3 persistence
-            // This matches Layer2State::load_from_db logic
-            let _ = db.put("l2:assets", self);
+            let holdings: Vec<(&str, &[String])> = owners
+                .iter()
+                .map(|owner| (*owner, self.ownership.get(*owner).map(Vec::as_slice).unwrap_or_default()))
+                .collect();
+            if let Err(e) = db.save_l2_asset_entries(assets, &holdings) {
+                tracing::error!("Failed to persist Layer 2 assets: {}", e);
+            }
         }
     }
 
@@ -83,8 +96,7 @@ impl AssetManager {
         // 2. Add to new owner
         self.ownership.entry(to.to_string()).or_default().push(token_id.to_string());
         
-        // Persist State
-        self.persist();
+        self.persist(&[], &[_from, to]);
 
         Ok(())
     }
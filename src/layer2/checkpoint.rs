@@ -0,0 +1,138 @@
+//! Layer 2 checkpoints.
+//!
+//! Layer 2 state (tokenomics, model NFTs, collateral stakes) is kept in Sled
+//! blobs outside the block pipeline. The leader periodically commits a hash of
+//! each component in a leader-signed `L2Checkpoint` block and keeps a snapshot of
+//! the state it hashed. The chain then records what Layer 2 looked like at that
+//! height: auditors and followers holding their own copy can compare against it,
+//! and an operator can restore the snapshot of any checkpoint.
+
+use serde::{Deserialize, Serialize};
+
+use super::{assets::AssetManager, collateral::CollateralManager, economics::TokenomicsEngine, Layer2State};
+use crate::encoding::{canonical_bytes, hash_to_hex, sha256};
+use crate::error::CompassError;
+
+/// Hashes of the Layer 2 components
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct L2Commitment {
+    pub economics_hash: String,
+    pub assets_hash: String,
+    pub collateral_hash: String,
+}
+
+impl L2Commitment {
+    /// The one hash a checkpoint is known by
+    pub fn state_hash(&self) -> String {
+        hash_to_hex(&sha256(format!("{}:{}:{}", self.economics_hash, self.assets_hash, self.collateral_hash)))
+    }
+}
+
+/// A checkpoint as recorded when its block executed
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct L2CheckpointRecord {
+    pub sequence: u64,
+    pub height: u64,
+    pub state_hash: String,
+    pub commitment: L2Commitment,
+}
+
+fn hash_of<T: Serialize + ?Sized>(value: &T) -> Result<String, CompassError> {
+    Ok(hash_to_hex(&sha256(canonical_bytes(value)?)))
+}
+
+/// Maps are hashed in key order so equal states hash equally on every node
+fn commit(economics: &TokenomicsEngine, assets: &AssetManager, collateral: &CollateralManager) -> Result<L2Commitment, CompassError> {
+    let mut registry: Vec<_> = assets.registry.iter().collect();
+    registry.sort_by(|a, b| a.0.cmp(b.0));
+    let mut ownership: Vec<(&String, Vec<&String>)> = assets
+        .ownership
+        .iter()
+        .map(|(owner, ids)| {
+            let mut ids: Vec<&String> = ids.iter().collect();
+            ids.sort();
+            (owner, ids)
+        })
+        .collect();
+    ownership.sort_by(|a, b| a.0.cmp(b.0));
+    let mut stakes: Vec<_> = collateral.stakes.iter().collect();
+    stakes.sort();
+
+    Ok(L2Commitment {
+        economics_hash: hash_of(economics)?,
+        assets_hash: hash_of(&(registry, ownership))?,
+        collateral_hash: hash_of(&(stakes, collateral.insurance_fund))?,
+    })
+}
+
+impl Layer2State {
+    /// Hashes of the current state
+    pub fn commitment(&self) -> Result<L2Commitment, CompassError> {
+        commit(&self.economics, &self.assets, &self.collateral)
+    }
+
+    /// Hash the current state and keep a snapshot of it under `l2_snapshot:<state hash>`
+    pub fn snapshot(&self) -> Result<L2Commitment, CompassError> {
+        let commitment = self.commitment()?;
+        if let Some(db) = &self.storage {
+            db.put(
+                &format!("l2_snapshot:{}", commitment.state_hash()),
+                &(&self.economics, &self.assets, &self.collateral),
+            )?;
+        }
+        Ok(commitment)
+    }
+
+    /// Replace the current state with the snapshot taken for `checkpoint`, once it
+    /// hashes to what the chain recorded
+    pub fn rollback_to(&mut self, checkpoint: &L2CheckpointRecord) -> Result<(), CompassError> {
+        let db = self.storage.clone().ok_or_else(|| CompassError::InvalidState("Layer 2 has no storage".to_string()))?;
+        let (economics, mut assets, mut collateral): (TokenomicsEngine, AssetManager, CollateralManager) = db
+            .get(&format!("l2_snapshot:{}", checkpoint.state_hash))?
+            .ok_or_else(|| CompassError::InvalidState(format!("no snapshot of checkpoint #{}", checkpoint.sequence)))?;
+        if commit(&economics, &assets, &collateral)?.state_hash() != checkpoint.state_hash {
+            return Err(CompassError::InvalidState(format!("snapshot of checkpoint #{} does not match its hash", checkpoint.sequence)));
+        }
+        // Unbonding queues are not checkpointed; pending withdrawals stay pending
+        collateral.unbonding = std::mem::take(&mut self.collateral.unbonding);
+        db.replace_l2_entries(&assets, &collateral)?;
+        assets.set_storage(db.clone());
+        collateral.set_storage(db);
+        self.economics = economics;
+        self.assets = assets;
+        self.collateral = collateral;
+        self.save("").map_err(|e| CompassError::DatabaseError(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+
+    #[test]
+    fn test_checkpoint_hash_is_order_free_and_restores() {
+        let storage = Arc::new(crate::storage::Storage::new_in_memory().unwrap());
+        let mut l2 = Layer2State::new(Some(storage.clone()));
+        l2.collateral.stake("alice".to_string(), 10);
+        l2.collateral.stake("bob".to_string(), 20);
+
+        let mut other = Layer2State::new(None);
+        other.collateral.stake("bob".to_string(), 20);
+        other.collateral.stake("alice".to_string(), 10);
+        assert_eq!(other.commitment().unwrap(), l2.commitment().unwrap());
+
+        let commitment = l2.snapshot().unwrap();
+        let record = L2CheckpointRecord { sequence: 1, height: 5, state_hash: commitment.state_hash(), commitment };
+        l2.collateral.stake("mallory".to_string(), 1_000);
+        assert_ne!(l2.commitment().unwrap().state_hash(), record.state_hash);
+
+        l2.rollback_to(&record).unwrap();
+        assert_eq!(l2.commitment().unwrap().state_hash(), record.state_hash);
+        assert!(!l2.collateral.stakes.contains_key("mallory"));
+        assert!(!storage.get_l2_stakes().contains_key("mallory"));
+
+        let unknown = L2CheckpointRecord { state_hash: "00".repeat(32), ..record };
+        assert!(l2.rollback_to(&unknown).is_err());
+    }
+}
@@ -1,12 +1,57 @@
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use std::sync::Arc;
+use crate::storage::Storage;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+/// Slots unstaked collateral waits before it can be withdrawn, unless
+/// `[consensus] unbonding_slots` says otherwise (three default epochs)
+pub const DEFAULT_UNBONDING_SLOTS: u64 = 3 * crate::leader_schedule::DEFAULT_SLOTS_PER_EPOCH;
+
+/// Unstaked collateral on its way out. It no longer counts as stake but can
+/// still be slashed until it is withdrawn.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Unbonding {
+    pub amount: u64,
+    /// First slot it can be withdrawn at
+    pub release_slot: u64,
+}
+
+/// Asset withdrawn collateral is paid out in
+pub const COLLATERAL_ASSET: &str = "Compass";
+
+/// Message an entity signs to withdraw its unbonded collateral. It names no
+/// amount, only what has matured is paid, and it spends the entity's account
+/// `nonce`, so it cannot be replayed.
+pub fn withdraw_message(entity: &str, nonce: u64) -> String {
+    format!("COMPASS_WITHDRAW_UNBONDED:{}:{}", entity, nonce)
+}
+
+/// Collateral in `queue` that has finished unbonding by `slot`
+pub fn matured(queue: &[Unbonding], slot: u64) -> u64 {
+    queue.iter().filter(|u| u.release_slot <= slot).map(|u| u.amount).sum()
+}
+
+/// Take every withdrawal in `queue` that has finished unbonding by `slot` out of it
+pub fn release_matured(queue: &mut Vec<Unbonding>, slot: u64) -> Vec<Unbonding> {
+    let (released, waiting): (Vec<_>, Vec<_>) = queue.drain(..).partition(|u| u.release_slot <= slot);
+    *queue = waiting;
+    released
+}
+
+#[derive(Clone, Serialize, Deserialize)]
 pub struct CollateralManager {
     // Map: Entity ID (Validator/Worker/Model) -> Staked Amount
     pub stakes: HashMap<String, u64>,
     // Insurance Fund Balance
     pub insurance_fund: u128,
+    // Map: Entity ID -> withdrawals waiting out the unbonding period, oldest first.
+    // Kept in its own entries, outside the serialized manager and checkpoints.
+    #[serde(skip)]
+    pub unbonding: HashMap<String, Vec<Unbonding>>,
+
+    // Write-through target for each stake change
+    #[serde(skip)]
+    pub storage: Option<Arc<Storage>>,
 }
 
 impl CollateralManager {
@@ -14,34 +59,122 @@ impl CollateralManager {
         Self {
             stakes: HashMap::new(),
             insurance_fund: 0,
+            unbonding: HashMap::new(),
+            storage: None,
+        }
+    }
+
+    /// Rebuild the manager from its per-stake entries, writing through to `storage`
+    pub fn load(storage: Arc<Storage>) -> Self {
+        Self {
+            stakes: storage.get_l2_stakes(),
+            insurance_fund: storage.get_l2_insurance_fund().unwrap_or_else(|e| {
+                tracing::error!("Failed to load insurance fund: {}", e);
+                0
+            }),
+            unbonding: storage.get_l2_unbonding(),
+            storage: Some(storage),
+        }
+    }
+
+    pub fn set_storage(&mut self, storage: Arc<Storage>) {
+        self.storage = Some(storage);
+    }
+
+    /// Write through `entity`'s stake, its unbonding queue and the insurance fund
+    fn persist(&self, entity: &str) {
+        if let Some(db) = &self.storage {
+            let amount = self.stakes.get(entity).copied().unwrap_or(0);
+            let unbonding = self.unbonding.get(entity).map(Vec::as_slice).unwrap_or_default();
+            if let Err(e) = db.save_l2_stake(entity, amount, unbonding, self.insurance_fund) {
+                tracing::error!("Failed to persist stake of {}: {}", entity, e);
+            }
         }
     }
 
     pub fn stake(&mut self, entity: String, amount: u64) {
-        *self.stakes.entry(entity).or_insert(0) += amount;
+        *self.stakes.entry(entity.clone()).or_insert(0) += amount;
+        self.persist(&entity);
     }
 
-    pub fn unstake(&mut self, entity: &str, amount: u64) -> Result<(), String> {
-        if let Some(balance) = self.stakes.get_mut(entity) {
-            if *balance >= amount {
-                *balance -= amount;
-                return Ok(());
+    /// Move `amount` of `entity`'s stake into its unbonding queue, withdrawable
+    /// from `release_slot`
+    pub fn unstake(&mut self, entity: &str, amount: u64, release_slot: u64) -> Result<(), String> {
+        if amount == 0 {
+            return Err("Unstake amount must be positive".to_string());
+        }
+        match self.stakes.get_mut(entity) {
+            Some(balance) if *balance >= amount => *balance -= amount,
+            _ => return Err("Insufficient stake".to_string()),
+        }
+        self.unbonding.entry(entity.to_string()).or_default().push(Unbonding { amount, release_slot });
+        self.persist(entity);
+        Ok(())
+    }
+
+    /// Collateral of `entity` still unbonding, matured or not
+    pub fn unbonding_total(&self, entity: &str) -> u64 {
+        self.unbonding.get(entity).map_or(0, |queue| queue.iter().map(|u| u.amount).sum())
+    }
+
+    /// Collateral of `entity` that has finished unbonding by `slot`
+    pub fn withdrawable(&self, entity: &str, slot: u64) -> u64 {
+        self.unbonding.get(entity).map_or(0, |queue| matured(queue, slot))
+    }
+
+    /// Re-read `entity`'s unbonding queue after an `UnbondedWithdrawal` block
+    /// released part of it in storage
+    pub fn reload_unbonding(&mut self, entity: &str) {
+        let Some(db) = &self.storage else { return };
+        match db.get_l2_unbonding_of(entity) {
+            Ok(queue) if queue.is_empty() => {
+                self.unbonding.remove(entity);
             }
+            Ok(queue) => {
+                self.unbonding.insert(entity.to_string(), queue);
+            }
+            Err(e) => tracing::error!("Failed to reload unbonding of {}: {}", entity, e),
         }
-        Err("Insufficient stake".to_string())
     }
 
+    /// Slash `entity`'s stake first, then its unbonding withdrawals, newest first
     pub fn slash(&mut self, entity: &str, amount: u64) -> Result<u64, String> {
+        if !self.stakes.contains_key(entity) && !self.unbonding.contains_key(entity) {
+            return Err("Entity has no stake".to_string());
+        }
+        let mut slash_amount = 0;
         if let Some(balance) = self.stakes.get_mut(entity) {
-            let slash_amount = std::cmp::min(*balance, amount);
+            slash_amount = std::cmp::min(*balance, amount);
             *balance -= slash_amount;
-            self.insurance_fund += slash_amount as u128; // Slashing goes to insurance
-            return Ok(slash_amount);
         }
-        Err("Entity has no stake".to_string())
+        if let Some(queue) = self.unbonding.get_mut(entity) {
+            for pending in queue.iter_mut().rev() {
+                let take = std::cmp::min(pending.amount, amount - slash_amount);
+                pending.amount -= take;
+                slash_amount += take;
+            }
+            queue.retain(|u| u.amount > 0);
+            if queue.is_empty() {
+                self.unbonding.remove(entity);
+            }
+        }
+        self.insurance_fund += slash_amount as u128; // Slashing goes to insurance
+        self.persist(entity);
+        Ok(slash_amount)
+    }
+
+    /// Pay `amount` out of the insurance fund
+    pub fn draw_insurance(&mut self, amount: u128) {
+        self.insurance_fund = self.insurance_fund.saturating_sub(amount);
+        if let Some(db) = &self.storage {
+            if let Err(e) = db.put("l2:insurance_fund", &self.insurance_fund) {
+                tracing::error!("Failed to persist insurance fund: {}", e);
+            }
+        }
     }
 
     pub fn reward(&mut self, entity: String, amount: u64) {
-        *self.stakes.entry(entity).or_insert(0) += amount;
+        *self.stakes.entry(entity.clone()).or_insert(0) += amount;
+        self.persist(&entity);
     }
 }
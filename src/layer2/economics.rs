@@ -38,3 +38,98 @@ impl TokenomicsEngine {
         self.total_supply += amount as u128;
     }
 }
+
+/// Asset block rewards are minted in
+pub const BLOCK_REWARD_ASSET: &str = "Compass";
+
+/// `[consensus.emission]`: how new tokens enter circulation. Rewards halve every
+/// `halving_interval_epochs` and stop once their cap is reached. Every validator
+/// must use the same values, since block rewards are minted when a PoH block executes.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq)]
+pub struct EmissionSchedule {
+    /// Compass minted to the proposer of each PoH block in the first era (0 disables block rewards)
+    #[serde(default)]
+    pub initial_block_reward: u64,
+    /// Epochs per era; each era halves block and PoUW rewards (0 never halves)
+    #[serde(default = "default_halving_interval_epochs")]
+    pub halving_interval_epochs: u64,
+    /// Most Compass block rewards will ever mint
+    #[serde(default = "default_max_block_emission")]
+    pub max_block_emission: u64,
+    /// Most COMPUTE PoUW rewards will ever mint (0 leaves them uncapped and unhalved)
+    #[serde(default)]
+    pub max_pouw_emission: u64,
+}
+
+fn default_halving_interval_epochs() -> u64 {
+    1_000
+}
+
+fn default_max_block_emission() -> u64 {
+    1_000_000_000
+}
+
+impl Default for EmissionSchedule {
+    fn default() -> Self {
+        Self {
+            initial_block_reward: 0,
+            halving_interval_epochs: default_halving_interval_epochs(),
+            max_block_emission: default_max_block_emission(),
+            max_pouw_emission: 0,
+        }
+    }
+}
+
+impl EmissionSchedule {
+    /// Halvings that have happened by `epoch`
+    pub fn era(&self, epoch: u64) -> u64 {
+        epoch.checked_div(self.halving_interval_epochs).unwrap_or(0)
+    }
+
+    fn halved(&self, amount: u64, epoch: u64) -> u64 {
+        amount.checked_shr(self.era(epoch).min(u32::MAX as u64) as u32).unwrap_or(0)
+    }
+
+    /// Reward for a PoH block proposed in `epoch`, given what block rewards have
+    /// minted so far
+    pub fn block_reward(&self, epoch: u64, emitted: u64) -> u64 {
+        self.halved(self.initial_block_reward, epoch)
+            .min(self.max_block_emission.saturating_sub(emitted))
+    }
+
+    /// What a PoUW reward of `reward` (as the curve priced it) pays in `epoch`,
+    /// given what PoUW rewards have minted so far
+    pub fn pouw_reward(&self, reward: u64, epoch: u64, emitted: u64) -> u64 {
+        if self.max_pouw_emission == 0 {
+            return reward;
+        }
+        self.halved(reward, epoch).min(self.max_pouw_emission.saturating_sub(emitted))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rewards_halve_per_era_and_stop_at_the_cap() {
+        let schedule = EmissionSchedule {
+            initial_block_reward: 100,
+            halving_interval_epochs: 10,
+            max_block_emission: 1_000,
+            max_pouw_emission: 500,
+        };
+        assert_eq!(schedule.block_reward(0, 0), 100);
+        assert_eq!(schedule.block_reward(9, 0), 100);
+        assert_eq!(schedule.block_reward(10, 0), 50);
+        assert_eq!(schedule.block_reward(35, 0), 12);
+        assert_eq!(schedule.block_reward(10_000, 0), 0);
+        assert_eq!(schedule.block_reward(0, 960), 40);
+        assert_eq!(schedule.block_reward(0, 1_000), 0);
+
+        assert_eq!(schedule.pouw_reward(40, 10, 0), 20);
+        assert_eq!(schedule.pouw_reward(40, 0, 490), 10);
+        let uncapped = EmissionSchedule::default();
+        assert_eq!((uncapped.block_reward(0, 0), uncapped.pouw_reward(40, 5_000, u64::MAX)), (0, 40));
+    }
+}
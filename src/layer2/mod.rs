@@ -1,6 +1,7 @@
 pub mod economics;
 pub mod assets;
 pub mod collateral;
+pub mod checkpoint;
 
 use std::sync::Arc;
 use crate::storage::Storage;
@@ -12,6 +13,10 @@ pub struct Layer2State {
     pub economics: economics::TokenomicsEngine,
     pub assets: assets::AssetManager,
     pub collateral: collateral::CollateralManager,
+    /// Treasury's share of slashed collateral (set from consensus config)
+    pub treasury: crate::treasury::TreasuryConfig,
+    /// Slots unstaked collateral waits before it can be withdrawn (set from consensus config)
+    pub unbonding_slots: u64,
     
     // DB Access (Skipped during Component serialization)
     storage: Option<Arc<Storage>>,
@@ -23,6 +28,8 @@ impl Layer2State {
             economics: economics::TokenomicsEngine::new(),
             assets: assets::AssetManager::new(),
             collateral: collateral::CollateralManager::new(),
+            treasury: Default::default(),
+            unbonding_slots: collateral::DEFAULT_UNBONDING_SLOTS,
             storage: storage.clone(),
         };
         
@@ -35,7 +42,7 @@ impl Layer2State {
         state
     }
 
-    fn load_from_db(&mut self, db: &Storage) {
+    fn load_from_db(&mut self, db: &Arc<Storage>) {
         println!("Persistence: Loading Layer 2 State from DB...");
         
         // 1. Load Custom "Chunks" if we saved them that way
@@ -44,37 +51,153 @@ impl Layer2State {
             self.economics = eco;
         }
 
-        // Collateral
-        if let Ok(Some(col)) = db.get::<collateral::CollateralManager>("l2:collateral") {
-            self.collateral = col;
+        // Assets and collateral are kept one entry per asset, owner and stake.
+        // Databases from before that still hold each manager as a single blob:
+        // split those into entries once, then drop the blob.
+        let legacy_assets = db.get::<assets::AssetManager>("l2:assets").ok().flatten();
+        let legacy_collateral = db.get::<collateral::CollateralManager>("l2:collateral").ok().flatten();
+        if legacy_assets.is_some() || legacy_collateral.is_some() {
+            let assets = legacy_assets.unwrap_or_else(|| assets::AssetManager::load(db.clone()));
+            let collateral = legacy_collateral.unwrap_or_else(|| collateral::CollateralManager::load(db.clone()));
+            match db.replace_l2_entries(&assets, &collateral) {
+                Ok(()) => {
+                    println!("Persistence: Migrated Layer 2 assets and collateral to keyed entries");
+                    let _ = db.delete("l2:assets");
+                    let _ = db.delete("l2:collateral");
+                }
+                Err(e) => println!("Persistence: Failed to migrate Layer 2 blobs: {}", e),
+            }
         }
 
-        // Assets (Special Handling: Load Registry)
-        // For now, simpler to load entire AssetManager Blob if we save it as blob
-        // But plan was Write-Through.
-        // Let's assume for v1.3 Phase 2 Step 1 we save AssetManager as blob "l2:assets"
-        // Granular updates come later or via internal Storage usage.
-        if let Ok(Some(assets)) = db.get::<assets::AssetManager>("l2:assets") {
-            self.assets = assets;
+        // Components write their own entries from here on
+        self.assets = assets::AssetManager::load(db.clone());
+        self.collateral = collateral::CollateralManager::load(db.clone());
+    }
+
+    /// Start unbonding `amount` of `entity`'s stake at `slot`; it can be withdrawn
+    /// once `unbonding_slots` have passed
+    pub fn unstake(&mut self, entity: &str, amount: u64, slot: u64) -> Result<u64, String> {
+        let release_slot = slot.saturating_add(self.unbonding_slots);
+        self.collateral.unstake(entity, amount, release_slot)?;
+        Ok(release_slot)
+    }
+
+    /// Slash an entity's collateral stake and record it in the audit log. The
+    /// treasury's share goes to the treasury account, the rest to the insurance fund.
+    pub fn slash(&mut self, actor: &str, entity: &str, amount: u64, reason: &str) -> Result<u64, String> {
+        let slashed = self.collateral.slash(entity, amount)?;
+        let share = self.treasury.slash_share(slashed);
+        if let (Some(db), true) = (&self.storage, share > 0) {
+            match db.writer().credit(crate::treasury::TREASURY_ACCOUNT, crate::treasury::TREASURY_ASSET, share) {
+                Ok(()) => self.collateral.draw_insurance(share as u128),
+                Err(e) => println!("Treasury: failed to credit slash share of {}: {}", entity, e),
+            }
         }
-        
-        // Inject Storage for Write-Through components
-        if let Some(db_arc) = &self.storage {
-             self.assets.set_storage(db_arc.clone());
+        if let Some(db) = &self.storage {
+            if let Err(e) = db.append_audit_entry(
+                actor,
+                crate::audit::AuditAction::Slash,
+                format!("entity={} amount={} reason={}", entity, slashed, reason),
+            ) {
+                println!("Audit: failed to record slash of {}: {}", entity, e);
+            }
         }
+        Ok(slashed)
     }
 
     pub fn save(&self, _path: &str) -> Result<(), std::io::Error> {
         // Deprecated JSON path usage.
         // We trigger DB save.
         if let Some(db) = &self.storage {
-            // Save Components
+            // Assets and collateral already wrote through on each change
             let _ = db.put("l2:economics", &self.economics);
-            let _ = db.put("l2:collateral", &self.collateral);
-            let _ = db.put("l2:assets", &self.assets); // TODO: Make granular
             
             let _ = db.flush();
         }
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layer3::model_nft::{ModelNFT, ModelStats};
+
+    #[test]
+    fn test_legacy_blobs_migrate_to_keyed_entries_that_write_through() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let stats = ModelStats {
+            accuracy: 0.6,
+            win_rate: 0.55,
+            total_predictions: 10,
+            profitable_predictions: 6,
+            total_profit: 0,
+            training_samples: 100,
+            training_epochs: 1,
+            final_loss: 0.1,
+            training_duration: 1,
+            data_hash: String::new(),
+        };
+        let nft = ModelNFT::from_job("job", "BTC", "alice".to_string(), &stats);
+        let mut assets = assets::AssetManager::new();
+        assets.register_mint(nft.clone(), "alice".to_string());
+        let mut collateral = collateral::CollateralManager::new();
+        collateral.stake("alice".to_string(), 50);
+        storage.put("l2:assets", &assets).unwrap();
+        storage.put("l2:collateral", &collateral).unwrap();
+
+        let mut l2 = Layer2State::new(Some(storage.clone()));
+        assert!(storage.get::<collateral::CollateralManager>("l2:collateral").unwrap().is_none());
+        assert_eq!(storage.get_l2_stakes().get("alice"), Some(&50));
+        assert_eq!(l2.assets.ownership.get("alice"), Some(&vec![nft.token_id.clone()]));
+
+        // Each change lands in its own entry without a save
+        l2.assets.transfer(&nft.token_id, "alice", "bob").unwrap();
+        l2.collateral.stake("bob".to_string(), 30);
+        l2.collateral.slash("alice", 20).unwrap();
+        let reloaded = Layer2State::new(Some(storage.clone()));
+        assert_eq!(reloaded.assets.ownership.get("bob"), Some(&vec![nft.token_id.clone()]));
+        assert!(!reloaded.assets.ownership.contains_key("alice"));
+        assert!(reloaded.assets.registry.contains_key(&nft.token_id));
+        assert_eq!(reloaded.collateral.stakes, l2.collateral.stakes);
+        assert_eq!(reloaded.collateral.insurance_fund, 20);
+        assert_eq!(storage.get_collateral_stakes().unwrap().get("bob"), Some(&30));
+    }
+
+    #[test]
+    fn test_unstaked_collateral_unbonds_before_withdrawal() {
+        let storage = Arc::new(Storage::new_in_memory().unwrap());
+        let mut l2 = Layer2State::new(Some(storage.clone()));
+        l2.unbonding_slots = 10;
+        l2.collateral.stake("alice".to_string(), 100);
+
+        // Partial unstakes queue separately; more than is staked is refused
+        assert_eq!(l2.unstake("alice", 30, 5), Ok(15));
+        assert_eq!(l2.unstake("alice", 20, 8), Ok(18));
+        assert!(l2.unstake("alice", 60, 9).is_err());
+        assert_eq!((l2.collateral.stakes["alice"], l2.collateral.unbonding_total("alice")), (50, 50));
+
+        // Unbonding collateral can still be slashed, newest withdrawal first
+        assert_eq!(l2.collateral.slash("alice", 60), Ok(60));
+        let expected = vec![
+            collateral::Unbonding { amount: 30, release_slot: 15 },
+            collateral::Unbonding { amount: 10, release_slot: 18 },
+        ];
+        assert_eq!(l2.collateral.unbonding["alice"], expected);
+
+        assert_eq!(l2.collateral.withdrawable("alice", 14), 0);
+        let reloaded = Layer2State::new(Some(storage.clone()));
+        assert_eq!(reloaded.collateral.unbonding["alice"], expected);
+        assert_eq!(l2.collateral.withdrawable("alice", 17), 30);
+
+        // Withdrawals are released in storage by their block, then reloaded
+        assert_eq!(storage.release_unbonded("alice", 15).unwrap(), 30);
+        l2.collateral.reload_unbonding("alice");
+        assert_eq!(l2.collateral.unbonding["alice"], expected[1..]);
+        assert_eq!(storage.release_unbonded("alice", 18).unwrap(), 10);
+        l2.collateral.reload_unbonding("alice");
+        assert_eq!(l2.collateral.unbonding_total("alice"), 0);
+        assert!(storage.get_l2_unbonding().is_empty());
+        assert_eq!(storage.get_l2_stakes()["alice"], 0);
+    }
+}
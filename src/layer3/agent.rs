@@ -22,9 +22,8 @@ pub async fn run_continuous_cycle(
     _duration_seconds: u64,
 ) -> Result<(), String> {
     
-    // Select Mission
-    let mut rng = rand::thread_rng();
-    let mission_idx = rng.gen_range(0..3);
+    // Select Mission (ThreadRng is not Send, so don't keep it across awaits)
+    let mission_idx = rand::thread_rng().gen_range(0..3);
     let mission = match mission_idx {
         0 => MissionType::EthGasOpt,
         1 => MissionType::SolTendency,
@@ -181,35 +180,13 @@ pub async fn run_continuous_cycle(
     ];
     
     use candle_core::{Tensor, Device};
-    use super::brain::{SimpleBrain, OnnxBrain};
+    use super::brain::SimpleBrain;
     
     // Check for ONNX Model
     let onnx_path = "model.onnx";
     let output_vec = if std::path::Path::new(onnx_path).exists() {
         println!("   🧠 Loading ONNX Model at '{}'...", onnx_path);
-        match OnnxBrain::new(onnx_path) {
-            Ok(mut brain) => {
-                let input_tensor = Tensor::new(inputs.clone(), &Device::Cpu).map_err(|e| format!("{}", e))?
-                    .unsqueeze(0).map_err(|e| format!("{}", e))?;
-                    
-                println!("   ⚡ Running ONNX Inference...");
-                match brain.forward(&input_tensor) {
-                    Ok(out) => {
-                         out.squeeze(0).map_err(|e| format!("{}", e))?.to_vec1::<f32>().map_err(|e| format!("{}", e))?
-                    },
-                    Err(e) => {
-                        println!("   ⚠️ ONNX Inference Failed (Fallback): {}", e);
-                         // Fallback logic duplicated below or simple panic? 
-                         // Check below fallback logic
-                         vec![0.5, 0.5, 0.5] // Graceful degradation
-                    }
-                }
-            },
-            Err(e) => {
-                 println!("   ⚠️ ONNX Load Failed (Fallback): {}", e);
-                 vec![0.5, 0.5, 0.5]
-            }
-        }
+        run_onnx_brain(onnx_path, &inputs)?
     } else {
         println!("   🧠 No ONNX found. Initializing Random Neural Weights (7x64x64x3)...");
         let brain = SimpleBrain::new_random(7, 3).map_err(|e| format!("Brain Init Failed: {}", e))?;
@@ -285,3 +262,38 @@ pub async fn run_continuous_cycle(
 
     Ok(())
 }
+
+/// Run the agent's inputs through an ONNX model, falling back to neutral outputs on failure
+#[cfg(feature = "onnx")]
+fn run_onnx_brain(onnx_path: &str, inputs: &[f32]) -> Result<Vec<f32>, String> {
+    use candle_core::{Tensor, Device};
+    use super::brain::OnnxBrain;
+
+    Ok(match OnnxBrain::new(onnx_path) {
+        Ok(mut brain) => {
+            let input_tensor = Tensor::new(inputs.to_vec(), &Device::Cpu).map_err(|e| format!("{}", e))?
+                .unsqueeze(0).map_err(|e| format!("{}", e))?;
+                
+            println!("   ⚡ Running ONNX Inference...");
+            match brain.forward(&input_tensor) {
+                Ok(out) => {
+                     out.squeeze(0).map_err(|e| format!("{}", e))?.to_vec1::<f32>().map_err(|e| format!("{}", e))?
+                },
+                Err(e) => {
+                    println!("   ⚠️ ONNX Inference Failed (Fallback): {}", e);
+                     vec![0.5, 0.5, 0.5] // Graceful degradation
+                }
+            }
+        },
+        Err(e) => {
+             println!("   ⚠️ ONNX Load Failed (Fallback): {}", e);
+             vec![0.5, 0.5, 0.5]
+        }
+    })
+}
+
+#[cfg(not(feature = "onnx"))]
+fn run_onnx_brain(_onnx_path: &str, _inputs: &[f32]) -> Result<Vec<f32>, String> {
+    println!("   ⚠️ Built without ONNX support (Fallback)");
+    Ok(vec![0.5, 0.5, 0.5])
+}
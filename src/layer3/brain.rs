@@ -61,17 +61,23 @@ impl SimpleBrain {
 // ==============================================================================
 //  ORT (ONNX Runtime) Implementation
 // ==============================================================================
+#[cfg(feature = "onnx")]
 use ort::session::{Session, builder::GraphOptimizationLevel};
+#[cfg(feature = "onnx")]
 use ort::value::Value;
+#[cfg(feature = "onnx")]
 use std::sync::Once;
 
+#[cfg(feature = "onnx")]
 static INIT: Once = Once::new();
 
 /// Real ONNX Brain using 'ort' crate
+#[cfg(feature = "onnx")]
 pub struct OnnxBrain {
     session: Session,
 }
 
+#[cfg(feature = "onnx")]
 impl OnnxBrain {
     pub fn new(path: &str) -> Result<Self> {
         // Initialize ORT environment once
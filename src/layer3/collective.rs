@@ -359,3 +359,292 @@ impl ModelPool {
         self.vault_balance += amount;
     }
 }
+
+// --- Pool governance ---
+//
+// Contributors change a pool's parameters by proposal and stake-weighted vote.
+// A proposal passes as soon as more than half the pool's stake has voted for it
+// and is rejected once half has voted against; if neither happens within
+// `POOL_VOTING_PERIOD_SECS` it lapses. Weights are contributors' stakes at the
+// time of counting, so stake that leaves the pool takes its votes with it.
+// Governance is stored beside the pool (`pool_gov:<pool_id>`), not inside it.
+
+/// How long a pool proposal stays open
+pub const POOL_VOTING_PERIOD_SECS: u64 = 3 * 24 * 60 * 60;
+
+/// Parameters pool contributors govern
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct PoolParams {
+    /// Epochs between retraining runs of the pool's model
+    pub training_cadence_epochs: u64,
+    /// Share of the vault contributors can claim as dividends, in basis points;
+    /// the rest stays in the vault to fund training
+    pub contributor_share_bps: u16,
+    /// Listing price of the pool's ensemble signal, in COMPASS
+    pub signal_price: u64,
+}
+
+impl Default for PoolParams {
+    fn default() -> Self {
+        Self { training_cadence_epochs: 1, contributor_share_bps: 10_000, signal_price: 0 }
+    }
+}
+
+/// A change to one pool parameter
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "param", content = "value", rename_all = "snake_case")]
+pub enum PoolParamChange {
+    TrainingCadenceEpochs(u64),
+    ContributorShareBps(u16),
+    SignalPrice(u64),
+}
+
+impl PoolParamChange {
+    fn validate(&self) -> Result<(), String> {
+        match self {
+            PoolParamChange::TrainingCadenceEpochs(0) => Err("Training cadence must be at least 1 epoch".to_string()),
+            PoolParamChange::ContributorShareBps(bps) if *bps > 10_000 => {
+                Err("Contributor share cannot exceed 10000 bps".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn apply(&self, params: &mut PoolParams) {
+        match self {
+            PoolParamChange::TrainingCadenceEpochs(epochs) => params.training_cadence_epochs = *epochs,
+            PoolParamChange::ContributorShareBps(bps) => params.contributor_share_bps = *bps,
+            PoolParamChange::SignalPrice(price) => params.signal_price = *price,
+        }
+    }
+}
+
+impl std::fmt::Display for PoolParamChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PoolParamChange::TrainingCadenceEpochs(epochs) => write!(f, "training_cadence_epochs={}", epochs),
+            PoolParamChange::ContributorShareBps(bps) => write!(f, "contributor_share_bps={}", bps),
+            PoolParamChange::SignalPrice(price) => write!(f, "signal_price={}", price),
+        }
+    }
+}
+
+/// What a proposer signs; `proposal_id` is the governance's `next_proposal_id`,
+/// so a signed proposal can only be submitted once
+pub fn pool_propose_message(pool_id: &str, proposal_id: u64, change: &PoolParamChange) -> String {
+    format!("COMPASS_POOL_PROPOSE:{}:{}:{}", pool_id, proposal_id, change)
+}
+
+pub fn pool_vote_message(pool_id: &str, proposal_id: u64, approve: bool) -> String {
+    format!("COMPASS_POOL_VOTE:{}:{}:{}", pool_id, proposal_id, approve)
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PoolProposalStatus {
+    Open,
+    /// Passed and applied to the pool's parameters
+    Executed,
+    Rejected,
+    /// Closed without a majority either way
+    Expired,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolProposal {
+    pub id: u64,
+    pub proposer: String,
+    pub change: PoolParamChange,
+    pub created_at: u64,
+    pub closes_at: u64,
+    /// Contributor -> approve; one vote each
+    pub votes: std::collections::BTreeMap<String, bool>,
+    pub status: PoolProposalStatus,
+}
+
+/// Stake behind each side of a proposal
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PoolTally {
+    pub yes: u64,
+    pub no: u64,
+    pub total_staked: u64,
+}
+
+/// A pool's current parameters and its proposals
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PoolGovernance {
+    pub pool_id: String,
+    pub params: PoolParams,
+    pub proposals: Vec<PoolProposal>,
+    pub next_proposal_id: u64,
+}
+
+impl PoolGovernance {
+    pub fn new(pool_id: String) -> Self {
+        Self { pool_id, params: PoolParams::default(), proposals: Vec::new(), next_proposal_id: 1 }
+    }
+
+    /// Open a proposal. The proposer must hold stake in `pool` and sign
+    /// `pool_propose_message` with the key their address names.
+    pub fn propose(
+        &mut self,
+        pool: &ModelPool,
+        proposer: &str,
+        change: PoolParamChange,
+        signature: &str,
+        now: u64,
+    ) -> Result<u64, String> {
+        change.validate()?;
+        if !pool.contributors.contains_key(proposer) {
+            return Err("Only pool contributors can propose".to_string());
+        }
+        let id = self.next_proposal_id;
+        let message = pool_propose_message(&pool.pool_id, id, &change);
+        if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), signature, proposer) {
+            return Err("Invalid proposer signature".to_string());
+        }
+        self.proposals.push(PoolProposal {
+            id,
+            proposer: proposer.to_string(),
+            change,
+            created_at: now,
+            closes_at: now + POOL_VOTING_PERIOD_SECS,
+            votes: std::collections::BTreeMap::new(),
+            status: PoolProposalStatus::Open,
+        });
+        self.next_proposal_id += 1;
+        Ok(id)
+    }
+
+    /// Record a contributor's signed vote, then settle the proposal if the vote decided it
+    pub fn vote(
+        &mut self,
+        pool: &ModelPool,
+        proposal_id: u64,
+        voter: &str,
+        approve: bool,
+        signature: &str,
+        now: u64,
+    ) -> Result<PoolProposalStatus, String> {
+        if !pool.contributors.contains_key(voter) {
+            return Err("Only pool contributors can vote".to_string());
+        }
+        let message = pool_vote_message(&pool.pool_id, proposal_id, approve);
+        if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), signature, voter) {
+            return Err("Invalid voter signature".to_string());
+        }
+        let proposal = self.proposal_mut(proposal_id)?;
+        if proposal.status != PoolProposalStatus::Open || now >= proposal.closes_at {
+            return Err(format!("Proposal {} is closed", proposal_id));
+        }
+        if proposal.votes.contains_key(voter) {
+            return Err(format!("{} has already voted on proposal {}", voter, proposal_id));
+        }
+        proposal.votes.insert(voter.to_string(), approve);
+        self.settle(pool, proposal_id, now)
+    }
+
+    /// Stake for and against a proposal, counted at current stakes
+    pub fn tally(&self, pool: &ModelPool, proposal_id: u64) -> Option<PoolTally> {
+        let proposal = self.proposals.iter().find(|p| p.id == proposal_id)?;
+        let mut tally = PoolTally { yes: 0, no: 0, total_staked: pool.total_staked };
+        for (voter, approve) in &proposal.votes {
+            let stake = pool.contributors.get(voter).copied().unwrap_or(0);
+            if *approve {
+                tally.yes += stake;
+            } else {
+                tally.no += stake;
+            }
+        }
+        Some(tally)
+    }
+
+    /// Execute, reject or expire an open proposal if its outcome is decided.
+    /// Returns its status afterwards.
+    pub fn settle(&mut self, pool: &ModelPool, proposal_id: u64, now: u64) -> Result<PoolProposalStatus, String> {
+        let tally = self.tally(pool, proposal_id).ok_or_else(|| format!("No proposal {}", proposal_id))?;
+        let proposal = self.proposal_mut(proposal_id)?;
+        if proposal.status != PoolProposalStatus::Open {
+            return Ok(proposal.status);
+        }
+        let half = tally.total_staked / 2;
+        proposal.status = if tally.yes > half {
+            PoolProposalStatus::Executed
+        } else if tally.total_staked > 0 && tally.no >= tally.total_staked - half {
+            PoolProposalStatus::Rejected
+        } else if now >= proposal.closes_at {
+            PoolProposalStatus::Expired
+        } else {
+            PoolProposalStatus::Open
+        };
+        let status = proposal.status;
+        if status == PoolProposalStatus::Executed {
+            let change = proposal.change.clone();
+            change.apply(&mut self.params);
+        }
+        Ok(status)
+    }
+
+    /// Settle every open proposal, e.g. after stakes changed or time passed
+    pub fn settle_all(&mut self, pool: &ModelPool, now: u64) {
+        let open: Vec<u64> =
+            self.proposals.iter().filter(|p| p.status == PoolProposalStatus::Open).map(|p| p.id).collect();
+        for id in open {
+            let _ = self.settle(pool, id, now);
+        }
+    }
+
+    fn proposal_mut(&mut self, proposal_id: u64) -> Result<&mut PoolProposal, String> {
+        self.proposals
+            .iter_mut()
+            .find(|p| p.id == proposal_id)
+            .ok_or_else(|| format!("No proposal {}", proposal_id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+
+    #[test]
+    fn test_pool_votes_are_stake_weighted() {
+        let whale = KeyPair::from_seed("pool-whale");
+        let minnow = KeyPair::from_seed("pool-minnow");
+        let mut pool = ModelPool::new("POOL-1".to_string(), "Alpha".to_string(), "signal-classifier".to_string());
+        pool.add_stake(whale.public_key_hex(), 600);
+        pool.add_stake(minnow.public_key_hex(), 400);
+        let mut gov = PoolGovernance::new(pool.pool_id.clone());
+
+        let propose = |gov: &mut PoolGovernance, who: &KeyPair, change: PoolParamChange| {
+            let sig = who.sign_hex(pool_propose_message(&pool.pool_id, gov.next_proposal_id, &change).as_bytes());
+            gov.propose(&pool, &who.public_key_hex(), change, &sig, 1_000)
+        };
+        let vote = |gov: &mut PoolGovernance, who: &KeyPair, id: u64, approve: bool, now: u64| {
+            let sig = who.sign_hex(pool_vote_message(&pool.pool_id, id, approve).as_bytes());
+            gov.vote(&pool, id, &who.public_key_hex(), approve, &sig, now)
+        };
+
+        // Outsiders and bad values are refused
+        let outsider = KeyPair::from_seed("pool-outsider");
+        assert!(propose(&mut gov, &outsider, PoolParamChange::SignalPrice(5)).is_err());
+        assert!(propose(&mut gov, &minnow, PoolParamChange::ContributorShareBps(10_001)).is_err());
+
+        // The minnow alone can't carry a change; the whale's majority executes it
+        let id = propose(&mut gov, &minnow, PoolParamChange::ContributorShareBps(8_000)).unwrap();
+        assert_eq!(vote(&mut gov, &minnow, id, true, 1_001), Ok(PoolProposalStatus::Open));
+        assert!(vote(&mut gov, &minnow, id, false, 1_002).is_err());
+        assert_eq!(vote(&mut gov, &whale, id, true, 1_003), Ok(PoolProposalStatus::Executed));
+        assert_eq!(gov.params.contributor_share_bps, 8_000);
+
+        // A signature for one choice can't be replayed as the other
+        let id = propose(&mut gov, &whale, PoolParamChange::SignalPrice(50)).unwrap();
+        let yes = whale.sign_hex(pool_vote_message(&pool.pool_id, id, true).as_bytes());
+        assert!(gov.vote(&pool, id, &whale.public_key_hex(), false, &yes, 1_004).is_err());
+
+        // Without a majority the proposal lapses after the voting period
+        assert_eq!(vote(&mut gov, &minnow, id, false, 1_005), Ok(PoolProposalStatus::Open));
+        gov.settle_all(&pool, 1_000 + POOL_VOTING_PERIOD_SECS);
+        assert_eq!(gov.proposals[1].status, PoolProposalStatus::Expired);
+        assert_eq!(gov.params.signal_price, 0);
+    }
+}
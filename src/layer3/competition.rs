@@ -0,0 +1,383 @@
+//! Paper Trading Competitions
+//!
+//! Scheduled contests built on the paper trading engine. Each entrant registers a
+//! model or agent, receives the same simulated starting capital, and trades it
+//! against live oracle prices between `start_time` and `end_time`. The leaderboard
+//! is re-ranked once per epoch, and at close the prize pool (the sum of all entry
+//! fees) is split between the top finishers according to `payout_bps`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::layer3::paper_trading::{PaperTrade, TradingPortfolio, TradingSignal};
+
+/// Account that holds entry fees until a competition pays out
+pub const COMPETITION_POOL_ACCOUNT: &str = "COMPETITION_POOL";
+
+/// Default prize split: 50% / 30% / 20%
+pub const DEFAULT_PAYOUT_BPS: &[u32] = &[5000, 3000, 2000];
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum CompetitionStatus {
+    Scheduled,
+    Active,
+    Closed,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Entrant {
+    pub account: String,
+    pub agent_id: String, // Model token ID or agent name trading on the account's behalf
+    pub joined_at: u64,
+    pub portfolio: TradingPortfolio,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    pub rank: usize,
+    pub account: String,
+    pub agent_id: String,
+    pub equity: f64,
+    pub return_pct: f64,
+    pub total_trades: u64,
+    pub win_rate: f64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Payout {
+    pub rank: usize,
+    pub account: String,
+    pub amount: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Competition {
+    pub competition_id: String,
+    pub name: String,
+    pub creator: String,
+    pub start_time: u64,
+    pub end_time: u64,
+    pub epoch_secs: u64,
+    pub fee_asset: String,
+    pub entry_fee: u64,
+    pub starting_capital: f64,
+    pub prize_pool: u64,
+    pub payout_bps: Vec<u32>, // Share of the pool per finishing place, in basis points
+    pub tickers: Vec<String>, // Markets entrants may trade
+    pub entrants: Vec<Entrant>,
+    pub leaderboard: Vec<LeaderboardEntry>,
+    pub last_epoch: Option<u64>,
+    pub status: CompetitionStatus,
+    pub payouts: Vec<Payout>,
+}
+
+impl Competition {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        competition_id: String,
+        name: String,
+        creator: String,
+        start_time: u64,
+        end_time: u64,
+        epoch_secs: u64,
+        fee_asset: String,
+        entry_fee: u64,
+        starting_capital: f64,
+        payout_bps: Vec<u32>,
+        tickers: Vec<String>,
+    ) -> Result<Self, String> {
+        if competition_id.trim().is_empty() || name.trim().is_empty() {
+            return Err("Competition ID and name are required".to_string());
+        }
+        if end_time <= start_time {
+            return Err("Competition must end after it starts".to_string());
+        }
+        if epoch_secs == 0 {
+            return Err("Epoch length must be positive".to_string());
+        }
+        if starting_capital <= 0.0 {
+            return Err("Starting capital must be positive".to_string());
+        }
+        if payout_bps.is_empty() || payout_bps.iter().sum::<u32>() != 10_000 {
+            return Err("Payout split must be non-empty and sum to 10000 bps".to_string());
+        }
+        if tickers.is_empty() {
+            return Err("At least one ticker is required".to_string());
+        }
+
+        Ok(Self {
+            competition_id,
+            name,
+            creator,
+            start_time,
+            end_time,
+            epoch_secs,
+            fee_asset,
+            entry_fee,
+            starting_capital,
+            prize_pool: 0,
+            payout_bps,
+            tickers,
+            entrants: Vec::new(),
+            leaderboard: Vec::new(),
+            last_epoch: None,
+            status: CompetitionStatus::Scheduled,
+            payouts: Vec::new(),
+        })
+    }
+
+    /// Status implied by the clock (a closed competition stays closed)
+    pub fn status_at(&self, now: u64) -> CompetitionStatus {
+        if self.status == CompetitionStatus::Closed {
+            CompetitionStatus::Closed
+        } else if now < self.start_time {
+            CompetitionStatus::Scheduled
+        } else {
+            CompetitionStatus::Active
+        }
+    }
+
+    /// Epoch index for a timestamp inside the window
+    pub fn epoch_at(&self, now: u64) -> u64 {
+        now.saturating_sub(self.start_time) / self.epoch_secs
+    }
+
+    fn entrant_mut(&mut self, account: &str) -> Result<&mut Entrant, String> {
+        self.entrants
+            .iter_mut()
+            .find(|e| e.account == account)
+            .ok_or_else(|| format!("{} is not entered in this competition", account))
+    }
+
+    /// Register an entrant. The caller is responsible for collecting `entry_fee`.
+    pub fn join(&mut self, account: &str, agent_id: &str, now: u64) -> Result<(), String> {
+        if self.status_at(now) == CompetitionStatus::Closed || now >= self.end_time {
+            return Err("Competition has closed".to_string());
+        }
+        if self.entrants.iter().any(|e| e.account == account) {
+            return Err(format!("{} already entered", account));
+        }
+        self.entrants.push(Entrant {
+            account: account.to_string(),
+            agent_id: agent_id.to_string(),
+            joined_at: now,
+            portfolio: TradingPortfolio::new(self.starting_capital),
+        });
+        self.prize_pool = self.prize_pool.saturating_add(self.entry_fee);
+        Ok(())
+    }
+
+    fn check_trading_window(&self, ticker: &str, now: u64) -> Result<(), String> {
+        if self.status_at(now) != CompetitionStatus::Active || now >= self.end_time {
+            return Err("Competition is not open for trading".to_string());
+        }
+        if !self.tickers.iter().any(|t| t == ticker) {
+            return Err(format!("{} is not traded in this competition", ticker));
+        }
+        Ok(())
+    }
+
+    pub fn open_position(
+        &mut self,
+        account: &str,
+        ticker: &str,
+        signal: TradingSignal,
+        position_size: f64,
+        price: f64,
+        now: u64,
+    ) -> Result<(), String> {
+        self.check_trading_window(ticker, now)?;
+        if position_size <= 0.0 || price <= 0.0 {
+            return Err("Position size and price must be positive".to_string());
+        }
+        let competition_id = self.competition_id.clone();
+        let entrant = self.entrant_mut(account)?;
+        let trade = PaperTrade::new(
+            ticker,
+            &entrant.agent_id,
+            "competition",
+            signal,
+            price,
+            position_size,
+            &competition_id,
+        );
+        entrant.portfolio.open_trade(trade)
+    }
+
+    pub fn close_position(&mut self, account: &str, ticker: &str, price: f64, now: u64) -> Result<(), String> {
+        self.check_trading_window(ticker, now)?;
+        self.entrant_mut(account)?.portfolio.close_trade(ticker, price)
+    }
+
+    /// Cash plus open positions marked at `prices`. Positions without a price are held at cost.
+    pub fn equity(portfolio: &TradingPortfolio, prices: &HashMap<String, f64>) -> f64 {
+        let open: f64 = portfolio
+            .open_trades
+            .values()
+            .map(|t| {
+                let price = prices.get(&t.ticker).copied().unwrap_or(t.entry_price);
+                let move_pct = (price - t.entry_price) / t.entry_price;
+                match t.signal {
+                    TradingSignal::Buy => t.position_size * (1.0 + move_pct),
+                    TradingSignal::Sell => t.position_size * (1.0 - move_pct),
+                    TradingSignal::Hold => t.position_size,
+                }
+            })
+            .sum();
+        portfolio.current_balance + open
+    }
+
+    /// Re-rank entrants by equity. Ties go to whoever joined first.
+    pub fn rank(&self, prices: &HashMap<String, f64>) -> Vec<LeaderboardEntry> {
+        let mut rows: Vec<(&Entrant, f64)> = self
+            .entrants
+            .iter()
+            .map(|e| (e, Self::equity(&e.portfolio, prices)))
+            .collect();
+        rows.sort_by(|a, b| {
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(a.0.joined_at.cmp(&b.0.joined_at))
+        });
+        rows.into_iter()
+            .enumerate()
+            .map(|(i, (e, equity))| LeaderboardEntry {
+                rank: i + 1,
+                account: e.account.clone(),
+                agent_id: e.agent_id.clone(),
+                equity,
+                return_pct: (equity - self.starting_capital) / self.starting_capital * 100.0,
+                total_trades: e.portfolio.total_trades,
+                win_rate: e.portfolio.win_rate,
+            })
+            .collect()
+    }
+
+    /// Refresh the leaderboard if a new epoch has started. Returns true if it was updated.
+    pub fn update_leaderboard(&mut self, prices: &HashMap<String, f64>, now: u64) -> bool {
+        if self.status_at(now) != CompetitionStatus::Active {
+            return false;
+        }
+        self.status = CompetitionStatus::Active;
+        let epoch = self.epoch_at(now.min(self.end_time));
+        if self.last_epoch == Some(epoch) {
+            return false;
+        }
+        self.leaderboard = self.rank(prices);
+        self.last_epoch = Some(epoch);
+        true
+    }
+
+    /// Close every open position at `prices`, freeze the final leaderboard and
+    /// split the prize pool. Rounding dust and shares for unfilled places go to first place.
+    pub fn settle(&mut self, prices: &HashMap<String, f64>) -> Result<Vec<Payout>, String> {
+        if self.status == CompetitionStatus::Closed {
+            return Err("Competition already settled".to_string());
+        }
+
+        for entrant in &mut self.entrants {
+            let tickers: Vec<String> = entrant.portfolio.open_trades.keys().cloned().collect();
+            for ticker in tickers {
+                let entry = entrant.portfolio.open_trades[&ticker].entry_price;
+                let price = prices.get(&ticker).copied().unwrap_or(entry);
+                entrant.portfolio.close_trade(&ticker, price)?;
+            }
+        }
+
+        self.leaderboard = self.rank(prices);
+        self.status = CompetitionStatus::Closed;
+
+        let mut payouts: Vec<Payout> = self
+            .leaderboard
+            .iter()
+            .zip(self.payout_bps.iter())
+            .map(|(row, bps)| Payout {
+                rank: row.rank,
+                account: row.account.clone(),
+                amount: (self.prize_pool as u128 * *bps as u128 / 10_000) as u64,
+            })
+            .collect();
+        let paid: u64 = payouts.iter().map(|p| p.amount).sum();
+        if let Some(first) = payouts.first_mut() {
+            first.amount += self.prize_pool - paid;
+        }
+        payouts.retain(|p| p.amount > 0);
+
+        self.payouts = payouts.clone();
+        Ok(payouts)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn competition() -> Competition {
+        Competition::new(
+            "weekly-1".to_string(),
+            "Weekly BTC Cup".to_string(),
+            "admin".to_string(),
+            1_000,
+            2_000,
+            100,
+            "COMPASS".to_string(),
+            10,
+            10_000.0,
+            DEFAULT_PAYOUT_BPS.to_vec(),
+            vec!["BTCUSDT".to_string()],
+        )
+        .unwrap()
+    }
+
+    fn prices(btc: f64) -> HashMap<String, f64> {
+        HashMap::from([("BTCUSDT".to_string(), btc)])
+    }
+
+    #[test]
+    fn test_trading_only_inside_window() {
+        let mut c = competition();
+        c.join("alice", "model-a", 500).unwrap();
+        assert!(c.open_position("alice", "BTCUSDT", TradingSignal::Buy, 1_000.0, 100.0, 900).is_err());
+        assert!(c.open_position("alice", "ETHUSDT", TradingSignal::Buy, 1_000.0, 100.0, 1_100).is_err());
+        assert!(c.open_position("alice", "BTCUSDT", TradingSignal::Buy, 1_000.0, 100.0, 1_100).is_ok());
+        assert!(c.join("late", "model-z", 2_000).is_err());
+    }
+
+    #[test]
+    fn test_leaderboard_updates_once_per_epoch() {
+        let mut c = competition();
+        c.join("alice", "model-a", 500).unwrap();
+        c.join("bob", "model-b", 600).unwrap();
+        c.open_position("bob", "BTCUSDT", TradingSignal::Buy, 5_000.0, 100.0, 1_050).unwrap();
+
+        assert!(c.update_leaderboard(&prices(110.0), 1_060));
+        assert_eq!(c.leaderboard[0].account, "bob");
+        assert!((c.leaderboard[0].equity - 10_500.0).abs() < 1e-9);
+
+        // Same epoch: leaderboard is frozen even though bob is now losing
+        assert!(!c.update_leaderboard(&prices(50.0), 1_090));
+        assert_eq!(c.leaderboard[0].account, "bob");
+
+        assert!(c.update_leaderboard(&prices(50.0), 1_100));
+        assert_eq!(c.leaderboard[0].account, "alice");
+    }
+
+    #[test]
+    fn test_settlement_pays_out_whole_pool() {
+        let mut c = competition();
+        for (i, account) in ["alice", "bob", "carol", "dave"].iter().enumerate() {
+            c.join(account, "agent", 500 + i as u64).unwrap();
+        }
+        c.open_position("carol", "BTCUSDT", TradingSignal::Sell, 2_000.0, 100.0, 1_100).unwrap();
+        c.open_position("dave", "BTCUSDT", TradingSignal::Buy, 2_000.0, 100.0, 1_100).unwrap();
+
+        let payouts = c.settle(&prices(90.0)).unwrap();
+        assert_eq!(c.status, CompetitionStatus::Closed);
+        assert_eq!(payouts.iter().map(|p| p.amount).sum::<u64>(), 40);
+        assert_eq!(payouts[0], Payout { rank: 1, account: "carol".to_string(), amount: 20 });
+        // alice and bob tie on equity; alice joined first
+        assert_eq!(payouts[1].account, "alice");
+        assert!(c.entrants.iter().all(|e| e.portfolio.open_trades.is_empty()));
+        assert!(c.settle(&prices(90.0)).is_err());
+    }
+}
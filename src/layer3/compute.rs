@@ -1,5 +1,7 @@
 use serde::{Serialize, Deserialize};
+#[cfg(feature = "ai")]
 use sha2::{Sha256, Digest};
+#[cfg(feature = "ai")]
 use ndarray::Array2; // Requires 'ndarray' dependency
 
 
@@ -51,6 +53,22 @@ pub struct ComputeVerify {
     pub is_match: bool,      // True if verifier agrees
 }
 
+/// A worker's output for a job, kept until `expires_at` for the job's creator
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JobResult {
+    pub job_id: String,
+    pub worker_id: String,
+    pub data: Vec<u8>,
+    pub result_hash: String, // sha256 of `data`
+    pub stored_at: u64,      // unix seconds
+    pub expires_at: u64,
+}
+
+/// Message a job's creator signs to read its result
+pub fn job_result_request_message(job_id: &str, timestamp: u64) -> String {
+    format!("getJobResult:{}:{}", job_id, timestamp)
+}
+
 impl ComputeJob {
     pub fn new(job_id: String, creator: String, model_id: String, inputs: Vec<u8>, reward_amount: u64) -> Self {
         Self {
@@ -100,6 +118,7 @@ impl ComputeJob {
     }
 
     /// Deterministic Matrix Multiplication (Legacy PoW)
+    #[cfg(feature = "ai")]
     pub fn execute_deterministic_task(&self, size: usize) -> Result<String, String> {
         let seed_str = format!("{}_seed", self.job_id);
         let seed = seed_str.as_bytes();
@@ -112,6 +131,7 @@ impl ComputeJob {
     }
 
     /// Real AI Inference using ONNX Runtime
+    #[cfg(feature = "onnx")]
     pub fn execute_inference(&self) -> Result<String, String> {
         use ort::session::{Session, builder::GraphOptimizationLevel};
         use ort::value::Value;
@@ -347,8 +367,18 @@ impl ComputeJob {
         
         Ok(result_json.to_string())
     }
+
+    /// Built without the `onnx` feature (e.g. 32-bit ARM workers): inference jobs are refused
+    #[cfg(not(feature = "onnx"))]
+    pub fn execute_inference(&self) -> Result<String, String> {
+        Err(format!(
+            "Model {} needs ONNX Runtime, which this worker was built without (enable the 'onnx' feature)",
+            self.model_id
+        ))
+    }
 }
 
+#[cfg(feature = "ai")]
 fn generate_deterministic_matrix(size: usize, seed: &[u8], salt: u8) -> Array2<f32> {
     let mut data = Vec::with_capacity(size * size);
     let mut cycle_seed = seed.iter().cycle();
@@ -0,0 +1,136 @@
+//! Worker earnings ledger.
+//!
+//! Job payouts, oracle update rewards and pool dividends used to be credited as
+//! COMPUTE the moment a handler decided them. They now accrue here instead, each
+//! with its reason and the job or pool it came from, and a worker moves its
+//! claimable balance into its wallet with a signed `EarningsClaim` block. Every
+//! payout is then an entry in the ledger plus a block on chain, and nodes can
+//! hold claims back until they reach `min_payout`.
+
+use serde::{Deserialize, Serialize};
+
+/// Asset earnings are paid in
+pub const EARNINGS_ASSET: &str = "COMPUTE";
+
+/// Entries kept per worker for `getEarnings`; older ones are dropped from the log
+/// (never from the balance)
+pub const MAX_EARNING_ENTRIES: usize = 1000;
+
+/// Why a worker was paid
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum EarningReason {
+    /// Accepted result for a compute job
+    Job,
+    /// Oracle verification the worker ran
+    OracleUpdate,
+    /// Share of a model pool's vault
+    PoolDividend,
+}
+
+impl std::fmt::Display for EarningReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            EarningReason::Job => "job",
+            EarningReason::OracleUpdate => "oracle_update",
+            EarningReason::PoolDividend => "pool_dividend",
+        })
+    }
+}
+
+/// One accrual
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct EarningEntry {
+    pub reason: EarningReason,
+    pub amount: u64,
+    /// Job or pool id the earning came from
+    pub reference: String,
+    /// Unix seconds
+    pub accrued_at: u64,
+}
+
+/// A worker's running totals and its most recent entries
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct WorkerEarnings {
+    pub worker: String,
+    /// Accrued and not yet claimed
+    pub claimable: u64,
+    pub total_accrued: u64,
+    pub total_claimed: u64,
+    /// Oldest first, at most MAX_EARNING_ENTRIES
+    pub entries: Vec<EarningEntry>,
+}
+
+impl WorkerEarnings {
+    pub fn new(worker: String) -> Self {
+        Self { worker, ..Default::default() }
+    }
+
+    pub fn accrue(&mut self, entry: EarningEntry) {
+        self.claimable = self.claimable.saturating_add(entry.amount);
+        self.total_accrued = self.total_accrued.saturating_add(entry.amount);
+        self.entries.push(entry);
+        if self.entries.len() > MAX_EARNING_ENTRIES {
+            let excess = self.entries.len() - MAX_EARNING_ENTRIES;
+            self.entries.drain(..excess);
+        }
+    }
+
+    /// Check a claim of `amount` against the balance and the node's minimum payout
+    pub fn check_claim(&self, amount: u64, min_payout: u64) -> Result<(), String> {
+        if amount == 0 {
+            return Err("nothing to claim".to_string());
+        }
+        if amount < min_payout {
+            return Err(format!("claim of {} is below the minimum payout of {}", amount, min_payout));
+        }
+        if amount > self.claimable {
+            return Err(format!("claim of {} exceeds the claimable {}", amount, self.claimable));
+        }
+        Ok(())
+    }
+
+    pub fn claim(&mut self, amount: u64, min_payout: u64) -> Result<(), String> {
+        self.check_claim(amount, min_payout)?;
+        self.claimable -= amount;
+        self.total_claimed = self.total_claimed.saturating_add(amount);
+        Ok(())
+    }
+}
+
+/// What a worker signs to claim `amount` with its account `nonce`
+pub fn claim_message(worker: &str, amount: u64, nonce: u64) -> String {
+    format!("COMPASS_CLAIM_EARNINGS:{}:{}:{}", worker, amount, nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(reason: EarningReason, amount: u64) -> EarningEntry {
+        EarningEntry { reason, amount, reference: "ref".to_string(), accrued_at: 0 }
+    }
+
+    #[test]
+    fn test_claims_respect_balance_and_minimum_payout() {
+        let mut earnings = WorkerEarnings::new("worker".to_string());
+        earnings.accrue(entry(EarningReason::Job, 40));
+        earnings.accrue(entry(EarningReason::OracleUpdate, 15));
+        earnings.accrue(entry(EarningReason::PoolDividend, 5));
+        assert_eq!((earnings.claimable, earnings.total_accrued), (60, 60));
+
+        assert!(earnings.claim(30, 50).is_err());
+        assert!(earnings.claim(61, 50).is_err());
+        earnings.claim(60, 50).unwrap();
+        assert_eq!((earnings.claimable, earnings.total_claimed), (0, 60));
+        assert!(earnings.claim(0, 0).is_err());
+
+        // The log is capped; the totals are not
+        for _ in 0..MAX_EARNING_ENTRIES {
+            earnings.accrue(entry(EarningReason::Job, 1));
+        }
+        assert_eq!(earnings.entries.len(), MAX_EARNING_ENTRIES);
+        assert_eq!(earnings.entries[0].reason, EarningReason::Job);
+        assert_eq!(earnings.total_accrued, 60 + MAX_EARNING_ENTRIES as u64);
+    }
+}
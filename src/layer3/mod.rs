@@ -1,19 +1,32 @@
 pub mod finance_oracle;
 pub mod data;
+#[cfg(feature = "ai")]
 pub mod models;
+#[cfg(feature = "ai")]
 pub mod agent;
 pub mod betting;
+#[cfg(feature = "ai")]
 pub mod advanced_nn;
 pub mod collective;
 pub mod model_nft;
+pub mod nft_metadata; // Off-chain metadata standard (URI + on-chain hash)
+pub mod nft_collection; // Creator-signed collections & verification badges
 pub mod marketplace; // v2.0 Phase 5: P2P Model Trading
 pub mod user_ops;
+#[cfg(feature = "ai")]
 pub mod brain;
 pub mod compute;
+pub mod pouw; // Reward curve and redundancy quorum for compute results
+pub mod earnings; // Claimable worker earnings ledger
 pub mod compute_integration; // v2.0 Phase 4: COMPUTE token integration
+#[cfg(feature = "ai")]
 pub mod training; // Pure Rust AI Training
 pub mod price_oracle; // Price Oracles & Epoch Tracking
+#[cfg(feature = "ai")]
 pub mod signal_model; // Per-asset BUY/SELL/HOLD Classification
+#[cfg(feature = "onnx")]
 pub mod onnx_inference; // LSTM ONNX Inference
+#[cfg(feature = "ai")]
 pub mod ensemble; // Model Ensemble System (Week 4)
 pub mod paper_trading; // Paper Trading Engine for Signal Verification
+pub mod competition; // Scheduled Paper Trading Competitions
@@ -77,6 +77,7 @@ pub struct SaleRecord {
 
 impl ModelNFT {
     /// Create NFT from a trained neural network
+    #[cfg(feature = "ai")]
     pub fn from_network(
         network: &crate::layer3::models::NeuralNetwork,
         creator: String,
@@ -247,6 +248,88 @@ impl ModelNFT {
     }
 }
 
+/// COMPASS charged per metered compute unit on a pay-per-call inference
+pub const INFERENCE_PRICE_PER_UNIT: u64 = 10;
+/// Input bytes covered by one compute unit
+pub const INFERENCE_BYTES_PER_UNIT: usize = 1024;
+
+/// Why a caller may run inference on a model
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entitlement {
+    Owner,
+    Renter,
+    OpenSource,
+    /// No standing right; each call is paid from the caller's balance
+    PayPerCall,
+}
+
+impl Entitlement {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Entitlement::Owner => "owner",
+            Entitlement::Renter => "renter",
+            Entitlement::OpenSource => "open_source",
+            Entitlement::PayPerCall => "pay_per_call",
+        }
+    }
+}
+
+/// Compute units metered for one call: one per started KiB of input, at least one
+pub fn inference_units(input_len: usize) -> u64 {
+    (input_len.div_ceil(INFERENCE_BYTES_PER_UNIT) as u64).max(1)
+}
+
+/// Message a caller signs to invoke a model; binds the inputs and a timestamp so a
+/// captured request cannot be replayed with other inputs or long after the fact
+pub fn invoke_message(token_id: &str, caller: &str, inputs: &[u8], timestamp: u64) -> String {
+    let inputs_hash = crate::encoding::to_hex(crate::encoding::sha256(inputs));
+    format!("invokeModel:{}:{}:{}:{}", token_id, caller, inputs_hash, timestamp)
+}
+
+/// Id of one signed invocation, the hash of its `invoke_message`. A paid call is
+/// settled at most once per id.
+pub fn invoke_request_id(token_id: &str, caller: &str, inputs: &[u8], timestamp: u64) -> String {
+    let message = invoke_message(token_id, caller, inputs, timestamp);
+    crate::encoding::hash_to_hex(&crate::encoding::sha256(message.as_bytes()))
+}
+
+impl ModelNFT {
+    /// How `caller` may invoke this model at unix time `now`. Exclusive models serve
+    /// only their owner and an active renter.
+    pub fn entitlement(&self, caller: &str, now: u64) -> Result<Entitlement, String> {
+        if self.current_owner == caller {
+            return Ok(Entitlement::Owner);
+        }
+        if let Some(rental) = &self.rental_status {
+            if rental.renter == caller && rental.expires_at > now {
+                return Ok(Entitlement::Renter);
+            }
+        }
+        match self.license {
+            LicenseType::OpenSource => Ok(Entitlement::OpenSource),
+            LicenseType::Commercial => Ok(Entitlement::PayPerCall),
+            LicenseType::Exclusive => Err(format!("Model {} is exclusive to its owner and renter", self.token_id)),
+        }
+    }
+
+    /// What `caller` owes for one call with `input_len` bytes of input at unix time
+    /// `now`: nothing unless the call is pay-per-call
+    pub fn invocation_fee(&self, caller: &str, input_len: usize, now: u64) -> Result<(Entitlement, u64), String> {
+        let entitlement = self.entitlement(caller, now)?;
+        let fee = match entitlement {
+            Entitlement::PayPerCall => inference_units(input_len).saturating_mul(INFERENCE_PRICE_PER_UNIT),
+            _ => 0,
+        };
+        Ok((entitlement, fee))
+    }
+
+    /// Split an inference fee into (creator royalty, owner share)
+    pub fn split_revenue(&self, fee: u64) -> (u64, u64) {
+        let royalty = ((fee as f64 * self.royalty_rate) as u64).min(fee);
+        (royalty, fee - royalty)
+    }
+}
+
 /// Performance statistics for NFT metadata
 #[derive(Clone, Debug)]
 pub struct ModelStats {
@@ -357,6 +440,7 @@ impl ModelNFTRegistry {
 }
 
 /// Helper: Extract stats from BridgePredictor for NFT minting
+#[cfg(feature = "ai")]
 pub fn extract_model_stats(predictor: &crate::layer3::models::BridgePredictor) -> ModelStats {
     let (staked, won, lost, win_rate) = predictor.betting_ledger.get_stats();
     
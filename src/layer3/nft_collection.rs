@@ -0,0 +1,209 @@
+// Model NFT Collections & Creator Verification
+//
+// A creator groups their models under a collection ID. Creating a collection and
+// adding a model to it both require a signature from the creator's account key,
+// and only NFTs the creator actually minted may join, so a collection badge means
+// "published by this account" and cannot be attached to a copycat's model.
+use serde::{Deserialize, Serialize};
+
+use crate::layer3::model_nft::ModelNFT;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NftCollection {
+    pub collection_id: String,
+    pub name: String,
+    pub description: String,
+    pub creator: String, // Creator public key (hex)
+    pub creator_signature: String,
+    pub token_ids: Vec<String>,
+    pub created_at: u64,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct CollectionStats {
+    pub item_count: usize,
+    pub unique_owners: usize,
+    pub total_sales: usize,
+    pub total_volume: u64,
+    pub avg_accuracy: f64,
+    pub avg_win_rate: f64,
+}
+
+/// Badge shown next to an NFT in listings
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum VerificationBadge {
+    /// Member of a collection whose creator signature verifies and who minted the model
+    VerifiedCreator,
+    /// Collection signature verifies but the model was minted by someone else
+    CreatorMismatch,
+    /// Collection signature does not verify
+    Unverified,
+    /// Not in any collection
+    None,
+}
+
+pub fn create_message(collection_id: &str, name: &str) -> String {
+    format!("COMPASS_COLLECTION_CREATE:{}:{}", collection_id, name)
+}
+
+pub fn add_message(collection_id: &str, token_id: &str) -> String {
+    format!("COMPASS_COLLECTION_ADD:{}:{}", collection_id, token_id)
+}
+
+impl NftCollection {
+    pub fn new(
+        collection_id: String,
+        name: String,
+        description: String,
+        creator: String,
+        creator_signature: String,
+    ) -> Result<Self, String> {
+        if collection_id.trim().is_empty() || name.trim().is_empty() {
+            return Err("Collection ID and name are required".to_string());
+        }
+        let collection = Self {
+            collection_id,
+            name,
+            description,
+            creator,
+            creator_signature,
+            token_ids: Vec::new(),
+            created_at: chrono::Utc::now().timestamp() as u64,
+        };
+        if !collection.is_signature_valid() {
+            return Err("Invalid creator signature".to_string());
+        }
+        Ok(collection)
+    }
+
+    pub fn is_signature_valid(&self) -> bool {
+        crate::crypto::verify_with_pubkey_hex(
+            create_message(&self.collection_id, &self.name).as_bytes(),
+            &self.creator_signature,
+            &self.creator,
+        )
+    }
+
+    /// Add a model. The creator must sign the addition and must be the NFT's original creator.
+    pub fn add_model(&mut self, nft: &ModelNFT, signature: &str) -> Result<(), String> {
+        if !crate::crypto::verify_with_pubkey_hex(
+            add_message(&self.collection_id, &nft.token_id).as_bytes(),
+            signature,
+            &self.creator,
+        ) {
+            return Err("Invalid creator signature".to_string());
+        }
+        if nft.creator != self.creator {
+            return Err(format!(
+                "NFT {} was created by {}, not the collection creator",
+                nft.token_id, nft.creator
+            ));
+        }
+        if self.token_ids.contains(&nft.token_id) {
+            return Err("NFT already in collection".to_string());
+        }
+        self.token_ids.push(nft.token_id.clone());
+        Ok(())
+    }
+
+    pub fn badge_for(&self, nft: &ModelNFT) -> VerificationBadge {
+        if !self.is_signature_valid() {
+            VerificationBadge::Unverified
+        } else if nft.creator == self.creator {
+            VerificationBadge::VerifiedCreator
+        } else {
+            VerificationBadge::CreatorMismatch
+        }
+    }
+
+    pub fn stats(&self, members: &[ModelNFT]) -> CollectionStats {
+        let owners: std::collections::HashSet<&str> =
+            members.iter().map(|n| n.current_owner.as_str()).collect();
+        let total_sales = members.iter().map(|n| n.sale_history.len()).sum();
+        let total_volume = members
+            .iter()
+            .flat_map(|n| n.sale_history.iter())
+            .map(|s| s.price)
+            .fold(0u64, |acc, p| acc.saturating_add(p));
+        let count = members.len().max(1) as f64;
+
+        CollectionStats {
+            item_count: members.len(),
+            unique_owners: owners.len(),
+            total_sales,
+            total_volume,
+            avg_accuracy: members.iter().map(|n| n.accuracy).sum::<f64>() / count,
+            avg_win_rate: members.iter().map(|n| n.win_rate).sum::<f64>() / count,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+    use crate::layer3::model_nft::ModelStats;
+
+    fn nft_by(creator: &str, ticker: &str) -> ModelNFT {
+        let stats = ModelStats {
+            accuracy: 0.6,
+            win_rate: 0.55,
+            total_predictions: 10,
+            profitable_predictions: 6,
+            total_profit: 0,
+            training_samples: 100,
+            training_epochs: 1,
+            final_loss: 0.1,
+            training_duration: 1,
+            data_hash: String::new(),
+        };
+        ModelNFT::from_job("job", ticker, creator.to_string(), &stats)
+    }
+
+    fn collection(kp: &KeyPair) -> NftCollection {
+        let sig = kp.sign_hex(create_message("official", "Official Signals").as_bytes());
+        NftCollection::new(
+            "official".to_string(),
+            "Official Signals".to_string(),
+            String::new(),
+            kp.public_key_hex(),
+            sig,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_creator_can_add_own_model() {
+        let kp = KeyPair::generate();
+        let mut col = collection(&kp);
+        let nft = nft_by(&kp.public_key_hex(), "BTC");
+        let sig = kp.sign_hex(add_message("official", &nft.token_id).as_bytes());
+        assert!(col.add_model(&nft, &sig).is_ok());
+        assert_eq!(col.badge_for(&nft), VerificationBadge::VerifiedCreator);
+        assert_eq!(col.stats(&[nft]).item_count, 1);
+    }
+
+    #[test]
+    fn test_copycat_model_rejected() {
+        let kp = KeyPair::generate();
+        let mut col = collection(&kp);
+        let copycat = nft_by("someone-else", "BTC");
+        let sig = kp.sign_hex(add_message("official", &copycat.token_id).as_bytes());
+        assert!(col.add_model(&copycat, &sig).is_err());
+    }
+
+    #[test]
+    fn test_forged_collection_rejected() {
+        let kp = KeyPair::generate();
+        let forger = KeyPair::generate();
+        let sig = forger.sign_hex(create_message("official", "Official Signals").as_bytes());
+        assert!(NftCollection::new(
+            "official".to_string(),
+            "Official Signals".to_string(),
+            String::new(),
+            kp.public_key_hex(),
+            sig,
+        )
+        .is_err());
+    }
+}
@@ -0,0 +1,317 @@
+// Model NFT Metadata Standard (compass-nft-metadata/v1)
+//
+// The full metadata document lives off-chain (HTTPS or IPFS). On-chain we keep
+// only a pointer: the document URI plus the SHA-256 of its exact bytes, so any
+// UI can fetch the document and prove it is the one the owner published.
+//
+// The node fetches owner-supplied URIs itself, so it only goes to public hosts:
+// https URLs whose every address is globally routable, or `ipfs://` through the
+// operator's gateway. Redirects are not followed.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+pub const METADATA_SCHEMA: &str = "compass-nft-metadata/v1";
+
+/// Gateway `ipfs://` URIs resolve through unless `[node] ipfs_gateway` names another
+pub const IPFS_GATEWAY: &str = "https://ipfs.io/ipfs/";
+
+/// Largest metadata document we will download (256 KiB)
+pub const MAX_METADATA_BYTES: usize = 256 * 1024;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct MetadataAttribute {
+    pub trait_type: String,
+    pub value: serde_json::Value,
+}
+
+/// Off-chain metadata document
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NftMetadata {
+    pub schema: String,
+    pub name: String,
+    pub description: String,
+    #[serde(default)]
+    pub image: Option<String>, // Image URI
+    #[serde(default)]
+    pub docs: Option<String>, // Model card / documentation URI
+    #[serde(default)]
+    pub architecture: Option<String>,
+    #[serde(default)]
+    pub attributes: Vec<MetadataAttribute>,
+}
+
+/// On-chain pointer to an NFT's metadata document
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct NftMetadataPointer {
+    pub token_id: String,
+    pub uri: String,
+    pub content_hash: String, // SHA-256 hex of the raw document bytes
+    pub updated_at: u64,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum MetadataError {
+    HashMismatch { expected: String, actual: String },
+    InvalidJson(String),
+    UnsupportedSchema(String),
+    MissingField(&'static str),
+    UnsupportedUri(String),
+    TooLarge(usize),
+    Fetch(String),
+}
+
+impl std::fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MetadataError::HashMismatch { expected, actual } => {
+                write!(f, "Content hash mismatch: expected {}, got {}", expected, actual)
+            }
+            MetadataError::InvalidJson(e) => write!(f, "Invalid metadata JSON: {}", e),
+            MetadataError::UnsupportedSchema(s) => write!(f, "Unsupported metadata schema: {}", s),
+            MetadataError::MissingField(field) => write!(f, "Missing required field: {}", field),
+            MetadataError::UnsupportedUri(uri) => write!(f, "Unsupported metadata URI: {}", uri),
+            MetadataError::TooLarge(size) => {
+                write!(f, "Metadata document too large: {} bytes (max {})", size, MAX_METADATA_BYTES)
+            }
+            MetadataError::Fetch(e) => write!(f, "Failed to fetch metadata: {}", e),
+        }
+    }
+}
+
+pub fn content_hash(bytes: &[u8]) -> String {
+    hex::encode(Sha256::digest(bytes))
+}
+
+/// Check a fetched document against its on-chain hash and the schema
+pub fn validate_document(bytes: &[u8], expected_hash: &str) -> Result<NftMetadata, MetadataError> {
+    if bytes.len() > MAX_METADATA_BYTES {
+        return Err(MetadataError::TooLarge(bytes.len()));
+    }
+
+    let actual = content_hash(bytes);
+    if !actual.eq_ignore_ascii_case(expected_hash) {
+        return Err(MetadataError::HashMismatch {
+            expected: expected_hash.to_string(),
+            actual,
+        });
+    }
+
+    let doc: NftMetadata =
+        serde_json::from_slice(bytes).map_err(|e| MetadataError::InvalidJson(e.to_string()))?;
+    if doc.schema != METADATA_SCHEMA {
+        return Err(MetadataError::UnsupportedSchema(doc.schema));
+    }
+    if doc.name.trim().is_empty() {
+        return Err(MetadataError::MissingField("name"));
+    }
+    Ok(doc)
+}
+
+/// Message the NFT's owner signs to publish `uri` with `content_hash`
+pub fn metadata_message(token_id: &str, uri: &str, content_hash: &str, timestamp: u64) -> String {
+    format!("setNFTMetadata:{}:{}:{}:{}", token_id, uri, content_hash, timestamp)
+}
+
+/// Map a metadata URI to a fetchable HTTPS URL: `ipfs://<cid>[/path]` through
+/// `ipfs_gateway`, or an https URL as-is
+pub fn resolve_uri(uri: &str, ipfs_gateway: &str) -> Result<String, MetadataError> {
+    let unsupported = || MetadataError::UnsupportedUri(uri.to_string());
+    if let Some(path) = uri.strip_prefix("ipfs://") {
+        // The path is appended to the gateway, so it must stay a plain path under it
+        let plain = |segment: &str| !segment.is_empty() && segment != ".." && segment.chars().all(|c| c.is_ascii_alphanumeric() || "-_.".contains(c));
+        if !path.split('/').all(plain) {
+            return Err(unsupported());
+        }
+        Ok(format!("{}{}", ipfs_gateway, path))
+    } else if uri.starts_with("https://") {
+        let url = reqwest::Url::parse(uri).map_err(|_| unsupported())?;
+        if url.host_str().is_none() || !url.username().is_empty() || url.password().is_some() {
+            return Err(unsupported());
+        }
+        Ok(uri.to_string())
+    } else {
+        Err(unsupported())
+    }
+}
+
+/// Whether the node may connect to `ip` on an owner's behalf: no loopback,
+/// private, link-local, shared, multicast or otherwise non-public address
+pub fn is_public_ip(ip: std::net::IpAddr) -> bool {
+    use std::net::IpAddr;
+    match ip {
+        IpAddr::V4(v4) => {
+            let [a, b, ..] = v4.octets();
+            !(v4.is_loopback()
+                || v4.is_private()
+                || v4.is_link_local()
+                || v4.is_unspecified()
+                || v4.is_broadcast()
+                || v4.is_multicast()
+                || v4.is_documentation()
+                || a == 0
+                || (a == 100 && (64..128).contains(&b)) // shared address space (CGNAT)
+                || (a == 198 && (b == 18 || b == 19)) // benchmarking
+                || a >= 240)
+        }
+        IpAddr::V6(v6) => {
+            if let Some(v4) = v6.to_ipv4_mapped() {
+                return is_public_ip(IpAddr::V4(v4));
+            }
+            let first = v6.segments()[0];
+            !(v6.is_loopback()
+                || v6.is_unspecified()
+                || v6.is_multicast()
+                || (first & 0xfe00) == 0xfc00 // unique local
+                || (first & 0xffc0) == 0xfe80 // link-local
+                || first == 0x2001 && v6.segments()[1] == 0x0db8) // documentation
+        }
+    }
+}
+
+/// Download and validate a metadata document. Owner-supplied hosts must resolve
+/// to public addresses only, and the connection goes to the addresses checked.
+pub async fn fetch_and_validate(pointer: &NftMetadataPointer, ipfs_gateway: &str) -> Result<NftMetadata, MetadataError> {
+    let url = resolve_uri(&pointer.uri, ipfs_gateway)?;
+    let mut client = reqwest::Client::builder().redirect(reqwest::redirect::Policy::none());
+    // The operator's gateway is trusted as configured; any other host is checked
+    if !pointer.uri.starts_with("ipfs://") {
+        let parsed = reqwest::Url::parse(&url).map_err(|_| MetadataError::UnsupportedUri(pointer.uri.clone()))?;
+        let host = parsed.host_str().ok_or_else(|| MetadataError::UnsupportedUri(pointer.uri.clone()))?.to_string();
+        let port = parsed.port_or_known_default().unwrap_or(443);
+        let addrs: Vec<std::net::SocketAddr> = tokio::net::lookup_host((host.trim_matches(|c| c == '[' || c == ']'), port))
+            .await
+            .map_err(|e| MetadataError::Fetch(e.to_string()))?
+            .collect();
+        if addrs.is_empty() || addrs.iter().any(|a| !is_public_ip(a.ip())) {
+            return Err(MetadataError::UnsupportedUri(format!("{} does not resolve to a public address", host)));
+        }
+        client = client.resolve_to_addrs(&host, &addrs);
+    }
+    let mut response = client
+        .build()
+        .map_err(|e| MetadataError::Fetch(e.to_string()))?
+        .get(&url)
+        .timeout(std::time::Duration::from_secs(10))
+        .send()
+        .await
+        .map_err(|e| MetadataError::Fetch(e.to_string()))?;
+    if !response.status().is_success() {
+        return Err(MetadataError::Fetch(format!("HTTP {}", response.status())));
+    }
+    // Refuse an oversized document up front, and stop reading once one passes the cap
+    if let Some(len) = response.content_length() {
+        if len > MAX_METADATA_BYTES as u64 {
+            return Err(MetadataError::TooLarge(len as usize));
+        }
+    }
+    let mut bytes = Vec::new();
+    while let Some(chunk) = response.chunk().await.map_err(|e| MetadataError::Fetch(e.to_string()))? {
+        bytes.extend_from_slice(&chunk);
+        if bytes.len() > MAX_METADATA_BYTES {
+            return Err(MetadataError::TooLarge(bytes.len()));
+        }
+    }
+    validate_document(&bytes, &pointer.content_hash)
+}
+
+impl NftMetadata {
+    /// Standard document derived from on-chain fields, for NFTs without published metadata
+    pub fn from_nft(nft: &crate::layer3::model_nft::ModelNFT) -> Self {
+        let attr = |trait_type: &str, value: serde_json::Value| MetadataAttribute {
+            trait_type: trait_type.to_string(),
+            value,
+        };
+        Self {
+            schema: METADATA_SCHEMA.to_string(),
+            name: nft.name.clone(),
+            description: nft.description.clone(),
+            image: None,
+            docs: None,
+            architecture: Some(nft.architecture.clone()),
+            attributes: vec![
+                attr("accuracy", serde_json::json!(nft.accuracy)),
+                attr("win_rate", serde_json::json!(nft.win_rate)),
+                attr("total_predictions", serde_json::json!(nft.total_predictions)),
+                attr("generation", serde_json::json!(nft.generation)),
+                attr("weights_hash", serde_json::json!(nft.weights_hash)),
+            ],
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_doc() -> Vec<u8> {
+        serde_json::to_vec(&serde_json::json!({
+            "schema": METADATA_SCHEMA,
+            "name": "BTC Signal v2",
+            "description": "LSTM signal model",
+            "image": "ipfs://bafyimage",
+            "attributes": [{ "trait_type": "ticker", "value": "BTCUSDT" }]
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_valid_document() {
+        let bytes = sample_doc();
+        let doc = validate_document(&bytes, &content_hash(&bytes)).unwrap();
+        assert_eq!(doc.name, "BTC Signal v2");
+        assert_eq!(doc.attributes.len(), 1);
+    }
+
+    #[test]
+    fn test_tampered_document_rejected() {
+        let bytes = sample_doc();
+        let hash = content_hash(&bytes);
+        let mut tampered = bytes.clone();
+        tampered[bytes.len() - 2] = b' ';
+        assert!(matches!(
+            validate_document(&tampered, &hash),
+            Err(MetadataError::HashMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_wrong_schema_rejected() {
+        let bytes = br#"{"schema":"erc721","name":"x","description":""}"#;
+        assert_eq!(
+            validate_document(bytes, &content_hash(bytes)),
+            Err(MetadataError::UnsupportedSchema("erc721".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_resolve_uri() {
+        assert_eq!(resolve_uri("ipfs://abc/meta.json", IPFS_GATEWAY).unwrap(), "https://ipfs.io/ipfs/abc/meta.json");
+        assert_eq!(resolve_uri("ipfs://abc", "http://127.0.0.1:8080/ipfs/").unwrap(), "http://127.0.0.1:8080/ipfs/abc");
+        assert!(resolve_uri("ipfs://abc/../../admin", IPFS_GATEWAY).is_err());
+        assert!(resolve_uri("ipfs://abc?x=1", IPFS_GATEWAY).is_err());
+        assert!(resolve_uri("http://example.com/meta.json", IPFS_GATEWAY).is_err());
+        assert!(resolve_uri("https://user@example.com/meta.json", IPFS_GATEWAY).is_err());
+        assert!(resolve_uri("compass://models/x", IPFS_GATEWAY).is_err());
+    }
+
+    #[test]
+    fn test_only_public_addresses_are_fetched() {
+        for private in ["127.0.0.1", "10.1.2.3", "172.16.0.1", "192.168.1.1", "169.254.169.254", "100.64.0.1", "0.0.0.0", "::1", "fd00::1", "fe80::1", "::ffff:127.0.0.1"] {
+            assert!(!is_public_ip(private.parse().unwrap()), "{}", private);
+        }
+        for public in ["93.184.216.34", "2606:4700::1111"] {
+            assert!(is_public_ip(public.parse().unwrap()), "{}", public);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_private_host_is_not_fetched() {
+        let pointer = NftMetadataPointer {
+            token_id: "t".to_string(),
+            uri: "https://127.0.0.1/meta.json".to_string(),
+            content_hash: content_hash(b""),
+            updated_at: 0,
+        };
+        assert!(matches!(fetch_and_validate(&pointer, IPFS_GATEWAY).await, Err(MetadataError::UnsupportedUri(_))));
+    }
+}
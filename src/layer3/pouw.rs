@@ -0,0 +1,182 @@
+//! Proof-of-useful-work rewards.
+//!
+//! The reward for a job comes from quantities the worker cannot set: the compute
+//! units the job's creator declared, the wall-clock time the node measured between
+//! the job being posted (or claimed) and the result arriving, and agreement between
+//! independent workers on the output. A job pays out once `required_matches`
+//! distinct workers have submitted the same result hash; every worker in that
+//! majority is paid the curve's reward, and later or dissenting results get nothing.
+
+use serde::{Deserialize, Serialize};
+
+use crate::layer3::compute::ComputeJob;
+
+/// Reward curve parameters, `[consensus.pouw]` in the node config
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PouwRewardCurve {
+    /// Paid for any accepted result
+    pub base_reward: u64,
+    /// COMPUTE per 1000 declared compute units
+    pub reward_per_kilo_unit: u64,
+    /// Declared units beyond this earn nothing extra
+    pub max_compute_units: u64,
+    /// Results arriving later than this after the job started are not rewarded
+    pub max_duration_secs: u64,
+    /// Distinct workers that must agree on a result before it pays (1 disables redundancy)
+    pub required_matches: usize,
+}
+
+impl Default for PouwRewardCurve {
+    fn default() -> Self {
+        Self {
+            base_reward: 1,
+            reward_per_kilo_unit: 2,
+            max_compute_units: 100_000,
+            max_duration_secs: 3600,
+            required_matches: 2,
+        }
+    }
+}
+
+impl PouwRewardCurve {
+    /// Reward for one worker's confirmed result on a job declaring `compute_units`
+    pub fn reward(&self, compute_units: u64) -> u64 {
+        let units = compute_units.min(self.max_compute_units);
+        self.base_reward
+            .saturating_add(units.saturating_mul(self.reward_per_kilo_unit) / 1000)
+    }
+
+    /// The anti-cheat window: no faster than the job's own minimum, no slower than the curve's maximum
+    pub fn check_duration(&self, elapsed_secs: u64, min_duration: u64) -> Result<(), String> {
+        if elapsed_secs < min_duration {
+            return Err(format!(
+                "job completed too quickly ({}/{}s). Possible cheating detected.",
+                elapsed_secs, min_duration
+            ));
+        }
+        if elapsed_secs > self.max_duration_secs {
+            return Err(format!("result arrived after {}s, limit is {}s", elapsed_secs, self.max_duration_secs));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ResultSubmission {
+    pub worker_id: String,
+    pub result_hash: String,
+    pub elapsed_secs: u64,
+}
+
+/// Results collected for one job. The job's parameters are copied in on the first
+/// submission so the tally can settle after the job itself is gone.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PouwTally {
+    pub job_id: String,
+    pub compute_units: u64,
+    pub min_duration: u64,
+    pub started_at: u64,
+    pub submissions: Vec<ResultSubmission>,
+    /// Result hash that reached the quorum and was paid
+    pub settled_hash: Option<String>,
+}
+
+/// What recording a result did
+#[derive(Debug, Clone, PartialEq)]
+pub enum PouwOutcome {
+    /// This worker already submitted for the job
+    Duplicate,
+    /// Counted; the job still needs more matching results
+    Pending { matches: usize, required: usize },
+    /// This result completed the quorum; these workers were paid
+    Settled { rewards: Vec<(String, u64)> },
+    /// The job had already settled; nothing is paid
+    AlreadySettled,
+}
+
+impl PouwTally {
+    pub fn new(job: &ComputeJob) -> Self {
+        Self {
+            job_id: job.job_id.clone(),
+            compute_units: job.max_compute_units,
+            min_duration: job.min_duration,
+            // Claiming is optional; without it the clock starts when the job was posted
+            started_at: job.started_at.unwrap_or(job.timestamp),
+            submissions: Vec::new(),
+            settled_hash: None,
+        }
+    }
+
+    pub fn matches(&self, result_hash: &str) -> usize {
+        self.submissions.iter().filter(|s| s.result_hash == result_hash).count()
+    }
+
+    /// Count `worker_id`'s result, checked against the curve's time bounds, and
+    /// settle the job if it completes the quorum
+    pub fn record(
+        &mut self,
+        curve: &PouwRewardCurve,
+        worker_id: &str,
+        result_hash: &str,
+        now: u64,
+    ) -> Result<PouwOutcome, String> {
+        if self.settled_hash.is_some() {
+            return Ok(PouwOutcome::AlreadySettled);
+        }
+        if self.submissions.iter().any(|s| s.worker_id == worker_id) {
+            return Ok(PouwOutcome::Duplicate);
+        }
+        let elapsed_secs = now.saturating_sub(self.started_at);
+        curve.check_duration(elapsed_secs, self.min_duration)?;
+        self.submissions.push(ResultSubmission {
+            worker_id: worker_id.to_string(),
+            result_hash: result_hash.to_string(),
+            elapsed_secs,
+        });
+
+        let matches = self.matches(result_hash);
+        let required = curve.required_matches.max(1);
+        if matches < required {
+            return Ok(PouwOutcome::Pending { matches, required });
+        }
+        self.settled_hash = Some(result_hash.to_string());
+        let reward = curve.reward(self.compute_units);
+        let rewards = self
+            .submissions
+            .iter()
+            .filter(|s| s.result_hash == result_hash)
+            .map(|s| (s.worker_id.clone(), reward))
+            .collect();
+        Ok(PouwOutcome::Settled { rewards })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reward_needs_matching_results_within_bounds() {
+        let curve = PouwRewardCurve::default();
+        let mut job = ComputeJob::new("job1".into(), "admin".into(), "model".into(), vec![], 0);
+        job.timestamp = 1_000;
+        job.max_compute_units = 5_000;
+        let mut tally = PouwTally::new(&job);
+
+        // Too fast, then too slow
+        assert!(tally.record(&curve, "w1", "aa", 1_005).is_err());
+        assert!(tally.record(&curve, "w1", "aa", 1_000 + 3_601).is_err());
+
+        assert_eq!(tally.record(&curve, "w1", "aa", 1_020), Ok(PouwOutcome::Pending { matches: 1, required: 2 }));
+        assert_eq!(tally.record(&curve, "w1", "aa", 1_030), Ok(PouwOutcome::Duplicate));
+        assert_eq!(tally.record(&curve, "w2", "bb", 1_030), Ok(PouwOutcome::Pending { matches: 1, required: 2 }));
+
+        // 1 + 5000 * 2 / 1000, for both workers that agreed and not the dissenter
+        let settled = tally.record(&curve, "w3", "aa", 1_040).unwrap();
+        assert_eq!(settled, PouwOutcome::Settled { rewards: vec![("w1".into(), 11), ("w3".into(), 11)] });
+        assert_eq!(tally.record(&curve, "w4", "aa", 1_050), Ok(PouwOutcome::AlreadySettled));
+
+        // Declared units are capped
+        assert_eq!(curve.reward(10_000_000), 1 + 100_000 * 2 / 1000);
+    }
+}
@@ -0,0 +1,122 @@
+// Leader schedule
+//
+// Slots are grouped into epochs, and each epoch gets a fixed list of leaders:
+// slot `i` of the epoch is assigned by hashing the epoch number and `i` and
+// picking a validator with probability proportional to its stake. Every node
+// with the same validator set and stakes derives the same schedule, so any of
+// them can tell whose turn a slot is without talking to the others.
+use crate::encoding::sha256;
+use serde::{Deserialize, Serialize};
+
+/// Slots per epoch unless `[consensus] slots_per_epoch` says otherwise
+pub const DEFAULT_SLOTS_PER_EPOCH: u64 = 432;
+
+/// A validator and the stake its leader slots are weighted by
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ValidatorStake {
+    pub validator: String,
+    pub stake: u64,
+}
+
+/// Leaders for every slot of one epoch
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeaderSchedule {
+    pub epoch: u64,
+    pub slots_per_epoch: u64,
+    /// Leader of each slot, in slot order; empty when there were no validators
+    pub leaders: Vec<String>,
+}
+
+pub fn epoch_of(slot: u64, slots_per_epoch: u64) -> u64 {
+    slot / slots_per_epoch.max(1)
+}
+
+impl LeaderSchedule {
+    /// Stake-weighted schedule for `epoch`. Order of `validators` doesn't matter;
+    /// zero-stake entries are left out, and if every stake is zero the validators
+    /// take equal turns instead.
+    pub fn compute(epoch: u64, slots_per_epoch: u64, validators: &[ValidatorStake]) -> Self {
+        let slots_per_epoch = slots_per_epoch.max(1);
+        let mut set: Vec<ValidatorStake> = validators.to_vec();
+        set.sort_by(|a, b| a.validator.cmp(&b.validator));
+        set.dedup_by(|a, b| a.validator == b.validator);
+        if set.iter().all(|v| v.stake == 0) {
+            set.iter_mut().for_each(|v| v.stake = 1);
+        }
+        set.retain(|v| v.stake > 0);
+
+        let total: u128 = set.iter().map(|v| v.stake as u128).sum();
+        let leaders = if total == 0 {
+            Vec::new()
+        } else {
+            (0..slots_per_epoch)
+                .map(|i| {
+                    let mut seed = b"compass-leader-schedule".to_vec();
+                    seed.extend_from_slice(&epoch.to_le_bytes());
+                    seed.extend_from_slice(&i.to_le_bytes());
+                    let digest = sha256(&seed);
+                    let mut draw = u128::from_le_bytes(digest[..16].try_into().unwrap()) % total;
+                    set.iter()
+                        .find(|v| {
+                            if draw < v.stake as u128 {
+                                return true;
+                            }
+                            draw -= v.stake as u128;
+                            false
+                        })
+                        .map(|v| v.validator.clone())
+                        .unwrap_or_default()
+                })
+                .collect()
+        };
+        Self { epoch, slots_per_epoch, leaders }
+    }
+
+    pub fn first_slot(&self) -> u64 {
+        self.epoch.saturating_mul(self.slots_per_epoch)
+    }
+
+    /// Leader of `slot`, if it falls in this epoch and there is anyone to lead it
+    pub fn leader_at(&self, slot: u64) -> Option<&str> {
+        let offset = slot.checked_sub(self.first_slot())?;
+        self.leaders.get(usize::try_from(offset).ok()?).map(String::as_str)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stakes(entries: &[(&str, u64)]) -> Vec<ValidatorStake> {
+        entries.iter().map(|(v, s)| ValidatorStake { validator: v.to_string(), stake: *s }).collect()
+    }
+
+    #[test]
+    fn test_schedule_is_deterministic_and_stake_weighted() {
+        let set = stakes(&[("alice", 300), ("bob", 100), ("carol", 0)]);
+        let mut reversed = set.clone();
+        reversed.reverse();
+        let schedule = LeaderSchedule::compute(7, 1000, &set);
+        assert_eq!(schedule, LeaderSchedule::compute(7, 1000, &reversed));
+        assert_ne!(schedule.leaders, LeaderSchedule::compute(8, 1000, &set).leaders);
+
+        let alice = schedule.leaders.iter().filter(|l| *l == "alice").count();
+        let bob = schedule.leaders.iter().filter(|l| *l == "bob").count();
+        assert_eq!(alice + bob, 1000);
+        assert!((650..850).contains(&alice), "alice led {} of 1000 slots", alice);
+
+        assert_eq!(schedule.leader_at(7000), schedule.leaders.first().map(String::as_str));
+        assert_eq!(schedule.leader_at(6999), None);
+        assert_eq!(schedule.leader_at(8000), None);
+    }
+
+    #[test]
+    fn test_unstaked_validators_take_equal_turns() {
+        let schedule = LeaderSchedule::compute(0, 100, &stakes(&[("a", 0), ("b", 0)]));
+        assert_eq!(schedule.leaders.len(), 100);
+        assert!(schedule.leaders.iter().any(|l| l == "a") && schedule.leaders.iter().any(|l| l == "b"));
+
+        assert!(LeaderSchedule::compute(0, 100, &[]).leaders.is_empty());
+        assert_eq!(epoch_of(863, 432), 1);
+    }
+}
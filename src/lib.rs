@@ -1,4 +1,6 @@
 pub mod account; // v2.0 account-based system (must be before storage)
+pub mod amm;
+pub mod audit;
 pub mod block;
 pub mod chain;
 pub mod layer2;
@@ -6,6 +8,7 @@ pub mod error;
 pub mod client;
 pub mod crypto;
 pub mod genesis;
+pub mod governance;
 pub mod gulf_stream;
 pub mod market;
 pub mod poh_recorder;
@@ -14,6 +17,7 @@ pub mod oracle;
 pub mod rpc;
 pub mod storage;
 pub mod vault;
+pub mod vesting;
 pub mod layer3;
 pub mod vdf;
 pub mod wallet;
@@ -21,9 +25,25 @@ pub mod  worker_menu;
 pub mod cli;
 pub mod network;
 pub mod encoding;
+pub mod epoch_report;
+pub mod events;
+pub mod executor;
+pub mod export;
+pub mod faucet;
+pub mod fees;
+pub mod finality;
+pub mod hooks;
+pub mod fixtures;
+pub mod leader_schedule;
+pub mod liveness;
+pub mod merkle;
+pub mod replay;
+pub mod integrity;
 pub mod identity;
 pub mod interactive;
+#[cfg(feature = "ai")]
 pub mod trainer; // Rust Native AI
+pub mod treasury;
 pub mod init;
 pub mod node;
 pub mod config;
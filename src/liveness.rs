@@ -0,0 +1,108 @@
+// Slot liveness accounting
+//
+// Every slot is expected to produce exactly one PoH block. Slots are derived from
+// PoH block timestamps rather than tick numbers, because the VDF restarts its tick
+// count on every boot while wall-clock slots keep running through downtime. When
+// the gap between two PoH blocks spans more than one slot, the slots in between
+// were skipped and are charged to the leader that was scheduled for them.
+use serde::{Deserialize, Serialize};
+
+/// Validators scoring below this are reported to the operator
+pub const LIVENESS_ALERT_THRESHOLD: f64 = 0.9;
+
+/// Where slot accounting left off: the last slot that produced a PoH block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SlotCursor {
+    pub slot: u64,
+    pub timestamp_ms: u64,
+}
+
+/// A run of consecutive slots with no PoH block
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SkippedSlots {
+    pub first_slot: u64,
+    pub last_slot: u64,
+    pub leader: String,
+    pub detected_at_height: u64,
+}
+
+impl SkippedSlots {
+    pub fn count(&self) -> u64 {
+        self.last_slot - self.first_slot + 1
+    }
+}
+
+/// Expected vs. actual block production for one validator
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+pub struct LeaderLiveness {
+    pub validator: String,
+    pub slots_produced: u64,
+    pub slots_skipped: u64,
+    pub last_produced_slot: Option<u64>,
+    pub last_skipped_slot: Option<u64>,
+}
+
+impl LeaderLiveness {
+    pub fn new(validator: &str) -> Self {
+        Self { validator: validator.to_string(), ..Default::default() }
+    }
+
+    pub fn slots_expected(&self) -> u64 {
+        self.slots_produced.saturating_add(self.slots_skipped)
+    }
+
+    /// Fraction of scheduled slots actually produced (1.0 before any are scheduled)
+    pub fn score(&self) -> f64 {
+        match self.slots_expected() {
+            0 => 1.0,
+            expected => self.slots_produced as f64 / expected as f64,
+        }
+    }
+}
+
+/// Slot of a new PoH block, and the skipped slot range since the cursor (if any).
+///
+/// Elapsed time is rounded to the nearest slot so ordinary VDF jitter around a
+/// slot boundary is neither counted as a skip nor as two blocks in one slot.
+pub fn advance(cursor: Option<&SlotCursor>, timestamp_ms: u64, slot_duration_ms: u64) -> (u64, Option<(u64, u64)>) {
+    let slot_duration_ms = slot_duration_ms.max(1);
+    let Some(cursor) = cursor else {
+        return (timestamp_ms / slot_duration_ms, None);
+    };
+
+    let elapsed = timestamp_ms.saturating_sub(cursor.timestamp_ms);
+    let slots = ((elapsed + slot_duration_ms / 2) / slot_duration_ms).max(1);
+    let slot = cursor.slot + slots;
+    let skipped = (slots > 1).then(|| (cursor.slot + 1, slot - 1));
+    (slot, skipped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_advance_tolerates_jitter() {
+        let cursor = SlotCursor { slot: 100, timestamp_ms: 100_000 };
+        assert_eq!(advance(Some(&cursor), 101_400, 1000), (101, None));
+        assert_eq!(advance(Some(&cursor), 100_600, 1000), (101, None));
+        assert_eq!(advance(Some(&cursor), 100_000, 1000), (101, None));
+    }
+
+    #[test]
+    fn test_advance_reports_skipped_range() {
+        let cursor = SlotCursor { slot: 100, timestamp_ms: 100_000 };
+        assert_eq!(advance(Some(&cursor), 104_100, 1000), (104, Some((101, 103))));
+        assert_eq!(advance(None, 104_100, 1000), (104, None));
+    }
+
+    #[test]
+    fn test_score() {
+        let mut l = LeaderLiveness::new("v1");
+        assert_eq!(l.score(), 1.0);
+        l.slots_produced = 9;
+        l.slots_skipped = 1;
+        assert_eq!(l.slots_expected(), 10);
+        assert!((l.score() - 0.9).abs() < 1e-9);
+    }
+}
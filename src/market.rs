@@ -1,3 +1,4 @@
+use crate::events::StateEvent;
 use crate::wallet::WalletManager;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -8,6 +9,36 @@ pub enum OrderSide {
     Sell,
 }
 
+impl OrderSide {
+    /// (buyer, seller) of a fill whose taker is on this side
+    pub fn buyer_seller<'a>(&self, taker: &'a str, maker: &'a str) -> (&'a str, &'a str) {
+        match self {
+            OrderSide::Buy => (taker, maker),
+            OrderSide::Sell => (maker, taker),
+        }
+    }
+}
+
+/// Limit orders name a worst price; market orders take whatever the book offers
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OrderType {
+    #[default]
+    Limit,
+    Market,
+}
+
+/// What happens to the part of an order that doesn't fill on arrival
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TimeInForce {
+    /// Good 'til cancelled: the rest waits on the book
+    #[default]
+    Gtc,
+    /// Immediate or cancel: the rest is cancelled and its escrow refunded
+    Ioc,
+    /// Fill or kill: the whole amount fills at once or the order is refused
+    Fok,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Order {
     pub id: u64,
@@ -21,6 +52,325 @@ pub struct Order {
     pub timestamp: u64,
 }
 
+impl Order {
+    /// What the unfilled part still needs held back: quote for the worst-case cost
+    /// and fee of a buy, base for a sell
+    pub fn escrow_needed(&self, fees: &MarketFeeConfig) -> u64 {
+        let open = self.amount.saturating_sub(self.amount_filled);
+        match self.side {
+            OrderSide::Buy => {
+                let cost = notional(self.price, open).unwrap_or(u64::MAX);
+                cost.saturating_add(fees.max_fee(cost))
+            }
+            OrderSide::Sell => open,
+        }
+    }
+}
+
+/// Part of an account's balance locked by one of its open orders
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct OrderEscrow {
+    pub order_id: u64,
+    pub account: String,
+    pub asset: String,
+    pub amount: u64,
+}
+
+/// One fill between a buy and a sell order
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Trade {
+    pub trade_id: String, // "<buy order id>-<sell order id>"; a pair of orders fills at most once
+    pub pair_base: String,
+    pub pair_quote: String,
+    pub buy_order_id: u64,
+    pub sell_order_id: u64,
+    pub buyer: String,
+    pub seller: String,
+    pub price: u64,
+    pub amount: u64,
+    pub timestamp: u64,
+}
+
+impl Trade {
+    fn new(base: &str, quote: &str, buy: &Order, sell: &Order, price: u64, amount: u64, timestamp: u64) -> Self {
+        Self {
+            trade_id: format!("{}-{}", buy.id, sell.id),
+            pair_base: base.to_string(),
+            pair_quote: quote.to_string(),
+            buy_order_id: buy.id,
+            sell_order_id: sell.id,
+            buyer: buy.user.clone(),
+            seller: sell.user.clone(),
+            price,
+            amount,
+            timestamp,
+        }
+    }
+}
+
+/// Proof of execution for one trade: the fill, the chain head it settled against and the node's signature over both
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TradeReceipt {
+    pub trade: Trade,
+    pub block_height: u64,
+    pub block_hash: String,
+    pub node_pubkey: String,
+    pub signature: String,
+}
+
+impl TradeReceipt {
+    /// Bytes the node signs: canonical encoding of the trade and the block reference
+    pub fn signing_message(trade: &Trade, block_height: u64, block_hash: &str) -> Vec<u8> {
+        let mut msg = b"COMPASS_TRADE_RECEIPT".to_vec();
+        msg.extend(crate::encoding::canonical_bytes(&(trade, block_height, block_hash)).unwrap_or_default());
+        msg
+    }
+
+    pub fn verify(&self) -> bool {
+        let msg = Self::signing_message(&self.trade, self.block_height, &self.block_hash);
+        crate::crypto::verify_with_pubkey_hex(&msg, &self.signature, &self.node_pubkey)
+    }
+}
+
+/// Quote units owed for `amount` base units at `price`; None if it does not fit in a u64
+pub fn notional(price: u64, amount: u64) -> Option<u64> {
+    price.checked_mul(amount)
+}
+
+/// `[consensus.market_fees]`: what each side of a fill pays, in basis points of
+/// the fill's quote value. Every validator must use the same rates, since the
+/// leader's Trade blocks are checked against them.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct MarketFeeConfig {
+    /// Rate paid by the resting order's owner
+    #[serde(default)]
+    pub maker_fee_bps: u64,
+    /// Rate paid by the incoming order's owner
+    #[serde(default)]
+    pub taker_fee_bps: u64,
+}
+
+impl MarketFeeConfig {
+    /// (maker fee, taker fee) in quote units for `amount` base units at `price`
+    pub fn fees(&self, price: u64, amount: u64) -> (u64, u64) {
+        let value = price as u128 * amount as u128;
+        let fee = |bps: u64| u64::try_from(value * bps as u128 / 10_000).unwrap_or(u64::MAX);
+        (fee(self.maker_fee_bps), fee(self.taker_fee_bps))
+    }
+
+    /// The larger fee either side could owe on `value` quote units
+    pub fn max_fee(&self, value: u64) -> u64 {
+        let (maker, taker) = self.fees(value, 1);
+        maker.max(taker)
+    }
+}
+
+/// One settled fill, as the trade history index reads it back from its Trade block
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TradeTick {
+    pub trade_id: String,
+    pub height: u64,
+    /// The Trade block's timestamp (ms)
+    pub timestamp: u64,
+    pub price: u64,
+    pub amount: u64,
+    /// Quote units the maker and taker paid in fees
+    #[serde(default)]
+    pub maker_fee: u64,
+    #[serde(default)]
+    pub taker_fee: u64,
+}
+
+impl TradeTick {
+    pub fn from_header(header: &crate::block::BlockHeader) -> Option<Self> {
+        let crate::block::BlockType::Trade { trade_id, price, amount, maker_fee, taker_fee, .. } = &header.block_type else {
+            return None;
+        };
+        Some(Self {
+            trade_id: trade_id.clone(),
+            height: header.index,
+            timestamp: header.timestamp,
+            price: *price,
+            amount: *amount,
+            maker_fee: *maker_fee,
+            taker_fee: *taker_fee,
+        })
+    }
+}
+
+/// Window `Ticker` stats cover
+pub const TICKER_WINDOW_MS: u64 = 24 * 3600 * 1000;
+
+/// Last price of a pair and its stats over the trailing `TICKER_WINDOW_MS`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct Ticker {
+    pub pair: String,
+    /// Price of the most recent fill, however old; None if the pair never traded
+    pub last_price: Option<u64>,
+    pub last_trade_ms: Option<u64>,
+    /// Base units traded in the window
+    pub volume_24h: u64,
+    /// Quote units traded in the window
+    pub quote_volume_24h: u64,
+    pub high_24h: Option<u64>,
+    pub low_24h: Option<u64>,
+    pub trades_24h: usize,
+}
+
+impl Ticker {
+    /// `recent` holds the fills inside the window, `last` the most recent fill
+    pub fn new(pair: &str, last: Option<&TradeTick>, recent: &[TradeTick]) -> Self {
+        Self {
+            pair: pair.to_string(),
+            last_price: last.map(|t| t.price),
+            last_trade_ms: last.map(|t| t.timestamp),
+            volume_24h: recent.iter().fold(0u64, |v, t| v.saturating_add(t.amount)),
+            quote_volume_24h: recent.iter().fold(0u64, |v, t| v.saturating_add(notional(t.price, t.amount).unwrap_or(u64::MAX))),
+            high_24h: recent.iter().map(|t| t.price).max(),
+            low_24h: recent.iter().map(|t| t.price).min(),
+            trades_24h: recent.len(),
+        }
+    }
+}
+
+/// Fees a pair's settled fills have paid since it first traded
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MarketStats {
+    pub pair: String,
+    pub trades: usize,
+    /// Base units traded
+    pub volume: u64,
+    /// Quote units traded
+    pub quote_volume: u64,
+    /// Quote units paid by makers and by takers
+    pub maker_fees: u64,
+    pub taker_fees: u64,
+}
+
+impl MarketStats {
+    pub fn new(pair: &str, trades: &[TradeTick]) -> Self {
+        let sum = |f: &dyn Fn(&TradeTick) -> u64| trades.iter().fold(0u64, |v, t| v.saturating_add(f(t)));
+        Self {
+            pair: pair.to_string(),
+            trades: trades.len(),
+            volume: sum(&|t| t.amount),
+            quote_volume: sum(&|t| notional(t.price, t.amount).unwrap_or(u64::MAX)),
+            maker_fees: sum(&|t| t.maker_fee),
+            taker_fees: sum(&|t| t.taker_fee),
+        }
+    }
+}
+
+/// OHLCV bar for one interval; `open_time` is the interval's start (ms)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Candle {
+    pub open_time: u64,
+    pub open: u64,
+    pub high: u64,
+    pub low: u64,
+    pub close: u64,
+    /// Base units traded
+    pub volume: u64,
+    pub trades: usize,
+}
+
+/// Most candles one `getCandles` call returns
+pub const MAX_CANDLES: u64 = 1_000;
+
+/// Bucket `trades` (oldest first) into `interval_ms` candles aligned to the Unix
+/// epoch. Intervals without a trade are left out.
+pub fn candles(trades: &[TradeTick], interval_ms: u64) -> Vec<Candle> {
+    let mut out: Vec<Candle> = Vec::new();
+    if interval_ms == 0 {
+        return out;
+    }
+    for t in trades {
+        let open_time = t.timestamp - t.timestamp % interval_ms;
+        match out.last_mut() {
+            Some(c) if c.open_time == open_time => {
+                c.high = c.high.max(t.price);
+                c.low = c.low.min(t.price);
+                c.close = t.price;
+                c.volume = c.volume.saturating_add(t.amount);
+                c.trades += 1;
+            }
+            _ => out.push(Candle {
+                open_time,
+                open: t.price,
+                high: t.price,
+                low: t.price,
+                close: t.price,
+                volume: t.amount,
+                trades: 1,
+            }),
+        }
+    }
+    out
+}
+
+/// Milliseconds in a period written as a count and a unit: `15m`, `4h`, `1d`, `1w`
+pub fn parse_period_ms(period: &str) -> Option<u64> {
+    let unit_ms = match period.chars().last()? {
+        'm' => 60_000,
+        'h' => 3_600_000,
+        'd' => 86_400_000,
+        'w' => 7 * 86_400_000,
+        _ => return None,
+    };
+    let count: u64 = period[..period.len() - 1].parse().ok()?;
+    count.checked_mul(unit_ms).filter(|ms| *ms > 0)
+}
+
+/// Resting quantity at one price
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct PriceLevel {
+    pub price: u64,
+    /// Unfilled base units
+    pub amount: u64,
+    pub orders: usize,
+}
+
+/// Result of placing an order: the new order id, matching log lines and the fills it produced
+#[derive(Debug, Clone)]
+pub struct OrderOutcome {
+    pub order_id: u64,
+    /// The incoming order's side, which makes it the taker of every trade
+    pub side: OrderSide,
+    pub logs: Vec<String>,
+    pub trades: Vec<Trade>,
+    /// Base units filled on arrival
+    pub filled: u64,
+    /// Base units cancelled rather than left on the book (IOC and market orders)
+    pub cancelled: u64,
+    /// Fills and cancellations, for the transaction's receipt
+    pub events: Vec<StateEvent>,
+    /// Rates the fills are charged
+    pub fees: MarketFeeConfig,
+}
+
+impl OrderOutcome {
+    /// The `BlockType::Trade` that settles `trade`, one of this order's fills
+    pub fn settlement(&self, trade: &Trade) -> crate::block::BlockType {
+        let (maker, taker) = match self.side {
+            OrderSide::Buy => (&trade.seller, &trade.buyer),
+            OrderSide::Sell => (&trade.buyer, &trade.seller),
+        };
+        let (maker_fee, taker_fee) = self.fees.fees(trade.price, trade.amount);
+        crate::block::BlockType::Trade {
+            trade_id: trade.trade_id.clone(),
+            maker: maker.clone(),
+            taker: taker.clone(),
+            taker_side: self.side.clone(),
+            base: trade.pair_base.clone(),
+            quote: trade.pair_quote.clone(),
+            amount: trade.amount,
+            price: trade.price,
+            maker_fee,
+            taker_fee,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct OrderBook {
     pub base_asset: String,
@@ -39,9 +389,70 @@ impl OrderBook {
         }
     }
 
-    /// Add order and attempt matching
-    pub fn add_order(&mut self, mut order: Order, wallets: &mut WalletManager) -> Vec<String> {
+    /// The best `levels` price levels on each side: bids highest first, asks lowest first
+    pub fn depth(&self, levels: usize) -> (Vec<PriceLevel>, Vec<PriceLevel>) {
+        fn aggregate(orders: &[Order], descending: bool, levels: usize) -> Vec<PriceLevel> {
+            let mut by_price: std::collections::BTreeMap<u64, PriceLevel> = std::collections::BTreeMap::new();
+            for o in orders {
+                let level = by_price.entry(o.price).or_insert(PriceLevel { price: o.price, amount: 0, orders: 0 });
+                level.amount = level.amount.saturating_add(o.amount - o.amount_filled);
+                level.orders += 1;
+            }
+            let mut out: Vec<PriceLevel> = by_price.into_values().collect();
+            if descending {
+                out.reverse();
+            }
+            out.truncate(levels);
+            out
+        }
+        (aggregate(&self.bids, true, levels), aggregate(&self.asks, false, levels))
+    }
+
+    /// The resting order `order_id`
+    pub fn get_order(&self, order_id: u64) -> Option<&Order> {
+        self.bids.iter().chain(self.asks.iter()).find(|o| o.id == order_id)
+    }
+
+    /// Take the resting order `order_id` off the book
+    pub fn remove_order(&mut self, order_id: u64) -> Option<Order> {
+        for orders in [&mut self.bids, &mut self.asks] {
+            if let Some(i) = orders.iter().position(|o| o.id == order_id) {
+                return Some(orders.remove(i));
+            }
+        }
+        None
+    }
+
+    /// Base units an incoming order could fill right now at `limit` or better (up
+    /// to `amount`) and the quote they trade for; None if that overflows
+    pub fn fillable(&self, side: &OrderSide, limit: u64, amount: u64) -> Option<(u64, u64)> {
+        // Same priority add_order matches in
+        let mut resting: Vec<&Order> = match side {
+            OrderSide::Buy => self.asks.iter().filter(|o| o.price <= limit).collect(),
+            OrderSide::Sell => self.bids.iter().filter(|o| o.price >= limit).collect(),
+        };
+        match side {
+            OrderSide::Buy => resting.sort_by_key(|o| o.price),
+            OrderSide::Sell => resting.sort_by_key(|o| std::cmp::Reverse(o.price)),
+        }
+        let (mut filled, mut quote) = (0u64, 0u64);
+        for o in resting {
+            if filled == amount {
+                break;
+            }
+            let take = std::cmp::min(amount - filled, o.amount - o.amount_filled);
+            quote = quote.checked_add(notional(o.price, take)?)?;
+            filled += take;
+        }
+        Some((filled, quote))
+    }
+
+    /// Add order and attempt matching; returns log lines and the trades executed.
+    /// Whatever doesn't fill rests on the book only for `Gtc` orders. Matching
+    /// moves no funds: each trade settles later as a `BlockType::Trade` block.
+    pub fn add_order(&mut self, mut order: Order, time_in_force: TimeInForce) -> (Vec<String>, Vec<Trade>) {
         let mut logs = Vec::new();
+        let mut trades = Vec::new();
         logs.push(format!(
             "Order Placed: {:?} {} {} @ {}",
             order.side, order.amount, order.pair_base, order.price
@@ -62,27 +473,16 @@ impl OrderBook {
                         order.amount - order.amount_filled,
                         ask.amount - ask.amount_filled,
                     );
-                    let cost = fill_amt * ask.price;
-
-                    // Execute Swap in Wallets
-                    // Buyer (order.user) gets Base, pays Quote
-                    // Seller (ask.user) pays Base, gets Quote
-                    wallets.credit(&order.user, &self.base_asset, fill_amt);
-                    // wallets.debit(&order.user, &self.quote_asset, cost); // Already escrowed? For simplicity, debit now or assume escrow.
-                    // Let's assume user must have balance NOW. logic is slightly complex for atomic.
-                    // For prototype: we assume "Hold" logic is external or implicit.
-                    // To be safe: we actually transfer NOW.
-                    wallets.credit(&ask.user, &self.quote_asset, cost);
-
-                    // Note: Sellers base was escrowed or debited?
-                    // Buyers quote was escrowed or debited?
-                    // Implementation Detail: In `place_order`, we should DEBIT the active asset immediately.
-                    // So here we only CREDIT the counterparty.
+                    // Trade blocks carry price and amount; their product must fit
+                    if notional(ask.price, fill_amt).is_none() {
+                        break;
+                    }
 
                     logs.push(format!(
                         "MATCH: Sold {} {} @ {}",
                         fill_amt, self.base_asset, ask.price
                     ));
+                    trades.push(Trade::new(&self.base_asset, &self.quote_asset, &order, ask, ask.price, fill_amt, order.timestamp));
 
                     order.amount_filled += fill_amt;
                     ask.amount_filled += fill_amt;
@@ -101,7 +501,7 @@ impl OrderBook {
             }
 
             // If remaining, add to Bids
-            if order.amount_filled < order.amount {
+            if order.amount_filled < order.amount && time_in_force == TimeInForce::Gtc {
                 self.bids.push(order);
             }
         } else {
@@ -117,17 +517,15 @@ impl OrderBook {
                         order.amount - order.amount_filled,
                         bid.amount - bid.amount_filled,
                     );
-                    let cost = fill_amt * bid.price;
-
-                    // Seller (order.user) gets Quote
-                    wallets.credit(&order.user, &self.quote_asset, cost);
-                    // Buyer (bid.user) gets Base
-                    wallets.credit(&bid.user, &self.base_asset, fill_amt);
+                    if notional(bid.price, fill_amt).is_none() {
+                        break;
+                    }
 
                     logs.push(format!(
                         "MATCH: Bought {} {} @ {}",
                         fill_amt, self.base_asset, bid.price
                     ));
+                    trades.push(Trade::new(&self.base_asset, &self.quote_asset, bid, &order, bid.price, fill_amt, order.timestamp));
 
                     order.amount_filled += fill_amt;
                     bid.amount_filled += fill_amt;
@@ -142,12 +540,12 @@ impl OrderBook {
                 }
             }
 
-            if order.amount_filled < order.amount {
+            if order.amount_filled < order.amount && time_in_force == TimeInForce::Gtc {
                 self.asks.push(order);
             }
         }
 
-        logs
+        (logs, trades)
     }
 }
 
@@ -170,6 +568,9 @@ pub struct Market {
     pub next_order_id: u64,
     #[serde(skip)]
     pub storage: Option<std::sync::Arc<crate::storage::Storage>>,
+    /// Maker and taker rates (set from consensus config)
+    #[serde(skip)]
+    pub fees: MarketFeeConfig,
 }
 
 impl std::fmt::Debug for Market {
@@ -189,6 +590,7 @@ impl Market {
             nft_listings: HashMap::new(),
             next_order_id: 1,
             storage: None,
+            fees: MarketFeeConfig::default(),
         }
     }
 
@@ -254,6 +656,14 @@ impl Market {
         }
     }
 
+    /// Place an order for `amount` base units. `price` is the limit in quote units
+    /// per base unit; `None` makes a market order, which sweeps the book and never
+    /// rests (GTC is treated as IOC). The user's ledger balance in `storage` must
+    /// cover the seller's base or the buyer's worst-case quote plus fee, on top of
+    /// what its other open orders hold in escrow; that much is locked until the
+    /// order fills or is cancelled. Nothing moves here: the caller settles each
+    /// returned trade as a `BlockType::Trade` block.
+    #[allow(clippy::too_many_arguments)]
     pub fn place_order(
         &mut self,
         user: &str,
@@ -261,31 +671,57 @@ impl Market {
         base: &str,
         quote: &str,
         amount: u64,
-        price: u64,
-        wallets: &mut WalletManager,
-    ) -> Result<Vec<String>, String> {
+        price: Option<u64>,
+        time_in_force: TimeInForce,
+        storage: &crate::storage::Storage,
+    ) -> Result<OrderOutcome, String> {
         let pair_key = format!("{}/{}", base, quote);
+        let time_in_force = match (price, time_in_force) {
+            (None, TimeInForce::Gtc) => TimeInForce::Ioc,
+            (_, tif) => tif,
+        };
+        // Market orders cross every price on the other side
+        let limit = price.unwrap_or(if side == OrderSide::Buy { u64::MAX } else { 0 });
 
-        // 1. Check Balance / Escrow
-        // If Buying: Need Quote Asset (Price * Amount)
-        // If Selling: Need Base Asset (Amount)
-        let cost = if side == OrderSide::Buy {
-            price * amount
-        } else {
-            0
+        let (fillable, fill_quote) = match self.books.get(&pair_key) {
+            Some(book) => book
+                .fillable(&side, limit, amount)
+                .ok_or_else(|| format!("Order value {} x {} overflows.", amount, limit))?,
+            None => (0, 0),
         };
-        let req_asset = if side == OrderSide::Buy { quote } else { base };
-        let req_amt = if side == OrderSide::Buy { cost } else { amount };
+        if time_in_force == TimeInForce::Fok && fillable < amount {
+            return Err(format!("Fill-or-kill order for {} {} can only fill {} now.", amount, base, fillable));
+        }
 
-        if !wallets.debit(user, req_asset, req_amt) {
-            return Err(format!("Insufficient {} balance.", req_asset));
+        // 1. Check Balance
+        // If Buying: Need Quote Asset (Price * Amount, or what the sweep costs, plus the fee)
+        // If Selling: Need Base Asset (Amount); the seller's fee comes out of the proceeds
+        let required = match (&side, price) {
+            (OrderSide::Buy, Some(price)) => {
+                notional(price, amount).ok_or_else(|| format!("Order value {} x {} overflows.", amount, price))?
+            }
+            (OrderSide::Buy, None) => fill_quote,
+            (OrderSide::Sell, _) => amount,
+        };
+        let required = match side {
+            OrderSide::Buy => required
+                .checked_add(self.fees.max_fee(required))
+                .ok_or_else(|| format!("Order value {} plus fees overflows.", required))?,
+            OrderSide::Sell => required,
+        };
+        let req_asset = if side == OrderSide::Buy { quote } else { base };
+        let available = storage.get_free_balance(user, req_asset).map_err(|e| e.to_string())?;
+        if available < required {
+            return Err(format!("Insufficient {} balance: has {} free, needs {}.", req_asset, available, required));
         }
-        // Note: Wallet debited. We should save wallet state?
-        // Caller often saves wallet. Or we can trigger it here if wallets helper allows?
-        // wallets.save("wallets.json"); // Uses internal storage if present.
-        // But let's leave it to caller/Node loop to not spam IO. 
-        // Although this is critical financial op. 
-        // For now, adhere to explicit save flow.
+        storage
+            .lock_escrow(&OrderEscrow {
+                order_id: self.next_order_id,
+                account: user.to_string(),
+                asset: req_asset.to_string(),
+                amount: required,
+            })
+            .map_err(|e| e.to_string())?;
 
         let book = self
             .books
@@ -297,25 +733,89 @@ impl Market {
             user: user.to_string(),
             pair_base: base.to_string(),
             pair_quote: quote.to_string(),
-            side,
-            price,
+            side: side.clone(),
+            price: limit,
             amount,
             amount_filled: 0,
-            timestamp: 0, // TODO: Time
+            timestamp: crate::block::current_unix_timestamp_ms(),
         };
+        let order_id = order.id;
         self.next_order_id += 1;
         if let Some(s) = &self.storage {
             let _ = s.save_market_meta(self.next_order_id);
         }
 
-        let logs = book.add_order(order, wallets);
+        let (mut logs, trades) = book.add_order(order, time_in_force);
+
+        // Each order touched keeps only the escrow its unfilled part needs; the rest
+        // is freed for the Trade blocks to move, and an order off the book keeps none
+        let touched = trades
+            .iter()
+            .map(|t| if side == OrderSide::Buy { t.sell_order_id } else { t.buy_order_id })
+            .chain(std::iter::once(order_id));
+        for id in touched {
+            let needed = book.get_order(id).map_or(0, |o| o.escrow_needed(&self.fees));
+            let held = storage.get_order_escrow(id).map_err(|e| e.to_string())?.map_or(0, |e| e.amount);
+            storage.release_escrow(id, held.saturating_sub(needed)).map_err(|e| e.to_string())?;
+        }
+
+        // Partial-fill accounting: whatever neither filled nor rests is dropped
+        let filled: u64 = trades.iter().map(|t| t.amount).sum();
+        let cancelled = if time_in_force == TimeInForce::Gtc { 0 } else { amount - filled };
+
+        let mut events = Vec::new();
+        for t in &trades {
+            let maker = if side == OrderSide::Buy { (t.sell_order_id, &t.seller) } else { (t.buy_order_id, &t.buyer) };
+            for (id, account) in [(order_id, user), (maker.0, maker.1.as_str())] {
+                events.push(StateEvent::OrderFilled {
+                    order_id: id,
+                    account: account.to_string(),
+                    trade_id: t.trade_id.clone(),
+                    price: t.price,
+                    amount: t.amount,
+                });
+            }
+        }
+        if cancelled > 0 {
+            logs.push(format!("CANCELLED: {} {} unfilled ({:?})", cancelled, base, time_in_force));
+            events.push(StateEvent::OrderCancelled {
+                order_id,
+                account: user.to_string(),
+                filled,
+                cancelled,
+            });
+        }
         
         // Persist Book Updates
         if let Some(s) = &self.storage {
              let _ = s.save_order_book(&pair_key, book);
         }
         
-        Ok(logs)
+        Ok(OrderOutcome { order_id, side, logs, trades, filled, cancelled, events, fees: self.fees })
+    }
+
+    /// Take `user`'s resting order `order_id` off its book and release its escrow
+    pub fn cancel_order(&mut self, user: &str, order_id: u64, storage: &crate::storage::Storage) -> Result<StateEvent, String> {
+        let (pair_key, book) = self
+            .books
+            .iter_mut()
+            .find(|(_, book)| book.get_order(order_id).is_some())
+            .ok_or_else(|| format!("Order {} is not open.", order_id))?;
+        if book.get_order(order_id).is_some_and(|o| o.user != user) {
+            return Err(format!("Order {} belongs to another account.", order_id));
+        }
+        let order = book.remove_order(order_id).expect("found above");
+        storage.release_escrow(order_id, u64::MAX).map_err(|e| e.to_string())?;
+        if let Some(s) = &self.storage {
+            let _ = s.save_order_book(pair_key, book);
+        }
+
+        Ok(StateEvent::OrderCancelled {
+            order_id,
+            account: user.to_string(),
+            filled: order.amount_filled,
+            cancelled: order.amount - order.amount_filled,
+        })
     }
 
     // --- NFT Marketplace Methods ---
@@ -403,3 +903,270 @@ impl Market {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::crypto::KeyPair;
+    use crate::storage::Storage;
+
+    fn funded(balances: &[(&str, &str, u64)]) -> Storage {
+        let storage = Storage::new_in_memory().unwrap();
+        for (account, asset, amount) in balances {
+            storage.writer().credit(account, asset, *amount).unwrap();
+        }
+        storage
+    }
+
+    /// Run `outcome`'s fills through the executor, as their Trade blocks would be
+    fn settle(storage: &Storage, outcome: &OrderOutcome) {
+        let mut vaults = crate::vault::VaultManager::new();
+        for trade in &outcome.trades {
+            let header = crate::block::BlockHeader {
+                index: 0,
+                block_type: outcome.settlement(trade),
+                proposer: "leader".to_string(),
+                signature_hex: String::new(),
+                prev_hash: String::new(),
+                hash: String::new(),
+                timestamp: 0,
+                state_root: String::new(),
+                root_attestation: None,
+            };
+            let mut state = crate::executor::State {
+                storage,
+                vaults: &mut vaults,
+                treasury: Default::default(),
+                stability_fee: Default::default(),
+                slot_duration_ms: 1000,
+                emission: Default::default(),
+                slots_per_epoch: 1,
+            };
+            crate::executor::apply_block(&header, &mut state).unwrap();
+        }
+    }
+
+    /// Escrowed plus free is the whole balance, and the escrow is what the open orders still need
+    fn assert_escrow_invariant(storage: &Storage, market: &Market, holdings: &[(&str, &str)]) {
+        for (account, asset) in holdings {
+            let total = storage.get_balance(account, asset).unwrap();
+            let escrowed = storage.get_escrowed(account, asset).unwrap();
+            let free = storage.get_free_balance(account, asset).unwrap();
+            assert_eq!(escrowed + free, total, "{} {}", account, asset);
+            let needed: u64 = market
+                .books
+                .values()
+                .flat_map(|book| book.bids.iter().chain(book.asks.iter()))
+                .filter(|o| o.user == *account)
+                .filter(|o| *asset == if o.side == OrderSide::Buy { &o.pair_quote } else { &o.pair_base })
+                .map(|o| o.escrow_needed(&market.fees))
+                .sum();
+            assert_eq!(escrowed, needed, "{} {}", account, asset);
+        }
+    }
+
+    #[test]
+    fn test_open_orders_hold_their_funds_in_escrow() {
+        let storage = funded(&[("alice", "LTC", 10), ("bob", "Compass", 1_000), ("carol", "LTC", 5)]);
+        let holdings = [("alice", "LTC"), ("alice", "Compass"), ("bob", "Compass"), ("bob", "LTC"), ("carol", "LTC"), ("carol", "Compass")];
+        let mut market = Market::new();
+        market.fees = MarketFeeConfig { maker_fee_bps: 100, taker_fee_bps: 200 };
+
+        market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 6, Some(50), TimeInForce::Gtc, &storage).unwrap();
+        assert_eq!(storage.get_free_balance("alice", "LTC").unwrap(), 4);
+        assert_escrow_invariant(&storage, &market, &holdings);
+        // What one order locked can't back another
+        let err = market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 5, Some(50), TimeInForce::Gtc, &storage).unwrap_err();
+        assert!(err.contains("has 4 free"), "{}", err);
+
+        // 6 fill at 50 for 300 plus a 6 fee; the other 4 rest at 60, holding 240 plus a 4 fee
+        let bid = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 10, Some(60), TimeInForce::Gtc, &storage).unwrap();
+        settle(&storage, &bid);
+        assert_eq!(storage.get_balance("bob", "Compass").unwrap(), 694);
+        assert_eq!(storage.get_escrowed("bob", "Compass").unwrap(), 244);
+        assert_eq!(storage.get_order_escrow(bid.order_id).unwrap().map(|e| e.amount), Some(244));
+        assert_escrow_invariant(&storage, &market, &holdings);
+        assert!(market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 5, Some(100), TimeInForce::Gtc, &storage).is_err());
+
+        // carol's sell fills 3 of bob's resting bid
+        let ask = market.place_order("carol", OrderSide::Sell, "LTC", "Compass", 3, Some(55), TimeInForce::Ioc, &storage).unwrap();
+        settle(&storage, &ask);
+        assert_eq!(storage.get_escrowed("bob", "Compass").unwrap(), 61);
+        assert_escrow_invariant(&storage, &market, &holdings);
+
+        // Cancelling releases the rest, and only the owner may cancel
+        assert!(market.cancel_order("alice", bid.order_id, &storage).is_err());
+        let cancelled = market.cancel_order("bob", bid.order_id, &storage).unwrap();
+        assert!(matches!(cancelled, StateEvent::OrderCancelled { filled: 9, cancelled: 1, .. }));
+        assert!(market.cancel_order("bob", bid.order_id, &storage).is_err());
+        assert_eq!(storage.get_order_escrow(bid.order_id).unwrap(), None);
+        assert_eq!(storage.get_free_balance("bob", "Compass").unwrap(), storage.get_balance("bob", "Compass").unwrap());
+        assert_escrow_invariant(&storage, &market, &holdings);
+    }
+
+    #[test]
+    fn test_match_produces_verifiable_receipt() {
+        let storage = funded(&[("alice", "LTC", 10), ("bob", "Compass", 1_000)]);
+
+        let mut market = Market::new();
+        let ask = market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 10, Some(50), TimeInForce::Gtc, &storage).unwrap();
+        assert!(ask.trades.is_empty());
+        let bid = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 4, Some(60), TimeInForce::Gtc, &storage).unwrap();
+
+        assert_eq!(bid.trades.len(), 1);
+        let trade = bid.trades[0].clone();
+        assert_eq!((trade.buyer.as_str(), trade.seller.as_str()), ("bob", "alice"));
+        assert_eq!((trade.price, trade.amount), (50, 4));
+        assert_eq!(trade.trade_id, format!("{}-{}", bid.order_id, ask.order_id));
+        // Matching moves nothing; the buyer is the taker of the settlement block
+        assert_eq!(storage.get_balance("alice", "Compass").unwrap(), 0);
+        assert!(matches!(
+            bid.settlement(&trade),
+            crate::block::BlockType::Trade { ref maker, ref taker, taker_side: OrderSide::Buy, amount: 4, price: 50, .. }
+                if maker == "alice" && taker == "bob"
+        ));
+
+        let node = KeyPair::generate();
+        let msg = TradeReceipt::signing_message(&trade, 7, "head");
+        let mut receipt = TradeReceipt {
+            trade,
+            block_height: 7,
+            block_hash: "head".into(),
+            node_pubkey: node.public_key_hex(),
+            signature: node.sign_hex(&msg),
+        };
+        assert!(receipt.verify());
+        receipt.trade.amount = 5;
+        assert!(!receipt.verify());
+    }
+
+    #[test]
+    fn test_market_ioc_and_fok_orders() {
+        let storage = funded(&[("alice", "LTC", 10), ("bob", "Compass", 1_000)]);
+        let mut market = Market::new();
+        market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 3, Some(50), TimeInForce::Gtc, &storage).unwrap();
+        market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 3, Some(70), TimeInForce::Gtc, &storage).unwrap();
+
+        // FOK for more than the book holds is refused before anything matches
+        let err = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 7, None, TimeInForce::Fok, &storage).unwrap_err();
+        assert!(err.contains("can only fill 6"), "{}", err);
+        assert_eq!(market.books["LTC/Compass"].asks.len(), 2);
+
+        // IOC limit at 60 takes the 50 level and cancels the rest
+        let ioc = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 5, Some(60), TimeInForce::Ioc, &storage).unwrap();
+        assert_eq!((ioc.filled, ioc.cancelled), (3, 2));
+        assert!(market.books["LTC/Compass"].bids.is_empty());
+        assert!(matches!(ioc.events.last(), Some(StateEvent::OrderCancelled { filled: 3, cancelled: 2, .. })));
+        let fills = ioc.events.iter().filter(|e| matches!(e, StateEvent::OrderFilled { amount: 3, price: 50, .. })).count();
+        assert_eq!(fills, 2); // taker and maker
+
+        // A market buy sweeps what's left, needing only what the sweep costs
+        let poor = funded(&[("bob", "Compass", 209)]);
+        assert!(market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 5, None, TimeInForce::Gtc, &poor).is_err());
+        let sweep = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 5, None, TimeInForce::Gtc, &storage).unwrap();
+        assert_eq!((sweep.filled, sweep.cancelled), (3, 2));
+        assert_eq!(sweep.trades[0].price, 70);
+        assert!(market.books["LTC/Compass"].asks.is_empty());
+
+        // A market sell into an empty book cancels everything
+        let none = market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 2, None, TimeInForce::Ioc, &storage).unwrap();
+        assert_eq!((none.filled, none.cancelled), (0, 2));
+        assert!(market.books["LTC/Compass"].asks.is_empty());
+    }
+
+    #[test]
+    fn test_depth_ticker_and_candles() {
+        let storage = funded(&[("alice", "LTC", 10), ("bob", "Compass", 1_000)]);
+        let mut market = Market::new();
+        for (user, side, amount, price) in [
+            ("alice", OrderSide::Sell, 2, 70),
+            ("alice", OrderSide::Sell, 3, 70),
+            ("alice", OrderSide::Sell, 1, 80),
+            ("bob", OrderSide::Buy, 4, 50),
+            ("bob", OrderSide::Buy, 1, 60),
+        ] {
+            market.place_order(user, side, "LTC", "Compass", amount, Some(price), TimeInForce::Gtc, &storage).unwrap();
+        }
+        let (bids, asks) = market.books["LTC/Compass"].depth(1);
+        assert_eq!(bids, vec![PriceLevel { price: 60, amount: 1, orders: 1 }]);
+        assert_eq!(asks, vec![PriceLevel { price: 70, amount: 5, orders: 2 }]);
+        assert_eq!(market.books["LTC/Compass"].depth(10).1.len(), 2);
+
+        let tick = |timestamp: u64, price: u64, amount: u64| TradeTick {
+            trade_id: String::new(),
+            height: 0,
+            timestamp,
+            price,
+            amount,
+            maker_fee: 0,
+            taker_fee: 0,
+        };
+        let trades = [tick(60_000, 10, 1), tick(90_000, 14, 2), tick(110_000, 9, 1), tick(200_000, 12, 5)];
+        let bars = candles(&trades, 60_000);
+        assert_eq!(bars.len(), 2); // 120_000-180_000 had no trades
+        assert_eq!(bars[0], Candle { open_time: 60_000, open: 10, high: 14, low: 9, close: 9, volume: 4, trades: 3 });
+        assert_eq!((bars[1].open_time, bars[1].close), (180_000, 12));
+
+        let ticker = Ticker::new("LTC/Compass", trades.last(), &trades[1..]);
+        assert_eq!((ticker.last_price, ticker.high_24h, ticker.low_24h), (Some(12), Some(14), Some(9)));
+        assert_eq!((ticker.volume_24h, ticker.quote_volume_24h, ticker.trades_24h), (8, 97, 3));
+        assert_eq!(Ticker::new("LTC/Compass", None, &[]).last_price, None);
+
+        assert_eq!(parse_period_ms("15m"), Some(900_000));
+        assert_eq!(parse_period_ms("1w"), Some(604_800_000));
+        for bad in ["", "m", "0h", "5x", "-1d"] {
+            assert_eq!(parse_period_ms(bad), None, "{}", bad);
+        }
+    }
+
+    #[test]
+    fn test_fills_are_charged_maker_and_taker_fees() {
+        let storage = funded(&[("alice", "LTC", 10), ("bob", "Compass", 1_000)]);
+        let mut market = Market::new();
+        market.fees = MarketFeeConfig { maker_fee_bps: 100, taker_fee_bps: 200 };
+        market.place_order("alice", OrderSide::Sell, "LTC", "Compass", 10, Some(100), TimeInForce::Gtc, &storage).unwrap();
+
+        // The buyer's balance must cover the fee as well as the cost
+        let err = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 10, Some(100), TimeInForce::Gtc, &storage).unwrap_err();
+        assert!(err.contains("needs 1020"), "{}", err);
+
+        let outcome = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 9, Some(100), TimeInForce::Gtc, &storage).unwrap();
+        let crate::block::BlockType::Trade { maker_fee, taker_fee, .. } = outcome.settlement(&outcome.trades[0]) else {
+            panic!("not a trade");
+        };
+        assert_eq!((maker_fee, taker_fee), (9, 18));
+        assert_eq!(MarketFeeConfig::default().fees(100, 9), (0, 0));
+
+        let tick = |price: u64, amount: u64, maker_fee: u64, taker_fee: u64| TradeTick {
+            trade_id: String::new(),
+            height: 0,
+            timestamp: 0,
+            price,
+            amount,
+            maker_fee,
+            taker_fee,
+        };
+        let stats = MarketStats::new("LTC/Compass", &[tick(100, 9, 9, 18), tick(50, 2, 1, 2)]);
+        assert_eq!((stats.trades, stats.volume, stats.quote_volume), (2, 11, 1_000));
+        assert_eq!((stats.maker_fees, stats.taker_fees), (10, 20));
+    }
+
+    #[test]
+    fn test_order_notional_is_overflow_checked() {
+        assert_eq!(notional(u64::MAX, 1), Some(u64::MAX));
+        assert_eq!(notional(u64::MAX, 0), Some(0));
+        assert_eq!(notional(1 << 32, 1 << 32), None);
+
+        // A buy whose value does not fit is refused before it reaches the book
+        let storage = funded(&[("bob", "Compass", u64::MAX)]);
+        let mut market = Market::new();
+        assert!(market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 2, Some(u64::MAX / 2 + 1), TimeInForce::Gtc, &storage).is_err());
+        assert!(market.books.is_empty());
+
+        // The largest buy that does fit needs exactly its notional
+        let order = market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 2, Some(u64::MAX / 2), TimeInForce::Gtc, &storage).unwrap();
+        assert!(order.trades.is_empty());
+        let short = funded(&[("bob", "Compass", u64::MAX - 2)]);
+        assert!(market.place_order("bob", OrderSide::Buy, "LTC", "Compass", 2, Some(u64::MAX / 2), TimeInForce::Gtc, &short).is_err());
+    }
+}
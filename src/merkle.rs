@@ -0,0 +1,205 @@
+//! Binary Merkle tree over key/value state entries.
+//!
+//! Leaves are `sha256(0x00 || key_len || key || value)` in key order, inner nodes
+//! `sha256(0x01 || left || right)`. The domain bytes keep a leaf from being passed
+//! off as an inner node. An odd node at the end of a level is carried up unchanged.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::encoding;
+
+pub type Hash = [u8; 32];
+
+/// Root of an empty tree
+pub const EMPTY_ROOT: Hash = [0u8; 32];
+
+pub fn leaf_hash(key: &[u8], value: &[u8]) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update((key.len() as u32).to_be_bytes());
+    hasher.update(key);
+    hasher.update(value);
+    hasher.finalize().into()
+}
+
+pub fn node_hash(left: &Hash, right: &Hash) -> Hash {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+fn next_level(level: &[Hash]) -> Vec<Hash> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+/// Root over leaf hashes, in the order given
+pub fn merkle_root(leaves: &[Hash]) -> Hash {
+    if leaves.is_empty() {
+        return EMPTY_ROOT;
+    }
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// A tree with every level kept, so changing one leaf rehashes only its path
+pub struct MerkleTree {
+    levels: Vec<Vec<Hash>>,
+}
+
+impl MerkleTree {
+    pub fn new(leaves: Vec<Hash>) -> Self {
+        let mut levels = vec![leaves];
+        while let Some(level) = levels.last().filter(|level| level.len() > 1) {
+            let next = next_level(level);
+            levels.push(next);
+        }
+        MerkleTree { levels }
+    }
+
+    /// Same as `merkle_root` over the leaves
+    pub fn root(&self) -> Hash {
+        self.levels.last().and_then(|level| level.first()).copied().unwrap_or(EMPTY_ROOT)
+    }
+
+    pub fn leaves(&self) -> &[Hash] {
+        &self.levels[0]
+    }
+
+    /// Replace the leaf at `index` and the nodes above it
+    pub fn set_leaf(&mut self, index: usize, leaf: Hash) {
+        self.levels[0][index] = leaf;
+        let mut index = index;
+        for depth in 1..self.levels.len() {
+            index /= 2;
+            let below = &self.levels[depth - 1];
+            let node = match below.get(2 * index + 1) {
+                Some(right) => node_hash(&below[2 * index], right),
+                None => below[2 * index],
+            };
+            self.levels[depth][index] = node;
+        }
+    }
+}
+
+/// One level of an inclusion proof. Levels where the node was carried up have no step.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ProofStep {
+    pub sibling: String, // hex
+    pub sibling_on_left: bool,
+}
+
+/// Path from the leaf at `index` up to the root
+pub fn merkle_proof(leaves: &[Hash], index: usize) -> Vec<ProofStep> {
+    let mut steps = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            steps.push(ProofStep {
+                sibling: encoding::hash_to_hex(&level[sibling]),
+                sibling_on_left: sibling < index,
+            });
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    steps
+}
+
+/// Root implied by a leaf and its proof; None if a sibling is not a valid hash
+pub fn root_from_proof(leaf: Hash, steps: &[ProofStep]) -> Option<Hash> {
+    steps.iter().try_fold(leaf, |node, step| {
+        let sibling = encoding::hash_from_hex(&step.sibling).ok()?;
+        Some(if step.sibling_on_left { node_hash(&sibling, &node) } else { node_hash(&node, &sibling) })
+    })
+}
+
+/// A state entry with the proof tying it to a state root, as served by `getProof`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct StateProof {
+    pub key: String,
+    pub value: String, // hex of the stored bytes
+    pub steps: Vec<ProofStep>,
+    pub state_root: String,
+}
+
+impl StateProof {
+    /// Whether the entry hashes up to `state_root`
+    pub fn verify(&self) -> bool {
+        let Ok(value) = hex::decode(&self.value) else { return false };
+        let leaf = leaf_hash(self.key.as_bytes(), &value);
+        root_from_proof(leaf, &self.steps).map(|root| encoding::hash_to_hex(&root)) == Some(self.state_root.clone())
+    }
+
+    /// The value read as a balance (`bal:` entries are big-endian u64)
+    pub fn balance(&self) -> Option<u64> {
+        let bytes: [u8; 8] = hex::decode(&self.value).ok()?.try_into().ok()?;
+        Some(u64::from_be_bytes(bytes))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_merkle_root_commits_to_every_leaf() {
+        let leaves: Vec<Hash> = (0u8..5).map(|i| leaf_hash(&[i], b"v")).collect();
+        let root = merkle_root(&leaves);
+        assert_eq!(merkle_root(&leaves[..1]), leaves[0]);
+        assert_eq!(merkle_root(&[]), EMPTY_ROOT);
+
+        // Three levels: ((0,1),(2,3)) then 4 carried up
+        let expected = node_hash(
+            &node_hash(&node_hash(&leaves[0], &leaves[1]), &node_hash(&leaves[2], &leaves[3])),
+            &leaves[4],
+        );
+        assert_eq!(root, expected);
+
+        let mut changed = leaves.clone();
+        changed[4] = leaf_hash(&[4], b"w");
+        assert_ne!(merkle_root(&changed), root);
+    }
+
+    #[test]
+    fn test_proofs_reach_the_root_from_every_leaf() {
+        for count in 1..=7 {
+            let leaves: Vec<Hash> = (0..count as u8).map(|i| leaf_hash(&[i], b"v")).collect();
+            let root = merkle_root(&leaves);
+            for (index, leaf) in leaves.iter().enumerate() {
+                let steps = merkle_proof(&leaves, index);
+                assert_eq!(root_from_proof(*leaf, &steps), Some(root), "leaf {} of {}", index, count);
+                assert_ne!(root_from_proof(leaf_hash(&[99], b"v"), &steps), Some(root));
+            }
+        }
+    }
+
+    #[test]
+    fn test_tree_updates_match_a_rebuilt_root() {
+        assert_eq!(MerkleTree::new(vec![]).root(), EMPTY_ROOT);
+        for count in 1..=7 {
+            let mut leaves: Vec<Hash> = (0..count as u8).map(|i| leaf_hash(&[i], b"v")).collect();
+            let mut tree = MerkleTree::new(leaves.clone());
+            assert_eq!(tree.root(), merkle_root(&leaves));
+            for index in 0..count {
+                leaves[index] = leaf_hash(&[index as u8], b"w");
+                tree.set_leaf(index, leaves[index]);
+                assert_eq!(tree.root(), merkle_root(&leaves), "leaf {} of {}", index, count);
+            }
+        }
+    }
+}
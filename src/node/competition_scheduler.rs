@@ -0,0 +1,176 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{error, info, warn};
+
+use crate::block::SettlementLeg;
+use crate::chain::Chain;
+use crate::layer3::competition::{Competition, CompetitionStatus, COMPETITION_POOL_ACCOUNT};
+use crate::layer3::price_oracle::PriceOracle;
+
+/// Cached oracle prices younger than this are used as-is
+const PRICE_MAX_AGE_SECS: u64 = 60;
+
+/// How often competitions are checked for epoch rollover and close
+const TICK_INTERVAL_SECS: u64 = 30;
+
+fn now_secs() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_secs()
+}
+
+/// Latest oracle price for a ticker: the stored price oracle if fresh, otherwise a live fetch
+pub async fn mark_price(chain: &Arc<Mutex<Chain>>, ticker: &str) -> Result<f64, String> {
+    let cached = {
+        let chain = chain.lock().map_err(|_| "Chain lock poisoned".to_string())?;
+        chain.storage.get_price_oracle(ticker).ok().flatten()
+    };
+    if let Some(oracle) = &cached {
+        if oracle.latest_price > 0.0 && now_secs().saturating_sub(oracle.last_updated) < PRICE_MAX_AGE_SECS {
+            return Ok(oracle.latest_price);
+        }
+    }
+
+    let price = PriceOracle::fetch_binance_price(ticker).await?;
+    if let Ok(chain) = chain.lock() {
+        let mut oracle = cached.unwrap_or_else(|| PriceOracle::new(ticker));
+        oracle.update(price, "binance");
+        let _ = chain.storage.save_price_oracle(&oracle);
+    }
+    Ok(price)
+}
+
+/// CompetitionScheduler rolls leaderboards over each epoch and pays out prize pools at close
+pub struct CompetitionScheduler {
+    pub chain: Arc<Mutex<Chain>>,
+    pub operator: String, // Recorded as the actor on payout audit entries
+}
+
+impl CompetitionScheduler {
+    pub fn new(chain: Arc<Mutex<Chain>>, operator: String) -> Self {
+        Self { chain, operator }
+    }
+
+    pub async fn start(self) {
+        info!("🏆 Competition Scheduler Started");
+        loop {
+            self.tick().await;
+            tokio::time::sleep(Duration::from_secs(TICK_INTERVAL_SECS)).await;
+        }
+    }
+
+    async fn tick(&self) {
+        let now = now_secs();
+        let live: Vec<Competition> = match self.chain.lock() {
+            Ok(chain) => chain
+                .storage
+                .get_all_competitions()
+                .into_iter()
+                .filter(|c| c.status_at(now) == CompetitionStatus::Active)
+                .collect(),
+            Err(_) => return,
+        };
+        if live.is_empty() {
+            return;
+        }
+
+        // Fetch prices without holding the chain lock
+        let mut prices = HashMap::new();
+        for ticker in live.iter().flat_map(|c| c.tickers.iter()) {
+            if prices.contains_key(ticker) {
+                continue;
+            }
+            match mark_price(&self.chain, ticker).await {
+                Ok(price) => {
+                    prices.insert(ticker.clone(), price);
+                }
+                Err(e) => warn!("Competition pricing failed for {}: {}", ticker, e),
+            }
+        }
+
+        let mut chain = match self.chain.lock() {
+            Ok(c) => c,
+            Err(_) => return,
+        };
+        for summary in live {
+            // Re-read: entrants may have traded while we were fetching prices
+            let mut competition = match chain.storage.get_competition(&summary.competition_id) {
+                Ok(Some(c)) => c,
+                _ => continue,
+            };
+
+            if now >= competition.end_time {
+                self.settle(&mut chain, &mut competition, &prices);
+            } else if competition.update_leaderboard(&prices, now) {
+                info!(
+                    "🏆 {} epoch {} leaderboard: {} entrants",
+                    competition.competition_id,
+                    competition.epoch_at(now),
+                    competition.leaderboard.len()
+                );
+            } else {
+                continue;
+            }
+
+            if let Err(e) = chain.storage.save_competition(&competition) {
+                error!("Failed to save competition {}: {}", competition.competition_id, e);
+            }
+        }
+    }
+
+    fn settle(&self, chain: &mut Chain, competition: &mut Competition, prices: &HashMap<String, f64>) {
+        let payouts = match competition.settle(prices) {
+            Ok(p) => p,
+            Err(e) => {
+                error!("Failed to settle competition {}: {}", competition.competition_id, e);
+                return;
+            }
+        };
+
+        // Every winner is paid in one block, each capped at what the pool still holds
+        let asset = competition.fee_asset.clone();
+        let mut pool = chain.storage.get_balance(COMPETITION_POOL_ACCOUNT, &asset).unwrap_or(0);
+        let mut paid = Vec::with_capacity(payouts.len());
+        for payout in &payouts {
+            let amount = payout.amount.min(pool);
+            pool -= amount;
+            paid.push((payout, amount));
+        }
+        let legs = paid
+            .iter()
+            .map(|(payout, amount)| SettlementLeg::Transfer {
+                from: COMPETITION_POOL_ACCOUNT.to_string(),
+                to: payout.account.clone(),
+                asset: asset.clone(),
+                amount: *amount,
+            })
+            .collect();
+        if let Err(e) = chain.settle(legs, &format!("competition {} payouts", competition.competition_id)) {
+            error!("Competition payouts failed for {}: {}", competition.competition_id, e);
+            return;
+        }
+
+        for (payout, amount) in paid {
+            if let Err(e) = chain.storage.append_audit_entry(
+                &self.operator,
+                crate::audit::AuditAction::EscrowRelease,
+                format!(
+                    "competition={} rank={} winner={} amount={} {}",
+                    competition.competition_id, payout.rank, payout.account, amount, asset
+                ),
+            ) {
+                error!("Failed to write audit entry: {}", e);
+            }
+        }
+
+        info!(
+            "🏁 Competition {} closed: {} {} paid to {} winners",
+            competition.competition_id,
+            competition.prize_pool,
+            asset,
+            payouts.len()
+        );
+    }
+}
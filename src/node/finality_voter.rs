@@ -0,0 +1,91 @@
+// Finality voting
+//
+// A node whose key belongs to an active validator votes once per slot for the
+// latest PoH block on its canonical chain. The vote goes into the local Gulf
+// Stream and out to peers like any other transaction; whichever node executes
+// it appends the `FinalityVote` block, and every node counts it on commit.
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tracing::{debug, warn};
+
+use crate::block::{Block, BlockType};
+use crate::chain::{Chain, MAX_REORG_DEPTH};
+use crate::encoding;
+use crate::gulf_stream::manager::CompassGulfStreamManager;
+use crate::network::{NetMessage, NetworkCommand, TransactionPayload};
+use crate::node::signer::{NodeSigner, SignPurpose};
+
+/// Latest PoH block within `MAX_REORG_DEPTH` of the head
+fn latest_poh_block(chain: &Chain) -> Option<Block> {
+    let mut height = chain.height;
+    for _ in 0..=MAX_REORG_DEPTH {
+        height = height.checked_sub(1)?;
+        let block = chain.storage.get_block_by_height(height).ok().flatten()?;
+        if matches!(block.header.block_type, BlockType::PoH { .. }) {
+            return Some(block);
+        }
+    }
+    None
+}
+
+/// Sign this slot's vote, if there is a new PoH block to vote for and the lockout allows it
+fn next_vote(chain: &Arc<Mutex<Chain>>, signer: &NodeSigner, voter: &str, last_voted: u64) -> Option<TransactionPayload> {
+    let (target_hash, target_height) = {
+        let c_guard = chain.lock().ok()?;
+        let target = latest_poh_block(&c_guard)?;
+        if target.header.index <= last_voted {
+            return None;
+        }
+        if let Err(e) = c_guard.check_finality_vote(voter, &target.header.hash, target.header.index) {
+            debug!("Not voting for height {}: {}", target.header.index, e);
+            return None;
+        }
+        (target.header.hash, target.header.index)
+    };
+
+    let message = crate::finality::vote_message(&target_hash, target_height);
+    match signer.sign_hex(message.as_bytes(), SignPurpose::FinalityVote) {
+        Ok(signature) => Some(TransactionPayload::FinalityVote {
+            voter: voter.to_string(),
+            target_hash,
+            target_height,
+            signature,
+        }),
+        Err(e) => {
+            warn!("Finality vote for height {} not signed: {}", target_height, e);
+            None
+        }
+    }
+}
+
+/// Vote every `slot` until the task is dropped
+pub async fn run(
+    chain: Arc<Mutex<Chain>>,
+    signer: Arc<NodeSigner>,
+    gulf_stream: Arc<Mutex<CompassGulfStreamManager>>,
+    cmd_tx: mpsc::Sender<NetworkCommand>,
+    slot: Duration,
+) {
+    let voter = signer.public_key_hex();
+    let mut last_voted = 0;
+    loop {
+        tokio::time::sleep(slot).await;
+        let Some(payload) = next_vote(&chain, &signer, &voter, last_voted) else { continue };
+        let TransactionPayload::FinalityVote { target_height, .. } = &payload else { continue };
+        let target_height = *target_height;
+
+        let Ok(raw_tx) = encoding::canonical_bytes(&payload) else { continue };
+        let tx_hash = encoding::sha256(&raw_tx).to_vec();
+        let added = gulf_stream.lock().map_err(|e| e.to_string()).and_then(|mut gs| {
+            gs.try_add_transaction(tx_hash, raw_tx, 0).map_err(|e| e.to_string())
+        });
+        if let Err(e) = added {
+            warn!("Finality vote for height {} not queued: {}", target_height, e);
+            continue;
+        }
+        let _ = cmd_tx.send(NetworkCommand::Broadcast(NetMessage::SubmitTx(payload))).await;
+        last_voted = target_height;
+        debug!("Voted for height {}", target_height);
+    }
+}
@@ -2,7 +2,6 @@ use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tracing::{info, warn};
-use sha2::Digest;
 
 use crate::chain::Chain;
 use crate::wallet::{WalletManager, WalletType};
@@ -12,10 +11,25 @@ use crate::gulf_stream::manager::CompassGulfStreamManager;
 use crate::layer2::Layer2State;
 use crate::oracle::OracleService;
 use crate::crypto::KeyPair;
-use crate::network::{NetMessage, NetworkCommand, PeerManager, TransactionPayload};
+use crate::network::{NetMessage, NetworkCommand, PeerManager, TransactionPayload, WireTransaction, MAX_BLOCK_RANGE};
 use crate::block::{self, BlockType};
-use crate::storage::Storage;
+use crate::encoding;
+use crate::storage::{Storage, TxStatus};
 pub mod oracle_scheduler;
+pub mod competition_scheduler;
+pub mod finality_voter;
+pub mod monitor;
+pub mod poh_service;
+pub mod signer;
+pub mod supervisor;
+
+use supervisor::{Backoff, Supervisor};
+
+/// Peers this many blocks ahead are synced from a snapshot rather than block by block
+const FAST_SYNC_MIN_GAP: u64 = 1000;
+
+/// How long each task gets to stop on shutdown before it is abandoned
+const SHUTDOWN_GRACE: Duration = Duration::from_secs(10);
 
 pub struct CompassNode {
     pub chain: Arc<Mutex<Chain>>,
@@ -33,7 +47,8 @@ pub struct CompassNode {
     pub cmd_rx: Arc<tokio::sync::Mutex<Option<mpsc::Receiver<NetworkCommand>>>>, // Wrapped for async access if needed
     
     pub p2p_port: u16,
-    pub identity: Arc<KeyPair>,
+    pub identity: Arc<signer::NodeSigner>,
+    #[cfg(feature = "p2p")]
     pub local_libp2p_key: libp2p::identity::Keypair,
     pub db_path: String,
     pub config: crate::config::CompassConfig,
@@ -53,55 +68,42 @@ impl CompassNode {
     // Setup Identity
     let admin = if let Some(k) = explicit_identity {
         println!("IDENTITY INJECTION: Using injected identity.");
-        k
-    } else if std::path::Path::new("admin.json").exists() {
-         let cwd = std::env::current_dir().unwrap();
-         println!("IDENTITY FOUND: 'admin.json' at {:?}", cwd.join("admin.json"));
-         print!("Enter password to unlock Admin Node: ");
-         std::io::Write::flush(&mut std::io::stdout()).unwrap();
-         let mut pass = String::new();
-         std::io::stdin().read_line(&mut pass).unwrap();
-         Arc::new(match crate::identity::Identity::load_and_decrypt(std::path::Path::new("admin.json"), pass.trim()) {
-             Ok(id) => {
-                 println!("IDENTITY UNLOCKED: '{}'", id.name);
-                 // Create valid backup
-                 let _ = std::fs::copy("admin.json", "admin.backup.json");
-                 id.into_keypair().expect("Identity locked")
-             },
-             Err(e) => {
-                 println!("IDENTITY ERROR: Failed to unlock: {}", e);
-                 if e.to_string().contains("missing field") {
-                     println!("⚠️  DETECTED CORRUPTION: Attempting to restore from backup...");
-                     match crate::identity::Identity::load_and_decrypt(std::path::Path::new("admin.backup.json"), pass.trim()) {
-                         Ok(id_bak) => {
-                             println!("✅ BACKUP RESTORED: '{}'", id_bak.name);
-                             let _ = std::fs::copy("admin.backup.json", "admin.json");
-                             id_bak.into_keypair().expect("Identity locked")
-                         },
-                         Err(e_bak) => {
-                             println!("❌ BACKUP FAILED: {}", e_bak);
-                             std::process::exit(1);
-                         }
-                     }
-                 } else {
-                     std::process::exit(1);
-                 }
-             }
-         })
-        } else if std::path::Path::new("admin_key.mnemonic").exists() {
-            info!("Loading Legacy Admin Key from 'admin_key.mnemonic'");
-            let phrase = std::fs::read_to_string("admin_key.mnemonic").unwrap();
-            Arc::new(KeyPair::from_mnemonic(phrase.trim()).expect("Invalid mnemonic"))
-        } else {
-            warn!("No Admin Identity found. Generating Temporary Key (NOT PERSISTED).");
-            Arc::new(KeyPair::generate())
-        };
+        Arc::new(signer::NodeSigner::Local(k))
+    } else if let Some(remote) = &config.signer {
+        match signer::RemoteSigner::connect(remote) {
+            Ok(s) => Arc::new(signer::NodeSigner::Remote(s)),
+            Err(e) => {
+                println!("IDENTITY ERROR: {}", e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        match crate::init::load_node_identity(&config.node) {
+            Ok(Some(kp)) => Arc::new(signer::NodeSigner::Local(Arc::new(kp))),
+            Ok(None) => {
+                warn!("No Admin Identity found. Generating Temporary Key (NOT PERSISTED). Run `compass init` to create one.");
+                Arc::new(signer::NodeSigner::Local(Arc::new(KeyPair::generate())))
+            }
+            Err(e) => {
+                println!("IDENTITY ERROR: {}", e);
+                std::process::exit(1);
+            }
+        }
+    };
         
         info!("IDENTITY: Node Public Key: {}", admin.public_key_hex());
 
         // --- 1. Storage & Persistence (Initialized First) ---
-        info!("Persistence: Opening Sled DB at '{}'...", db_path);
-        let storage = Storage::new(&db_path).expect("Failed to open DB");
+        info!("Persistence: Opening {} DB at '{}'...", config.node.db_backend, db_path);
+        if let Some(mb) = config.node.db_cache_mb {
+            info!("Persistence: {} cache capped at {} MiB", config.node.db_backend, mb);
+        }
+        let storage = Storage::open(&db_path, config.node.db_backend, config.node.db_cache_mb.map(|mb| mb * 1024 * 1024))
+            .expect("Failed to open DB")
+            .with_flush_policy(crate::storage::FlushPolicy {
+                every_blocks: config.node.db_flush_every_blocks.unwrap_or(0),
+                interval: Duration::from_millis(config.node.db_flush_interval_ms.unwrap_or(1000)),
+            });
         
         // Auto-Migrate Legacy NFTs
         let _ = storage.migrate_legacy_nfts();
@@ -152,28 +154,75 @@ impl CompassNode {
                  info!("Persistence: ✅ Market Migration Complete.");
              }
         }
+        market_struct.fees = config.consensus.market_fees;
         let market = Arc::new(Mutex::new(market_struct));
-        let gulf_stream = Arc::new(Mutex::new(CompassGulfStreamManager::new("Node1".to_string(), 1000)));
+        let mut gulf_stream_struct = CompassGulfStreamManager::new("Node1".to_string(), 1000);
+        gulf_stream_struct.set_nonce_source(storage_arc.clone());
+        gulf_stream_struct.accept_bare_transactions = config.node.accept_bare_transactions;
+        if let Some(window) = config.node.future_nonce_window {
+            gulf_stream_struct.future_nonce_window = window;
+        }
+        let gulf_stream = Arc::new(Mutex::new(gulf_stream_struct));
 
         // --- Chain & Layer 2 (Dependent on Storage) ---
-        let chain = Arc::new(Mutex::new(Chain::new(storage_arc.clone())));
+        let mut chain_struct = Chain::new(storage_arc.clone());
+        chain_struct.slot_duration_ms = config.consensus.slot_duration_ms;
+        chain_struct.slots_per_epoch = config.consensus.slots_per_epoch;
+        chain_struct.pouw_curve = config.consensus.pouw.clone();
+        chain_struct.fee_schedule = config.consensus.fees.clone();
+        chain_struct.min_earnings_payout = config.consensus.min_earnings_payout;
+        chain_struct.oracle_guard = config.consensus.oracle_guard.clone();
+        chain_struct.oracle_aggregation = config.consensus.oracle_aggregation.clone();
+        for oracle in &config.consensus.oracle_aggregation.oracles {
+            let registered = chain_struct.oracle_registry.lock().unwrap().register_oracle_key(
+                oracle.account.clone(),
+                oracle.pubkey.clone(),
+                oracle.stake,
+                0,
+            );
+            if let Err(e) = registered {
+                warn!("Oracle {} not registered: {}", oracle.account, e);
+            }
+        }
+        chain_struct.treasury = config.consensus.treasury;
+        chain_struct.market_fees = config.consensus.market_fees;
+        chain_struct.stability_fee = config.consensus.stability_fee;
+        chain_struct.emission = config.consensus.emission;
+        chain_struct.header_only = config.node.light;
+        chain_struct.root_signer = Some(admin.clone());
+        let chain = Arc::new(Mutex::new(chain_struct));
         
         // Validating Layer 2
-        let layer2 = Arc::new(Mutex::new(Layer2State::new(Some(storage_arc.clone()))));
+        let mut layer2_struct = Layer2State::new(Some(storage_arc.clone()));
+        layer2_struct.treasury = config.consensus.treasury;
+        layer2_struct.unbonding_slots = config.consensus.unbonding_slots;
+        let layer2 = Arc::new(Mutex::new(layer2_struct));
         
         // Genesis Init - ONLY if blockchain is empty
         {
             let mut c = chain.lock().unwrap();
-            if c.height == 0 && c.head_hash.is_none() {
+            if c.height == 0 && c.head_hash.is_none() && config.node.dev {
+                match crate::fixtures::install(&mut c) {
+                    Ok(_) => {
+                        info!("✅ Devnet genesis and fixtures installed");
+                        let mut vm = vaults.lock().unwrap();
+                        for v in storage_arc.get_all_vaults() {
+                            vm.vaults.insert(v.compass_asset.clone(), v);
+                        }
+                    }
+                    Err(e) => warn!("Devnet fixture install failed: {}", e),
+                }
+            } else if c.height == 0 && c.head_hash.is_none() {
                 // Fresh blockchain - initialize genesis
-                if let Ok(config) = crate::genesis::GenesisConfig::load("genesis.json") {
-                    match c.initialize_genesis(&config) {
+                match crate::genesis::GenesisConfig::load(&config.node.genesis_file) {
+                    Ok(genesis) => match c.initialize_genesis(&genesis) {
                         Ok(_) => info!("✅ Genesis block initialized"),
                         Err(e) => warn!("Genesis initialization failed: {}", e),
+                    },
+                    Err(e) => {
+                        warn!("❌ {} and blockchain is empty!", e);
+                        warn!("   Run `compass init` to generate or fetch a genesis file.");
                     }
-                } else {
-                    warn!("❌ genesis.json not found and blockchain is empty!");
-                    warn!("   Create genesis.json or let node generate it automatically.");
                 }
             } else {
                 info!("⏭️  Skipping genesis init - blockchain already exists (height: {})", c.height);
@@ -181,19 +230,28 @@ impl CompassNode {
         }
         
         // --- Network Setup ---
-        let peer_manager = Arc::new(Mutex::new(PeerManager::new(p2p_port)));
+        let peer_manager = Arc::new(Mutex::new(PeerManager::with_storage(p2p_port, storage_arc.clone())));
         let (gossip_tx, _gossip_rx) = tokio::sync::broadcast::channel(100); // We clone rx where needed
         let (cmd_tx, cmd_rx) = mpsc::channel(32);
+        #[cfg(feature = "p2p")]
         let local_libp2p_key = libp2p::identity::Keypair::generate_ed25519();
 
 
         // --- Oracle Init (v2.0: Use admin identity) ---
-        let oracle_config = crate::oracle::OracleConfig::default();
-        let oracle_keypair = crate::crypto::KeyPair::generate();  // Oracle needs its own KeyPair
+        let oracle_config = crate::oracle::OracleConfig {
+            ethereum: config.ethereum.clone(),
+            ..Default::default()
+        };
+        // With a remote signer the oracle signs through it too; otherwise it gets its own key
+        let oracle_signer = if admin.is_remote() {
+            admin.clone()
+        } else {
+            Arc::new(signer::NodeSigner::Local(Arc::new(KeyPair::generate())))
+        };
             
         let oracle = Arc::new(tokio::sync::Mutex::new(OracleService::new(
             oracle_config,
-            oracle_keypair,
+            oracle_signer,
             Arc::clone(&layer2),
         )));
 
@@ -225,6 +283,7 @@ impl CompassNode {
 
             p2p_port,
             identity: admin,
+            #[cfg(feature = "p2p")]
             local_libp2p_key,
             db_path,
             config: config.clone(),
@@ -232,20 +291,34 @@ impl CompassNode {
         }
     }
 
+    /// Attach an integrator hook; call before `start` so it sees every block
+    pub fn register_hook(&self, hook: Arc<dyn crate::hooks::Hook>) {
+        self.chain.lock().unwrap().hooks.register(hook);
+    }
+
     pub async fn start(self, rpc_port_val: Option<u16>, peer_val: Option<String>) {
         info!("Starting Compass Node Services...");
+        let mut supervisor = Supervisor::new(Backoff::default());
         
         let rpc_port = rpc_port_val.unwrap_or(9000);
         let peer_addr = peer_val.clone(); 
-        let follower_mode = peer_addr.is_some();
+        // Light nodes follow headers only: no block production, schedulers or tx execution
+        let light_mode = self.config.node.light;
+        let follower_mode = peer_addr.is_some() || light_mode;
+        if light_mode {
+            info!("🪶 Light mode: syncing block headers only");
+        }
 
         // 1. P2P Server
+        #[cfg(feature = "p2p")]
         let pm_clone = self.peer_manager.clone();
+        #[cfg(feature = "p2p")]
         let gtx_clone = self.gossip_tx.clone();
+        #[cfg(feature = "p2p")]
         let chain_p2p = self.chain.clone();
         
         
-        let genesis_hash = {
+        let network_id = {
              let chain = self.chain.lock().unwrap();
              // Cleanup for Test (User Request): Delete old 24h jobs
              let all_jobs = chain.storage.get_all_recurring_jobs();
@@ -259,7 +332,20 @@ impl CompassNode {
              // Job creation will happen in background task below
              info!("🤖 Neural Network Job System will initialize...");
              
-             chain.get_genesis_hash().unwrap_or_default()
+             // Databases from before the genesis hash was recorded take it from the genesis file
+             let file = crate::genesis::GenesisConfig::load(&self.config.node.genesis_file).ok();
+             let chain_id = chain.storage.get_chain_id().ok().flatten().or_else(|| file.as_ref().map(|g| g.chain_id.clone()));
+             let genesis_hash = chain.storage.get_genesis_config_hash().ok().flatten().or_else(|| {
+                 let hash = file.as_ref()?.canonical_hash();
+                 if let Err(e) = chain.storage.set_genesis_config_hash(&hash) {
+                     warn!("Failed to record genesis hash: {}", e);
+                 }
+                 Some(hash)
+             });
+             if chain_id.is_none() || genesis_hash.is_none() {
+                 warn!("Chain id or genesis hash unknown (no genesis in the database or at {}); peers will refuse this node", self.config.node.genesis_file);
+             }
+             crate::genesis::network_id(&chain_id.unwrap_or_default(), &genesis_hash.unwrap_or_default())
         };
         
         // Start background task for NN job creation
@@ -270,25 +356,60 @@ impl CompassNode {
             
             // Start Oracle Scheduler (Real AI Price Oracle)
             let chain_oracle = self.chain.clone();
-            let admin_pubkey_oracle = self.identity.public_key_hex();
+            let identity_oracle = self.identity.clone();
             
-            tokio::spawn(async move {
+            supervisor.spawn("oracle-scheduler", move || {
                 use crate::node::oracle_scheduler::OracleScheduler;
-                let scheduler = OracleScheduler::new(chain_oracle, admin_pubkey_oracle, network_cmd_tx);
-                scheduler.start().await;
+                let scheduler = OracleScheduler::new(chain_oracle.clone(), identity_oracle.clone(), network_cmd_tx.clone());
+                scheduler.start()
             });
+
+            // Competition epochs & payouts
+            #[cfg(feature = "market")]
+            {
+                let chain_competition = self.chain.clone();
+                let operator = self.identity.public_key_hex();
+                supervisor.spawn("competition-scheduler", move || {
+                    use crate::node::competition_scheduler::CompetitionScheduler;
+                    CompetitionScheduler::new(chain_competition.clone(), operator.clone()).start()
+                });
+            }
         }
         
-        let my_gen = genesis_hash.clone();
-        let server_key = self.local_libp2p_key.clone();
-        let cmd_rx_opt = self.cmd_rx.lock().await.take(); // Take the receiver
-        let p2p_port = self.p2p_port;
+        // The receiver stays in its slot so a restarted server picks up the same queue
+        let cmd_rx = self.cmd_rx.clone();
         let chain_sync = self.chain.clone();
         let cmd_tx_sync = self.cmd_tx.clone();
         
-        if let Some(rx) = cmd_rx_opt {
-             tokio::spawn(async move {
-                crate::network::start_server(p2p_port, pm_clone, gtx_clone, chain_p2p, my_gen, rx, server_key).await;
+        #[cfg(feature = "p2p")]
+        {
+             let my_gen = network_id.clone();
+             let server_key = self.local_libp2p_key.clone();
+             let p2p_port = self.p2p_port;
+             let p2p_tls = self.config.node.p2p_tls;
+             supervisor.spawn("p2p", move || {
+                let (cmd_rx, pm_clone, gtx_clone, chain_p2p) = (cmd_rx.clone(), pm_clone.clone(), gtx_clone.clone(), chain_p2p.clone());
+                let (my_gen, server_key) = (my_gen.clone(), server_key.clone());
+                async move {
+                    let mut slot = cmd_rx.lock().await;
+                    let Some(rx) = slot.as_mut() else { return };
+                    crate::network::start_server(p2p_port, p2p_tls, pm_clone, gtx_clone, chain_p2p, my_gen, rx, server_key).await;
+                }
+            });
+        }
+        // Built without libp2p: no peers, so network commands are drained and dropped
+        #[cfg(not(feature = "p2p"))]
+        {
+            warn!("P2P disabled at build time; running standalone on {}", network_id);
+            supervisor.spawn("p2p-drain", move || {
+                let cmd_rx = cmd_rx.clone();
+                async move {
+                    let mut slot = cmd_rx.lock().await;
+                    let Some(rx) = slot.as_mut() else { return };
+                    while let Some(cmd) = rx.recv().await {
+                        tracing::debug!("Dropping network command (no p2p): {:?}", cmd);
+                    }
+                }
             });
         }
         
@@ -302,6 +423,18 @@ impl CompassNode {
             });
         }
         
+        // Peers remembered from earlier runs, best reputation first
+        let known_peers = self.peer_manager.lock().unwrap().dial_candidates(8, now_secs());
+        if !known_peers.is_empty() {
+            let tx = self.cmd_tx.clone();
+            tokio::spawn(async move {
+                tokio::time::sleep(Duration::from_secs(3)).await;
+                for addr in known_peers {
+                    let _ = tx.send(NetworkCommand::Dial(addr)).await;
+                }
+            });
+        }
+
         if !bootnodes.is_empty() {
              let tx = self.cmd_tx.clone();
              tokio::spawn(async move {
@@ -315,45 +448,289 @@ impl CompassNode {
         }
         
         // Sync Task
-        let mut gossip_rx = self.gossip_tx.subscribe();
+        let gossip_sync = self.gossip_tx.clone();
         let gs_p2p = self.gulf_stream.clone();
         let chain_sync_task = self.chain.clone(); // For logic inside sync
+        let pm_sync = self.peer_manager.clone();
         
-        tokio::spawn(async move {
-            while let Ok((msg, peer_source)) = gossip_rx.recv().await {
-                 match msg {
-                    NetMessage::SubmitTx(payload) => {
-                        if let Ok(raw_tx) = bincode::serialize(&payload) {
-                            let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
-                            gs_p2p.lock().unwrap().add_transaction(tx_hash, raw_tx, 0);
+        supervisor.spawn("sync", move || {
+            let mut gossip_rx = gossip_sync.subscribe();
+            let gs_p2p = gs_p2p.clone();
+            let chain_sync_task = chain_sync_task.clone();
+            let cmd_tx_sync = cmd_tx_sync.clone();
+            let pm_sync = pm_sync.clone();
+            async move {
+                // Snapshot being downloaded: (peer, manifest, chunks received so far)
+                let mut fast_sync: Option<(String, crate::storage::SnapshotManifest, Vec<crate::storage::SnapshotChunk>)> = None;
+                while let Ok((msg, peer_source)) = gossip_rx.recv().await {
+                     match msg {
+                        // Followers take gossiped transactions in through the leader's blocks;
+                        // executing them here too would stamp a block of their own and fork
+                        NetMessage::SubmitTx(_) | NetMessage::SubmitSignedTx(_) if follower_mode => {}
+                        NetMessage::SubmitTx(payload) => {
+                            if let Ok(raw_tx) = encoding::canonical_bytes(&payload) {
+                                let tx_hash = encoding::sha256(&raw_tx).to_vec();
+                                if let Err(e) = gs_p2p.lock().unwrap().try_add_transaction(tx_hash, raw_tx, 0) {
+                                    warn!("Dropped gossiped tx from {}: {}", peer_source, e);
+                                }
+                            }
                         }
-                    }
-                    NetMessage::HeightResponse { height: remote_height } => {
-                         let local_height = chain_sync_task.lock().unwrap().height;
-                         if remote_height > local_height {
-                             let start = local_height + 1;
-                             let mut end = remote_height;
-                             if end - start > 50 { end = start + 50; }
-                             let req = NetMessage::RequestBlocks { start, end };
+                        NetMessage::SubmitSignedTx(signed) => {
+                            if let Ok(raw_tx) = encoding::canonical_bytes(&signed) {
+                                let tx_hash = encoding::sha256(&raw_tx).to_vec();
+                                if let Err(e) = gs_p2p.lock().unwrap().try_add_transaction(tx_hash, raw_tx, 0) {
+                                    warn!("Dropped gossiped signed tx from {}: {}", peer_source, e);
+                                }
+                            }
+                        }
+                        NetMessage::BlockAnnounce { height, hash, sent_at_ms } => {
+                             let fresh = pm_sync.lock().unwrap().propagation.record(&hash, sent_at_ms, block::current_unix_timestamp_ms());
+                             if !fresh {
+                                 continue;
+                             }
+                             // Pass it on, then fetch the block from whoever told us if we lack it
+                             let _ = cmd_tx_sync.send(NetworkCommand::Broadcast(NetMessage::BlockAnnounce { height, hash, sent_at_ms })).await;
+                             if height >= chain_sync_task.lock().unwrap().height {
+                                 let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req: NetMessage::GetHeight }).await;
+                             }
+                        }
+                        NetMessage::HeightResponse { height: remote_height } => {
+                             let local_height = chain_sync_task.lock().unwrap().height;
+                             // Fetch from the best-scored peer that is ahead, not just whoever answered
+                             let target = {
+                                 let mut pm = pm_sync.lock().unwrap();
+                                 pm.record_height(&peer_source, remote_height);
+                                 pm.best_sync_peer(local_height, now_secs()).unwrap_or_else(|| peer_source.clone())
+                             };
+                             // Far behind: fetch a snapshot first instead of every block since genesis
+                             let req = if light_mode && remote_height > local_height {
+                                 NetMessage::RequestHeaders { start: local_height, end: (remote_height - 1).min(local_height + 50) }
+                             } else if remote_height > local_height + FAST_SYNC_MIN_GAP && fast_sync.is_none() {
+                                 NetMessage::GetSnapshot
+                             } else if remote_height > local_height {
+                                 // Heights count blocks, so the next block index is the local height.
+                                 // The peer answers in chunks; each names where the next one starts.
+                                 let end = (remote_height - 1).min(local_height + MAX_BLOCK_RANGE - 1);
+                                 NetMessage::RequestBlockRange { start: local_height, end }
+                             } else {
+                                 continue;
+                             };
+                             let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: target, req }).await;
+                        }
+                        NetMessage::BlockResponse { blocks } => {
+                             let received = blocks.len();
+                             let outcome = sync_blocks(&chain_sync_task, blocks, &peer_source);
+                             if score_sync_response(&pm_sync, &cmd_tx_sync, &peer_source, outcome).await {
+                                 continue;
+                             }
+                             // Keep pulling until the peer has nothing newer
+                             if received > 0 {
+                                 let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req: NetMessage::GetHeight }).await;
+                             }
+                        }
+                        NetMessage::BlockChunk { end, blocks, next, .. } => {
+                             let received = blocks.len();
+                             let outcome = sync_blocks(&chain_sync_task, blocks, &peer_source);
+                             let clean = outcome.1.is_none();
+                             if score_sync_response(&pm_sync, &cmd_tx_sync, &peer_source, outcome).await {
+                                 continue;
+                             }
+                             // Resume the range where the chunk stopped, as long as every block
+                             // landed; otherwise start over from the peer's height
+                             let local_height = chain_sync_task.lock().unwrap().height;
+                             let req = match next {
+                                 Some(next) if clean && next == local_height => NetMessage::RequestBlockRange { start: next, end },
+                                 _ if received > 0 => NetMessage::GetHeight,
+                                 _ => continue,
+                             };
+                             let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req }).await;
+                        }
+                        NetMessage::HeaderResponse { headers } => {
+                             let received = headers.len();
+                             let outcome = {
+                                 let mut c = chain_sync_task.lock().unwrap();
+                                 let mut accepted = 0;
+                                 let mut rejected = None;
+                                 for header in headers {
+                                     // Same linkage and signature checks as a full block, minus the body
+                                     if let Err(e) = c.sync_block(block::Block { header, transactions: vec![] }) {
+                                         warn!("Synced header from {} rejected: {}", peer_source, e);
+                                         rejected = Some(e);
+                                         break;
+                                     }
+                                     accepted += 1;
+                                 }
+                                 (accepted, rejected)
+                             };
+                             if score_sync_response(&pm_sync, &cmd_tx_sync, &peer_source, outcome).await {
+                                 continue;
+                             }
+                             if received > 0 {
+                                 let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req: NetMessage::GetHeight }).await;
+                             }
+                        }
+                        NetMessage::SnapshotResponse { manifest } => {
+                             let local_height = chain_sync_task.lock().unwrap().height;
+                             let req = match manifest {
+                                 Some(m) if m.height > local_height && fast_sync.is_none() => {
+                                     info!("📸 Fast sync: fetching snapshot at height {} ({} chunks) from {}", m.height, m.chunk_hashes.len(), peer_source);
+                                     let req = NetMessage::GetSnapshotChunk { height: m.height, index: 0 };
+                                     fast_sync = Some((peer_source.clone(), m, Vec::new()));
+                                     req
+                                 }
+                                 // No usable snapshot: fall back to fetching blocks
+                                 _ => NetMessage::RequestBlockRange { start: local_height, end: local_height + MAX_BLOCK_RANGE - 1 },
+                             };
+                             let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req }).await;
+                        }
+                        NetMessage::SnapshotChunkResponse { height, index, entries } => {
+                             let Some((peer, manifest, chunks)) = fast_sync.as_mut() else { continue };
+                             if *peer != peer_source || height != manifest.height || index as usize != chunks.len() {
+                                 continue;
+                             }
+                             if let Err(e) = manifest.verify_chunk(chunks.len(), &entries) {
+                                 warn!("Fast sync from {} aborted: {}", peer_source, e);
+                                 fast_sync = None;
+                                 continue;
+                             }
+                             chunks.push(entries);
+                             let req = if chunks.len() < manifest.chunk_hashes.len() {
+                                 NetMessage::GetSnapshotChunk { height, index: index + 1 }
+                             } else {
+                                 let (_, manifest, chunks) = fast_sync.take().expect("fast sync in progress");
+                                 if let Err(e) = chain_sync_task.lock().unwrap().restore_snapshot(&manifest, &chunks) {
+                                     warn!("Snapshot from {} rejected: {}", peer_source, e);
+                                 }
+                                 // Then catch up on the blocks after it
+                                 NetMessage::GetHeight
+                             };
                              let _ = cmd_tx_sync.send(NetworkCommand::SendRequest { peer: peer_source.clone(), req }).await;
-                         }
+                        }
+                        _ => {}
                     }
-                    _ => {}
                 }
             }
         });
 
         // 2. Oracle Betting Loop
         let oracle_loop = self.oracle.clone();
-        tokio::spawn(async move {
-            println!("🤖 Oracle Betting Bridge Started.");
-            loop {
-                tokio::time::sleep(Duration::from_secs(60)).await;
-                let mut o = oracle_loop.lock().await;
-                o.process_betting_outcomes().await;
+        supervisor.spawn("oracle-betting", move || {
+            let oracle_loop = oracle_loop.clone();
+            async move {
+                println!("🤖 Oracle Betting Bridge Started.");
+                loop {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    let mut o = oracle_loop.lock().await;
+                    o.process_betting_outcomes().await;
+                }
             }
         });
 
+        // 2b. Redemption payouts: release each vault once its reported payout confirms
+        let payout_chain = self.chain.clone();
+        let payout_oracle = self.oracle.clone();
+        let payout_signer = self.identity.clone();
+        supervisor.spawn("redemption-payouts", move || {
+            let chain = payout_chain.clone();
+            let oracle = payout_oracle.clone();
+            let release_signer = payout_signer.clone();
+            async move {
+                let proposer_pk = release_signer.public_key_hex();
+                loop {
+                    tokio::time::sleep(Duration::from_secs(60)).await;
+                    let reported: Vec<_> = {
+                        let c_guard = chain.lock().unwrap();
+                        c_guard.storage.get_redemptions().into_iter().filter(|i| i.is_queued() && i.payout_tx.is_some()).collect()
+                    };
+                    for intent in reported {
+                        let external_tx = intent.payout_tx.clone().unwrap_or_default();
+                        if let Err(e) = oracle.lock().await.confirm_payout(&intent, &external_tx).await {
+                            info!("Redemption {} payout {} not confirmed yet: {}", intent.id, external_tx, e);
+                            continue;
+                        }
+                        let mut c_guard = chain.lock().unwrap();
+                        let head_hash = c_guard.head_hash().unwrap_or_default();
+                        let block_type = BlockType::RedemptionRelease {
+                            intent_id: intent.id.clone(),
+                            redeemer: intent.redeemer.clone(),
+                            external_tx: external_tx.clone(),
+                        };
+                        let released = poh_service::build_leader_header(block_type, c_guard.height, head_hash, &release_signer, signer::SignPurpose::RedemptionRelease)
+                            .map_err(|e| e.to_string())
+                            .and_then(|header| c_guard.append_redemption_release(header, &proposer_pk).map_err(|e| e.to_string()));
+                        match released {
+                            Ok(()) => info!("💸 Redemption {} paid by {}; {} released", intent.id, external_tx, intent.compass_asset),
+                            Err(e) => warn!("Redemption {} not released: {}", intent.id, e),
+                        }
+                    }
+                }
+            }
+        });
+
+        // 2c. Layer 2 checkpoints: the leader commits a hash of Layer 2 state when it has
+        // changed; followers compare new checkpoints with their own copy
+        let l2_interval = self.config.node.l2_checkpoint_interval_secs.unwrap_or(600);
+        if l2_interval > 0 {
+            let checkpoint_chain = self.chain.clone();
+            let checkpoint_layer2 = self.layer2.clone();
+            let checkpoint_signer = self.identity.clone();
+            supervisor.spawn("l2-checkpoints", move || {
+                let chain = checkpoint_chain.clone();
+                let layer2 = checkpoint_layer2.clone();
+                let l2_signer = checkpoint_signer.clone();
+                async move {
+                    let mut last_checked = 0;
+                    loop {
+                        tokio::time::sleep(Duration::from_secs(l2_interval)).await;
+                        let commitment = match layer2.lock().map_err(|e| e.to_string()).and_then(|l2| {
+                            if follower_mode { l2.commitment() } else { l2.snapshot() }.map_err(|e| e.to_string())
+                        }) {
+                            Ok(commitment) => commitment,
+                            Err(e) => {
+                                warn!("Layer 2 state not hashed: {}", e);
+                                continue;
+                            }
+                        };
+                        let mut c_guard = chain.lock().unwrap();
+                        let latest = c_guard.storage.get_latest_l2_checkpoint();
+
+                        if follower_mode {
+                            if let Some(checkpoint) = latest.filter(|c| c.sequence > last_checked) {
+                                last_checked = checkpoint.sequence;
+                                if checkpoint.state_hash == commitment.state_hash() {
+                                    info!("Layer 2 checkpoint #{} matches local state", checkpoint.sequence);
+                                } else {
+                                    warn!("Layer 2 checkpoint #{} differs from local state", checkpoint.sequence);
+                                }
+                            }
+                            continue;
+                        }
+
+                        let state_hash = commitment.state_hash();
+                        if latest.as_ref().is_some_and(|c| c.state_hash == state_hash) {
+                            continue;
+                        }
+                        let block_type = BlockType::L2Checkpoint {
+                            sequence: latest.map_or(1, |c| c.sequence + 1),
+                            state_hash: state_hash.clone(),
+                            economics_hash: commitment.economics_hash,
+                            assets_hash: commitment.assets_hash,
+                            collateral_hash: commitment.collateral_hash,
+                        };
+                        let head_hash = c_guard.head_hash().unwrap_or_default();
+                        let proposer_pk = l2_signer.public_key_hex();
+                        let committed = poh_service::build_leader_header(block_type, c_guard.height, head_hash, &l2_signer, signer::SignPurpose::L2Checkpoint)
+                            .map_err(|e| e.to_string())
+                            .and_then(|header| c_guard.append_l2_checkpoint(header, &proposer_pk).map_err(|e| e.to_string()));
+                        match committed {
+                            Ok(()) => info!("🧾 Layer 2 checkpoint {} committed", state_hash),
+                            Err(e) => warn!("Layer 2 checkpoint not committed: {}", e),
+                        }
+                    }
+                }
+            });
+        }
+
         // 3. RPC Server
         let rpc_chain = self.chain.clone();
         let rpc_pm = self.peer_manager.clone();
@@ -366,123 +743,673 @@ impl CompassNode {
         let rpc_cmd_tx = self.cmd_tx.clone();
         
         let rpc_identity = self.identity.public_key_hex();
-        
-        tokio::spawn(async move {
-            let server = crate::rpc::RpcServer::new(rpc_chain, rpc_pm, rpc_gs, rpc_vaults, rpc_wallets, rpc_layer2, rpc_betting, rpc_market, rpc_cmd_tx, rpc_port, rpc_identity);
-            server.start().await;
+
+        let rpc_faucet = self.config.faucet.as_ref().and_then(|cfg| {
+            let keypair = self.wallets.lock().ok()?.get_wallet(&cfg.wallet).and_then(|w| w.get_keypair());
+            let Some(keypair) = keypair else {
+                warn!("Faucet disabled: wallet '{}' not found or has no unlocked key", cfg.wallet);
+                return None;
+            };
+            match crate::faucet::Faucet::new(cfg, keypair) {
+                Ok(faucet) => {
+                    info!("🚰 Faucet enabled: paying from {}", faucet.address());
+                    Some(Arc::new(faucet))
+                }
+                Err(e) => {
+                    warn!("Faucet disabled: {}", e);
+                    None
+                }
+            }
+        });
+
+        let rpc_listeners = rpc_listeners(&self.config, rpc_port);
+        let rpc_tls = self.config.tls.clone();
+        let rpc_ipfs_gateway = self.config.node.ipfs_gateway.clone();
+        let rpc_sessions = Arc::new(match self.config.node.session_ttl_secs {
+            Some(secs) => crate::account::session::SessionStore::new(secs.saturating_mul(1000)),
+            None => crate::account::session::SessionStore::default(),
+        });
+        supervisor.spawn("rpc", move || {
+            let mut servers = tokio::task::JoinSet::new();
+            for (bind, allowlist) in &rpc_listeners {
+                let server = crate::rpc::RpcServer::new(
+                    rpc_chain.clone(),
+                    rpc_pm.clone(),
+                    rpc_gs.clone(),
+                    rpc_vaults.clone(),
+                    rpc_wallets.clone(),
+                    rpc_layer2.clone(),
+                    rpc_betting.clone(),
+                    rpc_market.clone(),
+                    rpc_cmd_tx.clone(),
+                    rpc_port,
+                    rpc_identity.clone(),
+                )
+                .with_faucet(rpc_faucet.clone())
+                .with_tls(rpc_tls.clone())
+                .with_sessions(rpc_sessions.clone())
+                .with_ipfs_gateway(rpc_ipfs_gateway.clone())
+                .with_listener(bind.clone(), allowlist.clone());
+                servers.spawn(server.start());
+            }
+            // One listener failing drops the rest, and the supervisor restarts them together
+            async move {
+                servers.join_next().await;
+            }
         });
 
         // 4. Transaction Processor
         let gulf_stream = self.gulf_stream.clone();
-        let wallets = self.wallets.clone();
         let market = self.market.clone();
         let chain = self.chain.clone();
         let layer2 = self.layer2.clone(); // For NFT usage
+        let receipt_signer = self.identity.clone();
+        let result_retention_secs = self.config.node.job_result_retention_hours.unwrap_or(168) * 3600;
+        let snapshot_interval = self.config.node.snapshot_interval_blocks.unwrap_or(3600);
+        let pruning = self.config.pruning.clone();
         
-        tokio::spawn(async move {
-            loop {
-                let mut txs_to_process = Vec::new();
-                {
-                    let mut gs = gulf_stream.lock().unwrap();
-                    let popped = gs.pop_ready_transactions(5000);
-                    for tx in popped { txs_to_process.push(tx); }
-                }
+        let tx_processor = move || {
+            let gulf_stream = gulf_stream.clone();
+            let market = market.clone();
+            let chain = chain.clone();
+            let layer2 = layer2.clone();
+            let receipt_signer = receipt_signer.clone();
+            let pruning = pruning.clone();
+            async move {
+                let mut last_result_prune = std::time::Instant::now();
+                let mut last_block_prune = std::time::Instant::now();
+                let mut next_snapshot_at = {
+                    let c_guard = chain.lock().unwrap();
+                    c_guard.storage.latest_snapshot().map(|m| m.height + 1).unwrap_or(0) + snapshot_interval
+                };
+                loop {
+                    // Scheduled transactions whose slot has arrived go back through the leader's
+                    // mempool; followers drop their copies and get the result in a block
+                    let due = {
+                        let c_guard = chain.lock().unwrap();
+                        c_guard.storage.take_due_scheduled_txs(c_guard.height)
+                    };
+                    if !due.is_empty() && !follower_mode {
+                        let mut gs = gulf_stream.lock().unwrap();
+                        for (slot, inner) in due {
+                            if let Ok(raw_tx) = encoding::canonical_bytes(&inner) {
+                                let tx_hash = encoding::sha256(&raw_tx).to_vec();
+                                match gs.try_add_transaction(tx_hash, raw_tx, 0) {
+                                    Ok(()) => info!("⏰ Released scheduled transaction (slot {})", slot),
+                                    Err(e) => warn!("Scheduled transaction for slot {} dropped: {}", slot, e),
+                                }
+                            }
+                        }
+                    }
+
+                    if last_result_prune.elapsed() >= Duration::from_secs(3600) {
+                        last_result_prune = std::time::Instant::now();
+                        let now = block::current_unix_timestamp_ms() / 1000;
+                        match chain.lock().unwrap().storage.prune_job_results(now) {
+                            Ok(0) => {}
+                            Ok(n) => info!("🧹 Expired {} job results", n),
+                            Err(e) => warn!("Job result pruning failed: {}", e),
+                        }
+                        match chain.lock().unwrap().storage.prune_client_ids(block::current_unix_timestamp_ms()) {
+                            Ok(0) => {}
+                            Ok(n) => info!("🧹 Expired {} client_id records", n),
+                            Err(e) => warn!("client_id pruning failed: {}", e),
+                        }
+                    }
+
+                    if let Some(pruning) = &pruning {
+                        if last_block_prune.elapsed() >= Duration::from_secs(pruning.interval_secs) {
+                            last_block_prune = std::time::Instant::now();
+                            match chain.lock().unwrap().prune(pruning.keep_blocks) {
+                                Ok(0) => {}
+                                Ok(n) => info!("🧹 Pruned {} old blocks", n),
+                                Err(e) => warn!("Block pruning failed: {}", e),
+                            }
+                        }
+                    }
+
+                    if snapshot_interval > 0 {
+                        let c_guard = chain.lock().unwrap();
+                        if c_guard.height >= next_snapshot_at {
+                            next_snapshot_at = c_guard.height + snapshot_interval;
+                            match c_guard.take_snapshot() {
+                                Ok(m) => info!("📸 State snapshot at height {} ({} chunks)", m.height, m.chunk_hashes.len()),
+                                Err(e) => warn!("State snapshot failed: {}", e),
+                            }
+                        }
+                    }
 
-                if !txs_to_process.is_empty() {
-                    let m_guard = market.lock().unwrap();
-                    let mut c_guard = chain.lock().unwrap();
+                    let mut txs_to_process = Vec::new();
+                    {
+                        let mut gs = gulf_stream.lock().unwrap();
+                        let popped = gs.pop_ready_transactions(5000);
+                        for tx in popped { txs_to_process.push(tx); }
+                    }
+
+                    if !txs_to_process.is_empty() {
+                        let mut m_guard = market.lock().unwrap();
+                        let mut c_guard = chain.lock().unwrap();
                     
-                    for tx in txs_to_process {
-                         if let Ok(payload) = bincode::deserialize::<TransactionPayload>(&tx.raw_tx) {
-                             match payload {
-                                 TransactionPayload::MintModelNFT(params) => {
-                                     let mut l2 = layer2.lock().unwrap();
-                                     let nft = crate::layer3::model_nft::ModelNFT {
-                                         token_id: params.model_id.clone(),
-                                         name: params.name,
-                                         description: params.description,
-                                         creator: params.creator.clone(),
-                                         license: crate::layer3::model_nft::LicenseType::Commercial,
-                                         rental_status: None,
+                        for tx in txs_to_process {
+                             let tx_hash = encoding::hash_to_hex(&tx.tx_hash);
+                             // Envelope signer, for payloads only their own account may send
+                             let (payload, envelope_signer) = match crate::network::decode_transaction(&tx.raw_tx) {
+                                 Ok(WireTransaction::Signed(stx)) => {
+                                     // Payloads without a nonce of their own spend the envelope's
+                                     let account = stx.account();
+                                     if stx.payload.get_nonce().is_none() {
+                                         let expected = c_guard.storage.get_nonce(&account).unwrap_or(0) + 1;
+                                         if stx.nonce != expected {
+                                             c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(format!("invalid nonce: expected {}, got {}", expected, stx.nonce)));
+                                             continue;
+                                         }
+                                         if let Err(e) = c_guard.storage.writer().set_nonce(&account, stx.nonce) {
+                                             c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e.to_string()));
+                                             continue;
+                                         }
+                                     }
+                                     (stx.payload, Some(account))
+                                 }
+                                 Ok(WireTransaction::Bare(payload)) => (payload, None),
+                                 Err(e) => {
+                                     c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e));
+                                     continue;
+                                 }
+                             };
+                             c_guard.hooks.on_tx(&payload);
+                                 match payload {
+                                     TransactionPayload::MintModelNFT(params) => {
+                                         let mut l2 = layer2.lock().unwrap();
+                                         let nft = crate::layer3::model_nft::ModelNFT {
+                                             token_id: params.model_id.clone(),
+                                             name: params.name,
+                                             description: params.description,
+                                             creator: params.creator.clone(),
+                                             license: crate::layer3::model_nft::LicenseType::Commercial,
+                                             rental_status: None,
                                          
-                                         // Use stats from params (preserves model performance!)
-                                         accuracy: params.accuracy,
-                                         win_rate: params.win_rate,
-                                         total_predictions: params.total_predictions as usize,
-                                         profitable_predictions: params.profitable_predictions as usize,
-                                         total_profit: params.total_profit,
-                                         training_samples: params.training_samples as usize,
-                                         training_epochs: params.training_epochs as usize,
-                                         final_loss: params.final_loss,
-                                         training_duration_seconds: params.training_duration_seconds,
+                                             // Use stats from params (preserves model performance!)
+                                             accuracy: params.accuracy,
+                                             win_rate: params.win_rate,
+                                             total_predictions: params.total_predictions as usize,
+                                             profitable_predictions: params.profitable_predictions as usize,
+                                             total_profit: params.total_profit,
+                                             training_samples: params.training_samples as usize,
+                                             training_epochs: params.training_epochs as usize,
+                                             final_loss: params.final_loss,
+                                             training_duration_seconds: params.training_duration_seconds,
                                          
-                                         // Model metadata
-                                         trained_on_data_hash: "binance_5m".into(),
-                                         weights_hash: format!("model_gen_{}", params.generation),
-                                         weights_uri: format!("ipfs://model_{}", params.model_id),
-                                         architecture: params.architecture,
-                                         parent_models: params.parent_models,
-                                         generation: params.generation,
-                                         mint_price: params.mint_price,
+                                             // Model metadata
+                                             trained_on_data_hash: "binance_5m".into(),
+                                             weights_hash: format!("model_gen_{}", params.generation),
+                                             weights_uri: format!("ipfs://model_{}", params.model_id),
+                                             architecture: params.architecture,
+                                             parent_models: params.parent_models,
+                                             generation: params.generation,
+                                             mint_price: params.mint_price,
                                          
-                                         royalty_rate: 0.05,
-                                         current_owner: params.creator.clone(),
-                                         sale_history: vec![],
-                                         minted_at: block::current_unix_timestamp_ms(),
-                                         last_updated: block::current_unix_timestamp_ms(),
-                                     };
-                                     l2.assets.register_mint(nft.clone(), params.creator);
-                                     let _ = l2.save("layer2.json"); 
+                                             royalty_rate: 0.05,
+                                             current_owner: params.creator.clone(),
+                                             sale_history: vec![],
+                                             minted_at: block::current_unix_timestamp_ms(),
+                                             last_updated: block::current_unix_timestamp_ms(),
+                                         };
+                                         l2.assets.register_mint(nft.clone(), params.creator);
+                                         let _ = l2.save("layer2.json"); 
                                      
-                                     // Also save to verified Sled Storage
-                                     if let Err(e) = c_guard.storage.save_model_nft(&nft) {
-                                         tracing::error!("Failed to save Model NFT to Sled: {}", e);
-                                     } else {
-                                         println!("✅ Presisted NFT to Chain Storage: {}", params.model_id);
-                                     }
+                                         // Also save to verified Sled Storage
+                                         if let Err(e) = c_guard.storage.save_model_nft(&nft) {
+                                             tracing::error!("Failed to save Model NFT to Sled: {}", e);
+                                         } else {
+                                             println!("✅ Presisted NFT to Chain Storage: {}", params.model_id);
+                                         }
                                      
-                                     println!("✅ L2: Minted NFT {}", params.model_id);
-                                 },
-                                 TransactionPayload::Stake(params) => {
-                                      let mut l2 = layer2.lock().unwrap();
-                                      l2.collateral.stake(params.entity.clone(), params.amount);
-                                      let _ = l2.save("layer2.json");
-                                      println!("✅ L2: Staked {} for {}", params.amount, params.entity);
-                                 },
-                                 TransactionPayload::Result(params) => {
-                                      // PoUW Logic
-                                      let reward = if params.compute_rate > 0 { params.compute_rate / 1000 } else { 1 };
-                                      let _ = c_guard.storage.update_balance(&params.worker_id, "COMPUTE", reward);
-                                      println!("✅ L1: PoUW Reward {} COMPUTE", reward);
-                                 },
-                                  // .. other standard txs like Transfer ..
-                                 TransactionPayload::Transfer { from, to, amount, nonce: _, signature, public_key, timestamp, prev_hash, .. } => {
-                                      // Construct block... check sig... append
-                                      // Minimal implementation
-                                      if crate::crypto::verify_with_pubkey_hex(from.as_bytes(), &signature, &public_key) { 
-                                           let header = crate::block::BlockHeader {
-                                                index: c_guard.height,
-                                                timestamp,
-                                                prev_hash: prev_hash.clone(),
-                                                hash: "".into(),
-                                                proposer: from.clone(),
-                                                signature_hex: signature.clone(),
-                                                block_type: BlockType::Transfer { from: from.clone(), to: to.clone(), asset: "Compass".into(), amount, nonce: 0, fee: 0 },
-                                           };
-                                           let mut h = header;
-                                           h.hash = h.calculate_hash().unwrap_or_default();
-                                           let _ = c_guard.append_transfer(h, &public_key);
-                                      }
-                                 },
-                                 _ => {}
-                             }
-                         }
+                                         println!("✅ L2: Minted NFT {}", params.model_id);
+                                         c_guard.record_tx_status(&tx_hash, TxStatus::Confirmed, None);
+                                     },
+                                     TransactionPayload::Stake(params) => {
+                                          let mut l2 = layer2.lock().unwrap();
+                                          l2.collateral.stake(params.entity.clone(), params.amount);
+                                          let _ = l2.save("layer2.json");
+                                          println!("✅ L2: Staked {} for {}", params.amount, params.entity);
+                                          c_guard.record_tx_status(&tx_hash, TxStatus::Confirmed, None);
+                                     },
+                                     TransactionPayload::Unstake(params) => {
+                                          if envelope_signer.as_deref() != Some(params.entity.as_str()) {
+                                               c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some("unstake must be signed by the staking entity".into()));
+                                               continue;
+                                          }
+                                          let slot = match c_guard.head_slot() {
+                                               Ok(slot) => slot,
+                                               Err(e) => {
+                                                    c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e.to_string()));
+                                                    continue;
+                                               }
+                                          };
+                                          let mut l2 = layer2.lock().unwrap();
+                                          match l2.unstake(&params.entity, params.amount, slot) {
+                                               Ok(release_slot) => {
+                                                    println!("✅ L2: Unbonding {} for {} until slot {}", params.amount, params.entity, release_slot);
+                                                    c_guard.record_tx_status(&tx_hash, TxStatus::Confirmed, None);
+                                               }
+                                               Err(e) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e)),
+                                          }
+                                     },
+                                     TransactionPayload::Result(params) => {
+                                          // Keep the first output per job for its creator
+                                          if let Ok(None) = c_guard.storage.get_job_result(&params.job_id) {
+                                               let now = block::current_unix_timestamp_ms() / 1000;
+                                               let result = crate::layer3::compute::JobResult {
+                                                    job_id: params.job_id.clone(),
+                                                    worker_id: params.worker_id.clone(),
+                                                    result_hash: encoding::hash_to_hex(&encoding::sha256(&params.result_data)),
+                                                    data: params.result_data.clone(),
+                                                    stored_at: now,
+                                                    expires_at: now + result_retention_secs,
+                                               };
+                                               if let Err(e) = c_guard.storage.save_job_result(&result) {
+                                                    warn!("Failed to store result for job {}: {}", params.job_id, e);
+                                               }
+                                          }
+                                          // PoUW: paid from the job's declared units once enough workers agree
+                                          let result_hash = encoding::hash_to_hex(&encoding::sha256(&params.result_data));
+                                          let now = block::current_unix_timestamp_ms() / 1000;
+                                          match c_guard.record_pouw_result(&params.job_id, &params.worker_id, &result_hash, now) {
+                                               Ok(outcome) => {
+                                                    if let crate::layer3::pouw::PouwOutcome::Settled { rewards } = &outcome {
+                                                         for (worker, reward) in rewards {
+                                                              println!("✅ L1: PoUW Reward {} COMPUTE to {}", reward, worker);
+                                                         }
+                                                         c_guard.storage.delete_compute_job(&params.job_id).ok();
+                                                    }
+                                                    c_guard.record_tx_status(&tx_hash, TxStatus::Confirmed, None);
+                                               }
+                                               Err(e) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e.to_string())),
+                                          }
+                                     },
+                                      // .. other standard txs like Transfer ..
+                                     TransactionPayload::Transfer { from, to, asset, amount, nonce, fee, signature, public_key, timestamp, prev_hash } => {
+                                          // append_transfer checks the signature and that the nonce is exactly the next one
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp,
+                                               prev_hash,
+                                               hash: "".into(),
+                                               proposer: from.clone(),
+                                               signature_hex: signature,
+                                               block_type: BlockType::Transfer { from: from.clone(), to, asset, amount, nonce, fee },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_transfer(header, &public_key)) {
+                                               warn!("Transfer from {} (nonce {}) rejected: {}", from, nonce, e);
+                                          }
+                                     },
+                                     TransactionPayload::Burn { vault_id, collateral_asset, compass_asset, amount, redeemer, recipient_btc_addr, signature, public_key, fee, timestamp, prev_hash } => {
+                                          // append_burn checks the signature and queues the payout to the redeemer
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp,
+                                               prev_hash,
+                                               hash: "".into(),
+                                               proposer: redeemer.clone(),
+                                               signature_hex: signature,
+                                               block_type: BlockType::Burn {
+                                                    vault_id,
+                                                    collateral_asset,
+                                                    compass_asset,
+                                                    burn_amount: amount,
+                                                    redeemer: redeemer.clone(),
+                                                    destination_address: recipient_btc_addr,
+                                                    fee,
+                                               },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_burn(header, &public_key)) {
+                                               warn!("Burn by {} rejected: {}", redeemer, e);
+                                          }
+                                     },
+                                     TransactionPayload::SponsoredTransfer { from, to, asset, amount, nonce, fee, signature, public_key, fee_payer, fee_payer_public_key, fee_payer_signature, timestamp, prev_hash } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp,
+                                               prev_hash,
+                                               hash: "".into(),
+                                               proposer: from.clone(),
+                                               signature_hex: signature,
+                                               block_type: BlockType::SponsoredTransfer { from: from.clone(), to, asset, amount, nonce, fee, fee_payer: fee_payer.clone(), fee_payer_signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_sponsored_transfer(header, &public_key, &fee_payer_public_key)) {
+                                               Ok(()) => println!("✅ L1: Sponsored transfer from {} (fee paid by {})", from, fee_payer),
+                                               Err(e) => warn!("Sponsored transfer from {} rejected: {}", from, e),
+                                          }
+                                     },
+                                     TransactionPayload::AssetFeeTransfer { from, to, asset, amount, nonce, fee, fee_asset, max_fee_in, signature, public_key, timestamp, prev_hash } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp,
+                                               prev_hash,
+                                               hash: "".into(),
+                                               proposer: from.clone(),
+                                               signature_hex: signature,
+                                               block_type: BlockType::AssetFeeTransfer { from: from.clone(), to, asset, amount, nonce, fee, fee_asset: fee_asset.clone(), max_fee_in },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_asset_fee_transfer(header, &public_key)) {
+                                               Ok(()) => println!("✅ L1: Transfer from {} (fee paid in {})", from, fee_asset),
+                                               Err(e) => warn!("Transfer from {} with {} fee rejected: {}", from, fee_asset, e),
+                                          }
+                                     },
+                                     TransactionPayload::MultisigTransfer { from, to, asset, amount, nonce, approvals } => {
+                                          // No header signature to pin, so the block links to the current head
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: from.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::MultisigTransfer { from: from.clone(), to, asset, amount, nonce, approvals },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_multisig_transfer(header)) {
+                                               Ok(()) => println!("✅ L1: Multisig transfer from {} (nonce {})", from, nonce),
+                                               Err(e) => warn!("Multisig transfer from {} rejected: {}", from, e),
+                                          }
+                                     },
+                                     TransactionPayload::FinalityVote { voter, target_hash, target_height, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: voter.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::FinalityVote { voter: voter.clone(), target_hash, target_height, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_finality_vote(header)) {
+                                               warn!("Finality vote by {} for height {} rejected: {}", voter, target_height, e);
+                                          }
+                                     },
+                                     TransactionPayload::ClaimEarnings { worker, amount, nonce, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: worker.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::EarningsClaim { worker: worker.clone(), amount, nonce, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_earnings_claim(header)) {
+                                               Ok(()) => println!("💸 Earnings claim: {} COMPUTE to {}", amount, worker),
+                                               Err(e) => warn!("Earnings claim by {} rejected: {}", worker, e),
+                                          }
+                                     },
+                                     TransactionPayload::Delegate { delegator, representative, nonce, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: delegator.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::Delegate { delegator: delegator.clone(), representative, nonce, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_delegate(header)) {
+                                               warn!("Delegation by {} rejected: {}", delegator, e);
+                                          }
+                                     },
+                                     TransactionPayload::WithdrawUnbonded { entity, nonce, signature } => {
+                                          // Whatever has matured by the block's slot, which the executor releases again
+                                          let timestamp = block::current_unix_timestamp_ms();
+                                          let queue = c_guard.storage.get_l2_unbonding_of(&entity).unwrap_or_default();
+                                          let amount = crate::layer2::collateral::matured(&queue, c_guard.slot_at(timestamp));
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp,
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: entity.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::UnbondedWithdrawal { entity: entity.clone(), amount, nonce, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_unbonded_withdrawal(header)) {
+                                               Ok(()) => {
+                                                    layer2.lock().unwrap().collateral.reload_unbonding(&entity);
+                                                    println!("✅ L2: Withdrew {} unbonded collateral of {}", amount, entity);
+                                               }
+                                               Err(e) => warn!("Unbonded withdrawal by {} rejected: {}", entity, e),
+                                          }
+                                     },
+                                     TransactionPayload::DeployContract { deployer, code, gas_limit, gas_price, nonce, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: deployer.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::ContractDeploy { deployer: deployer.clone(), code, gas_limit, gas_price, nonce, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_contract(header)) {
+                                               Ok(Ok(outcome)) => println!("📜 Contract {} deployed by {} ({} gas)", outcome.contract, deployer, outcome.gas_used),
+                                               // Committed with its gas paid, but nothing deployed
+                                               Ok(Err(e)) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e)),
+                                               Err(e) => warn!("Contract deployment by {} rejected: {}", deployer, e),
+                                          }
+                                     },
+                                     TransactionPayload::CallContract { caller, contract, method, input, gas_limit, gas_price, nonce, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: caller.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::ContractCall {
+                                                    caller: caller.clone(),
+                                                    contract: contract.clone(),
+                                                    method: method.clone(),
+                                                    input,
+                                                    gas_limit,
+                                                    gas_price,
+                                                    nonce,
+                                                    signature,
+                                               },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_contract(header)) {
+                                               Ok(Ok(outcome)) => println!("📜 {}::{} called by {} ({} gas)", contract, method, caller, outcome.gas_used),
+                                               Ok(Err(e)) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e)),
+                                               Err(e) => warn!("Contract call by {} rejected: {}", caller, e),
+                                          }
+                                     },
+                                     TransactionPayload::InvokeModel { token_id, caller, inputs, timestamp, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: caller.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::ModelInvocation { token_id: token_id.clone(), caller: caller.clone(), inputs, timestamp, signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_model_invocation(header)) {
+                                               warn!("Payment for model {} by {} rejected: {}", token_id, caller, e);
+                                          }
+                                     },
+                                     TransactionPayload::SetFrozen { compass_asset, account, frozen, reason, authority, seq, signature } => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: authority.clone(),
+                                               signature_hex: signature.clone(),
+                                               block_type: BlockType::AssetFreeze { compass_asset, account, frozen, authority, reason, seq, authority_signature: signature },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_asset_freeze(header)) {
+                                               Ok(r) => println!("🧊 L1: {} {} for {}", if r.frozen { "Froze" } else { "Thawed" }, r.compass_asset, r.account),
+                                               Err(e) => warn!("Freeze rejected: {}", e),
+                                          }
+                                     },
+                                     TransactionPayload::RegisterValidator(params) => {
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: params.validator_id.clone(),
+                                               signature_hex: params.signature.clone(),
+                                               block_type: BlockType::ValidatorRegistration {
+                                                    validator_id: params.validator_id.clone(),
+                                                    pubkey: params.pubkey,
+                                                    stake_amount: params.stake_amount,
+                                                    signature: params.signature,
+                                               },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          if let Err(e) = c_guard.apply_tx(&tx_hash, |c| c.append_validator_registration(header)) {
+                                               warn!("Validator registration for {} rejected: {}", params.validator_id, e);
+                                          }
+                                     },
+                                     TransactionPayload::PlaceOrder { user, side, base, quote, amount, price, order_type, time_in_force, .. } => {
+                                          let price = (order_type == crate::market::OrderType::Limit).then_some(price);
+                                          let storage = c_guard.storage.clone();
+                                          match m_guard.place_order(&user, side, &base, &quote, amount, price, time_in_force, &storage) {
+                                               Ok(outcome) => {
+                                                    // Each fill settles in its own Trade block, and its receipt is pinned to that block
+                                                    let proposer_pk = receipt_signer.public_key_hex();
+                                                    for trade in outcome.trades.iter().cloned() {
+                                                         let head_hash = c_guard.head_hash().unwrap_or_default();
+                                                         let settled = poh_service::build_leader_header(outcome.settlement(&trade), c_guard.height, head_hash, &receipt_signer, signer::SignPurpose::Trade)
+                                                              .map_err(|e| e.to_string())
+                                                              .and_then(|header| {
+                                                                   let pinned = (header.index, header.hash.clone());
+                                                                   c_guard.append_trade(header, &proposer_pk).map(|()| pinned).map_err(|e| e.to_string())
+                                                              });
+                                                         let (block_height, block_hash) = match settled {
+                                                              Ok(pinned) => pinned,
+                                                              Err(e) => {
+                                                                   warn!("Trade {} not settled: {}", trade.trade_id, e);
+                                                                   continue;
+                                                              }
+                                                         };
+                                                         let msg = crate::market::TradeReceipt::signing_message(&trade, block_height, &block_hash);
+                                                         match receipt_signer.sign_hex(&msg, signer::SignPurpose::TradeReceipt) {
+                                                              Ok(signature) => {
+                                                                   let receipt = crate::market::TradeReceipt {
+                                                                        trade,
+                                                                        block_height,
+                                                                        block_hash,
+                                                                        node_pubkey: proposer_pk.clone(),
+                                                                        signature,
+                                                                   };
+                                                                   if let Err(e) = c_guard.storage.save_trade_receipt(&receipt) {
+                                                                        warn!("Failed to store receipt for trade {}: {}", receipt.trade.trade_id, e);
+                                                                   }
+                                                              }
+                                                              Err(e) => warn!("Trade {} settled without a receipt: {}", trade.trade_id, e),
+                                                         }
+                                                    }
+                                                    for line in &outcome.logs { println!("📈 {}", line); }
+                                                    c_guard.record_tx_events(&tx_hash, outcome.events);
+                                               }
+                                               Err(e) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e)),
+                                          }
+                                     },
+                                     TransactionPayload::CancelOrder { user, order_id, .. } => {
+                                          if envelope_signer.as_deref() != Some(user.as_str()) {
+                                               c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some("cancel must be signed by the order's owner".into()));
+                                               continue;
+                                          }
+                                          let storage = c_guard.storage.clone();
+                                          match m_guard.cancel_order(&user, order_id, &storage) {
+                                               Ok(event) => {
+                                                    println!("📉 Cancelled order {} for {}", order_id, user);
+                                                    c_guard.record_tx_events(&tx_hash, vec![event]);
+                                               }
+                                               Err(e) => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some(e)),
+                                          }
+                                     },
+                                     TransactionPayload::Scheduled { execute_at_slot, fee, inner } => {
+                                          // Park until the slot in a block every node executes; released at the top of this loop
+                                          let Some(account) = inner.get_account_id() else {
+                                               c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some("scheduled transaction has no account".into()));
+                                               continue;
+                                          };
+                                          let mut header = crate::block::BlockHeader {
+                                               index: c_guard.height,
+                                               timestamp: block::current_unix_timestamp_ms(),
+                                               prev_hash: c_guard.head_hash().unwrap_or_default(),
+                                               hash: "".into(),
+                                               proposer: account.clone(),
+                                               signature_hex: String::new(),
+                                               block_type: BlockType::Schedule { account: account.clone(), execute_at_slot, fee, envelope: tx.raw_tx.clone() },
+                                               state_root: String::new(),
+                                               root_attestation: None,
+                                          };
+                                          header.hash = header.calculate_hash().unwrap_or_default();
+                                          match c_guard.apply_tx(&tx_hash, |c| c.append_schedule(header)) {
+                                               Ok(()) => info!("⏳ Parked scheduled tx {} until slot {}", &tx_hash[..12.min(tx_hash.len())], execute_at_slot),
+                                               Err(e) => warn!("Scheduled tx from {} rejected: {}", account, e),
+                                          }
+                                     },
+                                     _ => c_guard.record_tx_status(&tx_hash, TxStatus::Failed, Some("not executed by this node".into())),
+                                 }
+                        }
+                        m_guard.save("market.json");
                     }
-                    m_guard.save("market.json");
+                    tokio::time::sleep(Duration::from_millis(100)).await;
                 }
-                tokio::time::sleep(Duration::from_millis(100)).await;
             }
-        });
+        };
+        if !light_mode {
+            supervisor.spawn("tx-processor", tx_processor);
+
+            let (chain_vote, vote_signer, gs_vote, cmd_tx_vote) =
+                (self.chain.clone(), self.identity.clone(), self.gulf_stream.clone(), self.cmd_tx.clone());
+            let slot = Duration::from_millis(self.config.consensus.slot_duration_ms);
+            supervisor.spawn("finality-voter", move || {
+                finality_voter::run(chain_vote.clone(), vote_signer.clone(), gs_vote.clone(), cmd_tx_vote.clone(), slot)
+            });
+        }
 
         // 5. PoH Loop
         let admin_kp = self.identity.clone();
@@ -492,104 +1419,179 @@ impl CompassNode {
             let config_duration = self.config.consensus.slot_duration_ms;
             let target_duration = Duration::from_millis(config_duration);
             
-            tokio::spawn(async move {
-                use crate::poh_recorder::PoHRecorder;
-                let mut poh = PoHRecorder::new(b"COMPASS_GENESIS_SEED".to_vec(), 80_000); // 80k iterations ~ VDF work
-                
-                info!("PoH Service Started. Target Slot Duration: {}ms", config_duration);
-                info!("Initial VDF Difficulty: {} iterations/tick", poh.hashes_per_tick);
+            use crate::poh_recorder::PoHRecorder;
+            // Fill at most half the slot with VDF work so slow CPUs (e.g. SBCs) still keep up
+            let hashes_per_tick = self
+                .config
+                .consensus
+                .vdf_iterations
+                .unwrap_or_else(|| PoHRecorder::calibrate_hashes_per_tick(target_duration / 2));
+            let poh = PoHRecorder::new(b"COMPASS_GENESIS_SEED".to_vec(), hashes_per_tick);
 
-                loop {
-                    let start = std::time::Instant::now();
-                    let chain_poh = chain_poh_outer.clone();
-                    let admin_kp_poh = admin_kp.clone();
-                    
-                    // Run VDF in blocking thread to avoid starvation
-                    poh = tokio::task::spawn_blocking(move || {
-                        let (start_hash, end_hash) = poh.tick(); // Runs CPU intensive Modular Squaring
-                        
-                        // Create Block
-                        if let Ok(mut c_guard) = chain_poh.lock() {
-                            let head_hash = c_guard.head_hash().unwrap_or("0000000000000000000000000000000000000000000000000000000000000000".to_string());
-                            let height = c_guard.height;
-                            
-                            use crate::block::{BlockHeader, BlockType};
-                            let header = BlockHeader {
-                                index: height,
-                                timestamp: crate::block::current_unix_timestamp_ms(),
-                                prev_hash: head_hash,
-                                hash: "".to_string(),
-                                proposer: admin_kp_poh.public_key_hex(),
-                                signature_hex: "".to_string(),
-                                block_type: BlockType::PoH { 
-                                    tick: poh.tick_height,
-                                    iterations: poh.hashes_per_tick,
-                                    hash: hex::encode(&end_hash),
-                                    proof: "".to_string(), // Simplified
-                                },
-                            };
-                            
-                            let mut signed_header = header;
-                            signed_header.hash = signed_header.calculate_hash().unwrap();
-                            let sig = admin_kp_poh.sign(hex::decode(&signed_header.hash).unwrap().as_slice());
-                            signed_header.signature_hex = sig.to_string();
-
-                            // Append to Chain
-                            let _ = c_guard.append_poh(signed_header, &admin_kp_poh.public_key_hex());
-                        }
-                        poh
-                    }).await.expect("PoH Task failed");
+            info!("PoH Service Started. Target Slot Duration: {}ms", config_duration);
+            info!("Initial VDF Difficulty: {} iterations/tick", poh.hashes_per_tick);
 
-                    let elapsed = start.elapsed();
-                    let current_tick = poh.tick_height;
-                    
-                    if current_tick % 10 == 0 {
-                        info!("PoH Tick {} | VDF Time: {:?} | Hash: {}", 
-                            current_tick, elapsed, hex::encode(&poh.current_hash).get(0..16).unwrap_or(""));
-                    }
+            // VDF and block appends run on separate threads so chain lock contention never stalls the clock
+            let (vdf, appender) = crate::node::poh_service::start(chain_poh_outer, admin_kp, poh, target_duration, self.cmd_tx.clone(), supervisor.token());
+            supervisor.watch_thread("poh-vdf", vdf);
+            supervisor.watch_thread("poh-append", appender);
+        }
+        
+        
+        // 6. Auto-Trainer (Rust Native)
+        // This runs the enhanced Linear Regression model loop natively in the node
+        #[cfg(feature = "ai")]
+        if !light_mode {
+            supervisor.spawn("auto-trainer", || crate::trainer::AutoTrainer::new().run());
+        }
 
-                    // Dynamic Adjustment (Simple) or Sleep remainder
-                    if elapsed < target_duration {
-                        tokio::time::sleep(target_duration - elapsed).await;
-                    }
-                }
+        if let Some(alerts) = self.config.alerts.clone().filter(|a| !a.rules.is_empty()) {
+            info!("🔔 Alerting: {} rule(s), checked every {}s", alerts.rules.len(), alerts.interval_secs);
+            let (chain_monitor, peers, db_path) = (self.chain.clone(), self.peer_manager.clone(), self.db_path.clone());
+            supervisor.spawn("monitor", move || {
+                monitor::run(alerts.clone(), chain_monitor.clone(), peers.clone(), db_path.clone())
             });
         }
-        
-        // --- Graceful Shutdown Handler ---
-        // Register Ctrl+C handler to flush database before exit
-        let chain_flush = self.chain.clone();
-        tokio::spawn(async move {
-            tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
-            info!("🛑 Shutting down... flushing database");
-            if let Ok(c) = chain_flush.lock() {
-                if let Err(e) = c.storage.flush() {
-                    warn!("Failed to flush database: {}", e);
-                } else {
-                    info!("✅ Database flushed successfully");
+
+        // Blocks reach the disk on the flush policy's interval even when `every_blocks` doesn't trigger
+        let flush_storage = self.chain.lock().unwrap().storage.clone();
+        supervisor.spawn("db-flush", move || {
+            let storage = flush_storage.clone();
+            async move {
+                let mut ticker = tokio::time::interval(storage.flush_policy().interval.max(Duration::from_millis(10)));
+                loop {
+                    ticker.tick().await;
+                    if let Err(e) = storage.flush_if_due() {
+                        warn!("Database flush failed: {}", e);
+                    }
                 }
             }
-            std::process::exit(0);
         });
-        
-        // 6. Auto-Trainer (Rust Native)
-        // This runs the enhanced Linear Regression model loop natively in the node
-        let trainer = crate::trainer::AutoTrainer::new();
-        trainer.start().await;
 
         info!("Node Running. Press Ctrl+C to stop.");
-        // Keep main alive
-        loop { tokio::time::sleep(Duration::from_secs(60)).await; }
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            warn!("Failed to listen for Ctrl+C ({}); stopping now", e);
+        }
+        info!("🛑 Shutting down...");
+        supervisor.shutdown(SHUTDOWN_GRACE).await;
+        self.flush_state();
+    }
+
+    /// Persist everything held in memory; run once every task has stopped
+    fn flush_state(&self) {
+        if let Err(e) = self.wallets.lock().map_err(|e| e.to_string()).and_then(|w| w.save("wallets.json").map_err(|e| e.to_string())) {
+            warn!("Failed to save wallets: {}", e);
+        }
+        if let Ok(m) = self.market.lock() {
+            m.save("market.json");
+        }
+        if let Err(e) = self.vaults.lock().map_err(|e| e.to_string()).and_then(|v| v.save("vaults.json").map_err(|e| e.to_string())) {
+            warn!("Failed to save vaults: {}", e);
+        }
+        if let Err(e) = self.layer2.lock().map_err(|e| e.to_string()).and_then(|l| l.save("layer2.json").map_err(|e| e.to_string())) {
+            warn!("Failed to save layer2 state: {}", e);
+        }
+        match self.chain.lock() {
+            Ok(c) => match c.storage.flush() {
+                Ok(()) => info!("✅ Database flushed successfully"),
+                Err(e) => warn!("Failed to flush database: {}", e),
+            },
+            Err(_) => warn!("Chain lock poisoned; database not flushed"),
+        }
     }
 }
 
+fn now_secs() -> u64 {
+    crate::block::current_unix_timestamp_ms() / 1000
+}
+
+/// Address and allowlist of each RPC listener: `[[rpc_listeners]]` when set,
+/// otherwise every method on `0.0.0.0:<rpc_port>`
+fn rpc_listeners(
+    config: &crate::config::CompassConfig,
+    rpc_port: u16,
+) -> Vec<(String, Arc<crate::rpc::access::MethodAllowlist>)> {
+    use crate::rpc::access::{MethodAllowlist, RpcAccess};
+    if config.rpc_listeners.is_empty() {
+        return vec![(format!("0.0.0.0:{}", rpc_port), Arc::new(MethodAllowlist::all()))];
+    }
+    config
+        .rpc_listeners
+        .iter()
+        .map(|l| {
+            let loopback = l.bind.parse::<std::net::SocketAddr>().map(|a| a.ip().is_loopback()).unwrap_or(false);
+            if l.access == RpcAccess::Admin && !loopback {
+                warn!("RPC listener {} serves admin and wallet methods on a non-loopback address", l.bind);
+            }
+            info!("RPC listener {}: {:?} methods", l.bind, l.access);
+            (l.bind.clone(), Arc::new(MethodAllowlist::new(l.access, &l.allow)))
+        })
+        .collect()
+}
+
+/// Apply synced blocks in order until one is rejected. Returns how many were
+/// accepted and the rejection, if any.
+fn sync_blocks(
+    chain: &Arc<Mutex<Chain>>,
+    blocks: Vec<block::Block>,
+    peer: &str,
+) -> (u64, Option<crate::error::CompassError>) {
+    let mut c = chain.lock().unwrap();
+    let mut accepted = 0;
+    for block in blocks {
+        if let Err(e) = c.sync_block(block) {
+            warn!("Synced block from {} rejected: {}", peer, e);
+            return (accepted, Some(e));
+        }
+        accepted += 1;
+    }
+    (accepted, None)
+}
+
+/// Credit a peer for the synced blocks we accepted and strike it for an invalid one
+/// (bad hash or signature; an orphan or a fork is not its fault). Returns true when
+/// the strike got the peer banned, in which case it is disconnected.
+async fn score_sync_response(
+    peers: &Arc<Mutex<PeerManager>>,
+    cmd_tx: &mpsc::Sender<NetworkCommand>,
+    peer: &str,
+    (accepted, rejected): (u64, Option<crate::error::CompassError>),
+) -> bool {
+    use crate::error::CompassError;
+    let banned = {
+        let mut pm = peers.lock().unwrap();
+        pm.record_blocks_served(peer, accepted);
+        matches!(rejected, Some(CompassError::InvalidSignature | CompassError::HashMismatch(..)))
+            && pm.record_strike(peer, now_secs())
+    };
+    if banned {
+        warn!("⛔ Banned peer {} for sending invalid blocks", peer);
+        let _ = cmd_tx.send(NetworkCommand::Disconnect(peer.to_string())).await;
+    }
+    banned
+}
+
 // --- Helper for Node Startup (Exposed for Library Use) ---
 pub async fn run_node_mode_internal(
     config: crate::config::CompassConfig,
     peer_val: Option<String>, 
     explicit_identity: Option<std::sync::Arc<crate::crypto::KeyPair>>
+) {
+    run_node_with_hooks(config, peer_val, explicit_identity, Default::default()).await;
+}
+
+/// Start a node with integrator hooks attached before any block is produced or synced
+pub async fn run_node_with_hooks(
+    config: crate::config::CompassConfig,
+    peer_val: Option<String>,
+    explicit_identity: Option<std::sync::Arc<crate::crypto::KeyPair>>,
+    hooks: crate::hooks::HookRegistry,
 ) {
     let rpc_port = config.node.rpc_port;
     let node = CompassNode::new(config, explicit_identity).await;
+    if !hooks.is_empty() {
+        info!("🪝 {} execution hook(s) registered", hooks.len());
+    }
+    node.chain.lock().unwrap().hooks = hooks;
     node.start(Some(rpc_port), peer_val).await;
 }
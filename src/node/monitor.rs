@@ -0,0 +1,206 @@
+// Operator alerting
+//
+// `[alerts]` rules put thresholds on a handful of node health metrics. The
+// monitor task samples them every `interval_secs`, logs a warning when a rule
+// starts firing and a note when it clears, and posts both to `webhook_url` if
+// one is set. A rule fires once per breach rather than on every sample.
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tracing::{info, warn};
+
+use crate::chain::Chain;
+use crate::network::PeerManager;
+
+/// What a rule watches
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Metric {
+    /// Connected p2p peers
+    PeerCount,
+    /// Milliseconds since the latest PoH block; about one slot on a healthy chain
+    SlotTimeMs,
+    /// Used space on the disk holding the database, in percent
+    DiskUsedPercent,
+    /// Age in seconds of the stalest oracle price
+    OracleStalenessSecs,
+    /// Median delay from a leader announcing a block to this node hearing of it
+    BlockPropagationP50Ms,
+    /// 95th percentile of the same delay
+    BlockPropagationP95Ms,
+}
+
+/// Fires while `metric` is above `above` or below `below`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct AlertRule {
+    pub metric: Metric,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub above: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub below: Option<f64>,
+}
+
+impl AlertRule {
+    fn breached(&self, value: f64) -> bool {
+        self.above.is_some_and(|limit| value > limit) || self.below.is_some_and(|limit| value < limit)
+    }
+
+    fn describe(&self) -> String {
+        let metric = serde_json::to_value(self.metric).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default();
+        match (self.above, self.below) {
+            (Some(above), Some(below)) => format!("{} outside {}..={}", metric, below, above),
+            (Some(above), None) => format!("{} > {}", metric, above),
+            (None, Some(below)) => format!("{} < {}", metric, below),
+            (None, None) => metric,
+        }
+    }
+}
+
+/// A rule that started or stopped firing
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    pub rule: String,
+    pub value: f64,
+    pub firing: bool,
+}
+
+/// Which rules are currently firing
+#[derive(Debug, Default)]
+pub struct AlertState {
+    firing: Vec<bool>,
+}
+
+impl AlertState {
+    /// Check `sample` against `rules`, returning the rules whose state changed. Rules
+    /// whose metric has no value this time keep their state.
+    pub fn update(&mut self, rules: &[AlertRule], sample: &BTreeMap<Metric, f64>) -> Vec<AlertEvent> {
+        self.firing.resize(rules.len(), false);
+        let mut events = Vec::new();
+        for (rule, firing) in rules.iter().zip(self.firing.iter_mut()) {
+            let Some(&value) = sample.get(&rule.metric) else { continue };
+            let breached = rule.breached(value);
+            if breached != *firing {
+                *firing = breached;
+                events.push(AlertEvent { rule: rule.describe(), value, firing: breached });
+            }
+        }
+        events
+    }
+}
+
+/// Percent used of the disk whose mount point holds `path`
+fn disk_used_percent(path: &Path) -> Option<f64> {
+    let path = std::fs::canonicalize(path).ok()?;
+    let disks = sysinfo::Disks::new_with_refreshed_list();
+    let disk = disks
+        .list()
+        .iter()
+        .filter(|d| path.starts_with(d.mount_point()))
+        .max_by_key(|d| d.mount_point().as_os_str().len())?;
+    let total = disk.total_space();
+    (total > 0).then(|| 100.0 * total.saturating_sub(disk.available_space()) as f64 / total as f64)
+}
+
+/// Current value of every metric this node can measure
+pub fn sample(chain: &Arc<Mutex<Chain>>, peers: &Arc<Mutex<PeerManager>>, db_path: &str) -> BTreeMap<Metric, f64> {
+    let mut sample = BTreeMap::new();
+    if let Ok(pm) = peers.lock() {
+        sample.insert(Metric::PeerCount, pm.peers.len() as f64);
+        if let Some(p50) = pm.propagation.percentile(50) {
+            sample.insert(Metric::BlockPropagationP50Ms, p50 as f64);
+        }
+        if let Some(p95) = pm.propagation.percentile(95) {
+            sample.insert(Metric::BlockPropagationP95Ms, p95 as f64);
+        }
+    }
+    if let Some(percent) = disk_used_percent(Path::new(db_path)) {
+        sample.insert(Metric::DiskUsedPercent, percent);
+    }
+    let Ok(c_guard) = chain.lock() else { return sample };
+    let now_ms = crate::block::current_unix_timestamp_ms();
+    if let Ok(Some(cursor)) = c_guard.storage.get_slot_cursor() {
+        sample.insert(Metric::SlotTimeMs, now_ms.saturating_sub(cursor.timestamp_ms) as f64);
+    }
+    let now_secs = now_ms / 1000;
+    if let Some(oldest) = c_guard.storage.get_all_prices().iter().map(|(_, (_, ts))| *ts).min() {
+        sample.insert(Metric::OracleStalenessSecs, now_secs.saturating_sub(oldest) as f64);
+    }
+    sample
+}
+
+/// Sample and evaluate `config.rules` every `config.interval_secs` until the task is dropped
+pub async fn run(
+    config: crate::config::AlertsConfig,
+    chain: Arc<Mutex<Chain>>,
+    peers: Arc<Mutex<PeerManager>>,
+    db_path: String,
+) {
+    let http = reqwest::Client::builder().timeout(Duration::from_secs(10)).build().unwrap_or_default();
+    let mut state = AlertState::default();
+    loop {
+        tokio::time::sleep(Duration::from_secs(config.interval_secs.max(1))).await;
+        let current = sample(&chain, &peers, &db_path);
+        for event in state.update(&config.rules, &current) {
+            if event.firing {
+                warn!("🚨 Alert: {} (value {:.1})", event.rule, event.value);
+            } else {
+                info!("✅ Alert cleared: {} (value {:.1})", event.rule, event.value);
+            }
+            let Some(url) = &config.webhook_url else { continue };
+            let body = serde_json::json!({
+                "source": "compass-node",
+                "rule": event.rule,
+                "value": event.value,
+                "status": if event.firing { "firing" } else { "resolved" },
+            });
+            if let Err(e) = http.post(url).json(&body).send().await {
+                warn!("Alert webhook delivery failed: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rules_fire_once_and_clear() {
+        let config: crate::config::AlertsConfig = toml::from_str(
+            r#"
+            interval_secs = 5
+            [[rules]]
+            metric = "peer_count"
+            below = 3
+            [[rules]]
+            metric = "slot_time_ms"
+            above = 2000
+            "#,
+        )
+        .unwrap();
+        assert_eq!(config.rules[0], AlertRule { metric: Metric::PeerCount, above: None, below: Some(3.0) });
+
+        let mut state = AlertState::default();
+        let sample = |peers: f64, slot: Option<f64>| {
+            let mut s = BTreeMap::from([(Metric::PeerCount, peers)]);
+            if let Some(ms) = slot {
+                s.insert(Metric::SlotTimeMs, ms);
+            }
+            s
+        };
+
+        let fired = state.update(&config.rules, &sample(1.0, Some(2500.0)));
+        assert_eq!(fired.len(), 2);
+        assert!(fired.iter().all(|e| e.firing));
+        assert_eq!(fired[0].rule, "peer_count < 3");
+
+        // Still breached: nothing new. A missing sample keeps the slot rule firing.
+        assert!(state.update(&config.rules, &sample(2.0, None)).is_empty());
+
+        let cleared = state.update(&config.rules, &sample(5.0, Some(900.0)));
+        assert_eq!(cleared.len(), 2);
+        assert!(cleared.iter().all(|e| !e.firing));
+    }
+}
@@ -6,11 +6,14 @@ use crate::chain::Chain;
 use crate::layer3::compute::{ComputeJob, ComputeJobStatus};
 use crate::layer3::paper_trading::TradingPortfolio;
 use crate::network::{NetworkCommand, NetMessage};
+use crate::node::signer::NodeSigner;
 
 /// OracleScheduler fetches live prices and creates ComputeJobs for inference
 pub struct OracleScheduler {
     pub chain: Arc<Mutex<Chain>>,
     pub creator_pubkey: String,
+    /// Signs the price feed attestations
+    pub identity: Arc<NodeSigner>,
     pub cmd_tx: mpsc::Sender<NetworkCommand>,
     pub client: reqwest::Client,
     pub paper_portfolio: Arc<Mutex<TradingPortfolio>>,  // Paper trading portfolio
@@ -23,7 +26,8 @@ struct BinanceTicker {
 }
 
 impl OracleScheduler {
-    pub fn new(chain: Arc<Mutex<Chain>>, creator_pubkey: String, cmd_tx: mpsc::Sender<NetworkCommand>) -> Self {
+    pub fn new(chain: Arc<Mutex<Chain>>, identity: Arc<NodeSigner>, cmd_tx: mpsc::Sender<NetworkCommand>) -> Self {
+        let creator_pubkey = identity.public_key_hex();
         let client = reqwest::Client::builder()
             .timeout(Duration::from_secs(10))
             .build()
@@ -38,7 +42,7 @@ impl OracleScheduler {
             }
         };
         
-        Self { chain, creator_pubkey, cmd_tx, client, paper_portfolio }
+        Self { chain, creator_pubkey, identity, cmd_tx, client, paper_portfolio }
     }
 
     pub async fn start(self) {
@@ -120,6 +124,9 @@ impl OracleScheduler {
                     }
                 }
             }
+
+            // 4. Sign the aggregated prices of rounds that have closed
+            self.attest_price_rounds();
             
             // Wait 30 seconds before next cycle
             tokio::time::sleep(Duration::from_secs(30)).await;
@@ -190,7 +197,21 @@ impl OracleScheduler {
         }
     }
 
-    /// Verify predictions that are old enough (5 minutes by default)
+    /// Add the closed rounds of every published ticker to the signed price feed
+    fn attest_price_rounds(&self) {
+        let Ok(chain) = self.chain.lock() else { return };
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+        let current_round = chain.oracle_aggregation.round_at(now);
+        for ticker in chain.vault_manager.oracle_prices.keys() {
+            match crate::oracle::feed::attest_closed_rounds(&chain.storage, ticker, current_round, now, &self.identity) {
+                Ok(signed) if !signed.is_empty() => info!("🖋️ Attested {} {} price round(s)", signed.len(), ticker),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to attest {} price rounds: {}", ticker, e),
+            }
+        }
+    }
+
+        /// Verify predictions that are old enough (5 minutes by default)
     async fn verify_pending_predictions(&self) {
         use crate::layer3::price_oracle::{PriceOracle, TradingSignal, EpochConfig, ModelEpochState};
         
@@ -0,0 +1,324 @@
+// PoH block production
+//
+// The VDF runs continuously on its own OS thread and never touches the chain
+// mutex; each completed tick is handed over a bounded channel to a separate
+// appender thread, which is the only part that locks the chain. A slow RPC call
+// holding the lock therefore delays appends but never the clock itself, and the
+// appender drains any backlog as soon as the lock is free. The appender also
+// writes the report block for an epoch once its own PoH block opens the next one,
+// and announces each block it appends to the network.
+use std::sync::mpsc::{sync_channel, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tracing::{info, warn};
+
+use crate::block::{BlockHeader, BlockType};
+use crate::chain::Chain;
+use crate::epoch_report::EpochReport;
+use crate::network::{NetMessage, NetworkCommand};
+use crate::node::signer::{NodeSigner, SignPurpose, SignerError};
+use crate::node::supervisor::ShutdownToken;
+use crate::poh_recorder::PoHRecorder;
+
+/// Ticks buffered between the VDF and the appender before new ones are dropped
+pub const TICK_CHANNEL_CAPACITY: usize = 256;
+
+/// A completed VDF tick, ready to become a PoH block
+#[derive(Debug, Clone)]
+pub struct PohTick {
+    pub tick: u64,
+    pub iterations: u64,
+    pub end_hash: Vec<u8>,
+    pub vdf_time: Duration,
+}
+
+/// Run the VDF on a dedicated thread until shutdown, publishing one tick per slot
+pub fn spawn_vdf_worker(
+    mut poh: PoHRecorder,
+    slot_duration: Duration,
+    ticks: SyncSender<PohTick>,
+    shutdown: ShutdownToken,
+) -> JoinHandle<()> {
+    std::thread::Builder::new()
+        .name("poh-vdf".to_string())
+        .spawn(move || loop {
+            // Returning drops the sender, which in turn stops the appender
+            if shutdown.is_shutdown() {
+                return;
+            }
+            let start = Instant::now();
+            let (_start_hash, end_hash) = poh.tick();
+            let tick = PohTick {
+                tick: poh.tick_height,
+                iterations: poh.hashes_per_tick,
+                end_hash,
+                vdf_time: start.elapsed(),
+            };
+
+            match ticks.try_send(tick) {
+                Ok(()) => {}
+                Err(TrySendError::Full(t)) => warn!("PoH appender is behind; dropped tick {}", t.tick),
+                Err(TrySendError::Disconnected(_)) => {
+                    warn!("PoH appender stopped; VDF worker exiting");
+                    return;
+                }
+            }
+
+            let elapsed = start.elapsed();
+            if elapsed < slot_duration {
+                std::thread::sleep(slot_duration - elapsed);
+            }
+        })
+        .expect("Failed to spawn PoH VDF thread")
+}
+
+/// Build and sign the PoH block for `tick` on top of the given head
+pub fn build_poh_header(
+    tick: &PohTick,
+    height: u64,
+    head_hash: String,
+    proposer: &NodeSigner,
+) -> Result<BlockHeader, SignerError> {
+    let mut header = BlockHeader {
+        index: height,
+        timestamp: crate::block::current_unix_timestamp_ms(),
+        prev_hash: head_hash,
+        hash: String::new(),
+        proposer: proposer.public_key_hex(),
+        signature_hex: String::new(),
+        block_type: BlockType::PoH {
+            tick: tick.tick,
+            iterations: tick.iterations,
+            hash: hex::encode(&tick.end_hash),
+            proof: "".to_string(), // Simplified
+        },
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash().unwrap_or_default();
+    if let Ok(raw) = hex::decode(&header.hash) {
+        header.signature_hex = proposer.sign_hex(&raw, SignPurpose::PohBlock)?;
+    }
+    Ok(header)
+}
+
+/// Build and sign the report block for a finished epoch on top of the given head
+pub fn build_epoch_report_header(
+    report: &EpochReport,
+    height: u64,
+    head_hash: String,
+    proposer: &NodeSigner,
+) -> Result<BlockHeader, SignerError> {
+    let mut header = BlockHeader {
+        index: height,
+        timestamp: crate::block::current_unix_timestamp_ms(),
+        prev_hash: head_hash,
+        hash: String::new(),
+        proposer: proposer.public_key_hex(),
+        signature_hex: String::new(),
+        block_type: report.block_type(),
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash().unwrap_or_default();
+    if let Ok(raw) = hex::decode(&header.hash) {
+        header.signature_hex = proposer.sign_hex(&raw, SignPurpose::EpochReport)?;
+    }
+    Ok(header)
+}
+
+/// Build and sign a block the leader appends on its own account: a vesting
+/// release or treasury spend when an epoch opens, or a trade settlement
+pub fn build_leader_header(
+    block_type: BlockType,
+    height: u64,
+    head_hash: String,
+    proposer: &NodeSigner,
+    purpose: SignPurpose,
+) -> Result<BlockHeader, SignerError> {
+    let mut header = BlockHeader {
+        index: height,
+        timestamp: crate::block::current_unix_timestamp_ms(),
+        prev_hash: head_hash,
+        hash: String::new(),
+        proposer: proposer.public_key_hex(),
+        signature_hex: String::new(),
+        block_type,
+        state_root: String::new(),
+        root_attestation: None,
+    };
+    header.hash = header.calculate_hash().unwrap_or_default();
+    if let Ok(raw) = hex::decode(&header.hash) {
+        header.signature_hex = proposer.sign_hex(&raw, purpose)?;
+    }
+    Ok(header)
+}
+
+/// Release what vested during the epoch that just ended, one block per account
+fn append_vesting_releases(chain: &mut Chain, releases: Vec<(String, u64)>, proposer: &NodeSigner, proposer_pk: &str) {
+    for (account, amount) in releases {
+        let head_hash = chain.head_hash().unwrap_or_default();
+        let block_type = BlockType::Vesting { account: account.clone(), amount };
+        let appended = build_leader_header(block_type, chain.height, head_hash, proposer, SignPurpose::Vesting)
+            .map_err(|e| e.to_string())
+            .and_then(|header| chain.append_vesting(header, proposer_pk).map_err(|e| e.to_string()));
+        match appended {
+            Ok(()) => info!("🔓 Released {} vested Compass to {}", amount, account),
+            Err(e) => warn!("Vesting release for {} not appended: {}", account, e),
+        }
+    }
+}
+
+/// Carry out the treasury proposals that passed, one block per proposal
+fn append_treasury_spends(chain: &mut Chain, spends: Vec<BlockType>, proposer: &NodeSigner, proposer_pk: &str) {
+    for spend in spends {
+        let BlockType::TreasurySpend { proposal_id, ref recipient, amount } = spend else { continue };
+        let note = format!("{} Compass to {} for proposal #{}", amount, recipient, proposal_id);
+        let head_hash = chain.head_hash().unwrap_or_default();
+        let appended = build_leader_header(spend, chain.height, head_hash, proposer, SignPurpose::TreasurySpend)
+            .map_err(|e| e.to_string())
+            .and_then(|header| chain.append_treasury_spend(header, proposer_pk).map_err(|e| e.to_string()));
+        match appended {
+            Ok(()) => info!("🏛️ Treasury paid {}", note),
+            Err(e) => warn!("Treasury spend of {} not appended: {}", note, e),
+        }
+    }
+}
+
+/// Enact the stability fee proposals that passed, one block per proposal
+fn append_stability_fee_changes(chain: &mut Chain, changes: Vec<BlockType>, proposer: &NodeSigner, proposer_pk: &str) {
+    for change in changes {
+        let BlockType::StabilityFeeChange { proposal_id, annual_rate_bps } = change else { continue };
+        let head_hash = chain.head_hash().unwrap_or_default();
+        let appended = build_leader_header(change, chain.height, head_hash, proposer, SignPurpose::StabilityFeeChange)
+            .map_err(|e| e.to_string())
+            .and_then(|header| chain.append_stability_fee_change(header, proposer_pk).map_err(|e| e.to_string()));
+        match appended {
+            Ok(()) => info!("🏛️ Stability fee set to {} bps a year by proposal #{}", annual_rate_bps, proposal_id),
+            Err(e) => warn!("Stability fee change for proposal #{} not appended: {}", proposal_id, e),
+        }
+    }
+}
+
+/// Report the epoch that just ended, if the PoH block just appended opened a new one
+fn append_due_epoch_report(chain: &mut Chain, proposer: &NodeSigner, proposer_pk: &str) -> Result<(), String> {
+    let Some(epoch) = chain.epoch_report_due() else { return Ok(()) };
+    let report = chain.compute_epoch_report(epoch).map_err(|e| e.to_string())?;
+    let head_hash = chain.head_hash().unwrap_or_default();
+    let header = build_epoch_report_header(&report, chain.height, head_hash, proposer).map_err(|e| e.to_string())?;
+    chain.append_epoch_report(header, proposer_pk).map_err(|e| e.to_string())?;
+    info!(
+        "📒 Epoch {} report: {} blocks, fees {}, COMPUTE emitted {}",
+        epoch,
+        report.blocks_by_proposer.values().sum::<u64>(),
+        report.fees_collected,
+        report.compute_emitted
+    );
+    Ok(())
+}
+
+/// Append each received tick as a PoH block, in the slots this node is scheduled
+/// to lead, and announce it over `network`. Runs until the VDF worker goes away.
+pub fn spawn_block_appender(
+    chain: Arc<Mutex<Chain>>,
+    proposer: Arc<NodeSigner>,
+    ticks: Receiver<PohTick>,
+    network: tokio::sync::mpsc::Sender<NetworkCommand>,
+) -> JoinHandle<()> {
+    std::thread::Builder::new()
+        .name("poh-append".to_string())
+        .spawn(move || {
+            let proposer_pk = proposer.public_key_hex();
+            for tick in ticks {
+                let Ok(mut c_guard) = chain.lock() else {
+                    warn!("Chain lock poisoned; PoH appender exiting");
+                    return;
+                };
+                let slot = c_guard.slot_at(crate::block::current_unix_timestamp_ms());
+                if !c_guard.may_propose(slot, &proposer_pk) {
+                    continue;
+                }
+                let head_hash = c_guard
+                    .head_hash()
+                    .unwrap_or("0000000000000000000000000000000000000000000000000000000000000000".to_string());
+                match build_poh_header(&tick, c_guard.height, head_hash, &proposer) {
+                    Ok(header) => {
+                        let (height, hash) = (header.index, header.hash.clone());
+                        match c_guard.append_poh(header, &proposer_pk) {
+                            Ok(()) => {
+                                // Dropped rather than waited on if the network queue is full
+                                let sent_at_ms = crate::block::current_unix_timestamp_ms();
+                                let _ = network.try_send(NetworkCommand::Broadcast(NetMessage::BlockAnnounce { height, hash, sent_at_ms }));
+                                // Read before the report block moves the head off the PoH block
+                                let releases = c_guard.vesting_releases_due();
+                                let spends = c_guard.treasury_spends_due();
+                                let rate_changes = c_guard.stability_fee_changes_due();
+                                if let Err(e) = append_due_epoch_report(&mut c_guard, &proposer, &proposer_pk) {
+                                    warn!("Epoch report not appended: {}", e);
+                                }
+                                append_vesting_releases(&mut c_guard, releases, &proposer, &proposer_pk);
+                                append_treasury_spends(&mut c_guard, spends, &proposer, &proposer_pk);
+                                append_stability_fee_changes(&mut c_guard, rate_changes, &proposer, &proposer_pk);
+                            }
+                            Err(e) => warn!("PoH tick {} not appended: {}", tick.tick, e),
+                        }
+                    }
+                    Err(e) => warn!("PoH tick {} not signed: {}", tick.tick, e),
+                }
+                drop(c_guard);
+
+                if tick.tick % 10 == 0 {
+                    info!(
+                        "PoH Tick {} | VDF Time: {:?} | Hash: {}",
+                        tick.tick,
+                        tick.vdf_time,
+                        hex::encode(&tick.end_hash).get(0..16).unwrap_or("")
+                    );
+                }
+            }
+        })
+        .expect("Failed to spawn PoH appender thread")
+}
+
+/// Start both halves of block production; returns the (VDF, appender) threads
+pub fn start(
+    chain: Arc<Mutex<Chain>>,
+    proposer: Arc<NodeSigner>,
+    poh: PoHRecorder,
+    slot_duration: Duration,
+    network: tokio::sync::mpsc::Sender<NetworkCommand>,
+    shutdown: ShutdownToken,
+) -> (JoinHandle<()>, JoinHandle<()>) {
+    let (tx, rx) = sync_channel(TICK_CHANNEL_CAPACITY);
+    let appender = spawn_block_appender(chain, proposer, rx, network);
+    (spawn_vdf_worker(poh, slot_duration, tx, shutdown), appender)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_vdf_worker_publishes_increasing_ticks() {
+        let (tx, rx) = sync_channel(4);
+        let poh = PoHRecorder::new(b"test-seed".to_vec(), 10);
+        let supervisor = crate::node::supervisor::Supervisor::new(Default::default());
+        spawn_vdf_worker(poh, Duration::from_millis(1), tx, supervisor.token());
+
+        let ticks: Vec<u64> = rx.iter().take(3).map(|t| t.tick).collect();
+        assert_eq!(ticks, vec![1, 2, 3]);
+        // Dropping the receiver stops the worker on its next send
+    }
+
+    #[test]
+    fn test_poh_header_is_signed_by_proposer() {
+        let kp = Arc::new(crate::crypto::KeyPair::generate());
+        let tick = PohTick { tick: 5, iterations: 10, end_hash: vec![1, 2, 3], vdf_time: Duration::ZERO };
+        let header = build_poh_header(&tick, 7, "prev".to_string(), &NodeSigner::Local(kp.clone())).unwrap();
+        assert_eq!(header.index, 7);
+        assert_eq!(header.hash, header.calculate_hash().unwrap());
+        let raw = hex::decode(&header.hash).unwrap();
+        assert!(crate::crypto::verify_with_pubkey_hex(&raw, &header.signature_hex, &kp.public_key_hex()));
+    }
+}
@@ -0,0 +1,402 @@
+// Node signing key: local or remote
+//
+// Block production, the oracle and trade receipts sign through `NodeSigner`, so the producing key
+// can stay in admin.json or live behind a signing service on an isolated host or
+// HSM. The remote protocol is one JSON object per request, either POSTed to
+// `<url>/v1/<method>` or written as a single line to a unix socket:
+//
+//   {"method":"sign","key_id":"..","purpose":"poh_block","message":"<hex>",
+//    "timestamp_ms":..,"nonce":..,"auth":"<hex>"}
+//
+// `auth` is HMAC-SHA256 under the shared secret of the other fields (see
+// `request_mac`), so the service can refuse requests that did not come from this
+// node and, by checking `timestamp_ms`/`nonce`, replays of old ones. It answers
+// `{"signature":"<hex>"}`, `{"public_key":"<hex>"}` or `{"error":".."}`. Every
+// signature is checked against the key the service reported at startup before
+// it is used.
+use std::io::{BufRead, BufReader, Write};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::mpsc::{channel, sync_channel, Sender, SyncSender};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use thiserror::Error;
+use tracing::info;
+
+use crate::config::RemoteSignerConfig;
+use crate::crypto::{verify_with_pubkey_hex, KeyPair};
+use crate::encoding;
+
+#[derive(Error, Debug, Clone, PartialEq)]
+pub enum SignerError {
+    #[error("Signer configuration error: {0}")]
+    Config(String),
+
+    #[error("Signer unreachable: {0}")]
+    Transport(String),
+
+    #[error("Signer refused request: {0}")]
+    Rejected(String),
+
+    #[error("Malformed signer response: {0}")]
+    BadResponse(String),
+
+    #[error("Signer key {actual} does not match the pinned key {expected}")]
+    KeyMismatch { expected: String, actual: String },
+
+    #[error("Signer returned a signature that does not verify")]
+    InvalidSignature,
+}
+
+/// What a signature is for; sent along so the service can apply per-purpose policy
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SignPurpose {
+    PohBlock,
+    OracleAttestation,
+    TradeReceipt,
+    /// Wallet transactions signed through `wallet::connect_external_signer`
+    Transaction,
+    EpochReport,
+    FinalityVote,
+    Vesting,
+    TreasurySpend,
+    Trade,
+    RedemptionRelease,
+    L2Checkpoint,
+    /// `RootAttestation` on a block this node committed
+    StateRoot,
+    StabilityFeeChange,
+    Settlement,
+    FreezeAuthorityChange,
+}
+
+impl SignPurpose {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SignPurpose::PohBlock => "poh_block",
+            SignPurpose::OracleAttestation => "oracle_attestation",
+            SignPurpose::TradeReceipt => "trade_receipt",
+            SignPurpose::Transaction => "transaction",
+            SignPurpose::EpochReport => "epoch_report",
+            SignPurpose::FinalityVote => "finality_vote",
+            SignPurpose::Vesting => "vesting",
+            SignPurpose::TreasurySpend => "treasury_spend",
+            SignPurpose::Trade => "trade",
+            SignPurpose::RedemptionRelease => "redemption_release",
+            SignPurpose::L2Checkpoint => "l2_checkpoint",
+            SignPurpose::StateRoot => "state_root",
+            SignPurpose::StabilityFeeChange => "stability_fee_change",
+            SignPurpose::Settlement => "settlement",
+            SignPurpose::FreezeAuthorityChange => "freeze_authority_change",
+        }
+    }
+}
+
+/// The key this node signs blocks and oracle results with
+pub enum NodeSigner {
+    Local(Arc<KeyPair>),
+    Remote(RemoteSigner),
+}
+
+impl NodeSigner {
+    pub fn public_key_hex(&self) -> String {
+        match self {
+            NodeSigner::Local(kp) => kp.public_key_hex(),
+            NodeSigner::Remote(remote) => remote.public_key.clone(),
+        }
+    }
+
+    /// Hex signature over `message`
+    pub fn sign_hex(&self, message: &[u8], purpose: SignPurpose) -> Result<String, SignerError> {
+        match self {
+            NodeSigner::Local(kp) => Ok(kp.sign_hex(message)),
+            NodeSigner::Remote(remote) => remote.sign_hex(message, purpose),
+        }
+    }
+
+    pub fn is_remote(&self) -> bool {
+        matches!(self, NodeSigner::Remote(_))
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+struct SignerRequest {
+    method: String,
+    key_id: String,
+    purpose: String,
+    message: String,
+    timestamp_ms: u64,
+    nonce: u64,
+    auth: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+struct SignerResponse {
+    signature: Option<String>,
+    public_key: Option<String>,
+    error: Option<String>,
+}
+
+type Reply = SyncSender<Result<SignerResponse, SignerError>>;
+
+/// Client for a signing service. Requests go through one worker thread that owns the
+/// connection, so signing works the same from the PoH thread and from async tasks.
+pub struct RemoteSigner {
+    key_id: String,
+    secret: Vec<u8>,
+    public_key: String,
+    timeout: Duration,
+    next_nonce: AtomicU64,
+    requests: Mutex<Sender<(SignerRequest, Reply)>>,
+}
+
+/// HMAC-SHA256 over the request fields, newline-joined in declaration order
+pub fn request_mac(
+    secret: &[u8],
+    method: &str,
+    key_id: &str,
+    purpose: &str,
+    message_hex: &str,
+    timestamp_ms: u64,
+    nonce: u64,
+) -> String {
+    let mut mac = <Hmac<Sha256> as Mac>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    let canonical = format!("{}\n{}\n{}\n{}\n{}\n{}", method, key_id, purpose, message_hex, timestamp_ms, nonce);
+    mac.update(canonical.as_bytes());
+    encoding::to_hex(mac.finalize().into_bytes())
+}
+
+impl RemoteSigner {
+    /// Connect to the service, fetch its public key and check it against the pin
+    pub fn connect(config: &RemoteSignerConfig) -> Result<Self, SignerError> {
+        let secret_hex = std::fs::read_to_string(&config.auth_secret_file)
+            .map_err(|e| SignerError::Config(format!("cannot read {}: {}", config.auth_secret_file, e)))?;
+        let secret = encoding::from_hex(secret_hex.trim())
+            .map_err(|e| SignerError::Config(format!("{}: {}", config.auth_secret_file, e)))?;
+        if secret.len() < 16 {
+            return Err(SignerError::Config("auth secret must be at least 16 bytes".to_string()));
+        }
+
+        let transport = Transport::parse(&config.url, Duration::from_millis(config.timeout_ms))?;
+        let (tx, rx) = channel::<(SignerRequest, Reply)>();
+        std::thread::Builder::new()
+            .name("remote-signer".to_string())
+            .spawn(move || {
+                for (request, reply) in rx {
+                    let _ = reply.send(transport.call(&request));
+                }
+            })
+            .map_err(|e| SignerError::Transport(e.to_string()))?;
+
+        let mut signer = RemoteSigner {
+            key_id: config.key_id.clone(),
+            secret,
+            public_key: String::new(),
+            timeout: Duration::from_millis(config.timeout_ms),
+            next_nonce: AtomicU64::new(0),
+            requests: Mutex::new(tx),
+        };
+
+        let reported = signer
+            .request("public_key", SignPurpose::PohBlock, &[])?
+            .public_key
+            .ok_or_else(|| SignerError::BadResponse("missing public_key".to_string()))?;
+        encoding::pubkey_from_hex(&reported).map_err(|e| SignerError::BadResponse(e.to_string()))?;
+        if let Some(expected) = &config.public_key {
+            if !expected.eq_ignore_ascii_case(&reported) {
+                return Err(SignerError::KeyMismatch { expected: expected.clone(), actual: reported });
+            }
+        }
+        info!("Remote signer {} ready (key {}, id '{}')", config.url, reported, config.key_id);
+        signer.public_key = reported;
+        Ok(signer)
+    }
+
+    pub fn public_key_hex(&self) -> &str {
+        &self.public_key
+    }
+
+    fn sign_hex(&self, message: &[u8], purpose: SignPurpose) -> Result<String, SignerError> {
+        let signature = self
+            .request("sign", purpose, message)?
+            .signature
+            .ok_or_else(|| SignerError::BadResponse("missing signature".to_string()))?;
+        if !verify_with_pubkey_hex(message, &signature, &self.public_key) {
+            return Err(SignerError::InvalidSignature);
+        }
+        Ok(signature)
+    }
+
+    fn request(&self, method: &str, purpose: SignPurpose, message: &[u8]) -> Result<SignerResponse, SignerError> {
+        let message = encoding::to_hex(message);
+        let timestamp_ms = crate::block::current_unix_timestamp_ms();
+        let nonce = self.next_nonce.fetch_add(1, Ordering::SeqCst);
+        let auth = request_mac(&self.secret, method, &self.key_id, purpose.as_str(), &message, timestamp_ms, nonce);
+        let request = SignerRequest {
+            method: method.to_string(),
+            key_id: self.key_id.clone(),
+            purpose: purpose.as_str().to_string(),
+            message,
+            timestamp_ms,
+            nonce,
+            auth,
+        };
+
+        let (reply_tx, reply_rx) = sync_channel(1);
+        self.requests
+            .lock()
+            .map_err(|_| SignerError::Transport("signer worker poisoned".to_string()))?
+            .send((request, reply_tx))
+            .map_err(|_| SignerError::Transport("signer worker stopped".to_string()))?;
+        let response = reply_rx
+            .recv_timeout(self.timeout)
+            .map_err(|_| SignerError::Transport("timed out waiting for signer".to_string()))??;
+
+        match response.error {
+            Some(e) => Err(SignerError::Rejected(e)),
+            None => Ok(response),
+        }
+    }
+}
+
+/// Wallets delegate to the same service protocol; requests carry the `transaction` purpose
+impl crate::crypto::Signer for RemoteSigner {
+    fn public_key_hex(&self) -> String {
+        self.public_key.clone()
+    }
+
+    fn sign_hex(&self, message: &[u8]) -> Result<String, String> {
+        RemoteSigner::sign_hex(self, message, SignPurpose::Transaction).map_err(|e| e.to_string())
+    }
+}
+
+enum Transport {
+    Http { base: String, client: reqwest::blocking::Client },
+    #[cfg(unix)]
+    Unix { path: std::path::PathBuf, timeout: Duration },
+}
+
+impl Transport {
+    fn parse(url: &str, timeout: Duration) -> Result<Self, SignerError> {
+        if let Some(path) = url.strip_prefix("unix://") {
+            #[cfg(unix)]
+            return Ok(Transport::Unix { path: path.into(), timeout });
+            #[cfg(not(unix))]
+            return Err(SignerError::Config(format!("unix sockets are not supported here: {}", path)));
+        }
+        if url.starts_with("http://") || url.starts_with("https://") {
+            let client = reqwest::blocking::Client::builder()
+                .timeout(timeout)
+                .build()
+                .map_err(|e| SignerError::Config(e.to_string()))?;
+            return Ok(Transport::Http { base: url.trim_end_matches('/').to_string(), client });
+        }
+        Err(SignerError::Config(format!("unsupported signer URL '{}' (use http(s):// or unix://)", url)))
+    }
+
+    fn call(&self, request: &SignerRequest) -> Result<SignerResponse, SignerError> {
+        match self {
+            Transport::Http { base, client } => {
+                let response = client
+                    .post(format!("{}/v1/{}", base, request.method))
+                    .json(request)
+                    .send()
+                    .map_err(|e| SignerError::Transport(e.to_string()))?;
+                response.json().map_err(|e| SignerError::BadResponse(e.to_string()))
+            }
+            #[cfg(unix)]
+            Transport::Unix { path, timeout } => {
+                let mut stream = std::os::unix::net::UnixStream::connect(path)
+                    .map_err(|e| SignerError::Transport(format!("{}: {}", path.display(), e)))?;
+                stream.set_read_timeout(Some(*timeout)).map_err(|e| SignerError::Transport(e.to_string()))?;
+                stream.set_write_timeout(Some(*timeout)).map_err(|e| SignerError::Transport(e.to_string()))?;
+
+                let mut line = serde_json::to_vec(request).map_err(|e| SignerError::BadResponse(e.to_string()))?;
+                line.push(b'\n');
+                stream.write_all(&line).map_err(|e| SignerError::Transport(e.to_string()))?;
+
+                let mut reply = String::new();
+                BufReader::new(stream)
+                    .read_line(&mut reply)
+                    .map_err(|e| SignerError::Transport(e.to_string()))?;
+                serde_json::from_str(&reply).map_err(|e| SignerError::BadResponse(e.to_string()))
+            }
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::net::UnixListener;
+
+    /// Minimal signing service: checks the MAC and signs with `key`
+    fn spawn_service(path: std::path::PathBuf, secret: Vec<u8>, key: KeyPair) {
+        let listener = UnixListener::bind(&path).unwrap();
+        std::thread::spawn(move || {
+            for stream in listener.incoming() {
+                let mut stream = stream.unwrap();
+                let mut line = String::new();
+                BufReader::new(&stream).read_line(&mut line).unwrap();
+                let req: serde_json::Value = serde_json::from_str(&line).unwrap();
+                let field = |k: &str| req[k].as_str().unwrap_or_default().to_string();
+                let expected = request_mac(
+                    &secret,
+                    &field("method"),
+                    &field("key_id"),
+                    &field("purpose"),
+                    &field("message"),
+                    req["timestamp_ms"].as_u64().unwrap(),
+                    req["nonce"].as_u64().unwrap(),
+                );
+                let reply = if expected != field("auth") {
+                    serde_json::json!({ "error": "bad auth" })
+                } else if field("method") == "public_key" {
+                    serde_json::json!({ "public_key": key.public_key_hex() })
+                } else {
+                    let message = encoding::from_hex(&field("message")).unwrap();
+                    serde_json::json!({ "signature": key.sign_hex(&message) })
+                };
+                let _ = writeln!(stream, "{}", reply);
+            }
+        });
+    }
+
+    #[test]
+    fn test_remote_signer_over_unix_socket() {
+        let dir = std::env::temp_dir().join(format!("compass-signer-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+        let secret = vec![7u8; 32];
+        std::fs::write(dir.join("auth.key"), encoding::to_hex(&secret)).unwrap();
+
+        let key = KeyPair::from_seed("hsm");
+        let pubkey = key.public_key_hex();
+        spawn_service(dir.join("signer.sock"), secret, key);
+
+        let mut config = RemoteSignerConfig {
+            url: format!("unix://{}", dir.join("signer.sock").display()),
+            key_id: "producer".to_string(),
+            auth_secret_file: dir.join("auth.key").display().to_string(),
+            public_key: Some(pubkey.clone()),
+            timeout_ms: 2000,
+        };
+        let signer = NodeSigner::Remote(RemoteSigner::connect(&config).unwrap());
+        assert_eq!(signer.public_key_hex(), pubkey);
+        let sig = signer.sign_hex(b"block-hash", SignPurpose::PohBlock).unwrap();
+        assert!(verify_with_pubkey_hex(b"block-hash", &sig, &pubkey));
+
+        // A pinned key that differs from the service's is refused at startup
+        config.public_key = Some(KeyPair::from_seed("other").public_key_hex());
+        assert!(matches!(RemoteSigner::connect(&config), Err(SignerError::KeyMismatch { .. })));
+
+        // So is a node holding the wrong secret
+        std::fs::write(dir.join("auth.key"), encoding::to_hex([9u8; 32])).unwrap();
+        config.public_key = None;
+        assert!(matches!(RemoteSigner::connect(&config), Err(SignerError::Rejected(_))));
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
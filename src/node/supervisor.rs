@@ -0,0 +1,186 @@
+// Task supervision and shutdown
+//
+// The node's long-running services are started through `Supervisor::spawn`,
+// which runs each one until shutdown and restarts it if it panics or returns,
+// waiting longer after each consecutive failure. Shutdown drops every task at
+// its next `.await`; none of the service loops hold a lock across one, so a task
+// never stops halfway through a state update. OS threads (the PoH pair) can't be
+// dropped, so they poll the `ShutdownToken` and are joined instead.
+use std::future::Future;
+use std::time::{Duration, Instant};
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+
+/// Tells a task whether the node is shutting down
+#[derive(Clone)]
+pub struct ShutdownToken {
+    rx: watch::Receiver<bool>,
+}
+
+impl ShutdownToken {
+    pub fn is_shutdown(&self) -> bool {
+        *self.rx.borrow()
+    }
+
+    /// Resolves once shutdown has started
+    pub async fn cancelled(&mut self) {
+        // An error means the supervisor is gone, which is shutdown too
+        let _ = self.rx.wait_for(|stop| *stop).await;
+    }
+}
+
+/// Restart delays: doubling from `initial` up to `max`. A run that lasted
+/// `reset_after` counts as healthy, and the next failure starts over at `initial`.
+#[derive(Debug, Clone, Copy)]
+pub struct Backoff {
+    pub initial: Duration,
+    pub max: Duration,
+    pub reset_after: Duration,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self {
+            initial: Duration::from_secs(1),
+            max: Duration::from_secs(60),
+            reset_after: Duration::from_secs(300),
+        }
+    }
+}
+
+impl Backoff {
+    /// Delay before restarting after the `failures`-th failure in a row (1-based)
+    pub fn delay(&self, failures: u32) -> Duration {
+        let factor = 1u32.checked_shl(failures.saturating_sub(1)).unwrap_or(u32::MAX);
+        self.initial.saturating_mul(factor).min(self.max)
+    }
+}
+
+pub struct Supervisor {
+    stop: watch::Sender<bool>,
+    backoff: Backoff,
+    tasks: Vec<(&'static str, JoinHandle<()>)>,
+    threads: Vec<(&'static str, std::thread::JoinHandle<()>)>,
+}
+
+impl Supervisor {
+    pub fn new(backoff: Backoff) -> Self {
+        let (stop, _) = watch::channel(false);
+        Self { stop, backoff, tasks: Vec::new(), threads: Vec::new() }
+    }
+
+    pub fn token(&self) -> ShutdownToken {
+        ShutdownToken { rx: self.stop.subscribe() }
+    }
+
+    /// Run the future `task` builds until shutdown, building a fresh one whenever
+    /// the last panicked or returned
+    pub fn spawn<F, Fut>(&mut self, name: &'static str, mut task: F)
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = ()> + Send + 'static,
+    {
+        let backoff = self.backoff;
+        let mut token = self.token();
+        let handle = tokio::spawn(async move {
+            let mut failures = 0u32;
+            loop {
+                let started = Instant::now();
+                let mut run = tokio::spawn(task());
+                let outcome = tokio::select! {
+                    outcome = &mut run => outcome,
+                    _ = token.cancelled() => {
+                        run.abort();
+                        let _ = run.await;
+                        return;
+                    }
+                };
+
+                if started.elapsed() >= backoff.reset_after {
+                    failures = 0;
+                }
+                failures += 1;
+                let delay = backoff.delay(failures);
+                match outcome {
+                    Err(e) if e.is_panic() => warn!("Task '{}' panicked; restarting in {:?}", name, delay),
+                    _ => warn!("Task '{}' exited; restarting in {:?}", name, delay),
+                }
+                tokio::select! {
+                    _ = tokio::time::sleep(delay) => {}
+                    _ = token.cancelled() => return,
+                }
+            }
+        });
+        self.tasks.push((name, handle));
+    }
+
+    /// Join `handle` at shutdown; the thread is expected to watch a `ShutdownToken`
+    pub fn watch_thread(&mut self, name: &'static str, handle: std::thread::JoinHandle<()>) {
+        self.threads.push((name, handle));
+    }
+
+    /// Signal shutdown and wait up to `grace` for each task and thread to stop
+    pub async fn shutdown(self, grace: Duration) {
+        let _ = self.stop.send(true);
+        for (name, handle) in self.tasks {
+            if tokio::time::timeout(grace, handle).await.is_err() {
+                warn!("Task '{}' did not stop within {:?}", name, grace);
+            }
+        }
+        for (name, handle) in self.threads {
+            let joined = tokio::task::spawn_blocking(move || handle.join());
+            if tokio::time::timeout(grace, joined).await.is_err() {
+                warn!("Thread '{}' did not stop within {:?}", name, grace);
+            }
+        }
+        info!("All node tasks stopped");
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicU32, Ordering};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_backoff_doubles_up_to_max() {
+        let backoff = Backoff::default();
+        let delays: Vec<u64> = (1..=8).map(|n| backoff.delay(n).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 8, 16, 32, 60, 60]);
+        assert_eq!(backoff.delay(1_000), backoff.max);
+    }
+
+    #[tokio::test]
+    async fn test_crashed_task_restarts_until_shutdown() {
+        let mut supervisor = Supervisor::new(Backoff {
+            initial: Duration::from_millis(5),
+            max: Duration::from_millis(20),
+            reset_after: Duration::from_secs(60),
+        });
+        let runs = Arc::new(AtomicU32::new(0));
+        let counter = runs.clone();
+        supervisor.spawn("flaky", move || {
+            let counter = counter.clone();
+            async move {
+                // Panic twice, then run until stopped
+                if counter.fetch_add(1, Ordering::SeqCst) < 2 {
+                    panic!("simulated crash");
+                }
+                std::future::pending::<()>().await;
+            }
+        });
+
+        let token = supervisor.token();
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+        assert!(!token.is_shutdown());
+
+        tokio::time::timeout(Duration::from_secs(1), supervisor.shutdown(Duration::from_millis(500)))
+            .await
+            .expect("shutdown finishes");
+        assert!(token.is_shutdown());
+        assert_eq!(runs.load(Ordering::SeqCst), 3);
+    }
+}
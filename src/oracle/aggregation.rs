@@ -0,0 +1,188 @@
+//! Multi-oracle price aggregation.
+//!
+//! Registered oracles submit prices into fixed-length rounds per ticker. Each
+//! round's price is the stake-weighted median of its submissions, after dropping
+//! those more than `max_deviation_bps` from the median of all of them. A round
+//! publishes a price once `min_oracles` submissions survive that cut.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// An oracle allowed to submit prices, registered at startup
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct ConfiguredOracle {
+    pub account: String,
+    /// Key its submissions are signed with (hex)
+    pub pubkey: String,
+    /// Weight of its prices in the median
+    pub stake: u64,
+}
+
+/// `[consensus.oracle_aggregation]`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OracleAggregationConfig {
+    /// Length of one price round
+    pub round_secs: u64,
+    /// Submissions further than this from the round's median are left out
+    pub max_deviation_bps: u64,
+    /// Submissions a round needs, after outliers are left out, to publish a price
+    pub min_oracles: usize,
+    pub oracles: Vec<ConfiguredOracle>,
+}
+
+impl Default for OracleAggregationConfig {
+    fn default() -> Self {
+        Self { round_secs: 60, max_deviation_bps: 500, min_oracles: 1, oracles: Vec::new() }
+    }
+}
+
+impl OracleAggregationConfig {
+    /// Round that a submission received at `now` (unix seconds) falls in
+    pub fn round_at(&self, now: u64) -> u64 {
+        now / self.round_secs.max(1)
+    }
+}
+
+/// Message an oracle signs to submit `price` for `ticker`
+pub fn price_message(ticker: &str, price: f64, timestamp: u64) -> String {
+    format!("ORACLE_PRICE:{}:{}:{}", ticker, price, timestamp)
+}
+
+/// One oracle's price in a round
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriceContribution {
+    pub oracle: String,
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub price: Decimal,
+    pub stake: u64,
+    pub received_at: u64,
+}
+
+/// A round's published price and who it came from
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct AggregatePrice {
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub price: Decimal,
+    /// Oracles whose prices made up the median
+    pub contributors: Vec<String>,
+    /// Oracles left out as outliers
+    pub rejected: Vec<String>,
+    pub stake: u64,
+}
+
+/// Every submission to one ticker's round, and the price it publishes
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct PriceRound {
+    pub ticker: String,
+    pub round: u64,
+    pub submissions: Vec<PriceContribution>,
+    pub aggregate: Option<AggregatePrice>,
+}
+
+/// Price at which half the stake is at or below it; stake-less prices count once
+pub fn weighted_median(prices: &[(Decimal, u64)]) -> Option<Decimal> {
+    let mut sorted: Vec<(Decimal, u64)> = prices.iter().map(|(p, s)| (*p, (*s).max(1))).collect();
+    sorted.sort_by_key(|(price, _)| *price);
+    let total: u128 = sorted.iter().map(|(_, s)| *s as u128).sum();
+    let mut below = 0u128;
+    for (price, stake) in sorted {
+        below += stake as u128;
+        if below * 2 >= total {
+            return Some(price);
+        }
+    }
+    None
+}
+
+/// |a - b| in basis points of `b`
+fn deviation_bps(price: Decimal, reference: Decimal) -> u64 {
+    if reference.is_zero() {
+        return u64::MAX;
+    }
+    ((price - reference).abs() * Decimal::from(10_000) / reference).to_u64().unwrap_or(u64::MAX)
+}
+
+impl PriceRound {
+    pub fn new(ticker: &str, round: u64) -> Self {
+        Self { ticker: ticker.to_string(), round, ..Default::default() }
+    }
+
+    /// Add `contribution`, replacing its oracle's earlier one in this round, and
+    /// recompute the round's price
+    pub fn submit(&mut self, contribution: PriceContribution, cfg: &OracleAggregationConfig) -> Option<&AggregatePrice> {
+        self.submissions.retain(|c| c.oracle != contribution.oracle);
+        self.submissions.push(contribution);
+        self.aggregate = self.aggregate(cfg);
+        self.aggregate.as_ref()
+    }
+
+    fn aggregate(&self, cfg: &OracleAggregationConfig) -> Option<AggregatePrice> {
+        let all: Vec<(Decimal, u64)> = self.submissions.iter().map(|c| (c.price, c.stake)).collect();
+        let median = weighted_median(&all)?;
+        let (kept, outliers): (Vec<&PriceContribution>, Vec<&PriceContribution>) =
+            self.submissions.iter().partition(|c| deviation_bps(c.price, median) <= cfg.max_deviation_bps);
+        if kept.len() < cfg.min_oracles.max(1) {
+            return None;
+        }
+        let kept_prices: Vec<(Decimal, u64)> = kept.iter().map(|c| (c.price, c.stake)).collect();
+        Some(AggregatePrice {
+            price: weighted_median(&kept_prices)?,
+            contributors: kept.iter().map(|c| c.oracle.clone()).collect(),
+            rejected: outliers.iter().map(|c| c.oracle.clone()).collect(),
+            stake: kept.iter().fold(0u64, |total, c| total.saturating_add(c.stake)),
+        })
+    }
+
+    /// Whether `oracle`'s submission was left out as an outlier
+    pub fn is_rejected(&self, oracle: &str) -> bool {
+        self.aggregate.as_ref().is_some_and(|a| a.rejected.iter().any(|r| r == oracle))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn contribution(oracle: &str, price: i64, stake: u64) -> PriceContribution {
+        PriceContribution { oracle: oracle.to_string(), price: Decimal::from(price), stake, received_at: 0 }
+    }
+
+    #[test]
+    fn test_round_takes_stake_weighted_median_without_outliers() {
+        let cfg = OracleAggregationConfig { min_oracles: 3, ..Default::default() };
+        assert_eq!(weighted_median(&[(Decimal::from(1), 1), (Decimal::from(2), 1), (Decimal::from(9), 5)]), Some(Decimal::from(9)));
+        assert_eq!(weighted_median(&[]), None);
+
+        let mut round = PriceRound::new("BTC", cfg.round_at(125));
+        assert_eq!(round.round, 2);
+        assert!(round.submit(contribution("a", 100, 10), &cfg).is_none());
+        assert!(round.submit(contribution("b", 101, 10), &cfg).is_none());
+        // 20% off the median: left out, so the round still lacks a third price
+        assert!(round.submit(contribution("c", 120, 10), &cfg).is_none());
+        let aggregate = round.submit(contribution("d", 102, 30), &cfg).unwrap().clone();
+        assert_eq!(aggregate.price, Decimal::from(102));
+        assert_eq!(aggregate.contributors, vec!["a", "b", "d"]);
+        assert_eq!((aggregate.rejected, aggregate.stake), (vec!["c".to_string()], 50));
+        assert!(round.is_rejected("c"));
+
+        // Resubmitting replaces the oracle's earlier price
+        let aggregate = round.submit(contribution("c", 101, 10), &cfg).unwrap();
+        assert!(aggregate.rejected.is_empty());
+        assert_eq!(round.submissions.len(), 4);
+    }
+
+    #[test]
+    fn test_rounds_and_prices_reload_from_storage() {
+        let storage = crate::storage::Storage::new_in_memory().unwrap();
+        let cfg = OracleAggregationConfig::default();
+        let mut round = PriceRound::new("BTC", 7);
+        round.submit(PriceContribution { price: Decimal::new(1425, 1), ..contribution("a", 0, 10) }, &cfg);
+        storage.save_price_round(&round).unwrap();
+        assert_eq!(storage.get_price_round("BTC", 7).unwrap(), Some(round));
+
+        storage.save_oracle_price_info("BTC", &(Decimal::new(1425, 1), 99)).unwrap();
+        assert_eq!(storage.get_all_prices(), vec![("BTC".to_string(), (Decimal::new(1425, 1), 99))]);
+    }
+}
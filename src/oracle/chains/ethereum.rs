@@ -0,0 +1,359 @@
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+/// keccak256("Transfer(address,address,uint256)"), topic0 of every ERC-20 transfer log
+pub const TRANSFER_TOPIC: &str = "0xddf252ad1be2c89b69c2b068fc378daa952ba7f163c4a11628f55a4df523b3ef";
+
+/// Decimals vault amounts are kept in. Wei does not fit a u64, so 18-decimal
+/// assets are counted in gwei on Compass.
+pub const MAX_VAULT_DECIMALS: u32 = 9;
+
+/// Collateral that lives on an EVM chain
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+pub struct EvmAsset {
+    /// Vault ticker, e.g. "ETH" or "USDC"
+    pub ticker: String,
+    /// ERC-20 contract; `None` for the chain's native coin
+    #[serde(default)]
+    pub contract: Option<String>,
+    pub decimals: u32,
+}
+
+impl EvmAsset {
+    /// Convert an on-chain amount to vault units (at most `MAX_VAULT_DECIMALS`)
+    pub fn to_vault_units(&self, raw: u128) -> Option<u64> {
+        let scale = 10u128.checked_pow(self.decimals.saturating_sub(MAX_VAULT_DECIMALS))?;
+        u64::try_from(raw / scale).ok()
+    }
+}
+
+/// `[ethereum]`: the RPC endpoints ETH and ERC-20 vault deposits are checked against
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct EvmConfig {
+    pub rpc_url: String,
+    /// Endpoints run by other providers. A deposit or payout counts only if
+    /// `rpc_url` and every one of these report the same receipt.
+    pub extra_rpc_urls: Vec<String>,
+    /// Fewest endpoints a deposit must be checked against (default 2)
+    pub min_endpoints: usize,
+    pub min_confirmations: u32,
+    pub assets: Vec<EvmAsset>,
+}
+
+impl Default for EvmConfig {
+    fn default() -> Self {
+        Self {
+            rpc_url: "http://127.0.0.1:8545".to_string(),
+            extra_rpc_urls: vec![],
+            min_endpoints: 2,
+            min_confirmations: 12,
+            assets: vec![
+                EvmAsset { ticker: "ETH".to_string(), contract: None, decimals: 18 },
+                EvmAsset {
+                    ticker: "USDC".to_string(),
+                    contract: Some("0xa0b86991c6218b36c1d19d4a2e9eb0ce3606eb48".to_string()),
+                    decimals: 6,
+                },
+            ],
+        }
+    }
+}
+
+impl EvmConfig {
+    pub fn asset(&self, ticker: &str) -> Option<&EvmAsset> {
+        self.assets.iter().find(|a| a.ticker == ticker)
+    }
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmLog {
+    pub address: String,
+    pub topics: Vec<String>,
+    pub data: String,
+    pub block_number: Option<String>,
+    pub block_hash: Option<String>,
+    pub transaction_hash: Option<String>,
+    #[serde(default)]
+    pub removed: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EvmReceipt {
+    pub transaction_hash: String,
+    pub block_number: String,
+    pub block_hash: String,
+    /// "0x1" on success; absent before Byzantium
+    pub status: Option<String>,
+    pub logs: Vec<EvmLog>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EvmTransaction {
+    pub to: Option<String>,
+    pub value: String,
+}
+
+/// A deposit seen on chain, in vault units
+#[derive(Debug, Clone, PartialEq)]
+pub struct EvmDeposit {
+    pub tx_hash: String,
+    pub amount: u64,
+    pub block_number: u64,
+}
+
+/// Parse a JSON-RPC hex quantity ("0x1a") or 32-byte word
+pub fn parse_quantity(hex_str: &str) -> Option<u128> {
+    let digits = hex_str.strip_prefix("0x")?.trim_start_matches('0');
+    if digits.is_empty() {
+        return Some(0);
+    }
+    u128::from_str_radix(digits, 16).ok()
+}
+
+/// `address` left-padded to a 32-byte log topic
+pub fn address_topic(address: &str) -> String {
+    format!("0x{:0>64}", address.trim_start_matches("0x").to_lowercase())
+}
+
+/// Amount of `token` that `log` moves to `recipient`, if it is such a transfer
+pub fn transfer_to(log: &EvmLog, token: &str, recipient: &str) -> Option<u128> {
+    if log.removed
+        || !log.address.eq_ignore_ascii_case(token)
+        || log.topics.len() != 3
+        || !log.topics[0].eq_ignore_ascii_case(TRANSFER_TOPIC)
+        || !log.topics[2].eq_ignore_ascii_case(&address_topic(recipient))
+    {
+        return None;
+    }
+    parse_quantity(&log.data)
+}
+
+/// Blocks on top of (and including) `block`, given the chain `head`
+pub fn confirmations(head: u64, block: u64) -> u32 {
+    head.checked_sub(block).map_or(0, |d| u32::try_from(d + 1).unwrap_or(u32::MAX))
+}
+
+/// What one endpoint reports about a deposit
+#[derive(Debug, Clone, PartialEq)]
+pub struct DepositCheck {
+    /// Canonical block holding the receipt
+    pub block_hash: String,
+    /// Raw amount paid to the address
+    pub paid: u128,
+    pub confirmations: u32,
+}
+
+/// The answer every endpoint gave: the amount paid and the fewest confirmations
+/// any of them sees. Endpoints disagreeing on the block or the amount is an error.
+pub fn agreed_deposit(checks: &[DepositCheck]) -> Result<(u128, u32), String> {
+    let first = checks.first().ok_or("No endpoint answered")?;
+    if checks.iter().any(|c| !c.block_hash.eq_ignore_ascii_case(&first.block_hash) || c.paid != first.paid) {
+        return Err("Ethereum endpoints disagree about the deposit".to_string());
+    }
+    Ok((first.paid, checks.iter().map(|c| c.confirmations).min().unwrap_or(0)))
+}
+
+/// Ethereum (or any EVM chain) client over plain JSON-RPC
+#[derive(Debug, Clone)]
+pub struct EthereumClient {
+    client: Client,
+    rpc_url: String,
+    /// Independent endpoints deposits are cross-checked against
+    extra_rpc_urls: Vec<String>,
+    min_endpoints: usize,
+}
+
+impl EthereumClient {
+    pub fn new(rpc_url: String) -> Self {
+        Self { client: Client::new(), rpc_url, extra_rpc_urls: vec![], min_endpoints: 1 }
+    }
+
+    /// Client for `[ethereum]`, checking deposits against every configured endpoint
+    pub fn from_config(config: &EvmConfig) -> Self {
+        Self {
+            client: Client::new(),
+            rpc_url: config.rpc_url.clone(),
+            extra_rpc_urls: config.extra_rpc_urls.clone(),
+            min_endpoints: config.min_endpoints,
+        }
+    }
+
+    async fn call(&self, url: &str, method: &str, params: Value) -> Result<Value, String> {
+        let body = json!({ "jsonrpc": "2.0", "id": 1, "method": method, "params": params });
+        let response = self.client
+            .post(url)
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| format!("API request failed: {}", e))?;
+
+        if !response.status().is_success() {
+            return Err(format!("API returned error: {}", response.status()));
+        }
+
+        let mut reply: Value = response
+            .json()
+            .await
+            .map_err(|e| format!("Failed to parse response: {}", e))?;
+        if let Some(err) = reply.get("error") {
+            return Err(format!("{} failed: {}", method, err));
+        }
+        Ok(reply["result"].take())
+    }
+
+    pub async fn block_number(&self, url: &str) -> Result<u64, String> {
+        let head = self.call(url, "eth_blockNumber", json!([])).await?;
+        head.as_str().and_then(parse_quantity).map(|n| n as u64).ok_or("Invalid block number".to_string())
+    }
+
+    /// Hash of the canonical block at `number`
+    pub async fn block_hash(&self, url: &str, number: u64) -> Result<String, String> {
+        let block = self.call(url, "eth_getBlockByNumber", json!([format!("0x{:x}", number), false])).await?;
+        block["hash"].as_str().map(str::to_string).ok_or(format!("Block {} not found", number))
+    }
+
+    pub async fn get_receipt(&self, url: &str, tx_hash: &str) -> Result<EvmReceipt, String> {
+        let receipt = self.call(url, "eth_getTransactionReceipt", json!([tx_hash])).await?;
+        if receipt.is_null() {
+            return Err("Transaction not mined".to_string());
+        }
+        serde_json::from_value(receipt).map_err(|e| format!("Failed to parse receipt: {}", e))
+    }
+
+    pub async fn get_transaction(&self, url: &str, tx_hash: &str) -> Result<EvmTransaction, String> {
+        let tx = self.call(url, "eth_getTransactionByHash", json!([tx_hash])).await?;
+        serde_json::from_value(tx).map_err(|e| format!("Failed to parse transaction: {}", e))
+    }
+
+    /// Verify `tx_hash` paid at least `min_amount` (vault units) of `asset` to `address`.
+    ///
+    /// The receipt must have succeeded and sit in the block that is canonical at its
+    /// height right now, so a log from a reorged-out block is not accepted. Every
+    /// configured endpoint (at least `min_endpoints`) must report the same block and
+    /// amount, so one lying provider can't mint; confirmations are the fewest any sees.
+    pub async fn verify_deposit(
+        &self,
+        tx_hash: &str,
+        asset: &EvmAsset,
+        address: &str,
+        min_amount: u64,
+    ) -> Result<(bool, u32), String> {
+        let endpoints: Vec<&String> = std::iter::once(&self.rpc_url).chain(&self.extra_rpc_urls).collect();
+        if endpoints.len() < self.min_endpoints {
+            return Err(format!(
+                "Deposits need {} independent Ethereum endpoints, {} configured",
+                self.min_endpoints,
+                endpoints.len()
+            ));
+        }
+        let mut checks = Vec::with_capacity(endpoints.len());
+        for url in endpoints {
+            checks.push(self.check_deposit(url, tx_hash, asset, address).await?);
+        }
+        let (paid, confirmations) = agreed_deposit(&checks)?;
+        let verified = asset.to_vault_units(paid).is_some_and(|amount| amount >= min_amount);
+        Ok((verified, confirmations))
+    }
+
+    /// One endpoint's view of `tx_hash` paying `asset` to `address`
+    async fn check_deposit(&self, url: &str, tx_hash: &str, asset: &EvmAsset, address: &str) -> Result<DepositCheck, String> {
+        let receipt = self.get_receipt(url, tx_hash).await?;
+        if receipt.status.as_deref().and_then(parse_quantity) == Some(0) {
+            return Err("Transaction reverted".to_string());
+        }
+        let block = parse_quantity(&receipt.block_number).ok_or("Invalid receipt block number")? as u64;
+        if !self.block_hash(url, block).await?.eq_ignore_ascii_case(&receipt.block_hash) {
+            return Err("Receipt is not in the canonical chain".to_string());
+        }
+        let confirmations = confirmations(self.block_number(url).await?, block);
+
+        let paid = match &asset.contract {
+            Some(token) => receipt.logs.iter()
+                .filter(|log| log.block_hash.as_deref().is_none_or(|h| h.eq_ignore_ascii_case(&receipt.block_hash)))
+                .filter_map(|log| transfer_to(log, token, address))
+                .fold(0u128, u128::saturating_add),
+            None => {
+                let tx = self.get_transaction(url, tx_hash).await?;
+                match tx.to {
+                    Some(to) if to.eq_ignore_ascii_case(address) => parse_quantity(&tx.value).unwrap_or(0),
+                    _ => 0,
+                }
+            }
+        };
+        Ok(DepositCheck { block_hash: receipt.block_hash, paid, confirmations })
+    }
+
+    /// ERC-20 transfers of `asset` to `address` in blocks `from..=to`
+    pub async fn watch_deposits(&self, asset: &EvmAsset, address: &str, from: u64, to: u64) -> Result<Vec<EvmDeposit>, String> {
+        let token = asset.contract.as_ref().ok_or("Native deposits are claimed by transaction hash")?;
+        let filter = json!({
+            "fromBlock": format!("0x{:x}", from),
+            "toBlock": format!("0x{:x}", to),
+            "address": token,
+            "topics": [TRANSFER_TOPIC, Value::Null, address_topic(address)],
+        });
+        let logs: Vec<EvmLog> = serde_json::from_value(self.call(&self.rpc_url, "eth_getLogs", json!([filter])).await?)
+            .map_err(|e| format!("Failed to parse logs: {}", e))?;
+
+        Ok(logs
+            .iter()
+            .filter_map(|log| {
+                let amount = asset.to_vault_units(transfer_to(log, token, address)?)?;
+                Some(EvmDeposit {
+                    tx_hash: log.transaction_hash.clone()?,
+                    amount,
+                    block_number: parse_quantity(log.block_number.as_deref()?)? as u64,
+                })
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transfer_logs_decode_to_vault_units() {
+        let cfg = EvmConfig::default();
+        let usdc = cfg.asset("USDC").unwrap();
+        let vault = "0x00000000000000000000000000000000000000AB";
+        let log = EvmLog {
+            address: usdc.contract.clone().unwrap().to_uppercase().replace("0X", "0x"),
+            topics: vec![TRANSFER_TOPIC.to_string(), address_topic("0x01"), address_topic(vault)],
+            data: format!("0x{:064x}", 2_500_000u64),
+            block_number: Some("0x10".to_string()),
+            block_hash: None,
+            transaction_hash: Some("0xabc".to_string()),
+            removed: false,
+        };
+        let token = usdc.contract.as_deref().unwrap();
+        assert_eq!(transfer_to(&log, token, vault), Some(2_500_000));
+        assert_eq!(usdc.to_vault_units(2_500_000), Some(2_500_000));
+
+        // Someone else's transfer, a different token, or a reorged log don't count
+        assert_eq!(transfer_to(&log, token, "0x01"), None);
+        assert_eq!(transfer_to(&log, "0xdead", vault), None);
+        assert_eq!(transfer_to(&EvmLog { removed: true, ..log }, token, vault), None);
+
+        // 1.5 ETH in wei is 1.5e9 gwei
+        let eth = cfg.asset("ETH").unwrap();
+        assert_eq!(eth.to_vault_units(parse_quantity("0x14d1120d7b160000").unwrap()), Some(1_500_000_000));
+        assert_eq!(parse_quantity("0x0"), Some(0));
+        assert_eq!((confirmations(20, 16), confirmations(15, 16)), (5, 0));
+    }
+
+    #[test]
+    fn test_endpoints_must_agree_on_a_deposit() {
+        let check = |block_hash: &str, paid: u128, confirmations: u32| DepositCheck { block_hash: block_hash.to_string(), paid, confirmations };
+        assert_eq!(agreed_deposit(&[check("0xAB", 5, 20), check("0xab", 5, 14)]), Ok((5, 14)));
+        // A provider reporting a bigger payment, or the receipt in another block, blocks the mint
+        assert!(agreed_deposit(&[check("0xab", 5, 20), check("0xab", 500, 20)]).is_err());
+        assert!(agreed_deposit(&[check("0xab", 5, 20), check("0xcd", 5, 20)]).is_err());
+        assert!(agreed_deposit(&[]).is_err());
+    }
+}
@@ -1,5 +1,7 @@
 pub mod bitcoin;
+pub mod ethereum;
 pub mod litecoin;
 
 pub use bitcoin::BitcoinClient;
+pub use ethereum::EthereumClient;
 pub use litecoin::LitecoinClient;
@@ -0,0 +1,246 @@
+//! Oracle sanity bands and circuit breakers.
+//!
+//! Every price submission is compared with the ticker's time-weighted average over
+//! the last `twap_window_secs`:
+//! - within `confirm_above_bps` it is accepted;
+//! - up to `max_deviation_bps` it is a large move, held until `min_sources`
+//!   distinct oracles report prices that agree with it;
+//! - beyond that it is rejected. When `min_sources` distinct oracles report such a
+//!   price the move is taken as real but unsafe to act on, and the breaker trips:
+//!   vaults backed by the ticker stop minting and burning and fees stop converting
+//!   through it until governance resumes the feed.
+
+use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+/// `[consensus.oracle_guard]`
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(default)]
+pub struct OracleGuardConfig {
+    pub twap_window_secs: u64,
+    /// Moves larger than this need confirmation from `min_sources` oracles
+    pub confirm_above_bps: u64,
+    /// Moves larger than this are rejected, and trip the breaker once confirmed
+    pub max_deviation_bps: u64,
+    /// Distinct oracles that must agree on a large move
+    pub min_sources: usize,
+    /// Published prices older than this are stale (0: no limit)
+    pub max_price_age_secs: u64,
+    /// A published price that moved more than this in one update is stale until
+    /// the next update confirms it (0: no limit)
+    pub max_update_move_bps: u64,
+}
+
+impl Default for OracleGuardConfig {
+    fn default() -> Self {
+        Self {
+            twap_window_secs: 3600,
+            confirm_above_bps: 500,
+            max_deviation_bps: 2000,
+            min_sources: 2,
+            max_price_age_secs: 3600,
+            max_update_move_bps: 1000,
+        }
+    }
+}
+
+/// A price held back until other oracles confirm it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PendingPrice {
+    pub oracle: String,
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub price: Decimal,
+    pub received_at: u64,
+}
+
+/// Why and when a ticker was paused
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OracleTrip {
+    pub tripped_at: u64,
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub price: Decimal,
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub twap: Decimal,
+    pub deviation_bps: u64,
+    pub reporters: Vec<String>,
+}
+
+/// Outcome of one submission
+#[derive(Debug, Clone, PartialEq)]
+pub enum PriceVerdict {
+    Accepted,
+    AwaitingConfirmation { confirmations: usize, required: usize },
+    Rejected { deviation_bps: u64 },
+    Tripped { deviation_bps: u64 },
+    /// The breaker is already tripped; nothing is accepted until the feed resumes
+    Paused,
+}
+
+/// Per-ticker price history, pending large moves and breaker state
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct PriceGuard {
+    pub ticker: String,
+    /// Accepted prices as (received_at, price), oldest first
+    #[serde(with = "crate::encoding::decimal_str::timed")]
+    pub samples: Vec<(u64, Decimal)>,
+    pub pending: Vec<PendingPrice>,
+    /// Out-of-band reports, counted towards a trip
+    pub outliers: Vec<PendingPrice>,
+    pub tripped: Option<OracleTrip>,
+}
+
+/// |a - b| in basis points of `b`
+pub(crate) fn deviation_bps(price: Decimal, reference: Decimal) -> u64 {
+    if reference.is_zero() {
+        return u64::MAX;
+    }
+    ((price - reference).abs() * Decimal::from(10_000) / reference).to_u64().unwrap_or(u64::MAX)
+}
+
+/// Distinct oracles in `reports` whose price is within `band_bps` of `price`
+fn agreeing_sources(reports: &[PendingPrice], price: Decimal, band_bps: u64) -> Vec<String> {
+    let mut sources: Vec<String> = reports
+        .iter()
+        .filter(|r| deviation_bps(r.price, price) <= band_bps)
+        .map(|r| r.oracle.clone())
+        .collect();
+    sources.sort();
+    sources.dedup();
+    sources
+}
+
+impl PriceGuard {
+    pub fn new(ticker: String) -> Self {
+        Self { ticker, ..Default::default() }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.tripped.is_some()
+    }
+
+    /// Time-weighted average of accepted prices over the window ending at `now`;
+    /// `None` before the first accepted price
+    pub fn twap(&self, now: u64, window_secs: u64) -> Option<Decimal> {
+        let start = now.saturating_sub(window_secs);
+        let (mut weighted, mut total) = (Decimal::ZERO, 0u64);
+        for (i, (at, price)) in self.samples.iter().enumerate() {
+            let until = self.samples.get(i + 1).map(|(next, _)| *next).unwrap_or(now).min(now);
+            let weight = until.saturating_sub((*at).max(start));
+            weighted += *price * Decimal::from(weight);
+            total += weight;
+        }
+        if total == 0 {
+            return self.samples.last().map(|(_, price)| *price);
+        }
+        Some(weighted / Decimal::from(total))
+    }
+
+    fn accept(&mut self, price: Decimal, now: u64, cfg: &OracleGuardConfig) {
+        self.samples.push((now, price));
+        self.pending.clear();
+        self.outliers.clear();
+        // Keep one sample from before the window so the average covers all of it
+        let start = now.saturating_sub(cfg.twap_window_secs);
+        let first_inside = self.samples.iter().position(|(at, _)| *at >= start).unwrap_or(self.samples.len());
+        if first_inside > 1 {
+            self.samples.drain(..first_inside - 1);
+        }
+    }
+
+    /// Check `price` from `oracle`, received at `now` (unix seconds)
+    pub fn submit(&mut self, oracle: &str, price: Decimal, now: u64, cfg: &OracleGuardConfig) -> PriceVerdict {
+        if self.is_paused() {
+            return PriceVerdict::Paused;
+        }
+        let Some(twap) = self.twap(now, cfg.twap_window_secs) else {
+            self.accept(price, now, cfg);
+            return PriceVerdict::Accepted;
+        };
+        let window_start = now.saturating_sub(cfg.twap_window_secs);
+        self.pending.retain(|p| p.received_at >= window_start && p.oracle != oracle);
+        self.outliers.retain(|p| p.received_at >= window_start && p.oracle != oracle);
+        let report = PendingPrice { oracle: oracle.to_string(), price, received_at: now };
+
+        let deviation = deviation_bps(price, twap);
+        if deviation <= cfg.confirm_above_bps {
+            self.accept(price, now, cfg);
+            return PriceVerdict::Accepted;
+        }
+        if deviation > cfg.max_deviation_bps {
+            self.outliers.push(report);
+            let reporters = agreeing_sources(&self.outliers, price, cfg.confirm_above_bps);
+            if reporters.len() >= cfg.min_sources {
+                self.tripped = Some(OracleTrip { tripped_at: now, price, twap, deviation_bps: deviation, reporters });
+                self.outliers.clear();
+                return PriceVerdict::Tripped { deviation_bps: deviation };
+            }
+            return PriceVerdict::Rejected { deviation_bps: deviation };
+        }
+
+        self.pending.push(report);
+        let confirmations = agreeing_sources(&self.pending, price, cfg.confirm_above_bps).len();
+        if confirmations >= cfg.min_sources {
+            self.accept(price, now, cfg);
+            return PriceVerdict::Accepted;
+        }
+        PriceVerdict::AwaitingConfirmation { confirmations, required: cfg.min_sources }
+    }
+
+    /// Governance cleared the trip: restart the history from `price`, or from the
+    /// price that tripped the breaker when none is given
+    pub fn resume(&mut self, price: Option<Decimal>, now: u64) -> Option<OracleTrip> {
+        let trip = self.tripped.take()?;
+        self.samples = vec![(now, price.unwrap_or(trip.price))];
+        self.pending.clear();
+        self.outliers.clear();
+        Some(trip)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn price(p: i64) -> Decimal {
+        Decimal::from(p)
+    }
+
+    #[test]
+    fn test_bands_hold_large_moves_and_trip_on_confirmed_outliers() {
+        let cfg = OracleGuardConfig::default();
+        let mut guard = PriceGuard::new("SOL".to_string());
+        assert_eq!(guard.submit("a", price(100), 1_000, &cfg), PriceVerdict::Accepted);
+        assert_eq!(guard.submit("b", price(103), 1_600, &cfg), PriceVerdict::Accepted);
+        // 600s at 100 and 600s at 103
+        assert_eq!(guard.twap(2_200, cfg.twap_window_secs), Some(Decimal::new(1015, 1)));
+
+        // +10%: held until a second oracle agrees
+        assert_eq!(
+            guard.submit("a", price(112), 2_200, &cfg),
+            PriceVerdict::AwaitingConfirmation { confirmations: 1, required: 2 }
+        );
+        assert_eq!(
+            guard.submit("a", price(112), 2_210, &cfg),
+            PriceVerdict::AwaitingConfirmation { confirmations: 1, required: 2 }
+        );
+        assert_eq!(guard.submit("b", price(111), 2_220, &cfg), PriceVerdict::Accepted);
+        assert_eq!(guard.samples.last(), Some(&(2_220, price(111))));
+
+        // A lone outlier is rejected; a confirmed one trips the breaker
+        assert!(matches!(guard.submit("a", price(200), 2_300, &cfg), PriceVerdict::Rejected { .. }));
+        assert!(!guard.is_paused());
+        assert!(matches!(guard.submit("c", price(198), 2_310, &cfg), PriceVerdict::Tripped { .. }));
+        assert!(guard.is_paused());
+        assert_eq!(guard.submit("a", price(101), 2_320, &cfg), PriceVerdict::Paused);
+        let storage = crate::storage::Storage::new_in_memory().unwrap();
+        storage.save_price_guard(&guard).unwrap();
+        assert!(storage.is_oracle_paused("SOL"));
+        assert_eq!(storage.get_price_guard("SOL").unwrap(), guard);
+
+        // Governance resumes the feed at the new level
+        let trip = guard.resume(None, 2_400).unwrap();
+        assert_eq!(trip.reporters, vec!["a".to_string(), "c".to_string()]);
+        assert_eq!(guard.submit("a", price(199), 2_500, &cfg), PriceVerdict::Accepted);
+    }
+}
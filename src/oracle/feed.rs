@@ -0,0 +1,136 @@
+//! Signed price feed for consumers outside the chain.
+//!
+//! Once a price round has closed, the node signs its published price with its
+//! identity key. Each attestation names the hash of the ticker's previous one, so
+//! a consumer holding any attestation can walk the feed back and detect a gap or
+//! a rewritten entry. `getPriceAttestation` serves them.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::crypto::verify_with_pubkey_hex;
+use crate::error::CompassError;
+use crate::node::signer::{NodeSigner, SignPurpose};
+use crate::storage::Storage;
+
+/// A closed round's price, signed by the node that published it
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct PriceAttestation {
+    pub ticker: String,
+    pub round: u64,
+    #[serde(with = "crate::encoding::decimal_str")]
+    pub price: Decimal,
+    /// When the node signed it (unix seconds)
+    pub timestamp: u64,
+    /// `hash()` of the ticker's previous attestation; empty for the first
+    pub prev_hash: String,
+    /// Public key the signature verifies under (hex)
+    pub signer: String,
+    pub signature: String,
+}
+
+/// Message signed for `ticker`'s price in `round`
+pub fn attestation_message(ticker: &str, round: u64, price: Decimal, timestamp: u64, prev_hash: &str) -> String {
+    format!("COMPASS_PRICE:{}:{}:{}:{}:{}", ticker, round, price, timestamp, prev_hash)
+}
+
+impl PriceAttestation {
+    pub fn sign(
+        ticker: &str,
+        round: u64,
+        price: Decimal,
+        timestamp: u64,
+        prev_hash: String,
+        signer: &NodeSigner,
+    ) -> Result<Self, CompassError> {
+        let message = attestation_message(ticker, round, price, timestamp, &prev_hash);
+        let signature = signer
+            .sign_hex(message.as_bytes(), SignPurpose::OracleAttestation)
+            .map_err(|e| CompassError::InvalidState(e.to_string()))?;
+        Ok(Self {
+            ticker: ticker.to_string(),
+            round,
+            price,
+            timestamp,
+            prev_hash,
+            signer: signer.public_key_hex(),
+            signature,
+        })
+    }
+
+    pub fn message(&self) -> String {
+        attestation_message(&self.ticker, self.round, self.price, self.timestamp, &self.prev_hash)
+    }
+
+    /// What the next attestation of this ticker links to
+    pub fn hash(&self) -> String {
+        crate::encoding::hash_to_hex(&crate::encoding::sha256(format!("{}:{}", self.message(), self.signature)))
+    }
+
+    pub fn verify(&self) -> bool {
+        verify_with_pubkey_hex(self.message().as_bytes(), &self.signature, &self.signer)
+    }
+}
+
+/// Sign every round of `ticker` that published a price, closed before
+/// `current_round` and is not attested yet, oldest first
+pub fn attest_closed_rounds(
+    storage: &Storage,
+    ticker: &str,
+    current_round: u64,
+    now: u64,
+    signer: &NodeSigner,
+) -> Result<Vec<PriceAttestation>, CompassError> {
+    let last = storage.get_latest_price_attestation(ticker);
+    let after = last.as_ref().map(|a| a.round);
+    let mut prev_hash = last.map(|a| a.hash()).unwrap_or_default();
+
+    let mut signed = Vec::new();
+    for round in storage.get_price_rounds(ticker) {
+        if round.round >= current_round || after.is_some_and(|r| round.round <= r) {
+            continue;
+        }
+        let Some(aggregate) = round.aggregate else { continue };
+        let attestation = PriceAttestation::sign(ticker, round.round, aggregate.price, now, prev_hash, signer)?;
+        storage.save_price_attestation(&attestation)?;
+        prev_hash = attestation.hash();
+        signed.push(attestation);
+    }
+    Ok(signed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::oracle::aggregation::{OracleAggregationConfig, PriceContribution, PriceRound};
+    use std::sync::Arc;
+
+    #[test]
+    fn test_closed_rounds_are_signed_into_a_chain() {
+        let storage = Storage::new_in_memory().unwrap();
+        let signer = NodeSigner::Local(Arc::new(crate::crypto::KeyPair::from_seed("node")));
+        let cfg = OracleAggregationConfig::default();
+        for (round_no, price) in [(1, 100), (2, 101), (3, 102)] {
+            let mut round = PriceRound::new("BTC", round_no);
+            let contribution = PriceContribution { oracle: "a".to_string(), price: Decimal::from(price), stake: 1, received_at: 0 };
+            round.submit(contribution, &cfg);
+            storage.save_price_round(&round).unwrap();
+        }
+
+        // Round 3 is still open
+        let signed = attest_closed_rounds(&storage, "BTC", 3, 500, &signer).unwrap();
+        assert_eq!(signed.iter().map(|a| a.round).collect::<Vec<_>>(), vec![1, 2]);
+        assert!(signed.iter().all(PriceAttestation::verify));
+        assert_eq!(signed[0].prev_hash, "");
+        assert_eq!(signed[1].prev_hash, signed[0].hash());
+        assert_eq!(storage.get_price_attestation("BTC", 2).unwrap(), Some(signed[1].clone()));
+
+        let signed = attest_closed_rounds(&storage, "BTC", 4, 560, &signer).unwrap();
+        assert_eq!((signed.len(), signed[0].round), (1, 3));
+        assert_eq!(signed[0].prev_hash, storage.get_price_attestation("BTC", 2).unwrap().unwrap().hash());
+
+        let mut forged = signed[0].clone();
+        forged.price = Decimal::from(1);
+        assert!(!forged.verify());
+    }
+}
@@ -0,0 +1,89 @@
+//! Freshness of the prices published to the vaults.
+//!
+//! Each published price records how far it moved from the one before it. A price
+//! is stale when it is older than `max_price_age_secs`, or when that move was more
+//! than `max_update_move_bps`; such a jump stays stale until a later update lands
+//! within the bound of it. Mints, burns, fee conversion and liquidation refuse to
+//! act on a stale price.
+
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use super::circuit_breaker::{deviation_bps, OracleGuardConfig};
+
+/// What is known about a ticker's latest price besides its value
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct PriceFreshness {
+    /// Move from `previous`, in basis points
+    pub move_bps: u64,
+}
+
+/// Why a price can't be acted on
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+#[serde(rename_all = "snake_case", tag = "reason")]
+pub enum Staleness {
+    TooOld { age_secs: u64 },
+    BigMove { move_bps: u64 },
+}
+
+impl std::fmt::Display for Staleness {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Staleness::TooOld { age_secs } => write!(f, "last updated {}s ago", age_secs),
+            Staleness::BigMove { move_bps } => write!(f, "moved {} bps in one update", move_bps),
+        }
+    }
+}
+
+impl PriceFreshness {
+    /// Freshness of `price` replacing `previous`
+    pub fn after(previous: Option<Decimal>, price: Decimal) -> Self {
+        let move_bps = previous.map_or(0, |p| deviation_bps(price, p));
+        Self { move_bps }
+    }
+
+    /// Why the price published at `timestamp` is stale at `now` (unix seconds), if it is.
+    /// A zero limit turns its check off.
+    pub fn staleness(&self, timestamp: u64, now: u64, cfg: &OracleGuardConfig) -> Option<Staleness> {
+        let age_secs = now.saturating_sub(timestamp);
+        if cfg.max_price_age_secs > 0 && age_secs > cfg.max_price_age_secs {
+            return Some(Staleness::TooOld { age_secs });
+        }
+        if cfg.max_update_move_bps > 0 && self.move_bps > cfg.max_update_move_bps {
+            return Some(Staleness::BigMove { move_bps: self.move_bps });
+        }
+        None
+    }
+}
+
+/// One ticker's entry in `getOraclePrices`
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct OraclePriceStatus {
+    pub price: Decimal,
+    pub timestamp: u64,
+    pub move_bps: u64,
+    /// Set while the price can't be acted on
+    pub stale: Option<Staleness>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prices_go_stale_with_age_and_on_jumps() {
+        let cfg = OracleGuardConfig { max_price_age_secs: 600, max_update_move_bps: 1000, ..Default::default() };
+        let first = PriceFreshness::after(None, Decimal::from(100));
+        assert_eq!(first.staleness(1_000, 1_600, &cfg), None);
+        assert_eq!(first.staleness(1_000, 1_601, &cfg), Some(Staleness::TooOld { age_secs: 601 }));
+
+        // +15% in one update is held stale until the next update confirms the level
+        let jump = PriceFreshness::after(Some(Decimal::from(100)), Decimal::from(115));
+        assert_eq!(jump.staleness(1_000, 1_000, &cfg), Some(Staleness::BigMove { move_bps: 1500 }));
+        let settled = PriceFreshness::after(Some(Decimal::from(115)), Decimal::from(116));
+        assert_eq!(settled.staleness(1_000, 1_000, &cfg), None);
+
+        let off = OracleGuardConfig { max_price_age_secs: 0, max_update_move_bps: 0, ..cfg };
+        assert_eq!(jump.staleness(0, u64::MAX, &off), None);
+    }
+}
@@ -5,6 +5,10 @@ pub mod consensus;
 pub mod monitor;
 pub mod registry; // v2.0 oracle registry with staking
 pub mod attestation; // v2.1 multi-signature attestation for decentralization
+pub mod circuit_breaker; // TWAP sanity bands; trips pause vaults and fee conversion
+pub mod aggregation; // stake-weighted median of several oracles' prices per round
+pub mod freshness; // max age and single-update jump limits on published prices
+pub mod feed; // node-signed, hash-linked attestations of each closed round's price
 
 pub use service::OracleService;
 pub use types::OracleConfig;
@@ -30,6 +30,10 @@ pub struct RegisteredOracle {
     pub incorrect_submissions: u64,
     pub last_submission_block: u64,
     pub supported_feeds: Vec<String>,  // BTCUSD, ETHUSD, etc.
+    /// Key its price submissions must be signed with; unset for oracles
+    /// registered without one, whose submissions are taken unsigned
+    #[serde(default)]
+    pub pubkey: Option<String>,
 }
 
 impl RegisteredOracle {
@@ -46,6 +50,7 @@ impl RegisteredOracle {
             incorrect_submissions: 0,
             last_submission_block: registration_block,
             supported_feeds: vec![],
+            pubkey: None,
         }
     }
     
@@ -123,6 +128,24 @@ impl OracleRegistry {
         Ok(())
     }
     
+    /// Register an oracle whose submissions are signed with `pubkey`, or update
+    /// the key and stake of one already registered
+    pub fn register_oracle_key(
+        &mut self,
+        account_id: AccountId,
+        pubkey: String,
+        stake_amount: u64,
+        block_height: u64,
+    ) -> Result<(), String> {
+        if !self.oracles.contains_key(&account_id) {
+            self.register_oracle(account_id.clone(), stake_amount, block_height)?;
+        }
+        let oracle = self.oracles.get_mut(&account_id).expect("registered above");
+        oracle.stake_amount = stake_amount;
+        oracle.pubkey = Some(pubkey);
+        Ok(())
+    }
+
     /// Get oracle by account ID
     pub fn get_oracle(&self, account_id: &AccountId) -> Option<&RegisteredOracle> {
         self.oracles.get(account_id)
@@ -168,7 +191,19 @@ pub struct OraclePriceSubmission {
     pub ticker: String,
     pub price: f64,
     pub timestamp: u64,
-    pub signature: Vec<u8>,
+    /// Signature over `aggregation::price_message` (hex) by the oracle's key
+    #[serde(default)]
+    pub signature: String,
+}
+
+impl OraclePriceSubmission {
+    /// Whether `oracle` signed this submission; oracles registered without a key
+    /// aren't asked to
+    pub fn is_signed_by(&self, oracle: &RegisteredOracle) -> bool {
+        let Some(pubkey) = &oracle.pubkey else { return true };
+        let msg = crate::oracle::aggregation::price_message(&self.ticker, self.price, self.timestamp);
+        crate::crypto::verify_with_pubkey_hex(msg.as_bytes(), &self.signature, pubkey)
+    }
 }
 
 #[cfg(test)]
@@ -201,6 +236,30 @@ mod tests {
         ).is_err());
     }
     
+    #[test]
+    fn test_keyed_oracles_must_sign_submissions() {
+        let key = crate::crypto::KeyPair::from_seed("oracle-key");
+        let mut registry = OracleRegistry::new();
+        registry.register_oracle("legacy".to_string(), ORACLE_MIN_STAKE, 0).unwrap();
+        registry.register_oracle_key("keyed".to_string(), key.public_key_hex(), ORACLE_MIN_STAKE * 2, 0).unwrap();
+        assert_eq!(registry.get_oracle(&"keyed".to_string()).unwrap().stake_amount, ORACLE_MIN_STAKE * 2);
+
+        let mut submission = OraclePriceSubmission {
+            oracle_account: "keyed".to_string(),
+            ticker: "BTCUSD".to_string(),
+            price: 65_000.5,
+            timestamp: 1_000,
+            signature: String::new(),
+        };
+        let keyed = registry.get_oracle(&"keyed".to_string()).unwrap();
+        assert!(!submission.is_signed_by(keyed));
+        submission.signature = key.sign_hex(crate::oracle::aggregation::price_message("BTCUSD", 65_000.5, 1_000).as_bytes());
+        assert!(submission.is_signed_by(keyed));
+        submission.price = 1.0;
+        assert!(!submission.is_signed_by(keyed));
+        assert!(submission.is_signed_by(registry.get_oracle(&"legacy".to_string()).unwrap()));
+    }
+
     #[test]
     fn test_reputation_tracking() {
         let mut oracle = RegisteredOracle::new("oracle1".to_string(), ORACLE_MIN_STAKE, 0);
@@ -1,42 +1,57 @@
-use crate::crypto::KeyPair;
-use crate::oracle::chains::{BitcoinClient, LitecoinClient};
+use crate::node::signer::{NodeSigner, SignPurpose};
+use crate::oracle::chains::{BitcoinClient, EthereumClient, LitecoinClient};
 use crate::oracle::types::{DepositProof, DepositRequest, OracleConfig};
+use crate::vault::redemption::WithdrawalIntent;
 use std::collections::HashSet;
 use std::sync::{Arc, Mutex};
+#[cfg(feature = "ai")]
 use crate::layer3::models::BridgePredictor;
 use crate::layer2::Layer2State;
+#[cfg(feature = "ai")]
 use crate::layer3::data::FinanceDataFetcher;
 
 pub struct OracleService {
     config: OracleConfig,
     btc_client: BitcoinClient,
     ltc_client: LitecoinClient,
-    oracle_keypair: KeyPair,
+    eth_client: Option<EthereumClient>,
+    oracle_signer: Arc<NodeSigner>,
     processed_deposits: HashSet<String>,
     
     // Bridge Components
+    #[cfg(feature = "ai")]
     predictor: BridgePredictor,
+    #[cfg(feature = "ai")]
     fetcher: FinanceDataFetcher,
+    #[cfg_attr(not(feature = "ai"), allow(dead_code))]
     layer2: Arc<Mutex<Layer2State>>,
 }
 
 impl OracleService {
-    pub fn new(config: OracleConfig, oracle_keypair: KeyPair, layer2: Arc<Mutex<Layer2State>>) -> Self {
+    pub fn new(config: OracleConfig, oracle_signer: Arc<NodeSigner>, layer2: Arc<Mutex<Layer2State>>) -> Self {
         let btc_client = BitcoinClient::new(config.blockcypher_api_key.clone());
         let ltc_client = LitecoinClient::new(config.blockcypher_api_key.clone());
+        let eth_client = config.ethereum.as_ref().map(EthereumClient::from_config);
         
         // Initialize AI components
-        println!("[Oracle] Initializing Bridge Neural Network...");
-        let predictor = BridgePredictor::new();
+        #[cfg(feature = "ai")]
+        let predictor = {
+            println!("[Oracle] Initializing Bridge Neural Network...");
+            BridgePredictor::new()
+        };
+        #[cfg(feature = "ai")]
         let fetcher = FinanceDataFetcher::new();
 
         Self {
             config,
             btc_client,
             ltc_client,
-            oracle_keypair,
+            eth_client,
+            oracle_signer,
             processed_deposits: HashSet::new(),
+            #[cfg(feature = "ai")]
             predictor,
+            #[cfg(feature = "ai")]
             fetcher,
             layer2,
         }
@@ -52,6 +67,9 @@ impl OracleService {
             "BTC" => self.verify_btc_deposit(request).await,
             "LTC" => self.verify_ltc_deposit(request).await,
             "SOL" => Err("SOL verification not yet implemented".to_string()),
+            ticker if self.config.ethereum.as_ref().is_some_and(|eth| eth.asset(ticker).is_some()) => {
+                self.verify_evm_deposit(request).await
+            }
             _ => Err(format!("Unsupported chain: {}", request.chain)),
         }
     }
@@ -88,8 +106,11 @@ impl OracleService {
             request.requester
         );
 
-        let oracle_sig = self.oracle_keypair.sign_hex(message.as_bytes());
-        let oracle_pubkey = self.oracle_keypair.public_key_hex();
+        let oracle_sig = self
+            .oracle_signer
+            .sign_hex(message.as_bytes(), SignPurpose::OracleAttestation)
+            .map_err(|e| e.to_string())?;
+        let oracle_pubkey = self.oracle_signer.public_key_hex();
 
         // Mark as processed
         self.processed_deposits.insert(request.tx_hash.clone());
@@ -138,8 +159,11 @@ impl OracleService {
             request.requester
         );
 
-        let oracle_sig = self.oracle_keypair.sign_hex(message.as_bytes());
-        let oracle_pubkey = self.oracle_keypair.public_key_hex();
+        let oracle_sig = self
+            .oracle_signer
+            .sign_hex(message.as_bytes(), SignPurpose::OracleAttestation)
+            .map_err(|e| e.to_string())?;
+        let oracle_pubkey = self.oracle_signer.public_key_hex();
 
         // Mark as processed
         self.processed_deposits.insert(request.tx_hash.clone());
@@ -156,7 +180,96 @@ impl OracleService {
         })
     }
 
+    async fn verify_evm_deposit(&mut self, request: DepositRequest) -> Result<DepositProof, String> {
+        println!("[Oracle] Verifying {} deposit: {}", request.chain, request.tx_hash);
+
+        let (Some(eth), Some(client)) = (&self.config.ethereum, &self.eth_client) else {
+            return Err("Ethereum endpoint not configured".to_string());
+        };
+        let asset = eth.asset(&request.chain).ok_or(format!("Unsupported chain: {}", request.chain))?;
+
+        // Verify the receipt's transfer log (or native value) against the canonical chain
+        let (verified, confirmations) = client
+            .verify_deposit(&request.tx_hash, asset, &request.vault_address, request.expected_amount)
+            .await?;
+
+        if !verified {
+            return Err("Deposit not found or insufficient amount".to_string());
+        }
+
+        if confirmations < eth.min_confirmations {
+            return Err(format!(
+                "Insufficient confirmations: {} (need {})",
+                confirmations, eth.min_confirmations
+            ));
+        }
+
+        println!("[Oracle] ✓ Verified: {} confirmations", confirmations);
+
+        let message = format!(
+            "DEPOSIT:{}:{}:{}:{}:{}",
+            request.chain,
+            request.expected_amount,
+            request.tx_hash,
+            request.expected_amount,
+            request.requester
+        );
+
+        let oracle_sig = self
+            .oracle_signer
+            .sign_hex(message.as_bytes(), SignPurpose::OracleAttestation)
+            .map_err(|e| e.to_string())?;
+        let oracle_pubkey = self.oracle_signer.public_key_hex();
+
+        self.processed_deposits.insert(request.tx_hash.clone());
+
+        Ok(DepositProof {
+            verified: true,
+            tx_hash: request.tx_hash,
+            amount: request.expected_amount,
+            confirmations,
+            vault_address: request.vault_address,
+            timestamp: crate::block::current_unix_timestamp_ms(),
+            oracle_signature: oracle_sig,
+            oracle_pubkey,
+        })
+    }
+
+    /// Confirmations of `external_tx` paying `intent`'s payout to its destination.
+    /// Errors until the payment is found in full with the chain's minimum confirmations.
+    pub async fn confirm_payout(&self, intent: &WithdrawalIntent, external_tx: &str) -> Result<u32, String> {
+        let (to, amount) = (&intent.destination_address, intent.quote.payout);
+        let (verified, confirmations, needed) = match intent.collateral_asset.as_str() {
+            "BTC" => {
+                let (verified, confirmations) = self.btc_client.verify_deposit(external_tx, to, amount).await?;
+                (verified, confirmations, self.config.min_confirmations_btc)
+            }
+            "LTC" => {
+                let (verified, confirmations) = self.ltc_client.verify_deposit(external_tx, to, amount).await?;
+                (verified, confirmations, self.config.min_confirmations_ltc)
+            }
+            ticker => {
+                let unsupported = || format!("No payout verification for {}", ticker);
+                let (Some(eth), Some(client)) = (&self.config.ethereum, &self.eth_client) else {
+                    return Err(unsupported());
+                };
+                let asset = eth.asset(ticker).ok_or_else(unsupported)?;
+                let (verified, confirmations) = client.verify_deposit(external_tx, asset, to, amount).await?;
+                (verified, confirmations, eth.min_confirmations)
+            }
+        };
+
+        if !verified {
+            return Err("Payout not found or insufficient amount".to_string());
+        }
+        if confirmations < needed {
+            return Err(format!("Insufficient confirmations: {} (need {})", confirmations, needed));
+        }
+        Ok(confirmations)
+    }
+
     /// Bridge Function: Evaluate Betting Outcomes and Trigger Slashing
+    #[cfg(feature = "ai")]
     pub async fn process_betting_outcomes(&mut self) {
         // 1. Evaluate Bets via Neural Network
         let settled = self.predictor.evaluate_and_learn(&mut self.fetcher).await;
@@ -185,7 +298,7 @@ impl OracleService {
                     // Simplified: We assume Betting Risk comes from the Main Staked Balance in L2.
                     println!("[Oracle] ⚔️ Slashing {} by {} for incorrect prediction.", entity, slash_amount);
                     
-                    match l2.collateral.slash(&entity, slash_amount) {
+                    match l2.slash(&self.oracle_signer.public_key_hex(), &entity, slash_amount, "incorrect bridge prediction") {
                          Ok(slashed) => println!("[Oracle] ✅ Slashed {}. Insurance Fund increased.", slashed),
                          Err(e) => println!("[Oracle] ⚠️ Slashing failed (insufficient stake?): {}", e),
                     }
@@ -207,6 +320,10 @@ impl OracleService {
         // Save L2 state
         let _ = l2.save("layer2.json");
     }
+
+    /// Built without the `ai` feature there is no predictor placing bets, so nothing settles
+    #[cfg(not(feature = "ai"))]
+    pub async fn process_betting_outcomes(&mut self) {}
 }
 
 #[cfg(test)]
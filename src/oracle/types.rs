@@ -2,7 +2,7 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DepositRequest {
-    pub chain: String,           // "BTC", "LTC", "SOL"
+    pub chain: String,           // "BTC", "LTC", "SOL", or an EVM asset ("ETH", "USDC")
     pub tx_hash: String,          // Transaction hash
     pub vault_address: String,    // Expected destination
     pub expected_amount: u64,     // Expected amount in smallest unit
@@ -27,6 +27,8 @@ pub struct OracleConfig {
     pub min_confirmations_btc: u32,
     pub min_confirmations_ltc: u32,
     pub min_confirmations_sol: u32,
+    /// Ethereum endpoint and the EVM assets it verifies; EVM deposits are refused when unset
+    pub ethereum: Option<crate::oracle::chains::ethereum::EvmConfig>,
 }
 
 impl Default for OracleConfig {
@@ -36,6 +38,7 @@ impl Default for OracleConfig {
             min_confirmations_btc: 6,
             min_confirmations_ltc: 12,
             min_confirmations_sol: 32,
+            ethereum: None,
         }
     }
 }
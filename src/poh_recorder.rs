@@ -1,5 +1,15 @@
 // Removed unused: use sha2::Digest;
 use crate::vdf::{WesolowskiVDF, ALPHA_MODULUS};
+use std::time::{Duration, Instant};
+
+/// VDF iterations per tick on a typical desktop/server CPU
+pub const DEFAULT_HASHES_PER_TICK: u64 = 80_000;
+
+/// Floor for calibrated difficulty, so a very slow or busy host still does real work
+pub const MIN_HASHES_PER_TICK: u64 = 1_000;
+
+/// Squarings timed when calibrating
+const CALIBRATION_SAMPLE: u64 = 2_000;
 
 pub struct PoHRecorder {
     pub tick_height: u64,
@@ -41,6 +51,18 @@ impl PoHRecorder {
         (start_hash, end_hash)
     }
 
+    /// VDF iterations that fit in `budget` on this CPU, clamped to
+    /// [MIN_HASHES_PER_TICK, DEFAULT_HASHES_PER_TICK]
+    pub fn calibrate_hashes_per_tick(budget: Duration) -> u64 {
+        let vdf = WesolowskiVDF::new(ALPHA_MODULUS);
+        let start = Instant::now();
+        vdf.eval(b"COMPASS_VDF_CALIBRATION", CALIBRATION_SAMPLE);
+        let per_iteration_ns = (start.elapsed().as_nanos() / CALIBRATION_SAMPLE as u128).max(1);
+
+        let fit = budget.as_nanos() / per_iteration_ns;
+        (fit.min(u64::MAX as u128) as u64).clamp(MIN_HASHES_PER_TICK, DEFAULT_HASHES_PER_TICK)
+    }
+
     /// Prove a specific transition.
     pub fn prove(&self, start_hash: &[u8], iterations: u64) -> Vec<u8> {
         self.vdf.solve(start_hash, iterations).1
@@ -52,3 +74,17 @@ impl PoHRecorder {
         vdf.verify(start_hash, iterations, end_hash, proof)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_calibration_is_clamped() {
+        assert_eq!(PoHRecorder::calibrate_hashes_per_tick(Duration::ZERO), MIN_HASHES_PER_TICK);
+        assert_eq!(
+            PoHRecorder::calibrate_hashes_per_tick(Duration::from_secs(3600)),
+            DEFAULT_HASHES_PER_TICK
+        );
+    }
+}
@@ -0,0 +1,210 @@
+// State replay
+//
+// `compass replay` re-executes the canonical chain from genesis into a scratch
+// database and checks the result against the live one. After every block the
+// replayed state root must equal the root in that block's header, and once the
+// replay reaches the live head every balance and nonce must match key for key.
+// Ledger writes that happen outside blocks (PoUW payouts, fee pool seeding) are
+// not replayed, so they show up here as divergences too.
+use std::collections::{BTreeMap, BTreeSet};
+
+use crate::chain::Chain;
+use crate::error::CompassError;
+use crate::storage::Storage;
+
+/// A balance or nonce that differs between the live and the replayed state
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Divergence {
+    /// State key, e.g. `bal:alice:Compass` or `nonce:alice`
+    pub key: String,
+    pub live: Option<u64>,
+    pub replayed: Option<u64>,
+}
+
+/// A block whose header carries a different state root than replay produced
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RootMismatch {
+    pub height: u64,
+    pub header_root: String,
+    pub replayed_root: String,
+}
+
+/// What a replay found
+#[derive(Debug, Default)]
+pub struct ReplayReport {
+    /// Blocks re-executed
+    pub blocks: u64,
+    /// Height of the last block replayed
+    pub height: u64,
+    /// Number of blocks in the live chain
+    pub live_height: u64,
+    /// First block whose header root differs from the replayed one; every later
+    /// block usually differs as well, so the rest are only counted
+    pub first_root_mismatch: Option<RootMismatch>,
+    pub root_mismatches: u64,
+    /// Block the replay stopped at and the error it hit
+    pub failed: Option<(u64, String)>,
+    /// Key by key differences from the live state. Only compared when the replay
+    /// reached the live head; `None` otherwise.
+    pub divergences: Option<Vec<Divergence>>,
+}
+
+impl ReplayReport {
+    /// Replay matched the live chain everywhere it was checked
+    pub fn is_clean(&self) -> bool {
+        self.root_mismatches == 0
+            && self.failed.is_none()
+            && self.divergences.as_ref().is_none_or(|d| d.is_empty())
+    }
+}
+
+/// Number of blocks in `storage`'s canonical chain
+pub fn chain_height(storage: &Storage) -> Result<u64, CompassError> {
+    let Some(head) = storage.get::<String>("chain_info:head")? else { return Ok(0) };
+    Ok(storage.get_block(&head)?.map_or(0, |b| b.header.index + 1))
+}
+
+/// Balances are stored big-endian, nonces as bincode (little-endian)
+fn decode_state_value(key: &[u8], value: &[u8]) -> Option<u64> {
+    let bytes: [u8; 8] = value.try_into().ok()?;
+    Some(if key.starts_with(b"bal:") { u64::from_be_bytes(bytes) } else { u64::from_le_bytes(bytes) })
+}
+
+/// Balances and nonces that differ between two databases. Zero and absent
+/// entries count as equal, as they do in the state root.
+pub fn diff_state(live: &Storage, replayed: &Storage) -> Result<Vec<Divergence>, CompassError> {
+    let live: BTreeMap<Vec<u8>, Vec<u8>> = live.state_leaves()?.into_iter().collect();
+    let replayed: BTreeMap<Vec<u8>, Vec<u8>> = replayed.state_leaves()?.into_iter().collect();
+    let keys: BTreeSet<&Vec<u8>> = live.keys().chain(replayed.keys()).collect();
+    Ok(keys
+        .into_iter()
+        .filter(|key| live.get(*key) != replayed.get(*key))
+        .map(|key| Divergence {
+            key: String::from_utf8_lossy(key).into_owned(),
+            live: live.get(key).and_then(|v| decode_state_value(key, v)),
+            replayed: replayed.get(key).and_then(|v| decode_state_value(key, v)),
+        })
+        .collect())
+}
+
+/// Re-execute `live`'s canonical blocks up to height `to` (capped at its head) on
+/// `scratch`, which must be empty or start from the same genesis block, checking
+/// each block's state root and finally the whole state against `live`.
+pub fn replay(live: &Storage, scratch: &mut Chain, to: u64) -> Result<ReplayReport, CompassError> {
+    let live_height = chain_height(live)?;
+    if live_height == 0 {
+        return Err(CompassError::InvalidState("live database has no blocks".to_string()));
+    }
+    if scratch.height > 0 {
+        let live_hash = live.get_block_by_height(scratch.height - 1)?.map(|b| b.header.hash);
+        if scratch.head_hash() != live_hash {
+            return Err(CompassError::InvalidState(format!(
+                "scratch chain at height {} does not match the live chain",
+                scratch.height
+            )));
+        }
+    }
+
+    let to = to.min(live_height - 1);
+    let mut report = ReplayReport { live_height, ..Default::default() };
+    for height in scratch.height..=to {
+        let Some(block) = live.get_block_by_height(height)? else {
+            report.failed = Some((height, "block missing from the live database".to_string()));
+            break;
+        };
+        let header_root = block.header.state_root.clone();
+        if let Err(e) = scratch.replay_block(block) {
+            report.failed = Some((height, e.to_string()));
+            break;
+        }
+        report.blocks += 1;
+        report.height = height;
+
+        // Blocks from before state roots were stamped carry none
+        if header_root.is_empty() {
+            continue;
+        }
+        let replayed_root = scratch.state_root()?;
+        if replayed_root != header_root {
+            report.root_mismatches += 1;
+            report.first_root_mismatch.get_or_insert(RootMismatch { height, header_root, replayed_root });
+        }
+    }
+
+    if report.failed.is_none() && to + 1 == live_height {
+        report.divergences = Some(diff_state(live, &scratch.storage)?);
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::block::{BlockHeader, BlockType};
+    use crate::crypto::KeyPair;
+
+    fn transfer(chain: &Chain, sender: &KeyPair, to: &str, amount: u64, nonce: u64) -> BlockHeader {
+        let mut header = BlockHeader {
+            index: chain.height,
+            timestamp: 1_000 + chain.height,
+            prev_hash: chain.head_hash().unwrap_or_default(),
+            hash: String::new(),
+            proposer: sender.public_key_hex(),
+            signature_hex: String::new(),
+            block_type: BlockType::Transfer {
+                from: sender.public_key_hex(),
+                to: to.to_string(),
+                asset: "Compass".to_string(),
+                amount,
+                nonce,
+                fee: 1,
+            },
+            state_root: String::new(),
+            root_attestation: None,
+        };
+        header.hash = header.calculate_hash().unwrap();
+        header.signature_hex = sender.sign_hex(&crate::encoding::hash_from_hex(&header.hash).unwrap());
+        header
+    }
+
+    #[test]
+    fn test_replay_matches_then_reports_out_of_band_writes() {
+        let alice = KeyPair::from_seed("replay-alice");
+        let mut live = Chain::new_in_memory();
+        live.storage.writer().set_balance(&alice.public_key_hex(), "Compass", 100).unwrap();
+        for nonce in 1..=3 {
+            let header = transfer(&live, &alice, "bob", 10, nonce);
+            live.append_transfer(header, &alice.public_key_hex()).unwrap();
+        }
+
+        // The scratch chain gets the same genesis allocation before replay starts
+        let fresh = || {
+            let chain = Chain::new_in_memory();
+            chain.storage.writer().set_balance(&alice.public_key_hex(), "Compass", 100).unwrap();
+            chain
+        };
+
+        let mut scratch = fresh();
+        let report = replay(&live.storage, &mut scratch, u64::MAX).unwrap();
+        assert_eq!((report.blocks, report.height, report.live_height), (3, 2, 3));
+        assert!(report.is_clean(), "{:?}", report);
+        assert_eq!(scratch.storage.get_balance("bob", "Compass").unwrap(), 30);
+
+        // Stopping short checks roots but leaves the live state alone
+        let partial = replay(&live.storage, &mut fresh(), 1).unwrap();
+        assert_eq!(partial.blocks, 2);
+        assert!(partial.divergences.is_none());
+
+        // A balance written outside any block is caught at the key and the root
+        live.storage.writer().credit("bob", "Compass", 5).unwrap();
+        let header = transfer(&live, &alice, "carol", 1, 4);
+        live.append_transfer(header, &alice.public_key_hex()).unwrap();
+        let report = replay(&live.storage, &mut fresh(), u64::MAX).unwrap();
+        assert_eq!(report.root_mismatches, 1);
+        assert_eq!(report.first_root_mismatch.unwrap().height, 3);
+        assert_eq!(
+            report.divergences.unwrap(),
+            vec![Divergence { key: "bal:bob:Compass".to_string(), live: Some(35), replayed: Some(30) }]
+        );
+    }
+}
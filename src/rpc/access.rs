@@ -0,0 +1,148 @@
+// Per-listener method allowlists
+//
+// Each RPC listener serves either the public method set (chain reads and signed
+// transaction submission) or every method. The check runs as middleware in front
+// of the JSON-RPC handler, so a method left off a listener's list never reaches
+// `dispatch` there, whatever the handler itself does.
+use axum::body::{to_bytes, Body};
+use axum::extract::{Request, State};
+use axum::middleware::Next;
+use axum::response::{IntoResponse, Response};
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use super::types::{RpcError, RpcReply, RpcResponse};
+
+/// Largest request body the allowlist buffers to read method names
+const MAX_RPC_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Reads anyone may make, and submissions that carry their own signature
+pub const PUBLIC_METHODS: &[&str] = &[
+    // Accounts and names
+    "getBalance", "getNonce", "getAccountInfo", "getAccountBalances", "resolveName", "getProof",
+    "getAccountHistory", "getAccountActivity", "getFreezeStatus", "getMultisigProposal", "getVaultAddress",
+    "getVestingSchedule", "getTreasury", "getSupplyInfo", "getRedemptions", "getStakeInfo", "queryContract",
+    // Blocks and indexes
+    "getChainHeight", "getBlock", "getLatestBlocks", "getBlockRange", "getHeaders", "search",
+    "getBlocksByAccount", "getBlocksByProposer", "getTransfersByAsset", "getMintsByVault", "getBlocksByType",
+    "getNftEvents", "getStateDiff", "getProofOfReserve", "getVaultState", "getL2Checkpoint", "getFinalizedHeight",
+    // Node and validators
+    "getNodeInfo", "getVersion", "getValidatorStats", "getValidatorLiveness", "getLeaderSchedule",
+    "getEpochReport", "getSkippedSlots",
+    // Transactions
+    "quoteFee", "estimateFee", "getTransactionStatus", "getTransactionReceipt", "getScheduledTransactions", "getJobResult", "getTradeReceipt",
+    "submitTransaction", "submitSignedTransaction", "submitSponsoredTransaction", "submitAssetFeeTransaction",
+    "submitScheduledTransaction", "submitFreeze", "login", "logout", "revokeSessions",
+    "proposeMultisigTransaction", "approveMultisigTransaction", "executeMultisigTransaction",
+    "proposePoolChange", "votePoolProposal", "claimEarnings", "withdrawUnbonded", "deployContract", "callContract",
+    // Markets, NFTs and prices
+    "getOraclePrices", "getOracleBreakers", "getPriceRound", "getPriceAttestation", "getLatestPrice", "getAllNFTs", "getNFTMetadata", "getNFTVerification",
+    "getCollection", "getCollections", "getMarketListings", "getRentableModels", "getModelPools", "getPoolGovernance", "getEarnings",
+    "getCompetition", "getCompetitions", "getOrderBook", "getTicker", "getCandles", "getMarketStats",
+];
+
+/// Method set a listener starts from; a listener that doesn't say gets the public one
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum RpcAccess {
+    /// `PUBLIC_METHODS` only
+    #[default]
+    Public,
+    /// Every method, including wallet and admin ones
+    Admin,
+}
+
+/// Methods one listener serves
+#[derive(Debug, Clone)]
+pub struct MethodAllowlist {
+    access: RpcAccess,
+    extra: HashSet<String>,
+}
+
+impl MethodAllowlist {
+    /// `access`'s methods plus `extra`
+    pub fn new(access: RpcAccess, extra: &[String]) -> Self {
+        Self { access, extra: extra.iter().cloned().collect() }
+    }
+
+    pub fn all() -> Self {
+        Self::new(RpcAccess::Admin, &[])
+    }
+
+    pub fn allows(&self, method: &str) -> bool {
+        match self.access {
+            RpcAccess::Admin => true,
+            RpcAccess::Public => PUBLIC_METHODS.contains(&method) || self.extra.contains(method),
+        }
+    }
+
+    /// First method named in a request body (one call or a batch) this listener refuses
+    pub fn first_refused<'a>(&self, body: &'a serde_json::Value) -> Option<&'a str> {
+        let calls = match body {
+            serde_json::Value::Array(entries) => entries.as_slice(),
+            single => std::slice::from_ref(single),
+        };
+        calls
+            .iter()
+            .filter_map(|call| call.get("method")?.as_str())
+            .find(|method| !self.allows(method))
+    }
+}
+
+/// Middleware: answer a call (or a batch holding one) to a method outside the
+/// listener's allowlist with -32601 before it reaches the handler
+pub async fn enforce_allowlist(
+    State(allowlist): State<Arc<MethodAllowlist>>,
+    req: Request,
+    next: Next,
+) -> Response {
+    if allowlist.access == RpcAccess::Admin {
+        return next.run(req).await;
+    }
+
+    let (parts, body) = req.into_parts();
+    let bytes = match to_bytes(body, MAX_RPC_BODY_BYTES).await {
+        Ok(bytes) => bytes,
+        Err(e) => return refusal(0, -32600, format!("Invalid Request: {}", e)),
+    };
+    // Bodies that aren't JSON are left for the handler to reject
+    if let Ok(value) = serde_json::from_slice::<serde_json::Value>(&bytes) {
+        if let Some(method) = allowlist.first_refused(&value) {
+            let id = value.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+            return refusal(id, -32601, format!("Method not available on this listener: {}", method));
+        }
+    }
+    next.run(Request::from_parts(parts, Body::from(bytes))).await
+}
+
+fn refusal(id: u64, code: i32, message: String) -> Response {
+    let resp = RpcResponse { jsonrpc: "2.0".to_string(), result: None, error: Some(RpcError { code, message }), id };
+    Json(RpcReply::Single(resp)).into_response()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_public_listener_refuses_wallet_and_admin_methods() {
+        let public = MethodAllowlist::new(RpcAccess::Public, &["getPeers".to_string()]);
+        assert!(public.allows("getBalance"));
+        assert!(public.allows("submitSignedTransaction"));
+        assert!(public.allows("getPeers"));
+        assert!(!public.allows("clearAllNFTs"));
+        assert!(!public.allows("mintModelNFT"));
+
+        let batch = json!([
+            { "jsonrpc": "2.0", "method": "getBalance", "params": {}, "id": 1 },
+            { "jsonrpc": "2.0", "method": "seedFeePool", "params": {}, "id": 2 },
+        ]);
+        assert_eq!(public.first_refused(&batch), Some("seedFeePool"));
+        assert_eq!(public.first_refused(&json!({ "method": "getChainHeight" })), None);
+
+        assert!(MethodAllowlist::all().allows("clearAllNFTs"));
+    }
+}
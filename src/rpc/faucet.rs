@@ -0,0 +1,54 @@
+// `POST /faucet` on the RPC port: {"address": ..., "captcha": optional token}
+use axum::extract::{ConnectInfo, State};
+use axum::http::StatusCode;
+use axum::Json;
+use serde::{Deserialize, Serialize};
+use std::net::SocketAddr;
+
+use super::RpcState;
+use crate::faucet::FaucetError;
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct FaucetRequest {
+    pub address: String,
+    #[serde(default)]
+    pub captcha: Option<String>,
+}
+
+pub async fn handle_faucet_request(
+    State(state): State<RpcState>,
+    ConnectInfo(client): ConnectInfo<SocketAddr>,
+    Json(req): Json<FaucetRequest>,
+) -> (StatusCode, Json<serde_json::Value>) {
+    let Some(faucet) = state.faucet.clone() else {
+        return (StatusCode::NOT_FOUND, Json(serde_json::json!({ "error": "faucet is not enabled on this node" })));
+    };
+    let ip = client.ip().to_string();
+
+    let result = match faucet.verify(&req.address, &ip, req.captcha.as_deref()).await {
+        Ok(()) => match state.chain.lock() {
+            Ok(mut chain) => {
+                let now = crate::block::current_unix_timestamp_ms() / 1000;
+                faucet.dispense(&mut chain, &req.address, &ip, now)
+            }
+            Err(_) => Err(FaucetError::Failed("chain lock poisoned".to_string())),
+        },
+        Err(e) => Err(e),
+    };
+
+    match result {
+        Ok(grant) => (StatusCode::OK, Json(serde_json::json!({ "status": "Sent", "grant": grant }))),
+        Err(e) => {
+            let status = match &e {
+                FaucetError::RateLimited { .. } => StatusCode::TOO_MANY_REQUESTS,
+                FaucetError::Denied(_) => StatusCode::FORBIDDEN,
+                FaucetError::Failed(_) => StatusCode::SERVICE_UNAVAILABLE,
+            };
+            let retry_after = match &e {
+                FaucetError::RateLimited { retry_after_secs } => Some(*retry_after_secs),
+                _ => None,
+            };
+            (status, Json(serde_json::json!({ "error": e.to_string(), "retry_after_secs": retry_after })))
+        }
+    }
+}
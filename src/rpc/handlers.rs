@@ -1,48 +1,172 @@
 use super::types::*;
-use crate::block::{BlockHeader, BlockType};
+use crate::block::{BlockHeader, BlockType, SettlementLeg};
 use crate::chain::Chain;
+use crate::encoding;
 use crate::rpc::RpcState;
-use axum::{debug_handler, extract::State, Json};
+use axum::{debug_handler, extract::{ConnectInfo, State}, Json};
 use std::sync::{Arc, Mutex};
-use sha2::Digest;
 use tracing::{info, debug, warn, error};
-use num_traits::ToPrimitive;
+use crate::layer3::earnings::EarningReason;
 
 
 
 
 
 
-/// Main dispatcher: routes incoming JSON-RPC requests to the correct handler.
+/// Trading endpoints (listings, rentals, paper trading, competitions), served only with the `market` feature
+#[cfg(not(feature = "market"))]
+const MARKET_METHODS: &[&str] = &[
+    "listModelNFT", "buyModelNFT",
+    "listModelForRent", "rentModel", "getRentableModels",
+    "listModel", "buyModel", "cancelListing", "getMarketListings",
+    "getPaperTradingStats", "getPaperTradeHistory", "getPortfolioSummary",
+    "createCompetition", "joinCompetition", "submitCompetitionTrade", "getCompetition", "getCompetitions",
+    "getTradeReceipt", "getOrderBook", "getTicker", "getCandles", "getMarketStats",
+];
+
+/// Most calls accepted in one JSON-RPC batch
+pub const MAX_BATCH_SIZE: usize = 100;
+
+/// HTTP entry point: a single request object or a JSON-RPC 2.0 batch (array of requests).
+/// Batch entries are dispatched in order and answered in one array.
 #[debug_handler]
 pub async fn handle_rpc_request(
     State(state): State<RpcState>,
-    Json(req): Json<RpcRequest>,
-) -> Json<RpcResponse> {
+    ConnectInfo(client): ConnectInfo<std::net::SocketAddr>,
+    Json(body): Json<serde_json::Value>,
+) -> Json<RpcReply> {
+    let ip = client.ip().to_string();
+    let entries = match body {
+        serde_json::Value::Array(entries) => entries,
+        single => {
+            let resp = match parse_request(single) {
+                Ok(req) => dispatch_from(state, req, Some(&ip)).await,
+                Err(err) => error_response(0, err),
+            };
+            return Json(RpcReply::Single(resp));
+        }
+    };
+
+    if entries.is_empty() || entries.len() > MAX_BATCH_SIZE {
+        let err = RpcError {
+            code: -32600,
+            message: format!("Invalid Request: batch must hold 1..={} calls, got {}", MAX_BATCH_SIZE, entries.len()),
+        };
+        return Json(RpcReply::Single(error_response(0, err)));
+    }
+
+    debug!("RPC Batch: {} calls", entries.len());
+    let mut responses = Vec::with_capacity(entries.len());
+    for entry in entries {
+        // A malformed entry only fails itself; echo its id when it has a usable one
+        let id = entry.get("id").and_then(|v| v.as_u64()).unwrap_or(0);
+        responses.push(match parse_request(entry) {
+            Ok(req) => dispatch_from(state.clone(), req, Some(&ip)).await,
+            Err(err) => error_response(id, err),
+        });
+    }
+    Json(RpcReply::Batch(responses))
+}
+
+fn parse_request(value: serde_json::Value) -> Result<RpcRequest, RpcError> {
+    serde_json::from_value(value).map_err(|e| RpcError {
+        code: -32600,
+        message: format!("Invalid Request: {}", e),
+    })
+}
+
+fn error_response(id: u64, err: RpcError) -> RpcResponse {
+    RpcResponse {
+        jsonrpc: "2.0".to_string(),
+        result: None,
+        error: Some(err),
+        id,
+    }
+}
+
+/// Main dispatcher: routes one JSON-RPC request to the correct handler.
+pub async fn dispatch(state: RpcState, req: RpcRequest) -> RpcResponse {
+    dispatch_from(state, req, None).await
+}
+
+/// `dispatch` for a request from the caller at `ip`, which password checks are throttled by
+pub async fn dispatch_from(state: RpcState, req: RpcRequest, ip: Option<&str>) -> RpcResponse {
     debug!("RPC Request: method={}, id={}", req.method, req.id);
 
+    // Submits that spend from a password account need a live session of it
+    if let Err(err) = check_debit_session(&state, &req) {
+        return error_response(req.id, err);
+    }
+
+    // A retried submission with the same client_id gets the first attempt's answer
+    let client_id = match claim_client_id(&state, &req).await {
+        Ok(ClientIdClaim::Claimed(id)) => id,
+        Ok(ClientIdClaim::Replay(val)) => {
+            return RpcResponse { jsonrpc: "2.0".to_string(), result: Some(val), error: None, id: req.id };
+        }
+        Err(err) => return error_response(req.id, err),
+    };
+
     // Dispatch based on method name
     let result = match req.method.as_str() {
+        #[cfg(not(feature = "market"))]
+        m if MARKET_METHODS.contains(&m) => Err(RpcError {
+            code: -32601,
+            message: format!("Method not available: {} (node built without the 'market' feature)", m),
+        }),
         "getBalance" => handle_get_balance(state.chain.clone(), req.params).await,
         "getNonce" => handle_get_nonce(state.chain.clone(), req.params).await,
         "getChainHeight" => handle_get_chain_height(state.chain.clone()).await,
-        "getAccountInfo" => handle_get_account_info(state.chain.clone(), req.params).await,
+        "getAccountInfo" => handle_get_account_info(state.clone(), req.params).await,
+        "resolveName" => handle_resolve_name(state.chain.clone(), req.params).await,
+        "getProof" => handle_get_proof(state.chain.clone(), req.params).await,
         "submitTransaction" => handle_submit_transaction(state.clone(), req.params).await, // Pass STATE
+        "login" => handle_login(state.clone(), req.params, ip).await,
+        "logout" => handle_logout(state.clone(), req.params).await,
+        "revokeSessions" => handle_revoke_sessions(state.clone(), req.params, ip).await,
+        "submitSponsoredTransaction" => handle_submit_sponsored_transaction(state.clone(), req.params).await,
+        "submitAssetFeeTransaction" => handle_submit_asset_fee_transaction(state.clone(), req.params).await,
+        "quoteFee" => handle_quote_fee(state.chain.clone(), req.params).await,
+        "estimateFee" => handle_estimate_fee(state.clone(), req.params).await,
+        "seedFeePool" => handle_seed_fee_pool(state.clone(), req.params).await,
+        "createMultisigAccount" => handle_create_multisig_account(state.chain.clone(), req.params).await,
+        "proposeMultisigTransaction" => handle_propose_multisig(state.chain.clone(), req.params).await,
+        "approveMultisigTransaction" => handle_approve_multisig(state.chain.clone(), req.params).await,
+        "executeMultisigTransaction" => handle_execute_multisig(state.clone(), req.params).await,
+        "getMultisigProposal" => handle_get_multisig_proposal(state.chain.clone(), req.params).await,
+        "submitScheduledTransaction" => handle_submit_scheduled_transaction(state.clone(), req.params).await,
+        "submitSignedTransaction" => handle_submit_signed_transaction(state.clone(), req.params).await,
+        "getScheduledTransactions" => handle_get_scheduled_transactions(state.chain.clone()).await,
+        "setFreezeAuthority" => handle_set_freeze_authority(state.clone(), req.params).await,
+        "submitFreeze" => handle_submit_freeze(state.clone(), req.params).await,
+        "getFreezeStatus" => handle_get_freeze_status(state.chain.clone(), req.params).await,
         "getBlock" => handle_get_block(state.chain.clone(), req.params).await,
         "getLatestBlocks" => handle_get_latest_blocks(state.chain.clone(), req.params).await,
-        "getTransactionStatus" => {
-            handle_get_transaction_status(state.chain.clone(), req.params).await
-        }
+        "getTransactionStatus" => handle_get_transaction_status(state.clone(), req.params).await,
+        "getTransactionReceipt" => handle_get_transaction_receipt(state.chain.clone(), req.params).await,
         "getNodeInfo" => handle_get_node_info(state.chain.clone()).await,
         "getVersion" => handle_get_version().await,
         "submitMint" => handle_submit_mint(state.clone(), req.params).await, // Pass STATE
         "submitBurn" => handle_submit_burn(state.clone(), req.params).await, // Pass STATE
+        "getRedemptions" => handle_get_redemptions(state.clone(), req.params).await,
+        "submitRedemptionPayment" => handle_submit_redemption_payment(state.clone(), req.params).await,
+        "getL2Checkpoint" => handle_get_l2_checkpoint(state.clone(), req.params).await,
+        "restoreL2Checkpoint" => handle_restore_l2_checkpoint(state.clone(), req.params).await,
         "submitCompute" => handle_submit_compute(state.clone(), req.params).await, // New AI Endpoint
         "getPendingComputeJobs" => handle_get_pending_compute_jobs(state.clone(), req.params).await,
         "submitResult" => handle_submit_result(state.clone(), req.params).await,
+        "invokeModel" => handle_invoke_model(state.clone(), req.params).await,
+        "getJobResult" => handle_get_job_result(state.chain.clone(), req.params).await,
         "getPeers" => handle_get_peers(state.clone()).await,
+        "getPeerInfo" => handle_get_peer_info(state.clone()).await,
         "getVaultAddress" => handle_get_vault_address(req.params).await,
         "getValidatorStats" => handle_get_validator_stats(state.chain.clone(), req.params).await,
+        "registerValidator" => handle_register_validator(state.clone(), req.params).await,
+        "getValidatorLiveness" => handle_get_validator_liveness(state.chain.clone(), req.params).await,
+        "getLeaderSchedule" => handle_get_leader_schedule(state.chain.clone(), req.params).await,
+        "getEpochReport" => handle_get_epoch_report(state.chain.clone(), req.params).await,
+        "getFinalizedHeight" => handle_get_finalized_height(state.chain.clone()).await,
+        "getSkippedSlots" => handle_get_skipped_slots(state.chain.clone(), req.params).await,
         "submitOracleVerificationJob" => handle_submit_oracle_verification_job(state.clone(), req.params).await,
         "getPendingOracleJobs" => handle_get_pending_oracle_jobs(state.clone()).await,
         "submitOracleVerificationResult" => handle_submit_oracle_verification_result(state.clone(), req.params).await,
@@ -54,19 +178,50 @@ pub async fn handle_rpc_request(
         "purchasePrediction" => handle_purchase_prediction(state.clone(), req.params).await,
         "purchaseSubscription" => handle_purchase_subscription(state.clone(), req.params).await,
         "getLatestSignal" => handle_get_latest_signal(state.clone(), req.params).await,
+        "getTradeReceipt" => handle_get_trade_receipt(state.chain.clone(), req.params).await,
+        "getOrderBook" => handle_get_order_book(state.clone(), req.params).await,
+        "getTicker" => handle_get_ticker(state.chain.clone(), req.params).await,
+        "getCandles" => handle_get_candles(state.chain.clone(), req.params).await,
+        "getMarketStats" => handle_get_market_stats(state.chain.clone(), req.params).await,
         "listModelNFT" => handle_list_model_nft(state.clone(), req.params).await,
         "buyModelNFT" => handle_buy_model_nft(state.clone(), req.params).await,
         "getAllNFTs" => handle_get_all_nfts(state.clone()).await,
+        "setNFTMetadata" => handle_set_nft_metadata(state.clone(), req.params).await,
+        "getNFTMetadata" => handle_get_nft_metadata(state.clone(), req.params).await,
+        "createCollection" => handle_create_collection(state.clone(), req.params).await,
+        "addToCollection" => handle_add_to_collection(state.clone(), req.params).await,
+        "getCollection" => handle_get_collection(state.clone(), req.params).await,
+        "getCollections" => handle_get_collections(state.clone()).await,
+        "getNFTVerification" => handle_get_nft_verification(state.clone(), req.params).await,
         "getMyModels" => handle_get_my_models(state.clone(), req.params).await,
         
         // Paper Trading
         "getPaperTradingStats" => handle_get_paper_trading_stats(state.clone()).await,
         "getPaperTradeHistory" => handle_get_paper_trade_history(state.clone()).await,
         "getPortfolioSummary" => handle_get_portfolio_summary(state.clone()).await,
+        "createCompetition" => handle_create_competition(state.clone(), req.params).await,
+        "joinCompetition" => handle_join_competition(state.clone(), req.params).await,
+        "submitCompetitionTrade" => handle_submit_competition_trade(state.clone(), req.params).await,
+        "getCompetition" => handle_get_competition(state.clone(), req.params).await,
+        "getCompetitions" => handle_get_competitions(state.clone()).await,
         "getLatestSignal" => handle_get_latest_signal(state.clone(), req.params).await,
         
         "getBlockRange" => handle_get_block_range(state.chain.clone(), req.params).await,
+        "search" => handle_search(state.chain.clone(), req.params).await,
+        "getAccountHistory" => handle_get_account_history(state.chain.clone(), req.params).await,
+        "getAccountActivity" => handle_get_account_activity(state.chain.clone(), req.params).await,
+        "getBlocksByAccount" => handle_get_indexed_blocks(state.chain.clone(), req.params, BlockIndex::Account).await,
+        "getBlocksByProposer" => handle_get_indexed_blocks(state.chain.clone(), req.params, BlockIndex::Proposer).await,
+        "getTransfersByAsset" => handle_get_indexed_blocks(state.chain.clone(), req.params, BlockIndex::Asset).await,
+        "getMintsByVault" => handle_get_indexed_blocks(state.chain.clone(), req.params, BlockIndex::Vault).await,
+        "getBlocksByType" => handle_get_indexed_blocks(state.chain.clone(), req.params, BlockIndex::Type).await,
+        "getNftEvents" => handle_get_nft_events(state.chain.clone(), req.params).await,
         "getOraclePrices" => handle_get_oracle_prices(state.chain.clone()).await,
+        "getAuditLog" => handle_get_audit_log(state.chain.clone(), req.params).await,
+        "getHeaders" => handle_get_headers(state.chain.clone(), req.params).await,
+        "getProofOfReserve" => handle_get_proof_of_reserve(state.chain.clone()).await,
+        "getVaultState" => handle_get_vault_state(state.chain.clone(), req.params).await,
+        "getStateDiff" => handle_get_state_diff(state.chain.clone(), req.params).await,
         "submitNativeVault" => handle_submit_native_vault(state.clone(), req.params).await,
         "getTrainableModels" => handle_get_trainable_models().await.and_then(|v| to_json(&v)),
         // NFT Lending Market
@@ -86,8 +241,25 @@ pub async fn handle_rpc_request(
         "joinPool" => handle_join_pool(state.clone(), req.params).await,
         "getModelPools" => handle_get_model_pools(state.clone()).await,
         "claimDividends" => handle_claim_dividends(state.clone(), req.params).await,
+        "getPoolGovernance" => handle_get_pool_governance(state.clone(), req.params).await,
+        "getEarnings" => handle_get_earnings(state.clone(), req.params).await,
+        "claimEarnings" => handle_claim_earnings(state.clone(), req.params).await,
+        "getStakeInfo" => handle_get_stake_info(state.clone(), req.params).await,
+        "withdrawUnbonded" => handle_withdraw_unbonded(state.clone(), req.params).await,
+        "deployContract" => handle_deploy_contract(state.clone(), req.params).await,
+        "callContract" => handle_call_contract(state.clone(), req.params).await,
+        "queryContract" => handle_query_contract(state.clone(), req.params).await,
+        "getVestingSchedule" => handle_get_vesting_schedule(state.clone(), req.params).await,
+        "getTreasury" => handle_get_treasury(state.clone()).await,
+        "getSupplyInfo" => handle_get_supply_info(state.clone()).await,
+        "proposePoolChange" => handle_propose_pool_change(state.clone(), req.params).await,
+        "votePoolProposal" => handle_vote_pool_proposal(state.clone(), req.params).await,
         // v2.0 Oracle Layer
         "submitOraclePrice" => handle_submit_oracle_price(state.clone(), req.params).await,
+        "getOracleBreakers" => handle_get_oracle_breakers(state.clone()).await,
+        "getPriceRound" => handle_get_price_round(state.clone(), req.params).await,
+        "getPriceAttestation" => handle_get_price_attestation(state.clone(), req.params).await,
+        "resumeOracleFeed" => handle_resume_oracle_feed(state.clone(), req.params).await,
         // v2.0 Phase 4: COMPUTE & Account Balances
         "convertCompute" => handle_convert_compute(state.clone(), req.params).await,
         "getAccountBalances" => handle_get_account_balances(state.clone(), req.params).await,
@@ -97,6 +269,7 @@ pub async fn handle_rpc_request(
         "cancelListing" => handle_cancel_listing(state.clone(), req.params).await,
         "getMarketListings" => handle_get_market_listings(state.clone()).await,
         // v2.0 Phase 7: Model Training
+        #[cfg(feature = "ai")]
         "trainModel" => handle_train_model(state.clone(), Some(req.params)).await,
         _ => Err(RpcError {
             code: -32601,
@@ -104,20 +277,118 @@ pub async fn handle_rpc_request(
         }),
     };
 
+    if let Some(key) = &client_id {
+        finish_client_id(&state, key, &req.method, &result);
+    }
+
     // Build response
     match result {
-        Ok(val) => Json(RpcResponse {
+        Ok(val) => RpcResponse {
             jsonrpc: "2.0".to_string(),
             result: Some(val),
             error: None,
             id: req.id,
-        }),
-        Err(err) => Json(RpcResponse {
-            jsonrpc: "2.0".to_string(),
-            result: None,
-            error: Some(err),
-            id: req.id,
-        }),
+        },
+        Err(err) => error_response(req.id, err),
+    }
+}
+
+/// Longest client_id accepted
+const MAX_CLIENT_ID_LEN: usize = 128;
+
+/// Params naming the account behind a `submit*` call, in the order checked
+const SENDER_PARAMS: &[&str] = &["from", "account", "owner", "owner_id", "submitter", "user", "pubkey", "public_key"];
+
+/// A client_id as claimed: scoped to the account submitting under it
+struct ClientIdKey {
+    sender: String,
+    id: String,
+}
+
+enum ClientIdClaim {
+    /// No earlier attempt; the id (if any) is now held by this request
+    Claimed(Option<ClientIdKey>),
+    /// Answer recorded for an earlier attempt
+    Replay(serde_json::Value),
+}
+
+/// Claim the `client_id` param of a `submit*` call, or look up the earlier attempt that used it
+async fn claim_client_id(state: &RpcState, req: &RpcRequest) -> Result<ClientIdClaim, RpcError> {
+    if !req.method.starts_with("submit") {
+        return Ok(ClientIdClaim::Claimed(None));
+    }
+    let Some(id) = req.params.get("client_id").filter(|v| !v.is_null()) else {
+        return Ok(ClientIdClaim::Claimed(None));
+    };
+    let id = id.as_str().map(str::trim).unwrap_or_default();
+    if id.is_empty() || id.len() > MAX_CLIENT_ID_LEN {
+        return Err(RpcError {
+            code: -32602,
+            message: format!("Invalid params: client_id must be a non-empty string of at most {} bytes", MAX_CLIENT_ID_LEN),
+        });
+    }
+
+    // Scoping by sender keeps one client's ids from answering, or blocking, another's
+    let sender = SENDER_PARAMS
+        .iter()
+        .find_map(|field| req.params.get(*field).and_then(|v| v.as_str()))
+        .unwrap_or_default();
+
+    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+    let earlier = {
+        let chain = safe_lock(&state.chain)?;
+        chain.storage.reserve_client_id(sender, id, &req.method, now).map_err(|e| RpcError {
+            code: -32603,
+            message: format!("Storage error: {}", e),
+        })?
+    };
+    let Some(earlier) = earlier else {
+        return Ok(ClientIdClaim::Claimed(Some(ClientIdKey { sender: sender.to_string(), id: id.to_string() })));
+    };
+
+    if earlier.method != req.method {
+        return Err(RpcError {
+            code: -32602,
+            message: format!("Invalid params: client_id '{}' was already used for {}", id, earlier.method),
+        });
+    }
+    let Some(response) = earlier.response else {
+        return Err(RpcError {
+            code: -32009,
+            message: format!("Submission with client_id '{}' is still in progress", id),
+        });
+    };
+    let mut val: serde_json::Value = serde_json::from_str(&response).map_err(|e| RpcError {
+        code: -32603,
+        message: format!("Stored response unreadable: {}", e),
+    })?;
+    if let Some(obj) = val.as_object_mut() {
+        if let Some(tx_hash) = obj.get("tx_hash").cloned() {
+            if let Ok(status) = handle_get_transaction_status(state.clone(), serde_json::json!({ "tx_hash": tx_hash })).await {
+                obj.insert("tx_status".to_string(), status["status"].clone());
+            }
+        }
+        obj.insert("duplicate".to_string(), serde_json::Value::Bool(true));
+    }
+    Ok(ClientIdClaim::Replay(val))
+}
+
+/// Keep the answer to a claimed submission for its retries, or free the id if it failed
+fn finish_client_id(state: &RpcState, key: &ClientIdKey, method: &str, result: &Result<serde_json::Value, RpcError>) {
+    let Ok(chain) = safe_lock(&state.chain) else { return };
+    let stored = match result {
+        Ok(val) => {
+            let submission = crate::storage::ClientSubmission {
+                method: method.to_string(),
+                response: Some(val.to_string()),
+                submitted_at: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64,
+            };
+            chain.storage.complete_client_id(&key.sender, &key.id, &submission)
+        }
+        Err(_) => chain.storage.release_client_id(&key.sender, &key.id),
+    };
+    if let Err(e) = stored {
+        warn!("Failed to record client_id '{}': {}", key.id, e);
     }
 }
 
@@ -145,12 +416,58 @@ fn to_json<T: serde::Serialize>(value: &T) -> Result<serde_json::Value, RpcError
 
 /// Safely serialize with bincode
 fn safe_serialize<T: serde::Serialize>(value: &T) -> Result<Vec<u8>, RpcError> {
-    bincode::serialize(value).map_err(|e| RpcError {
+    crate::encoding::canonical_bytes(value).map_err(|e| RpcError {
         code: -32603,
         message: format!("Binary serialization failed: {}", e),
     })
 }
 
+/// Admit a transaction into Gulf Stream, surfacing size/quota violations to the caller
+fn admit_transaction(
+    state: &RpcState,
+    tx_hash: Vec<u8>,
+    raw_tx: Vec<u8>,
+    priority_fee: u64,
+) -> Result<(), RpcError> {
+    let mut gs = safe_lock(&state.gulf_stream)?;
+    gs.try_add_transaction(tx_hash, raw_tx, priority_fee).map_err(|e| RpcError {
+        code: -32010,
+        message: format!("Transaction rejected: {}", e),
+    })
+}
+
+/// What a transfer pays above the fee schedule's minimum, which Gulf Stream ranks it by
+fn priority_fee(state: &RpcState, payload: &crate::network::TransactionPayload) -> Result<u64, RpcError> {
+    let Some(block_type) = payload.fee_block_type() else {
+        return Ok(0);
+    };
+    safe_lock(&state.chain)?
+        .fee_schedule
+        .priority_fee(&block_type)
+        .map_err(|message| RpcError { code: -32002, message })
+}
+
+/// Block JSON plus a human-readable `summary` of its type
+fn block_with_summary(block: &crate::block::Block) -> serde_json::Value {
+    let mut value = serde_json::to_value(block).unwrap_or(serde_json::Value::Null);
+    if let Some(obj) = value.as_object_mut() {
+        obj.insert("summary".to_string(), serde_json::json!(block.header.block_type.to_string()));
+    }
+    value
+}
+
+/// Record a privileged action in the audit log. Failures are logged, never surfaced to the caller.
+fn record_audit(
+    storage: &crate::storage::Storage,
+    actor: &str,
+    action: crate::audit::AuditAction,
+    details: String,
+) {
+    if let Err(e) = storage.append_audit_entry(actor, action, details) {
+        error!("Failed to write audit entry: {}", e);
+    }
+}
+
 //
 // === Individual Handlers ===
 //
@@ -163,6 +480,40 @@ async fn handle_get_peers(state: RpcState) -> Result<serde_json::Value, RpcError
     to_json(&GetPeersResponse { peers })
 }
 
+/// Handle getPeerInfo(): every known peer with its reputation, best score first
+async fn handle_get_peer_info(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    let pm = safe_lock(&state.peer_manager)?;
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let mut peers: Vec<_> = pm.records().collect();
+    peers.sort_by_key(|r| std::cmp::Reverse(r.score(now)));
+    let peers: Vec<_> = peers
+        .into_iter()
+        .map(|r| {
+            serde_json::json!({
+                "peer_id": r.peer_id,
+                "connected": pm.peers.contains(&r.peer_id),
+                "score": r.score(now),
+                "banned": r.is_banned(now),
+                "banned_until": r.banned_until,
+                "strikes": r.strikes,
+                "latency_ms": r.latency_ms,
+                "height": r.height,
+                "blocks_served": r.blocks_served,
+                "last_seen": r.last_seen,
+                "addresses": r.addresses,
+            })
+        })
+        .collect();
+    let propagation = serde_json::json!({
+        "samples": pm.propagation.samples(),
+        "p50_ms": pm.propagation.percentile(50),
+        "p95_ms": pm.propagation.percentile(95),
+        "p99_ms": pm.propagation.percentile(99),
+        "announce_fanout": pm.announce_fanout,
+    });
+    Ok(serde_json::json!({ "peers": peers, "propagation": propagation }))
+}
+
 /// Handle getVersion()
 async fn handle_get_version() -> Result<serde_json::Value, RpcError> {
     Ok(serde_json::json!({ "version": "0.1.0" }))
@@ -201,6 +552,8 @@ async fn handle_submit_mint(
             hash: "".to_string(),
             proposer: "Client".to_string(), // In real system, this is Miner/Validator
             signature_hex: tx.signature.clone(),
+            state_root: String::new(),
+            root_attestation: None,
         };
 
         // If from client, they should have signed it. We verify signature here?
@@ -239,21 +592,27 @@ async fn handle_submit_mint(
         let raw = safe_serialize(&payload)?;
         
         // Hash
-        use sha2::Digest;
-        let p_hash = sha2::Sha256::digest(&raw).to_vec();
+        let p_hash = encoding::sha256(&raw).to_vec();
+
+        record_audit(
+            &chain_lock.storage,
+            &tx.owner,
+            crate::audit::AuditAction::OracleSignature,
+            format!(
+                "mint vault={} amount={} {} proof={} oracle_sig={}",
+                tx.vault_id, tx.mint_amount, tx.compass_asset, tx.tx_proof, tx.oracle_signature
+            ),
+        );
         
         (p_hash, raw)
     };
 
     // Add to Gulf Stream
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        gs.add_transaction(tx_hash.clone(), result, tx.fee);
-    }
+    admit_transaction(&state, tx_hash.clone(), result, tx.fee)?;
 
     Ok(serde_json::json!({
         "status": "Submitted to Gulf Stream",
-        "tx_hash": hex::encode(tx_hash)
+        "tx_hash": encoding::to_hex(tx_hash)
     }))
 }
 
@@ -270,25 +629,191 @@ async fn handle_submit_burn(
     // Scope for locking chain (optional here if just constructing payload)
     let payload = crate::network::TransactionPayload::Burn {
         vault_id: tx.vault_id.clone(),
+        collateral_asset: tx.collateral_asset.clone(),
+        compass_asset: tx.compass_asset.clone(),
         amount: tx.burn_amount,
+        redeemer: tx.redeemer.clone(),
         recipient_btc_addr: tx.destination_address.clone(),
         signature: tx.signature.clone(),
+        public_key: tx.public_key.clone(),
         fee: tx.fee,
+        timestamp: tx.timestamp,
+        prev_hash: tx.prev_hash.clone(),
     };
-    let raw = bincode::serialize(&payload).unwrap();
+    let raw = safe_serialize(&payload)?;
     
-    use sha2::Digest;
-    let tx_hash = sha2::Sha256::digest(&raw).to_vec();
+    let tx_hash = encoding::sha256(&raw).to_vec();
 
     // Add to Gulf Stream
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        gs.add_transaction(tx_hash.clone(), raw, tx.fee);
-    }
+    admit_transaction(&state, tx_hash.clone(), raw, tx.fee)?;
 
     Ok(serde_json::json!({
         "status": "Submitted to Gulf Stream",
-        "tx_hash": hex::encode(tx_hash)
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle getRedemptions(status?, redeemer?) - burns and their payouts, each with
+/// a payment request the operator can pay from
+async fn handle_get_redemptions(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetRedemptionsParams = if params.is_null() {
+        GetRedemptionsParams::default()
+    } else {
+        serde_json::from_value(params).map_err(|e| RpcError {
+            code: -32602,
+            message: format!("Invalid params: {}", e),
+        })?
+    };
+    let queued = match p.status.as_deref() {
+        None => None,
+        Some("queued") => Some(true),
+        Some("released") => Some(false),
+        Some(other) => {
+            return Err(RpcError { code: -32602, message: format!("Unknown redemption status: {}", other) });
+        }
+    };
+
+    let chain = safe_lock(&state.chain)?;
+    let redemptions: Vec<serde_json::Value> = chain
+        .storage
+        .get_redemptions()
+        .into_iter()
+        .filter(|i| queued.is_none_or(|q| i.is_queued() == q))
+        .filter(|i| p.redeemer.as_ref().is_none_or(|r| &i.redeemer == r))
+        .map(|i| serde_json::json!({ "payment_uri": i.payment_uri(), "intent": i }))
+        .collect();
+    Ok(serde_json::json!({ "redemptions": redemptions }))
+}
+
+/// Handle submitRedemptionPayment(intent_id, external_tx, submitter) - admin reports
+/// the payout it sent; the vault is released once the payout confirms
+/// Look up checkpoint `sequence`, or the latest one
+fn l2_checkpoint(state: &RpcState, sequence: Option<u64>) -> Result<crate::layer2::checkpoint::L2CheckpointRecord, RpcError> {
+    let chain = safe_lock(&state.chain)?;
+    let checkpoint = match sequence {
+        Some(sequence) => chain.storage.get_l2_checkpoint(sequence)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?,
+        None => chain.storage.get_latest_l2_checkpoint(),
+    };
+    checkpoint.ok_or(RpcError { code: -32001, message: "No such layer 2 checkpoint".to_string() })
+}
+
+/// Handle getL2Checkpoint(sequence?) - a committed Layer 2 checkpoint and whether
+/// this node's Layer 2 state still hashes to it
+async fn handle_get_l2_checkpoint(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetL2CheckpointParams = if params.is_null() {
+        GetL2CheckpointParams::default()
+    } else {
+        serde_json::from_value(params).map_err(|e| RpcError {
+            code: -32602,
+            message: format!("Invalid params: {}", e),
+        })?
+    };
+    let checkpoint = l2_checkpoint(&state, p.sequence)?;
+    let local = safe_lock(&state.layer2)?
+        .commitment()
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .state_hash();
+    Ok(serde_json::json!({
+        "matches_local": local == checkpoint.state_hash,
+        "local_state_hash": local,
+        "checkpoint": checkpoint,
+    }))
+}
+
+/// Handle restoreL2Checkpoint(sequence, submitter) - admin only: put Layer 2 state
+/// back to the snapshot taken for a checkpoint
+async fn handle_restore_l2_checkpoint(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let req: RestoreL2CheckpointParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    if req.submitter != state.node_identity && req.submitter != "admin" {
+        return Err(RpcError {
+            code: -32003,
+            message: "Unauthorized: Only Admin can restore layer 2 checkpoints".to_string(),
+        });
+    }
+
+    let checkpoint = l2_checkpoint(&state, Some(req.sequence))?;
+    safe_lock(&state.layer2)?
+        .rollback_to(&checkpoint)
+        .map_err(|e| RpcError { code: -32004, message: e.to_string() })?;
+    record_audit(
+        &safe_lock(&state.chain)?.storage,
+        &req.submitter,
+        crate::audit::AuditAction::L2Rollback,
+        format!("checkpoint={} height={} state={}", checkpoint.sequence, checkpoint.height, checkpoint.state_hash),
+    );
+    Ok(serde_json::json!({
+        "status": "restored",
+        "sequence": checkpoint.sequence,
+        "state_hash": checkpoint.state_hash,
+    }))
+}
+
+async fn handle_submit_redemption_payment(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let req: SubmitRedemptionPaymentParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    if req.submitter != state.node_identity && req.submitter != "admin" {
+        return Err(RpcError {
+            code: -32003,
+            message: "Unauthorized: Only Admin can report redemption payouts".to_string(),
+        });
+    }
+    if req.external_tx.is_empty() {
+        return Err(RpcError { code: -32602, message: "external_tx is required".to_string() });
+    }
+
+    let chain = safe_lock(&state.chain)?;
+    let mut intent = chain
+        .storage
+        .get_redemption(&req.intent_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: format!("Unknown redemption {}", req.intent_id) })?;
+    if !intent.is_queued() {
+        return Err(RpcError { code: -32004, message: format!("Redemption {} already released", req.intent_id) });
+    }
+    // One payout pays one redemption: refuse a txid already released or reported for another
+    let paid = chain
+        .storage
+        .get_payout_tx_redemption(&req.external_tx)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .or_else(|| {
+            chain
+                .storage
+                .get_redemptions()
+                .into_iter()
+                .find(|other| other.id != intent.id && other.payout_tx.as_ref() == Some(&req.external_tx))
+                .map(|other| other.id)
+        });
+    if let Some(paid) = paid {
+        return Err(RpcError {
+            code: -32004,
+            message: format!("Payout {} is already reported for redemption {}", req.external_tx, paid),
+        });
+    }
+    intent.payout_tx = Some(req.external_tx.clone());
+    chain
+        .storage
+        .save_redemption(&intent)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    record_audit(
+        &chain.storage,
+        &req.submitter,
+        crate::audit::AuditAction::RedemptionPayout,
+        format!(
+            "redemption={} payout={} {} to {} tx={}",
+            intent.id, intent.quote.payout, intent.collateral_asset, intent.destination_address, req.external_tx
+        ),
+    );
+
+    Ok(serde_json::json!({
+        "status": "awaiting_confirmations",
+        "intent_id": intent.id,
     }))
 }
 
@@ -321,7 +846,9 @@ async fn handle_get_block(
 
     let chain = safe_lock(&chain)?;
     if let Ok(Some(block)) = chain.storage.get_block_by_height(p.height) {
-         Ok(serde_json::to_value(block).unwrap())
+         let mut value = serde_json::to_value(block).unwrap();
+         value["finalized"] = serde_json::json!(chain.is_finalized(p.height));
+         Ok(value)
     } else {
         Err(RpcError {
             code: -32602,
@@ -330,152 +857,1414 @@ async fn handle_get_block(
     }
 }
 
-/// Handle getLatestBlocks(count)
-async fn handle_get_latest_blocks(
+/// Handle getTradeReceipt(trade_id): the node-signed settlement receipt for one fill
+async fn handle_get_trade_receipt(
     chain: Arc<Mutex<Chain>>,
     params: serde_json::Value,
 ) -> Result<serde_json::Value, RpcError> {
-    let p: GetLatestBlocksParams = serde_json::from_value(params).map_err(|e| RpcError {
+    let p: GetTradeReceiptParams = serde_json::from_value(params).map_err(|e| RpcError {
         code: -32602,
         message: format!("Invalid params: {}", e),
     })?;
 
     let chain = safe_lock(&chain)?;
-    let height = chain.height;
-    let start = if height > p.count as u64 {
-        height - p.count as u64
-    } else {
-        0
-    };
-
-    let mut blocks = Vec::new();
-    for i in start..=height {
-        if let Ok(Some(b)) = chain.storage.get_block_by_height(i) {
-            blocks.push(b);
-        }
-    }
-    // Reverse to show newest first
-    blocks.reverse();
-
-    Ok(serde_json::to_value(blocks).unwrap())
+    let receipt = chain.storage.get_trade_receipt(&p.trade_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: format!("Trade {} not found", p.trade_id) })?;
+    Ok(serde_json::to_value(receipt).unwrap())
 }
 
-/// Handle getTransactionStatus(tx_hash)
-async fn handle_get_transaction_status(
-    _chain: Arc<Mutex<Chain>>,
-    params: serde_json::Value,
-) -> Result<serde_json::Value, RpcError> {
-    let _p: GetTxStatusParams = serde_json::from_value(params).map_err(|e| RpcError {
+/// Price levels per side `getOrderBook` returns by default, and at most
+const DEFAULT_BOOK_DEPTH: usize = 20;
+const MAX_BOOK_DEPTH: usize = 500;
+
+/// Handle getOrderBook(pair, depth?): resting bids and asks aggregated by price.
+/// Only the leader, which matches orders, holds a book.
+async fn handle_get_order_book(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetOrderBookParams = serde_json::from_value(params).map_err(|e| RpcError {
         code: -32602,
         message: format!("Invalid params: {}", e),
     })?;
+    let depth = p.depth.unwrap_or(DEFAULT_BOOK_DEPTH).min(MAX_BOOK_DEPTH);
 
-    // Check Gulf Stream first? (Not easily accessible here without RpcState refactor, skipping for now)
-    // Check Chain Storage (Confirmed)
-    // We assume Storage has get_transaction_status or index? 
-    // Implementing simple check: "Confirmed" if found in history indices (TODO)
-    
-    Ok(serde_json::json!({ "status": "Unknown (Not indexed)" }))
+    let market = safe_lock(&state.market)?;
+    let (bids, asks) = market.books.get(&p.pair).map(|book| book.depth(depth)).unwrap_or_default();
+    Ok(serde_json::json!({
+        "pair": p.pair,
+        "bids": bids,
+        "asks": asks,
+        "best_bid": bids.first().map(|l| l.price),
+        "best_ask": asks.first().map(|l| l.price),
+    }))
 }
 
-/// Handle getBalance
-async fn handle_get_balance(
-    chain: Arc<Mutex<Chain>>,
-    params: serde_json::Value,
-) -> Result<serde_json::Value, RpcError> {
-    let p: GetBalanceParams = serde_json::from_value(params).map_err(|e| RpcError {
+/// Handle getTicker(pair): last fill price and 24h volume, high and low, from the
+/// trade history index
+async fn handle_get_ticker(chain: Arc<Mutex<Chain>>, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetTickerParams = serde_json::from_value(params).map_err(|e| RpcError {
         code: -32602,
         message: format!("Invalid params: {}", e),
     })?;
 
     let chain = safe_lock(&chain)?;
-    // Use storage to get balance
-    let bal = chain.storage.get_balance(&p.wallet_id, &p.asset).unwrap_or(0);
-    Ok(serde_json::json!({ "balance": bal }))
+    let since = crate::block::current_unix_timestamp_ms().saturating_sub(crate::market::TICKER_WINDOW_MS);
+    let recent = chain.storage.get_pair_trades(&p.pair, since);
+    let last = chain.storage.get_last_pair_trade(&p.pair);
+    to_json(&crate::market::Ticker::new(&p.pair, last.as_ref(), &recent))
 }
 
-/// Handle getNonce
-async fn handle_get_nonce(
-    chain: Arc<Mutex<Chain>>,
-    params: serde_json::Value,
-) -> Result<serde_json::Value, RpcError> {
-    let params: serde_json::Value = params;
-    let wallet_id = params
-        .get("wallet_id")
-        .and_then(|v| v.as_str())
-        .ok_or(RpcError {
+/// Handle getMarketStats(pair?): the maker and taker rates in force and the
+/// volume and fees each pair's settled fills have paid
+async fn handle_get_market_stats(chain: Arc<Mutex<Chain>>, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetMarketStatsParams = if params.is_null() {
+        GetMarketStatsParams::default()
+    } else {
+        serde_json::from_value(params).map_err(|e| RpcError {
             code: -32602,
-            message: "Missing wallet_id".to_string(),
-        })?;
-
-    let chain = safe_lock(&chain)?;
-    let nonce = chain.storage.get_nonce(wallet_id).unwrap_or(0);
-    Ok(serde_json::json!({ "nonce": nonce }))
-}
+            message: format!("Invalid params: {}", e),
+        })?
+    };
 
-/// Handle getChainHeight
-async fn handle_get_chain_height(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
     let chain = safe_lock(&chain)?;
-    Ok(serde_json::json!({ "height": chain.height }))
+    let pairs = match p.pair {
+        Some(pair) => vec![pair],
+        None => chain.storage.get_traded_pairs(),
+    };
+    let stats: Vec<crate::market::MarketStats> = pairs
+        .iter()
+        .map(|pair| crate::market::MarketStats::new(pair, &chain.storage.get_pair_trades(pair, 0)))
+        .collect();
+    Ok(serde_json::json!({
+        "maker_fee_bps": chain.market_fees.maker_fee_bps,
+        "taker_fee_bps": chain.market_fees.taker_fee_bps,
+        "treasury_fee_percent": chain.treasury.fee_percent,
+        "pairs": stats,
+    }))
 }
 
-/// Handle getAccountInfo
-async fn handle_get_account_info(
-    chain: Arc<Mutex<Chain>>,
-    params: serde_json::Value,
-) -> Result<serde_json::Value, RpcError> {
-    let params: serde_json::Value = params;
-    let wallet_id = params
-        .get("wallet_id")
-        .and_then(|v| v.as_str())
-        .ok_or(RpcError {
+/// Handle getCandles(pair, interval, range?): OHLCV candles over the last `range`,
+/// oldest first; intervals without trades are left out
+async fn handle_get_candles(chain: Arc<Mutex<Chain>>, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetCandlesParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let bad_period = |what: &str, value: &str| RpcError {
+        code: -32602,
+        message: format!("Invalid {} '{}': use a count and a unit, e.g. 15m, 4h, 1d, 1w", what, value),
+    };
+    let interval_ms = crate::market::parse_period_ms(&p.interval).ok_or_else(|| bad_period("interval", &p.interval))?;
+    let range = p.range.unwrap_or_else(|| "1d".to_string());
+    let range_ms = crate::market::parse_period_ms(&range).ok_or_else(|| bad_period("range", &range))?;
+    if range_ms / interval_ms > crate::market::MAX_CANDLES {
+        return Err(RpcError {
             code: -32602,
-            message: "Missing wallet_id".to_string(),
-        })?;
+            message: format!("{} of {} candles is more than {}", range, p.interval, crate::market::MAX_CANDLES),
+        });
+    }
 
     let chain = safe_lock(&chain)?;
-    // Return mock info or aggregate
-    let nonce = chain.storage.get_nonce(wallet_id).unwrap_or(0);
-    // TODO: List all balances (Storage needs iteration support)
-    
+    let since = crate::block::current_unix_timestamp_ms().saturating_sub(range_ms);
+    let trades = chain.storage.get_pair_trades(&p.pair, since);
     Ok(serde_json::json!({
-        "wallet_id": wallet_id,
-        "nonce": nonce,
-        "balances": {} // Placeholder
+        "pair": p.pair,
+        "interval": p.interval,
+        "candles": crate::market::candles(&trades, interval_ms),
     }))
 }
 
-/// Handle getNodeInfo
-async fn handle_get_node_info(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
+/// Largest page for the block and NFT event indexes
+const MAX_HISTORY_PAGE: usize = 100;
+
+#[derive(Clone, Copy)]
+enum BlockIndex {
+    Account,
+    Proposer,
+    Asset,
+    Vault,
+    Type,
+}
+
+/// Resolve one page of the `key` entries of an index to canonical blocks; returns the
+/// page size used, the index's total size and the blocks
+fn indexed_blocks(
+    chain: &Chain,
+    index: BlockIndex,
+    key: &str,
+    page: usize,
+    limit: Option<usize>,
+) -> Result<(usize, usize, Vec<crate::block::Block>), RpcError> {
+    let limit = limit.unwrap_or(20).clamp(1, MAX_HISTORY_PAGE);
+    let offset = page.saturating_mul(limit);
+    let (heights, total) = match index {
+        BlockIndex::Account => chain.storage.get_account_block_heights(key, offset, limit),
+        BlockIndex::Proposer => chain.storage.get_proposer_block_heights(key, offset, limit),
+        BlockIndex::Asset => chain.storage.get_asset_transfer_heights(key, offset, limit),
+        BlockIndex::Vault => chain.storage.get_vault_mint_heights(key, offset, limit),
+        BlockIndex::Type => chain.storage.get_type_block_heights(key, offset, limit),
+    };
+    let mut blocks = Vec::with_capacity(heights.len());
+    for height in heights {
+        match chain.storage.get_block_by_height(height) {
+            Ok(Some(block)) => blocks.push(block),
+            Ok(None) => warn!("Block index points at missing height {}", height),
+            Err(e) => return Err(RpcError { code: -32603, message: format!("Storage error: {}", e) }),
+        }
+    }
+    Ok((limit, total, blocks))
+}
+
+/// Handle getAccountHistory(wallet_id, page, limit): summaries of the blocks touching an account
+async fn handle_get_account_history(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let mut p: AccountHistoryParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    p.wallet_id = account_id_param(&p.wallet_id)?;
+    let chain = safe_lock(&chain)?;
+    let (limit, total, blocks) = indexed_blocks(&chain, BlockIndex::Account, &p.wallet_id, p.page, p.limit)?;
+    let entries: Vec<AccountHistoryEntry> = blocks
+        .into_iter()
+        .map(|b| AccountHistoryEntry {
+            height: b.header.index,
+            hash: b.header.hash,
+            timestamp: b.header.timestamp,
+            summary: b.header.block_type.to_string(),
+            block_type: b.header.block_type,
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "wallet_id": p.wallet_id,
+        "page": p.page,
+        "limit": limit,
+        "total": total,
+        "entries": entries,
+    }))
+}
+
+/// Handle getAccountActivity(wallet_id, page, limit): one page of the account history
+/// as bookkeeping rows (`export::ActivityRecord`), newest first. Paging counts
+/// blocks, so a page may hold more or fewer rows than `limit`.
+async fn handle_get_account_activity(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let mut p: AccountHistoryParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    p.wallet_id = account_id_param(&p.wallet_id)?;
+    let chain = safe_lock(&chain)?;
+    let (limit, total, blocks) = indexed_blocks(&chain, BlockIndex::Account, &p.wallet_id, p.page, p.limit)?;
+    let records: Vec<crate::export::ActivityRecord> = blocks
+        .iter()
+        .flat_map(|b| {
+            let h = &b.header;
+            crate::export::ActivityRecord::from_block(&p.wallet_id, h.index, &h.hash, h.timestamp, &h.block_type)
+        })
+        .collect();
+    Ok(serde_json::json!({
+        "wallet_id": p.wallet_id,
+        "page": p.page,
+        "limit": limit,
+        "total": total,
+        "records": records,
+    }))
+}
+
+/// Handle getBlocksByAccount / getBlocksByProposer / getTransfersByAsset / getMintsByVault /
+/// getBlocksByType: full blocks, newest first
+async fn handle_get_indexed_blocks(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+    index: BlockIndex,
+) -> Result<serde_json::Value, RpcError> {
+    let invalid = |e: serde_json::Error| RpcError { code: -32602, message: format!("Invalid params: {}", e) };
+    let (key, page, limit) = match index {
+        BlockIndex::Account | BlockIndex::Proposer => {
+            let p: AccountHistoryParams = serde_json::from_value(params).map_err(invalid)?;
+            (p.wallet_id, p.page, p.limit)
+        }
+        BlockIndex::Asset | BlockIndex::Vault | BlockIndex::Type => {
+            let p: BlockFilterParams = serde_json::from_value(params).map_err(invalid)?;
+            (p.key, p.page, p.limit)
+        }
+    };
+    let chain = safe_lock(&chain)?;
+    let (limit, total, blocks) = indexed_blocks(&chain, index, &key, page, limit)?;
+    Ok(serde_json::json!({
+        "page": page,
+        "limit": limit,
+        "total": total,
+        "blocks": blocks,
+    }))
+}
+
+/// Handle getNftEvents(token_id, page, limit): ownership changes of one NFT, newest first
+async fn handle_get_nft_events(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: NftEventsParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let limit = p.limit.unwrap_or(20).clamp(1, MAX_HISTORY_PAGE);
+    let chain = safe_lock(&chain)?;
+    let (events, total) = chain.storage.get_nft_events(&p.token_id, p.page.saturating_mul(limit), limit);
+    Ok(serde_json::json!({
+        "token_id": p.token_id,
+        "page": p.page,
+        "limit": limit,
+        "total": total,
+        "events": events,
+    }))
+}
+
+/// Most hits `search` returns
+const MAX_SEARCH_RESULTS: usize = 100;
+
+/// Handle search: case-insensitive lookup over blocks (height or hash), accounts,
+/// token names and NFT / collection names and descriptions.
+/// Transactions carry no memo field, so there is no memo text to match.
+async fn handle_search(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: SearchParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let query = p.query.trim().to_string();
+    if query.is_empty() {
+        return Err(RpcError {
+            code: -32602,
+            message: "Invalid params: query is empty".to_string(),
+        });
+    }
+    let limit = p.limit.unwrap_or(20).clamp(1, MAX_SEARCH_RESULTS);
+    let needle = query.to_lowercase();
+    let matches = |text: &str| text.to_lowercase().contains(&needle);
+
+    let chain = safe_lock(&chain)?;
+    let storage = &chain.storage;
+    let mut hits = Vec::new();
+
+    // Exact lookups first: block by height or hash, then account
+    if let Ok(height) = query.parse::<u64>() {
+        if let Ok(Some(block)) = storage.get_block_by_height(height) {
+            hits.push(SearchHit { kind: "block".into(), id: height.to_string(), label: block.header.hash });
+        }
+    }
+    if let Ok(Some(block)) = storage.get_block(&needle) {
+        hits.push(SearchHit { kind: "block".into(), id: block.header.index.to_string(), label: block.header.hash });
+    }
+    let balances = storage.get_balances(&query);
+    if !balances.is_empty() {
+        hits.push(SearchHit { kind: "account".into(), id: query.clone(), label: format!("{} assets", balances.len()) });
+    }
+
+    for asset in storage.get_all_assets() {
+        if matches(&asset) {
+            hits.push(SearchHit { kind: "token".into(), id: asset.clone(), label: asset });
+        }
+    }
+    for nft in storage.get_all_nfts() {
+        if matches(&nft.name) || matches(&nft.description) || matches(&nft.token_id) {
+            hits.push(SearchHit { kind: "nft".into(), id: nft.token_id, label: nft.name });
+        }
+    }
+    for collection in storage.get_all_nft_collections() {
+        if matches(&collection.name) || matches(&collection.description) {
+            hits.push(SearchHit { kind: "collection".into(), id: collection.collection_id, label: collection.name });
+        }
+    }
+
+    let total = hits.len();
+    hits.truncate(limit);
+    Ok(serde_json::json!({ "query": query, "total": total, "results": hits }))
+}
+
+/// Handle getLatestBlocks(count)
+async fn handle_get_latest_blocks(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: GetLatestBlocksParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let height = chain.height;
+    let start = if height > p.count as u64 {
+        height - p.count as u64
+    } else {
+        0
+    };
+
+    let mut blocks = Vec::new();
+    for i in start..=height {
+        if let Ok(Some(b)) = chain.storage.get_block_by_height(i) {
+            blocks.push(b);
+        }
+    }
+    // Reverse to show newest first
+    blocks.reverse();
+
+    Ok(serde_json::json!(blocks.iter().map(block_with_summary).collect::<Vec<_>>()))
+}
+
+/// Handle getTransactionStatus(tx_hash)
+async fn handle_get_transaction_status(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: GetTxStatusParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let hash = encoding::hash_from_hex(p.tx_hash.trim()).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: tx_hash: {}", e),
+    })?;
+    let tx_hash = encoding::hash_to_hex(&hash);
+
+    // Applied (or parked) transactions are in the index
+    let record = {
+        let chain = safe_lock(&state.chain)?;
+        chain.storage.get_tx_record(&tx_hash).map_err(|e| RpcError {
+            code: -32603,
+            message: format!("Storage error: {}", e),
+        })?
+    };
+    if let Some(record) = record {
+        return Ok(serde_json::json!({
+            "tx_hash": tx_hash,
+            "status": record.status,
+            "block_height": record.block_height,
+            "block_hash": record.block_hash,
+            "error": record.error,
+            "updated_at": record.updated_at,
+        }));
+    }
+
+    // Otherwise it may still be queued in Gulf Stream
+    let queued = {
+        let gs = safe_lock(&state.gulf_stream)?;
+        gs.pending_transactions.contains_key(hash.as_slice()) || gs.processing_transactions.contains_key(hash.as_slice())
+    };
+    Ok(serde_json::json!({
+        "tx_hash": tx_hash,
+        "status": if queued { "Pending" } else { "Unknown" },
+    }))
+}
+
+/// Handle getTransactionReceipt(tx_hash): null until the transaction has run
+async fn handle_get_transaction_receipt(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: GetTxStatusParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let hash = encoding::hash_from_hex(p.tx_hash.trim()).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: tx_hash: {}", e),
+    })?;
+    let receipt = safe_lock(&chain)?.storage.get_receipt(&encoding::hash_to_hex(&hash)).map_err(|e| RpcError {
+        code: -32603,
+        message: format!("Storage error: {}", e),
+    })?;
+    to_json(&receipt)
+}
+
+/// Handle getBalance
+async fn handle_get_balance(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let mut p: GetBalanceParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    p.wallet_id = account_id_param(&p.wallet_id)?;
+
+    let chain = safe_lock(&chain)?;
+    // Use storage to get balance
+    let bal = chain.storage.get_balance(&p.wallet_id, &p.asset).unwrap_or(0);
+    let amount = crate::encoding::Amount::new(bal, &p.asset);
+    Ok(serde_json::json!({
+        "balance": bal,
+        "decimals": amount.decimals(),
+        "formatted": amount.to_string(),
+    }))
+}
+
+/// Handle getNonce
+async fn handle_get_nonce(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: serde_json::Value = params;
+    let wallet_id = params
+        .get("wallet_id")
+        .and_then(|v| v.as_str())
+        .ok_or(RpcError {
+            code: -32602,
+            message: "Missing wallet_id".to_string(),
+        })?;
+    let wallet_id = &account_id_param(wallet_id)?;
+
+    let chain = safe_lock(&chain)?;
+    let nonce = chain.storage.get_nonce(wallet_id).unwrap_or(0);
+    Ok(serde_json::json!({ "nonce": nonce }))
+}
+
+/// Handle getChainHeight
+async fn handle_get_chain_height(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
+    let chain = safe_lock(&chain)?;
+    Ok(serde_json::json!({ "height": chain.height }))
+}
+
+/// Handle getAccountInfo(wallet_id) - every balance, nonce, stakes and the account's
+/// transactions still waiting in Gulf Stream
+async fn handle_get_account_info(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: serde_json::Value = params;
+    let wallet_id = params
+        .get("wallet_id")
+        .and_then(|v| v.as_str())
+        .ok_or(RpcError {
+            code: -32602,
+            message: "Missing wallet_id".to_string(),
+        })?;
+    let wallet_id = &account_id_param(wallet_id)?;
+
+    let (nonce, balances, pools, collateral) = {
+        let chain = safe_lock(&state.chain)?;
+        let nonce = chain.storage.get_nonce(wallet_id).unwrap_or(0);
+        let balances: std::collections::BTreeMap<String, u64> = chain.storage.get_balances(wallet_id).into_iter().collect();
+        let pools: std::collections::BTreeMap<String, u64> = chain.storage.get_all_model_pools()
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .into_iter()
+            .filter_map(|pool| pool.contributors.get(wallet_id).map(|stake| (pool.pool_id.clone(), *stake)))
+            .collect();
+        let collateral = chain.storage.get_collateral_stakes()
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .get(wallet_id)
+            .copied()
+            .unwrap_or(0);
+        (nonce, balances, pools, collateral)
+    };
+
+    let mut pending: Vec<_> = {
+        let gs = safe_lock(&state.gulf_stream)?;
+        gs.pending_transactions
+            .values()
+            .chain(gs.processing_transactions.values())
+            .filter(|tx| tx.sender.as_deref() == Some(wallet_id))
+            .map(|tx| (tx.nonce, tx.timestamp_ms, encoding::to_hex(&tx.tx_hash), tx.priority_fee, format!("{:?}", tx.status)))
+            .collect()
+    };
+    pending.sort();
+    let pending: Vec<_> = pending
+        .into_iter()
+        .map(|(nonce, timestamp_ms, tx_hash, priority_fee, status)| serde_json::json!({
+            "tx_hash": tx_hash,
+            "nonce": nonce,
+            "priority_fee": priority_fee,
+            "status": status,
+            "received_at_ms": timestamp_ms,
+        }))
+        .collect();
+
+    Ok(serde_json::json!({
+        "wallet_id": wallet_id,
+        "nonce": nonce,
+        "balances": balances,
+        "staked": {
+            "pools": pools,
+            "collateral": collateral,
+        },
+        "pending_transactions": pending
+    }))
+}
+
+/// Handle resolveName(name) - look up a registered account by name, `cmps1` address
+/// or hex public key, mapping between the three
+async fn handle_resolve_name(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ResolveNameParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let id = account_id_param(&req.name)?;
+
+    let chain = safe_lock(&chain)?;
+    let store = safe_lock(&chain.account_store)?;
+    if encoding::decode_address(&id).is_ok() {
+        // A key: find the account it signs for, if any
+        let name = store.name_for_pubkey(&id).map(str::to_string).or_else(|| {
+            chain.storage.get_all_accounts().into_iter().find(|a| a.signing_pubkey == id).map(|a| a.name)
+        });
+        return Ok(serde_json::json!({
+            "name": name,
+            "registered": name.is_some(),
+            "signing_pubkey": id,
+            "address": encoding::account_bech32_address(&id),
+        }));
+    }
+
+    let account = match chain.storage.get_account(&id) {
+        Ok(Some(account)) => Some(account),
+        _ => store.get(&id).cloned(),
+    };
+    Ok(match account {
+        Some(account) => serde_json::json!({
+            "name": id,
+            "registered": true,
+            "address": encoding::account_bech32_address(&account.signing_pubkey),
+            "signing_pubkey": account.signing_pubkey,
+            "multisig": account.is_multisig(),
+        }),
+        None => serde_json::json!({ "name": id, "registered": false }),
+    })
+}
+
+/// Signed fields carry the account id itself, so an address can't be mapped to
+/// its key here without breaking the signature: clients convert before signing
+fn signed_account_field(field: &str, value: &str) -> Result<(), RpcError> {
+    if !encoding::is_bech32_address(value) {
+        return Ok(());
+    }
+    let message = match encoding::decode_bech32_address(value) {
+        Ok(key) => format!("Invalid params: {}: sign over the hex key {}, not the cmps1 address", field, encoding::encode_address(&key)),
+        Err(e) => format!("Invalid params: {}: {}", field, e),
+    };
+    Err(RpcError { code: -32602, message })
+}
+
+/// Account id from RPC input: a `cmps1` address (checksum-validated) becomes its
+/// hex key, hex keys are lowercased, names pass through
+fn account_id_param(input: &str) -> Result<String, RpcError> {
+    encoding::parse_account_id(input).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })
+}
+
+/// Handle getProof(wallet_id, asset): a balance with its Merkle proof against the
+/// current state root, for light clients holding only headers
+async fn handle_get_proof(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let mut req: GetProofParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    req.wallet_id = account_id_param(&req.wallet_id)?;
+
+    let chain = safe_lock(&chain)?;
+    let proof = chain
+        .balance_proof(&req.wallet_id, &req.asset)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or_else(|| RpcError {
+            code: -32001,
+            message: format!("No {} balance for {} to prove", req.asset, req.wallet_id),
+        })?;
+    Ok(serde_json::json!({
+        "wallet_id": req.wallet_id,
+        "asset": req.asset,
+        "balance": proof.balance(),
+        "height": chain.height.saturating_sub(1),
+        "block_hash": chain.head_hash(),
+        "proof": proof,
+    }))
+}
+
+/// Handle getNodeInfo
+async fn handle_get_node_info(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
+    let chain = safe_lock(&chain)?;
+    Ok(serde_json::to_value(NodeInfo {
+        height: chain.height,
+        head_hash: chain.head_hash(),
+        version: "0.1.0".to_string(),
+        peer_count: 0, // Need PeerManager access
+    })
+    .unwrap())
+}
+
+/// Handle submitTransaction(from, to, asset, amount, nonce, fee?, signature, public_key, prev_hash?, timestamp?)
+async fn handle_submit_transaction(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: SubmitTransactionParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    if req.nonce == 0 {
+        return Err(RpcError {
+            code: -32602,
+            message: "Invalid params: nonces start at 1".to_string(),
+        });
+    }
+    signed_account_field("from", &req.from)?;
+    signed_account_field("to", &req.to)?;
+
+    let payload = crate::network::TransactionPayload::Transfer {
+        from: req.from,
+        to: req.to,
+        asset: req.asset,
+        amount: req.amount,
+        nonce: req.nonce,
+        fee: req.fee,
+        signature: req.signature,
+        public_key: req.public_key,
+        timestamp: req.timestamp.unwrap_or_default(),
+        prev_hash: req.prev_hash.unwrap_or_default(),
+    };
+    let priority = priority_fee(&state, &payload)?;
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+
+    // Push to Gulf Stream (checks the nonce against the account's next one)
+    admit_transaction(&state, tx_hash.clone(), raw_tx, priority)?;
+    
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Argon2 password hash of `account`, if it is a password account
+fn password_hash_of(state: &RpcState, account: &str) -> Result<Option<String>, RpcError> {
+    let chain = safe_lock(&state.chain)?;
+    let store = safe_lock(&chain.account_store)?;
+    Ok(store.get(account).map(|a| a.password_hash.clone()).filter(|hash| !hash.is_empty()))
+}
+
+/// The account a `submit*` call spends from, for the calls that take it from a
+/// password account: transfers name it in `from`, signed envelopes in the payload
+fn debited_account(req: &RpcRequest) -> Option<String> {
+    match req.method.as_str() {
+        "submitTransaction" | "submitSponsoredTransaction" | "submitAssetFeeTransaction" => {
+            req.params.get("from").and_then(|v| v.as_str()).map(str::to_string)
+        }
+        "submitSignedTransaction" | "submitScheduledTransaction" => {
+            serde_json::from_value::<crate::network::SignedTransaction>(req.params.clone()).ok().map(|tx| tx.account())
+        }
+        _ => None,
+    }
+}
+
+/// Refuse a submit debiting a password account unless its `session_token` param
+/// is a live session of that account
+fn check_debit_session(state: &RpcState, req: &RpcRequest) -> Result<(), RpcError> {
+    let Some(account) = debited_account(req) else { return Ok(()) };
+    if password_hash_of(state, &account)?.is_none() {
+        return Ok(());
+    }
+    let token = req.params.get("session_token").and_then(|v| v.as_str());
+    state
+        .sessions
+        .check(token, &account, crate::block::current_unix_timestamp_ms())
+        .map_err(|e| RpcError { code: -32003, message: e.to_string() })
+}
+
+/// Check `account`'s password off the async runtime (argon2 is deliberately slow).
+/// Checks are throttled per account and per caller address.
+async fn verify_account_password(state: &RpcState, account: &str, password: String, ip: Option<&str>) -> Result<(), RpcError> {
+    state
+        .sessions
+        .throttle(account, ip, crate::block::current_unix_timestamp_ms())
+        .map_err(|e| RpcError { code: -32003, message: e.to_string() })?;
+    let refused = || RpcError { code: -32003, message: "Invalid account or password".to_string() };
+    let hash = password_hash_of(state, account)?.ok_or_else(refused)?;
+    tokio::task::spawn_blocking(move || crate::account::auth::verify_password(&password, &hash))
+        .await
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .map_err(|_| refused())
+}
+
+/// Handle login(account, password) - starts a session whose token every
+/// submit spending from the account needs
+async fn handle_login(
+    state: RpcState,
+    params: serde_json::Value,
+    ip: Option<&str>,
+) -> Result<serde_json::Value, RpcError> {
+    let req: LoginParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    verify_account_password(&state, &req.account, req.password, ip).await?;
+    let (token, expires_at) = state.sessions.issue(&req.account, crate::block::current_unix_timestamp_ms());
+    Ok(serde_json::json!({
+        "account": req.account,
+        "token": token,
+        "expires_at": expires_at
+    }))
+}
+
+/// Handle logout(token) - ends one session
+async fn handle_logout(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: LogoutParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    Ok(serde_json::json!({ "revoked": state.sessions.revoke(&req.token) }))
+}
+
+/// Handle revokeSessions(account, password) - ends every session of the account,
+/// e.g. after a token leaked
+async fn handle_revoke_sessions(
+    state: RpcState,
+    params: serde_json::Value,
+    ip: Option<&str>,
+) -> Result<serde_json::Value, RpcError> {
+    let req: LoginParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    verify_account_password(&state, &req.account, req.password, ip).await?;
+    Ok(serde_json::json!({ "revoked": state.sessions.revoke_account(&req.account) }))
+}
+
+/// Handle submitSponsoredTransaction(from, to, asset, amount, nonce, fee, signature, public_key, fee_payer, ...)
+async fn handle_submit_sponsored_transaction(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: SubmitSponsoredTransferParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let payload = crate::network::TransactionPayload::SponsoredTransfer {
+        from: req.from,
+        to: req.to,
+        asset: req.asset,
+        amount: req.amount,
+        nonce: req.nonce,
+        fee: req.fee,
+        signature: req.signature,
+        public_key: req.public_key,
+        fee_payer: req.fee_payer,
+        fee_payer_public_key: req.fee_payer_public_key,
+        fee_payer_signature: req.fee_payer_signature,
+        timestamp: req.timestamp,
+        prev_hash: req.prev_hash,
+    };
+    // Reject bad sponsorships at the door; balances are checked at execution
+    if !payload.verify() {
+        return Err(RpcError {
+            code: -32003,
+            message: "Invalid sender or fee payer signature".to_string(),
+        });
+    }
+
+    let priority = priority_fee(&state, &payload)?;
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, priority)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle submitAssetFeeTransaction(from, to, asset, amount, nonce, fee, fee_asset, max_fee_in, signature, ...)
+async fn handle_submit_asset_fee_transaction(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: SubmitAssetFeeTransferParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    // Price it now so a missing pool or rate fails here rather than at execution
+    let fee_in = safe_lock(&state.chain)?
+        .quote_asset_fee(&req.fee_asset, req.fee)
+        .map_err(|e| RpcError { code: -32602, message: e.to_string() })?;
+    if fee_in > req.max_fee_in {
+        return Err(RpcError {
+            code: -32002,
+            message: format!("Fee costs {} {}, above max_fee_in {}", fee_in, req.fee_asset, req.max_fee_in),
+        });
+    }
+
+    let payload = crate::network::TransactionPayload::AssetFeeTransfer {
+        from: req.from,
+        to: req.to,
+        asset: req.asset,
+        amount: req.amount,
+        nonce: req.nonce,
+        fee: req.fee,
+        fee_asset: req.fee_asset,
+        max_fee_in: req.max_fee_in,
+        signature: req.signature,
+        public_key: req.public_key,
+        timestamp: req.timestamp,
+        prev_hash: req.prev_hash,
+    };
+    let priority = priority_fee(&state, &payload)?;
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, priority)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash),
+        "fee_in": fee_in
+    }))
+}
+
+/// Handle quoteFee(fee_asset, fee) - what a Compass fee costs in another asset
+async fn handle_quote_fee(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: QuoteFeeParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let rate = chain
+        .fee_conversion_rate(&req.fee_asset)
+        .map_err(|e| RpcError { code: -32001, message: e.to_string() })?;
+    let fee_in = crate::amm::fee_in_asset(req.fee, rate).map_err(|e| RpcError { code: -32602, message: e })?;
+    let pool = chain
+        .storage
+        .get_amm_pool(&req.fee_asset)
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?;
+    let pool_out = pool.as_ref().map(|p| p.quote_compass_out(fee_in)).unwrap_or(0);
+
+    Ok(serde_json::json!({
+        "fee_asset": req.fee_asset,
+        "fee": req.fee,
+        "rate": rate.to_string(),
+        "surcharge_bps": crate::amm::FEE_SURCHARGE_BPS,
+        "fee_in": fee_in,
+        "pool": pool,
+        "payable": pool_out >= req.fee
+    }))
+}
+
+/// Handle estimateFee(block_type) - the schedule's minimum fee for a block, plus the
+/// median priority fee currently pending in Gulf Stream
+async fn handle_estimate_fee(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: EstimateFeeParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let breakdown = safe_lock(&state.chain)?.fee_schedule.breakdown(&req.block_type);
+    let priority_fee = safe_lock(&state.gulf_stream)?.median_priority_fee();
+    Ok(serde_json::json!({
+        "base_fee": breakdown.base_fee,
+        "size_bytes": breakdown.size_bytes,
+        "byte_fee": breakdown.byte_fee,
+        "min_fee": breakdown.min_fee,
+        "priority_fee": priority_fee,
+        "suggested_fee": breakdown.min_fee.saturating_add(priority_fee)
+    }))
+}
+
+/// Handle seedFeePool(asset, asset_amount, compass_amount, submitter) - admin funds a fee pool from the foundation
+async fn handle_seed_fee_pool(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: SeedFeePoolParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    if req.submitter != state.node_identity && req.submitter != "admin" {
+        return Err(RpcError {
+            code: -32003,
+            message: "Unauthorized: Only Admin can seed fee pools".to_string(),
+        });
+    }
+
+    let mut chain = safe_lock(&state.chain)?;
+    let pool = chain
+        .seed_fee_pool(&req.asset, req.asset_amount, req.compass_amount)
+        .map_err(|e| RpcError { code: -32002, message: e.to_string() })?;
+    record_audit(
+        &chain.storage,
+        &req.submitter,
+        crate::audit::AuditAction::FeePoolSeeded,
+        format!("asset={} asset_amount={} compass_amount={}", req.asset, req.asset_amount, req.compass_amount),
+    );
+
+    Ok(serde_json::json!({
+        "status": "ok",
+        "pool": pool
+    }))
+}
+
+/// Handle createMultisigAccount(name, threshold, signers)
+async fn handle_create_multisig_account(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: CreateMultisigAccountParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let mut chain = safe_lock(&chain)?;
+    let account = chain
+        .create_multisig_account(&req.name, req.threshold, req.signers)
+        .map_err(|e| RpcError { code: -32602, message: e.to_string() })?;
+
+    Ok(serde_json::json!({
+        "status": "ok",
+        "name": account.name,
+        "account_type": account.account_type
+    }))
+}
+
+/// Proposal plus its approval progress
+fn multisig_proposal_json(chain: &Chain, proposal: &crate::account::MultisigProposal) -> serde_json::Value {
+    let auth = chain.storage.get_account(&proposal.account).ok().flatten().map(|a| a.authorization());
+    let threshold = auth.as_ref().map(|a| a.threshold()).unwrap_or(0);
+    let approved = auth
+        .and_then(|a| a.verify_threshold(proposal.message().as_bytes(), &proposal.approvals).ok())
+        .is_some();
+    let executed = chain.storage.get_nonce(&proposal.account).unwrap_or(0) >= proposal.nonce;
+    serde_json::json!({
+        "proposal": proposal,
+        "threshold": threshold,
+        "approvals": proposal.approvals.len(),
+        "ready": approved && !executed,
+        "executed": executed
+    })
+}
+
+/// Look up a proposal, mapping absence to -32001
+fn load_multisig_proposal(chain: &Chain, proposal_id: &str) -> Result<crate::account::MultisigProposal, RpcError> {
+    chain
+        .storage
+        .get_multisig_proposal(proposal_id)
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?
+        .ok_or_else(|| RpcError { code: -32001, message: format!("Proposal {} not found", proposal_id) })
+}
+
+/// Handle proposeMultisigTransaction(account, to, asset, amount, nonce?, proposer, signature)
+async fn handle_propose_multisig(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ProposeMultisigParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let account = chain
+        .storage
+        .get_account(&req.account)
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?
+        .filter(|a| a.is_multisig())
+        .ok_or_else(|| RpcError { code: -32001, message: format!("No multisig account {}", req.account) })?;
+    if !account.authorization().can_sign(&req.proposer) {
+        return Err(RpcError { code: -32003, message: "Proposer is not a signer of this account".to_string() });
+    }
+
+    let next_nonce = chain.storage.get_nonce(&req.account).unwrap_or(0) + 1;
+    let nonce = req.nonce.unwrap_or(next_nonce);
+    if nonce < next_nonce {
+        return Err(RpcError { code: -32602, message: format!("Nonce {} already used", nonce) });
+    }
+    let now = crate::block::current_unix_timestamp_ms();
+    let mut proposal = crate::account::MultisigProposal::new(
+        &req.account, &req.to, &req.asset, req.amount, nonce, &req.proposer, now,
+    );
+    if chain.storage.get_multisig_proposal(&proposal.proposal_id).ok().flatten().is_some() {
+        return Err(RpcError { code: -32602, message: format!("Already proposed as {}", proposal.proposal_id) });
+    }
+    proposal
+        .approve(&req.proposer, &req.signature)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+    chain
+        .storage
+        .save_multisig_proposal(&proposal)
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?;
+
+    Ok(multisig_proposal_json(&chain, &proposal))
+}
+
+/// Handle approveMultisigTransaction(proposal_id, signer, signature)
+async fn handle_approve_multisig(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ApproveMultisigParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let mut proposal = load_multisig_proposal(&chain, &req.proposal_id)?;
+    let can_sign = chain
+        .storage
+        .get_account(&proposal.account)
+        .ok()
+        .flatten()
+        .is_some_and(|a| a.authorization().can_sign(&req.signer));
+    if !can_sign {
+        return Err(RpcError { code: -32003, message: "Not a signer of this account".to_string() });
+    }
+    proposal
+        .approve(&req.signer, &req.signature)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+    chain
+        .storage
+        .save_multisig_proposal(&proposal)
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?;
+
+    Ok(multisig_proposal_json(&chain, &proposal))
+}
+
+/// Handle executeMultisigTransaction(proposal_id) - submit once the threshold is met
+async fn handle_execute_multisig(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: MultisigProposalParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let proposal = {
+        let chain = safe_lock(&state.chain)?;
+        let proposal = load_multisig_proposal(&chain, &req.proposal_id)?;
+        let auth = chain
+            .storage
+            .get_account(&proposal.account)
+            .ok()
+            .flatten()
+            .map(|a| a.authorization())
+            .ok_or_else(|| RpcError { code: -32001, message: format!("No multisig account {}", proposal.account) })?;
+        auth.verify_threshold(proposal.message().as_bytes(), &proposal.approvals)
+            .map_err(|e| RpcError { code: -32003, message: format!("Not enough approvals: {}", e) })?;
+        proposal
+    };
+
+    let payload = crate::network::TransactionPayload::MultisigTransfer {
+        from: proposal.account,
+        to: proposal.to,
+        asset: proposal.asset,
+        amount: proposal.amount,
+        nonce: proposal.nonce,
+        approvals: proposal.approvals,
+    };
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "proposal_id": req.proposal_id,
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle getMultisigProposal(proposal_id)
+async fn handle_get_multisig_proposal(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: MultisigProposalParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let proposal = load_multisig_proposal(&chain, &req.proposal_id)?;
+    Ok(multisig_proposal_json(&chain, &proposal))
+}
+
+/// Handle submitSignedTransaction(payload, pubkey, signature, nonce, chain_id)
+async fn handle_submit_signed_transaction(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let tx: crate::network::SignedTransaction = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    // Admission verifies too, but a specific reason beats "invalid signature"
+    {
+        let chain = safe_lock(&state.chain)?;
+        let chain_id = chain.storage.get_chain_id().unwrap_or(None);
+        tx.verify(chain_id.as_deref(), Some(&chain.storage)).map_err(|e| RpcError { code: -32003, message: e })?;
+    }
+
+    let priority = priority_fee(&state, &tx.payload)?;
+    let raw_tx = safe_serialize(&tx)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, priority)?;
+
+    let msg = crate::network::NetMessage::SubmitSignedTx(tx);
+    let _ = state.cmd_tx.send(crate::network::NetworkCommand::Broadcast(msg)).await;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// How far ahead a transaction may be scheduled (~1 year of 1s slots)
+const MAX_SCHEDULE_AHEAD_SLOTS: u64 = 31_536_000;
+
+/// Handle submitScheduledTransaction(signed transaction) - the envelope must carry a
+/// `Scheduled` payload and be signed by the scheduled transaction's account
+async fn handle_submit_scheduled_transaction(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let tx: crate::network::SignedTransaction = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let crate::network::TransactionPayload::Scheduled { execute_at_slot, fee, .. } = &tx.payload else {
+        return Err(RpcError {
+            code: -32602,
+            message: "Expected a Scheduled payload".to_string(),
+        });
+    };
+    let execute_at_slot = *execute_at_slot;
+
+    let height = safe_lock(&state.chain)?.height;
+    if execute_at_slot > height.saturating_add(MAX_SCHEDULE_AHEAD_SLOTS) {
+        return Err(RpcError {
+            code: -32602,
+            message: format!("execute_at_slot may be at most {} slots ahead", MAX_SCHEDULE_AHEAD_SLOTS),
+        });
+    }
+    if *fee < crate::chain::MIN_SCHEDULE_FEE {
+        return Err(RpcError {
+            code: -32002,
+            message: format!("fee {} is below the minimum of {} to schedule", fee, crate::chain::MIN_SCHEDULE_FEE),
+        });
+    }
+    // Checks the envelope and the scheduled transaction's own signature
+    {
+        let chain = safe_lock(&state.chain)?;
+        let chain_id = chain.storage.get_chain_id().unwrap_or(None);
+        tx.verify(chain_id.as_deref(), Some(&chain.storage)).map_err(|e| RpcError { code: -32003, message: e })?;
+    }
+
+    let raw_tx = safe_serialize(&tx)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    let msg = crate::network::NetMessage::SubmitSignedTx(tx);
+    let _ = state.cmd_tx.send(crate::network::NetworkCommand::Broadcast(msg)).await;
+
+    Ok(serde_json::json!({
+        "status": "Scheduled",
+        "tx_hash": encoding::to_hex(tx_hash),
+        "execute_at_slot": execute_at_slot,
+        "current_slot": height
+    }))
+}
+
+/// Handle getScheduledTransactions()
+async fn handle_get_scheduled_transactions(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
     let chain = safe_lock(&chain)?;
-    Ok(serde_json::to_value(NodeInfo {
-        height: chain.height,
-        head_hash: chain.head_hash(),
-        version: "0.1.0".to_string(),
-        peer_count: 0, // Need PeerManager access
-    })
-    .unwrap())
+    let txs: Vec<serde_json::Value> = chain.storage.get_scheduled_txs()
+        .into_iter()
+        .map(|(slot, tx_hash, payload)| serde_json::json!({
+            "execute_at_slot": slot,
+            "tx_hash": tx_hash,
+            "account": payload.get_account_id(),
+            "payload": payload,
+        }))
+        .collect();
+    Ok(serde_json::json!({
+        "current_slot": chain.height,
+        "scheduled": txs
+    }))
 }
 
-/// Handle submitTransaction
-async fn handle_submit_transaction(
+/// Handle setFreezeAuthority(compass_asset, authority, submitter, timestamp, signature) -
+/// the node operator opts an asset into issuer controls with a signed on-chain block
+async fn handle_set_freeze_authority(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: SetFreezeAuthorityParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    if state.node_identity.is_empty() || req.submitter != state.node_identity {
+        return Err(RpcError {
+            code: -32003,
+            message: "Unauthorized: Only the node operator can assign freeze authorities".to_string(),
+        });
+    }
+    if crate::block::current_unix_timestamp_ms().abs_diff(req.timestamp) > SIGNED_REQUEST_MAX_SKEW_MS {
+        return Err(RpcError { code: -32602, message: "Request timestamp is stale or in the future".to_string() });
+    }
+    let message = crate::vault::compliance::freeze_authority_message(&req.compass_asset, &req.authority, req.timestamp);
+    if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), &req.signature, &req.submitter) {
+        return Err(RpcError { code: -32003, message: "Invalid submitter signature".to_string() });
+    }
+
+    let mut chain = safe_lock(&state.chain)?;
+    if !chain.vault_manager.vaults.contains_key(&req.compass_asset) {
+        return Err(RpcError { code: -32001, message: format!("No vault for {}", req.compass_asset) });
+    }
+    chain
+        .set_freeze_authority(&req.compass_asset, &req.authority)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to assign freeze authority: {}", e) })?;
+    record_audit(
+        &chain.storage,
+        &req.submitter,
+        crate::audit::AuditAction::FreezeAuthoritySet,
+        format!("asset={} authority={}", req.compass_asset, req.authority),
+    );
+
+    Ok(serde_json::json!({
+        "status": "ok",
+        "compass_asset": req.compass_asset,
+        "authority": req.authority
+    }))
+}
+
+/// Handle submitFreeze(compass_asset, account, frozen, reason, authority, seq, signature)
+async fn handle_submit_freeze(
     state: RpcState,
     params: serde_json::Value,
 ) -> Result<serde_json::Value, RpcError> {
-    // NOTE: Simplified transaction handling - proper implementation needed
-    let raw_tx = bincode::serialize(&params).unwrap_or_default();
-    let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
+    let req: SubmitFreezeParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
 
-    // Push to Gulf Stream
     {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        // Check duplication?
-        gs.add_transaction(tx_hash.clone(), raw_tx, 0); // fee=0 for now
+        let chain = safe_lock(&state.chain)?;
+        let authority = chain.storage.get_freeze_authority(&req.compass_asset)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        if authority.as_deref() != Some(req.authority.as_str()) {
+            return Err(RpcError {
+                code: -32003,
+                message: format!("{} is not the freeze authority for {}", req.authority, req.compass_asset),
+            });
+        }
     }
-    
+
+    let payload = crate::network::TransactionPayload::SetFrozen {
+        compass_asset: req.compass_asset,
+        account: req.account,
+        frozen: req.frozen,
+        reason: req.reason,
+        authority: req.authority,
+        seq: req.seq,
+        signature: req.signature,
+    };
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid freeze authority signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle getFreezeStatus(compass_asset, account?)
+async fn handle_get_freeze_status(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetFreezeStatusParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let authority = chain.storage.get_freeze_authority(&req.compass_asset)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    let mut history = chain.storage.get_freeze_history(&req.compass_asset);
+    if let Some(account) = &req.account {
+        history.retain(|r| &r.account == account);
+    }
+
+    Ok(serde_json::json!({
+        "compass_asset": req.compass_asset,
+        "freeze_authority": authority,
+        "next_seq": chain.storage.get_freeze_seq(&req.compass_asset),
+        "frozen": req.account.as_ref().map(|a| chain.storage.is_frozen(&req.compass_asset, a)),
+        "frozen_accounts": chain.storage.get_frozen_accounts(&req.compass_asset),
+        "history": history,
+    }))
+}
+
+/// Handle registerValidator(validator_id, pubkey, stake_amount, signature)
+async fn handle_register_validator(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: RegisterValidatorParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    // Stake is checked again when the registration block is applied
+    {
+        let chain = safe_lock(&state.chain)?;
+        let balance = chain.storage.get_balance(&req.validator_id, "Compass").unwrap_or(0);
+        if balance < req.stake_amount {
+            return Err(RpcError {
+                code: -32010,
+                message: format!("Insufficient Compass balance for stake. Has {}, needs {}", balance, req.stake_amount),
+            });
+        }
+    }
+
+    let payload = crate::network::TransactionPayload::RegisterValidator(req);
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid validator key signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
     Ok(serde_json::json!({
         "status": "Submitted",
-        "tx_hash": hex::encode(tx_hash)
+        "tx_hash": encoding::to_hex(tx_hash)
     }))
 }
 
@@ -498,6 +2287,141 @@ async fn handle_get_validator_stats(
     Ok(serde_json::to_value(stats).unwrap())
 }
 
+/// Handle getValidatorLiveness(validator?) - one validator, or all, worst score first
+async fn handle_get_validator_liveness(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: GetValidatorLivenessParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let mut records = match params.validator {
+        Some(v) => vec![chain.storage.get_leader_liveness(&v).map_err(|e| RpcError {
+            code: -32603,
+            message: e.to_string(),
+        })?],
+        None => chain.storage.get_all_leader_liveness(),
+    };
+    records.sort_by(|a, b| a.score().total_cmp(&b.score()));
+
+    let validators: Vec<serde_json::Value> = records
+        .iter()
+        .map(|l| {
+            serde_json::json!({
+                "validator": l.validator,
+                "slots_expected": l.slots_expected(),
+                "slots_produced": l.slots_produced,
+                "slots_skipped": l.slots_skipped,
+                "last_produced_slot": l.last_produced_slot,
+                "last_skipped_slot": l.last_skipped_slot,
+                "score": l.score(),
+                "below_threshold": l.score() < crate::liveness::LIVENESS_ALERT_THRESHOLD,
+            })
+        })
+        .collect();
+
+    Ok(serde_json::json!({
+        "alert_threshold": crate::liveness::LIVENESS_ALERT_THRESHOLD,
+        "validators": validators,
+    }))
+}
+
+/// Handle getLeaderSchedule(epoch?) - who leads each slot of an epoch (default: the current one)
+async fn handle_get_leader_schedule(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: GetLeaderScheduleParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let current_slot = chain.slot_at(crate::block::current_unix_timestamp_ms());
+    let epoch = params
+        .epoch
+        .unwrap_or_else(|| crate::leader_schedule::epoch_of(current_slot, chain.slots_per_epoch));
+    let storage_error = |e: crate::error::CompassError| RpcError { code: -32603, message: e.to_string() };
+    let schedule = chain.leader_schedule(epoch).map_err(storage_error)?;
+    let stakes = chain.validator_stakes().map_err(storage_error)?;
+
+    Ok(serde_json::json!({
+        "epoch": schedule.epoch,
+        "first_slot": schedule.first_slot(),
+        "slots_per_epoch": schedule.slots_per_epoch,
+        "current_slot": current_slot,
+        "current_leader": schedule.leader_at(current_slot),
+        "validators": stakes,
+        "leaders": schedule.leaders,
+    }))
+}
+
+/// Handle getEpochReport(epoch?) - fees, rewards and burns for an epoch. A finished
+/// epoch's recorded report is final; otherwise (e.g. the current epoch) a running tally.
+async fn handle_get_epoch_report(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: GetEpochReportParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let epoch = params
+        .epoch
+        .unwrap_or_else(|| chain.epoch_at(crate::block::current_unix_timestamp_ms()));
+    let storage_error = |e: crate::error::CompassError| RpcError { code: -32603, message: e.to_string() };
+    let (report, is_final) = match chain.storage.get_epoch_report(epoch).map_err(storage_error)? {
+        Some(report) => (report, true),
+        None => (chain.compute_epoch_report(epoch).map_err(storage_error)?, false),
+    };
+
+    Ok(serde_json::json!({
+        "epoch": report.epoch,
+        "final": is_final,
+        "report_hash": report.hash(),
+        "heights": report.heights,
+        "blocks_by_proposer": report.blocks_by_proposer,
+        "fees_collected": report.fees_collected,
+        "compute_emitted": report.compute_emitted,
+        "rewards": report.rewards,
+        "burns": report.burns,
+    }))
+}
+
+/// Handle getFinalizedHeight - highest block a supermajority of validator stake voted for
+async fn handle_get_finalized_height(chain: Arc<Mutex<Chain>>) -> Result<serde_json::Value, RpcError> {
+    let chain = safe_lock(&chain)?;
+    let finalized = chain
+        .storage
+        .get_finalized()
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    Ok(serde_json::json!({
+        "finalized_height": finalized.as_ref().map(|(height, _)| *height),
+        "finalized_hash": finalized.map(|(_, hash)| hash),
+        "height": chain.height,
+    }))
+}
+
+/// Handle getSkippedSlots(limit?) - most recent skipped slot runs
+async fn handle_get_skipped_slots(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let params: GetSkippedSlotsParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&chain)?;
+    let runs = chain.storage.get_skipped_slots(params.limit.unwrap_or(50).min(1000));
+    Ok(serde_json::to_value(runs).unwrap())
+}
+
 
 
 
@@ -529,6 +2453,21 @@ async fn handle_submit_compute(
         inputs: req.inputs.clone(),
         max_compute_units: req.max_compute_units,
     };
+
+    // Reject oversized jobs before any funds are escrowed
+    let raw_tx = safe_serialize(&payload)?;
+    if raw_tx.len() > crate::gulf_stream::admission::MAX_TX_SIZE_BYTES {
+        return Err(RpcError {
+            code: -32010,
+            message: format!(
+                "Transaction rejected: {}",
+                crate::gulf_stream::AdmissionError::TooLarge {
+                    size: raw_tx.len(),
+                    limit: crate::gulf_stream::admission::MAX_TX_SIZE_BYTES,
+                }
+            ),
+        });
+    }
     
     // 0. Validate Bid Asset (Phase 1: Only COMPASS)
     if req.bid_asset != "COMPASS" {
@@ -540,7 +2479,7 @@ async fn handle_submit_compute(
 
     // 1. Escrow Logic: Check Balance & Lock Funds
     {
-        let chain = safe_lock(&state.chain)?;
+        let mut chain = safe_lock(&state.chain)?;
         
         // Check User Balance
         let balance = chain.storage.get_balance(&req.owner_id, "COMPASS").unwrap_or(0);
@@ -552,16 +2491,17 @@ async fn handle_submit_compute(
         }
 
         // Transfer to Escrow Vault
-        // Deduct from User
-        chain.storage.set_balance(&req.owner_id, "COMPASS", balance - req.bid_amount).map_err(|e| RpcError {
+        let escrow = SettlementLeg::Transfer {
+            from: req.owner_id.clone(),
+            to: "ESCROW_VAULT".to_string(),
+            asset: "COMPASS".to_string(),
+            amount: req.bid_amount,
+        };
+        chain.settle(vec![escrow], &format!("escrow for job {}", req.job_id)).map_err(|e| RpcError {
              code: -32603,
              message: format!("Storage error: {}", e),
         })?;
         
-        // Credit Escrow
-        let escrow_bal = chain.storage.get_balance("ESCROW_VAULT", "COMPASS").unwrap_or(0);
-        chain.storage.set_balance("ESCROW_VAULT", "COMPASS", escrow_bal + req.bid_amount).ok();
-        
         info!("?? Escrow Locked: {} COMPASS from {} for Job {}", req.bid_amount, req.owner_id, req.job_id);
 
         use crate::layer3::compute::ComputeJob;
@@ -579,21 +2519,10 @@ async fn handle_submit_compute(
     }
 
     // 2. Add to Local Gulf Stream
-    let raw_tx = bincode::serialize(&payload).unwrap(); // safe_serialize?
-    use sha2::Digest;
-    let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
 
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        // Priority fee is implicit or 0 for now? 
-        let added = gs.add_transaction(tx_hash.clone(), raw_tx.clone(), 0);
-        if !added {
-             return Err(RpcError {
-                code: -32603,
-                message: "Transaction rejected (duplicate or full)".to_string(),
-            });
-        }
-    }
+    // Priority fee is implicit or 0 for now? 
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
 
     // GOSSIP: Broadcast to peers
     let msg = crate::network::NetMessage::SubmitTx(payload);
@@ -613,13 +2542,177 @@ async fn handle_submit_compute(
         let worker_msg = crate::network::NetMessage::ComputeJob(job);
         let _ = state.cmd_tx.send(crate::network::NetworkCommand::Broadcast(worker_msg)).await;
     }
-    
-    info!("?? AI Job Submitted: {} (Model: {})", req.job_id, req.model_id);
+    
+    info!("?? AI Job Submitted: {} (Model: {})", req.job_id, req.model_id);
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash),
+        "job_id": req.job_id
+    }))
+}
+
+/// How far the timestamp on a signed request (invokeModel, getJobResult) may drift from the node clock
+const SIGNED_REQUEST_MAX_SKEW_MS: u64 = 5 * 60 * 1000;
+
+/// Handle invokeModel(token_id, caller, inputs, timestamp, signature, mode?)
+///
+/// Owners, active renters and open-source models run free; anyone else pays
+/// `INFERENCE_PRICE_PER_UNIT` per metered compute unit, split between the model's
+/// creator (royalty) and owner. Local runs answer with the prediction; worker runs
+/// answer with the queued job id. A failed local run is not charged. The charge is
+/// submitted as an `InvokeModel` transaction, settled once per signed request.
+async fn handle_invoke_model(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::compute::ComputeJob;
+    use crate::layer3::model_nft::{inference_units, invoke_message, invoke_request_id};
+
+    let req: InvokeModelParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let inputs = serde_json::to_vec(&req.inputs).map_err(|e| RpcError { code: -32602, message: e.to_string() })?;
+    if inputs.len() > MAX_INPUT_SIZE {
+        return Err(RpcError {
+            code: -32602,
+            message: format!("Input size exceeds limit of {} bytes", MAX_INPUT_SIZE),
+        });
+    }
+    let now_ms = crate::block::current_unix_timestamp_ms();
+    if now_ms.abs_diff(req.timestamp) > SIGNED_REQUEST_MAX_SKEW_MS {
+        return Err(RpcError { code: -32602, message: "Request timestamp is stale or in the future".to_string() });
+    }
+    let message = invoke_message(&req.token_id, &req.caller, &inputs, req.timestamp);
+    if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), &req.signature, &req.caller) {
+        return Err(RpcError { code: -32003, message: "Invalid caller signature".to_string() });
+    }
+
+    let request_id = invoke_request_id(&req.token_id, &req.caller, &inputs, req.timestamp);
+
+    let units = inference_units(inputs.len());
+    let (nft, entitlement, fee) = {
+        let chain = safe_lock(&state.chain)?;
+        if chain.storage.is_invocation_settled(&request_id).map_err(|e| RpcError { code: -32603, message: e.to_string() })? {
+            return Err(RpcError { code: -32003, message: "Request was already used; sign a new one".to_string() });
+        }
+        let nft = chain.storage.get_model_nft(&req.token_id)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .ok_or(RpcError { code: -32001, message: "NFT not found in database".into() })?;
+        let (entitlement, fee) = nft.invocation_fee(&req.caller, inputs.len(), now_ms / 1000)
+            .map_err(|e| RpcError { code: -32003, message: e })?;
+        let balance = chain.storage.get_balance(&req.caller, "COMPASS").unwrap_or(0);
+        if balance < fee {
+            return Err(RpcError {
+                code: -32002,
+                message: format!("Insufficient balance. Have: {}, Need: {}", balance, fee),
+            });
+        }
+        (nft, entitlement, fee)
+    };
+
+    let job_id = format!("invoke-{}", &request_id[..16]);
+    let mut job = ComputeJob::new(job_id.clone(), req.caller.clone(), nft.token_id.clone(), inputs.clone(), 0);
+    job.max_compute_units = units;
+
+    let run_local = match req.mode {
+        InvokeMode::Local => true,
+        InvokeMode::Worker => false,
+        InvokeMode::Auto => cfg!(feature = "onnx") && std::path::Path::new(&format!("models/{}.onnx", nft.token_id)).exists(),
+    };
+    let output = if run_local {
+        let local_job = job.clone();
+        let raw = tokio::task::spawn_blocking(move || local_job.execute_inference())
+            .await
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .map_err(|e| RpcError { code: -32603, message: format!("Inference failed: {}", e) })?;
+        Some(serde_json::from_str::<serde_json::Value>(&raw).unwrap_or(serde_json::Value::String(raw)))
+    } else {
+        None
+    };
+
+    // The caller's signed request doubles as the payment, settled by the block that carries it
+    let (royalty, owner_share) = nft.split_revenue(fee);
+    let tx_hash = if fee > 0 {
+        let payload = crate::network::TransactionPayload::InvokeModel {
+            token_id: req.token_id.clone(),
+            caller: req.caller.clone(),
+            inputs,
+            timestamp: req.timestamp,
+            signature: req.signature.clone(),
+        };
+        let raw_tx = safe_serialize(&payload)?;
+        let tx_hash = encoding::sha256(&raw_tx).to_vec();
+        admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+        Some(encoding::to_hex(tx_hash))
+    } else {
+        None
+    };
+    if output.is_none() {
+        safe_lock(&state.chain)?.storage.save_compute_job(&job).map_err(|e| RpcError {
+            code: -32603,
+            message: format!("Failed to save job: {}", e),
+        })?;
+        let _ = state.cmd_tx.send(crate::network::NetworkCommand::Broadcast(crate::network::NetMessage::ComputeJob(job))).await;
+    }
+
+    info!("?? Model {} invoked by {} ({}, {} units, fee {})", nft.token_id, &req.caller[..8.min(req.caller.len())], entitlement.as_str(), units, fee);
+
+    Ok(serde_json::json!({
+        "token_id": nft.token_id,
+        "job_id": job_id,
+        "status": if output.is_some() { "Completed" } else { "Queued" },
+        "entitlement": entitlement.as_str(),
+        "compute_units": units,
+        "fee": fee,
+        "royalty": royalty,
+        "owner_share": owner_share,
+        "tx_hash": tx_hash,
+        "result": output,
+    }))
+}
+
+/// Handle getJobResult(job_id, requester, timestamp, signature): a job's stored output,
+/// readable only by the job's creator until its retention window ends
+async fn handle_get_job_result(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetJobResultParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let now_ms = crate::block::current_unix_timestamp_ms();
+    if now_ms.abs_diff(req.timestamp) > SIGNED_REQUEST_MAX_SKEW_MS {
+        return Err(RpcError { code: -32602, message: "Request timestamp is stale or in the future".to_string() });
+    }
+    let message = crate::layer3::compute::job_result_request_message(&req.job_id, req.timestamp);
+    if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), &req.signature, &req.requester) {
+        return Err(RpcError { code: -32003, message: "Invalid requester signature".to_string() });
+    }
+
+    let chain = safe_lock(&chain)?;
+    let job = chain.storage.get_compute_job(&req.job_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Job not found".to_string() })?;
+    if job.creator != req.requester {
+        return Err(RpcError { code: -32003, message: "Only the job's creator may read its result".to_string() });
+    }
+    let result = chain.storage.get_job_result(&req.job_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .filter(|r| r.expires_at > now_ms / 1000)
+        .ok_or(RpcError { code: -32001, message: "No result stored for this job (pending or expired)".to_string() })?;
 
     Ok(serde_json::json!({
-        "status": "Submitted",
-        "tx_hash": hex::encode(tx_hash),
-        "job_id": req.job_id
+        "job_id": result.job_id,
+        "worker_id": result.worker_id,
+        "result_hash": result.result_hash,
+        "data": encoding::to_base64(&result.data),
+        "stored_at": result.stored_at,
+        "expires_at": result.expires_at,
     }))
 }
 
@@ -644,64 +2737,29 @@ async fn handle_submit_result(
     
     info!("?? AI Result Received for Job: {} (Worker: {})", req.job_id, req.worker_id);
 
+    // ===== ANTI-CHEAT & REDUNDANCY: the PoUW curve checks the measured duration
+    // and only settles the job once enough workers agree on the output =====
+    let result_hash = encoding::hash_to_hex(&encoding::sha256(&req.result_data));
+    let outcome = {
+        let chain = safe_lock(&state.chain)?;
+        let now = crate::block::current_unix_timestamp_ms() / 1000;
+        chain
+            .record_pouw_result(&req.job_id, &req.worker_id, &result_hash, now)
+            .map_err(|e| {
+                warn!("?? Result for Job {} rejected (Worker: {}): {}", req.job_id, req.worker_id, e);
+                RpcError { code: -32007, message: e.to_string() }
+            })?
+    };
+
     // 2. Add to Local Gulf Stream
-    let raw_tx = bincode::serialize(&payload).unwrap();
-    use sha2::Digest;
-    let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
 
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        let added = gs.add_transaction(tx_hash.clone(), raw_tx.clone(), 0);
-        if !added {
-             return Err(RpcError {
-                code: -32603,
-                message: "Transaction rejected".to_string(),
-            });
-        }
-    }
-    
-    // ===== ANTI-CHEAT: Validate Job Duration =====
-    {
-        let chain = safe_lock(&state.chain)?;
-        if let Ok(Some(job)) = chain.storage.get_compute_job(&req.job_id) {
-            // Check if job was started
-            if let Some(started_at) = job.started_at {
-                let current_time = std::time::SystemTime::now()
-                    .duration_since(std::time::UNIX_EPOCH)
-                    .unwrap()
-                    .as_secs();
-                
-                let elapsed = current_time.saturating_sub(started_at);
-                
-                if elapsed < job.min_duration {
-                    warn!(
-                        "?? Job {} completed too quickly! Elapsed: {}s, Required: {}s (Worker: {})",
-                        req.job_id, elapsed, job.min_duration, req.worker_id
-                    );
-                    return Err(RpcError {
-                        code: -32007,
-                        message: format!(
-                            "Job completed too quickly ({}/{}s). Possible cheating detected.",
-                            elapsed, job.min_duration
-                        ),
-                    });
-                }
-                
-                info!(
-                    "? Job {} duration validated: {}s (min: {}s)",
-                    req.job_id, elapsed, job.min_duration
-                );
-            } else {
-                // Job was never properly started, but allow it for beta resilience
-                info!("?? Job {} has no start time (resync?). Accepting result.", req.job_id);
-                // Assume valid logic happened off-chain
-            }
-        }
-    }
-    
-    // Remove from Pending Queue & handle training completion
-    {
-        let chain = safe_lock(&state.chain)?;
+    admit_transaction(&state, tx_hash.clone(), raw_tx.clone(), 0)?;
+
+    // Remove from Pending Queue & handle training completion, once the result is settled
+    if matches!(outcome, crate::layer3::pouw::PouwOutcome::Settled { .. }) {
+        let mut chain = safe_lock(&state.chain)?;
         
         // Get the completed job before deleting
         if let Ok(Some(job)) = chain.storage.get_compute_job(&req.job_id) {
@@ -717,10 +2775,17 @@ async fn handle_submit_result(
                 // This is a standard Oracle/Inference job.
                 // 1. PAY THE WORKER
                 // The worker who submitted the valid result deserves the reward.
-                if let Err(e) = chain.storage.update_balance(&req.worker_id, "COMPUTE", job.reward_amount) {
+                // It accrues to the worker's earnings ledger and is paid out by claimEarnings.
+                if let Err(e) = chain.storage.accrue_earnings(&req.worker_id, EarningReason::Job, job.reward_amount, &req.job_id) {
                     error!("Failed to pay worker {}: {}", req.worker_id, e);
                 } else {
-                    info!("?? Paid Worker {}: {} COMPUTE", req.worker_id, job.reward_amount);
+                    info!("?? Accrued to Worker {}: {} COMPUTE", req.worker_id, job.reward_amount);
+                    record_audit(
+                        &chain.storage,
+                        &state.node_identity,
+                        crate::audit::AuditAction::EscrowRelease,
+                        format!("job={} worker={} amount={} COMPUTE", req.job_id, req.worker_id, job.reward_amount),
+                    );
                 }
                 
                 // 2. Model Owner Royalty (15% of Reward)
@@ -739,7 +2804,8 @@ async fn handle_submit_result(
                     .map(|nft| nft.current_owner.clone())
                     .unwrap_or_else(|| state.node_identity.clone());
                 
-                if let Err(e) = chain.storage.update_balance(&nft_owner, "COMPUTE", royalty_amount) {
+                let royalty = SettlementLeg::Credit { account: nft_owner.clone(), asset: "COMPUTE".to_string(), amount: royalty_amount };
+                if let Err(e) = chain.settle(vec![royalty], &format!("royalty for job {}", req.job_id)) {
                      error!("Failed to pay royalty: {}", e);
                 } else {
                      if nft_owner == state.node_identity {
@@ -849,9 +2915,19 @@ async fn handle_submit_result(
     let msg = crate::network::NetMessage::SubmitTx(payload);
     let _ = state.cmd_tx.send(crate::network::NetworkCommand::Broadcast(msg)).await;
 
+    let settlement = match outcome {
+        crate::layer3::pouw::PouwOutcome::Pending { matches, required } => {
+            serde_json::json!({ "state": "awaiting_matches", "matches": matches, "required": required })
+        }
+        crate::layer3::pouw::PouwOutcome::Settled { rewards } => serde_json::json!({ "state": "settled", "rewards": rewards }),
+        crate::layer3::pouw::PouwOutcome::Duplicate => serde_json::json!({ "state": "duplicate" }),
+        crate::layer3::pouw::PouwOutcome::AlreadySettled => serde_json::json!({ "state": "already_settled" }),
+    };
+
     Ok(serde_json::json!({
         "status": "Result Submitted",
-        "tx_hash": hex::encode(tx_hash)
+        "tx_hash": encoding::to_hex(tx_hash),
+        "settlement": settlement
     }))
 }
 
@@ -962,21 +3038,11 @@ async fn handle_submit_oracle_verification_result(
     }
 
     // Add to GulfStream
-    let raw_tx = bincode::serialize(&payload).unwrap();
-    use sha2::Digest;
-    let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
-    let _tx_hash_hex = hex::encode(&tx_hash);
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    let _tx_hash_hex = encoding::to_hex(&tx_hash);
 
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        let added = gs.add_transaction(tx_hash.clone(), raw_tx.clone(), 0);
-        if !added {
-            return Err(RpcError {
-                code: -32603,
-                message: "Transaction rejected (duplicate or queue full)".to_string(),
-            });
-        }
-    }
+    admit_transaction(&state, tx_hash.clone(), raw_tx.clone(), 0)?;
 
     info!("? Oracle Verification Result Submitted: {} ({}) | Price: {} | Dev: {}%", 
         req.ticker, if req.passed { "PASS" } else { "FAIL" }, req.oracle_price, req.deviation_pct);
@@ -1006,7 +3072,7 @@ async fn handle_submit_oracle_verification_result(
                 } else {
                     // Loss: Slash
                     let loss_abs = pnl.abs() as u64;
-                    let _ = l2.collateral.slash(&req.worker_id, loss_abs);
+                    let _ = l2.slash(&state.node_identity, &req.worker_id, loss_abs, "lost prediction bet");
                     info!("   ?? Bet LOST! Slashed {} from {}", loss_abs, req.worker_id);
                 }
             }
@@ -1038,10 +3104,11 @@ async fn handle_submit_oracle_verification_result(
     
     // --- 4. Reward Compute Tokens ---
     if req.compute_units_used > 0 {
-        let mut wallets = safe_lock(&state.wallet_manager)?;
-        wallets.credit(&req.worker_id, "COMPUTE", req.compute_units_used);
-        info!("   ?? PoUW Reward: {} COMPUTE credited to {}", req.compute_units_used, req.worker_id);
-        let _ = wallets.save("");
+        let chain = safe_lock(&state.chain)?;
+        match chain.storage.accrue_earnings(&req.worker_id, EarningReason::OracleUpdate, req.compute_units_used, &req.job_id) {
+            Ok(()) => info!("   ?? PoUW Reward: {} COMPUTE accrued to {}", req.compute_units_used, req.worker_id),
+            Err(e) => error!("Failed to accrue oracle reward for {}: {}", req.worker_id, e),
+        }
     }
 
 
@@ -1082,7 +3149,7 @@ async fn handle_submit_oracle_verification_result(
                     training_epochs: job.completed_updates as usize, // Continuous learning (usize)
                     final_loss: 1.0 - win_rate, // Simple proxy
                     training_duration: job.completed_updates as u64 * job.interval_seconds,
-                    data_hash: hex::encode(&req.job_id),
+                    data_hash: encoding::to_hex(&req.job_id),
                 };
                 
                 let nft = ModelNFT::from_job(
@@ -1158,6 +3225,15 @@ async fn handle_submit_recurring_oracle_job(
     {
         let chain = safe_lock(&state.chain)?;
         chain.storage.save_recurring_job(&job).unwrap();
+        record_audit(
+            &chain.storage,
+            &req.submitter,
+            crate::audit::AuditAction::RecurringJobCreated,
+            format!(
+                "job={} ticker={} updates={} reward_per_update={}",
+                job_id, req.ticker, total_updates, req.reward_per_update
+            ),
+        );
     }
 
     info!("?? Recurring Oracle Job Created: {} for ticker {}", job_id, req.ticker);
@@ -1252,7 +3328,176 @@ async fn handle_get_block_range(
     
     let blocks = chain_guard.get_blocks_range(start, end);
     
-    Ok(serde_json::to_value(blocks).unwrap())
+    Ok(serde_json::json!(blocks.iter().map(block_with_summary).collect::<Vec<_>>()))
+}
+
+/// Handle getHeaders(start?, count?) - block headers without transactions, for watchtowers
+async fn handle_get_headers(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    #[derive(serde::Deserialize)]
+    struct Params {
+        start: Option<u64>,
+        count: Option<u64>,
+    }
+
+    let p: Params = serde_json::from_value(params)
+        .unwrap_or(Params { start: None, count: None });
+
+    let chain_guard = safe_lock(&chain)?;
+    let current_height = chain_guard.height;
+
+    let count = p.count.unwrap_or(100).clamp(1, 500); // Cap at 500
+    let start = p.start.unwrap_or(current_height.saturating_sub(count));
+    let end = start.saturating_add(count - 1);
+
+    let headers: Vec<BlockHeader> = chain_guard
+        .get_blocks_range(start, end)
+        .into_iter()
+        .map(|b| b.header)
+        .collect();
+
+    to_json(&headers)
+}
+
+/// Handle getProofOfReserve()
+async fn handle_get_proof_of_reserve(
+    chain: Arc<Mutex<Chain>>,
+) -> Result<serde_json::Value, RpcError> {
+    let chain_guard = safe_lock(&chain)?;
+
+    let mut vaults: Vec<VaultReserve> = chain_guard
+        .vault_manager
+        .vaults
+        .values()
+        .map(|v| VaultReserve {
+            compass_asset: v.compass_asset.clone(),
+            collateral_asset: v.collateral_asset.clone(),
+            vault_address: v.vault_address.clone(),
+            collateral_balance: v.collateral_balance,
+            minted_supply: v.minted_supply,
+            exchange_rate: v.exchange_rate.to_string(),
+        })
+        .collect();
+    vaults.sort_by(|a, b| a.compass_asset.cmp(&b.compass_asset));
+
+    to_json(&ProofOfReserve {
+        height: chain_guard.height,
+        head_hash: chain_guard.head_hash(),
+        vaults,
+    })
+}
+
+/// Handle getVaultState(vault_id) - debt with the stability fee accrued so far,
+/// collateral ratio and fees of one vault
+async fn handle_get_vault_state(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: GetVaultStateParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let chain_guard = safe_lock(&chain)?;
+    let vault = chain_guard.vault_manager.vaults.get(&p.vault_id).ok_or(RpcError {
+        code: -32001,
+        message: format!("Vault {} not found", p.vault_id),
+    })?;
+
+    let slot = chain_guard.slot_at(crate::block::current_unix_timestamp_ms());
+    let accrual = chain_guard.storage.get_vault_accrual(&p.vault_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .unwrap_or_else(|| crate::vault::stability::VaultAccrual::starting_at(slot));
+    let stability_fee = chain_guard.stability_fee_config();
+    let pending = accrual.pending(vault.minted_supply, slot, &stability_fee, chain_guard.slot_duration_ms);
+
+    let mut with_fee = vault.clone();
+    with_fee.minted_supply = vault.minted_supply.saturating_add(pending);
+    let price = chain_guard.vault_manager.oracle_prices.get(&vault.collateral_asset).map(|(price, _)| *price);
+
+    to_json(&VaultState {
+        vault_id: p.vault_id.clone(),
+        collateral_asset: vault.collateral_asset.clone(),
+        collateral_balance: vault.collateral_balance,
+        debt: with_fee.minted_supply,
+        minted_supply: vault.minted_supply,
+        pending_stability_fee: pending,
+        stability_fees_charged: accrual.charged,
+        annual_rate_bps: stability_fee.annual_rate_bps,
+        accumulated_fees: vault.accumulated_fees,
+        oracle_price: price.map(|p| p.to_string()),
+        collateral_ratio: price.and_then(|p| with_fee.collateral_ratio(p)).map(|r| r.round_dp(4).to_string()),
+    })
+}
+
+const MAX_STATE_DIFF_RANGE: u64 = 10_000; // blocks
+
+/// Handle getStateDiff(from_height, to_height)
+/// Net balance, NFT ownership and vault changes made by blocks in (from_height, to_height]
+async fn handle_get_state_diff(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let p: GetStateDiffParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    if p.from_height > p.to_height {
+        return Err(RpcError {
+            code: -32602,
+            message: "from_height must not exceed to_height".to_string(),
+        });
+    }
+    if p.to_height - p.from_height > MAX_STATE_DIFF_RANGE {
+        return Err(RpcError {
+            code: -32602,
+            message: format!("Range too large (max {} blocks)", MAX_STATE_DIFF_RANGE),
+        });
+    }
+
+    let chain_guard = safe_lock(&chain)?;
+    let events = if p.from_height == p.to_height {
+        Vec::new()
+    } else {
+        chain_guard.storage.get_events_range(p.from_height + 1, p.to_height)
+    };
+
+    to_json(&crate::events::StateDiff::from_events(p.from_height, p.to_height, &events))
+}
+
+/// Handle getAuditLog(start?, count?)
+async fn handle_get_audit_log(
+    chain: Arc<Mutex<Chain>>,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    #[derive(serde::Deserialize)]
+    struct Params {
+        start: Option<u64>,
+        count: Option<u64>,
+    }
+
+    let p: Params = serde_json::from_value(params)
+        .unwrap_or(Params { start: None, count: None });
+
+    let chain_guard = safe_lock(&chain)?;
+    let total = chain_guard.storage.get_audit_log_len();
+
+    // Default: last 100 entries
+    let count = p.count.unwrap_or(100).clamp(1, 500); // Cap at 500
+    let start = p.start.unwrap_or(total.saturating_sub(count));
+    let end = start.saturating_add(count - 1);
+
+    let entries = chain_guard.storage.get_audit_log(start, end);
+    let verified = crate::audit::verify_chain(&entries);
+
+    Ok(serde_json::json!({
+        "total": total,
+        "entries": entries,
+        "chain_valid": verified.is_ok(),
+        "first_invalid_seq": verified.err(),
+    }))
 }
 
 /// Handle getOraclePrices()
@@ -1260,14 +3505,16 @@ async fn handle_get_oracle_prices(
     chain: Arc<Mutex<Chain>>,
 ) -> Result<serde_json::Value, RpcError> {
     let chain_guard = safe_lock(&chain)?;
-    let prices = chain_guard.vault_manager.oracle_prices.clone();
-    
-    // Convert to simple map
-    let mut result = std::collections::HashMap::new();
-    for (ticker, (price, _timestamp)) in prices {
-        result.insert(ticker, price.to_f64().unwrap_or(0.0));
-    }
-    
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let vm = &chain_guard.vault_manager;
+
+    // Price, age and whether vaults may act on it
+    let result: std::collections::HashMap<_, _> = vm
+        .oracle_prices
+        .keys()
+        .filter_map(|ticker| Some((ticker.clone(), vm.price_status(ticker, now, &chain_guard.oracle_guard)?)))
+        .collect();
+
     Ok(serde_json::to_value(result).unwrap())
 }
 
@@ -1355,6 +3602,246 @@ async fn handle_list_model_nft(
     Ok(serde_json::json!({ "status": "listed", "message": msg }))
 }
 
+/// Handle setNFTMetadata(token_id, owner, uri, content_hash, timestamp, signature)
+async fn handle_set_nft_metadata(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::nft_metadata::{metadata_message, resolve_uri, NftMetadataPointer};
+    let req: SetNFTMetadataParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    resolve_uri(&req.uri, &state.ipfs_gateway).map_err(|e| RpcError { code: -32602, message: e.to_string() })?;
+    let hash_ok = encoding::hash_from_hex(&req.content_hash).is_ok();
+    if !hash_ok {
+        return Err(RpcError { code: -32602, message: "content_hash must be a SHA-256 hex digest".into() });
+    }
+    if crate::block::current_unix_timestamp_ms().abs_diff(req.timestamp) > SIGNED_REQUEST_MAX_SKEW_MS {
+        return Err(RpcError { code: -32602, message: "Request timestamp is stale or in the future".to_string() });
+    }
+    let message = metadata_message(&req.token_id, &req.uri, &req.content_hash, req.timestamp);
+    if !crate::crypto::verify_with_pubkey_hex(message.as_bytes(), &req.signature, &req.owner) {
+        return Err(RpcError { code: -32003, message: "Invalid owner signature".into() });
+    }
+
+    let chain = safe_lock(&state.chain)?;
+    let nft = chain.storage.get_model_nft(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "NFT not found in database".into() })?;
+    if nft.current_owner != req.owner {
+        return Err(RpcError { code: -32003, message: "Caller does not own NFT".into() });
+    }
+    // An older signed pointer can't be replayed over a newer one
+    let previous = chain.storage.get_nft_metadata_pointer(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    if previous.is_some_and(|p| p.updated_at >= req.timestamp / 1000) {
+        return Err(RpcError { code: -32004, message: "A newer metadata pointer is already published".into() });
+    }
+
+    let pointer = NftMetadataPointer {
+        token_id: req.token_id.clone(),
+        uri: req.uri,
+        content_hash: req.content_hash.to_lowercase(),
+        updated_at: req.timestamp / 1000,
+    };
+    chain.storage.save_nft_metadata_pointer(&pointer)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save metadata pointer: {}", e) })?;
+
+    to_json(&pointer)
+}
+
+/// Handle getNFTMetadata(token_id)
+/// Fetches the published document and checks it against the on-chain hash.
+/// NFTs without published metadata get a standard document built from on-chain fields.
+async fn handle_get_nft_metadata(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::nft_metadata::{fetch_and_validate, NftMetadata};
+    let req: GetNFTMetadataParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let (nft, pointer) = {
+        let chain = safe_lock(&state.chain)?;
+        let nft = chain.storage.get_model_nft(&req.token_id)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .ok_or(RpcError { code: -32001, message: "NFT not found in database".into() })?;
+        let pointer = chain.storage.get_nft_metadata_pointer(&req.token_id)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        (nft, pointer)
+    };
+
+    match pointer {
+        Some(pointer) => match fetch_and_validate(&pointer, &state.ipfs_gateway).await {
+            Ok(metadata) => Ok(serde_json::json!({
+                "token_id": req.token_id,
+                "source": "uri",
+                "uri": pointer.uri,
+                "content_hash": pointer.content_hash,
+                "verified": true,
+                "metadata": metadata,
+            })),
+            Err(e) => Ok(serde_json::json!({
+                "token_id": req.token_id,
+                "source": "uri",
+                "uri": pointer.uri,
+                "content_hash": pointer.content_hash,
+                "verified": false,
+                "error": e.to_string(),
+                "metadata": NftMetadata::from_nft(&nft),
+            })),
+        },
+        None => Ok(serde_json::json!({
+            "token_id": req.token_id,
+            "source": "on-chain",
+            "verified": false,
+            "metadata": NftMetadata::from_nft(&nft),
+        })),
+    }
+}
+
+/// Handle createCollection(collection_id, name, description, creator, signature)
+async fn handle_create_collection(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::nft_collection::NftCollection;
+    let req: CreateCollectionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let collection = NftCollection::new(req.collection_id, req.name, req.description, req.creator, req.signature)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let exists = chain.storage.get_nft_collection(&collection.collection_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .is_some();
+    if exists {
+        return Err(RpcError { code: -32004, message: "Collection ID already taken".into() });
+    }
+    chain.storage.save_nft_collection(&collection)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save collection: {}", e) })?;
+
+    info!("📚 Collection created: {} ({}) by {}", collection.collection_id, collection.name, collection.creator);
+    to_json(&collection)
+}
+
+/// Handle addToCollection(collection_id, token_id, signature)
+async fn handle_add_to_collection(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: AddToCollectionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let mut collection = chain.storage.get_nft_collection(&req.collection_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Collection not found".into() })?;
+    let nft = chain.storage.get_model_nft(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "NFT not found in database".into() })?;
+
+    if let Some(existing) = chain.storage.get_collection_of_nft(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+    {
+        return Err(RpcError { code: -32004, message: format!("NFT already belongs to collection {}", existing) });
+    }
+
+    collection.add_model(&nft, &req.signature)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+    chain.storage.save_nft_collection(&collection)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save collection: {}", e) })?;
+
+    Ok(serde_json::json!({
+        "status": "added",
+        "collection_id": collection.collection_id,
+        "token_id": req.token_id,
+        "item_count": collection.token_ids.len()
+    }))
+}
+
+/// Handle getCollection(collection_id) - collection, stats and member badges
+async fn handle_get_collection(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetCollectionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let collection = chain.storage.get_nft_collection(&req.collection_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Collection not found".into() })?;
+
+    let members: Vec<_> = collection.token_ids.iter()
+        .filter_map(|id| chain.storage.get_model_nft(id).ok().flatten())
+        .collect();
+    let items: Vec<serde_json::Value> = members.iter()
+        .map(|nft| serde_json::json!({
+            "token_id": nft.token_id,
+            "name": nft.name,
+            "current_owner": nft.current_owner,
+            "badge": collection.badge_for(nft),
+        }))
+        .collect();
+
+    Ok(serde_json::json!({
+        "collection": collection,
+        "verified": collection.is_signature_valid(),
+        "stats": collection.stats(&members),
+        "items": items,
+    }))
+}
+
+/// Handle getCollections()
+async fn handle_get_collections(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    let chain = safe_lock(&state.chain)?;
+    let collections: Vec<serde_json::Value> = chain.storage.get_all_nft_collections()
+        .into_iter()
+        .map(|c| serde_json::json!({
+            "collection_id": c.collection_id,
+            "name": c.name,
+            "creator": c.creator,
+            "item_count": c.token_ids.len(),
+            "verified": c.is_signature_valid(),
+        }))
+        .collect();
+    Ok(serde_json::json!(collections))
+}
+
+/// Handle getNFTVerification(token_id) - verification badge for a single NFT
+async fn handle_get_nft_verification(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::nft_collection::VerificationBadge;
+    let req: GetNFTMetadataParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let nft = chain.storage.get_model_nft(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "NFT not found in database".into() })?;
+
+    let collection = match chain.storage.get_collection_of_nft(&req.token_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+    {
+        Some(id) => chain.storage.get_nft_collection(&id)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?,
+        None => None,
+    };
+
+    let badge = collection.as_ref().map(|c| c.badge_for(&nft)).unwrap_or(VerificationBadge::None);
+    Ok(serde_json::json!({
+        "token_id": nft.token_id,
+        "creator": nft.creator,
+        "collection_id": collection.as_ref().map(|c| c.collection_id.clone()),
+        "collection_name": collection.as_ref().map(|c| c.name.clone()),
+        "badge": badge,
+    }))
+}
+
 /// Handle buyModelNFT
 async fn handle_buy_model_nft(
     state: RpcState,
@@ -1424,14 +3911,16 @@ async fn handle_submit_native_vault(
         message: format!("Invalid params: {}", e),
     })?;
 
-    // 1. Lock COMPASS collateral from user's balance
+    // 1. The COMPASS collateral must be there to lock
     {
         let chain = safe_lock(&state.chain)?;
-        chain.storage.lock_vault_collateral(&req.owner_id, req.compass_collateral)
-            .map_err(|e| RpcError {
-                code: -32603,
-                message: format!("Failed to lock collateral: {:?}", e),
-            })?;
+        let balance = chain.storage.get_balance(&req.owner_id, "COMPASS").unwrap_or(0);
+        if balance < req.compass_collateral {
+            return Err(RpcError {
+                code: -32002,
+                message: format!("Insufficient balance. Have: {}, Need: {}", balance, req.compass_collateral),
+            });
+        }
     }
 
     // 2. Process mint via VaultManager
@@ -1469,13 +3958,21 @@ async fn handle_submit_native_vault(
         })?
     };
 
-    // 3. Credit minted tokens to user
+    // 3. Lock the collateral and credit the minted tokens in one block
     {
-        let chain = safe_lock(&state.chain)?;
-        chain.storage.update_balance(&req.owner_id, &asset_name, minted)
+        let mut chain = safe_lock(&state.chain)?;
+        let legs = vec![
+            SettlementLeg::Debit { account: req.owner_id.clone(), asset: "COMPASS".to_string(), amount: req.compass_collateral },
+            SettlementLeg::Credit { account: req.owner_id.clone(), asset: asset_name.clone(), amount: minted },
+        ];
+        chain.settle(legs, &format!("native vault mint of {}", asset_name)).map_err(|e| RpcError {
+            code: -32605,
+            message: format!("Failed to settle vault mint: {}", e),
+        })?;
+        chain.storage.lock_vault_collateral(&req.owner_id, req.compass_collateral)
             .map_err(|e| RpcError {
-                code: -32605,
-                message: format!("Failed to credit tokens: {:?}", e),
+                code: -32603,
+                message: format!("Failed to lock collateral: {:?}", e),
             })?;
     }
 
@@ -1513,7 +4010,7 @@ async fn handle_purchase_prediction(
     // Fee (e.g., 5 COMPASS)
     let fee: u64 = 5;
 
-    let chain = safe_lock(&state.chain)?;
+    let mut chain = safe_lock(&state.chain)?;
 
     // 1. Check Balance
     let balance = chain.storage.get_balance(buyer_id, "COMPASS").unwrap_or(0);
@@ -1527,7 +4024,8 @@ async fn handle_purchase_prediction(
     // 2. Transfer Fee (Burn logic or Admin?)
     // Payment for signal -> Goes to Protocol (Burn) + Node?
     // Let's burn it for now (Simple)
-    if let Err(e) = chain.storage.set_balance(buyer_id, "COMPASS", balance - fee) {
+    let burn = SettlementLeg::Debit { account: buyer_id.to_string(), asset: "COMPASS".to_string(), amount: fee };
+    if let Err(e) = chain.settle(vec![burn], &format!("prediction for {}", ticker)) {
          return Err(RpcError {
              code: -32603,
              message: format!("Balance deduct failed: {}", e),
@@ -1670,7 +4168,7 @@ pub async fn handle_rent_model(
     let req: RentModelParams = serde_json::from_value(params)
         .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
     
-    let chain = safe_lock(&state.chain)?;
+    let mut chain = safe_lock(&state.chain)?;
     
     // Find NFT
     let mut nft = chain.storage.get_model_nft(&req.token_id)
@@ -1699,24 +4197,31 @@ pub async fn handle_rent_model(
         return Err(RpcError { code: -32603, message: format!("Insufficient balance. Need {} COMPASS", total_cost) });
     }
     
-    // Transfer funds: Renter -> Owner
-    chain.storage.update_balance(&req.renter, "COMPASS", -(total_cost as i64) as u64) // Deduct - note: this is hacky, need signed or separate debit fn
-        .map_err(|e| RpcError { code: -32603, message: format!("Debit failed: {}", e) })?;
-    chain.storage.update_balance(&nft.current_owner, "COMPASS", total_cost)
-        .map_err(|e| RpcError { code: -32603, message: format!("Credit failed: {}", e) })?;
-    
-    // Update rental status
+    // Pay the owner and record the rental in one block
     let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
+    let expires_at = now + (req.duration_hours * 3600);
+    let legs = vec![
+        SettlementLeg::Transfer {
+            from: req.renter.clone(),
+            to: nft.current_owner.clone(),
+            asset: "COMPASS".to_string(),
+            amount: total_cost,
+        },
+        SettlementLeg::Rental {
+            token_id: nft.token_id.clone(),
+            renter: req.renter.clone(),
+            expires_at,
+            rate_per_hour: rental.rate_per_hour,
+        },
+    ];
+    chain.settle(legs, &format!("rental of {}", nft.token_id))
+        .map_err(|e| RpcError { code: -32603, message: format!("Transfer failed: {}", e) })?;
     nft.rental_status = Some(RentalAgreement {
         renter: req.renter.clone(),
-        expires_at: now + (req.duration_hours * 3600),
+        expires_at,
         rate_per_hour: rental.rate_per_hour,
     });
     
-    // Save
-    chain.storage.save_model_nft(&nft)
-        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save: {}", e) })?;
-    
     info!("?? NFT {} rented by {} for {} hours ({} COMPASS)", 
         nft.token_id, &req.renter[..8.min(req.renter.len())], req.duration_hours, total_cost);
     
@@ -1992,7 +4497,7 @@ pub async fn handle_get_prediction_history(
     
     for item in chain.storage.db.scan_prefix(prefix.as_bytes()).rev() {
         if let Ok((_, value)) = item {
-            if let Ok(pred) = bincode::deserialize::<crate::layer3::price_oracle::PredictionRecord>(&value) {
+            if let Ok(pred) = encoding::from_canonical_bytes::<crate::layer3::price_oracle::PredictionRecord>(&value) {
                 predictions.push(serde_json::json!({
                     "id": pred.id,
                     "ticker": pred.ticker,
@@ -2178,19 +4683,10 @@ pub async fn handle_mint_model_nft(
     
     let payload = crate::network::TransactionPayload::MintModelNFT(mint_params);
     let raw_tx = safe_serialize(&payload)?;
-    let tx_hash = sha2::Sha256::digest(&raw_tx).to_vec();
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
     
     // Submit to Gulf Stream
-    {
-        let mut gs = safe_lock(&state.gulf_stream)?;
-        let added = gs.add_transaction(tx_hash.clone(), raw_tx.clone(), 0);
-        if !added {
-            return Err(RpcError {
-                code: -32603,
-                message: "Transaction rejected by Gulf Stream (duplicate or full)".to_string(),
-            });
-        }
-    }
+    admit_transaction(&state, tx_hash.clone(), raw_tx.clone(), 0)?;
     
     // Reset epoch state for next generation
     {
@@ -2225,7 +4721,7 @@ pub async fn handle_mint_model_nft(
         "accuracy": nft_accuracy,
         "epochs_trained": epochs_trained,
         "estimated_value": nft_estimated_value,
-        "tx_hash": hex::encode(tx_hash),
+        "tx_hash": encoding::to_hex(tx_hash),
         "message": format!("? NFT mint transaction submitted via Gulf Stream! Will be processed by network. Gen {} training reset.", generation + 1),
         "note": "Transaction is in mempool and will be included in next block"
     }))
@@ -2286,7 +4782,7 @@ async fn handle_purchase_subscription(
     let end_time = start_time + (req.duration_days as u64 * 86400);
 
     {
-        let chain = safe_lock(&state.chain)?;
+        let mut chain = safe_lock(&state.chain)?;
         let balance = chain.storage.get_balance(&req.subscriber, "COMPASS").unwrap_or(0);
         
         if balance < cost {
@@ -2297,7 +4793,8 @@ async fn handle_purchase_subscription(
         }
         
         // Deduct Balance (Burn)
-        chain.storage.update_balance(&req.subscriber, "COMPASS", balance - cost).map_err(|e| RpcError {
+        let burn = SettlementLeg::Debit { account: req.subscriber.clone(), asset: "COMPASS".to_string(), amount: cost };
+        chain.settle(vec![burn], "premium subscription").map_err(|e| RpcError {
             code: -32603,
             message: format!("Storage error: {}", e),
         })?;
@@ -2430,7 +4927,7 @@ async fn handle_join_pool(
         message: format!("Invalid params: {}", e),
     })?;
 
-    let chain = safe_lock(&state.chain)?;
+    let mut chain = safe_lock(&state.chain)?;
     
     // 1. Get Pool
     let mut pool = chain.storage.get_model_pool(&req.pool_id)
@@ -2447,7 +4944,8 @@ async fn handle_join_pool(
     }
     
     // 3. Deduct User Balance
-    chain.storage.update_balance(&req.contributor, "COMPASS", balance - req.amount).map_err(|e| RpcError {
+    let stake = SettlementLeg::Debit { account: req.contributor.clone(), asset: "COMPASS".to_string(), amount: req.amount };
+    chain.settle(vec![stake], &format!("stake in pool {}", req.pool_id)).map_err(|e| RpcError {
         code: -32603,
         message: format!("Storage error: {}", e),
     })?;
@@ -2484,62 +4982,536 @@ async fn handle_get_model_pools(
     to_json(&pools)
 }
 
-/// Handle claimDividends(pool_id, contributor)
-async fn handle_claim_dividends(
+/// Handle claimDividends(pool_id, contributor)
+async fn handle_claim_dividends(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ClaimDividendsParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&state.chain)?;
+    
+    let mut pool = chain.storage.get_model_pool(&req.pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32602, message: "Pool not found".to_string() })?;
+        
+    let share = pool.get_share(&req.contributor);
+    if share <= 0.0 {
+        return Err(RpcError { code: -32602, message: "No stake in pool".to_string() });
+    }
+    
+    // Calculate Payout
+    let vault_bal = pool.vault_balance;
+    if vault_bal == 0 {
+         return Ok(serde_json::json!({
+             "status": "No Dividends",
+             "message": "Vault is empty"
+         }));
+    }
+    
+    // Simple logic: Payout everything based on share? 
+    // Usually need "claimed" tracking. For v1:
+    // Payout share of the claimable part of the vault, deduct from vault.
+    let split = chain.storage.get_pool_governance(&req.pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .params
+        .contributor_share_bps;
+    let claimable = vault_bal as u128 * split as u128 / 10_000;
+
+    let payout = (claimable as f64 * share) as u64;
+    
+    if payout > 0 {
+        // Accrue to the contributor's earnings; claimEarnings pays it out
+        chain.storage
+            .accrue_earnings(&req.contributor, EarningReason::PoolDividend, payout, &req.pool_id)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        
+        // Deduct from Pool Vault
+        pool.vault_balance = pool.vault_balance.saturating_sub(payout);
+        chain.storage.save_model_pool(&pool).ok(); // Save updated vault
+        
+        info!("?? Dividends Accrued: {} COMPUTE to {} (Pool: {})", payout, req.contributor, req.pool_id);
+    }
+    
+    Ok(serde_json::json!({
+        "status": "Accrued",
+        "amount": payout,
+        "currency": "COMPUTE"
+    }))
+}
+
+
+/// Handle getEarnings(worker) - claimable balance, totals and recent accruals
+async fn handle_get_earnings(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetEarningsParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let earnings = chain.storage.get_worker_earnings(&req.worker)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    let nonce = chain.storage.get_nonce(&req.worker)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+
+    Ok(serde_json::json!({
+        "worker": req.worker,
+        "asset": crate::layer3::earnings::EARNINGS_ASSET,
+        "claimable": earnings.claimable,
+        "total_accrued": earnings.total_accrued,
+        "total_claimed": earnings.total_claimed,
+        "min_payout": chain.min_earnings_payout,
+        "next_nonce": nonce + 1,
+        "entries": earnings.entries
+    }))
+}
+
+/// Handle getVestingSchedule(account?) - an account's locked genesis allocation and
+/// how much of it has vested and been released; every schedule without an account
+async fn handle_get_vesting_schedule(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetVestingScheduleParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let now_ms = crate::block::current_unix_timestamp_ms();
+    let describe = |s: crate::vesting::VestingSchedule| {
+        serde_json::json!({
+            "account": s.account,
+            "asset": crate::vesting::VESTING_ASSET,
+            "total": s.total,
+            "vested": s.vested_at(now_ms),
+            "released": s.released,
+            "releasable": s.releasable_at(now_ms),
+            "locked": s.total - s.vested_at(now_ms),
+            "start_ms": s.start_ms,
+            "cliff_end_ms": s.cliff_end_ms(),
+            "end_ms": s.end_ms()
+        })
+    };
+
+    match req.account {
+        Some(account) => {
+            let schedule = chain.storage.get_vesting_schedule(&account)
+                .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+                .ok_or(RpcError { code: -32602, message: format!("{} has no vesting schedule", account) })?;
+            Ok(describe(schedule))
+        }
+        None => Ok(serde_json::json!({
+            "schedules": chain.storage.get_vesting_schedules().into_iter().map(describe).collect::<Vec<_>>()
+        })),
+    }
+}
+
+/// Handle getSupplyInfo - circulating, staked, escrowed and burned block reward
+/// asset, and where the emission schedule stands
+async fn handle_get_supply_info(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    use crate::layer2::economics::BLOCK_REWARD_ASSET;
+    use crate::treasury::TREASURY_ACCOUNT;
+
+    let internal = |e: crate::error::CompassError| RpcError { code: -32603, message: e.to_string() };
+    let mut info = {
+        let chain = safe_lock(&state.chain)?;
+        let storage = &chain.storage;
+        let total = storage.get_asset_supply(BLOCK_REWARD_ASSET);
+        let escrowed = storage.get_total_escrowed(BLOCK_REWARD_ASSET);
+        let treasury = storage.get_balance(TREASURY_ACCOUNT, BLOCK_REWARD_ASSET).map_err(internal)?;
+        let emitted = storage.get_emitted(BLOCK_REWARD_ASSET).map_err(internal)?;
+        let epoch = chain.epoch_at(crate::block::current_unix_timestamp_ms());
+        SupplyInfo {
+            asset: BLOCK_REWARD_ASSET.to_string(),
+            total,
+            circulating: total.saturating_sub(escrowed).saturating_sub(treasury),
+            escrowed,
+            treasury,
+            staked: 0,
+            unbonding: 0,
+            burned: 0,
+            emitted,
+            max_emission: chain.emission.max_block_emission,
+            epoch,
+            era: chain.emission.era(epoch),
+            block_reward: chain.emission.block_reward(epoch, emitted),
+            pouw_emitted: storage.get_emitted(crate::epoch_report::COMPUTE_ASSET).map_err(internal)?,
+            max_pouw_emission: chain.emission.max_pouw_emission,
+        }
+    };
+    {
+        let l2 = safe_lock(&state.layer2)?;
+        info.staked = l2.collateral.stakes.values().fold(0u64, |sum, stake| sum.saturating_add(*stake));
+        info.unbonding = l2.collateral.unbonding.keys().map(|entity| l2.collateral.unbonding_total(entity)).sum();
+        info.burned = l2.economics.burned_tokens;
+    }
+    to_json(&info)
+}
+
+/// Handle getTreasury() - the treasury's balance, what it takes, its proposals and
+/// what it has spent
+async fn handle_get_treasury(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    use crate::treasury::{TREASURY_ACCOUNT, TREASURY_ASSET};
+
+    let chain = safe_lock(&state.chain)?;
+    let internal = |e: crate::error::CompassError| RpcError { code: -32603, message: e.to_string() };
+    let balance = chain.storage.get_balance(TREASURY_ACCOUNT, TREASURY_ASSET).map_err(internal)?;
+    let proposals = chain.treasury_proposals(crate::block::current_unix_timestamp_ms()).map_err(internal)?;
+
+    let (heights, _) = chain.storage.get_type_block_heights("TreasurySpend", 0, usize::MAX);
+    let mut spends = Vec::with_capacity(heights.len());
+    let mut total_spent: u64 = 0;
+    for height in heights {
+        let Some(block) = chain.storage.get_block_by_height(height).map_err(internal)? else { continue };
+        if let BlockType::TreasurySpend { proposal_id, recipient, amount } = block.header.block_type {
+            total_spent = total_spent.saturating_add(amount);
+            spends.push(serde_json::json!({
+                "proposal_id": proposal_id,
+                "recipient": recipient,
+                "amount": amount,
+                "height": height,
+                "timestamp": block.header.timestamp
+            }));
+        }
+    }
+
+    Ok(serde_json::json!({
+        "account": TREASURY_ACCOUNT,
+        "asset": TREASURY_ASSET,
+        "balance": balance,
+        "fee_percent": chain.treasury.fee_percent,
+        "slash_percent": chain.treasury.slash_percent,
+        "total_spent": total_spent,
+        "spends": spends,
+        "proposals": proposals
+    }))
+}
+
+/// Handle claimEarnings(worker, amount, nonce, signature) - pays claimable earnings
+/// into the worker's wallet through an EarningsClaim block
+async fn handle_claim_earnings(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ClaimEarningsParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    {
+        let chain = safe_lock(&state.chain)?;
+        let earnings = chain.storage.get_worker_earnings(&req.worker)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        earnings.check_claim(req.amount, chain.min_earnings_payout)
+            .map_err(|message| RpcError { code: -32602, message })?;
+    }
+
+    let payload = crate::network::TransactionPayload::ClaimEarnings {
+        worker: req.worker,
+        amount: req.amount,
+        nonce: req.nonce,
+        signature: req.signature,
+    };
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid claim signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "amount": req.amount,
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+fn stake_info(l2: &crate::layer2::Layer2State, entity: &str, slot: u64) -> StakeInfo {
+    let unbonding = l2.collateral.unbonding.get(entity).cloned().unwrap_or_default();
+    StakeInfo {
+        entity: entity.to_string(),
+        staked: l2.collateral.stakes.get(entity).copied().unwrap_or(0),
+        withdrawable: unbonding.iter().filter(|u| u.release_slot <= slot).map(|u| u.amount).sum(),
+        unbonding,
+        current_slot: slot,
+        unbonding_slots: l2.unbonding_slots,
+    }
+}
+
+/// Handle getStakeInfo(entity) - collateral staked and withdrawals unbonding
+async fn handle_get_stake_info(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: StakeInfoParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let slot = safe_lock(&state.chain)?.head_slot().map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    let l2 = safe_lock(&state.layer2)?;
+    to_json(&stake_info(&l2, &req.entity, slot))
+}
+
+/// Handle withdrawUnbonded(entity, nonce, signature) - pays the entity's unstaked
+/// collateral whose unbonding period has ended into its balance through an
+/// UnbondedWithdrawal block
+async fn handle_withdraw_unbonded(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: WithdrawUnbondedParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let withdrawable = {
+        let slot = safe_lock(&state.chain)?.head_slot().map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        safe_lock(&state.layer2)?.collateral.withdrawable(&req.entity, slot)
+    };
+    if withdrawable == 0 {
+        return Err(RpcError { code: -32602, message: "Nothing has finished unbonding".to_string() });
+    }
+
+    let payload = crate::network::TransactionPayload::WithdrawUnbonded {
+        entity: req.entity,
+        nonce: req.nonce,
+        signature: req.signature,
+    };
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid withdrawal signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "withdrawable": withdrawable,
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+fn decode_hex_param(name: &str, value: &str) -> Result<Vec<u8>, RpcError> {
+    hex::decode(value).map_err(|e| RpcError { code: -32602, message: format!("{} is not hex: {}", name, e) })
+}
+
+/// Handle deployContract(deployer, code, gas_limit, gas_price, nonce, signature) - queue a
+/// WASM contract for deployment; its address is known before it runs
+async fn handle_deploy_contract(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: DeployContractParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let code = decode_hex_param("code", &req.code)?;
+    if code.len() > crate::vm::MAX_CODE_BYTES {
+        return Err(RpcError { code: -32602, message: format!("Contract code exceeds {} bytes", crate::vm::MAX_CODE_BYTES) });
+    }
+
+    let address = crate::vm::contract_address(&req.deployer, req.nonce);
+    let payload = crate::network::TransactionPayload::DeployContract {
+        deployer: req.deployer,
+        code,
+        gas_limit: req.gas_limit,
+        gas_price: req.gas_price,
+        nonce: req.nonce,
+        signature: req.signature,
+    };
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid deployment signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "address": address,
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle callContract(caller, contract, method, input, gas_limit, gas_price, nonce, signature)
+/// - queue a state-changing call of a deployed contract
+async fn handle_call_contract(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: CallContractParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let input = decode_hex_param("input", &req.input)?;
+    if safe_lock(&state.chain)?.storage.get_contract(&req.contract)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .is_none()
+    {
+        return Err(RpcError { code: -32001, message: format!("No contract at {}", req.contract) });
+    }
+
+    let payload = crate::network::TransactionPayload::CallContract {
+        caller: req.caller,
+        contract: req.contract,
+        method: req.method,
+        input,
+        gas_limit: req.gas_limit,
+        gas_price: req.gas_price,
+        nonce: req.nonce,
+        signature: req.signature,
+    };
+    if !payload.verify() {
+        return Err(RpcError { code: -32003, message: "Invalid call signature".to_string() });
+    }
+
+    let raw_tx = safe_serialize(&payload)?;
+    let tx_hash = encoding::sha256(&raw_tx).to_vec();
+    admit_transaction(&state, tx_hash.clone(), raw_tx, 0)?;
+
+    Ok(serde_json::json!({
+        "status": "Submitted",
+        "tx_hash": encoding::to_hex(tx_hash)
+    }))
+}
+
+/// Handle queryContract(contract, method, input?, gas_limit?) - run a method
+/// against current state without a transaction; it may read state but not write it
+async fn handle_query_contract(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: QueryContractParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let input = decode_hex_param("input", &req.input)?;
+    let storage = safe_lock(&state.chain)?.storage.clone();
+    if storage.get_contract(&req.contract).map_err(|e| RpcError { code: -32603, message: e.to_string() })?.is_none() {
+        return Err(RpcError { code: -32001, message: format!("No contract at {}", req.contract) });
+    }
+
+    let gas_limit = req.gas_limit.unwrap_or(crate::vm::MAX_GAS_LIMIT);
+    let outcome = crate::vm::query(&storage, &req.contract, &req.method, &input, gas_limit)
+        .map_err(|e| RpcError { code: -32602, message: e.to_string() })?;
+    Ok(serde_json::json!({
+        "contract": outcome.contract,
+        "output": hex::encode(&outcome.output),
+        "gas_used": outcome.gas_used
+    }))
+}
+
+/// Handle getPoolGovernance(pool_id) - parameters, proposals and their current tallies
+async fn handle_get_pool_governance(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetPoolParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let pool_id = req.pool_id.ok_or(RpcError { code: -32602, message: "pool_id is required".to_string() })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let pool = chain.storage.get_model_pool(&pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32602, message: "Pool not found".to_string() })?;
+    let mut gov = chain.storage.get_pool_governance(&pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    // Report lapsed proposals as such without waiting for a vote to settle them
+    gov.settle_all(&pool, chrono::Utc::now().timestamp() as u64);
+
+    let tallies: Vec<_> = gov.proposals.iter().map(|p| gov.tally(&pool, p.id)).collect();
+    Ok(serde_json::json!({
+        "pool_id": pool_id,
+        "params": gov.params,
+        "next_proposal_id": gov.next_proposal_id,
+        "proposals": gov.proposals,
+        "tallies": tallies
+    }))
+}
+
+/// Handle proposePoolChange(pool_id, proposer, change, signature)
+async fn handle_propose_pool_change(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ProposePoolChangeParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let pool = chain.storage.get_model_pool(&req.pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32602, message: "Pool not found".to_string() })?;
+    let mut gov = chain.storage.get_pool_governance(&req.pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let proposal_id = gov
+        .propose(&pool, &req.proposer, req.change, &req.signature, now)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+    chain.storage.save_pool_governance(&gov).map_err(|e| RpcError {
+        code: -32603,
+        message: format!("Failed to save proposal: {}", e),
+    })?;
+
+    info!("Pool {} proposal {} opened by {}", req.pool_id, proposal_id, req.proposer);
+    Ok(serde_json::json!({
+        "status": "Open",
+        "proposal_id": proposal_id,
+        "closes_at": now + crate::layer3::collective::POOL_VOTING_PERIOD_SECS
+    }))
+}
+
+/// Handle votePoolProposal(pool_id, proposal_id, voter, approve, signature) - stake-weighted;
+/// a vote that decides the proposal executes or rejects it
+async fn handle_vote_pool_proposal(
     state: RpcState,
     params: serde_json::Value,
 ) -> Result<serde_json::Value, RpcError> {
-    let req: ClaimDividendsParams = serde_json::from_value(params).map_err(|e| RpcError {
+    let req: VotePoolProposalParams = serde_json::from_value(params).map_err(|e| RpcError {
         code: -32602,
         message: format!("Invalid params: {}", e),
     })?;
 
     let chain = safe_lock(&state.chain)?;
-    
-    let mut pool = chain.storage.get_model_pool(&req.pool_id)
+    let pool = chain.storage.get_model_pool(&req.pool_id)
         .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
         .ok_or(RpcError { code: -32602, message: "Pool not found".to_string() })?;
-        
-    let share = pool.get_share(&req.contributor);
-    if share <= 0.0 {
-        return Err(RpcError { code: -32602, message: "No stake in pool".to_string() });
-    }
-    
-    // Calculate Payout
-    let vault_bal = pool.vault_balance;
-    if vault_bal == 0 {
-         return Ok(serde_json::json!({
-             "status": "No Dividends",
-             "message": "Vault is empty"
-         }));
-    }
-    
-    // Simple logic: Payout everything based on share? 
-    // Usually need "claimed" tracking. For v1:
-    // Payout share of current vault, deduct from vault.
-    
-    let payout = (vault_bal as f64 * share) as u64;
-    
-    if payout > 0 {
-        // Credit User
-        let user_bal = chain.storage.get_balance(&req.contributor, "COMPUTE").unwrap_or(0);
-        chain.storage.update_balance(&req.contributor, "COMPUTE", user_bal + payout).ok();
-        
-        // Deduct from Pool Vault
-        pool.vault_balance = pool.vault_balance.saturating_sub(payout);
-        chain.storage.save_model_pool(&pool).ok(); // Save updated vault
-        
-        info!("?? Dividends Claimed: {} COMPUTE to {} (Pool: {})", payout, req.contributor, req.pool_id);
+    let mut gov = chain.storage.get_pool_governance(&req.pool_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+
+    let now = chrono::Utc::now().timestamp() as u64;
+    let status = gov
+        .vote(&pool, req.proposal_id, &req.voter, req.approve, &req.signature, now)
+        .map_err(|e| RpcError { code: -32003, message: e })?;
+    chain.storage.save_pool_governance(&gov).map_err(|e| RpcError {
+        code: -32603,
+        message: format!("Failed to save vote: {}", e),
+    })?;
+
+    if status == crate::layer3::collective::PoolProposalStatus::Executed {
+        info!("Pool {} proposal {} executed; params now {:?}", req.pool_id, req.proposal_id, gov.params);
     }
-    
     Ok(serde_json::json!({
-        "status": "Claimed",
-        "amount": payout,
-        "currency": "COMPUTE"
+        "status": status,
+        "tally": gov.tally(&pool, req.proposal_id),
+        "params": gov.params
     }))
 }
 
-
 /// Handle getMyModels(owner)
 async fn handle_get_my_models(
     state: RpcState,
@@ -2605,6 +5577,204 @@ async fn handle_get_portfolio_summary(state: RpcState) -> Result<serde_json::Val
     }))
 }
 
+// ============================================================
+// PAPER TRADING COMPETITIONS
+// ============================================================
+
+/// Handle createCompetition(competition_id, name, creator, start_time, end_time, ...) - admin only
+async fn handle_create_competition(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::competition::{Competition, DEFAULT_PAYOUT_BPS};
+    use crate::layer3::price_oracle::SUPPORTED_TICKERS;
+    let req: CreateCompetitionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    if req.creator != state.node_identity && req.creator != "admin" {
+        return Err(RpcError {
+            code: -32003,
+            message: "Unauthorized: Only Admin can create competitions".to_string(),
+        });
+    }
+
+    let competition = Competition::new(
+        req.competition_id,
+        req.name,
+        req.creator,
+        req.start_time,
+        req.end_time,
+        req.epoch_secs.unwrap_or(3600),
+        req.fee_asset.unwrap_or_else(|| "COMPASS".to_string()),
+        req.entry_fee,
+        req.starting_capital.unwrap_or(10_000.0),
+        req.payout_bps.unwrap_or_else(|| DEFAULT_PAYOUT_BPS.to_vec()),
+        req.tickers.unwrap_or_else(|| SUPPORTED_TICKERS.iter().map(|t| t.to_string()).collect()),
+    )
+    .map_err(|e| RpcError { code: -32602, message: e })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let exists = chain.storage.get_competition(&competition.competition_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .is_some();
+    if exists {
+        return Err(RpcError { code: -32004, message: "Competition ID already taken".into() });
+    }
+    chain.storage.save_competition(&competition)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save competition: {}", e) })?;
+
+    info!("🏆 Competition created: {} ({} -> {})", competition.competition_id, competition.start_time, competition.end_time);
+    to_json(&competition)
+}
+
+/// Handle joinCompetition(competition_id, account, agent_id) - entry fee moves into the prize pool
+async fn handle_join_competition(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::competition::COMPETITION_POOL_ACCOUNT;
+    let req: JoinCompetitionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let mut chain = safe_lock(&state.chain)?;
+    let mut competition = chain.storage.get_competition(&req.competition_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Competition not found".into() })?;
+
+    let asset = competition.fee_asset.clone();
+    let fee = competition.entry_fee;
+    let balance = chain.storage.get_balance(&req.account, &asset).unwrap_or(0);
+    if balance < fee {
+        return Err(RpcError {
+            code: -32002,
+            message: format!("Insufficient balance. Have: {}, Need: {}", balance, fee),
+        });
+    }
+
+    competition.join(&req.account, &req.agent_id, now)
+        .map_err(|e| RpcError { code: -32004, message: e })?;
+
+    let entry = SettlementLeg::Transfer {
+        from: req.account.clone(),
+        to: COMPETITION_POOL_ACCOUNT.to_string(),
+        asset: asset.clone(),
+        amount: fee,
+    };
+    chain.settle(vec![entry], &format!("entry to {}", competition.competition_id))
+        .map_err(|e| RpcError { code: -32603, message: format!("Storage error: {}", e) })?;
+    chain.storage.save_competition(&competition)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save competition: {}", e) })?;
+
+    info!("🏆 {} entered {} with {}", req.account, competition.competition_id, req.agent_id);
+    Ok(serde_json::json!({
+        "status": "joined",
+        "competition_id": competition.competition_id,
+        "entrants": competition.entrants.len(),
+        "prize_pool": competition.prize_pool,
+        "starting_capital": competition.starting_capital
+    }))
+}
+
+/// Handle submitCompetitionTrade(competition_id, account, ticker, side, size) - filled at the live oracle price
+async fn handle_submit_competition_trade(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    use crate::layer3::paper_trading::TradingSignal;
+    let req: SubmitCompetitionTradeParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let signal = match req.side.to_lowercase().as_str() {
+        "buy" => Some(TradingSignal::Buy),
+        "sell" => Some(TradingSignal::Sell),
+        "close" => None,
+        other => return Err(RpcError { code: -32602, message: format!("Invalid side: {}", other) }),
+    };
+
+    // Price outside the lock
+    let price = crate::node::competition_scheduler::mark_price(&state.chain, &req.ticker).await
+        .map_err(|e| RpcError { code: -32603, message: format!("Price fetch failed: {}", e) })?;
+
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let chain = safe_lock(&state.chain)?;
+    let mut competition = chain.storage.get_competition(&req.competition_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Competition not found".into() })?;
+
+    match signal {
+        Some(signal) => competition.open_position(&req.account, &req.ticker, signal, req.size, price, now),
+        None => competition.close_position(&req.account, &req.ticker, price, now),
+    }
+    .map_err(|e| RpcError { code: -32004, message: e })?;
+
+    chain.storage.save_competition(&competition)
+        .map_err(|e| RpcError { code: -32603, message: format!("Failed to save competition: {}", e) })?;
+
+    let balance = competition.entrants.iter()
+        .find(|e| e.account == req.account)
+        .map(|e| e.portfolio.current_balance)
+        .unwrap_or(0.0);
+    Ok(serde_json::json!({
+        "status": "filled",
+        "ticker": req.ticker,
+        "side": req.side.to_lowercase(),
+        "price": price,
+        "cash_balance": balance
+    }))
+}
+
+/// Handle getCompetition(competition_id) - details, leaderboard and payouts
+async fn handle_get_competition(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: GetCompetitionParams = serde_json::from_value(params)
+        .map_err(|e| RpcError { code: -32602, message: format!("Invalid params: {}", e) })?;
+
+    let chain = safe_lock(&state.chain)?;
+    let competition = chain.storage.get_competition(&req.competition_id)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+        .ok_or(RpcError { code: -32001, message: "Competition not found".into() })?;
+
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    Ok(serde_json::json!({
+        "competition_id": competition.competition_id,
+        "name": competition.name,
+        "status": competition.status_at(now),
+        "start_time": competition.start_time,
+        "end_time": competition.end_time,
+        "epoch": competition.last_epoch,
+        "entry_fee": competition.entry_fee,
+        "fee_asset": competition.fee_asset,
+        "prize_pool": competition.prize_pool,
+        "payout_bps": competition.payout_bps,
+        "tickers": competition.tickers,
+        "entrants": competition.entrants.len(),
+        "leaderboard": competition.leaderboard,
+        "payouts": competition.payouts,
+    }))
+}
+
+/// Handle getCompetitions()
+async fn handle_get_competitions(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let chain = safe_lock(&state.chain)?;
+    let competitions: Vec<serde_json::Value> = chain.storage.get_all_competitions()
+        .into_iter()
+        .map(|c| serde_json::json!({
+            "competition_id": c.competition_id,
+            "name": c.name,
+            "status": c.status_at(now),
+            "start_time": c.start_time,
+            "end_time": c.end_time,
+            "entrants": c.entrants.len(),
+            "prize_pool": c.prize_pool,
+        }))
+        .collect();
+    Ok(serde_json::json!(competitions))
+}
+
 /// Handle submitOraclePrice (v2.0)
 /// Allows registered oracles to submit price feeds
 async fn handle_submit_oracle_price(
@@ -2619,23 +5789,26 @@ async fn handle_submit_oracle_price(
             message: format!("Invalid params: {}", e),
         })?;
     
-    // 1. Verify oracle is registered and active
-    {
+    // 1. Verify oracle is registered and active, and signed with its key
+    let stake = {
         let chain = safe_lock(&state.chain)?;
         let oracle_reg = chain.oracle_registry.lock().unwrap();
         
-        if !oracle_reg.is_oracle(&submission.oracle_account) {
-            return Err(RpcError {
+        let oracle = oracle_reg
+            .get_oracle(&submission.oracle_account)
+            .filter(|o| o.is_active())
+            .ok_or_else(|| RpcError {
                 code: -32001,
                 message: "Oracle not registered or inactive".to_string(),
-            });
+            })?;
+        if !submission.is_signed_by(oracle) {
+            return Err(RpcError { code: -32602, message: "Invalid oracle price signature".to_string() });
         }
-    }
-    
-    // 2. TODO: Verify signature (for now, accept if registered)
+        oracle.stake_amount
+    };
     
-    // 3. Store price in vault manager (existing system)
-    {
+    // 2. Check the sanity bands, then add the price to this round's median
+    let (round, aggregate) = {
         let mut chain = safe_lock(&state.chain)?;
         
         // Convert f64 to Decimal properly
@@ -2645,28 +5818,208 @@ async fn handle_submit_oracle_price(
                 code: -32602,
                 message: "Invalid price value".to_string(),
             })?;
+
+        // Sanity bands: large moves wait for other oracles, outliers are refused
+        // and, once several oracles report them, pause the ticker
+        use crate::oracle::circuit_breaker::PriceVerdict;
+        let mut guard = chain.storage.get_price_guard(&submission.ticker)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        let now = crate::block::current_unix_timestamp_ms() / 1000;
+        let verdict = guard.submit(&submission.oracle_account, price_decimal, now, &chain.oracle_guard);
+        chain.storage.save_price_guard(&guard)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+        match verdict {
+            PriceVerdict::Accepted => {}
+            PriceVerdict::AwaitingConfirmation { confirmations, required } => {
+                return Ok(serde_json::json!({
+                    "status": "pending_confirmation",
+                    "ticker": submission.ticker,
+                    "price": submission.price,
+                    "confirmations": confirmations,
+                    "required": required
+                }));
+            }
+            PriceVerdict::Rejected { deviation_bps } => {
+                warn!("Oracle price {} = {} from {} rejected: {} bps off TWAP", submission.ticker, submission.price, submission.oracle_account, deviation_bps);
+                return Err(RpcError {
+                    code: -32004,
+                    message: format!("Price deviates {} bps from the TWAP", deviation_bps),
+                });
+            }
+            PriceVerdict::Tripped { deviation_bps } => {
+                warn!("⛔ Oracle circuit breaker tripped for {}: {} bps off TWAP", submission.ticker, deviation_bps);
+                record_audit(
+                    &chain.storage,
+                    &submission.oracle_account,
+                    crate::audit::AuditAction::OracleCircuitBreaker,
+                    format!("tripped ticker={} price={} deviation_bps={}", submission.ticker, submission.price, deviation_bps),
+                );
+                return Err(RpcError {
+                    code: -32004,
+                    message: format!("Price deviates {} bps from the TWAP; {} paused pending governance review", deviation_bps, submission.ticker),
+                });
+            }
+            PriceVerdict::Paused => {
+                return Err(RpcError {
+                    code: -32004,
+                    message: format!("{} is paused by the oracle circuit breaker", submission.ticker),
+                });
+            }
+        }
         
-        // Update oracle price in vault manager
-        chain.vault_manager.oracle_prices.insert(
-            submission.ticker.clone(),
-            (price_decimal, submission.timestamp),
+        record_audit(
+            &chain.storage,
+            &submission.oracle_account,
+            crate::audit::AuditAction::OracleSignature,
+            format!("price ticker={} price={} ts={}", submission.ticker, submission.price, submission.timestamp),
         );
-        
-        // Save vault state
-        chain.vault_manager.save("").map_err(|e| RpcError {
-            code: -32603,
-            message: format!("Failed to save oracle price: {}", e),
-        })?;
+
+        let round_no = chain.oracle_aggregation.round_at(now);
+        let mut round = chain.storage.get_price_round(&submission.ticker, round_no)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?
+            .unwrap_or_else(|| crate::oracle::aggregation::PriceRound::new(&submission.ticker, round_no));
+        let contribution = crate::oracle::aggregation::PriceContribution {
+            oracle: submission.oracle_account.clone(),
+            price: price_decimal,
+            stake,
+            received_at: now,
+        };
+        let aggregate = round.submit(contribution, &chain.oracle_aggregation).cloned();
+        chain.storage.save_price_round(&round)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+
+        // 3. Publish the round's price to the vaults (existing system)
+        if let Some(aggregate) = &aggregate {
+            chain.vault_manager.publish_price(&submission.ticker, aggregate.price, submission.timestamp);
+            chain.vault_manager.save("").map_err(|e| RpcError {
+                code: -32603,
+                message: format!("Failed to save oracle price: {}", e),
+            })?;
+        }
+        (round, aggregate)
+    };
+
+    let Some(aggregate) = aggregate else {
+        return Ok(serde_json::json!({
+            "status": "pending_quorum",
+            "ticker": submission.ticker,
+            "price": submission.price,
+            "round": round.round,
+            "submissions": round.submissions.len(),
+        }));
+    };
+    if round.is_rejected(&submission.oracle_account) {
+        warn!("Oracle price {} = {} from {} left out of round {}: too far from the median", submission.ticker, submission.price, submission.oracle_account, round.round);
+        return Err(RpcError {
+            code: -32004,
+            message: format!("Price is more than the allowed deviation from round {}'s median {}", round.round, aggregate.price),
+        });
     }
-    
-    info!("🔮 Oracle Price Update: {} = ${:.2} (from {})", 
-        submission.ticker, submission.price, submission.oracle_account);
+
+    info!("🔮 Oracle Price Update: {} = {} (round {}, {} oracles, latest from {})",
+        submission.ticker, aggregate.price, round.round, aggregate.contributors.len(), submission.oracle_account);
     
     Ok(serde_json::json!({
         "status": "accepted",
         "ticker": submission.ticker,
         "price": submission.price,
-        "oracle": submission.oracle_account
+        "oracle": submission.oracle_account,
+        "round": round.round,
+        "aggregate_price": aggregate.price.to_string(),
+        "contributors": aggregate.contributors,
+    }))
+}
+
+/// Handle getPriceRound(ticker, round?) - one aggregation round's submissions and
+/// published price; the latest round that published one by default
+async fn handle_get_price_round(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetPriceRoundParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let chain = safe_lock(&state.chain)?;
+    let round = match p.round {
+        Some(round) => chain.storage.get_price_round(&p.ticker, round)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?,
+        None => chain.storage.get_latest_price_round(&p.ticker),
+    };
+    let round = round.ok_or(RpcError { code: -32001, message: format!("No price round for {}", p.ticker) })?;
+    to_json(&round)
+}
+
+/// Handle getPriceAttestation(ticker, round?) - the node's signed price for a closed
+/// round, the latest one by default
+async fn handle_get_price_attestation(state: RpcState, params: serde_json::Value) -> Result<serde_json::Value, RpcError> {
+    let p: GetPriceAttestationParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let chain = safe_lock(&state.chain)?;
+    let attestation = match p.round {
+        Some(round) => chain.storage.get_price_attestation(&p.ticker, round)
+            .map_err(|e| RpcError { code: -32603, message: e.to_string() })?,
+        None => chain.storage.get_latest_price_attestation(&p.ticker),
+    };
+    let attestation = attestation.ok_or(RpcError { code: -32001, message: format!("No price attestation for {}", p.ticker) })?;
+    Ok(serde_json::json!({
+        "hash": attestation.hash(),
+        "message": attestation.message(),
+        "attestation": attestation,
+    }))
+}
+
+/// Handle getOracleBreakers() - TWAP and breaker state of every guarded ticker
+async fn handle_get_oracle_breakers(state: RpcState) -> Result<serde_json::Value, RpcError> {
+    let chain = safe_lock(&state.chain)?;
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let guards: Vec<_> = chain.storage.get_all_price_guards()
+        .into_iter()
+        .map(|guard| serde_json::json!({
+            "ticker": guard.ticker,
+            "twap": guard.twap(now, chain.oracle_guard.twap_window_secs).map(|p| p.to_string()),
+            "paused": guard.is_paused(),
+            "trip": guard.tripped,
+            "pending": guard.pending,
+        }))
+        .collect();
+    Ok(serde_json::json!({ "config": chain.oracle_guard, "tickers": guards }))
+}
+
+/// Handle resumeOracleFeed(ticker, price?) - governance clears a tripped breaker,
+/// restarting the TWAP from `price` or from the price that tripped it
+async fn handle_resume_oracle_feed(
+    state: RpcState,
+    params: serde_json::Value,
+) -> Result<serde_json::Value, RpcError> {
+    let req: ResumeOracleFeedParams = serde_json::from_value(params).map_err(|e| RpcError {
+        code: -32602,
+        message: format!("Invalid params: {}", e),
+    })?;
+    let price = req.price
+        .map(|p| rust_decimal::Decimal::from_f64_retain(p).ok_or(RpcError { code: -32602, message: "Invalid price value".to_string() }))
+        .transpose()?;
+
+    let chain = safe_lock(&state.chain)?;
+    let mut guard = chain.storage.get_price_guard(&req.ticker)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    let now = crate::block::current_unix_timestamp_ms() / 1000;
+    let trip = guard.resume(price, now)
+        .ok_or(RpcError { code: -32004, message: format!("{} is not paused", req.ticker) })?;
+    chain.storage.save_price_guard(&guard)
+        .map_err(|e| RpcError { code: -32603, message: e.to_string() })?;
+    record_audit(
+        &chain.storage,
+        &state.node_identity,
+        crate::audit::AuditAction::OracleCircuitBreaker,
+        format!("resumed ticker={} from={}", req.ticker, guard.samples[0].1),
+    );
+    info!("✅ Oracle feed {} resumed at {}", req.ticker, guard.samples[0].1);
+
+    Ok(serde_json::json!({
+        "status": "Resumed",
+        "ticker": req.ticker,
+        "price": guard.samples[0].1.to_string(),
+        "trip": trip
     }))
 }
 
@@ -2726,11 +6079,12 @@ async fn handle_get_account_balances(
         account: String,
     }
     
-    let req: BalanceParams = serde_json::from_value(params)
+    let mut req: BalanceParams = serde_json::from_value(params)
         .map_err(|e| RpcError {
             code: -32602,
             message: format!("Invalid params: {}", e),
         })?;
+    req.account = account_id_param(&req.account)?;
     
     let chain = safe_lock(&state.chain)?;
     
@@ -2849,11 +6203,13 @@ async fn handle_get_market_listings(
 // Add to end of src/rpc/handlers.rs (before the final closing brace)
 
 // Phase 7: Model Training RPC Handler
+#[cfg(feature = "ai")]
 #[derive(serde::Deserialize)]
 struct TrainModelParams {
     ticker: String,
 }
 
+#[cfg(feature = "ai")]
 async fn handle_train_model(
     _state: RpcState,
     params: Option<serde_json::Value>,
@@ -2895,3 +6251,416 @@ async fn handle_train_model(
     }))
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::gulf_stream::CompassGulfStreamManager;
+
+    fn test_state() -> RpcState {
+        let (cmd_tx, _cmd_rx) = tokio::sync::mpsc::channel(1);
+        RpcState {
+            chain: Arc::new(Mutex::new(Chain::new_in_memory())),
+            peer_manager: Arc::new(Mutex::new(crate::network::PeerManager::new(0))),
+            gulf_stream: Arc::new(Mutex::new(CompassGulfStreamManager::new("test".to_string(), 16))),
+            vault_manager: Arc::new(Mutex::new(crate::vault::VaultManager::new())),
+            wallet_manager: Arc::new(Mutex::new(crate::wallet::WalletManager::new())),
+            layer2: Arc::new(Mutex::new(crate::layer2::Layer2State::new(None))),
+            betting_ledger: Arc::new(Mutex::new(crate::layer3::betting::BettingLedger::new())),
+            market: Arc::new(Mutex::new(crate::market::Market::new())),
+            cmd_tx,
+            node_identity: String::new(),
+            faucet: None,
+            sessions: Arc::default(),
+            ipfs_gateway: crate::layer3::nft_metadata::IPFS_GATEWAY.to_string(),
+        }
+    }
+
+    async fn call(body: serde_json::Value) -> serde_json::Value {
+        let Json(reply) = handle_rpc_request(State(test_state()), ConnectInfo(([127, 0, 0, 1], 9000).into()), Json(body)).await;
+        serde_json::to_value(reply).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_batch_answers_each_call_in_order() {
+        let reply = call(serde_json::json!([
+            { "jsonrpc": "2.0", "method": "getChainHeight", "params": null, "id": 7 },
+            { "jsonrpc": "2.0", "method": "noSuchMethod", "params": null, "id": 8 },
+            { "jsonrpc": "2.0", "params": null, "id": 9 },
+            { "jsonrpc": "2.0", "method": "getVersion", "params": null, "id": 10 },
+        ])).await;
+
+        let responses = reply.as_array().unwrap();
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["id"], 7);
+        assert_eq!(responses[0]["result"]["height"], 0);
+        assert_eq!(responses[1]["error"]["code"], -32601);
+        assert_eq!(responses[2]["id"], 9);
+        assert_eq!(responses[2]["error"]["code"], -32600);
+        assert_eq!(responses[3]["id"], 10);
+        assert!(responses[3].get("result").is_some());
+    }
+
+    #[tokio::test]
+    async fn test_client_id_deduplicates_retries() {
+        let state = test_state();
+        let submit = |method: &str| RpcRequest {
+            jsonrpc: "2.0".into(),
+            method: method.into(),
+            params: serde_json::json!({ "client_id": "gui-42" }),
+            id: 1,
+        };
+
+        // A failed attempt frees the id, so the retry runs (and fails) again
+        for _ in 0..2 {
+            let reply = dispatch(state.clone(), submit("submitTransaction")).await;
+            assert_eq!(reply.error.unwrap().code, -32602);
+        }
+
+        // A completed one is answered from the record
+        {
+            let chain = state.chain.lock().unwrap();
+            let first = crate::storage::ClientSubmission {
+                method: "submitTransaction".into(),
+                response: Some(serde_json::json!({ "status": "queued", "tx_hash": "ab".repeat(32) }).to_string()),
+                submitted_at: std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64,
+            };
+            chain.storage.complete_client_id("", "gui-42", &first).unwrap();
+        }
+        let replay = dispatch(state.clone(), submit("submitTransaction")).await.result.unwrap();
+        assert_eq!(replay["duplicate"], true);
+        assert_eq!(replay["tx_hash"], "ab".repeat(32));
+        assert_eq!(replay["tx_status"], "Unknown");
+
+        // The id can't be reused for another method
+        let reply = dispatch(state.clone(), submit("submitMint")).await;
+        assert_eq!(reply.error.unwrap().code, -32602);
+
+        // Ids belong to their sender: bob's gui-42 is his own
+        let storage = state.chain.lock().unwrap().storage.clone();
+        let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_millis() as u64;
+        assert!(storage.reserve_client_id("bob", "gui-42", "submitMint", now).unwrap().is_none());
+
+        // A claim whose attempt never finished stops blocking retries after a while
+        assert_eq!(storage.reserve_client_id("bob", "gui-42", "submitMint", now).unwrap().unwrap().response, None);
+        let later = now + crate::storage::CLIENT_ID_CLAIM_TIMEOUT_MS;
+        assert!(storage.reserve_client_id("bob", "gui-42", "submitMint", later).unwrap().is_none());
+
+        // Records past the window are collected
+        assert_eq!(storage.prune_client_ids(later).unwrap(), 0);
+        assert_eq!(storage.prune_client_ids(now + crate::storage::CLIENT_ID_WINDOW_MS).unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_password_accounts_need_a_session_to_transfer() {
+        let state = test_state();
+        {
+            let chain = state.chain.lock().unwrap();
+            let mut store = chain.account_store.lock().unwrap();
+            let user = crate::account::AccountType::User(Default::default());
+            store.create_account("alice".into(), "correct horse battery".into(), user).unwrap();
+        }
+        let call = |method: &str, params: serde_json::Value| {
+            dispatch(state.clone(), RpcRequest { jsonrpc: "2.0".into(), method: method.into(), params, id: 1 })
+        };
+        let transfer = |token: &str| {
+            serde_json::json!({
+                "from": "alice", "to": "bob", "asset": "Compass", "amount": 1, "nonce": 1,
+                "signature": "00", "public_key": "00", "session_token": token
+            })
+        };
+
+        let wrong = call("login", serde_json::json!({ "account": "alice", "password": "guess" })).await;
+        assert_eq!(wrong.error.unwrap().code, -32003);
+        let refused = call("submitTransaction", transfer("")).await.error.unwrap();
+        assert_eq!((refused.code, refused.message.as_str()), (-32003, "session token required: log in first"));
+        // Every other way of spending from alice goes through the same check
+        let mut sponsored = transfer("");
+        sponsored["fee_payer"] = "carol".into();
+        let refused = call("submitSponsoredTransaction", sponsored).await.error.unwrap();
+        assert_eq!(refused.message, "session token required: log in first");
+        let payload: crate::network::TransactionPayload = serde_json::from_value(serde_json::json!({
+            "Transfer": {
+                "from": "alice", "to": "bob", "asset": "Compass", "amount": 1, "nonce": 1, "fee": 0,
+                "signature": "00", "public_key": "00", "timestamp": 0, "prev_hash": ""
+            }
+        }))
+        .unwrap();
+        let envelope = crate::network::SignedTransaction::sign(payload, 1, "", &crate::crypto::KeyPair::from_seed("alice"));
+        let refused = call("submitSignedTransaction", serde_json::to_value(&envelope).unwrap()).await.error.unwrap();
+        assert_eq!(refused.message, "session token required: log in first");
+
+        let login = call("login", serde_json::json!({ "account": "alice", "password": "correct horse battery" })).await;
+        let token = login.result.unwrap()["token"].as_str().unwrap().to_string();
+        // Past the session check; admission checks the rest
+        let submitted = call("submitTransaction", transfer(&token)).await;
+        assert!(submitted.error.is_none_or(|e| !e.message.contains("session")));
+
+        let logout = call("logout", serde_json::json!({ "token": token })).await;
+        assert_eq!(logout.result.unwrap()["revoked"], true);
+        let revoked = call("submitTransaction", transfer(&token)).await.error.unwrap();
+        assert_eq!(revoked.message, "unknown or revoked session token");
+    }
+
+    #[tokio::test]
+    async fn test_addresses_resolve_to_names_and_keys() {
+        let state = test_state();
+        let pubkey = {
+            let chain = state.chain.lock().unwrap();
+            let mut store = chain.account_store.lock().unwrap();
+            let user = crate::account::AccountType::User(Default::default());
+            let account = store.create_account("alice".into(), "correct horse battery".into(), user).unwrap();
+            chain.storage.writer().set_balance(&account.signing_pubkey, "Compass", 42).unwrap();
+            account.signing_pubkey
+        };
+        let address = encoding::account_bech32_address(&pubkey).unwrap();
+
+        let by_name = handle_resolve_name(state.chain.clone(), serde_json::json!({ "name": "alice" })).await.unwrap();
+        assert_eq!(by_name["address"], address);
+        let by_address = handle_resolve_name(state.chain.clone(), serde_json::json!({ "name": address })).await.unwrap();
+        assert_eq!((by_address["name"].as_str(), by_address["signing_pubkey"].as_str()), (Some("alice"), Some(pubkey.as_str())));
+
+        let balance = handle_get_balance(state.chain.clone(), serde_json::json!({ "wallet_id": address, "asset": "Compass" })).await.unwrap();
+        assert_eq!(balance["balance"], 42);
+
+        let mut typo = address.clone();
+        let last = typo.pop().unwrap();
+        typo.push(if last == 'q' { 'p' } else { 'q' });
+        let err = handle_get_balance(state.chain.clone(), serde_json::json!({ "wallet_id": typo, "asset": "Compass" })).await.unwrap_err();
+        assert_eq!(err.code, -32602);
+
+        // Signed transfers must name the hex key the signature covers
+        let transfer = serde_json::json!({
+            "from": "bob", "to": address, "asset": "Compass", "amount": 1, "nonce": 1,
+            "signature": "00", "public_key": "00"
+        });
+        let err = handle_submit_transaction(state, transfer).await.unwrap_err();
+        assert!(err.message.contains(&pubkey), "{}", err.message);
+    }
+
+    #[tokio::test]
+    async fn test_account_info_lists_balances_and_pending() {
+        let state = test_state();
+        {
+            let chain = state.chain.lock().unwrap();
+            chain.storage.writer().set_balance("alice", "Compass", 7).unwrap();
+            chain.storage.writer().set_balance("alice", "COMPUTE", 3).unwrap();
+            chain.storage.writer().set_balance("bob", "Compass", 9).unwrap();
+        }
+        {
+            let mut gs = state.gulf_stream.lock().unwrap();
+            for (hash, sender) in [(vec![1u8; 32], "alice"), (vec![2u8; 32], "bob")] {
+                let mut tx = crate::gulf_stream::transactions::CompassGulfStreamTransaction::new(hash.clone(), vec![], 5);
+                tx.sender = Some(sender.to_string());
+                tx.nonce = Some(1);
+                gs.pending_transactions.insert(hash, tx);
+            }
+        }
+
+        let info = handle_get_account_info(state, serde_json::json!({ "wallet_id": "alice" })).await.unwrap();
+        assert_eq!(info["balances"], serde_json::json!({ "COMPUTE": 3, "Compass": 7 }));
+        assert_eq!(info["staked"]["collateral"], 0);
+        let pending = info["pending_transactions"].as_array().unwrap();
+        assert_eq!(pending.len(), 1);
+        assert_eq!(pending[0]["tx_hash"], "01".repeat(32));
+    }
+
+    #[tokio::test]
+    async fn test_search_matches_tokens_and_accounts() {
+        let state = test_state();
+        {
+            let chain = state.chain.lock().unwrap();
+            chain.storage.writer().set_balance("alice", "Compass:Alice:LTC", 5).unwrap();
+            chain.storage.writer().set_balance("bob", "Compass", 9).unwrap();
+        }
+
+        let found = handle_search(state.chain.clone(), serde_json::json!({ "query": "alice:ltc" })).await.unwrap();
+        let hits: Vec<SearchHit> = serde_json::from_value(found["results"].clone()).unwrap();
+        assert_eq!(hits, vec![SearchHit { kind: "token".into(), id: "Compass:Alice:LTC".into(), label: "Compass:Alice:LTC".into() }]);
+
+        let found = handle_search(state.chain.clone(), serde_json::json!({ "query": "bob" })).await.unwrap();
+        assert_eq!(found["results"][0]["kind"], "account");
+        assert!(handle_search(state.chain, serde_json::json!({ "query": "  " })).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_invoke_model_charges_per_call_and_queues_job() {
+        use crate::crypto::KeyPair;
+        use crate::layer3::model_nft::{invoke_message, ModelNFT, ModelStats};
+
+        let state = test_state();
+        let caller = KeyPair::generate();
+        let stats = ModelStats {
+            accuracy: 0.6, win_rate: 0.5, total_predictions: 0, profitable_predictions: 0, total_profit: 0,
+            training_samples: 1, training_epochs: 1, final_loss: 0.1, training_duration: 1, data_hash: String::new(),
+        };
+        let mut nft = ModelNFT::from_job("job", "BTC", "creator".to_string(), &stats);
+        nft.current_owner = "owner".to_string();
+        {
+            let chain = state.chain.lock().unwrap();
+            chain.storage.save_model_nft(&nft).unwrap();
+            chain.storage.writer().set_balance(&caller.public_key_hex(), "COMPASS", 100).unwrap();
+        }
+
+        let inputs = serde_json::json!([[1.0, 2.0]]);
+        let timestamp = crate::block::current_unix_timestamp_ms();
+        let msg = invoke_message(&nft.token_id, &caller.public_key_hex(), &serde_json::to_vec(&inputs).unwrap(), timestamp);
+        let params = serde_json::json!({
+            "token_id": nft.token_id,
+            "caller": caller.public_key_hex(),
+            "inputs": inputs,
+            "timestamp": timestamp,
+            "signature": caller.sign_hex(msg.as_bytes()),
+            "mode": "worker",
+        });
+
+        let mut forged = params.clone();
+        forged["signature"] = serde_json::json!(KeyPair::generate().sign_hex(msg.as_bytes()));
+        assert_eq!(handle_invoke_model(state.clone(), forged).await.unwrap_err().code, -32003);
+
+        let res = handle_invoke_model(state.clone(), params.clone()).await.unwrap();
+        assert_eq!(res["entitlement"], "pay_per_call");
+        assert_eq!(res["status"], "Queued");
+        assert_eq!((res["fee"].as_u64(), res["royalty"].as_u64()), (Some(10), Some(1)));
+
+        // The fee waits in the pool for its block rather than being taken here
+        let tx_hash = crate::encoding::from_hex(res["tx_hash"].as_str().unwrap()).unwrap();
+        assert!(state.gulf_stream.lock().unwrap().pending_transactions.contains_key(&tx_hash));
+        {
+            let chain = state.chain.lock().unwrap();
+            assert_eq!(chain.storage.get_balance(&caller.public_key_hex(), "COMPASS").unwrap(), 100);
+            assert!(chain.storage.get_compute_job(res["job_id"].as_str().unwrap()).unwrap().is_some());
+            let request_id = crate::layer3::model_nft::invoke_request_id(
+                &nft.token_id, &caller.public_key_hex(), &serde_json::to_vec(&inputs).unwrap(), timestamp,
+            );
+            chain.storage.mark_invocation_settled(&request_id, 1).unwrap();
+        }
+        assert_eq!(handle_invoke_model(state.clone(), params).await.unwrap_err().code, -32003, "a settled request is not served again");
+    }
+
+    #[tokio::test]
+    async fn test_job_result_is_creator_only_and_expires() {
+        use crate::crypto::KeyPair;
+        use crate::layer3::compute::{job_result_request_message, ComputeJob, JobResult};
+
+        let state = test_state();
+        let creator = KeyPair::generate();
+        let now = crate::block::current_unix_timestamp_ms();
+        {
+            let chain = state.chain.lock().unwrap();
+            chain.storage.save_compute_job(&ComputeJob::new("job-1".into(), creator.public_key_hex(), "m".into(), vec![], 0)).unwrap();
+            chain.storage.save_job_result(&JobResult {
+                job_id: "job-1".into(),
+                worker_id: "w".into(),
+                data: b"42".to_vec(),
+                result_hash: String::new(),
+                stored_at: now / 1000,
+                expires_at: now / 1000 + 60,
+            }).unwrap();
+        }
+        let request = |kp: &KeyPair| serde_json::json!({
+            "job_id": "job-1",
+            "requester": kp.public_key_hex(),
+            "timestamp": now,
+            "signature": kp.sign_hex(job_result_request_message("job-1", now).as_bytes()),
+        });
+
+        let res = handle_get_job_result(state.chain.clone(), request(&creator)).await.unwrap();
+        assert_eq!(res["data"], encoding::to_base64(b"42"));
+        let stranger = handle_get_job_result(state.chain.clone(), request(&KeyPair::generate())).await;
+        assert_eq!(stranger.unwrap_err().code, -32003);
+
+        state.chain.lock().unwrap().storage.prune_job_results(now / 1000 + 60).unwrap();
+        assert_eq!(handle_get_job_result(state.chain.clone(), request(&creator)).await.unwrap_err().code, -32001);
+    }
+
+    #[tokio::test]
+    async fn test_nft_metadata_needs_owner_signature() {
+        use crate::crypto::KeyPair;
+        use crate::layer3::model_nft::{ModelNFT, ModelStats};
+        use crate::layer3::nft_metadata::metadata_message;
+
+        let state = test_state();
+        let owner = KeyPair::generate();
+        let stats = ModelStats {
+            accuracy: 0.6, win_rate: 0.5, total_predictions: 0, profitable_predictions: 0, total_profit: 0,
+            training_samples: 1, training_epochs: 1, final_loss: 0.1, training_duration: 1, data_hash: String::new(),
+        };
+        let mut nft = ModelNFT::from_job("job", "BTC", "creator".to_string(), &stats);
+        nft.current_owner = owner.public_key_hex();
+        state.chain.lock().unwrap().storage.save_model_nft(&nft).unwrap();
+
+        let hash = crate::layer3::nft_metadata::content_hash(b"{}");
+        let publish = |signer: &KeyPair, uri: &str, timestamp: u64| serde_json::json!({
+            "token_id": nft.token_id,
+            "owner": owner.public_key_hex(),
+            "uri": uri,
+            "content_hash": hash,
+            "timestamp": timestamp,
+            "signature": signer.sign_hex(metadata_message(&nft.token_id, uri, &hash, timestamp).as_bytes()),
+        });
+        let now = crate::block::current_unix_timestamp_ms();
+
+        let forged = handle_set_nft_metadata(state.clone(), publish(&KeyPair::generate(), "ipfs://cid", now)).await;
+        assert_eq!(forged.unwrap_err().code, -32003);
+        let internal = handle_set_nft_metadata(state.clone(), publish(&owner, "http://169.254.169.254/meta", now)).await;
+        assert_eq!(internal.unwrap_err().code, -32602);
+        handle_set_nft_metadata(state.clone(), publish(&owner, "ipfs://cid", now)).await.unwrap();
+        // The older pointer can't be replayed over the newer one
+        handle_set_nft_metadata(state.clone(), publish(&owner, "ipfs://cid2", now + 2_000)).await.unwrap();
+        let replayed = handle_set_nft_metadata(state.clone(), publish(&owner, "ipfs://cid", now)).await;
+        assert_eq!(replayed.unwrap_err().code, -32004);
+    }
+
+    #[tokio::test]
+    async fn test_payout_reports_one_txid_per_redemption() {
+        use crate::vault::redemption::{RedemptionStatus, WithdrawalIntent};
+
+        let state = test_state();
+        {
+            let chain = state.chain.lock().unwrap();
+            for id in ["burn-a", "burn-b"] {
+                chain.storage.save_redemption(&WithdrawalIntent {
+                    id: id.to_string(),
+                    compass_asset: "Compass:alice:LTC".to_string(),
+                    collateral_asset: "LTC".to_string(),
+                    redeemer: "alice".to_string(),
+                    destination_address: "ltc1qdest".to_string(),
+                    quote: crate::vault::RedemptionQuote { burn_amount: 1, gross: 1, fee: 0, payout: 1 },
+                    signature: String::new(),
+                    burn_height: 1,
+                    status: RedemptionStatus::Queued,
+                    payout_tx: None,
+                }).unwrap();
+            }
+            chain.storage.mark_payout_tx("spent-tx", "burn-old").unwrap();
+        }
+        let report = |intent_id: &str, external_tx: &str| serde_json::json!({
+            "intent_id": intent_id,
+            "external_tx": external_tx,
+            "submitter": "admin",
+        });
+
+        handle_submit_redemption_payment(state.clone(), report("burn-a", "tx-1")).await.unwrap();
+        // Reporting it again for the same redemption is fine; for another one it is not
+        handle_submit_redemption_payment(state.clone(), report("burn-a", "tx-1")).await.unwrap();
+        let reused = handle_submit_redemption_payment(state.clone(), report("burn-b", "tx-1")).await;
+        assert_eq!(reused.unwrap_err().code, -32004);
+        let released = handle_submit_redemption_payment(state.clone(), report("burn-b", "spent-tx")).await;
+        assert_eq!(released.unwrap_err().code, -32004);
+        handle_submit_redemption_payment(state.clone(), report("burn-b", "tx-2")).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_single_request_and_batch_limits() {
+        let single = call(serde_json::json!({ "jsonrpc": "2.0", "method": "getChainHeight", "params": null, "id": 1 })).await;
+        assert_eq!(single["result"]["height"], 0);
+
+        let empty = call(serde_json::json!([])).await;
+        assert_eq!(empty["error"]["code"], -32600);
+
+        let call_obj = serde_json::json!({ "jsonrpc": "2.0", "method": "getVersion", "params": null, "id": 1 });
+        let oversized = call(serde_json::Value::Array(vec![call_obj; MAX_BATCH_SIZE + 1])).await;
+        assert_eq!(oversized["error"]["code"], -32600);
+    }
+}
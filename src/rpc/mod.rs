@@ -1,8 +1,10 @@
+pub mod access;
+pub mod faucet;
 pub mod handlers;
 pub mod types;
 
 use crate::chain::Chain;
-use axum::{routing::post, Router};
+use axum::{middleware, routing::post, Router};
 use std::sync::{Arc, Mutex};
 use tower_http::cors::CorsLayer;
 
@@ -23,11 +25,19 @@ pub struct RpcState {
     pub market: Arc<Mutex<crate::market::Market>>,
     pub cmd_tx: mpsc::Sender<NetworkCommand>,
     pub node_identity: String, // Public Key Hex
+    /// Testnet faucet behind `POST /faucet`, when configured
+    pub faucet: Option<Arc<crate::faucet::Faucet>>,
+    /// Login sessions of password accounts, shared across listeners
+    pub sessions: Arc<crate::account::session::SessionStore>,
+    /// Gateway `ipfs://` NFT metadata is fetched through
+    pub ipfs_gateway: String,
 }
 
 pub struct RpcServer {
     state: RpcState,
     bind_addr: String,
+    allowlist: Arc<access::MethodAllowlist>,
+    tls: Option<crate::config::TlsConfig>,
 }
 
 impl RpcServer {
@@ -56,23 +66,81 @@ impl RpcServer {
                 market,
                 cmd_tx,
                 node_identity,
+                faucet: None,
+                sessions: Arc::default(),
+                ipfs_gateway: crate::layer3::nft_metadata::IPFS_GATEWAY.to_string(),
             },
             bind_addr: format!("0.0.0.0:{}", port),
+            allowlist: Arc::new(access::MethodAllowlist::all()),
+            tls: None,
         }
     }
 
+    /// Also serve `POST /faucet`
+    pub fn with_faucet(mut self, faucet: Option<Arc<crate::faucet::Faucet>>) -> Self {
+        self.state.faucet = faucet;
+        self
+    }
+
+    /// Share `sessions` with the node's other listeners, so a login on one is
+    /// honoured on all
+    pub fn with_sessions(mut self, sessions: Arc<crate::account::session::SessionStore>) -> Self {
+        self.state.sessions = sessions;
+        self
+    }
+
+    /// Fetch `ipfs://` NFT metadata through `gateway` instead of the public one
+    pub fn with_ipfs_gateway(mut self, gateway: Option<String>) -> Self {
+        if let Some(gateway) = gateway {
+            self.state.ipfs_gateway = gateway;
+        }
+        self
+    }
+
+    /// Listen on `bind_addr` instead of the RPC port, serving only `allowlist`
+    pub fn with_listener(mut self, bind_addr: String, allowlist: Arc<access::MethodAllowlist>) -> Self {
+        self.bind_addr = bind_addr;
+        self.allowlist = allowlist;
+        self
+    }
+
+    /// Serve HTTPS with the certificate in `tls` instead of plain HTTP
+    pub fn with_tls(mut self, tls: Option<crate::config::TlsConfig>) -> Self {
+        self.tls = tls;
+        self
+    }
+
     pub async fn start(self) {
         let app = Router::new()
             .route("/", post(handlers::handle_rpc_request))
+            .route_layer(middleware::from_fn_with_state(self.allowlist.clone(), access::enforce_allowlist))
+            .route("/faucet", post(faucet::handle_faucet_request))
             .layer(CorsLayer::permissive())
             .with_state(self.state);
 
+        // Peer addresses feed the faucet's per-IP limit
+        let service = app.into_make_service_with_connect_info::<std::net::SocketAddr>();
+
+        if let Some(tls) = &self.tls {
+            // Certificates are read on every (re)start, so a restart picks up a renewal
+            let _ = rustls::crypto::ring::default_provider().install_default();
+            let config = axum_server::tls_rustls::RustlsConfig::from_pem_file(&tls.cert_path, &tls.key_path)
+                .await
+                .expect("Failed to load RPC TLS certificate");
+            let addr: std::net::SocketAddr = self.bind_addr.parse().expect("RPC bind address must be ip:port with TLS");
+            println!("🔒 RPC server listening on https://{}", self.bind_addr);
+            axum_server::bind_rustls(addr, config).serve(service).await.expect("RPC server failed");
+            return;
+        }
+
         let listener = tokio::net::TcpListener::bind(&self.bind_addr)
             .await
             .expect("Failed to bind RPC server");
 
         println!("🌐 RPC server listening on {}", self.bind_addr);
-        axum::serve(listener, app).await.expect("RPC server failed");
+        axum::serve(listener, service)
+            .await
+            .expect("RPC server failed");
     }
 }
 // RPC server module
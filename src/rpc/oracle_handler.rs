@@ -31,10 +31,7 @@ async fn handle_submit_oracle_price(
         let mut chain = safe_lock(&state.chain)?;
         
         // Update oracle price in vault manager
-        chain.vault_manager.oracle_prices.insert(
-            submission.ticker.clone(),
-            (submission.price.into(), submission.timestamp),
-        );
+        chain.vault_manager.publish_price(&submission.ticker, submission.price.into(), submission.timestamp);
         
         // Save vault state
         chain.vault_manager.save("").map_err(|e| RpcError {
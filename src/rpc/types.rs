@@ -20,6 +20,14 @@ pub struct RpcResponse {
     pub id: u64,
 }
 
+/// Reply body: one response, or an array answering a batch in request order
+#[derive(Serialize, Debug)]
+#[serde(untagged)]
+pub enum RpcReply {
+    Single(RpcResponse),
+    Batch(Vec<RpcResponse>),
+}
+
 #[derive(Serialize, Debug, Clone)]
 pub struct RpcError {
     pub code: i32,
@@ -54,6 +62,273 @@ pub struct SubmitTransferParams {
     pub prev_hash: String,
 }
 
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SubmitTransactionParams {
+    pub from: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: u64,
+    pub nonce: u64,
+    /// Compass fee; at least the schedule's minimum, the rest is priority fee
+    #[serde(default)]
+    pub fee: u64,
+    pub signature: String, // Over the block hash
+    pub public_key: String,
+    #[serde(default)]
+    pub prev_hash: Option<String>,
+    #[serde(default)]
+    pub timestamp: Option<u64>,
+    /// From `login`; required when `from` is a password account
+    #[serde(default)]
+    pub session_token: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SubmitSponsoredTransferParams {
+    pub from: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub fee: u64,
+    pub signature: String,  // Sender, over the block hash
+    pub public_key: String, // Sender
+    pub fee_payer: String,
+    pub fee_payer_public_key: String,
+    pub fee_payer_signature: String, // Over block::sponsor_message(..)
+    pub timestamp: u64,
+    pub prev_hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SubmitAssetFeeTransferParams {
+    pub from: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: u64,
+    pub nonce: u64,
+    pub fee: u64,           // In Compass
+    pub fee_asset: String,  // e.g. "COMPUTE" or a Compass-X asset
+    pub max_fee_in: u64,    // Most fee_asset the sender will spend on the fee
+    pub signature: String,  // Over the block hash
+    pub public_key: String,
+    pub timestamp: u64,
+    pub prev_hash: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct QuoteFeeParams {
+    pub fee_asset: String,
+    pub fee: u64,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct EstimateFeeParams {
+    /// The block to price, as in `getBlock`'s `header.block_type`
+    pub block_type: crate::block::BlockType,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SeedFeePoolParams {
+    pub asset: String,
+    pub asset_amount: u64,
+    pub compass_amount: u64,
+    pub submitter: String, // Must be the node admin
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ResolveNameParams {
+    pub name: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GetProofParams {
+    pub wallet_id: String,
+    pub asset: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct CreateMultisigAccountParams {
+    pub name: String,
+    pub threshold: u8,
+    pub signers: Vec<String>, // Public keys (hex)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ProposeMultisigParams {
+    pub account: String,
+    pub to: String,
+    pub asset: String,
+    pub amount: u64,
+    #[serde(default)]
+    pub nonce: Option<u64>, // Defaults to the account's next nonce
+    pub proposer: String,   // Signer public key
+    pub signature: String,  // Over account::multisig::approval_message(..)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct ApproveMultisigParams {
+    pub proposal_id: String,
+    pub signer: String,
+    pub signature: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct MultisigProposalParams {
+    pub proposal_id: String,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SetFreezeAuthorityParams {
+    pub compass_asset: String,
+    pub authority: String, // Issuer public key hex
+    pub submitter: String, // Must be the node identity
+    pub timestamp: u64,    // Unix ms
+    pub signature: String, // Over compliance::freeze_authority_message(..)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SubmitFreezeParams {
+    pub compass_asset: String,
+    pub account: String,
+    pub frozen: bool, // false = thaw
+    #[serde(default)]
+    pub reason: String,
+    pub authority: String,
+    pub seq: u64,          // See getFreezeStatus.next_seq
+    pub signature: String, // Over compliance::freeze_message(..)
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct GetFreezeStatusParams {
+    pub compass_asset: String,
+    #[serde(default)]
+    pub account: Option<String>,
+}
+
+/// Paging for getAccountHistory / getBlocksByAccount / getBlocksByProposer (`page` is 0-based)
+#[derive(Deserialize, Debug)]
+pub struct AccountHistoryParams {
+    #[serde(alias = "pubkey", alias = "account")]
+    pub wallet_id: String,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// Asset, vault id or block type name to list blocks for
+#[derive(Deserialize, Debug)]
+pub struct BlockFilterParams {
+    #[serde(alias = "asset", alias = "vault_id", alias = "block_type")]
+    pub key: String,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NftEventsParams {
+    pub token_id: String,
+    #[serde(default)]
+    pub page: usize,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// One block in an account's history, newest first
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct AccountHistoryEntry {
+    pub height: u64,
+    pub hash: String,
+    pub timestamp: u64,
+    /// `BlockType` display form, e.g. "Transfer 10 Compass from alice to bob, fee 1"
+    pub summary: String,
+    pub block_type: crate::block::BlockType,
+}
+
+/// Where invokeModel runs the model. `Auto` runs locally when this node has the ONNX file, otherwise queues for workers
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum InvokeMode {
+    #[default]
+    Auto,
+    Local,
+    Worker,
+}
+
+/// invokeModel: `signature` is the caller's over `model_nft::invoke_message`; `caller` is their public key
+#[derive(Deserialize, Debug)]
+pub struct InvokeModelParams {
+    pub token_id: String,
+    pub caller: String,
+    pub inputs: serde_json::Value,
+    pub timestamp: u64,
+    pub signature: String,
+    #[serde(default)]
+    pub mode: InvokeMode,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetTradeReceiptParams {
+    pub trade_id: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetOrderBookParams {
+    pub pair: String,     // "BASE/QUOTE"
+    pub depth: Option<usize>, // Price levels per side
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetTickerParams {
+    pub pair: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetPriceRoundParams {
+    pub ticker: String,
+    #[serde(default)]
+    pub round: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetPriceAttestationParams {
+    pub ticker: String,
+    #[serde(default)]
+    pub round: Option<u64>,
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct GetMarketStatsParams {
+    /// One pair, or every pair that has traded
+    #[serde(default)]
+    pub pair: Option<String>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct GetCandlesParams {
+    pub pair: String,
+    pub interval: String,      // e.g. "1m", "1h", "1d"
+    pub range: Option<String>, // How far back, same syntax; default "1d"
+}
+
+#[derive(Deserialize, Debug)]
+pub struct SearchParams {
+    pub query: String,
+    #[serde(default)]
+    pub limit: Option<usize>,
+}
+
+/// One `search` hit. `kind` is block, account, token, nft or collection; `id` is what to look it up by
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SearchHit {
+    pub kind: String,
+    pub id: String,
+    pub label: String,
+}
+
 #[derive(Deserialize, Debug)]
 pub struct GetBlockParams {
     pub height: u64,
@@ -77,6 +352,136 @@ pub struct NodeInfo {
     pub peer_count: u32, // placeholder
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetStateDiffParams {
+    pub from_height: u64,
+    pub to_height: u64,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SetNFTMetadataParams {
+    pub token_id: String,
+    pub owner: String,
+    pub uri: String,
+    pub content_hash: String,
+    pub timestamp: u64,    // Unix ms
+    pub signature: String, // Owner's, over nft_metadata::metadata_message(..)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetNFTMetadataParams {
+    pub token_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateCollectionParams {
+    pub collection_id: String,
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    pub creator: String,   // Public key hex
+    pub signature: String, // Over "COMPASS_COLLECTION_CREATE:{collection_id}:{name}"
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct AddToCollectionParams {
+    pub collection_id: String,
+    pub token_id: String,
+    pub signature: String, // Over "COMPASS_COLLECTION_ADD:{collection_id}:{token_id}"
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetCollectionParams {
+    pub collection_id: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CreateCompetitionParams {
+    pub competition_id: String,
+    pub name: String,
+    pub creator: String, // Must be the node admin
+    pub start_time: u64, // Unix seconds
+    pub end_time: u64,
+    #[serde(default)]
+    pub epoch_secs: Option<u64>, // Defaults to 1 hour
+    #[serde(default)]
+    pub fee_asset: Option<String>, // Defaults to COMPASS
+    #[serde(default)]
+    pub entry_fee: u64,
+    #[serde(default)]
+    pub starting_capital: Option<f64>, // Defaults to 10,000
+    #[serde(default)]
+    pub payout_bps: Option<Vec<u32>>, // Defaults to 50/30/20
+    #[serde(default)]
+    pub tickers: Option<Vec<String>>, // Defaults to all supported tickers
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct JoinCompetitionParams {
+    pub competition_id: String,
+    pub account: String,
+    pub agent_id: String, // Model token ID or agent name
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct SubmitCompetitionTradeParams {
+    pub competition_id: String,
+    pub account: String,
+    pub ticker: String,
+    pub side: String, // "buy", "sell" or "close"
+    #[serde(default)]
+    pub size: f64, // Simulated capital committed (ignored for "close")
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetCompetitionParams {
+    pub competition_id: String,
+}
+
+/// Published backing of a single vault (see getProofOfReserve)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VaultReserve {
+    pub compass_asset: String,
+    pub collateral_asset: String,
+    pub vault_address: String,
+    pub collateral_balance: u64,
+    pub minted_supply: u64,
+    pub exchange_rate: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ProofOfReserve {
+    pub height: u64,
+    pub head_hash: Option<String>,
+    pub vaults: Vec<VaultReserve>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetVaultStateParams {
+    pub vault_id: String,
+}
+
+/// One vault's debt and health right now (see getVaultState)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct VaultState {
+    pub vault_id: String,
+    pub collateral_asset: String,
+    pub collateral_balance: u64,
+    /// Minted supply plus the stability fee accrued since the vault was last charged
+    pub debt: u64,
+    pub minted_supply: u64,
+    /// Stability fee accrued but not yet charged
+    pub pending_stability_fee: u64,
+    /// Stability fees charged so far
+    pub stability_fees_charged: u64,
+    pub annual_rate_bps: u64,
+    /// Mint and redeem fees the vault has kept, in collateral units
+    pub accumulated_fees: u64,
+    pub oracle_price: Option<String>,
+    /// Collateral value over `debt`, at `oracle_price`
+    pub collateral_ratio: Option<String>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SubmitMintParams {
     pub vault_id: String,
@@ -98,6 +503,8 @@ pub struct SubmitMintParams {
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct SubmitBurnParams {
     pub vault_id: String,
+    /// Ticker the vault holds (e.g. "LTC"); the payout goes out on its chain
+    pub collateral_asset: String,
     pub compass_asset: String,
     pub burn_amount: u64,
     pub redeemer: String,
@@ -105,6 +512,39 @@ pub struct SubmitBurnParams {
     #[serde(default)]
     pub fee: u64,
     pub signature: String, // header signature
+    pub prev_hash: String,
+    pub timestamp: u64,
+    pub public_key: String,
+}
+
+#[derive(Deserialize, Serialize, Debug, Default)]
+pub struct GetRedemptionsParams {
+    /// "queued" or "released"; every intent when unset
+    #[serde(default)]
+    pub status: Option<String>,
+    #[serde(default)]
+    pub redeemer: Option<String>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct SubmitRedemptionPaymentParams {
+    pub intent_id: String,
+    /// Payout transaction on the collateral's chain
+    pub external_tx: String,
+    pub submitter: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct GetL2CheckpointParams {
+    /// Latest checkpoint when absent
+    #[serde(default)]
+    pub sequence: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize, Debug)]
+pub struct RestoreL2CheckpointParams {
+    pub sequence: u64,
+    pub submitter: String,
 }
 
 #[derive(Serialize, Deserialize, Debug)]
@@ -136,6 +576,26 @@ pub struct GetValidatorStatsParams {
     pub validator: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetValidatorLivenessParams {
+    pub validator: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetLeaderScheduleParams {
+    pub epoch: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEpochReportParams {
+    pub epoch: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetSkippedSlotsParams {
+    pub limit: Option<usize>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct SubmitComputeParams {
     pub job_id: String,
@@ -173,7 +633,16 @@ pub struct SubmitResultParams {
     pub pow_hash: Option<String>,   // Proof-of-work hash
     pub pow_nonce: Option<u64>,     // PoW nonce
     #[serde(default)]
-    pub compute_rate: u64,          // NEW: Ops/sec or Score
+    pub compute_rate: u64,          // Worker-reported ops/sec; informational, rewards follow the PoUW curve
+}
+
+/// getJobResult: `signature` is the requester's over `compute::job_result_request_message`
+#[derive(Deserialize, Debug)]
+pub struct GetJobResultParams {
+    pub job_id: String,
+    pub requester: String,
+    pub timestamp: u64,
+    pub signature: String,
 }
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -294,6 +763,99 @@ pub struct UnstakeParams {
     pub signature: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Default)]
+pub struct StakeInfoParams {
+    pub entity: String,
+}
+
+/// Signed with the entity's key over `layer2::collateral::withdraw_message`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct WithdrawUnbondedParams {
+    pub entity: String,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+/// One entity's collateral stake and withdrawals (see getStakeInfo)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct StakeInfo {
+    pub entity: String,
+    pub staked: u64,
+    /// Unstaked collateral still waiting out the unbonding period, or ready to withdraw
+    pub unbonding: Vec<crate::layer2::collateral::Unbonding>,
+    /// Part of `unbonding` whose release slot has passed
+    pub withdrawable: u64,
+    pub current_slot: u64,
+    pub unbonding_slots: u64,
+}
+
+/// Signed with the deployer's key over `vm::deploy_message`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct DeployContractParams {
+    pub deployer: String,
+    /// WASM binary, hex encoded
+    pub code: String,
+    pub gas_limit: u64,
+    /// Compass per unit of gas, at least `[consensus.fees] gas_price`
+    pub gas_price: u64,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+/// Signed with the caller's key over `vm::call_message`
+#[derive(Serialize, Deserialize, Debug)]
+pub struct CallContractParams {
+    pub caller: String,
+    pub contract: String,
+    pub method: String,
+    /// Hex encoded
+    #[serde(default)]
+    pub input: String,
+    pub gas_limit: u64,
+    /// Compass per unit of gas, at least `[consensus.fees] gas_price`
+    pub gas_price: u64,
+    pub nonce: u64,
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct QueryContractParams {
+    pub contract: String,
+    pub method: String,
+    /// Hex encoded
+    #[serde(default)]
+    pub input: String,
+    /// Defaults to `vm::MAX_GAS_LIMIT`
+    pub gas_limit: Option<u64>,
+}
+
+/// Where the block reward asset sits and how much of it is left to emit (see getSupplyInfo)
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SupplyInfo {
+    pub asset: String,
+    /// Every account's balance added up
+    pub total: u64,
+    /// `total` less what order escrow and the treasury hold
+    pub circulating: u64,
+    pub escrowed: u64,
+    pub treasury: u64,
+    /// Layer 2 collateral stakes, and unstaked collateral still unbonding
+    pub staked: u64,
+    pub unbonding: u64,
+    pub burned: u128,
+    /// Minted by block rewards so far, out of `max_emission`
+    pub emitted: u64,
+    pub max_emission: u64,
+    pub epoch: u64,
+    /// Halvings so far
+    pub era: u64,
+    /// What the next PoH block mints to its proposer
+    pub block_reward: u64,
+    /// COMPUTE minted by PoUW rewards under the schedule's cap (0 when uncapped)
+    pub pouw_emitted: u64,
+    pub max_pouw_emission: u64,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct ListModelNFTParams {
     pub token_id: String,
@@ -403,6 +965,62 @@ pub struct ClaimDividendsParams {
     pub signature: String,
 }
 
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ResumeOracleFeedParams {
+    pub ticker: String,
+    /// Price the TWAP restarts from; the one that tripped the breaker when omitted
+    #[serde(default)]
+    pub price: Option<f64>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetEarningsParams {
+    pub worker: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LoginParams {
+    pub account: String,
+    pub password: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LogoutParams {
+    pub token: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct GetVestingScheduleParams {
+    pub account: Option<String>, // If None, every schedule
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ClaimEarningsParams {
+    pub worker: String,
+    pub amount: u64,
+    pub nonce: u64,        // See getEarnings.next_nonce
+    pub signature: String, // Over earnings::claim_message(..)
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ProposePoolChangeParams {
+    pub pool_id: String,
+    pub proposer: String,
+    pub change: crate::layer3::collective::PoolParamChange,
+    /// Over `collective::pool_propose_message` with the pool's `next_proposal_id`
+    pub signature: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct VotePoolProposalParams {
+    pub pool_id: String,
+    pub proposal_id: u64,
+    pub voter: String,
+    pub approve: bool,
+    /// Over `collective::pool_vote_message`
+    pub signature: String,
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 pub struct GetPoolParams {
     pub pool_id: Option<String>, // If None, get all
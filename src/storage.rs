@@ -1,24 +1,333 @@
-use sled::Db;
 use serde::{Deserialize, Serialize};
 use crate::error::CompassError;
+use crate::events::{IndexedEvent, StateEvent};
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+/// Where a transaction submitted by hash stands
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TxStatus {
+    Pending,
+    Confirmed,
+    Failed,
+}
+
+/// Entry in the tx-hash index, written when a transaction leaves Gulf Stream
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TxRecord {
+    pub status: TxStatus,
+    /// Height of the block that included it; for L2 payloads, the height it was applied at
+    pub block_height: Option<u64>,
+    pub block_hash: Option<String>,
+    pub error: Option<String>,
+    pub updated_at: u64,
+}
+
+/// What executing a transaction did, written alongside its final `TxRecord`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Receipt {
+    pub tx_hash: String,
+    /// Height of the block that included it; for L2 payloads, the height it was
+    /// applied at. `None` when it failed.
+    pub block_height: Option<u64>,
+    /// Confirmed or Failed; parked transactions get a receipt once they run
+    pub status: TxStatus,
+    /// Compass fee charged, in base units
+    pub fee_paid: u64,
+    /// State changes made by the transaction's block. Empty for failed
+    /// transactions and for L2 payloads, which don't produce a block.
+    pub logs: Vec<StateEvent>,
+    pub error: Option<String>,
+}
+
+/// A submission made under a client-chosen id, kept so retries get the same answer
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ClientSubmission {
+    pub method: String,
+    /// JSON result of the first attempt; `None` while it is still running
+    pub response: Option<String>,
+    /// Milliseconds since the epoch
+    pub submitted_at: u64,
+}
+
+/// How long a client_id keeps deduplicating retries
+pub const CLIENT_ID_WINDOW_MS: u64 = 24 * 60 * 60 * 1000;
+
+/// How long an unfinished claim holds its client_id before a retry may take it
+pub const CLIENT_ID_CLAIM_TIMEOUT_MS: u64 = 60 * 1000;
+
+impl ClientSubmission {
+    fn expired(&self, now_ms: u64) -> bool {
+        let ttl = if self.response.is_some() { CLIENT_ID_WINDOW_MS } else { CLIENT_ID_CLAIM_TIMEOUT_MS };
+        now_ms.saturating_sub(self.submitted_at) >= ttl
+    }
+}
+
+/// Client ids are scoped to the submitting account; hex keeps a `:` in the
+/// account from running into the id
+fn client_id_key(sender: &str, client_id: &str) -> String {
+    format!("client_id:{}:{}", hex::encode(sender), client_id)
+}
+
+/// Key prefixes holding replicated ledger state: what a snapshot copies. Blocks,
+/// indexes, events and node-local caches are left out; indexes are rebuilt from
+/// blocks and history before the snapshot height is not needed to keep going.
+pub const SNAPSHOT_PREFIXES: &[&str] = &[
+    "account:",
+    "amm:pool:",
+    "bal:",
+    "chain_info:chain_id",
+    "chain_info:fee_recipient",
+    "chain_info:genesis_config",
+    "chain_info:validators",
+    "contract:",
+    "contract_code:",
+    "contract_state:",
+    "deposit:",
+    "freeze_auth:",
+    "freeze_seq:",
+    "frozen:",
+    "governance:",
+    "invoke_settled:",
+    "model_nft:",
+    "multisig:proposal:",
+    "nonce:",
+    "payout_tx:",
+    "price:",
+    "sched:",
+    "val_pubkey:",
+    "vault:",
+    "vault_collateral:",
+    "vesting:",
+    "wallet:",
+];
+
+/// Key prefixes committed to by a block's state root
+pub const STATE_ROOT_PREFIXES: &[&str] = &["bal:", "nonce:"];
+
+/// Snapshot chunks stay well under the sync protocol's message cap
+pub const SNAPSHOT_CHUNK_BYTES: usize = 4 * 1024 * 1024;
+
+/// Snapshots kept on disk; older ones are deleted when a new one is taken
+pub const SNAPSHOTS_RETAINED: usize = 2;
+
+/// `sched:<slot>:<tx hash>` key of a parked transaction; zero-padded so keys sort by slot
+fn scheduled_key(execute_at_slot: u64, tx_hash: &str) -> String {
+    format!("sched:{:020}:{}", execute_at_slot, tx_hash)
+}
+
+/// `nft_evt:<token id>:<height>:<id>` index key for an NFT ownership change recorded
+/// under `evt:<height>:<id>`; `None` for other events
+fn nft_event_key(event_key: &[u8], event: &StateEvent) -> Option<String> {
+    let StateEvent::NftOwnerChanged { token_id, .. } = event else { return None };
+    let suffix = std::str::from_utf8(event_key.strip_prefix(b"evt:")?).ok()?;
+    Some(format!("nft_evt:{}:{}", token_id, suffix))
+}
+
+/// One piece of a snapshot: raw (key, value) pairs in key order
+pub type SnapshotChunk = Vec<(String, Vec<u8>)>;
+
+/// Contract state keys (hex) a run wrote, with the value each held before
+type ContractStateBefore = Vec<(String, Option<Vec<u8>>)>;
+
+/// Raw (key, value) leaves of the state tree in key order
+type StateLeaves = Vec<(Vec<u8>, Vec<u8>)>;
+
+/// Describes the state at `block`: the chunks it is split into and their hashes.
+/// `state_hash` commits to the chunk hashes in order.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct SnapshotManifest {
+    pub height: u64,
+    pub block: crate::block::Block,
+    pub chunk_hashes: Vec<String>,
+    pub state_hash: String,
+    pub created_at: u64,
+}
+
+impl SnapshotManifest {
+    pub fn chunk_hash(chunk: &SnapshotChunk) -> String {
+        crate::encoding::hash_to_hex(&crate::encoding::sha256(bincode::serialize(chunk).unwrap_or_default()))
+    }
+
+    pub fn state_hash_of(chunk_hashes: &[String]) -> String {
+        crate::encoding::hash_to_hex(&crate::encoding::sha256(chunk_hashes.concat()))
+    }
+
+    /// Check a chunk received from a peer against this manifest
+    pub fn verify_chunk(&self, index: usize, chunk: &SnapshotChunk) -> Result<(), String> {
+        let expected = self.chunk_hashes.get(index).ok_or_else(|| format!("Snapshot has no chunk {}", index))?;
+        if &Self::chunk_hash(chunk) != expected {
+            return Err(format!("Snapshot chunk {} does not match its hash", index));
+        }
+        if chunk.iter().any(|(key, _)| !SNAPSHOT_PREFIXES.iter().any(|p| key.starts_with(p))) {
+            return Err(format!("Snapshot chunk {} holds keys outside ledger state", index));
+        }
+        Ok(())
+    }
+}
+
+mod state_writer;
+pub use state_writer::{StateBatch, StateWriter};
+pub mod store;
+pub use store::{ChainStore, DbBackend, Store, WriteBatch};
+
+static AUDIT_APPEND_LOCK: std::sync::Mutex<()> = std::sync::Mutex::new(());
+
+/// When writes are forced to disk. Between flushes they live in the engine's
+/// buffers: a crash can lose the tail of the chain, but never tears a block,
+/// since each block is written in one batch.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct FlushPolicy {
+    /// Flush once this many blocks have been committed since the last flush; 0 leaves
+    /// it to the interval. 1 means a committed block is on disk before `commit` returns.
+    pub every_blocks: u64,
+    /// Flush at least this often (see `Storage::flush_if_due`)
+    pub interval: std::time::Duration,
+}
+
+impl Default for FlushPolicy {
+    fn default() -> Self {
+        FlushPolicy { every_blocks: 0, interval: std::time::Duration::from_millis(1000) }
+    }
+}
+
+struct FlushState {
+    policy: FlushPolicy,
+    blocks_since: AtomicU64,
+    last_flush: std::sync::Mutex<std::time::Instant>,
+}
+
+/// Writes staged by `Storage::batch` and applied all at once by `Storage::write`
+#[must_use = "a batch does nothing until it is written"]
+pub struct StorageBatch {
+    writes: WriteBatch,
+}
+
+impl StorageBatch {
+    pub fn put<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), CompassError> {
+        let serialized = bincode::serialize(value).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        self.writes.insert(key, serialized);
+        Ok(())
+    }
+
+    pub fn delete(&mut self, key: &str) {
+        self.writes.remove(key);
+    }
+}
 
 #[derive(Clone)]
 pub struct Storage {
-    pub(crate) db: Db, // pub(crate) for NFT scanning in handlers
+    pub(crate) db: Store, // pub(crate) for NFT scanning in handlers
+    // Height that state events are tagged with; kept in step with Chain::height
+    event_height: Arc<AtomicU64>,
+    flush: Arc<FlushState>,
+    // Built on the first `state_root` and kept current from the keys `db` saw written
+    state_tree: Arc<std::sync::Mutex<Option<StateTree>>>,
+}
+
+/// State keys in order and the Merkle tree over their leaves
+struct StateTree {
+    keys: Vec<Vec<u8>>,
+    tree: crate::merkle::MerkleTree,
+}
+
+impl StateTree {
+    fn new(entries: StateLeaves) -> Self {
+        let leaves = entries.iter().map(|(k, v)| crate::merkle::leaf_hash(k, v)).collect();
+        StateTree { keys: entries.into_iter().map(|(k, _)| k).collect(), tree: crate::merkle::MerkleTree::new(leaves) }
+    }
+
+    /// Re-read `touched` keys. Changed values rehash their path; keys that appear or
+    /// drop out shift the leaves, so the tree is rebuilt from the leaf hashes.
+    fn update(&mut self, db: &Store, touched: BTreeSet<Vec<u8>>) -> Result<(), CompassError> {
+        let mut changes = Vec::with_capacity(touched.len());
+        let mut reshaped = false;
+        for key in touched {
+            let leaf = db.get(&key)?.filter(|v| !v.iter().all(|b| *b == 0)).map(|v| crate::merkle::leaf_hash(&key, &v));
+            let index = self.keys.binary_search(&key);
+            reshaped |= index.is_ok() != leaf.is_some();
+            changes.push((key, index, leaf));
+        }
+        if !reshaped {
+            for (_, index, leaf) in changes {
+                if let (Ok(index), Some(leaf)) = (index, leaf) {
+                    self.tree.set_leaf(index, leaf);
+                }
+            }
+            return Ok(());
+        }
+        let mut leaves: BTreeMap<Vec<u8>, crate::merkle::Hash> =
+            self.keys.drain(..).zip(self.tree.leaves().iter().copied()).collect();
+        for (key, _, leaf) in changes {
+            match leaf {
+                Some(leaf) => leaves.insert(key, leaf),
+                None => leaves.remove(&key),
+            };
+        }
+        let (keys, leaves) = leaves.into_iter().unzip();
+        self.keys = keys;
+        self.tree = crate::merkle::MerkleTree::new(leaves);
+        Ok(())
+    }
 }
 
 impl Storage {
     pub fn new(path: &str) -> Result<Self, CompassError> {
-        let db = sled::open(path).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
-        Ok(Storage { db })
+        Self::open(path, DbBackend::Sled, None)
+    }
+
+    /// Open the `backend` database at `path`, its cache capped at `cache_bytes` if given
+    pub fn open(path: &str, backend: DbBackend, cache_bytes: Option<u64>) -> Result<Self, CompassError> {
+        Ok(Self::with_store(store::open(path, backend, cache_bytes)?))
+    }
+
+    pub fn with_store(db: Store) -> Self {
+        Storage {
+            db,
+            event_height: Arc::new(AtomicU64::new(0)),
+            flush: Self::flush_state(FlushPolicy::default()),
+            state_tree: Arc::new(std::sync::Mutex::new(None)),
+        }
+    }
+
+    pub fn with_flush_policy(mut self, policy: FlushPolicy) -> Self {
+        self.flush = Self::flush_state(policy);
+        self
+    }
+
+    fn flush_state(policy: FlushPolicy) -> Arc<FlushState> {
+        Arc::new(FlushState {
+            policy,
+            blocks_since: AtomicU64::new(0),
+            last_flush: std::sync::Mutex::new(std::time::Instant::now()),
+        })
+    }
+
+    pub fn batch(&self) -> StorageBatch {
+        StorageBatch { writes: WriteBatch::default() }
+    }
+
+    /// Apply a batch atomically: after a crash either all of it is there or none
+    pub fn write(&self, batch: StorageBatch) -> Result<(), CompassError> {
+        self.db.apply_batch(batch.writes)
+    }
+
+    /// Throwaway database for tests and fuzzing; sled removes it when dropped
+    pub fn new_in_memory() -> Result<Self, CompassError> {
+        Ok(Self::with_store(Store::new(store::SledStore::temporary()?)))
+    }
+
+    /// Open with sled's page cache capped at `cache_bytes` (for memory-constrained hosts)
+    pub fn new_with_cache_capacity(path: &str, cache_bytes: u64) -> Result<Self, CompassError> {
+        Self::open(path, DbBackend::Sled, Some(cache_bytes))
     }
 
     // Generic Helper: Put
     pub fn put<T: Serialize + ?Sized>(&self, key: &str, value: &T) -> Result<(), CompassError> {
         let serialized = bincode::serialize(value).map_err(|e| CompassError::SerializationError(e.to_string()))?;
         self.db
-            .insert(key.as_bytes(), serialized)
-            .map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+            .insert(key.as_bytes(), serialized)?;
         Ok(())
     }
 
@@ -30,13 +339,13 @@ impl Storage {
                 Ok(Some(deserialized))
             }
             Ok(None) => Ok(None),
-            Err(e) => Err(CompassError::DatabaseError(e.to_string())),
+            Err(e) => Err(e),
         }
     }
 
     // Helper: Delete
     pub fn delete(&self, key: &str) -> Result<(), CompassError> {
-        self.db.remove(key.as_bytes()).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        self.db.remove(key.as_bytes())?;
         Ok(())
     }
 
@@ -46,25 +355,125 @@ impl Storage {
         let key = format!("bal:{}:{}", wallet_id, asset);
         match self.db.get(key.as_bytes()) {
             Ok(Some(val)) => {
-                let bytes: [u8; 8] = val.as_ref().try_into().map_err(|_| CompassError::SerializationError("Invalid balance bytes".to_string()))?;
+                let bytes: [u8; 8] = val.as_slice().try_into().map_err(|_| CompassError::SerializationError("Invalid balance bytes".to_string()))?;
                 Ok(u64::from_be_bytes(bytes))
             }
             Ok(None) => Ok(0),
-            Err(e) => Err(CompassError::DatabaseError(e.to_string())),
+            Err(e) => Err(e),
         }
     }
 
-    pub fn set_balance(&self, wallet_id: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
+    /// Balances and nonces change through this; see `StateWriter`
+    pub fn writer(&self) -> StateWriter<'_> {
+        StateWriter::new(self)
+    }
+
+    fn set_balance(&self, wallet_id: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
         let key = format!("bal:{}:{}", wallet_id, asset);
         let bytes = amount.to_be_bytes();
-        self.db.insert(key.as_bytes(), &bytes).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        let previous = self.db.insert(key.as_bytes(), bytes)?;
+        let old = previous
+            .and_then(|v| <[u8; 8]>::try_from(v.as_ref()).ok())
+            .map(u64::from_be_bytes)
+            .unwrap_or(0);
+        if old != amount {
+            self.record_event(StateEvent::BalanceChanged {
+                account: wallet_id.to_string(),
+                asset: asset.to_string(),
+                old,
+                new: amount,
+            })?;
+        }
         Ok(())
     }
 
-    pub fn update_balance(&self, wallet_id: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
-        let current = self.get_balance(wallet_id, asset)?;
-        let new_bal = current.saturating_add(amount); 
-        self.set_balance(wallet_id, asset, new_bal)
+    /// Leaves of the state tree in key order. Zero entries are left out so a balance
+    /// reset to zero hashes the same as one that was never written.
+    pub(crate) fn state_leaves(&self) -> Result<StateLeaves, CompassError> {
+        let mut entries = Vec::new();
+        for prefix in STATE_ROOT_PREFIXES {
+            for item in self.db.scan_prefix(prefix) {
+                let (key, value) = item?;
+                if value.iter().all(|b| *b == 0) {
+                    continue;
+                }
+                entries.push((key.to_vec(), value.to_vec()));
+            }
+        }
+        Ok(entries)
+    }
+
+    /// Merkle root over balances and nonces, hex encoded. The first call scans the
+    /// state; later ones only revisit the keys written since.
+    pub fn state_root(&self) -> Result<String, CompassError> {
+        let mut cached = self.state_tree.lock().unwrap_or_else(|e| e.into_inner());
+        match (cached.as_mut(), self.db.take_state_keys()) {
+            (Some(state), Some(touched)) => {
+                if let Err(e) = state.update(&self.db, touched) {
+                    // The touched keys are gone; rescan next time
+                    *cached = None;
+                    return Err(e);
+                }
+            }
+            _ => {
+                self.db.track_state_keys();
+                *cached = Some(StateTree::new(self.state_leaves()?));
+            }
+        }
+        let root = cached.as_ref().map(|state| state.tree.root()).unwrap_or(crate::merkle::EMPTY_ROOT);
+        Ok(crate::encoding::hash_to_hex(&root))
+    }
+
+    /// Inclusion proof for one state entry against the current root; None when the
+    /// entry is absent or zero, which this tree cannot prove
+    pub fn state_proof(&self, key: &str) -> Result<Option<crate::merkle::StateProof>, CompassError> {
+        let entries = self.state_leaves()?;
+        let Some(index) = entries.iter().position(|(k, _)| k == key.as_bytes()) else {
+            return Ok(None);
+        };
+        let leaves: Vec<_> = entries.iter().map(|(k, v)| crate::merkle::leaf_hash(k, v)).collect();
+        Ok(Some(crate::merkle::StateProof {
+            key: key.to_string(),
+            value: hex::encode(&entries[index].1),
+            steps: crate::merkle::merkle_proof(&leaves, index),
+            state_root: crate::encoding::hash_to_hex(&crate::merkle::merkle_root(&leaves)),
+        }))
+    }
+
+    /// Non-zero balances held by one account, by asset
+    pub fn get_balances(&self, wallet_id: &str) -> Vec<(String, u64)> {
+        let prefix = format!("bal:{}:", wallet_id);
+        self.db.scan_prefix(prefix.as_bytes())
+            .flatten()
+            .filter_map(|(key, value)| {
+                let asset = String::from_utf8_lossy(&key[prefix.len()..]).to_string();
+                let amount = u64::from_be_bytes(value.as_slice().try_into().ok()?);
+                (amount > 0).then_some((asset, amount))
+            })
+            .collect()
+    }
+
+    /// Every asset that has a balance entry on chain, sorted
+    pub fn get_all_assets(&self) -> Vec<String> {
+        let mut assets = std::collections::BTreeSet::new();
+        for (key, _) in self.db.scan_prefix("bal:").flatten() {
+            // bal:<account>:<asset>, where the asset itself may contain ':'
+            if let Some(asset) = String::from_utf8_lossy(&key).splitn(3, ':').nth(2) {
+                assets.insert(asset.to_string());
+            }
+        }
+        assets.into_iter().collect()
+    }
+
+    /// Sum of every account's `asset` balance
+    pub fn get_asset_supply(&self, asset: &str) -> u64 {
+        let suffix = format!(":{}", asset);
+        self.db
+            .scan_prefix("bal:")
+            .flatten()
+            .filter(|(key, _)| key.ends_with(suffix.as_bytes()))
+            .filter_map(|(_, value)| Some(u64::from_be_bytes(value.as_slice().try_into().ok()?)))
+            .fold(0u64, u64::saturating_add)
     }
 
     // --- Wallets (Phase 2 Migration) ---
@@ -89,20 +498,86 @@ impl Storage {
         self.get(&format!("account:{}", name))
     }
 
+    /// Key that signs for `account`: a registered account's signing key, otherwise
+    /// the account id itself, which for keypair wallets is the public key
+    pub fn signing_key(&self, account: &str) -> Result<String, CompassError> {
+        Ok(match self.get_account(account)? {
+            Some(registered) => registered.signing_pubkey,
+            None => account.to_string(),
+        })
+    }
+
     pub fn get_all_accounts(&self) -> Vec<crate::account::Account> {
         self.get_by_prefix("account:")
     }
 
+    pub fn save_multisig_proposal(&self, proposal: &crate::account::MultisigProposal) -> Result<(), CompassError> {
+        self.put(&format!("multisig:proposal:{}", proposal.proposal_id), proposal)
+    }
+
+    pub fn get_multisig_proposal(&self, proposal_id: &str) -> Result<Option<crate::account::MultisigProposal>, CompassError> {
+        self.get(&format!("multisig:proposal:{}", proposal_id))
+    }
+
 
     // --- Vaults (Phase 2) ---
+    /// Stability fee charged to a vault so far; key `vault_accrual:<compass_asset>`
+    pub fn get_vault_accrual(&self, compass_asset: &str) -> Result<Option<crate::vault::stability::VaultAccrual>, CompassError> {
+        self.get(&format!("vault_accrual:{}", compass_asset))
+    }
+
+    pub fn save_vault_accrual(&self, compass_asset: &str, accrual: &crate::vault::stability::VaultAccrual) -> Result<(), CompassError> {
+        self.put(&format!("vault_accrual:{}", compass_asset), accrual)
+    }
+
     pub fn save_vault(&self, id: &str, vault: &crate::vault::Vault) -> Result<(), CompassError> {
-        self.put(&format!("vault:{}", id), vault)
+        let key = format!("vault:{}", id);
+        let changed = match self.get::<crate::vault::Vault>(&key)? {
+            Some(prev) => prev.collateral_balance != vault.collateral_balance || prev.minted_supply != vault.minted_supply,
+            None => true,
+        };
+        self.put(&key, vault)?;
+        if changed {
+            self.record_event(StateEvent::VaultUpdated {
+                compass_asset: vault.compass_asset.clone(),
+                collateral_balance: vault.collateral_balance,
+                minted_supply: vault.minted_supply,
+            })?;
+        }
+        Ok(())
     }
     
     pub fn get_all_vaults(&self) -> Vec<crate::vault::Vault> {
         self.get_by_prefix("vault:")
     }
 
+    // Redemption queue (see `vault::redemption`), keyed by burn block hash
+    pub fn save_redemption(&self, intent: &crate::vault::redemption::WithdrawalIntent) -> Result<(), CompassError> {
+        self.put(&format!("redemption:{}", intent.id), intent)
+    }
+
+    pub fn get_redemption(&self, id: &str) -> Result<Option<crate::vault::redemption::WithdrawalIntent>, CompassError> {
+        self.get(&format!("redemption:{}", id))
+    }
+
+    /// The redemption whose release spent payout `external_tx`, if one has
+    pub fn get_payout_tx_redemption(&self, external_tx: &str) -> Result<Option<String>, CompassError> {
+        self.get(&format!("payout_tx:{}", external_tx))
+    }
+
+    /// Record that payout `external_tx` settled redemption `intent_id`, so it can't settle another
+    pub fn mark_payout_tx(&self, external_tx: &str, intent_id: &str) -> Result<(), CompassError> {
+        self.put(&format!("payout_tx:{}", external_tx), &intent_id.to_string())?;
+        self.record_event(StateEvent::PayoutTxUsed { external_tx: external_tx.to_string() })
+    }
+
+    /// Every withdrawal intent, oldest burn first
+    pub fn get_redemptions(&self) -> Vec<crate::vault::redemption::WithdrawalIntent> {
+        let mut intents: Vec<crate::vault::redemption::WithdrawalIntent> = self.get_by_prefix("redemption:");
+        intents.sort_by_key(|i| i.burn_height);
+        intents
+    }
+
     pub fn mark_deposit_processed(&self, tx_hash: &str) -> Result<(), CompassError> {
         self.put(&format!("deposit:{}", tx_hash), &true)
     }
@@ -114,10 +589,94 @@ impl Storage {
         }
     }
 
+    /// Sanity band state for `ticker`; fresh for tickers never priced
+    pub fn get_price_guard(&self, ticker: &str) -> Result<crate::oracle::circuit_breaker::PriceGuard, CompassError> {
+        Ok(self
+            .get(&format!("oracle_guard:{}", ticker))?
+            .unwrap_or_else(|| crate::oracle::circuit_breaker::PriceGuard::new(ticker.to_string())))
+    }
+
+    pub fn save_price_guard(&self, guard: &crate::oracle::circuit_breaker::PriceGuard) -> Result<(), CompassError> {
+        self.put(&format!("oracle_guard:{}", guard.ticker), guard)
+    }
+
+    pub fn get_all_price_guards(&self) -> Vec<crate::oracle::circuit_breaker::PriceGuard> {
+        self.get_by_prefix("oracle_guard:")
+    }
+
+    /// Submissions and published price of one aggregation round; key
+    /// `oracle_round:<ticker>:<round, 20 digits>`
+    pub fn get_price_round(&self, ticker: &str, round: u64) -> Result<Option<crate::oracle::aggregation::PriceRound>, CompassError> {
+        self.get(&format!("oracle_round:{}:{:020}", ticker, round))
+    }
+
+    pub fn save_price_round(&self, round: &crate::oracle::aggregation::PriceRound) -> Result<(), CompassError> {
+        self.put(&format!("oracle_round:{}:{:020}", round.ticker, round.round), round)
+    }
+
+    /// Most recent round of `ticker` that published a price
+    pub fn get_latest_price_round(&self, ticker: &str) -> Option<crate::oracle::aggregation::PriceRound> {
+        let rounds: Vec<crate::oracle::aggregation::PriceRound> = self.get_by_prefix(&format!("oracle_round:{}:", ticker));
+        rounds.into_iter().rev().find(|r| r.aggregate.is_some())
+    }
+
+    /// A Layer 2 checkpoint recorded by its block; key `l2_checkpoint:<sequence, 20 digits>`
+    pub fn get_l2_checkpoint(&self, sequence: u64) -> Result<Option<crate::layer2::checkpoint::L2CheckpointRecord>, CompassError> {
+        self.get(&format!("l2_checkpoint:{:020}", sequence))
+    }
+
+    pub fn save_l2_checkpoint(&self, checkpoint: &crate::layer2::checkpoint::L2CheckpointRecord) -> Result<(), CompassError> {
+        self.put(&format!("l2_checkpoint:{:020}", checkpoint.sequence), checkpoint)
+    }
+
+    pub fn get_latest_l2_checkpoint(&self) -> Option<crate::layer2::checkpoint::L2CheckpointRecord> {
+        let checkpoints: Vec<crate::layer2::checkpoint::L2CheckpointRecord> = self.get_by_prefix("l2_checkpoint:");
+        checkpoints.into_iter().last()
+    }
+
+    /// Every stored round of `ticker`, oldest first
+    pub fn get_price_rounds(&self, ticker: &str) -> Vec<crate::oracle::aggregation::PriceRound> {
+        self.get_by_prefix(&format!("oracle_round:{}:", ticker))
+    }
+
+    /// Signed price of one closed round; key `price_attestation:<ticker>:<round, 20 digits>`
+    pub fn get_price_attestation(&self, ticker: &str, round: u64) -> Result<Option<crate::oracle::feed::PriceAttestation>, CompassError> {
+        self.get(&format!("price_attestation:{}:{:020}", ticker, round))
+    }
+
+    pub fn save_price_attestation(&self, attestation: &crate::oracle::feed::PriceAttestation) -> Result<(), CompassError> {
+        self.put(&format!("price_attestation:{}:{:020}", attestation.ticker, attestation.round), attestation)
+    }
+
+    pub fn get_latest_price_attestation(&self, ticker: &str) -> Option<crate::oracle::feed::PriceAttestation> {
+        let attestations: Vec<crate::oracle::feed::PriceAttestation> = self.get_by_prefix(&format!("price_attestation:{}:", ticker));
+        attestations.into_iter().last()
+    }
+
+    /// Whether the circuit breaker has paused `ticker`
+    pub fn is_oracle_paused(&self, ticker: &str) -> bool {
+        self.get_price_guard(ticker).map(|g| g.is_paused()).unwrap_or(false)
+    }
+
     pub fn save_oracle_price_info(&self, ticker: &str, info: &(rust_decimal::Decimal, u64)) -> Result<(), CompassError> {
         self.put(&format!("price:{}", ticker), info)
     }
     
+    pub fn save_price_freshness(&self, ticker: &str, freshness: &crate::oracle::freshness::PriceFreshness) -> Result<(), CompassError> {
+        self.put(&format!("oracle_freshness:{}", ticker), freshness)
+    }
+
+    pub fn get_all_price_freshness(&self) -> Vec<(String, crate::oracle::freshness::PriceFreshness)> {
+        self.db
+            .scan_prefix("oracle_freshness:")
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, val)| {
+                let ticker = std::str::from_utf8(&key).ok()?.strip_prefix("oracle_freshness:")?.to_string();
+                Some((ticker, bincode::deserialize(&val).ok()?))
+            })
+            .collect()
+    }
+
     // Note: To return HashMap of prices, we might need a specific method since get_by_prefix returns Vec<T>
     // But we lose the key (Ticker).
     // Just helper function:
@@ -125,11 +684,13 @@ impl Storage {
         let mut out = Vec::new();
         for item in self.db.scan_prefix("price:") {
             if let Ok((key_bytes, val_bytes)) = item {
-                if let Ok(p) = bincode::deserialize::<(rust_decimal::Decimal, u64)>(&val_bytes) {
+                // A Decimal is written as its string; bincode can't read one back as a Decimal
+                if let Ok((price, ts)) = bincode::deserialize::<(String, u64)>(&val_bytes) {
+                     let Ok(price) = price.parse::<rust_decimal::Decimal>() else { continue };
                      if let Ok(k_str) = std::str::from_utf8(&key_bytes) {
                         // Key is "price:BTC", need to strip prefix
                         if let Some(ticker) = k_str.strip_prefix("price:") {
-                             out.push((ticker.to_string(), p));
+                             out.push((ticker.to_string(), (price, ts)));
                         }
                      }
                 }
@@ -184,6 +745,95 @@ impl Storage {
         self.get("market:meta")
     }
 
+    // Order escrow: what open orders hold back from their owners' balances. Only the
+    // leader that matches orders keeps it, outside the state root; its admission
+    // checks spend the free part of a balance.
+    //   market:escrow:<order id>           one order's lock
+    //   market:escrowed:<account>:<asset>  an account's locks in one asset
+    pub fn get_order_escrow(&self, order_id: u64) -> Result<Option<crate::market::OrderEscrow>, CompassError> {
+        self.get(&format!("market:escrow:{}", order_id))
+    }
+
+    /// Part of `account`'s `asset` balance locked by its open orders
+    pub fn get_escrowed(&self, account: &str, asset: &str) -> Result<u64, CompassError> {
+        Ok(self.get(&format!("market:escrowed:{}:{}", account, asset))?.unwrap_or(0))
+    }
+
+    /// Part of every account's `asset` balance locked by open orders
+    pub fn get_total_escrowed(&self, asset: &str) -> u64 {
+        let suffix = format!(":{}", asset);
+        self.db
+            .scan_prefix("market:escrowed:")
+            .flatten()
+            .filter(|(key, _)| key.ends_with(suffix.as_bytes()))
+            .filter_map(|(_, value)| bincode::deserialize::<u64>(&value).ok())
+            .fold(0u64, u64::saturating_add)
+    }
+
+    /// Part of `account`'s `asset` balance that no open order has locked
+    pub fn get_free_balance(&self, account: &str, asset: &str) -> Result<u64, CompassError> {
+        Ok(self.get_balance(account, asset)?.saturating_sub(self.get_escrowed(account, asset)?))
+    }
+
+    /// Lock `escrow.amount` of its owner's free balance for order `escrow.order_id`
+    pub fn lock_escrow(&self, escrow: &crate::market::OrderEscrow) -> Result<(), CompassError> {
+        if self.get_order_escrow(escrow.order_id)?.is_some() {
+            return Err(CompassError::InvalidState(format!("order {} already holds escrow", escrow.order_id)));
+        }
+        let free = self.get_free_balance(&escrow.account, &escrow.asset)?;
+        if free < escrow.amount {
+            return Err(CompassError::InvalidState(format!(
+                "insufficient {} balance: has {} free, needs {}",
+                escrow.asset, free, escrow.amount
+            )));
+        }
+        let escrowed = self.get_escrowed(&escrow.account, &escrow.asset)?;
+        let mut batch = self.batch();
+        batch.put(&format!("market:escrow:{}", escrow.order_id), escrow)?;
+        batch.put(&format!("market:escrowed:{}:{}", escrow.account, escrow.asset), &(escrowed + escrow.amount))?;
+        self.write(batch)
+    }
+
+    /// Hand back up to `amount` of order `order_id`'s escrow to its owner's free
+    /// balance, dropping the lock once it is empty. Returns what was released.
+    pub fn release_escrow(&self, order_id: u64, amount: u64) -> Result<u64, CompassError> {
+        let Some(mut escrow) = self.get_order_escrow(order_id)? else { return Ok(0) };
+        let released = amount.min(escrow.amount);
+        escrow.amount -= released;
+        let total_key = format!("market:escrowed:{}:{}", escrow.account, escrow.asset);
+        let escrowed = self.get_escrowed(&escrow.account, &escrow.asset)?.saturating_sub(released);
+        let mut batch = self.batch();
+        if escrow.amount == 0 {
+            batch.delete(&format!("market:escrow:{}", order_id));
+        } else {
+            batch.put(&format!("market:escrow:{}", order_id), &escrow)?;
+        }
+        if escrowed == 0 {
+            batch.delete(&total_key);
+        } else {
+            batch.put(&total_key, &escrowed)?;
+        }
+        self.write(batch)?;
+        Ok(released)
+    }
+
+    pub fn save_trade_receipt(&self, receipt: &crate::market::TradeReceipt) -> Result<(), CompassError> {
+        self.put(&format!("market:receipt:{}", receipt.trade.trade_id), receipt)
+    }
+
+    pub fn get_trade_receipt(&self, trade_id: &str) -> Result<Option<crate::market::TradeReceipt>, CompassError> {
+        self.get(&format!("market:receipt:{}", trade_id))
+    }
+
+    // Fee conversion pools (see `amm`)
+    pub fn save_amm_pool(&self, pool: &crate::amm::LiquidityPool) -> Result<(), CompassError> {
+        self.put(&format!("amm:pool:{}", pool.asset), pool)
+    }
+
+    pub fn get_amm_pool(&self, asset: &str) -> Result<Option<crate::amm::LiquidityPool>, CompassError> {
+        self.get(&format!("amm:pool:{}", asset))
+    }
+
     // NFT Listings
     pub fn save_nft_listing(&self, listing: &crate::market::NFTListing) -> Result<(), CompassError> {
         self.put(&format!("market:listing:{}", listing.token_id), listing)
@@ -205,11 +855,11 @@ impl Storage {
         let mut deleted = 0u64;
         for result in self.db.scan_prefix(b"model_nft:") {
             if let Ok((key, _)) = result {
-                self.db.remove(&key).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+                self.db.remove(&key)?;
                 deleted += 1;
             }
         }
-        self.db.flush().map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        self.db.flush()?;
         Ok(deleted)
     }
 
@@ -234,99 +884,483 @@ impl Storage {
             .collect()
     }
 
-    pub fn delete_compute_job(&self, job_id: &str) -> Result<(), CompassError> {
-        self.delete(&format!("compute_job:{}", job_id))
+    pub fn save_job_result(&self, result: &crate::layer3::compute::JobResult) -> Result<(), CompassError> {
+        self.put(&format!("job_result:{}", result.job_id), result)
     }
 
-    // 1. Blocks
-    pub fn save_block(&self, block: &crate::block::Block) -> Result<(), CompassError> {
-        let hash = &block.header.hash;
-        if hash.is_empty() { return Err(CompassError::InvalidState("Block has no hash".to_string())); }
-        
-        self.put(&format!("block:{}", hash), block)?;
-        self.put(&format!("height:{}", block.header.index), hash)?;
-        Ok(())
+    pub fn get_job_result(&self, job_id: &str) -> Result<Option<crate::layer3::compute::JobResult>, CompassError> {
+        self.get(&format!("job_result:{}", job_id))
     }
 
-    pub fn get_block(&self, hash: &str) -> Result<Option<crate::block::Block>, CompassError> {
-        self.get(&format!("block:{}", hash))
+    pub fn save_pouw_tally(&self, tally: &crate::layer3::pouw::PouwTally) -> Result<(), CompassError> {
+        self.put(&format!("pouw_tally:{}", tally.job_id), tally)
     }
 
-    pub fn get_block_by_height(&self, height: u64) -> Result<Option<crate::block::Block>, CompassError> {
-        if let Ok(Some(hash)) = self.get::<String>(&format!("height:{}", height)) {
-            self.get_block(&hash)
-        } else {
-            Ok(None)
-        }
+    pub fn get_pouw_tally(&self, job_id: &str) -> Result<Option<crate::layer3::pouw::PouwTally>, CompassError> {
+        self.get(&format!("pouw_tally:{}", job_id))
     }
 
-    // 2. Nonces
-    pub fn get_nonce(&self, wallet_id: &str) -> Result<u64, CompassError> {
-        let key = format!("nonce:{}", wallet_id);
-        Ok(self.get::<u64>(&key)?.unwrap_or(0))
+    /// Delete results whose retention ran out by `now` (unix seconds); returns how many
+    pub fn prune_job_results(&self, now: u64) -> Result<usize, CompassError> {
+        let expired: Vec<String> = self
+            .get_by_prefix::<crate::layer3::compute::JobResult>("job_result:")
+            .into_iter()
+            .filter(|r| r.expires_at <= now)
+            .map(|r| format!("job_result:{}", r.job_id))
+            .collect();
+        for key in &expired {
+            self.db.remove(key.as_bytes())?;
+        }
+        Ok(expired.len())
     }
 
-    pub fn set_nonce(&self, wallet_id: &str, nonce: u64) -> Result<(), CompassError> {
-        let key = format!("nonce:{}", wallet_id);
-        self.put(&key, &nonce)
+    pub fn delete_compute_job(&self, job_id: &str) -> Result<(), CompassError> {
+        self.delete(&format!("compute_job:{}", job_id))
     }
 
-    // 3. Validators
-    pub fn get_validator_stats(&self, validator: &str) -> Result<crate::rpc::types::ValidatorStats, CompassError> {
-         let key = format!("stats:{}", validator);
-         Ok(self.get(&key)?.unwrap_or_default())
-    }
+    // --- State snapshots ---
+    //   snapshot:manifest:<height, 20 digits>
+    //   snapshot:chunk:<height, 20 digits>:<index, 6 digits>
+
+    /// Copy ledger state as of `head` into chunks and record the manifest
+    pub fn create_snapshot(&self, head: &crate::block::Block) -> Result<SnapshotManifest, CompassError> {
+        let height = head.header.index;
+        let mut chunks: Vec<SnapshotChunk> = vec![Vec::new()];
+        let mut chunk_bytes = 0;
+        for prefix in SNAPSHOT_PREFIXES {
+            for item in self.db.scan_prefix(prefix) {
+                let (key, value) = item?;
+                let key = String::from_utf8_lossy(&key).into_owned();
+                let size = key.len() + value.len();
+                if chunk_bytes + size > SNAPSHOT_CHUNK_BYTES && chunk_bytes > 0 {
+                    chunks.push(Vec::new());
+                    chunk_bytes = 0;
+                }
+                chunk_bytes += size;
+                chunks.last_mut().expect("at least one chunk").push((key, value.to_vec()));
+            }
+        }
 
-    pub fn set_validator_stats(&self, validator: &str, stats: &crate::rpc::types::ValidatorStats) -> Result<(), CompassError> {
-        let key = format!("stats:{}", validator);
-        self.put(&key, stats)
+        let chunk_hashes: Vec<String> = chunks.iter().map(SnapshotManifest::chunk_hash).collect();
+        for (index, chunk) in chunks.iter().enumerate() {
+            self.put(&format!("snapshot:chunk:{:020}:{:06}", height, index), chunk)?;
+        }
+        let manifest = SnapshotManifest {
+            height,
+            block: head.clone(),
+            state_hash: SnapshotManifest::state_hash_of(&chunk_hashes),
+            chunk_hashes,
+            created_at: crate::block::current_unix_timestamp_ms(),
+        };
+        self.put(&format!("snapshot:manifest:{:020}", height), &manifest)?;
+
+        let manifests: Vec<SnapshotManifest> = self.get_by_prefix("snapshot:manifest:");
+        for old in manifests.iter().rev().skip(SNAPSHOTS_RETAINED) {
+            self.delete_snapshot(old)?;
+        }
+        Ok(manifest)
     }
 
-    pub fn get_active_validators(&self) -> Result<Vec<String>, CompassError> {
-        self.get::<Vec<String>>("chain_info:validators")
-            .map(|v| v.unwrap_or_else(|| vec!["admin".to_string()]))
+    fn delete_snapshot(&self, manifest: &SnapshotManifest) -> Result<(), CompassError> {
+        for index in 0..manifest.chunk_hashes.len() {
+            self.delete(&format!("snapshot:chunk:{:020}:{:06}", manifest.height, index))?;
+        }
+        self.delete(&format!("snapshot:manifest:{:020}", manifest.height))
     }
 
-    pub fn set_active_validators(&self, validators: &[String]) -> Result<(), CompassError> {
-        self.put("chain_info:validators", validators)
+    /// Newest snapshot taken by this node
+    pub fn latest_snapshot(&self) -> Option<SnapshotManifest> {
+        self.get_by_prefix::<SnapshotManifest>("snapshot:manifest:").pop()
     }
 
-    pub fn get_validator_pubkey(&self, validator_id: &str) -> Result<Option<String>, CompassError> {
-        self.get::<String>(&format!("val_pubkey:{}", validator_id))
+    pub fn get_snapshot_chunk(&self, height: u64, index: u32) -> Result<Option<SnapshotChunk>, CompassError> {
+        self.get(&format!("snapshot:chunk:{:020}:{:06}", height, index))
     }
 
-    pub fn set_validator_pubkey(&self, validator_id: &str, pubkey: &str) -> Result<(), CompassError> {
-        self.put(&format!("val_pubkey:{}", validator_id), &pubkey.to_string())
-    }
+    /// Replace ledger state with a verified snapshot and make its block the head.
+    /// Blocks below the snapshot are never fetched; the chain continues from it.
+    pub fn restore_snapshot(&self, manifest: &SnapshotManifest, chunks: &[SnapshotChunk]) -> Result<(), CompassError> {
+        if chunks.len() != manifest.chunk_hashes.len()
+            || SnapshotManifest::state_hash_of(&manifest.chunk_hashes) != manifest.state_hash
+        {
+            return Err(CompassError::InvalidState("snapshot manifest is inconsistent".to_string()));
+        }
+        for (index, chunk) in chunks.iter().enumerate() {
+            manifest.verify_chunk(index, chunk).map_err(CompassError::InvalidState)?;
+        }
 
-    // 4. Prefix Scan
-    pub fn get_by_prefix<T: for<'a> Deserialize<'a>>(&self, prefix: &str) -> Vec<T> {
-        let mut items = Vec::new();
-        for item in self.db.scan_prefix(prefix) {
-             if let Ok((_key, value)) = item {
-                 if let Ok(deserialized) = bincode::deserialize::<T>(&value) {
-                     items.push(deserialized);
-                 }
-             }
+        let mut batch = WriteBatch::default();
+        for prefix in SNAPSHOT_PREFIXES {
+            for item in self.db.scan_prefix(prefix) {
+                let (key, _) = item?;
+                batch.remove(key);
+            }
         }
-        items
-    }
+        for (key, value) in chunks.iter().flatten() {
+            batch.insert(key.as_bytes(), value.as_slice());
+        }
+        self.db.apply_batch(batch)?;
 
-    // 5. Oracle
-    pub fn save_oracle_job(&self, job: &crate::rpc::types::OracleVerificationJob) -> Result<(), CompassError> {
-        self.put(&format!("oracle_job:{}", job.job_id), job)
+        self.save_block(&manifest.block)?;
+        self.put("chain_info:head", &manifest.block.header.hash)?;
+        self.put(&format!("snapshot:manifest:{:020}", manifest.height), manifest)?;
+        for (index, chunk) in chunks.iter().enumerate() {
+            self.put(&format!("snapshot:chunk:{:020}:{:06}", manifest.height, index), chunk)?;
+        }
+        self.flush()
     }
 
-    pub fn get_oracle_job(&self, job_id: &str) -> Result<Option<crate::rpc::types::OracleVerificationJob>, CompassError> {
-        self.get(&format!("oracle_job:{}", job_id))
+    // 1. Blocks
+    pub fn save_block(&self, block: &crate::block::Block) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        self.stage_block(&mut batch, block)?;
+        self.write(batch)
     }
 
-    pub fn get_pending_oracle_jobs(&self) -> Vec<crate::rpc::types::OracleVerificationJob> {
-        self.get_by_prefix("oracle_job:")
+    /// Stage a canonical block with its height entry and indexes, for a commit
+    /// that writes them together with the head
+    pub fn stage_block(&self, batch: &mut StorageBatch, block: &crate::block::Block) -> Result<(), CompassError> {
+        let hash = &block.header.hash;
+        if hash.is_empty() { return Err(CompassError::InvalidState("Block has no hash".to_string())); }
+
+        batch.put(&format!("block:{}", hash), block)?;
+        batch.put(&format!("height:{}", block.header.index), hash)?;
+        Self::stage_block_indexes(batch, block, true);
+        Ok(())
     }
 
-    pub fn delete_oracle_job(&self, job_id: &str) -> Result<(), CompassError> {
-        self.delete(&format!("oracle_job:{}", job_id))
+    // Block indexes for explorers and wallets, keyed so a prefix scan returns heights in order:
+    //   acct_blk:<account>:<height, 20 digits>    blocks touching an account
+    //   proposer_blk:<pubkey>:<height, 20 digits> blocks signed by a proposer
+    //   asset_blk:<asset>:<height, 20 digits>     transfers of an asset
+    //   vault_blk:<vault id>:<height, 20 digits>  mints against a vault
+    //   type_blk:<block kind>:<height, 20 digits> blocks of one type
+    //   trade_blk:<base>/<quote>:<height, 20 digits> trade settlements of a pair
+    fn index_block(&self, block: &crate::block::Block, add: bool) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        Self::stage_block_indexes(&mut batch, block, add);
+        self.write(batch)
+    }
+
+    fn stage_block_indexes(batch: &mut StorageBatch, block: &crate::block::Block, add: bool) {
+        use crate::block::BlockType;
+
+        let height = block.header.index;
+        let block_type = &block.header.block_type;
+        let mut keys: Vec<String> = block_type.accounts()
+            .into_iter()
+            .map(|account| format!("acct_blk:{}:{:020}", account, height))
+            .collect();
+        if !block.header.proposer.is_empty() {
+            keys.push(format!("proposer_blk:{}:{:020}", block.header.proposer, height));
+        }
+        keys.push(format!("type_blk:{}:{:020}", block_type.kind(), height));
+        match block_type {
+            BlockType::Transfer { asset, .. }
+            | BlockType::SponsoredTransfer { asset, .. }
+            | BlockType::AssetFeeTransfer { asset, .. }
+            | BlockType::MultisigTransfer { asset, .. } => keys.push(format!("asset_blk:{}:{:020}", asset, height)),
+            BlockType::Mint { vault_id, .. } => keys.push(format!("vault_blk:{}:{:020}", vault_id, height)),
+            BlockType::Trade { base, quote, .. } => keys.push(format!("trade_blk:{}/{}:{:020}", base, quote, height)),
+            _ => {}
+        }
+        for key in keys {
+            if add {
+                batch.writes.insert(key.as_bytes(), []);
+            } else {
+                batch.writes.remove(key.as_bytes());
+            }
+        }
+    }
+
+    /// Heights under an index prefix, newest first, after skipping `offset` entries
+    fn indexed_heights(&self, prefix: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        let heights: Vec<u64> = self.db.scan_prefix(prefix.as_bytes())
+            .flatten()
+            .filter_map(|(key, _)| std::str::from_utf8(&key[prefix.len()..]).ok()?.parse().ok())
+            .collect();
+        let total = heights.len();
+        (heights.into_iter().rev().skip(offset).take(limit).collect(), total)
+    }
+
+    /// Canonical block heights touching `account`, newest first, with the total count
+    pub fn get_account_block_heights(&self, account: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        self.indexed_heights(&format!("acct_blk:{}:", account), offset, limit)
+    }
+
+    /// Canonical block heights proposed by `pubkey`, newest first, with the total count
+    pub fn get_proposer_block_heights(&self, pubkey: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        self.indexed_heights(&format!("proposer_blk:{}:", pubkey), offset, limit)
+    }
+
+    /// Canonical transfer heights for `asset`, newest first, with the total count
+    pub fn get_asset_transfer_heights(&self, asset: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        self.indexed_heights(&format!("asset_blk:{}:", asset), offset, limit)
+    }
+
+    /// Canonical mint heights for `vault_id`, newest first, with the total count
+    pub fn get_vault_mint_heights(&self, vault_id: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        self.indexed_heights(&format!("vault_blk:{}:", vault_id), offset, limit)
+    }
+
+    /// Canonical heights of blocks whose `BlockType::kind` is `kind`, newest first, with the total count
+    pub fn get_type_block_heights(&self, kind: &str, offset: usize, limit: usize) -> (Vec<u64>, usize) {
+        self.indexed_heights(&format!("type_blk:{}:", kind), offset, limit)
+    }
+
+    /// Settled fills of `pair` ("BASE/QUOTE") at or after `since_ms`, oldest first.
+    /// Fills whose Trade block was pruned are left out.
+    pub fn get_pair_trades(&self, pair: &str, since_ms: u64) -> Vec<crate::market::TradeTick> {
+        let (heights, _) = self.indexed_heights(&format!("trade_blk:{}:", pair), 0, usize::MAX);
+        let mut trades = Vec::new();
+        for height in heights {
+            let Ok(Some(block)) = self.get_block_by_height(height) else { continue };
+            if block.header.timestamp < since_ms {
+                break;
+            }
+            trades.extend(crate::market::TradeTick::from_header(&block.header));
+        }
+        trades.reverse();
+        trades
+    }
+
+    /// Most recent settled fill of `pair`
+    pub fn get_last_pair_trade(&self, pair: &str) -> Option<crate::market::TradeTick> {
+        let (heights, _) = self.indexed_heights(&format!("trade_blk:{}:", pair), 0, 1);
+        let block = self.get_block_by_height(*heights.first()?).ok()??;
+        crate::market::TradeTick::from_header(&block.header)
+    }
+
+    /// Pairs ("BASE/QUOTE") with at least one indexed fill, sorted
+    pub fn get_traded_pairs(&self) -> Vec<String> {
+        let mut pairs: Vec<String> = self.db.scan_prefix(b"trade_blk:")
+            .flatten()
+            .filter_map(|(key, _)| {
+                let key = std::str::from_utf8(&key["trade_blk:".len()..]).ok()?;
+                Some(key.rsplit_once(':')?.0.to_string())
+            })
+            .collect();
+        pairs.dedup();
+        pairs
+    }
+
+    /// Ownership changes of NFT `token_id`, newest first, with the total count
+    pub fn get_nft_events(&self, token_id: &str, offset: usize, limit: usize) -> (Vec<IndexedEvent>, usize) {
+        let events: Vec<IndexedEvent> = self.get_by_prefix(&format!("nft_evt:{}:", token_id));
+        let total = events.len();
+        (events.into_iter().rev().skip(offset).take(limit).collect(), total)
+    }
+
+    /// Index every canonical block below `height`, and the NFT events recorded so far
+    /// (databases written before the indexes existed)
+    pub fn backfill_block_indexes(&self, height: u64) -> Result<u64, CompassError> {
+        if self.get::<bool>("chain_info:block_indexes_v2")?.unwrap_or(false) {
+            return Ok(0);
+        }
+        let mut indexed = 0;
+        for h in 0..height {
+            if let Some(block) = self.get_block_by_height(h)? {
+                self.index_block(&block, true)?;
+                indexed += 1;
+            }
+        }
+        for item in self.db.scan_prefix(b"evt:") {
+            let (key, value) = item?;
+            if let Ok(indexed_event) = bincode::deserialize::<IndexedEvent>(&value) {
+                if let Some(nft_key) = nft_event_key(&key, &indexed_event.event) {
+                    self.db.insert(nft_key.as_bytes(), value)?;
+                }
+            }
+        }
+        self.put("chain_info:block_indexes_v2", &true)?;
+        Ok(indexed)
+    }
+
+    /// Lowest canonical height whose block is still stored (0 on archive nodes)
+    pub fn pruned_below(&self) -> u64 {
+        self.get::<u64>("chain_info:pruned_below").ok().flatten().unwrap_or(0)
+    }
+
+    /// Delete canonical blocks below `below`, with their height and explorer indexes,
+    /// and flush so sled can reclaim the space. Genesis is kept; chain identity checks
+    /// read it. Returns how many blocks went.
+    pub fn prune_blocks(&self, below: u64) -> Result<u64, CompassError> {
+        let from = self.pruned_below().max(1);
+        if below <= from {
+            return Ok(0);
+        }
+        let mut pruned = 0;
+        for height in from..below {
+            let mut batch = self.batch();
+            if let Some(block) = self.get_block_by_height(height)? {
+                Self::stage_block_indexes(&mut batch, &block, false);
+                batch.delete(&format!("block:{}", block.header.hash));
+                pruned += 1;
+            }
+            batch.delete(&format!("height:{}", height));
+            self.write(batch)?;
+        }
+        self.put("chain_info:pruned_below", &below)?;
+        self.flush()?;
+        Ok(pruned)
+    }
+
+    /// Store a block that is not on the canonical chain; the height index is left alone
+    pub fn save_side_block(&self, block: &crate::block::Block) -> Result<(), CompassError> {
+        let hash = &block.header.hash;
+        if hash.is_empty() { return Err(CompassError::InvalidState("Block has no hash".to_string())); }
+        self.put(&format!("block:{}", hash), block)
+    }
+
+    /// Drop height index entries `from..to` after the canonical chain was cut back
+    pub fn truncate_height_index(&self, from: u64, to: u64) -> Result<(), CompassError> {
+        for height in from..to {
+            let mut batch = self.batch();
+            if let Some(block) = self.get_block_by_height(height)? {
+                Self::stage_block_indexes(&mut batch, &block, false);
+            }
+            batch.delete(&format!("height:{}", height));
+            self.write(batch)?;
+        }
+        Ok(())
+    }
+
+    // Fork tips: heads of known side branches, "fork_tip:<hash>" -> block index
+    pub fn add_fork_tip(&self, hash: &str, index: u64) -> Result<(), CompassError> {
+        self.put(&format!("fork_tip:{}", hash), &index)
+    }
+
+    pub fn remove_fork_tip(&self, hash: &str) -> Result<(), CompassError> {
+        self.delete(&format!("fork_tip:{}", hash))
+    }
+
+    /// Known side-branch heads as (hash, index)
+    pub fn get_fork_tips(&self) -> Vec<(String, u64)> {
+        self.db
+            .scan_prefix(b"fork_tip:")
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, value)| {
+                let hash = String::from_utf8(key["fork_tip:".len()..].to_vec()).ok()?;
+                let index = bincode::deserialize::<u64>(&value).ok()?;
+                Some((hash, index))
+            })
+            .collect()
+    }
+
+    pub fn get_block(&self, hash: &str) -> Result<Option<crate::block::Block>, CompassError> {
+        self.get(&format!("block:{}", hash))
+    }
+
+    pub fn get_block_by_height(&self, height: u64) -> Result<Option<crate::block::Block>, CompassError> {
+        if let Ok(Some(hash)) = self.get::<String>(&format!("height:{}", height)) {
+            self.get_block(&hash)
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub fn set_chain_id(&self, chain_id: &str) -> Result<(), CompassError> {
+        self.put("chain_info:chain_id", chain_id)
+    }
+
+    /// Chain id from genesis; `None` for databases created before it was recorded
+    pub fn get_chain_id(&self) -> Result<Option<String>, CompassError> {
+        self.get("chain_info:chain_id")
+    }
+
+    pub fn set_genesis_config_hash(&self, hash: &str) -> Result<(), CompassError> {
+        self.put("chain_info:genesis_config", hash)
+    }
+
+    /// `GenesisConfig::canonical_hash` of the genesis this database started from;
+    /// `None` for databases created before it was recorded
+    pub fn get_genesis_config_hash(&self) -> Result<Option<String>, CompassError> {
+        self.get("chain_info:genesis_config")
+    }
+
+    // 2. Nonces
+    pub fn get_nonce(&self, wallet_id: &str) -> Result<u64, CompassError> {
+        let key = format!("nonce:{}", wallet_id);
+        Ok(self.get::<u64>(&key)?.unwrap_or(0))
+    }
+
+    fn set_nonce(&self, wallet_id: &str, nonce: u64) -> Result<(), CompassError> {
+        let old = self.get_nonce(wallet_id)?;
+        let key = format!("nonce:{}", wallet_id);
+        self.put(&key, &nonce)?;
+        if old != nonce {
+            self.record_event(StateEvent::NonceChanged { account: wallet_id.to_string(), old, new: nonce })?;
+        }
+        Ok(())
+    }
+
+    // 3. Validators
+    pub fn get_validator_stats(&self, validator: &str) -> Result<crate::rpc::types::ValidatorStats, CompassError> {
+         let key = format!("stats:{}", validator);
+         Ok(self.get(&key)?.unwrap_or_default())
+    }
+
+    pub fn set_validator_stats(&self, validator: &str, stats: &crate::rpc::types::ValidatorStats) -> Result<(), CompassError> {
+        let key = format!("stats:{}", validator);
+        self.put(&key, stats)
+    }
+
+    pub fn get_active_validators(&self) -> Result<Vec<String>, CompassError> {
+        self.get::<Vec<String>>("chain_info:validators")
+            .map(|v| v.unwrap_or_else(|| vec!["admin".to_string()]))
+    }
+
+    pub fn set_active_validators(&self, validators: &[String]) -> Result<(), CompassError> {
+        self.put("chain_info:validators", validators)
+    }
+
+    /// Who transaction fees are credited to: the proposer of the latest PoH block,
+    /// or the foundation before the first one
+    pub fn get_fee_recipient(&self) -> Result<String, CompassError> {
+        self.get::<String>("chain_info:fee_recipient")
+            .map(|r| r.unwrap_or_else(|| "foundation".to_string()))
+    }
+
+    pub fn set_fee_recipient(&self, proposer: &str) -> Result<(), CompassError> {
+        self.put("chain_info:fee_recipient", &proposer.to_string())
+    }
+
+    pub fn get_validator_pubkey(&self, validator_id: &str) -> Result<Option<String>, CompassError> {
+        self.get::<String>(&format!("val_pubkey:{}", validator_id))
+    }
+
+    pub fn set_validator_pubkey(&self, validator_id: &str, pubkey: &str) -> Result<(), CompassError> {
+        self.put(&format!("val_pubkey:{}", validator_id), &pubkey.to_string())
+    }
+
+    // 4. Prefix Scan
+    pub fn get_by_prefix<T: for<'a> Deserialize<'a>>(&self, prefix: &str) -> Vec<T> {
+        let mut items = Vec::new();
+        for item in self.db.scan_prefix(prefix) {
+             if let Ok((_key, value)) = item {
+                 if let Ok(deserialized) = bincode::deserialize::<T>(&value) {
+                     items.push(deserialized);
+                 }
+             }
+        }
+        items
+    }
+
+    // 5. Oracle
+    pub fn save_oracle_job(&self, job: &crate::rpc::types::OracleVerificationJob) -> Result<(), CompassError> {
+        self.put(&format!("oracle_job:{}", job.job_id), job)
+    }
+
+    pub fn get_oracle_job(&self, job_id: &str) -> Result<Option<crate::rpc::types::OracleVerificationJob>, CompassError> {
+        self.get(&format!("oracle_job:{}", job_id))
+    }
+
+    pub fn get_pending_oracle_jobs(&self) -> Vec<crate::rpc::types::OracleVerificationJob> {
+        self.get_by_prefix("oracle_job:")
+    }
+
+    pub fn delete_oracle_job(&self, job_id: &str) -> Result<(), CompassError> {
+        self.delete(&format!("oracle_job:{}", job_id))
     }
 
     // Recurring
@@ -349,21 +1383,13 @@ impl Storage {
 
     // --- Vault Collateral Locking ---
     
-    /// Lock COMPASS as vault collateral (deduct from balance, track separately)
+    /// Track COMPASS locked as vault collateral. The balance side moves on chain,
+    /// as a `Settlement` debit.
     pub fn lock_vault_collateral(
         &self,
         wallet_id: &str,
         amount: u64
     ) -> Result<(), CompassError> {
-        let current = self.get_balance(wallet_id, "COMPASS")?;
-        if current < amount {
-            return Err(CompassError::DatabaseError("Insufficient COMPASS balance".to_string()));
-        }
-        
-        // Deduct from balance
-        let new_bal = current - amount;
-        self.set_balance(wallet_id, "COMPASS", new_bal)?;
-        
         // Track locked amount
         let key = format!("vault_collateral:{}:COMPASS", wallet_id);
         let locked = self.get::<u64>(&key)?.unwrap_or(0);
@@ -372,7 +1398,7 @@ impl Storage {
         Ok(())
     }
     
-    /// Unlock collateral back to wallet
+    /// Release tracked collateral; the balance comes back through a `Settlement` credit
     pub fn unlock_vault_collateral(
         &self,
         wallet_id: &str,
@@ -387,10 +1413,6 @@ impl Storage {
         
         self.put(&key, &(locked - amount))?;
         
-        // Add back to balance
-        let current = self.get_balance(wallet_id, "COMPASS")?;
-        self.set_balance(wallet_id, "COMPASS", current + amount)?;
-        
         Ok(())
     }
     
@@ -404,7 +1426,16 @@ impl Storage {
     // 4. Model NFTs
     pub fn save_model_nft(&self, nft: &crate::layer3::model_nft::ModelNFT) -> Result<(), CompassError> {
         let key = format!("model_nft:{}", nft.token_id); 
-        self.put(&key, nft)
+        let prev_owner = self.get::<crate::layer3::model_nft::ModelNFT>(&key).ok().flatten().map(|n| n.current_owner);
+        self.put(&key, nft)?;
+        if prev_owner.as_deref() != Some(nft.current_owner.as_str()) {
+            self.record_event(StateEvent::NftOwnerChanged {
+                token_id: nft.token_id.clone(),
+                from: prev_owner,
+                to: nft.current_owner.clone(),
+            })?;
+        }
+        Ok(())
     }
 
     pub fn get_model_nft(&self, token_id: &str) -> Result<Option<crate::layer3::model_nft::ModelNFT>, CompassError> {
@@ -416,7 +1447,7 @@ impl Storage {
         let mut nfts = Vec::new();
         for item in self.db.scan_prefix(prefix) {
             if let Ok((_, value)) = item {
-                if let Ok(nft) = serde_json::from_slice::<crate::layer3::model_nft::ModelNFT>(&value) {
+                if let Ok(nft) = bincode::deserialize::<crate::layer3::model_nft::ModelNFT>(&value) {
                     nfts.push(nft);
                 }
             }
@@ -428,6 +1459,35 @@ impl Storage {
         self.get_all_nfts().into_iter().filter(|n| n.current_owner == owner).collect()
     }
     
+    pub fn save_nft_metadata_pointer(&self, pointer: &crate::layer3::nft_metadata::NftMetadataPointer) -> Result<(), CompassError> {
+        self.put(&format!("nft_meta:{}", pointer.token_id), pointer)
+    }
+
+    pub fn get_nft_metadata_pointer(&self, token_id: &str) -> Result<Option<crate::layer3::nft_metadata::NftMetadataPointer>, CompassError> {
+        self.get(&format!("nft_meta:{}", token_id))
+    }
+
+    pub fn save_nft_collection(&self, collection: &crate::layer3::nft_collection::NftCollection) -> Result<(), CompassError> {
+        self.put(&format!("nft_collection:{}", collection.collection_id), collection)?;
+        for token_id in &collection.token_ids {
+            self.put(&format!("nft_collection_of:{}", token_id), &collection.collection_id)?;
+        }
+        Ok(())
+    }
+
+    pub fn get_nft_collection(&self, collection_id: &str) -> Result<Option<crate::layer3::nft_collection::NftCollection>, CompassError> {
+        self.get(&format!("nft_collection:{}", collection_id))
+    }
+
+    pub fn get_all_nft_collections(&self) -> Vec<crate::layer3::nft_collection::NftCollection> {
+        self.get_by_prefix("nft_collection:")
+    }
+
+    /// Collection an NFT belongs to, if any
+    pub fn get_collection_of_nft(&self, token_id: &str) -> Result<Option<String>, CompassError> {
+        self.get(&format!("nft_collection_of:{}", token_id))
+    }
+
     /// Find a Model NFT by the model_id used for inference (e.g., "price_decision_v2")
     /// Scans all NFTs and returns the first match where the architecture/weights contain the model_id
     pub fn get_model_nft_by_model_id(&self, model_id: &str) -> Result<Option<crate::layer3::model_nft::ModelNFT>, CompassError> {
@@ -450,6 +1510,17 @@ impl Storage {
         Ok(None)
     }
 
+    /// Whether the signed model invocation `request_id` was already paid for
+    pub fn is_invocation_settled(&self, request_id: &str) -> Result<bool, CompassError> {
+        Ok(self.get::<u64>(&format!("invoke_settled:{}", request_id))?.is_some())
+    }
+
+    /// Record that `request_id` was paid for at `height`, so it is never charged again
+    pub fn mark_invocation_settled(&self, request_id: &str, height: u64) -> Result<(), CompassError> {
+        self.put(&format!("invoke_settled:{}", request_id), &height)?;
+        self.record_event(StateEvent::InvocationSettled { request_id: request_id.to_string() })
+    }
+
     // ============================================================
     // PRICE ORACLE STORAGE
     // ============================================================
@@ -619,6 +1690,46 @@ impl Storage {
         self.get(&format!("model_pool:{}", pool_id))
     }
 
+    /// A worker's earnings ledger; empty for workers that never earned
+    pub fn get_worker_earnings(&self, worker: &str) -> Result<crate::layer3::earnings::WorkerEarnings, CompassError> {
+        Ok(self
+            .get(&format!("earnings:{}", worker))?
+            .unwrap_or_else(|| crate::layer3::earnings::WorkerEarnings::new(worker.to_string())))
+    }
+
+    pub fn save_worker_earnings(&self, earnings: &crate::layer3::earnings::WorkerEarnings) -> Result<(), CompassError> {
+        self.put(&format!("earnings:{}", earnings.worker), earnings)
+    }
+
+    /// Add `amount` to `worker`'s claimable earnings
+    pub fn accrue_earnings(
+        &self,
+        worker: &str,
+        reason: crate::layer3::earnings::EarningReason,
+        amount: u64,
+        reference: &str,
+    ) -> Result<(), CompassError> {
+        let mut earnings = self.get_worker_earnings(worker)?;
+        earnings.accrue(crate::layer3::earnings::EarningEntry {
+            reason,
+            amount,
+            reference: reference.to_string(),
+            accrued_at: crate::block::current_unix_timestamp_ms() / 1000,
+        });
+        self.save_worker_earnings(&earnings)
+    }
+
+    pub fn save_pool_governance(&self, gov: &crate::layer3::collective::PoolGovernance) -> Result<(), CompassError> {
+        self.put(&format!("pool_gov:{}", gov.pool_id), gov)
+    }
+
+    /// A pool's governance; pools nobody has proposed changes to yet have default parameters
+    pub fn get_pool_governance(&self, pool_id: &str) -> Result<crate::layer3::collective::PoolGovernance, CompassError> {
+        Ok(self
+            .get(&format!("pool_gov:{}", pool_id))?
+            .unwrap_or_else(|| crate::layer3::collective::PoolGovernance::new(pool_id.to_string())))
+    }
+
     pub fn get_all_model_pools(&self) -> Result<Vec<crate::layer3::collective::ModelPool>, CompassError> {
         let prefix = b"model_pool:";
         let mut pools = Vec::new();
@@ -639,7 +1750,7 @@ impl Storage {
     pub fn migrate_legacy_nfts(&self) -> Result<usize, CompassError> {
         let prefix = b"nft:";
         let mut count = 0;
-        let mut batch = sled::Batch::default();
+        let mut batch = WriteBatch::default();
         
         for item in self.db.scan_prefix(prefix) {
              if let Ok((key, value)) = item {
@@ -656,7 +1767,7 @@ impl Storage {
         }
         
         if count > 0 {
-            self.db.apply_batch(batch).map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+            self.db.apply_batch(batch)?;
             println!("Migration: Moved {} legacy 'nft:' records to 'model_nft:'", count);
         }
         Ok(count)
@@ -697,8 +1808,816 @@ impl Storage {
         self.get("paper_portfolio")
     }
 
+    // === Paper Trading Competitions ===
+
+    pub fn save_competition(&self, competition: &crate::layer3::competition::Competition) -> Result<(), CompassError> {
+        self.put(&format!("competition:{}", competition.competition_id), competition)
+    }
+
+    pub fn get_competition(&self, competition_id: &str) -> Result<Option<crate::layer3::competition::Competition>, CompassError> {
+        self.get(&format!("competition:{}", competition_id))
+    }
+
+    pub fn get_all_competitions(&self) -> Vec<crate::layer3::competition::Competition> {
+        self.get_by_prefix("competition:")
+    }
+
+    // ============================================================
+    // ASSET COMPLIANCE (issuer freeze authority)
+    // ============================================================
+
+    pub fn set_freeze_authority(&self, compass_asset: &str, authority: &str) -> Result<(), CompassError> {
+        self.put(&format!("freeze_auth:{}", compass_asset), authority)
+    }
+
+    pub fn get_freeze_authority(&self, compass_asset: &str) -> Result<Option<String>, CompassError> {
+        self.get(&format!("freeze_auth:{}", compass_asset))
+    }
+
+    /// Sequence number the next freeze/thaw for this asset must be signed with
+    pub fn get_freeze_seq(&self, compass_asset: &str) -> u64 {
+        self.get::<u64>(&format!("freeze_seq:{}", compass_asset)).ok().flatten().unwrap_or(0)
+    }
+
+    pub fn is_frozen(&self, compass_asset: &str, account: &str) -> bool {
+        self.db
+            .contains_key(format!("frozen:{}:{}", compass_asset, account).as_bytes())
+            .unwrap_or(false)
+    }
+
+    pub fn get_frozen_accounts(&self, compass_asset: &str) -> Vec<String> {
+        let prefix = format!("frozen:{}:", compass_asset);
+        self.db
+            .scan_prefix(prefix.as_bytes())
+            .filter_map(|item| item.ok())
+            .map(|(k, _)| String::from_utf8_lossy(&k[prefix.len()..]).to_string())
+            .collect()
+    }
+
+    /// Apply a freeze/thaw: flag, sequence bump and history entry are written atomically
+    pub fn apply_freeze(&self, record: &crate::vault::compliance::FreezeRecord) -> Result<(), CompassError> {
+        let flag_key = format!("frozen:{}:{}", record.compass_asset, record.account);
+        let history_key = format!("freeze_evt:{}:{:020}", record.compass_asset, record.seq);
+        let seq = bincode::serialize(&(record.seq + 1)).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let history = bincode::serialize(record).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+
+        let mut batch = WriteBatch::default();
+        if record.frozen {
+            batch.insert(flag_key.as_bytes(), vec![1u8]);
+        } else {
+            batch.remove(flag_key.as_bytes());
+        }
+        batch.insert(format!("freeze_seq:{}", record.compass_asset).as_bytes(), seq);
+        batch.insert(history_key.as_bytes(), history);
+        self.db.apply_batch(batch)
+    }
+
+    pub fn get_freeze_history(&self, compass_asset: &str) -> Vec<crate::vault::compliance::FreezeRecord> {
+        self.get_by_prefix(&format!("freeze_evt:{}:", compass_asset))
+    }
+
+    /// `account`'s vesting schedule, if a genesis allocation vests to it
+    pub fn get_vesting_schedule(&self, account: &str) -> Result<Option<crate::vesting::VestingSchedule>, CompassError> {
+        self.get(&format!("vesting:{}", account))
+    }
+
+    /// Every vesting schedule, in account order
+    pub fn get_vesting_schedules(&self) -> Vec<crate::vesting::VestingSchedule> {
+        self.get_by_prefix("vesting:")
+    }
+
+    /// Store a schedule as genesis sets it up; releases go through `release_vesting`
+    pub fn save_vesting_schedule(&self, schedule: &crate::vesting::VestingSchedule) -> Result<(), CompassError> {
+        self.put(&format!("vesting:{}", schedule.account), schedule)
+    }
+
+    /// Mark `amount` of `account`'s allocation released at `now_ms`, recording the
+    /// change so a reorg can undo it. The credit itself goes through `writer()`.
+    pub fn release_vesting(&self, account: &str, amount: u64, now_ms: u64) -> Result<(), CompassError> {
+        let mut schedule = self
+            .get_vesting_schedule(account)?
+            .ok_or_else(|| CompassError::InvalidState(format!("{} has no vesting schedule", account)))?;
+        let old = schedule.released;
+        schedule.release(amount, now_ms).map_err(CompassError::InvalidState)?;
+        self.save_vesting_schedule(&schedule)?;
+        self.record_event(StateEvent::VestingReleased { account: account.to_string(), old, new: schedule.released })
+    }
+
+
+    /// Total `asset` the emission schedule has minted
+    pub fn get_emitted(&self, asset: &str) -> Result<u64, CompassError> {
+        Ok(self.get(&format!("emission:{}", asset))?.unwrap_or(0))
+    }
+
+    /// Count `amount` of `asset` as minted by the emission schedule, recording the
+    /// change so a reorg can undo it. The credit itself goes through `writer()`.
+    pub fn record_emission(&self, asset: &str, amount: u64) -> Result<(), CompassError> {
+        let old = self.get_emitted(asset)?;
+        let new = old.saturating_add(amount);
+        self.put(&format!("emission:{}", asset), &new)?;
+        self.record_event(StateEvent::Emitted { asset: asset.to_string(), old, new })
+    }
+
+    /// Stability fee rate governance has set, in place of `[consensus.stability_fee]`
+    pub fn get_stability_fee_rate(&self) -> Result<Option<u64>, CompassError> {
+        self.get("governance:stability_fee_bps")
+    }
+
+    /// Set the stability fee rate, recording the change so a reorg can undo it
+    pub fn set_stability_fee_rate(&self, annual_rate_bps: u64) -> Result<(), CompassError> {
+        let old = self.get_stability_fee_rate()?;
+        self.put("governance:stability_fee_bps", &annual_rate_bps)?;
+        self.record_event(StateEvent::StabilityFeeSet { old, new: annual_rate_bps })
+    }
+
+    // ============================================================
+
+    /// Park `payload` until `execute_at_slot`, recording it so a reorg can unpark it
+    pub fn save_scheduled_tx(
+        &self,
+        execute_at_slot: u64,
+        tx_hash: &str,
+        payload: &crate::network::TransactionPayload,
+    ) -> Result<(), CompassError> {
+        self.put(&scheduled_key(execute_at_slot, tx_hash), &(execute_at_slot, payload))?;
+        self.record_event(StateEvent::TxScheduled { execute_at_slot, tx_hash: tx_hash.to_string() })
+    }
+
+    pub fn is_scheduled(&self, execute_at_slot: u64, tx_hash: &str) -> bool {
+        self.db.contains_key(scheduled_key(execute_at_slot, tx_hash)).unwrap_or(false)
+    }
+
+    /// How many transactions `account` has parked
+    pub fn scheduled_count(&self, account: &str) -> usize {
+        self.get_scheduled_txs()
+            .iter()
+            .filter(|(_, _, payload)| payload.get_account_id().as_deref() == Some(account))
+            .count()
+    }
+
+    /// All parked transactions as (slot, tx_hash, payload), earliest slot first
+    pub fn get_scheduled_txs(&self) -> Vec<(u64, String, crate::network::TransactionPayload)> {
+        let mut txs = Vec::new();
+        for (key, value) in self.db.scan_prefix(b"sched:").flatten() {
+            if let Ok((slot, payload)) = bincode::deserialize::<(u64, crate::network::TransactionPayload)>(&value) {
+                let key = String::from_utf8_lossy(&key).to_string();
+                let tx_hash = key.rsplit(':').next().unwrap_or_default().to_string();
+                txs.push((slot, tx_hash, payload));
+            }
+        }
+        txs
+    }
+
+    /// Remove and return every parked transaction whose slot is `<= height`
+    pub fn take_due_scheduled_txs(&self, height: u64) -> Vec<(u64, crate::network::TransactionPayload)> {
+        let end = format!("sched:{:020};", height); // ';' sorts after ':'
+        let mut due = Vec::new();
+        let mut batch = WriteBatch::default();
+        for (key, value) in self.db.range(b"sched:".as_slice()..end.as_bytes()).flatten() {
+            if let Ok(entry) = bincode::deserialize::<(u64, crate::network::TransactionPayload)>(&value) {
+                due.push(entry);
+            }
+            batch.remove(key);
+        }
+        if !due.is_empty() {
+            if let Err(e) = self.db.apply_batch(batch) {
+                // Leave them parked; they will be retried on the next pass
+                tracing::error!("Failed to release scheduled transactions: {}", e);
+                return Vec::new();
+            }
+        }
+        due
+    }
+
+    // ============================================================
+    // AUDIT LOG (append-only, hash-chained)
+    // ============================================================
+
+    /// Append a privileged action to the audit log, chaining it to the current head
+    pub fn append_audit_entry(
+        &self,
+        actor: &str,
+        action: crate::audit::AuditAction,
+        details: String,
+    ) -> Result<crate::audit::AuditEntry, CompassError> {
+        // Serialize appends so two writers can never chain off the same head
+        let _guard = AUDIT_APPEND_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+
+        let (seq, prev_hash) = match self.get::<(u64, String)>("audit_head")? {
+            Some((last_seq, last_hash)) => (last_seq + 1, last_hash),
+            None => (0, crate::audit::AUDIT_GENESIS_HASH.to_string()),
+        };
+        let entry = crate::audit::AuditEntry::new(seq, prev_hash, actor, action, details);
+
+        let mut batch = WriteBatch::default();
+        let encoded = bincode::serialize(&entry).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        let head = bincode::serialize(&(entry.seq, entry.hash.clone())).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        batch.insert(format!("audit:{:020}", entry.seq).as_bytes(), encoded);
+        batch.insert("audit_head".as_bytes(), head);
+        self.db.apply_batch(batch)?;
+
+        Ok(entry)
+    }
+
+    /// Audit entries with `start <= seq <= end`, in order
+    pub fn get_audit_log(&self, start: u64, end: u64) -> Vec<crate::audit::AuditEntry> {
+        let from = format!("audit:{:020}", start);
+        let to = format!("audit:{:020}", end);
+        let mut entries = Vec::new();
+        for (_key, value) in self.db.range(from.as_bytes()..=to.as_bytes()).flatten() {
+            if let Ok(entry) = bincode::deserialize::<crate::audit::AuditEntry>(&value) {
+                entries.push(entry);
+            }
+        }
+        entries
+    }
+
+    /// Number of entries in the audit log
+    pub fn get_audit_log_len(&self) -> u64 {
+        match self.get::<(u64, String)>("audit_head") {
+            Ok(Some((last_seq, _))) => last_seq + 1,
+            _ => 0,
+        }
+    }
+
+    // ============================================================
+    // LEADER SCHEDULE (per epoch, node-local cache)
+    // ============================================================
+
+    pub fn get_leader_schedule(&self, epoch: u64) -> Result<Option<crate::leader_schedule::LeaderSchedule>, CompassError> {
+        self.get(&format!("leader_schedule:{:020}", epoch))
+    }
+
+    pub fn save_leader_schedule(&self, schedule: &crate::leader_schedule::LeaderSchedule) -> Result<(), CompassError> {
+        self.put(&format!("leader_schedule:{:020}", schedule.epoch), schedule)
+    }
+
+    /// Collateral stakes as last saved by Layer 2, by entity
+    pub fn get_collateral_stakes(&self) -> Result<std::collections::HashMap<String, u64>, CompassError> {
+        let stakes = self.get_l2_stakes();
+        if !stakes.is_empty() {
+            return Ok(stakes);
+        }
+        // Databases Layer 2 hasn't migrated yet still hold the whole manager as one blob
+        Ok(self
+            .get::<crate::layer2::collateral::CollateralManager>("l2:collateral")?
+            .map(|c| c.stakes)
+            .unwrap_or_default())
+    }
+
+    // ============================================================
+    // LAYER 2 (one entry per asset, owner and stake)
+    // ============================================================
+
+    /// Entries under `prefix`, keyed by the rest of their key
+    fn get_keyed<T: for<'a> Deserialize<'a>>(&self, prefix: &str) -> std::collections::HashMap<String, T> {
+        self.db
+            .scan_prefix(prefix)
+            .filter_map(|item| item.ok())
+            .filter_map(|(key, val)| {
+                let id = std::str::from_utf8(&key).ok()?.strip_prefix(prefix)?.to_string();
+                Some((id, bincode::deserialize(&val).ok()?))
+            })
+            .collect()
+    }
+
+    pub fn get_l2_assets(&self) -> std::collections::HashMap<String, crate::layer3::model_nft::ModelNFT> {
+        self.get_keyed("l2:asset:")
+    }
+
+    /// Token IDs held by each owner
+    pub fn get_l2_asset_owners(&self) -> std::collections::HashMap<String, Vec<String>> {
+        self.get_keyed("l2:asset_owner:")
+    }
+
+    pub fn get_l2_stakes(&self) -> std::collections::HashMap<String, u64> {
+        self.get_keyed("l2:stake:")
+    }
+
+    /// Withdrawals waiting out the unbonding period, by entity
+    pub fn get_l2_unbonding(&self) -> std::collections::HashMap<String, Vec<crate::layer2::collateral::Unbonding>> {
+        self.get_keyed("l2:unbonding:")
+    }
+
+    pub fn get_l2_unbonding_of(&self, entity: &str) -> Result<Vec<crate::layer2::collateral::Unbonding>, CompassError> {
+        Ok(self.get(&format!("l2:unbonding:{}", entity))?.unwrap_or_default())
+    }
+
+    /// Take `entity`'s withdrawals that have finished unbonding by `slot` out of
+    /// its queue, recording the change so a reorg can undo it. Returns the amount
+    /// released; the credit itself goes through `writer()`.
+    pub fn release_unbonded(&self, entity: &str, slot: u64) -> Result<u64, CompassError> {
+        let old = self.get_l2_unbonding_of(entity)?;
+        let mut queue = old.clone();
+        let released: u64 = crate::layer2::collateral::release_matured(&mut queue, slot).iter().map(|u| u.amount).sum();
+        if released == 0 {
+            return Ok(0);
+        }
+        let key = format!("l2:unbonding:{}", entity);
+        if queue.is_empty() {
+            self.delete(&key)?;
+        } else {
+            self.put(&key, &queue)?;
+        }
+        self.record_event(StateEvent::UnbondingReleased { entity: entity.to_string(), old })?;
+        Ok(released)
+    }
+
+    pub fn get_l2_insurance_fund(&self) -> Result<u128, CompassError> {
+        Ok(self.get("l2:insurance_fund")?.unwrap_or(0))
+    }
+
+    /// Write the given assets and owners' holdings together; an owner left with
+    /// nothing loses their entry
+    pub fn save_l2_asset_entries(
+        &self,
+        assets: &[&crate::layer3::model_nft::ModelNFT],
+        owners: &[(&str, &[String])],
+    ) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        for nft in assets {
+            batch.put(&format!("l2:asset:{}", nft.token_id), *nft)?;
+        }
+        for (owner, ids) in owners {
+            let key = format!("l2:asset_owner:{}", owner);
+            if ids.is_empty() {
+                batch.delete(&key);
+            } else {
+                batch.put(&key, ids)?;
+            }
+        }
+        self.write(batch)
+    }
+
+    /// Write one entity's stake and unbonding queue together with the insurance
+    /// fund they may have fed
+    pub fn save_l2_stake(
+        &self,
+        entity: &str,
+        amount: u64,
+        unbonding: &[crate::layer2::collateral::Unbonding],
+        insurance_fund: u128,
+    ) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        batch.put(&format!("l2:stake:{}", entity), &amount)?;
+        let key = format!("l2:unbonding:{}", entity);
+        if unbonding.is_empty() {
+            batch.delete(&key);
+        } else {
+            batch.put(&key, unbonding)?;
+        }
+        batch.put("l2:insurance_fund", &insurance_fund)?;
+        self.write(batch)
+    }
+
+    /// Replace every Layer 2 asset and stake entry with `assets` and `collateral`
+    /// (migrating a legacy blob, or restoring a snapshot). Unbonding queues are
+    /// left as they are.
+    pub fn replace_l2_entries(
+        &self,
+        assets: &crate::layer2::assets::AssetManager,
+        collateral: &crate::layer2::collateral::CollateralManager,
+    ) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        for prefix in ["l2:asset:", "l2:asset_owner:", "l2:stake:"] {
+            for (key, _) in self.db.scan_prefix(prefix).flatten() {
+                batch.delete(&String::from_utf8_lossy(&key));
+            }
+        }
+        for (id, nft) in &assets.registry {
+            batch.put(&format!("l2:asset:{}", id), nft)?;
+        }
+        for (owner, ids) in assets.ownership.iter().filter(|(_, ids)| !ids.is_empty()) {
+            batch.put(&format!("l2:asset_owner:{}", owner), ids)?;
+        }
+        for (entity, amount) in &collateral.stakes {
+            batch.put(&format!("l2:stake:{}", entity), amount)?;
+        }
+        batch.put("l2:insurance_fund", &collateral.insurance_fund)?;
+        self.write(batch)
+    }
+
+    // ============================================================
+    // EPOCH REPORTS
+    // ============================================================
+
+    pub fn get_epoch_report(&self, epoch: u64) -> Result<Option<crate::epoch_report::EpochReport>, CompassError> {
+        self.get(&format!("epoch_report:{:020}", epoch))
+    }
+
+    pub fn save_epoch_report(&self, report: &crate::epoch_report::EpochReport) -> Result<(), CompassError> {
+        self.put(&format!("epoch_report:{:020}", report.epoch), report)
+    }
+
+    // ============================================================
+    // FINALITY
+    // ============================================================
+
+    pub fn get_finality_vote(&self, validator: &str) -> Result<Option<crate::finality::VoteRecord>, CompassError> {
+        self.get(&format!("finality_vote:{}", validator))
+    }
+
+    pub fn save_finality_vote(&self, validator: &str, record: &crate::finality::VoteRecord) -> Result<(), CompassError> {
+        self.put(&format!("finality_vote:{}", validator), record)
+    }
+
+    /// Height and hash of the highest finalized block
+    pub fn get_finalized(&self) -> Result<Option<(u64, String)>, CompassError> {
+        self.get("chain_info:finalized")
+    }
+
+    pub fn set_finalized(&self, height: u64, hash: &str) -> Result<(), CompassError> {
+        self.put("chain_info:finalized", &(height, hash.to_string()))
+    }
+
+    // ============================================================
+    // PEERS
+    // ============================================================
+
+    pub fn save_peer(&self, record: &crate::network::PeerRecord) -> Result<(), CompassError> {
+        self.put(&format!("peer:{}", record.peer_id), record)
+    }
+
+    /// Every peer this node has met, banned ones included
+    pub fn get_peers(&self) -> Vec<crate::network::PeerRecord> {
+        self.get_by_prefix("peer:")
+    }
+
+    // ============================================================
+    // FAUCET
+    // ============================================================
+
+    /// When the faucet last paid a rate-limit key (`addr:<address>` or `ip:<ip>`)
+    pub fn get_faucet_grant(&self, key: &str) -> Result<Option<u64>, CompassError> {
+        self.get(&format!("faucet:{}", key))
+    }
+
+    pub fn record_faucet_grant(&self, key: &str, timestamp: u64) -> Result<(), CompassError> {
+        self.put(&format!("faucet:{}", key), &timestamp)
+    }
+
+    // ============================================================
+    // SLOT LIVENESS
+    // ============================================================
+
+    pub fn get_slot_cursor(&self) -> Result<Option<crate::liveness::SlotCursor>, CompassError> {
+        self.get("liveness_cursor")
+    }
+
+    pub fn get_leader_liveness(&self, validator: &str) -> Result<crate::liveness::LeaderLiveness, CompassError> {
+        Ok(self
+            .get(&format!("liveness:{}", validator))?
+            .unwrap_or_else(|| crate::liveness::LeaderLiveness::new(validator)))
+    }
+
+    pub fn get_all_leader_liveness(&self) -> Vec<crate::liveness::LeaderLiveness> {
+        self.get_by_prefix("liveness:")
+    }
+
+    /// Record a produced slot (and any skipped run before it) in one batch
+    pub fn record_slot(
+        &self,
+        cursor: &crate::liveness::SlotCursor,
+        producer: &crate::liveness::LeaderLiveness,
+        skipped: Option<(&crate::liveness::SkippedSlots, &crate::liveness::LeaderLiveness)>,
+    ) -> Result<(), CompassError> {
+        fn encode<T: Serialize>(v: &T) -> Result<Vec<u8>, CompassError> {
+            bincode::serialize(v).map_err(|e| CompassError::SerializationError(e.to_string()))
+        }
+
+        let mut batch = WriteBatch::default();
+        batch.insert("liveness_cursor".as_bytes(), encode(cursor)?);
+        batch.insert(format!("liveness:{}", producer.validator).as_bytes(), encode(producer)?);
+        if let Some((run, leader)) = skipped {
+            batch.insert(format!("skipped:{:020}", run.first_slot).as_bytes(), encode(run)?);
+            // The leader may also be the producer; its record already includes the skip
+            if leader.validator != producer.validator {
+                batch.insert(format!("liveness:{}", leader.validator).as_bytes(), encode(leader)?);
+            }
+        }
+        self.db.apply_batch(batch)
+    }
+
+    /// Most recent skipped slot runs, newest first
+    pub fn get_skipped_slots(&self, limit: usize) -> Vec<crate::liveness::SkippedSlots> {
+        let mut runs = Vec::new();
+        for (_key, value) in self.db.scan_prefix(b"skipped:").rev().take(limit).flatten() {
+            if let Ok(run) = bincode::deserialize(&value) {
+                runs.push(run);
+            }
+        }
+        runs
+    }
+
+    // ============================================================
+    // TRANSACTION INDEX (tx hash -> status, receipt)
+    // ============================================================
+
+    pub fn index_tx(&self, tx_hash: &str, record: &TxRecord) -> Result<(), CompassError> {
+        self.put(&format!("txidx:{}", tx_hash), record)
+    }
+
+    pub fn get_tx_record(&self, tx_hash: &str) -> Result<Option<TxRecord>, CompassError> {
+        self.get(&format!("txidx:{}", tx_hash))
+    }
+
+    pub fn save_receipt(&self, receipt: &Receipt) -> Result<(), CompassError> {
+        self.put(&format!("receipt:{}", receipt.tx_hash), receipt)
+    }
+
+    pub fn get_receipt(&self, tx_hash: &str) -> Result<Option<Receipt>, CompassError> {
+        self.get(&format!("receipt:{}", tx_hash))
+    }
+
+    // ============================================================
+    // CONTRACTS
+    // ============================================================
+
+    /// Store a newly deployed contract with the state its `init` wrote, recording
+    /// both so a reorg can remove them
+    pub fn save_contract(
+        &self,
+        info: &crate::vm::ContractInfo,
+        code: &[u8],
+        state: &std::collections::BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        batch.put(&format!("contract:{}", info.address), info)?;
+        batch.put(&format!("contract_code:{}", info.address), code)?;
+        let old = self.stage_contract_state(&mut batch, &info.address, state)?;
+        self.write(batch)?;
+        self.record_event(StateEvent::ContractDeployed { address: info.address.clone() })?;
+        self.record_contract_writes(&info.address, old)
+    }
+
+    pub fn get_contract(&self, address: &str) -> Result<Option<crate::vm::ContractInfo>, CompassError> {
+        self.get(&format!("contract:{}", address))
+    }
+
+    pub fn get_contract_code(&self, address: &str) -> Result<Option<Vec<u8>>, CompassError> {
+        self.get(&format!("contract_code:{}", address))
+    }
+
+    /// One state value of a contract; state keys are hex encoded under the contract's prefix
+    pub fn get_contract_state(&self, address: &str, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+        self.get(&format!("contract_state:{}:{}", address, hex::encode(key)))
+    }
+
+    /// Apply a contract's state writes together; an empty value deletes the key.
+    /// Each write is recorded so a reorg can undo it.
+    pub fn save_contract_state(
+        &self,
+        address: &str,
+        state: &std::collections::BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<(), CompassError> {
+        let mut batch = self.batch();
+        let old = self.stage_contract_state(&mut batch, address, state)?;
+        self.write(batch)?;
+        self.record_contract_writes(address, old)
+    }
+
+    /// Stage `state` under `address`, returning each key's value before
+    fn stage_contract_state(
+        &self,
+        batch: &mut StorageBatch,
+        address: &str,
+        state: &std::collections::BTreeMap<Vec<u8>, Vec<u8>>,
+    ) -> Result<ContractStateBefore, CompassError> {
+        let mut old = Vec::with_capacity(state.len());
+        for (key, value) in state {
+            old.push((hex::encode(key), self.get_contract_state(address, key)?));
+            let key = format!("contract_state:{}:{}", address, hex::encode(key));
+            if value.is_empty() {
+                batch.delete(&key);
+            } else {
+                batch.put(&key, value)?;
+            }
+        }
+        Ok(old)
+    }
+
+    fn record_contract_writes(&self, address: &str, old: ContractStateBefore) -> Result<(), CompassError> {
+        for (key, old) in old {
+            self.record_event(StateEvent::ContractStateChanged { address: address.to_string(), key, old })?;
+        }
+        Ok(())
+    }
+
+    // ============================================================
+    // CLIENT SUBMISSION IDS (retry deduplication, node-local)
+    // ============================================================
+
+    /// Claim `client_id` for `sender`'s submission of `method`. Returns the
+    /// earlier submission if the pair was already claimed within
+    /// `CLIENT_ID_WINDOW_MS`; an older record, or a claim left in progress for
+    /// `CLIENT_ID_CLAIM_TIMEOUT_MS` by an attempt that never finished, is
+    /// replaced. The check and the claim are one compare-and-swap, so two
+    /// concurrent retries can't both get through.
+    pub fn reserve_client_id(
+        &self,
+        sender: &str,
+        client_id: &str,
+        method: &str,
+        now_ms: u64,
+    ) -> Result<Option<ClientSubmission>, CompassError> {
+        let key = client_id_key(sender, client_id);
+        let claim = bincode::serialize(&ClientSubmission { method: method.to_string(), response: None, submitted_at: now_ms })
+            .map_err(|e| CompassError::SerializationError(e.to_string()))?;
+        loop {
+            let current = self.db.get(key.as_bytes())?;
+            if let Some(bytes) = &current {
+                let earlier: ClientSubmission =
+                    bincode::deserialize(bytes).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                if !earlier.expired(now_ms) {
+                    return Ok(Some(earlier));
+                }
+            }
+            if self.db.compare_and_swap(key.as_bytes(), current, Some(&claim))? {
+                return Ok(None);
+            }
+        }
+    }
+
+    /// Record the response for a claimed id so retries get it back
+    pub fn complete_client_id(&self, sender: &str, client_id: &str, submission: &ClientSubmission) -> Result<(), CompassError> {
+        self.put(&client_id_key(sender, client_id), submission)
+    }
+
+    /// Drop the claim on an id whose submission failed, so a retry runs again
+    pub fn release_client_id(&self, sender: &str, client_id: &str) -> Result<(), CompassError> {
+        self.delete(&client_id_key(sender, client_id))
+    }
+
+    /// Delete submission records that no longer deduplicate anything; returns how many
+    pub fn prune_client_ids(&self, now_ms: u64) -> Result<usize, CompassError> {
+        let mut expired = Vec::new();
+        for (key, value) in self.db.scan_prefix("client_id:").flatten() {
+            let stale = bincode::deserialize::<ClientSubmission>(&value).map(|s| s.expired(now_ms)).unwrap_or(true);
+            if stale {
+                expired.push(key);
+            }
+        }
+        for key in &expired {
+            self.db.remove(key)?;
+        }
+        Ok(expired.len())
+    }
+
+    // ============================================================
+    // EVENT INDEX (state changes by height)
+    // ============================================================
+
+    /// Set the height new events are tagged with (called by Chain as blocks commit)
+    pub fn set_event_height(&self, height: u64) {
+        self.event_height.store(height, Ordering::SeqCst);
+    }
+
+    fn record_event(&self, event: StateEvent) -> Result<(), CompassError> {
+        let height = self.event_height.load(Ordering::SeqCst);
+        let id = self.db.generate_id()?;
+        let key = format!("evt:{:020}:{:020}", height, id);
+        let indexed = IndexedEvent { height, event };
+        if let Some(nft_key) = nft_event_key(key.as_bytes(), &indexed.event) {
+            self.put(&nft_key, &indexed)?;
+        }
+        self.put(&key, &indexed)
+    }
+
+    /// Note why `account` was paid; the payout itself goes through `writer()`
+    pub fn record_reward(&self, account: &str, asset: &str, amount: u64, reason: &str) -> Result<(), CompassError> {
+        self.record_event(StateEvent::RewardPaid {
+            account: account.to_string(),
+            asset: asset.to_string(),
+            amount,
+            reason: reason.to_string(),
+        })
+    }
+
+    /// Events tagged with `start <= height <= end`, in the order they were recorded
+    pub fn get_events_range(&self, start: u64, end: u64) -> Vec<IndexedEvent> {
+        let from = format!("evt:{:020}:", start);
+        let to = format!("evt:{:020};", end); // ';' sorts after ':'
+        let mut events = Vec::new();
+        for (_key, value) in self.db.range(from.as_bytes()..to.as_bytes()).flatten() {
+            if let Ok(event) = bincode::deserialize::<IndexedEvent>(&value) {
+                events.push(event);
+            }
+        }
+        events
+    }
+
+    /// Undo every balance, nonce and vesting release recorded at heights `start..end`, newest first,
+    /// and drop those events. Used when a reorg abandons the blocks at those heights.
+    /// NFT and vault events only carry the new value, so they are dropped without being
+    /// undone; the count of those is returned so the caller can report it.
+    pub fn revert_events(&self, start: u64, end: u64) -> Result<usize, CompassError> {
+        let from = format!("evt:{:020}:", start);
+        let to = format!("evt:{:020}:", end);
+        let mut entries = Vec::new();
+        for item in self.db.range(from.as_bytes()..to.as_bytes()) {
+            let (key, value) = item?;
+            let indexed: IndexedEvent = bincode::deserialize(&value)
+                .map_err(|e| CompassError::SerializationError(e.to_string()))?;
+            entries.push((key, indexed.event));
+        }
+
+        let mut batch = WriteBatch::default();
+        let mut not_reverted = 0;
+        for (key, event) in entries.into_iter().rev() {
+            // The ownership change itself stays, but it no longer happened on the canonical chain
+            if let Some(nft_key) = nft_event_key(&key, &event) {
+                batch.remove(nft_key.as_bytes());
+            }
+            match event {
+                StateEvent::BalanceChanged { account, asset, old, .. } => {
+                    batch.insert(format!("bal:{}:{}", account, asset).as_bytes(), old.to_be_bytes());
+                }
+                StateEvent::NonceChanged { account, old, .. } => {
+                    let bytes = bincode::serialize(&old).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                    batch.insert(format!("nonce:{}", account).as_bytes(), bytes);
+                }
+                StateEvent::VestingReleased { account, old, .. } => {
+                    if let Some(mut schedule) = self.get_vesting_schedule(&account)? {
+                        schedule.released = old;
+                        let bytes = bincode::serialize(&schedule).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                        batch.insert(format!("vesting:{}", account).as_bytes(), bytes);
+                    }
+                }
+                StateEvent::Emitted { asset, old, .. } => {
+                    let bytes = bincode::serialize(&old).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                    batch.insert(format!("emission:{}", asset).as_bytes(), bytes);
+                }
+                StateEvent::TxScheduled { execute_at_slot, tx_hash } => {
+                    batch.remove(scheduled_key(execute_at_slot, &tx_hash).as_bytes());
+                }
+                StateEvent::StabilityFeeSet { old: Some(old), .. } => {
+                    let bytes = bincode::serialize(&old).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                    batch.insert("governance:stability_fee_bps".as_bytes(), bytes);
+                }
+                StateEvent::StabilityFeeSet { old: None, .. } => {
+                    batch.remove("governance:stability_fee_bps".as_bytes());
+                }
+                StateEvent::UnbondingReleased { entity, old } => {
+                    let bytes = bincode::serialize(&old).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                    batch.insert(format!("l2:unbonding:{}", entity).as_bytes(), bytes);
+                }
+                StateEvent::ContractDeployed { address } => {
+                    batch.remove(format!("contract:{}", address).as_bytes());
+                    batch.remove(format!("contract_code:{}", address).as_bytes());
+                }
+                StateEvent::ContractStateChanged { address, key, old: Some(old) } => {
+                    let bytes = bincode::serialize(&old).map_err(|e| CompassError::SerializationError(e.to_string()))?;
+                    batch.insert(format!("contract_state:{}:{}", address, key).as_bytes(), bytes);
+                }
+                StateEvent::ContractStateChanged { address, key, old: None } => {
+                    batch.remove(format!("contract_state:{}:{}", address, key).as_bytes());
+                }
+                StateEvent::InvocationSettled { request_id } => {
+                    batch.remove(format!("invoke_settled:{}", request_id).as_bytes());
+                }
+                StateEvent::PayoutTxUsed { external_tx } => {
+                    batch.remove(format!("payout_tx:{}", external_tx).as_bytes());
+                }
+                StateEvent::NftOwnerChanged { .. } | StateEvent::VaultUpdated { .. } => not_reverted += 1,
+                StateEvent::RewardPaid { .. } | StateEvent::OrderFilled { .. } | StateEvent::OrderCancelled { .. } => {}
+            }
+            batch.remove(key);
+        }
+        self.db.apply_batch(batch)?;
+        Ok(not_reverted)
+    }
+
     pub fn flush(&self) -> Result<(), CompassError> {
-        self.db.flush().map_err(|e| CompassError::DatabaseError(e.to_string()))?;
+        self.db.flush()?;
+        self.flush.blocks_since.store(0, Ordering::SeqCst);
+        *self.flush.last_flush.lock().unwrap_or_else(|e| e.into_inner()) = std::time::Instant::now();
         Ok(())
     }
+
+    /// Count a committed block, flushing if that makes `every_blocks`. Returns
+    /// whether it flushed.
+    pub fn block_committed(&self) -> Result<bool, CompassError> {
+        let pending = self.flush.blocks_since.fetch_add(1, Ordering::SeqCst) + 1;
+        let every = self.flush.policy.every_blocks;
+        if every == 0 || pending < every {
+            return Ok(false);
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    /// Flush if the policy's interval has passed since the last flush; the node
+    /// calls this on a timer so quiet periods still reach the disk
+    pub fn flush_if_due(&self) -> Result<bool, CompassError> {
+        let last = *self.flush.last_flush.lock().unwrap_or_else(|e| e.into_inner());
+        if last.elapsed() < self.flush.policy.interval {
+            return Ok(false);
+        }
+        self.flush()?;
+        Ok(true)
+    }
+
+    pub fn flush_policy(&self) -> FlushPolicy {
+        self.flush.policy
+    }
 }
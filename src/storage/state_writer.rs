@@ -0,0 +1,202 @@
+//! The one way to change balances and nonces.
+//!
+//! `Storage`'s raw balance and nonce setters are private to the storage module;
+//! everything else goes through a `StateWriter`. Changes are staged in a
+//! `StateBatch` and checked together on commit: balances cannot go below zero
+//! or overflow, nonces only move forward, and nothing is written unless every
+//! change passes. A commit holds a process-wide lock across its reads and
+//! writes, so two writers cannot interleave a read-modify-write on the same
+//! balance. Each write still records its `StateEvent`, so reorgs can undo it.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use super::Storage;
+use crate::error::CompassError;
+
+static STATE_WRITE_LOCK: Mutex<()> = Mutex::new(());
+
+#[derive(Debug, Clone)]
+enum Change {
+    Credit { account: String, asset: String, amount: u64 },
+    Debit { account: String, asset: String, amount: u64 },
+    SetBalance { account: String, asset: String, amount: u64 },
+    SetNonce { account: String, nonce: u64 },
+}
+
+/// Handle for mutating ledger state; get one with `Storage::writer`
+pub struct StateWriter<'a> {
+    storage: &'a Storage,
+}
+
+impl<'a> StateWriter<'a> {
+    pub(super) fn new(storage: &'a Storage) -> Self {
+        Self { storage }
+    }
+
+    /// Start a set of changes that commit together or not at all
+    pub fn batch(&self) -> StateBatch<'a> {
+        StateBatch { storage: self.storage, changes: Vec::new() }
+    }
+
+    pub fn credit(&self, account: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
+        self.batch().credit(account, asset, amount).commit()
+    }
+
+    pub fn debit(&self, account: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
+        self.batch().debit(account, asset, amount).commit()
+    }
+
+    pub fn transfer(&self, from: &str, to: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
+        self.batch().transfer(from, to, asset, amount).commit()
+    }
+
+    /// Overwrite a balance outright. For genesis allocations and setup; ledger
+    /// movements should use credit/debit so they cannot mint or burn by mistake.
+    pub fn set_balance(&self, account: &str, asset: &str, amount: u64) -> Result<(), CompassError> {
+        self.batch().set_balance(account, asset, amount).commit()
+    }
+
+    pub fn set_nonce(&self, account: &str, nonce: u64) -> Result<(), CompassError> {
+        self.batch().set_nonce(account, nonce).commit()
+    }
+}
+
+/// Staged balance and nonce changes, applied in order by `commit`
+#[must_use = "a batch does nothing until it is committed"]
+pub struct StateBatch<'a> {
+    storage: &'a Storage,
+    changes: Vec<Change>,
+}
+
+impl<'a> StateBatch<'a> {
+    pub fn credit(mut self, account: &str, asset: &str, amount: u64) -> Self {
+        self.changes.push(Change::Credit { account: account.to_string(), asset: asset.to_string(), amount });
+        self
+    }
+
+    pub fn debit(mut self, account: &str, asset: &str, amount: u64) -> Self {
+        self.changes.push(Change::Debit { account: account.to_string(), asset: asset.to_string(), amount });
+        self
+    }
+
+    pub fn transfer(self, from: &str, to: &str, asset: &str, amount: u64) -> Self {
+        self.debit(from, asset, amount).credit(to, asset, amount)
+    }
+
+    pub fn set_balance(mut self, account: &str, asset: &str, amount: u64) -> Self {
+        self.changes.push(Change::SetBalance { account: account.to_string(), asset: asset.to_string(), amount });
+        self
+    }
+
+    pub fn set_nonce(mut self, account: &str, nonce: u64) -> Self {
+        self.changes.push(Change::SetNonce { account: account.to_string(), nonce });
+        self
+    }
+
+    /// Check every change against the running balances, then write them all
+    pub fn commit(self) -> Result<(), CompassError> {
+        let _guard = STATE_WRITE_LOCK.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        let mut balances: HashMap<(String, String), u64> = HashMap::new();
+        let mut nonces: HashMap<String, u64> = HashMap::new();
+        for change in &self.changes {
+            match change {
+                Change::Credit { account, asset, amount } => {
+                    let balance = self.staged_balance(&mut balances, account, asset)?;
+                    *balance = balance.checked_add(*amount).ok_or_else(|| {
+                        CompassError::Overflow(format!("{} balance of {} plus {}", asset, account, amount))
+                    })?;
+                }
+                Change::Debit { account, asset, amount } => {
+                    let balance = self.staged_balance(&mut balances, account, asset)?;
+                    *balance = balance.checked_sub(*amount).ok_or_else(|| {
+                        CompassError::InvalidState(format!(
+                            "insufficient {} balance for {}: has {}, needs {}",
+                            asset, account, balance, amount
+                        ))
+                    })?;
+                }
+                Change::SetBalance { account, asset, amount } => {
+                    balances.insert((account.clone(), asset.clone()), *amount);
+                }
+                Change::SetNonce { account, nonce } => {
+                    let current = match nonces.get(account) {
+                        Some(n) => *n,
+                        None => self.storage.get_nonce(account)?,
+                    };
+                    if *nonce <= current {
+                        return Err(CompassError::InvalidState(format!(
+                            "nonce for {} must move forward: at {}, got {}",
+                            account, current, nonce
+                        )));
+                    }
+                    nonces.insert(account.clone(), *nonce);
+                }
+            }
+        }
+
+        for ((account, asset), amount) in balances {
+            self.storage.set_balance(&account, &asset, amount)?;
+        }
+        for (account, nonce) in nonces {
+            self.storage.set_nonce(&account, nonce)?;
+        }
+        Ok(())
+    }
+
+    fn staged_balance<'m>(
+        &self,
+        balances: &'m mut HashMap<(String, String), u64>,
+        account: &str,
+        asset: &str,
+    ) -> Result<&'m mut u64, CompassError> {
+        let key = (account.to_string(), asset.to_string());
+        if !balances.contains_key(&key) {
+            let current = self.storage.get_balance(account, asset)?;
+            balances.insert(key.clone(), current);
+        }
+        Ok(balances.get_mut(&key).expect("inserted above"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_batch_is_checked_as_a_whole() {
+        let storage = Storage::new_in_memory().unwrap();
+        let writer = storage.writer();
+        writer.credit("alice", "Compass", 100).unwrap();
+
+        // Fee and amount each fit, together they do not: nothing is written
+        let overdrawn = writer
+            .batch()
+            .transfer("alice", "foundation", "Compass", 10)
+            .transfer("alice", "bob", "Compass", 95)
+            .set_nonce("alice", 1)
+            .commit();
+        assert!(overdrawn.is_err());
+        assert_eq!(storage.get_balance("alice", "Compass").unwrap(), 100);
+        assert_eq!(storage.get_balance("foundation", "Compass").unwrap(), 0);
+        assert_eq!(storage.get_nonce("alice").unwrap(), 0);
+
+        writer
+            .batch()
+            .transfer("alice", "foundation", "Compass", 10)
+            .transfer("alice", "bob", "Compass", 90)
+            .set_nonce("alice", 1)
+            .commit()
+            .unwrap();
+        assert_eq!(storage.get_balance("alice", "Compass").unwrap(), 0);
+        assert_eq!(storage.get_balance("bob", "Compass").unwrap(), 90);
+        assert_eq!(storage.get_nonce("alice").unwrap(), 1);
+
+        // Nonces never go back, balances never wrap
+        assert!(writer.set_nonce("alice", 1).is_err());
+        writer.set_balance("carol", "Compass", u64::MAX).unwrap();
+        assert!(matches!(writer.credit("carol", "Compass", 1), Err(CompassError::Overflow(_))));
+        assert!(writer.debit("bob", "Compass", 91).is_err());
+    }
+}
@@ -0,0 +1,561 @@
+// Key-value engines under `Storage`
+//
+// `Storage` keeps everything in one ordered keyspace of prefixed keys (`bal:`,
+// `block:`, `evt:`, ...) and needs only a handful of operations from the engine
+// below it: point reads and writes, ordered range scans in both directions,
+// atomic batches and a compare-and-swap. `ChainStore` is that surface. sled is
+// the default; RocksDB (`db_backend = "rocksdb"`, built with the `rocksdb`
+// feature) holds up better once the block history runs to many gigabytes.
+use std::collections::BTreeSet;
+use std::ops::{Bound, RangeBounds};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::CompassError;
+
+pub type KvPair = (Vec<u8>, Vec<u8>);
+
+/// Entries in key order; `.rev()` walks them newest-key first
+pub type KvIter<'a> = Box<dyn DoubleEndedIterator<Item = Result<KvPair, CompassError>> + 'a>;
+
+/// `[node] db_backend`
+#[derive(Debug, Deserialize, Serialize, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum DbBackend {
+    #[default]
+    Sled,
+    Rocksdb,
+}
+
+impl std::fmt::Display for DbBackend {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DbBackend::Sled => write!(f, "sled"),
+            DbBackend::Rocksdb => write!(f, "rocksdb"),
+        }
+    }
+}
+
+/// Writes applied together by `ChainStore::apply_batch`, in order
+#[derive(Default)]
+pub struct WriteBatch {
+    ops: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+impl WriteBatch {
+    pub fn insert(&mut self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) {
+        self.ops.push((key.as_ref().to_vec(), Some(value.as_ref().to_vec())));
+    }
+
+    pub fn remove(&mut self, key: impl AsRef<[u8]>) {
+        self.ops.push((key.as_ref().to_vec(), None));
+    }
+
+    pub fn len(&self) -> usize {
+        self.ops.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ops.is_empty()
+    }
+}
+
+/// What `Storage` needs from a key-value engine
+pub trait ChainStore: Send + Sync {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError>;
+    /// Returns the value it replaced
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, CompassError>;
+    /// Returns the value it removed
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError>;
+    /// Set `key` to `new` (or remove it) only if it holds `expected`; whether it did
+    fn compare_and_swap(&self, key: &[u8], expected: Option<&[u8]>, new: Option<&[u8]>) -> Result<bool, CompassError>;
+    fn range<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> KvIter<'a>;
+    /// All or nothing
+    fn apply_batch(&self, batch: WriteBatch) -> Result<(), CompassError>;
+    /// Make every write so far durable
+    fn flush(&self) -> Result<(), CompassError>;
+    /// Unique, increasing across restarts
+    fn generate_id(&self) -> Result<u64, CompassError>;
+}
+
+fn db_err(e: impl std::fmt::Display) -> CompassError {
+    CompassError::DatabaseError(e.to_string())
+}
+
+/// First key after every key starting with `prefix`; `None` when there is none
+fn prefix_end(prefix: &[u8]) -> Option<Vec<u8>> {
+    let mut end = prefix.to_vec();
+    while let Some(last) = end.pop() {
+        if last < u8::MAX {
+            end.push(last + 1);
+            return Some(end);
+        }
+    }
+    None
+}
+
+/// Open the `backend` database at `path`. `cache_bytes` caps sled's page cache or
+/// RocksDB's block cache.
+pub fn open(path: &str, backend: DbBackend, cache_bytes: Option<u64>) -> Result<Store, CompassError> {
+    match backend {
+        DbBackend::Sled => Ok(Store::new(SledStore::open(path, cache_bytes)?)),
+        #[cfg(feature = "rocksdb")]
+        DbBackend::Rocksdb => Ok(Store::new(rocks::RocksStore::open(path, cache_bytes)?)),
+        #[cfg(not(feature = "rocksdb"))]
+        DbBackend::Rocksdb => Err(CompassError::DatabaseError(
+            "this build has no RocksDB support (rebuild with --features rocksdb)".to_string(),
+        )),
+    }
+}
+
+/// Copy every entry of `from` into `to`, `batch_size` at a time; returns how many.
+/// Re-running after an interruption just writes the same entries again.
+pub fn copy_all(from: &Store, to: &Store, batch_size: usize, mut progress: impl FnMut(u64)) -> Result<u64, CompassError> {
+    let mut copied = 0u64;
+    let mut batch = WriteBatch::default();
+    for item in from.scan_prefix(b"") {
+        let (key, value) = item?;
+        batch.insert(key, value);
+        if batch.len() >= batch_size {
+            copied += batch.len() as u64;
+            to.apply_batch(std::mem::take(&mut batch))?;
+            progress(copied);
+        }
+    }
+    copied += batch.len() as u64;
+    to.apply_batch(batch)?;
+    to.flush()?;
+    Ok(copied)
+}
+
+/// Shared handle to a `ChainStore`, with sled-style generic arguments so call
+/// sites can pass `&str`, `String` or bytes alike. Once `track_state_keys` is on,
+/// it notes every key written under `STATE_ROOT_PREFIXES`, so the state tree can
+/// catch up without rescanning.
+#[derive(Clone)]
+pub struct Store {
+    engine: Arc<dyn ChainStore>,
+    touched: Arc<Mutex<Option<BTreeSet<Vec<u8>>>>>,
+}
+
+impl Store {
+    pub fn new(store: impl ChainStore + 'static) -> Self {
+        Store { engine: Arc::new(store), touched: Arc::new(Mutex::new(None)) }
+    }
+
+    fn note(&self, key: &[u8]) {
+        if !crate::storage::STATE_ROOT_PREFIXES.iter().any(|p| key.starts_with(p.as_bytes())) {
+            return;
+        }
+        if let Some(touched) = self.touched.lock().unwrap_or_else(|e| e.into_inner()).as_mut() {
+            touched.insert(key.to_vec());
+        }
+    }
+
+    /// Start noting state keys written from now on, dropping any noted so far
+    pub fn track_state_keys(&self) {
+        *self.touched.lock().unwrap_or_else(|e| e.into_inner()) = Some(BTreeSet::new());
+    }
+
+    /// State keys written since the last call; None if tracking is off
+    pub fn take_state_keys(&self) -> Option<BTreeSet<Vec<u8>>> {
+        self.touched.lock().unwrap_or_else(|e| e.into_inner()).as_mut().map(std::mem::take)
+    }
+
+    pub fn get(&self, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>, CompassError> {
+        self.engine.get(key.as_ref())
+    }
+
+    pub fn insert(&self, key: impl AsRef<[u8]>, value: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>, CompassError> {
+        self.note(key.as_ref());
+        self.engine.insert(key.as_ref(), value.as_ref())
+    }
+
+    pub fn remove(&self, key: impl AsRef<[u8]>) -> Result<Option<Vec<u8>>, CompassError> {
+        self.note(key.as_ref());
+        self.engine.remove(key.as_ref())
+    }
+
+    pub fn contains_key(&self, key: impl AsRef<[u8]>) -> Result<bool, CompassError> {
+        Ok(self.engine.get(key.as_ref())?.is_some())
+    }
+
+    pub fn compare_and_swap(
+        &self,
+        key: impl AsRef<[u8]>,
+        expected: Option<impl AsRef<[u8]>>,
+        new: Option<impl AsRef<[u8]>>,
+    ) -> Result<bool, CompassError> {
+        self.note(key.as_ref());
+        self.engine.compare_and_swap(key.as_ref(), expected.as_ref().map(|v| v.as_ref()), new.as_ref().map(|v| v.as_ref()))
+    }
+
+    pub fn range<K: AsRef<[u8]>, R: RangeBounds<K>>(&self, range: R) -> KvIter<'_> {
+        fn bound<K: AsRef<[u8]>>(b: Bound<&K>) -> Bound<&[u8]> {
+            match b {
+                Bound::Included(k) => Bound::Included(k.as_ref()),
+                Bound::Excluded(k) => Bound::Excluded(k.as_ref()),
+                Bound::Unbounded => Bound::Unbounded,
+            }
+        }
+        self.engine.range(bound(range.start_bound()), bound(range.end_bound()))
+    }
+
+    pub fn scan_prefix(&self, prefix: impl AsRef<[u8]>) -> KvIter<'_> {
+        let prefix = prefix.as_ref();
+        match prefix_end(prefix) {
+            Some(end) => self.engine.range(Bound::Included(prefix), Bound::Excluded(&end)),
+            None => self.engine.range(Bound::Included(prefix), Bound::Unbounded),
+        }
+    }
+
+    pub fn apply_batch(&self, batch: WriteBatch) -> Result<(), CompassError> {
+        for (key, _) in &batch.ops {
+            self.note(key);
+        }
+        self.engine.apply_batch(batch)
+    }
+
+    pub fn flush(&self) -> Result<(), CompassError> {
+        self.engine.flush()
+    }
+
+    pub fn generate_id(&self) -> Result<u64, CompassError> {
+        self.engine.generate_id()
+    }
+}
+
+pub struct SledStore {
+    db: sled::Db,
+}
+
+impl SledStore {
+    pub fn open(path: &str, cache_bytes: Option<u64>) -> Result<Self, CompassError> {
+        let mut config = sled::Config::new().path(path);
+        if let Some(bytes) = cache_bytes {
+            config = config.cache_capacity(bytes);
+        }
+        Ok(SledStore { db: config.open().map_err(db_err)? })
+    }
+
+    /// Removed when dropped
+    pub fn temporary() -> Result<Self, CompassError> {
+        Ok(SledStore { db: sled::Config::new().temporary(true).open().map_err(db_err)? })
+    }
+}
+
+impl ChainStore for SledStore {
+    fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+        Ok(self.db.get(key).map_err(db_err)?.map(|v| v.to_vec()))
+    }
+
+    fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+        Ok(self.db.insert(key, value).map_err(db_err)?.map(|v| v.to_vec()))
+    }
+
+    fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+        Ok(self.db.remove(key).map_err(db_err)?.map(|v| v.to_vec()))
+    }
+
+    fn compare_and_swap(&self, key: &[u8], expected: Option<&[u8]>, new: Option<&[u8]>) -> Result<bool, CompassError> {
+        Ok(self.db.compare_and_swap(key, expected, new).map_err(db_err)?.is_ok())
+    }
+
+    fn range<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> KvIter<'a> {
+        Box::new(
+            self.db
+                .range::<&[u8], _>((start, end))
+                .map(|item| item.map(|(k, v)| (k.to_vec(), v.to_vec())).map_err(db_err)),
+        )
+    }
+
+    fn apply_batch(&self, batch: WriteBatch) -> Result<(), CompassError> {
+        let mut sled_batch = sled::Batch::default();
+        for (key, value) in batch.ops {
+            match value {
+                Some(value) => sled_batch.insert(key, value),
+                None => sled_batch.remove(key),
+            }
+        }
+        self.db.apply_batch(sled_batch).map_err(db_err)
+    }
+
+    fn flush(&self) -> Result<(), CompassError> {
+        self.db.flush().map_err(db_err)?;
+        Ok(())
+    }
+
+    fn generate_id(&self) -> Result<u64, CompassError> {
+        self.db.generate_id().map_err(db_err)
+    }
+}
+
+#[cfg(feature = "rocksdb")]
+mod rocks {
+    use std::ops::Bound;
+    use std::sync::Mutex;
+
+    use rocksdb::{BlockBasedOptions, Cache, DBRawIterator, Options, DB};
+
+    use super::{db_err, ChainStore, KvIter, KvPair, WriteBatch};
+    use crate::error::CompassError;
+
+    /// Where `generate_id` keeps its counter; sorts before every `Storage` prefix
+    const ID_COUNTER_KEY: &[u8] = b"\x00generated_id";
+
+    pub struct RocksStore {
+        db: DB,
+        // Must outlive `db`
+        _cache: Option<Cache>,
+        // RocksDB has no compare-and-swap or insert-returning-old of its own: the
+        // read and the write happen under this lock. Plain writes don't take it,
+        // which is fine for `Storage`, whose read-modify-writes all come through here.
+        rmw: Mutex<()>,
+    }
+
+    impl RocksStore {
+        pub fn open(path: &str, cache_bytes: Option<u64>) -> Result<Self, CompassError> {
+            let mut opts = Options::default();
+            opts.create_if_missing(true);
+            let cache = cache_bytes.map(|bytes| Cache::new_lru_cache(bytes as usize));
+            if let Some(cache) = &cache {
+                let mut table = BlockBasedOptions::default();
+                table.set_block_cache(cache);
+                opts.set_block_based_table_factory(&table);
+            }
+            let db = DB::open(&opts, path).map_err(db_err)?;
+            Ok(RocksStore { db, _cache: cache, rmw: Mutex::new(()) })
+        }
+    }
+
+    impl ChainStore for RocksStore {
+        fn get(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+            self.db.get(key).map_err(db_err)
+        }
+
+        fn insert(&self, key: &[u8], value: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+            let _guard = self.rmw.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = self.db.get(key).map_err(db_err)?;
+            self.db.put(key, value).map_err(db_err)?;
+            Ok(previous)
+        }
+
+        fn remove(&self, key: &[u8]) -> Result<Option<Vec<u8>>, CompassError> {
+            let _guard = self.rmw.lock().unwrap_or_else(|e| e.into_inner());
+            let previous = self.db.get(key).map_err(db_err)?;
+            if previous.is_some() {
+                self.db.delete(key).map_err(db_err)?;
+            }
+            Ok(previous)
+        }
+
+        fn compare_and_swap(&self, key: &[u8], expected: Option<&[u8]>, new: Option<&[u8]>) -> Result<bool, CompassError> {
+            let _guard = self.rmw.lock().unwrap_or_else(|e| e.into_inner());
+            if self.db.get(key).map_err(db_err)?.as_deref() != expected {
+                return Ok(false);
+            }
+            match new {
+                Some(value) => self.db.put(key, value).map_err(db_err)?,
+                None => self.db.delete(key).map_err(db_err)?,
+            }
+            Ok(true)
+        }
+
+        fn range<'a>(&'a self, start: Bound<&[u8]>, end: Bound<&[u8]>) -> KvIter<'a> {
+            Box::new(RocksRange::new(&self.db, start, end))
+        }
+
+        fn apply_batch(&self, batch: WriteBatch) -> Result<(), CompassError> {
+            let mut rocks_batch = rocksdb::WriteBatch::default();
+            for (key, value) in batch.ops {
+                match value {
+                    Some(value) => rocks_batch.put(key, value),
+                    None => rocks_batch.delete(key),
+                }
+            }
+            self.db.write(rocks_batch).map_err(db_err)
+        }
+
+        // Writes go to the WAL as they happen; syncing it is what makes them durable
+        fn flush(&self) -> Result<(), CompassError> {
+            self.db.flush_wal(true).map_err(db_err)
+        }
+
+        fn generate_id(&self) -> Result<u64, CompassError> {
+            let _guard = self.rmw.lock().unwrap_or_else(|e| e.into_inner());
+            let next = match self.db.get(ID_COUNTER_KEY).map_err(db_err)? {
+                Some(bytes) => u64::from_be_bytes(bytes.as_slice().try_into().map_err(db_err)?),
+                None => 0,
+            };
+            self.db.put(ID_COUNTER_KEY, (next + 1).to_be_bytes()).map_err(db_err)?;
+            Ok(next)
+        }
+    }
+
+    /// Bounded scan walked from both ends by two raw iterators, which stop when
+    /// they meet
+    struct RocksRange<'a> {
+        front: DBRawIterator<'a>,
+        back: DBRawIterator<'a>,
+        start: Bound<Vec<u8>>,
+        end: Bound<Vec<u8>>,
+        // Last keys handed out from each end
+        last_front: Option<Vec<u8>>,
+        last_back: Option<Vec<u8>>,
+        done: bool,
+    }
+
+    impl<'a> RocksRange<'a> {
+        fn new(db: &'a DB, start: Bound<&[u8]>, end: Bound<&[u8]>) -> Self {
+            let mut front = db.raw_iterator();
+            match start {
+                Bound::Included(k) | Bound::Excluded(k) => front.seek(k),
+                Bound::Unbounded => front.seek_to_first(),
+            }
+            let mut back = db.raw_iterator();
+            match end {
+                Bound::Included(k) | Bound::Excluded(k) => back.seek_for_prev(k),
+                Bound::Unbounded => back.seek_to_last(),
+            }
+            RocksRange {
+                front,
+                back,
+                start: start.map(|k| k.to_vec()),
+                end: end.map(|k| k.to_vec()),
+                last_front: None,
+                last_back: None,
+                done: false,
+            }
+        }
+
+        fn in_range(&self, key: &[u8]) -> bool {
+            let after_start = match &self.start {
+                Bound::Included(s) => key >= s.as_slice(),
+                Bound::Excluded(s) => key > s.as_slice(),
+                Bound::Unbounded => true,
+            };
+            let before_end = match &self.end {
+                Bound::Included(e) => key <= e.as_slice(),
+                Bound::Excluded(e) => key < e.as_slice(),
+                Bound::Unbounded => true,
+            };
+            after_start && before_end
+        }
+
+        /// The entry under `iter`, or why there is none
+        fn current(iter: &DBRawIterator<'a>) -> Option<Result<KvPair, CompassError>> {
+            match (iter.key(), iter.value()) {
+                (Some(k), Some(v)) => Some(Ok((k.to_vec(), v.to_vec()))),
+                _ => iter.status().err().map(|e| Err(db_err(e))),
+            }
+        }
+    }
+
+    impl Iterator for RocksRange<'_> {
+        type Item = Result<KvPair, CompassError>;
+
+        fn next(&mut self) -> Option<Self::Item> {
+            while !self.done {
+                let Some(item) = Self::current(&self.front) else { break };
+                let (key, value) = match item {
+                    Ok(kv) => kv,
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                };
+                self.front.next();
+                // Skips an excluded start key, which the seek lands on
+                if matches!(&self.start, Bound::Excluded(s) if *s == key) {
+                    continue;
+                }
+                if !self.in_range(&key) || self.last_back.as_ref().is_some_and(|b| key >= *b) {
+                    break;
+                }
+                self.last_front = Some(key.clone());
+                return Some(Ok((key, value)));
+            }
+            self.done = true;
+            None
+        }
+    }
+
+    impl DoubleEndedIterator for RocksRange<'_> {
+        fn next_back(&mut self) -> Option<Self::Item> {
+            while !self.done {
+                let Some(item) = Self::current(&self.back) else { break };
+                let (key, value) = match item {
+                    Ok(kv) => kv,
+                    Err(e) => {
+                        self.done = true;
+                        return Some(Err(e));
+                    }
+                };
+                self.back.prev();
+                // Skips an excluded end key, which the seek lands on
+                if matches!(&self.end, Bound::Excluded(e) if *e == key) {
+                    continue;
+                }
+                if !self.in_range(&key) || self.last_front.as_ref().is_some_and(|f| key <= *f) {
+                    break;
+                }
+                self.last_back = Some(key.clone());
+                return Some(Ok((key, value)));
+            }
+            self.done = true;
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_store() -> Store {
+        Store::new(SledStore::temporary().unwrap())
+    }
+
+    #[test]
+    fn test_prefix_scans_both_ways_and_batches_apply_in_order() {
+        let store = temp_store();
+        for key in ["a:1", "a:2", "a:3", "a;", "b:1"] {
+            store.insert(key, key.as_bytes()).unwrap();
+        }
+        store.insert([0xffu8, 0xff], b"tail").unwrap();
+        let keys = |iter: KvIter| iter.map(|item| String::from_utf8_lossy(&item.unwrap().0).into_owned()).collect::<Vec<_>>();
+        assert_eq!(keys(store.scan_prefix("a:")), ["a:1", "a:2", "a:3"]);
+        assert_eq!(keys(Box::new(store.scan_prefix("a:").rev())), ["a:3", "a:2", "a:1"]);
+        assert_eq!(keys(store.range("a:2".."b:1")), ["a:2", "a:3", "a;"]);
+        assert_eq!(store.scan_prefix([0xffu8]).count(), 1);
+
+        let mut batch = WriteBatch::default();
+        batch.insert("a:4", b"x");
+        batch.remove("a:1");
+        batch.insert("a:1", b"again");
+        store.apply_batch(batch).unwrap();
+        assert_eq!(keys(store.scan_prefix("a:")), ["a:1", "a:2", "a:3", "a:4"]);
+        assert_eq!(store.get("a:1").unwrap().as_deref(), Some(b"again".as_slice()));
+
+        assert!(store.compare_and_swap("c", None::<&[u8]>, Some(b"1")).unwrap());
+        assert!(!store.compare_and_swap("c", None::<&[u8]>, Some(b"2")).unwrap());
+        assert!(store.compare_and_swap("c", Some(b"1"), None::<&[u8]>).unwrap());
+        assert!(!store.contains_key("c").unwrap());
+        assert!(store.generate_id().unwrap() < store.generate_id().unwrap());
+    }
+
+    #[test]
+    fn test_copy_all_moves_every_entry() {
+        let (from, to) = (temp_store(), temp_store());
+        for i in 0..25u32 {
+            from.insert(format!("k:{:03}", i), i.to_be_bytes()).unwrap();
+        }
+        let mut reported = Vec::new();
+        assert_eq!(copy_all(&from, &to, 10, |n| reported.push(n)).unwrap(), 25);
+        assert_eq!(reported, [10, 20]);
+        let copied: Vec<KvPair> = to.scan_prefix(b"").map(|item| item.unwrap()).collect();
+        let original: Vec<KvPair> = from.scan_prefix(b"").map(|item| item.unwrap()).collect();
+        assert_eq!(copied, original);
+    }
+}
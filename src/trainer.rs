@@ -24,62 +24,61 @@ impl AutoTrainer {
         }
     }
 
-    pub async fn start(&self) {
+    /// Fetch, train and predict forever; the node runs this under its supervisor
+    pub async fn run(self) {
         let history = self.history.clone();
         let model = self.model.clone();
         let last_prediction = self.last_prediction.clone();
 
-        tokio::spawn(async move {
-            info!("🧠 Auto-Trainer Started: Continuous Learning Loop Active");
+        info!("🧠 Auto-Trainer Started: Continuous Learning Loop Active");
             
-            loop {
-                // 1. Fetch Price
-                match fetch_price().await {
-                    Ok(price) => {
-                        let mut hist = history.lock().unwrap();
-                        hist.push_back(price);
-                        if hist.len() > 1000 {
-                            hist.pop_front();
-                        }
+        loop {
+            // 1. Fetch Price
+            match fetch_price().await {
+                Ok(price) => {
+                    let mut hist = history.lock().unwrap();
+                    hist.push_back(price);
+                    if hist.len() > 1000 {
+                        hist.pop_front();
+                    }
                         
-                        // 2. Train if enough data
-                        if hist.len() > 10 {
-                            let data: Vec<f64> = hist.iter().cloned().collect();
+                    // 2. Train if enough data
+                    if hist.len() > 10 {
+                        let data: Vec<f64> = hist.iter().cloned().collect();
                             
-                            // Input: [t] (index), Target: [price]
-                            // Simple trend following
-                            let x_raw: Vec<Vec<f64>> = (0..data.len()).map(|i| vec![i as f64]).collect();
-                            let y_raw: Vec<f64> = data.clone();
+                        // Input: [t] (index), Target: [price]
+                        // Simple trend following
+                        let x_raw: Vec<Vec<f64>> = (0..data.len()).map(|i| vec![i as f64]).collect();
+                        let y_raw: Vec<f64> = data.clone();
                             
-                            let x = DenseMatrix::from_2d_vec(&x_raw);
-                            let y = y_raw; 
+                        let x = DenseMatrix::from_2d_vec(&x_raw);
+                        let y = y_raw; 
                             
-                            // Linear Regression
-                            match LinearRegression::fit(&x, &y, LinearRegressionParameters::default()) {
-                                Ok(lr) => {
-                                    // Predict next step
-                                    let next_idx = vec![vec![(data.len()) as f64]];
-                                    let next_x = DenseMatrix::from_2d_vec(&next_idx);
+                        // Linear Regression
+                        match LinearRegression::fit(&x, &y, LinearRegressionParameters::default()) {
+                            Ok(lr) => {
+                                // Predict next step
+                                let next_idx = vec![vec![(data.len()) as f64]];
+                                let next_x = DenseMatrix::from_2d_vec(&next_idx);
                                     
-                                    if let Ok(pred_vals) = lr.predict(&next_x) {
-                                         let pred = pred_vals[0];
-                                         info!("🧠 Enhanced Model Update: Trained on {} samples", data.len());
-                                         info!("   🔮 Next BTC Prediction: ${:.2}", pred);
-                                         *last_prediction.lock().unwrap() = pred;
-                                    }
+                                if let Ok(pred_vals) = lr.predict(&next_x) {
+                                     let pred = pred_vals[0];
+                                     info!("🧠 Enhanced Model Update: Trained on {} samples", data.len());
+                                     info!("   🔮 Next BTC Prediction: ${:.2}", pred);
+                                     *last_prediction.lock().unwrap() = pred;
+                                }
                                     
-                                    *model.lock().unwrap() = Some(lr);
-                                },
-                                Err(e) => warn!("Training failed: {}", e),
-                            }
+                                *model.lock().unwrap() = Some(lr);
+                            },
+                            Err(e) => warn!("Training failed: {}", e),
                         }
-                    },
-                    Err(e) => warn!("Trainer failed to fetch price: {}", e),
-                }
-
-                tokio::time::sleep(Duration::from_secs(60)).await;
+                    }
+                },
+                Err(e) => warn!("Trainer failed to fetch price: {}", e),
             }
-        });
+
+            tokio::time::sleep(Duration::from_secs(60)).await;
+        }
     }
 }
 
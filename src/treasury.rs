@@ -0,0 +1,112 @@
+//! Protocol treasury.
+//!
+//! The treasury is an ordinary account that takes a share of every Compass fee
+//! as blocks execute and a share of collateral slashed in Layer 2. Nothing
+//! spends from it except a `TreasurySpend` block, which the leader appends once
+//! the `TreasuryProposal` it carries out has passed: its deadline is behind the
+//! chain and the stake voting for it outweighs the stake voting against.
+
+use serde::{Deserialize, Serialize};
+
+use crate::governance::VoteTally;
+
+/// Account the treasury's funds are held in
+pub const TREASURY_ACCOUNT: &str = "treasury";
+
+/// Asset the treasury collects and spends
+pub const TREASURY_ASSET: &str = "Compass";
+
+/// `[consensus.treasury]`: what the treasury takes. Every validator must use the
+/// same values, since the fee share is part of executing a block.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct TreasuryConfig {
+    /// Percent of each block's Compass fee paid to the treasury instead of the
+    /// fee recipient
+    #[serde(default)]
+    pub fee_percent: u64,
+    /// Percent of slashed collateral paid to the treasury instead of the
+    /// insurance fund
+    #[serde(default)]
+    pub slash_percent: u64,
+}
+
+impl TreasuryConfig {
+    /// Treasury's share of a `fee`
+    pub fn fee_share(&self, fee: u64) -> u64 {
+        share(fee, self.fee_percent)
+    }
+
+    /// Treasury's share of a `slashed` amount
+    pub fn slash_share(&self, slashed: u64) -> u64 {
+        share(slashed, self.slash_percent)
+    }
+}
+
+fn share(amount: u64, percent: u64) -> u64 {
+    (amount as u128 * percent.min(100) as u128 / 100) as u64
+}
+
+/// Where a treasury proposal stands
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ProposalStatus {
+    /// Its deadline hasn't passed
+    Open,
+    /// Passed and waiting for its `TreasurySpend` block
+    Passed,
+    Rejected,
+    Spent,
+}
+
+impl ProposalStatus {
+    /// Status of a proposal with `deadline` at `now_ms`, given its tally and
+    /// whether it has been spent
+    pub fn at(deadline: u64, now_ms: u64, tally: VoteTally, spent: bool) -> Self {
+        if spent {
+            ProposalStatus::Spent
+        } else if now_ms <= deadline {
+            ProposalStatus::Open
+        } else if tally.yes > tally.no {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        }
+    }
+}
+
+/// A treasury proposal and its standing, as `getTreasury` reports it
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct TreasuryProposalInfo {
+    pub id: u64,
+    pub proposer: String,
+    pub recipient: String,
+    pub amount: u64,
+    pub deadline: u64,
+    pub text: String,
+    /// Stake counted for and against, from votes cast by the deadline
+    pub yes: u64,
+    pub no: u64,
+    pub status: ProposalStatus,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shares_and_status() {
+        let config = TreasuryConfig { fee_percent: 10, slash_percent: 150 };
+        assert_eq!(config.fee_share(1_005), 100);
+        assert_eq!(config.fee_share(9), 0);
+        // Clamped to everything
+        assert_eq!(config.slash_share(u64::MAX), u64::MAX);
+        assert_eq!(TreasuryConfig::default().fee_share(1_000), 0);
+
+        let tally = |yes, no| VoteTally { yes, no };
+        assert_eq!(ProposalStatus::at(100, 100, tally(5, 0), false), ProposalStatus::Open);
+        assert_eq!(ProposalStatus::at(100, 101, tally(5, 0), false), ProposalStatus::Passed);
+        assert_eq!(ProposalStatus::at(100, 101, tally(5, 5), false), ProposalStatus::Rejected);
+        assert_eq!(ProposalStatus::at(100, 101, tally(0, 0), false), ProposalStatus::Rejected);
+        assert_eq!(ProposalStatus::at(100, 50, tally(0, 0), true), ProposalStatus::Spent);
+    }
+}
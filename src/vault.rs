@@ -6,7 +6,13 @@ use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::str::FromStr;
 
+use crate::oracle::circuit_breaker::OracleGuardConfig;
+use crate::oracle::freshness::{OraclePriceStatus, PriceFreshness, Staleness};
+
 pub mod keys;
+pub mod compliance;
+pub mod redemption;
+pub mod stability;
 pub use keys::VaultKeyManager;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
@@ -14,16 +20,36 @@ pub struct Vault {
     pub collateral_asset: String, // e.g., "SOL"
     pub compass_asset: String,    // e.g., "Compass-SOL"
     pub vault_address: String,    // External Chain Address (e.g. Solana Addr)
+    #[serde(with = "decimal_f64")]
     pub exchange_rate: Decimal,   // Amount of Compass per 1 unit of Collateral. Default 1.0
     pub collateral_balance: u64,  // Total External Asset Locked
     pub minted_supply: u64,       // Total Compass-Asset Minted
     pub accumulated_fees: u64,    // Fees collected
+    #[serde(with = "decimal_f64")]
     pub mint_fee_rate: Decimal,       // e.g. 0.0025
+    #[serde(with = "decimal_f64")]
     pub redeem_fee_rate: Decimal,     // e.g. 0.0050
     #[serde(default)]
     pub derivation_path: String,  // HD wallet derivation path
 }
 
+/// Decimals as plain f64, the format `serde-with-float` writes. Its own reader
+/// goes through `deserialize_any`, which bincode (Sled) cannot decode.
+mod decimal_f64 {
+    use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+    use rust_decimal::Decimal;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Decimal, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_f64(value.to_f64().unwrap_or_default())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Decimal, D::Error> {
+        let value = f64::deserialize(deserializer)?;
+        Decimal::from_f64(value).ok_or_else(|| serde::de::Error::custom(format!("{} is not a valid decimal", value)))
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub enum VaultHealth {
     Safe,
@@ -32,24 +58,24 @@ pub enum VaultHealth {
 }
 
 impl Vault {
+    /// Collateral value over debt at `current_price_per_unit` (e.g. $100 per SOL,
+    /// debt pegged at 1.0). None when nothing is minted, or when the collateral
+    /// value is beyond what a Decimal holds, which can only mean the vault is
+    /// overwhelmingly collateralised.
+    pub fn collateral_ratio(&self, current_price_per_unit: Decimal) -> Option<Decimal> {
+        if self.minted_supply == 0 {
+            return None;
+        }
+        Decimal::from(self.collateral_balance)
+            .checked_mul(current_price_per_unit)?
+            .checked_div(Decimal::from(self.minted_supply))
+    }
+
     pub fn check_health(&self, current_price_per_unit: Decimal) -> VaultHealth {
-        // current_price_per_unit: e.g. $100 per SOL
-        // Collateral Value = balance * price
-        // Minted Value = minted_supply * 1.0 (assuming Peg = $1)
-        
-        // Wait, price is usually "Collateral per Compass" or "Compass per Collateral"?
-        // exchange_rate is "Compass per Collateral".
-        // Let's use exchange_rate (Oracle Price) directly.
-        
-        let col_val = Decimal::from(self.collateral_balance) * current_price_per_unit;
-        let debt_val = Decimal::from(self.minted_supply);
-        
-        if debt_val.is_zero() {
+        let Some(ratio) = self.collateral_ratio(current_price_per_unit) else {
             return VaultHealth::Safe;
-        }
+        };
 
-        let ratio = col_val / debt_val;
-        
         if ratio < Decimal::from_str("1.10").unwrap() {
             VaultHealth::Liquidatable
         } else if ratio < Decimal::from_str("1.50").unwrap() {
@@ -60,6 +86,26 @@ impl Vault {
     }
 }
 
+/// Collateral ticker a vault asset is named after: "LTC" for "Compass:Daniel:LTC" or "Compass-LTC"
+pub fn collateral_ticker(compass_asset: &str) -> &str {
+    compass_asset.rsplit([':', '-']).next().unwrap_or(compass_asset)
+}
+
+/// Collateral one burn redeems: `gross` leaves the vault, `fee` of it stays as
+/// vault fees and `payout` goes to the redeemer
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RedemptionQuote {
+    pub burn_amount: u64,
+    pub gross: u64,
+    pub fee: u64,
+    pub payout: u64,
+}
+
+/// Asset a personal vault mints against deposited collateral, e.g. "Compass:Daniel:LTC"
+pub fn vault_asset_name(owner_id: &str, collateral_ticker: &str) -> String {
+    format!("Compass:{}:{}", owner_id, collateral_ticker)
+}
+
 #[derive(Serialize, Deserialize, Clone)] // Removed generic Debug
 pub struct VaultManager {
     // Keyed by compass_asset name (e.g., "Compass-SOL")
@@ -68,6 +114,8 @@ pub struct VaultManager {
     pub processed_deposits: HashSet<String>,
     #[serde(default)]
     pub oracle_prices: HashMap<String, (Decimal, u64)>, // Ticker -> (Price, Timestamp)
+    #[serde(default)]
+    pub price_freshness: HashMap<String, PriceFreshness>,
     
     #[serde(skip)]
     pub storage: Option<std::sync::Arc<crate::storage::Storage>>,
@@ -79,6 +127,7 @@ impl std::fmt::Debug for VaultManager {
          .field("vaults", &self.vaults)
          .field("processed_deposits", &self.processed_deposits)
          .field("oracle_prices", &self.oracle_prices)
+         .field("price_freshness", &self.price_freshness)
          .finish()
     }
 }
@@ -89,6 +138,7 @@ impl VaultManager {
             vaults: HashMap::new(),
             processed_deposits: HashSet::new(),
             oracle_prices: HashMap::new(),
+            price_freshness: HashMap::new(),
             storage: None,
         }
     }
@@ -98,6 +148,7 @@ impl VaultManager {
             vaults: HashMap::new(),
             processed_deposits: HashSet::new(),
             oracle_prices: HashMap::new(),
+            price_freshness: HashMap::new(),
             storage: Some(storage.clone()),
         };
         
@@ -110,6 +161,9 @@ impl VaultManager {
         for (ticker, info) in storage.get_all_prices() {
             vm.oracle_prices.insert(ticker, info);
         }
+        for (ticker, freshness) in storage.get_all_price_freshness() {
+            vm.price_freshness.insert(ticker, freshness);
+        }
         
         // Note: We don't load ALL processed deposits into RAM if the set is huge. 
         // We might rely on DB checks. But for consistency with JSON logic currently,
@@ -139,6 +193,9 @@ impl VaultManager {
              for (t, info) in &self.oracle_prices {
                  let _ = s.save_oracle_price_info(t, info);
              }
+             for (t, freshness) in &self.price_freshness {
+                 let _ = s.save_price_freshness(t, freshness);
+             }
              // Deposits marked individually usually, but loop here if bulk save?
              for d in &self.processed_deposits {
                  let _ = s.mark_deposit_processed(d);
@@ -151,13 +208,14 @@ impl VaultManager {
         }
     }
 
-    /// Register a new vault type (e.g. "Compass-SOL")
+    /// Register a new vault type (e.g. "Compass-SOL"), recording `registered_by` in the audit log
     pub fn register_vault(
         &mut self,
         collateral: &str,
         compass_asset: &str,
         address: &str,
         rate: u64,
+        registered_by: &str,
     ) -> Result<(), String> {
         if self.vaults.contains_key(compass_asset) {
             return Err("Vault already exists for this asset".to_string());
@@ -178,6 +236,13 @@ impl VaultManager {
         
         if let Some(s) = &self.storage {
             let _ = s.save_vault(&vault.compass_asset, &vault);
+            if let Err(e) = s.append_audit_entry(
+                registered_by,
+                crate::audit::AuditAction::VaultRegistration,
+                format!("asset={} collateral={} address={} rate={}", compass_asset, collateral, address, rate),
+            ) {
+                tracing::error!("Failed to write audit entry: {}", e);
+            }
         }
         
         Ok(())
@@ -220,6 +285,7 @@ impl VaultManager {
             "BTC" => format!("bc1q{}", hex::encode(&hash[..20])),
             "LTC" => format!("ltc1q{}", hex::encode(&hash[..20])),
             "SOL" => format!("sol1{}", hex::encode(&hash[..20])),
+            "ETH" | "USDC" => format!("0x{}", hex::encode(&hash[..20])),
             _ => format!("addr1{}", hex::encode(&hash[..20])),
         };
         
@@ -249,7 +315,31 @@ impl VaultManager {
         oracle_sig_hex: &str,
         oracle_pubkey_hex: &str,
     ) -> Result<(String, u64), String> {
-        // Returns (Asset Name, Minted Amount)
+        self.verify_deposit(
+            collateral_ticker,
+            collateral_amount,
+            requested_mint_amount,
+            owner_id,
+            tx_hash,
+            oracle_sig_hex,
+            oracle_pubkey_hex,
+        )?;
+        self.apply_deposit(collateral_ticker, collateral_amount, requested_mint_amount, owner_id, tx_hash)
+    }
+
+    /// The admission half of `deposit_and_mint`: the oracle signed this deposit and
+    /// it has not been claimed before. Changes nothing.
+    #[allow(clippy::too_many_arguments)]
+    pub fn verify_deposit(
+        &self,
+        collateral_ticker: &str,
+        collateral_amount: u64,
+        requested_mint_amount: u64,
+        owner_id: &str,
+        tx_hash: &str,
+        oracle_sig_hex: &str,
+        oracle_pubkey_hex: &str,
+    ) -> Result<(), String> {
         // 1. Verify Oracle Signature
         let msg = format!(
             "DEPOSIT:{}:{}:{}:{}:{}",
@@ -272,15 +362,26 @@ impl VaultManager {
                  return Err("Deposit Transaction already processed (DB)!".to_string());
              }
         }
-        
+        Ok(())
+    }
+
+    /// The state half of `deposit_and_mint`, for a deposit already verified (by this
+    /// node, or by the leader whose block recorded it). Returns (Asset Name, Minted Amount)
+    pub fn apply_deposit(
+        &mut self,
+        collateral_ticker: &str,
+        collateral_amount: u64,
+        requested_mint_amount: u64,
+        owner_id: &str,
+        tx_hash: &str,
+    ) -> Result<(String, u64), String> {
         self.processed_deposits.insert(tx_hash.to_string());
         if let Some(s) = &self.storage {
              let _ = s.mark_deposit_processed(tx_hash);
         }
 
         // 2. Determine Asset Name (Traceable Owner)
-        // e.g. "Compass:Daniel:LTC"
-        let asset_name = format!("Compass:{}:{}", owner_id, collateral_ticker);
+        let asset_name = vault_asset_name(owner_id, collateral_ticker);
 
         // 3. Get or Create Personal Vault
         let vault = self.vaults.entry(asset_name.clone()).or_insert_with(|| {
@@ -303,12 +404,15 @@ impl VaultManager {
         let fee_dec = col_dec * vault.mint_fee_rate;
         let fee = fee_dec.to_u64().unwrap_or(0);
         
-        let net_collateral = collateral_amount - fee;
+        let net_collateral = collateral_amount.checked_sub(fee).ok_or("Mint fee exceeds deposit")?;
 
-        // 5. Update State
-        vault.collateral_balance += net_collateral;
-        vault.accumulated_fees += fee;
-        vault.minted_supply += requested_mint_amount;
+        // 5. Update State (all checked before anything is written)
+        let collateral_balance = vault.collateral_balance.checked_add(net_collateral).ok_or("Vault collateral overflow")?;
+        let accumulated_fees = vault.accumulated_fees.checked_add(fee).ok_or("Vault fee overflow")?;
+        let minted_supply = vault.minted_supply.checked_add(requested_mint_amount).ok_or("Minted supply overflow")?;
+        vault.collateral_balance = collateral_balance;
+        vault.accumulated_fees = accumulated_fees;
+        vault.minted_supply = minted_supply;
 
         // Update implied rate
         if vault.collateral_balance > 0 {
@@ -399,8 +503,10 @@ impl VaultManager {
         });
 
         // 5. Update Vault State (COMPASS collateral noted, payment amount tracked)
-        vault.collateral_balance += compass_collateral;  // COMPASS locked
-        vault.minted_supply += requested_mint_amount;
+        let collateral_balance = vault.collateral_balance.checked_add(compass_collateral).ok_or("Vault collateral overflow")?;
+        let minted_supply = vault.minted_supply.checked_add(requested_mint_amount).ok_or("Minted supply overflow")?;
+        vault.collateral_balance = collateral_balance;  // COMPASS locked
+        vault.minted_supply = minted_supply;
 
         // Calculate implied rate
         if vault.collateral_balance > 0 {
@@ -431,10 +537,14 @@ impl VaultManager {
         compass_asset: &str,
         burn_amount: u64,
     ) -> Result<u64, String> {
-        let vault = self
-            .vaults
-            .get_mut(compass_asset)
-            .ok_or("Vault not found")?;
+        let quote = self.quote_redemption(compass_asset, burn_amount)?;
+        self.release_redemption(compass_asset, &quote)?;
+        Ok(quote.payout)
+    }
+
+    /// What burning `burn_amount` of `compass_asset` redeems, without changing the vault
+    pub fn quote_redemption(&self, compass_asset: &str, burn_amount: u64) -> Result<RedemptionQuote, String> {
+        let vault = self.vaults.get(compass_asset).ok_or("Vault not found")?;
 
         if burn_amount > vault.minted_supply {
             return Err("Burn amount exceeds minted supply (Critical Error)".to_string());
@@ -447,8 +557,8 @@ impl VaultManager {
         
         let burn_dec = Decimal::from(burn_amount);
         // Collateral = Burn / Rate
-        let gross_collateral_dec = burn_dec / vault.exchange_rate;
-        let gross_collateral_value = gross_collateral_dec.to_u64().unwrap_or(0);
+        let gross_collateral_dec = burn_dec.checked_div(vault.exchange_rate).ok_or("Redemption value overflow")?;
+        let gross_collateral_value = gross_collateral_dec.to_u64().ok_or("Redemption value overflow")?;
 
         if gross_collateral_value == 0 {
             return Err("Burn amount too small to redeem any collateral".to_string());
@@ -461,12 +571,23 @@ impl VaultManager {
         // 2. Calculate Fee (0.5%)
         let fee_dec = gross_collateral_dec * vault.redeem_fee_rate;
         let fee = fee_dec.to_u64().unwrap_or(0);
-        let net_payout = gross_collateral_value - fee;
+        let payout = gross_collateral_value.checked_sub(fee).ok_or("Redeem fee exceeds payout")?;
+
+        Ok(RedemptionQuote { burn_amount, gross: gross_collateral_value, fee, payout })
+    }
+
+    /// Take a redemption out of the vault: its burnt supply and the collateral paid
+    /// out for it, keeping the fee. Runs once the payout has left the vault.
+    pub fn release_redemption(&mut self, compass_asset: &str, quote: &RedemptionQuote) -> Result<(), String> {
+        let vault = self.vaults.get_mut(compass_asset).ok_or("Vault not found")?;
+        let minted_supply = vault.minted_supply.checked_sub(quote.burn_amount).ok_or("Burn amount exceeds minted supply")?;
+        let collateral_balance = vault.collateral_balance.checked_sub(quote.gross).ok_or("Vault holds less than the redemption")?;
+        let accumulated_fees = vault.accumulated_fees.checked_add(quote.fee).ok_or("Vault fee overflow")?;
 
         // 3. Update Vault State
-        vault.minted_supply -= burn_amount;
-        vault.collateral_balance -= gross_collateral_value; // Deduct gross (User + Fee)
-        vault.accumulated_fees += fee; // Keep fee
+        vault.minted_supply = minted_supply;
+        vault.collateral_balance = collateral_balance; // Deduct gross (User + Fee)
+        vault.accumulated_fees = accumulated_fees; // Keep fee
         
         if let Some(s) = &self.storage {
              let _ = s.save_vault(&vault.compass_asset, vault);
@@ -474,16 +595,16 @@ impl VaultManager {
 
         eprintln!(
             "   [Redeem] Burning {} Compass -> Releasing {} Collateral",
-            burn_amount, gross_collateral_value
+            quote.burn_amount, quote.gross
         );
         eprintln!(
             "   [Fee] Charged {} Units ({}%)",
-            fee,
+            quote.fee,
             vault.redeem_fee_rate * Decimal::from(100)
         );
-        eprintln!("   [Payout] {} Units", net_payout);
+        eprintln!("   [Payout] {} Units", quote.payout);
 
-        Ok(net_payout)
+        Ok(())
     }
 
     /// Update Oracle Price
@@ -511,11 +632,38 @@ impl VaultManager {
         }
         
         // 3. Update
-        self.oracle_prices.insert(ticker.to_string(), (price, timestamp));
+        self.publish_price(ticker, price, timestamp);
+        Ok(())
+    }
+
+    /// Make `price` the one vaults of `ticker` act on, noting how far it moved
+    pub fn publish_price(&mut self, ticker: &str, price: Decimal, timestamp: u64) {
+        let previous = self.oracle_prices.get(ticker).map(|(p, _)| *p);
+        let freshness = PriceFreshness::after(previous, price);
         if let Some(s) = &self.storage {
-             let _ = s.save_oracle_price_info(ticker, &(price, timestamp));
+            let _ = s.save_oracle_price_info(ticker, &(price, timestamp));
+            let _ = s.save_price_freshness(ticker, &freshness);
         }
-        Ok(())
+        self.oracle_prices.insert(ticker.to_string(), (price, timestamp));
+        self.price_freshness.insert(ticker.to_string(), freshness);
+    }
+
+    /// Why `ticker`'s price can't be acted on at `now`, if it can't. A ticker
+    /// with no price at all is reported by the callers that need one.
+    pub fn price_staleness(&self, ticker: &str, now: u64, cfg: &OracleGuardConfig) -> Option<Staleness> {
+        let (_, timestamp) = self.oracle_prices.get(ticker)?;
+        self.price_freshness.get(ticker).cloned().unwrap_or_default().staleness(*timestamp, now, cfg)
+    }
+
+    /// `ticker`'s price as `getOraclePrices` reports it
+    pub fn price_status(&self, ticker: &str, now: u64, cfg: &OracleGuardConfig) -> Option<OraclePriceStatus> {
+        let (price, timestamp) = *self.oracle_prices.get(ticker)?;
+        Some(OraclePriceStatus {
+            price,
+            timestamp,
+            move_bps: self.price_freshness.get(ticker).map_or(0, |f| f.move_bps),
+            stale: self.price_staleness(ticker, now, cfg),
+        })
     }
 
     /// Liquidate an undercollateralized vault
@@ -523,17 +671,20 @@ impl VaultManager {
         &mut self,
         compass_asset: &str,
         burn_amount: u64,
+        guard: &OracleGuardConfig,
     ) -> Result<u64, String> {
-        let vault = self.vaults.get_mut(compass_asset).ok_or("Vault not found")?;
-
         // 1. Get Global Price
-        let ticker = &vault.collateral_asset;
-        let (price, timestamp) = *self.oracle_prices.get(ticker).ok_or("No Oracle Price for asset")?;
-        
+        let ticker = self.vaults.get(compass_asset).ok_or("Vault not found")?.collateral_asset.clone();
+        if self.storage.as_ref().is_some_and(|s| s.is_oracle_paused(&ticker)) {
+            return Err(format!("{} is paused by the oracle circuit breaker. Cannot Liquidate.", ticker));
+        }
+        let (price, _) = *self.oracle_prices.get(&ticker).ok_or("No Oracle Price for asset")?;
+
         let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
-        if now > timestamp + 3600 {
-            return Err("Oracle Price is Stale (>1 hour old). Cannot Liquidate.".to_string());
+        if let Some(reason) = self.price_staleness(&ticker, now, guard) {
+            return Err(format!("Oracle Price is Stale ({}). Cannot Liquidate.", reason));
         }
+        let vault = self.vaults.get_mut(compass_asset).ok_or("Vault not found")?;
 
         if price.is_zero() { return Err("Invalid Oracle Price".to_string()); }
 
@@ -550,10 +701,11 @@ impl VaultManager {
 
         // 3. Calculate Payout
         let burn_dec = Decimal::from(burn_amount);
-        let base_col = burn_dec / price;
+        let base_col = burn_dec.checked_div(price).ok_or("Liquidation payout overflow")?;
         let bonus = base_col * Decimal::from_str("0.10").unwrap();
-        let total_payout_dec = base_col + bonus;
-        let total_payout = total_payout_dec.to_u64().unwrap_or(0);
+        let total_payout_dec = base_col.checked_add(bonus).ok_or("Liquidation payout overflow")?;
+        // Beyond u64 it is certainly more than the vault holds
+        let total_payout = total_payout_dec.to_u64().unwrap_or(u64::MAX);
 
         // 4. Check Vault Solvency
         if total_payout > vault.collateral_balance {
@@ -577,3 +729,53 @@ impl VaultManager {
         Ok(total_payout)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vault(collateral_balance: u64, minted_supply: u64) -> Vault {
+        Vault {
+            collateral_asset: "SOL".to_string(),
+            compass_asset: "Compass-SOL".to_string(),
+            vault_address: String::new(),
+            exchange_rate: Decimal::ONE,
+            collateral_balance,
+            minted_supply,
+            accumulated_fees: 0,
+            mint_fee_rate: Decimal::ZERO,
+            redeem_fee_rate: Decimal::ZERO,
+            derivation_path: String::new(),
+        }
+    }
+
+    #[test]
+    fn test_collateral_ratio_at_the_boundaries() {
+        let one = Decimal::ONE;
+        assert_eq!(vault(100, 0).collateral_ratio(one), None);
+        assert_eq!(vault(100, 0).check_health(one), VaultHealth::Safe);
+
+        // Thresholds are inclusive on the healthy side
+        assert_eq!(vault(110, 100).check_health(one), VaultHealth::AtRisk);
+        assert_eq!(vault(109, 100).check_health(one), VaultHealth::Liquidatable);
+        assert_eq!(vault(150, 100).check_health(one), VaultHealth::Safe);
+        assert_eq!(vault(149, 100).check_health(one), VaultHealth::AtRisk);
+
+        // Extremes neither wrap nor panic
+        assert_eq!(vault(u64::MAX, 1).collateral_ratio(Decimal::MAX), None);
+        assert_eq!(vault(u64::MAX, 1).check_health(Decimal::MAX), VaultHealth::Safe);
+        assert_eq!(vault(1, u64::MAX).check_health(one), VaultHealth::Liquidatable);
+        assert_eq!(vault(0, 1).collateral_ratio(one), Some(Decimal::ZERO));
+    }
+
+    #[test]
+    fn test_redeem_rejects_values_beyond_u64() {
+        let mut manager = VaultManager::new();
+        let mut v = vault(u64::MAX, u64::MAX);
+        v.exchange_rate = Decimal::new(1, 10);
+        manager.vaults.insert("Compass-SOL".to_string(), v);
+
+        assert!(manager.burn_and_redeem("Compass-SOL", u64::MAX).is_err());
+        assert_eq!(manager.vaults["Compass-SOL"].minted_supply, u64::MAX);
+    }
+}
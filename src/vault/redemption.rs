@@ -0,0 +1,85 @@
+//! Redemption queue: burns waiting on their external payout.
+//!
+//! A `Burn` block takes the redeemer's Compass out of circulation and queues a
+//! withdrawal intent, signed by the redeemer as part of that block. The vault
+//! keeps the collateral until the payout to `destination_address` has confirmed
+//! on the collateral's chain; a leader-signed `RedemptionRelease` block then takes
+//! the burnt supply and the paid collateral out of the vault together.
+
+use serde::{Deserialize, Serialize};
+
+use super::RedemptionQuote;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum RedemptionStatus {
+    /// Burnt; the payout has not confirmed yet
+    Queued,
+    /// Paid by `external_tx` and released from the vault at `height`
+    Released { external_tx: String, height: u64 },
+}
+
+/// A burn's claim on its vault's collateral
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct WithdrawalIntent {
+    /// Hash of the burn block
+    pub id: String,
+    pub compass_asset: String,
+    pub collateral_asset: String,
+    pub redeemer: String,
+    pub destination_address: String,
+    pub quote: RedemptionQuote,
+    /// Redeemer's signature over the burn block
+    pub signature: String,
+    pub burn_height: u64,
+    pub status: RedemptionStatus,
+    /// Payout an operator reported to this node, waiting for confirmations.
+    /// Node-local: it reaches the chain only in the release block.
+    #[serde(default)]
+    pub payout_tx: Option<String>,
+}
+
+impl WithdrawalIntent {
+    pub fn is_queued(&self) -> bool {
+        self.status == RedemptionStatus::Queued
+    }
+
+    /// BIP21 payment request for the payout, for chains whose wallets take one
+    pub fn payment_uri(&self) -> Option<String> {
+        let scheme = match self.collateral_asset.as_str() {
+            "BTC" => "bitcoin",
+            "LTC" => "litecoin",
+            _ => return None,
+        };
+        // Whole coins, from base units of 1e-8
+        let mut amount = rust_decimal::Decimal::from(self.quote.payout);
+        amount.set_scale(8).ok()?;
+        Some(format!("{}:{}?amount={}&label=compass-redemption-{}", scheme, self.destination_address, amount, self.id))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_payment_uri_pays_the_net_payout() {
+        let mut intent = WithdrawalIntent {
+            id: "abc".to_string(),
+            compass_asset: "Compass:alice:LTC".to_string(),
+            collateral_asset: "LTC".to_string(),
+            redeemer: "alice".to_string(),
+            destination_address: "ltc1qdest".to_string(),
+            quote: RedemptionQuote { burn_amount: 200, gross: 150_000_000, fee: 750_000, payout: 149_250_000 },
+            signature: String::new(),
+            burn_height: 7,
+            status: RedemptionStatus::Queued,
+            payout_tx: None,
+        };
+        assert_eq!(
+            intent.payment_uri().as_deref(),
+            Some("litecoin:ltc1qdest?amount=1.49250000&label=compass-redemption-abc")
+        );
+        intent.collateral_asset = "USDC".to_string();
+        assert_eq!(intent.payment_uri(), None);
+    }
+}
@@ -0,0 +1,108 @@
+//! Stability fee: interest on vault debt.
+//!
+//! A vault's minted supply accrues `annual_rate_bps` a year, charged per slot
+//! whenever a block touches the vault. The fee is minted in the vault's own asset
+//! to the treasury, so it raises the vault's debt (and lowers its collateral
+//! ratio) while the asset's supply still matches what the vault has minted.
+//! Fractions of a unit carry over to the next charge instead of being dropped.
+//!
+//! The rate starts as `[consensus.stability_fee]`. Stake holders change it with
+//! a `StabilityFeeProposal`; once one passes, the leader enacts it in a
+//! `StabilityFeeChange` block and every node charges the new rate from then on.
+
+use serde::{Deserialize, Serialize};
+
+const MS_PER_YEAR: u128 = 365 * 24 * 3600 * 1000;
+
+/// Fee numerator per unit: basis points times milliseconds in a year
+const DENOMINATOR: u128 = 10_000 * MS_PER_YEAR;
+
+/// `[consensus.stability_fee]`. Every validator must use the same rate, since
+/// charging it is part of executing a block.
+#[derive(Debug, Clone, Copy, Default, Deserialize, Serialize, PartialEq, Eq)]
+pub struct StabilityFeeConfig {
+    /// Yearly interest on vault debt, in basis points (0 charges nothing)
+    #[serde(default)]
+    pub annual_rate_bps: u64,
+}
+
+impl StabilityFeeConfig {
+    /// The configured rate, or the one governance set in its place
+    pub fn governed(self, annual_rate_bps: Option<u64>) -> Self {
+        Self { annual_rate_bps: annual_rate_bps.unwrap_or(self.annual_rate_bps) }
+    }
+}
+
+/// A stability fee proposal and its standing. `Spent` means enacted.
+#[derive(Debug, Clone, Serialize, PartialEq)]
+pub struct StabilityFeeProposalInfo {
+    pub id: u64,
+    pub proposer: String,
+    pub annual_rate_bps: u64,
+    pub deadline: u64,
+    pub text: String,
+    pub yes: u64,
+    pub no: u64,
+    pub status: crate::treasury::ProposalStatus,
+}
+
+/// Stability fee bookkeeping of one vault
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct VaultAccrual {
+    /// Slot the fee has been charged up to
+    pub last_slot: u64,
+    /// Stability fees charged so far
+    pub charged: u64,
+    /// Fraction of a unit owed, in units of 1/`DENOMINATOR`
+    pub carry: u64,
+}
+
+impl VaultAccrual {
+    pub fn starting_at(slot: u64) -> Self {
+        Self { last_slot: slot, ..Default::default() }
+    }
+
+    /// Fee due on `debt` from the last charge up to `slot`, and the carry left over
+    fn due(&self, debt: u64, slot: u64, cfg: &StabilityFeeConfig, slot_duration_ms: u64) -> (u64, u64) {
+        let elapsed_ms = slot.saturating_sub(self.last_slot) as u128 * slot_duration_ms as u128;
+        let owed = debt as u128 * cfg.annual_rate_bps as u128 * elapsed_ms + self.carry as u128;
+        (u64::try_from(owed / DENOMINATOR).unwrap_or(u64::MAX), (owed % DENOMINATOR) as u64)
+    }
+
+    /// Fee that charging at `slot` would add to `debt`
+    pub fn pending(&self, debt: u64, slot: u64, cfg: &StabilityFeeConfig, slot_duration_ms: u64) -> u64 {
+        self.due(debt, slot, cfg, slot_duration_ms).0
+    }
+
+    /// Charge the fee on `debt` up to `slot`, returning it
+    pub fn charge(&mut self, debt: u64, slot: u64, cfg: &StabilityFeeConfig, slot_duration_ms: u64) -> u64 {
+        let (fee, carry) = self.due(debt, slot, cfg, slot_duration_ms);
+        self.last_slot = self.last_slot.max(slot);
+        self.carry = carry;
+        self.charged = self.charged.saturating_add(fee);
+        fee
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fee_accrues_per_slot_and_carries_fractions() {
+        let cfg = StabilityFeeConfig { annual_rate_bps: 500 };
+        let slots_per_year = 365 * 24 * 3600;
+        let mut accrual = VaultAccrual::starting_at(0);
+
+        // 5% a year on 1,000,000 is 50,000
+        assert_eq!(accrual.pending(1_000_000, slots_per_year, &cfg, 1000), 50_000);
+
+        // One slot owes a fraction of a unit; it is kept, not lost
+        assert_eq!(accrual.charge(1_000_000, 1, &cfg, 1000), 0);
+        assert_eq!(accrual.charge(1_000_000, slots_per_year, &cfg, 1000), 50_000);
+        assert_eq!((accrual.charged, accrual.carry), (50_000, 0));
+
+        assert_eq!(accrual.charge(1_000_000, slots_per_year + 100, &cfg, 1000), 0);
+        assert_eq!(StabilityFeeConfig::default(), StabilityFeeConfig { annual_rate_bps: 0 });
+    }
+}
@@ -0,0 +1,136 @@
+//! Vesting schedules for genesis allocations.
+//!
+//! A genesis allocation with vesting terms is not credited at genesis. It is
+//! stored as a `VestingSchedule` instead, and the leader releases whatever has
+//! vested since the last release with a `Vesting` block when each epoch opens.
+//! Executing that block credits the account and advances the schedule's
+//! `released`, so followers and replays release exactly what the leader did.
+
+use serde::{Deserialize, Serialize};
+
+/// Asset genesis allocations are made in
+pub const VESTING_ASSET: &str = "Compass";
+
+/// How a genesis allocation vests, as written in genesis.json
+#[derive(Serialize, Deserialize, Clone, Debug, Default, PartialEq, Eq)]
+pub struct VestingTerms {
+    /// Unix ms vesting starts from; the genesis timestamp if left out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub start_ms: Option<u64>,
+    /// Nothing vests until this long after the start
+    #[serde(default)]
+    pub cliff_ms: u64,
+    /// Time from the start until the whole allocation has vested, linearly.
+    /// 0 vests everything at the cliff.
+    #[serde(default)]
+    pub duration_ms: u64,
+}
+
+impl VestingTerms {
+    /// The schedule these terms give `total` for `account` on a chain that
+    /// started at `genesis_ms`
+    pub fn schedule(&self, account: &str, total: u64, genesis_ms: u64) -> VestingSchedule {
+        VestingSchedule {
+            account: account.to_string(),
+            total,
+            released: 0,
+            start_ms: self.start_ms.unwrap_or(genesis_ms),
+            cliff_ms: self.cliff_ms,
+            duration_ms: self.duration_ms,
+        }
+    }
+}
+
+/// An account's locked allocation and how much of it has been released
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct VestingSchedule {
+    pub account: String,
+    pub total: u64,
+    /// Already credited to the account
+    pub released: u64,
+    pub start_ms: u64,
+    pub cliff_ms: u64,
+    pub duration_ms: u64,
+}
+
+impl VestingSchedule {
+    /// When the first funds vest
+    pub fn cliff_end_ms(&self) -> u64 {
+        self.start_ms.saturating_add(self.cliff_ms)
+    }
+
+    /// When the whole allocation has vested
+    pub fn end_ms(&self) -> u64 {
+        self.start_ms.saturating_add(self.cliff_ms.max(self.duration_ms))
+    }
+
+    /// Amount vested by `now_ms`, released or not
+    pub fn vested_at(&self, now_ms: u64) -> u64 {
+        if now_ms < self.cliff_end_ms() {
+            return 0;
+        }
+        if self.duration_ms == 0 || now_ms >= self.end_ms() {
+            return self.total;
+        }
+        let elapsed = now_ms.saturating_sub(self.start_ms);
+        (self.total as u128 * elapsed as u128 / self.duration_ms as u128) as u64
+    }
+
+    /// Vested by `now_ms` and not yet released
+    pub fn releasable_at(&self, now_ms: u64) -> u64 {
+        self.vested_at(now_ms).saturating_sub(self.released)
+    }
+
+    /// Mark `amount` released at `now_ms`; Err if that is more than has vested
+    pub fn release(&mut self, amount: u64, now_ms: u64) -> Result<(), String> {
+        let releasable = self.releasable_at(now_ms);
+        if amount == 0 || amount > releasable {
+            return Err(format!(
+                "cannot release {} of {}'s vesting allocation: {} is releasable",
+                amount, self.account, releasable
+            ));
+        }
+        self.released += amount;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: u64 = 86_400_000;
+
+    fn schedule(cliff_ms: u64, duration_ms: u64) -> VestingSchedule {
+        VestingTerms { start_ms: None, cliff_ms, duration_ms }.schedule("team", 1_000, 10 * DAY)
+    }
+
+    #[test]
+    fn test_cliff_then_linear_vesting() {
+        let s = schedule(100 * DAY, 400 * DAY);
+        assert_eq!(s.vested_at(10 * DAY), 0);
+        assert_eq!(s.vested_at(109 * DAY), 0);
+        // At the cliff, everything accrued since the start unlocks at once
+        assert_eq!(s.vested_at(110 * DAY), 250);
+        assert_eq!(s.vested_at(210 * DAY), 500);
+        assert_eq!(s.vested_at(410 * DAY), 1_000);
+        assert_eq!(s.vested_at(u64::MAX), 1_000);
+        assert_eq!(s.end_ms(), 410 * DAY);
+
+        let cliff_only = schedule(30 * DAY, 0);
+        assert_eq!(cliff_only.vested_at(39 * DAY), 0);
+        assert_eq!(cliff_only.vested_at(40 * DAY), 1_000);
+    }
+
+    #[test]
+    fn test_release_is_capped_by_what_has_vested() {
+        let mut s = schedule(0, 100 * DAY);
+        assert!(s.release(1, 10 * DAY).is_err());
+        assert!(s.release(101, 20 * DAY).is_err());
+        s.release(100, 20 * DAY).unwrap();
+        assert_eq!(s.releasable_at(20 * DAY), 0);
+        assert_eq!(s.releasable_at(30 * DAY), 100);
+        s.release(900, 200 * DAY).unwrap();
+        assert!(s.release(1, u64::MAX).is_err());
+    }
+}
@@ -1,20 +1,249 @@
+//! WASM smart contracts.
+//!
+//! Contracts run in a sandbox that gives the same result on every node: fuel
+//! metering bounds each run by its gas limit, NaNs are canonicalized, threads and
+//! relaxed SIMD are off, and memory is capped. A contract reaches the outside only
+//! through the host functions linked under `env`:
+//!
+//! - `input_len() -> i32`, `input_read(ptr)`: the call's input bytes
+//! - `caller_len() -> i32`, `caller_read(ptr)`: the calling account
+//! - `storage_read(key_ptr, key_len, val_ptr, val_cap) -> i32`: copies up to
+//!   `val_cap` bytes of a state value, returning its full length or -1 if unset
+//! - `storage_write(key_ptr, key_len, val_ptr, val_len)`: an empty value deletes the key
+//! - `set_output(ptr, len)`: bytes returned to the caller
+//!
+//! A contract exports its `memory` and one `() -> ()` function per method. An
+//! exported `init` runs once when the contract is deployed. State writes are
+//! buffered and land in Storage, under the contract's own prefix, only if the
+//! run succeeds.
+//!
+//! Deploys and calls reach the chain as `ContractDeploy` and `ContractCall`
+//! blocks, which `executor::apply_block` runs here. The sender pays the gas it
+//! used at the price it signed, or its whole gas limit if the run fails.
+
+use std::collections::BTreeMap;
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
 use wasmtime::*;
 
+use crate::encoding::{hash_to_hex, sha256};
+use crate::error::CompassError;
+use crate::storage::Storage;
+
+/// Most gas one deploy or call may buy
+pub const MAX_GAS_LIMIT: u64 = 50_000_000;
+/// Largest contract binary accepted
+pub const MAX_CODE_BYTES: usize = 512 * 1024;
+const MAX_MEMORY_BYTES: usize = 16 * 1024 * 1024;
+const MAX_KEY_BYTES: usize = 256;
+const MAX_VALUE_BYTES: usize = 64 * 1024;
+
+/// Gas host functions charge on top of the instructions they cost
+const STORAGE_READ_GAS: u64 = 200;
+const STORAGE_WRITE_GAS: u64 = 2_000;
+const GAS_PER_BYTE: u64 = 10;
+
+/// Metadata of a deployed contract
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractInfo {
+    pub address: String,
+    pub deployer: String,
+    pub code_hash: String,
+    pub code_size: usize,
+    /// Chain height it was deployed at
+    pub deployed_at: u64,
+}
+
+/// Result of a successful deploy, call or query
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ContractOutcome {
+    pub contract: String,
+    pub output: Vec<u8>,
+    pub gas_used: u64,
+    /// State keys written (none for a query)
+    pub state_writes: usize,
+}
+
+/// Address a contract deployed by `deployer` with `nonce` gets
+pub fn contract_address(deployer: &str, nonce: u64) -> String {
+    hash_to_hex(&sha256(format!("COMPASS_CONTRACT:{}:{}", deployer, nonce)))
+}
+
+/// Message a deployer signs for a `DeployContract` payload
+pub fn deploy_message(deployer: &str, code: &[u8], gas_limit: u64, gas_price: u64, nonce: u64) -> String {
+    format!("COMPASS_DEPLOY:{}:{}:{}:{}:{}", deployer, hash_to_hex(&sha256(code)), gas_limit, gas_price, nonce)
+}
+
+/// Message a caller signs for a `CallContract` payload
+pub fn call_message(
+    caller: &str,
+    contract: &str,
+    method: &str,
+    input: &[u8],
+    gas_limit: u64,
+    gas_price: u64,
+    nonce: u64,
+) -> String {
+    format!(
+        "COMPASS_CALL:{}:{}:{}:{}:{}:{}:{}",
+        caller,
+        contract,
+        method,
+        hash_to_hex(&sha256(input)),
+        gas_limit,
+        gas_price,
+        nonce
+    )
+}
+
+/// Compass a run pays for `gas` at `gas_price`, None on overflow
+pub fn gas_cost(gas: u64, gas_price: u64) -> Option<u64> {
+    gas.checked_mul(gas_price)
+}
+
+/// What a running contract sees of the node
+struct Host {
+    storage: Storage,
+    contract: String,
+    caller: String,
+    input: Vec<u8>,
+    output: Vec<u8>,
+    writes: BTreeMap<Vec<u8>, Vec<u8>>,
+    read_only: bool,
+    limits: StoreLimits,
+}
+
+impl Host {
+    fn new(storage: &Storage, contract: &str, caller: &str, input: Vec<u8>, read_only: bool) -> Self {
+        Self {
+            storage: storage.clone(),
+            contract: contract.to_string(),
+            caller: caller.to_string(),
+            input,
+            output: Vec::new(),
+            writes: BTreeMap::new(),
+            read_only,
+            limits: StoreLimitsBuilder::new().memory_size(MAX_MEMORY_BYTES).instances(1).memories(1).tables(1).build(),
+        }
+    }
+}
+
+fn charge(caller: &mut Caller<'_, Host>, gas: u64) -> Result<()> {
+    let left = caller.get_fuel()?;
+    if left < gas {
+        caller.set_fuel(0)?;
+        return Err(Error::msg("out of gas"));
+    }
+    caller.set_fuel(left - gas)
+}
+
+fn memory(caller: &mut Caller<'_, Host>) -> Result<Memory> {
+    caller
+        .get_export("memory")
+        .and_then(Extern::into_memory)
+        .ok_or_else(|| Error::msg("contract does not export its memory"))
+}
+
+fn read_bytes(caller: &mut Caller<'_, Host>, ptr: i32, len: i32, max: usize) -> Result<Vec<u8>> {
+    let len = usize::try_from(len).map_err(|_| Error::msg("negative length"))?;
+    if len > max {
+        return Err(Error::msg(format!("{} bytes exceeds the limit of {}", len, max)));
+    }
+    let mut buf = vec![0; len];
+    memory(caller)?.read(&*caller, ptr as u32 as usize, &mut buf)?;
+    Ok(buf)
+}
+
+fn write_bytes(caller: &mut Caller<'_, Host>, ptr: i32, bytes: &[u8]) -> Result<()> {
+    memory(caller)?.write(&mut *caller, ptr as u32 as usize, bytes)?;
+    Ok(())
+}
+
+fn link(engine: &Engine) -> Result<Linker<Host>> {
+    let mut linker = Linker::new(engine);
+    linker.func_wrap("env", "input_len", |caller: Caller<'_, Host>| caller.data().input.len() as i32)?;
+    linker.func_wrap("env", "input_read", |mut caller: Caller<'_, Host>, ptr: i32| {
+        let input = caller.data().input.clone();
+        write_bytes(&mut caller, ptr, &input)
+    })?;
+    linker.func_wrap("env", "caller_len", |caller: Caller<'_, Host>| caller.data().caller.len() as i32)?;
+    linker.func_wrap("env", "caller_read", |mut caller: Caller<'_, Host>, ptr: i32| {
+        let account = caller.data().caller.clone();
+        write_bytes(&mut caller, ptr, account.as_bytes())
+    })?;
+    linker.func_wrap(
+        "env",
+        "storage_read",
+        |mut caller: Caller<'_, Host>, key_ptr: i32, key_len: i32, val_ptr: i32, val_cap: i32| -> Result<i32> {
+            charge(&mut caller, STORAGE_READ_GAS)?;
+            let key = read_bytes(&mut caller, key_ptr, key_len, MAX_KEY_BYTES)?;
+            let host = caller.data();
+            let value = match host.writes.get(&key) {
+                Some(value) => Some(value.clone()),
+                None => host.storage.get_contract_state(&host.contract, &key).map_err(|e| Error::msg(e.to_string()))?,
+            };
+            let Some(value) = value.filter(|v| !v.is_empty()) else { return Ok(-1) };
+            charge(&mut caller, value.len() as u64 * GAS_PER_BYTE)?;
+            let copied = value.len().min(usize::try_from(val_cap).unwrap_or(0));
+            write_bytes(&mut caller, val_ptr, &value[..copied])?;
+            Ok(value.len() as i32)
+        },
+    )?;
+    linker.func_wrap(
+        "env",
+        "storage_write",
+        |mut caller: Caller<'_, Host>, key_ptr: i32, key_len: i32, val_ptr: i32, val_len: i32| -> Result<()> {
+            if caller.data().read_only {
+                return Err(Error::msg("state writes are not allowed in a query"));
+            }
+            charge(&mut caller, STORAGE_WRITE_GAS)?;
+            let key = read_bytes(&mut caller, key_ptr, key_len, MAX_KEY_BYTES)?;
+            let value = read_bytes(&mut caller, val_ptr, val_len, MAX_VALUE_BYTES)?;
+            charge(&mut caller, (key.len() + value.len()) as u64 * GAS_PER_BYTE)?;
+            caller.data_mut().writes.insert(key, value);
+            Ok(())
+        },
+    )?;
+    linker.func_wrap("env", "set_output", |mut caller: Caller<'_, Host>, ptr: i32, len: i32| -> Result<()> {
+        let output = read_bytes(&mut caller, ptr, len, MAX_VALUE_BYTES)?;
+        charge(&mut caller, output.len() as u64 * GAS_PER_BYTE)?;
+        caller.data_mut().output = output;
+        Ok(())
+    })?;
+    Ok(linker)
+}
+
 pub struct SVM {
     engine: Engine,
+    linker: Linker<Host>,
 }
 
 impl SVM {
     pub fn new() -> Self {
-        let config = Config::new();
-        // config.consume_fuel(true); // Enable gas metering later
+        let mut config = Config::new();
+        config
+            .consume_fuel(true)
+            .cranelift_nan_canonicalization(true)
+            .wasm_threads(false)
+            .wasm_relaxed_simd(false)
+            .wasm_multi_memory(false)
+            .wasm_memory64(false);
         let engine = Engine::new(&config).expect("Failed to create Wasmtime engine");
-        SVM { engine }
+        let linker = link(&engine).expect("Failed to link contract host functions");
+        SVM { engine, linker }
+    }
+
+    /// The VM every deploy, call and query runs on
+    pub fn shared() -> &'static SVM {
+        static VM: OnceLock<SVM> = OnceLock::new();
+        VM.get_or_init(SVM::new)
     }
 
     /// Run a simple WASM binary that exports a "run" function
     pub fn execute(&self, wasm_bytes: &[u8]) -> Result<(), String> {
         let mut store = Store::new(&self.engine, ());
+        store.set_fuel(MAX_GAS_LIMIT).map_err(|e| e.to_string())?;
         let module = Module::from_binary(&self.engine, wasm_bytes)
             .map_err(|e| format!("Invalid WASM binary: {}", e))?;
 
@@ -33,6 +262,119 @@ impl SVM {
 
         Ok(())
     }
+
+    fn compile(&self, code: &[u8]) -> Result<Module, CompassError> {
+        Module::from_binary(&self.engine, code).map_err(|e| CompassError::TransactionError(format!("Invalid WASM binary: {}", e)))
+    }
+
+    /// Run `method` of `module` with at most `gas_limit` gas, returning the host
+    /// state it left and the gas it used
+    fn run(&self, module: &Module, host: Host, method: &str, gas_limit: u64) -> Result<(Host, u64), CompassError> {
+        let failed = |e: Error| CompassError::TransactionError(format!("{:#}", e));
+        let mut store = Store::new(&self.engine, host);
+        store.limiter(|host| &mut host.limits);
+        store.set_fuel(gas_limit).map_err(failed)?;
+        let result = self
+            .linker
+            .instantiate(&mut store, module)
+            .and_then(|instance| instance.get_typed_func::<(), ()>(&mut store, method))
+            .and_then(|func| func.call(&mut store, ()));
+        let gas_used = gas_limit.saturating_sub(store.get_fuel().unwrap_or(0));
+        result.map_err(|e| CompassError::TransactionError(format!("{} failed after {} gas: {:#}", method, gas_used, e)))?;
+        Ok((store.into_data(), gas_used))
+    }
+}
+
+impl Default for SVM {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub fn check_gas(gas_limit: u64) -> Result<(), CompassError> {
+    if gas_limit == 0 || gas_limit > MAX_GAS_LIMIT {
+        return Err(CompassError::TransactionError(format!("gas limit must be between 1 and {}", MAX_GAS_LIMIT)));
+    }
+    Ok(())
+}
+
+pub fn check_code(code: &[u8]) -> Result<(), CompassError> {
+    if code.is_empty() || code.len() > MAX_CODE_BYTES {
+        return Err(CompassError::TransactionError(format!("contract code must be 1 to {} bytes", MAX_CODE_BYTES)));
+    }
+    Ok(())
+}
+
+/// Deploy `code` for `deployer` at `height` as its `nonce`th transaction, running
+/// its `init` export if it has one. The caller spends the nonce and charges the gas.
+pub fn deploy(
+    storage: &Storage,
+    height: u64,
+    deployer: &str,
+    code: &[u8],
+    gas_limit: u64,
+    nonce: u64,
+) -> Result<ContractOutcome, CompassError> {
+    check_gas(gas_limit)?;
+    check_code(code)?;
+    let vm = SVM::shared();
+    let module = vm.compile(code)?;
+    let address = contract_address(deployer, nonce);
+    if storage.get_contract(&address)?.is_some() {
+        return Err(CompassError::TransactionError(format!("contract {} already exists", address)));
+    }
+
+    let host = Host::new(storage, &address, deployer, Vec::new(), false);
+    let (host, gas_used) = match module.get_export("init") {
+        Some(_) => vm.run(&module, host, "init", gas_limit)?,
+        None => (host, 0),
+    };
+    let info = ContractInfo {
+        address: address.clone(),
+        deployer: deployer.to_string(),
+        code_hash: hash_to_hex(&sha256(code)),
+        code_size: code.len(),
+        deployed_at: height,
+    };
+    storage.save_contract(&info, code, &host.writes)?;
+    Ok(ContractOutcome { contract: address, output: host.output, gas_used, state_writes: host.writes.len() })
+}
+
+fn load(storage: &Storage, contract: &str, method: &str) -> Result<Module, CompassError> {
+    if method == "init" {
+        return Err(CompassError::TransactionError("init only runs at deployment".to_string()));
+    }
+    let code = storage
+        .get_contract_code(contract)?
+        .ok_or_else(|| CompassError::TransactionError(format!("no contract at {}", contract)))?;
+    SVM::shared().compile(&code)
+}
+
+/// Call `method` of `contract` as `caller`, committing its state writes if it
+/// succeeds. The caller spends the nonce and charges the gas.
+pub fn call(
+    storage: &Storage,
+    caller: &str,
+    contract: &str,
+    method: &str,
+    input: &[u8],
+    gas_limit: u64,
+) -> Result<ContractOutcome, CompassError> {
+    check_gas(gas_limit)?;
+    let module = load(storage, contract, method)?;
+    let host = Host::new(storage, contract, caller, input.to_vec(), false);
+    let (host, gas_used) = SVM::shared().run(&module, host, method, gas_limit)?;
+    storage.save_contract_state(contract, &host.writes)?;
+    Ok(ContractOutcome { contract: contract.to_string(), output: host.output, gas_used, state_writes: host.writes.len() })
+}
+
+/// Run `method` of `contract` without a transaction; a state write fails the query
+pub fn query(storage: &Storage, contract: &str, method: &str, input: &[u8], gas_limit: u64) -> Result<ContractOutcome, CompassError> {
+    check_gas(gas_limit)?;
+    let module = load(storage, contract, method)?;
+    let host = Host::new(storage, contract, "", input.to_vec(), true);
+    let (host, gas_used) = SVM::shared().run(&module, host, method, gas_limit)?;
+    Ok(ContractOutcome { contract: contract.to_string(), output: host.output, gas_used, state_writes: 0 })
 }
 
 #[cfg(test)]
@@ -43,7 +385,7 @@ mod tests {
     fn test_execute_empty_module() {
         // A minimal WASM binary (header + strict empty module)
         // This won't work with "run" expectation, but proves engine init.
-        // wat: (module (func (export "run"))) 
+        // wat: (module (func (export "run")))
         let wat = r#"(module (func (export "run")))"#;
         let wasm = wat::parse_str(wat).unwrap();
 
@@ -51,4 +393,52 @@ mod tests {
         let res = vm.execute(&wasm);
         assert!(res.is_ok());
     }
+
+    /// Counter: `init` stores 0, `increment` adds the input's first byte, `get`
+    /// returns the count, `spin` never ends
+    const COUNTER: &str = r#"(module
+        (import "env" "input_read" (func $input_read (param i32)))
+        (import "env" "storage_read" (func $storage_read (param i32 i32 i32 i32) (result i32)))
+        (import "env" "storage_write" (func $storage_write (param i32 i32 i32 i32)))
+        (import "env" "set_output" (func $set_output (param i32 i32)))
+        (memory (export "memory") 1)
+        (data (i32.const 0) "count")
+        (func (export "init")
+            (i32.store8 (i32.const 16) (i32.const 0))
+            (call $storage_write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 1)))
+        (func (export "increment")
+            (drop (call $storage_read (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 1)))
+            (call $input_read (i32.const 32))
+            (i32.store8 (i32.const 16) (i32.add (i32.load8_u (i32.const 16)) (i32.load8_u (i32.const 32))))
+            (call $storage_write (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 1)))
+        (func (export "get")
+            (drop (call $storage_read (i32.const 0) (i32.const 5) (i32.const 16) (i32.const 1)))
+            (call $set_output (i32.const 16) (i32.const 1)))
+        (func (export "spin") (loop (br 0))))"#;
+
+    #[test]
+    fn test_contract_state_persists_across_calls_and_gas_is_metered() {
+        let storage = Storage::new_in_memory().unwrap();
+        let code = wat::parse_str(COUNTER).unwrap();
+
+        let deployed = deploy(&storage, 7, "alice", &code, 1_000_000, 1).unwrap();
+        assert_eq!(deployed.contract, contract_address("alice", 1));
+        assert_eq!(storage.get_contract(&deployed.contract).unwrap().unwrap().deployed_at, 7);
+        assert!(deploy(&storage, 7, "alice", &code, 1_000_000, 1).is_err(), "a nonce deploys once");
+
+        let address = deployed.contract;
+        let called = call(&storage, "bob", &address, "increment", &[5], 1_000_000).unwrap();
+        assert!(called.gas_used > STORAGE_WRITE_GAS);
+        call(&storage, "bob", &address, "increment", &[3], 1_000_000).unwrap();
+        assert_eq!(query(&storage, &address, "get", &[], 1_000_000).unwrap().output, vec![8]);
+
+        // Running out of gas fails the call and writes nothing
+        assert!(call(&storage, "bob", &address, "spin", &[], 10_000).is_err());
+        assert!(call(&storage, "bob", &address, "increment", &[1], 100).is_err());
+        assert_eq!(query(&storage, &address, "get", &[], 1_000_000).unwrap().output, vec![8]);
+
+        // Queries can't write, and init can't be called again
+        assert!(query(&storage, &address, "increment", &[1], 1_000_000).is_err());
+        assert!(call(&storage, "bob", &address, "init", &[], 1_000_000).is_err());
+    }
 }
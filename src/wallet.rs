@@ -11,7 +11,9 @@ use hmac::Hmac;
 use sha2::Sha256;
 use rand::{Rng, thread_rng};
 
+use crate::crypto::{KeyPair, Signer};
 use crate::error::CompassError;
+use std::sync::Arc;
 
 /// Different roles a wallet can have
 #[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
@@ -39,8 +41,21 @@ pub struct Wallet {
     pub encryption_salt: Option<Vec<u8>>,
     #[serde(default)]
     pub is_encrypted: bool,
+    /// HD accounts derived from the mnemonic so far, by index
+    #[serde(default)]
+    pub accounts: Vec<DerivedAccount>,
+}
+
+/// One of a wallet's HD accounts, at `crypto::derivation_path(index)`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct DerivedAccount {
+    pub index: u32,
+    pub public_key: String,
 }
 
+/// Unused accounts in a row after which a restore stops looking (BIP44's gap limit)
+pub const DEFAULT_GAP_LIMIT: u32 = 20;
+
 impl Wallet {
     /// Create a new wallet with a given type (Generates new keys)
     pub fn new(owner: &str, wallet_type: WalletType) -> Self {
@@ -58,6 +73,7 @@ impl Wallet {
             encrypted_mnemonic: None,
             encryption_salt: None,
             is_encrypted: false,
+            accounts: Vec::new(),
         }
     }
 
@@ -73,6 +89,7 @@ impl Wallet {
             encrypted_mnemonic: None,
             encryption_salt: None,
             is_encrypted: false,
+            accounts: Vec::new(),
         }
     }
 
@@ -90,7 +107,7 @@ impl Wallet {
 
     pub fn save(&self, path: &str) -> std::io::Result<()> {
         println!("DEBUG: Wallet::save called for path: {}", path);
-        let json = serde_json::to_string_pretty(self).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
         fs::write(path, json)
     }
 
@@ -109,6 +126,25 @@ impl Wallet {
         }
     }
 
+    /// Derive HD account `index` from the plaintext mnemonic and remember it
+    pub fn derive_account(&mut self, index: u32) -> Result<DerivedAccount, CompassError> {
+        let keypair = self.derived_keypair(index)?;
+        let account = DerivedAccount { index, public_key: keypair.public_key_hex() };
+        if let Err(pos) = self.accounts.binary_search_by_key(&index, |a| a.index) {
+            self.accounts.insert(pos, account.clone());
+        }
+        Ok(account)
+    }
+
+    /// Keys of HD account `index`; needs the plaintext mnemonic
+    pub fn derived_keypair(&self, index: u32) -> Result<KeyPair, CompassError> {
+        let mnemonic = self
+            .mnemonic
+            .as_ref()
+            .ok_or_else(|| CompassError::InvalidState(format!("Wallet '{}' has no plaintext mnemonic", self.owner)))?;
+        KeyPair::derive_from_mnemonic(mnemonic, index).map_err(CompassError::InvalidState)
+    }
+
     pub fn encrypt_wallet(&mut self, password: &str) -> Result<(), CompassError> {
         if self.mnemonic.is_none() {
             return Err(CompassError::InvalidState("No mnemonic to encrypt".to_string()));
@@ -196,6 +232,9 @@ pub struct WalletManager {
     pub wallets: HashMap<String, Wallet>,
     #[serde(skip)]
     pub storage: Option<std::sync::Arc<crate::storage::Storage>>,
+    /// Owners whose key lives outside this process; never persisted
+    #[serde(skip)]
+    signers: HashMap<String, Arc<dyn Signer>>,
 }
 
 impl std::fmt::Debug for WalletManager {
@@ -203,6 +242,7 @@ impl std::fmt::Debug for WalletManager {
         f.debug_struct("WalletManager")
          .field("wallets", &self.wallets)
          .field("storage", &"Option<Storage>")
+         .field("external_signers", &self.signers.keys().collect::<Vec<_>>())
          .finish()
     }
 }
@@ -212,6 +252,7 @@ impl WalletManager {
         WalletManager {
             wallets: HashMap::new(),
             storage: None,
+            signers: HashMap::new(),
         }
     }
 
@@ -219,6 +260,7 @@ impl WalletManager {
         let mut wm = WalletManager {
             wallets: HashMap::new(),
             storage: Some(storage.clone()),
+            signers: HashMap::new(),
         };
         // Load existing wallets from DB
         let wallets = storage.get_all_wallets();
@@ -254,6 +296,32 @@ impl WalletManager {
         self.wallets.get_mut(owner)
     }
 
+    /// Sign for `owner` with `signer` (e.g. a hardware wallet) instead of a stored mnemonic
+    pub fn attach_signer(&mut self, owner: &str, signer: Arc<dyn Signer>) {
+        self.signers.insert(owner.to_string(), signer);
+    }
+
+    /// The signer for `owner`: an attached external one, else a key restored from
+    /// the wallet's plaintext mnemonic. `name/N` signs as HD account N of wallet `name`.
+    pub fn signer_for(&self, owner: &str) -> Result<Arc<dyn Signer>, String> {
+        if let Some(signer) = self.signers.get(owner) {
+            return Ok(signer.clone());
+        }
+        if let Some((name, index)) = owner.rsplit_once('/').filter(|_| !self.wallets.contains_key(owner)) {
+            let index: u32 = index.parse().map_err(|_| format!("Wallet '{}' not found", owner))?;
+            let wallet = self.get_wallet(name).ok_or_else(|| format!("Wallet '{}' not found", name))?;
+            let keypair = wallet.derived_keypair(index).map_err(|e| e.to_string())?;
+            return Ok(Arc::new(keypair));
+        }
+        let wallet = self.get_wallet(owner).ok_or_else(|| format!("Wallet '{}' not found", owner))?;
+        let mnemonic = wallet
+            .mnemonic
+            .as_ref()
+            .ok_or_else(|| format!("Wallet '{}' does not have a mnemonic (cannot sign)", owner))?;
+        let keypair = KeyPair::from_mnemonic(mnemonic).map_err(|e| format!("Error restoring keys: {}", e))?;
+        Ok(Arc::new(keypair))
+    }
+
     /// Credit coins to a wallet (mint or transfer)
     pub fn credit(&mut self, owner: &str, asset: &str, amount: u64) {
         if let Some(wallet) = self.wallets.get_mut(owner) {
@@ -276,6 +344,7 @@ impl WalletManager {
                 encrypted_mnemonic: None,
                 encryption_salt: None,
                 is_encrypted: false,
+                accounts: Vec::new(),
             };
             self.wallets.insert(owner.to_string(), wallet);
         }
@@ -321,7 +390,7 @@ impl WalletManager {
             let _ = s.flush();
             Ok(())
         } else {
-            let json = serde_json::to_string_pretty(self).map_err(|e| std::io::Error::new(std::io::ErrorKind::Other, e))?;
+            let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
             fs::write(path, json)
         }
     }
@@ -334,3 +403,273 @@ impl WalletManager {
         }
     }
 }
+
+/// Find the HD accounts of `mnemonic` that have been used, as a restore does:
+/// derive accounts 0, 1, ... and ask `is_used` about each address, stopping once
+/// `gap_limit` unused ones come in a row.
+pub async fn scan_used_accounts<F, Fut>(mnemonic: &str, gap_limit: u32, mut is_used: F) -> Result<Vec<DerivedAccount>, String>
+where
+    F: FnMut(String) -> Fut,
+    Fut: std::future::Future<Output = Result<bool, String>>,
+{
+    let mut used = Vec::new();
+    let mut gap = 0;
+    let mut index = 0;
+    while gap < gap_limit {
+        let public_key = KeyPair::derive_from_mnemonic(mnemonic, index)?.public_key_hex();
+        if is_used(public_key.clone()).await? {
+            used.push(DerivedAccount { index, public_key });
+            gap = 0;
+        } else {
+            gap += 1;
+        }
+        index += 1;
+    }
+    Ok(used)
+}
+
+/// Default address book file, kept beside `wallets.json`
+pub const ADDRESS_BOOK_PATH: &str = "address_book.json";
+
+/// A labelled recipient
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct Contact {
+    pub label: String,
+    pub address: String,
+    #[serde(default)]
+    pub note: String,
+    /// The name registry confirmed this address belongs to the labelled account
+    #[serde(default)]
+    pub verified: bool,
+    pub added_at: u64,
+}
+
+/// What the address book knows about a recipient typed into a send flow
+#[derive(Debug, Clone, PartialEq)]
+pub enum RecipientCheck {
+    /// Matches a saved contact by label or address
+    Known(Contact),
+    /// Not saved; `similar` holds contacts it could be a typo of
+    Unknown { similar: Vec<Contact> },
+}
+
+/// Saved contacts, keyed by label
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct AddressBook {
+    contacts: std::collections::BTreeMap<String, Contact>,
+}
+
+impl AddressBook {
+    pub fn load(path: &str) -> Self {
+        fs::read_to_string(path)
+            .ok()
+            .and_then(|data| serde_json::from_str(&data).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self, path: &str) -> std::io::Result<()> {
+        let json = serde_json::to_string_pretty(self).map_err(std::io::Error::other)?;
+        fs::write(path, json)
+    }
+
+    pub fn add(&mut self, label: &str, address: &str, note: &str, verified: bool) -> Result<&Contact, String> {
+        let (label, address) = (label.trim(), address.trim());
+        if label.is_empty() || address.is_empty() {
+            return Err("Contact needs a label and an address".to_string());
+        }
+        if self.contacts.contains_key(label) {
+            return Err(format!("A contact named '{}' already exists", label));
+        }
+        if let Some(existing) = self.contacts.values().find(|c| c.address == address) {
+            return Err(format!("{} is already saved as '{}'", address, existing.label));
+        }
+        let contact = Contact {
+            label: label.to_string(),
+            address: address.to_string(),
+            note: note.to_string(),
+            verified,
+            added_at: chrono::Utc::now().timestamp() as u64,
+        };
+        Ok(self.contacts.entry(label.to_string()).or_insert(contact))
+    }
+
+    pub fn remove(&mut self, label: &str) -> Option<Contact> {
+        self.contacts.remove(label)
+    }
+
+    pub fn get(&self, label: &str) -> Option<&Contact> {
+        self.contacts.get(label)
+    }
+
+    pub fn contacts(&self) -> impl Iterator<Item = &Contact> {
+        self.contacts.values()
+    }
+
+    /// Look up a recipient by label or address; if it is neither, list saved contacts
+    /// close enough to it (a couple of edits, or an address sharing both ends) to be a typo.
+    pub fn check_recipient(&self, input: &str) -> RecipientCheck {
+        let input = input.trim();
+        if let Some(contact) = self.contacts.get(input).or_else(|| self.contacts.values().find(|c| c.address == input)) {
+            return RecipientCheck::Known(contact.clone());
+        }
+        let similar = self
+            .contacts
+            .values()
+            .filter(|c| {
+                edit_distance(&c.label.to_lowercase(), &input.to_lowercase()) <= 2
+                    || edit_distance(&c.address, input) <= 2
+                    || lookalike_address(&c.address, input)
+            })
+            .cloned()
+            .collect();
+        RecipientCheck::Unknown { similar }
+    }
+}
+
+/// Long addresses that agree on their first 6 and last 4 characters, which is all
+/// most people compare by eye
+fn lookalike_address(a: &str, b: &str) -> bool {
+    a.len() >= 16 && b.len() >= 16 && a.get(..6) == b.get(..6) && a.get(a.len() - 4..) == b.get(b.len() - 4..)
+}
+
+/// Levenshtein distance over chars
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut row = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            row[j + 1] = (prev[j] + usize::from(ca != *cb)).min(prev[j + 1] + 1).min(row[j] + 1);
+        }
+        prev = row;
+    }
+    prev[b.len()]
+}
+
+/// Parse an `external://` signer URL into the signing-service settings it names.
+///
+/// `external://host:port?key=ID` reaches a daemon over HTTP and
+/// `external:///run/signer.sock?key=ID` over a unix socket. `secret=FILE` names the
+/// shared HMAC secret (default `signer.secret`) and `pubkey=HEX` pins the expected key.
+/// Hardware wallets such as a Ledger are reached through a bridge daemon that speaks
+/// this protocol (see `node::signer`).
+pub fn external_signer_config(url: &str) -> Result<crate::config::RemoteSignerConfig, String> {
+    let rest = url
+        .strip_prefix("external://")
+        .ok_or_else(|| format!("Signer URL must start with external://, got '{}'", url))?;
+    let (location, query) = rest.split_once('?').unwrap_or((rest, ""));
+    if location.is_empty() {
+        return Err("Signer URL has no host or socket path".to_string());
+    }
+
+    let mut config = crate::config::RemoteSignerConfig {
+        url: if location.starts_with('/') { format!("unix://{}", location) } else { format!("http://{}", location) },
+        key_id: "default".to_string(),
+        auth_secret_file: "signer.secret".to_string(),
+        public_key: None,
+        timeout_ms: 30_000, // a person may be confirming on the device
+    };
+    for pair in query.split('&').filter(|p| !p.is_empty()) {
+        match pair.split_once('=') {
+            Some(("key", v)) => config.key_id = v.to_string(),
+            Some(("secret", v)) => config.auth_secret_file = v.to_string(),
+            Some(("pubkey", v)) => config.public_key = Some(v.to_string()),
+            _ => return Err(format!("Unknown signer URL parameter '{}'", pair)),
+        }
+    }
+    Ok(config)
+}
+
+/// Connect to the signer an `external://` URL names. Blocks while the service answers.
+pub fn connect_external_signer(url: &str) -> Result<Arc<dyn Signer>, String> {
+    let config = external_signer_config(url)?;
+    let signer = crate::node::signer::RemoteSigner::connect(&config).map_err(|e| e.to_string())?;
+    Ok(Arc::new(signer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_external_signer_urls() {
+        let http = external_signer_config("external://127.0.0.1:7070?key=ledger&secret=/etc/s.hex").unwrap();
+        assert_eq!(http.url, "http://127.0.0.1:7070");
+        assert_eq!((http.key_id.as_str(), http.auth_secret_file.as_str()), ("ledger", "/etc/s.hex"));
+
+        let unix = external_signer_config("external:///run/compass-ledger.sock").unwrap();
+        assert_eq!(unix.url, "unix:///run/compass-ledger.sock");
+        assert_eq!(unix.key_id, "default");
+
+        assert!(external_signer_config("external://").is_err());
+        assert!(external_signer_config("http://127.0.0.1:7070").is_err());
+        assert!(external_signer_config("external://host?colour=red").is_err());
+    }
+
+    #[test]
+    fn test_attached_signer_takes_precedence() {
+        let mut wm = WalletManager::new();
+        wm.credit("alice", "Compass", 1);
+        let stored = wm.signer_for("alice").unwrap().public_key_hex();
+
+        let device = KeyPair::generate();
+        let device_pk = device.public_key_hex();
+        wm.attach_signer("alice", Arc::new(device));
+        assert_eq!(wm.signer_for("alice").unwrap().public_key_hex(), device_pk);
+        assert_ne!(stored, device_pk);
+        assert!(wm.signer_for("nobody").is_err());
+    }
+
+    #[tokio::test]
+    async fn test_derived_accounts_and_gap_scan() {
+        let mut wm = WalletManager::new();
+        wm.credit("alice", "Compass", 1);
+        let wallet = wm.get_wallet_mut("alice").unwrap();
+        let third = wallet.derive_account(3).unwrap();
+        wallet.derive_account(1).unwrap();
+        wallet.derive_account(3).unwrap();
+        assert_eq!(wallet.accounts.iter().map(|a| a.index).collect::<Vec<_>>(), vec![1, 3]);
+        assert_eq!(wm.signer_for("alice/3").unwrap().public_key_hex(), third.public_key);
+        assert!(wm.signer_for("alice/x").is_err());
+        assert!(wm.signer_for("bob/3").is_err());
+
+        // Accounts 0 and 3 were used: a gap limit of 2 stops short of 3, a limit of 3 reaches it
+        let mnemonic = wm.get_wallet("alice").unwrap().mnemonic.clone().unwrap();
+        let used: Vec<String> = [0, 3].iter().map(|i| KeyPair::derive_from_mnemonic(&mnemonic, *i).unwrap().public_key_hex()).collect();
+        let mut asked = 0;
+        let found = scan_used_accounts(&mnemonic, 2, |pk| {
+            asked += 1;
+            let hit = used.contains(&pk);
+            async move { Ok(hit) }
+        })
+        .await
+        .unwrap();
+        assert_eq!(found.iter().map(|a| a.index).collect::<Vec<_>>(), vec![0]);
+        assert_eq!(asked, 3);
+
+        let found = scan_used_accounts(&mnemonic, 3, |pk| std::future::ready(Ok(used.contains(&pk)))).await.unwrap();
+        assert_eq!(found.iter().map(|a| a.index).collect::<Vec<_>>(), vec![0, 3]);
+    }
+
+    #[test]
+    fn test_address_book_flags_typos() {
+        let mut book = AddressBook::default();
+        let bob = "ab12cd34ef56ab12cd34ef56ab12cd34ef56ab12cd34ef56ab12cd34ef569f0e";
+        book.add("bob", bob, "rent", true).unwrap();
+        assert!(book.add("bob", "elsewhere", "", false).is_err());
+        assert!(book.add("robert", bob, "", false).is_err());
+
+        assert!(matches!(book.check_recipient("bob"), RecipientCheck::Known(c) if c.address == bob));
+        assert!(matches!(book.check_recipient(bob), RecipientCheck::Known(c) if c.label == "bob"));
+
+        // One character off in the middle of the address, or in the label
+        let typo = bob.replacen("ef56ab12", "ef56ab13", 1);
+        for input in [typo.as_str(), "bbo"] {
+            match book.check_recipient(input) {
+                RecipientCheck::Unknown { similar } => assert_eq!(similar[0].label, "bob"),
+                other => panic!("expected a typo warning, got {:?}", other),
+            }
+        }
+        assert_eq!(book.check_recipient("carol_the_builder"), RecipientCheck::Unknown { similar: vec![] });
+    }
+}
@@ -1,6 +1,10 @@
-/// Interactive Worker Menu - View and Select Oracle Verification Jobs
+/// Interactive Worker Console - claimed jobs, live earnings and job controls
 use crate::client::rpc_client::RpcClient;
+use crate::client::scheduler::{ClaimError, JobClass, JobHandle, WorkerScheduler};
+use crate::interactive::prompt::{prompt, prompt_u64};
 use std::io::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use serde_json::json;
 use crate::rpc::types::OracleVerificationJob;
@@ -9,12 +13,56 @@ use crate::client::price_fetcher::PriceFetcher;
 use rust_decimal::prelude::*;
 use crate::crypto::KeyPair;
 
+/// How often the dispatcher looks for new jobs when auto-claim is on
+const DISPATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// A job offered by the node, of any class
+#[derive(Clone)]
+enum AvailableJob {
+    Oracle(OracleVerificationJob),
+    Recurring(RecurringOracleJob),
+    Compute(crate::layer3::compute::ComputeJob),
+}
+
+impl AvailableJob {
+    fn id(&self) -> &str {
+        match self {
+            AvailableJob::Oracle(j) => &j.job_id,
+            AvailableJob::Recurring(j) => &j.job_id,
+            AvailableJob::Compute(j) => &j.job_id,
+        }
+    }
+
+    fn class(&self) -> JobClass {
+        match self {
+            AvailableJob::Oracle(_) => JobClass::Oracle,
+            AvailableJob::Recurring(_) => JobClass::Recurring,
+            AvailableJob::Compute(_) => JobClass::Compute,
+        }
+    }
+
+    fn label(&self) -> String {
+        match self {
+            AvailableJob::Oracle(j) => j.ticker.clone(),
+            AvailableJob::Recurring(j) => format!("{} every {}s", j.ticker, j.interval_seconds),
+            AvailableJob::Compute(j) => j.model_id.clone(),
+        }
+    }
+}
+
+/// Everything a running job needs, shared by the console and the dispatcher
+struct WorkerContext {
+    client: RpcClient,
+    keypair: KeyPair,
+    worker_id: String,
+    scheduler: WorkerScheduler,
+    auto_claim: AtomicBool,
+    tasks: Mutex<Vec<tokio::task::AbortHandle>>,
+}
+
 pub async fn worker_job_menu(node_url: &str) -> Result<(), String> {
-    let client = RpcClient::new(node_url.to_string());
-    
     // 1. Select Worker Wallet
     println!("\n🔑 Select Worker Wallet");
-    // 1. Select Worker Wallet
     // Use helper for robust interaction
     let identity = match crate::interactive::load_and_maybe_create_identity("worker") {
         Some(id) => id,
@@ -26,177 +74,236 @@ pub async fn worker_job_menu(node_url: &str) -> Result<(), String> {
     
     println!("✅ Worker Identity Loaded: {}", worker_id);
     println!("   (Rewards will be sent to this address)");
+
+    let ctx = Arc::new(WorkerContext {
+        client: RpcClient::new(node_url.to_string()),
+        keypair: worker_keypair,
+        worker_id,
+        scheduler: WorkerScheduler::new(1),
+        auto_claim: AtomicBool::new(false),
+        tasks: Mutex::new(Vec::new()),
+    });
+    let dispatcher = tokio::spawn(dispatch_loop(ctx.clone()));
+
+    // Session earnings are measured against the balance when the console opened
+    let mut session_start = compute_balance(&ctx).await;
     
     loop {
-        println!("\n╔════════════════════════════════════════════════╗");
-        println!("║    🔍 Oracle Verification Worker Dashboard    ║");
-        println!("╚════════════════════════════════════════════════╝");
-        println!();
-        
-        // Fetch ALL job types
-        println!("📡 Fetching available jobs...");
-        
-        // Ignore errors to keep menu alive
-        let single_jobs = fetch_pending_oracle_jobs(&client).await.unwrap_or_else(|_| Vec::new());
-        let recurring_jobs = fetch_recurring_jobs(&client).await.unwrap_or_else(|_| Vec::new());
-        let compute_jobs: Vec<crate::layer3::compute::ComputeJob> = client.call_method("getPendingComputeJobs", json!({})).await.unwrap_or_else(|_| Vec::new());
-        
-        let total_jobs = single_jobs.len() + recurring_jobs.len() + compute_jobs.len();
-        
-        if total_jobs == 0 {
-             println!("✓ No available jobs found.");
-             // Show options even if no jobs
-             println!();
-             println!("Options:");
-             println!("  A. Start Autonomous AI Worker (Loop)");
-             println!("  R. Refresh");
-             println!("  Q. Exit");
-             print!("Select: ");
-             io::stdout().flush().unwrap();
-             
-             let mut choice = String::new();
-             io::stdin().read_line(&mut choice).unwrap();
-             
-             match choice.trim().to_uppercase().as_str() {
-                 "Q" | "2" => break,
-                 "A" => {
-                      ai_worker_loop(&client, &worker_keypair).await?;
-                 },
-                 _ => continue,
-             }
-             continue;
-        }
-        
-        // Display banner
-        println!("\n╔════════════════════════════════════════════════╗");
-        println!("║         📋 AVAILABLE JOBS ({:2} Total)         ║", total_jobs);
-        println!("╚════════════════════════════════════════════════╝\n");
-        
-        let mut job_index = 1;
-        
-        // Display Single Oracle Jobs
-        if !single_jobs.is_empty() {
-            println!("🔍 SINGLE ORACLE VERIFICATION JOBS:");
-            println!("-------------------------------------------------");
-            for job in &single_jobs {
-                println!("  [{}] Job ID: {}", job_index, job.job_id);
-                println!("      Ticker: {}", job.ticker);
-                job_index += 1;
+        print_dashboard(&ctx, &mut session_start).await;
+
+        println!("\nOptions:");
+        println!("  R - Refresh");
+        println!("  J - Browse available jobs and start one");
+        println!("  A - Turn auto-claim {}", if ctx.auto_claim.load(Ordering::Relaxed) { "OFF" } else { "ON" });
+        println!("  P - Pause/resume a job class");
+        println!("  C - Set concurrency");
+        println!("  Q - Quit (stops running jobs)");
+
+        match prompt("\nSelect: ").to_uppercase().as_str() {
+            "J" => browse_jobs(&ctx).await,
+            "A" => {
+                let on = !ctx.auto_claim.load(Ordering::Relaxed);
+                ctx.auto_claim.store(on, Ordering::Relaxed);
+                println!("Auto-claim {}", if on { "enabled" } else { "disabled" });
             }
-            println!();
+            "P" => pause_menu(&ctx.scheduler),
+            "C" => match prompt_u64(&format!("Max concurrent jobs [{}]: ", ctx.scheduler.concurrency())) {
+                Some(n) if n > 0 => ctx.scheduler.set_concurrency(n as usize),
+                _ => println!("Unchanged."),
+            },
+            "Q" => break,
+            _ => continue,
         }
-        
-        // Display Recurring Oracle Jobs
-        if !recurring_jobs.is_empty() {
-            println!("🔁 RECURRING ORACLE JOBS:");
-            println!("-------------------------------------------------");
-            for job in &recurring_jobs {
-                println!("  [{}] {} ({})", job_index, job.ticker, job.job_id);
-                println!("      Interval: {}s | Reward: {} COMPASS/update", job.interval_seconds, job.worker_reward_per_update);
-                job_index += 1;
-            }
-            println!();
+    }
+
+    dispatcher.abort();
+    let tasks = std::mem::take(&mut *ctx.tasks.lock().unwrap_or_else(|e| e.into_inner()));
+    for task in tasks {
+        task.abort();
+    }
+    
+    Ok(())
+}
+
+async fn compute_balance(ctx: &WorkerContext) -> Option<u64> {
+    ctx.client.get_balance(&ctx.worker_id, "COMPUTE").await.ok()
+}
+
+async fn print_dashboard(ctx: &WorkerContext, session_start: &mut Option<u64>) {
+    println!("\n╔════════════════════════════════════════════════╗");
+    println!("║             ⚙️  Worker Console                 ║");
+    println!("╚════════════════════════════════════════════════╝");
+    println!("Worker: {}", ctx.worker_id);
+
+    match compute_balance(ctx).await {
+        Some(balance) => {
+            let start = *session_start.get_or_insert(balance);
+            println!(
+                "Earned: {} this session | {} lifetime",
+                crate::encoding::Amount::new(balance.saturating_sub(start), "COMPUTE"),
+                crate::encoding::Amount::new(balance, "COMPUTE")
+            );
         }
-        
-        // Display Compute Jobs
-        if !compute_jobs.is_empty() {
-            println!("🤖 NEURAL NETWORK COMPUTE JOBS:");
-            println!("-------------------------------------------------");
-            for job in &compute_jobs {
-                println!("  [{}] {} ({})", job_index, job.model_id, job.job_id);
-                println!("      Compute Units: {} | Reward: {} COMPUTE", job.max_compute_units, job.reward_amount);
-                job_index += 1;
-            }
-            println!();
+        None => println!("Earned: ? (node unreachable)"),
+    }
+
+    let snap = ctx.scheduler.snapshot();
+    println!(
+        "Slots:  {}/{} busy | Auto-claim: {} | Paused: {}",
+        snap.active.len(),
+        snap.max_concurrency,
+        if ctx.auto_claim.load(Ordering::Relaxed) { "on" } else { "off" },
+        if snap.paused.is_empty() {
+            "none".to_string()
+        } else {
+            snap.paused.iter().map(|c| c.to_string()).collect::<Vec<_>>().join(", ")
         }
-        
-        println!("A. [AUTO] Start Autonomous AI Worker (Loop)");
-        println!("-------------------------------------------------");
-        
-        println!("\nOptions:");
-        if total_jobs > 0 {
-            println!("  [1-{}] - Select job to work on", job_index - 1);
+    );
+    println!("Done:   {} completed, {} failed", snap.completed, snap.failed);
+
+    if snap.active.is_empty() {
+        println!("\nNo jobs running.");
+    } else {
+        println!("\nClaimed jobs:");
+        for job in &snap.active {
+            let progress = job.progress.map(|(d, t)| format!(" {}/{} updates", d, t)).unwrap_or_default();
+            println!(
+                "  [{}] {} ({}){} - running {}s",
+                job.class,
+                job.label,
+                job.job_id,
+                progress,
+                job.elapsed().as_secs()
+            );
         }
-        println!("  R - Refresh list");
-        println!("  Q - Quit");
-        print!("\nSelect: ");
-        io::stdout().flush().unwrap();
-        
-        let mut choice = String::new();
-        io::stdin().read_line(&mut choice).unwrap();
-        match choice.trim().to_uppercase().as_str() {
-            "R" => continue,
-            "Q" => break,
-            "A" => {
-                ai_worker_loop(&client, &worker_keypair).await?;
-            }
-            num => {
-                 if let Ok(idx) = num.parse::<usize>() {
-                     if idx > 0 && idx < job_index {
-                         // Determine execution path
-                         if idx <= single_jobs.len() {
-                             // Process single job
-                             let job = &single_jobs[idx - 1];
-                             process_single_job(job, &client, &worker_keypair).await?;
-                         } else if idx <= single_jobs.len() + recurring_jobs.len() {
-                             // Process recurring job
-                             let job = &recurring_jobs[idx - 1 - single_jobs.len()];
-                             process_recurring_job(job, &client, &worker_keypair).await?;
-                         } else {
-                             // Process Compute Job
-                             let job = &compute_jobs[idx - 1 - single_jobs.len() - recurring_jobs.len()];
-                             process_compute_job(job, &client, &worker_keypair).await?;
-                         }
-                     }
-                 }
+    }
+}
+
+/// Pending work of every class, oracle checks first
+async fn fetch_available_jobs(client: &RpcClient) -> Vec<AvailableJob> {
+    // Ignore errors to keep the console alive
+    let single_jobs = fetch_pending_oracle_jobs(client).await.unwrap_or_default();
+    let recurring_jobs = fetch_recurring_jobs(client).await.unwrap_or_default();
+    let compute_jobs: Vec<crate::layer3::compute::ComputeJob> =
+        client.call_method("getPendingComputeJobs", json!({})).await.unwrap_or_default();
+
+    single_jobs
+        .into_iter()
+        .map(AvailableJob::Oracle)
+        .chain(recurring_jobs.into_iter().map(AvailableJob::Recurring))
+        .chain(compute_jobs.into_iter().map(AvailableJob::Compute))
+        .collect()
+}
+
+/// Claim a slot for `job` and run it in the background
+fn spawn_job(ctx: &Arc<WorkerContext>, job: AvailableJob) -> Result<(), ClaimError> {
+    let handle = ctx.scheduler.try_claim(job.id(), job.class(), &job.label())?;
+    let task_ctx = ctx.clone();
+    let task = tokio::spawn(async move {
+        let ctx = task_ctx;
+        let result = match &job {
+            AvailableJob::Oracle(j) => process_single_job(j, &ctx.client, &ctx.keypair).await,
+            AvailableJob::Recurring(j) => process_recurring_job(j, &ctx.client, &ctx.keypair, &handle).await,
+            AvailableJob::Compute(j) => process_compute_job(j, &ctx.client, &ctx.keypair).await,
+        };
+        if let Err(e) = &result {
+            println!("❌ Job {} failed: {}", handle.job_id(), e);
+        }
+        handle.finish(result.is_ok());
+    });
+
+    let mut tasks = ctx.tasks.lock().unwrap_or_else(|e| e.into_inner());
+    tasks.retain(|t| !t.is_finished());
+    tasks.push(task.abort_handle());
+    Ok(())
+}
+
+/// Claims new jobs whenever auto-claim is on and a slot is free
+async fn dispatch_loop(ctx: Arc<WorkerContext>) {
+    loop {
+        if ctx.auto_claim.load(Ordering::Relaxed) && ctx.scheduler.available() > 0 {
+            for job in fetch_available_jobs(&ctx.client).await {
+                if ctx.scheduler.available() == 0 {
+                    break;
+                }
+                // Recurring jobs may be reserved for another worker
+                if let AvailableJob::Recurring(j) = &job {
+                    if j.assigned_worker.as_ref().is_some_and(|w| w != &ctx.worker_id) {
+                        continue;
+                    }
+                }
+                if ctx.scheduler.has_seen(job.id()) || ctx.scheduler.is_paused(job.class()) {
+                    continue;
+                }
+                let _ = spawn_job(&ctx, job);
             }
         }
+        tokio::time::sleep(DISPATCH_INTERVAL).await;
+    }
+}
+
+async fn browse_jobs(ctx: &Arc<WorkerContext>) {
+    println!("📡 Fetching available jobs...");
+    let jobs = fetch_available_jobs(&ctx.client).await;
+    if jobs.is_empty() {
+        println!("✓ No available jobs found.");
+        return;
+    }
+
+    println!("\n╔════════════════════════════════════════════════╗");
+    println!("║         📋 AVAILABLE JOBS ({:2} Total)         ║", jobs.len());
+    println!("╚════════════════════════════════════════════════╝\n");
+    for (i, job) in jobs.iter().enumerate() {
+        let detail = match job {
+            AvailableJob::Oracle(_) => "Single oracle verification".to_string(),
+            AvailableJob::Recurring(j) => format!("Reward: {} COMPASS/update", j.worker_reward_per_update),
+            AvailableJob::Compute(j) => format!("Compute Units: {} | Reward: {} COMPUTE", j.max_compute_units, j.reward_amount),
+        };
+        let claimed = if ctx.scheduler.has_seen(job.id()) { " (claimed)" } else { "" };
+        println!("  [{}] [{}] {} ({}){}", i + 1, job.class(), job.label(), job.id(), claimed);
+        println!("      {}", detail);
+    }
+
+    let Some(idx) = prompt_u64(&format!("\nStart job [1-{}] (Enter to go back): ", jobs.len())) else {
+        return;
+    };
+    let Some(job) = jobs.get((idx as usize).wrapping_sub(1)).cloned() else {
+        println!("Invalid selection.");
+        return;
+    };
+    match spawn_job(ctx, job) {
+        Ok(()) => println!("🚀 Job started in the background."),
+        Err(e) => println!("❌ Cannot start job: {}", e),
+    }
+}
+
+fn pause_menu(scheduler: &WorkerScheduler) {
+    for (i, class) in JobClass::ALL.iter().enumerate() {
+        let state = if scheduler.is_paused(*class) { "paused" } else { "active" };
+        println!("  {}. {} ({})", i + 1, class, state);
+    }
+    let Some(class) = prompt_u64("Toggle class: ").and_then(|i| JobClass::ALL.get((i as usize).wrapping_sub(1)).copied()) else {
+        return;
+    };
+    if scheduler.is_paused(class) {
+        scheduler.resume(class);
+        println!("▶️  {} jobs resumed", class);
+    } else {
+        scheduler.pause(class);
+        println!("⏸️  {} jobs paused (running jobs finish; recurring jobs wait)", class);
     }
-    
-    Ok(())
 }
 
 async fn process_compute_job(job: &crate::layer3::compute::ComputeJob, client: &RpcClient, worker_keypair: &KeyPair) -> Result<(), String> {
     println!("\n🧠 Processing Single AI Job: {}", job.job_id);
     let worker_id = worker_keypair.public_key_hex();
     
-    // Execute logic using the shared helper (extracted from loop)
+    // Execute logic using the shared helper
     execute_ai_logic(job, &worker_id, worker_keypair, client).await?;
     log_job_history("COMPUTE", &job.job_id, &format!("Model: {}", job.model_id));
     Ok(())
 }
 
-async fn ai_worker_loop(client: &RpcClient, worker_keypair: &KeyPair) -> Result<(), String> {
-    println!("\n🧠 Starting AI Worker...");
-    println!("   Monitoring for compute jobs...");
-    println!("   Supported Models: llama-3-8b, stable-diffusion-xl, crypto-signal-v1");
-    println!("   (Press Ctrl+C to stop)");
-    
-    let worker_id = worker_keypair.public_key_hex();
-
-    loop {
-        // 1. Poll for Jobs
-        let jobs: Vec<crate::layer3::compute::ComputeJob> = match client.call_method("getPendingComputeJobs", json!({})).await {
-            Ok(j) => j,
-            Err(_) => {
-                tokio::time::sleep(Duration::from_secs(2)).await;
-                continue;
-            }
-        };
-        
-        // 2. Pick a Job
-        if let Some(job) = jobs.first() {
-            println!("\n🚀 Job Detected: {}", job.job_id);
-            execute_ai_logic(job, &worker_id, worker_keypair, client).await?;
-            log_job_history("COMPUTE_AUTO", &job.job_id, &format!("Model: {}", job.model_id));
-        } else {
-            print!("."); io::stdout().flush().unwrap();
-        }
-        
-        tokio::time::sleep(Duration::from_secs(2)).await;
-    }
-}
-
 
 async fn fetch_recurring_jobs(client: &RpcClient) -> Result<Vec<RecurringOracleJob>, String> {
     client.call_method("getRecurringJobs", serde_json::json!({})).await
@@ -207,12 +314,11 @@ async fn fetch_pending_oracle_jobs(client: &RpcClient) -> Result<Vec<OracleVerif
 }
 
 
-async fn process_recurring_job(job: &RecurringOracleJob, client: &RpcClient, worker_keypair: &KeyPair) -> Result<(), String> {
+async fn process_recurring_job(job: &RecurringOracleJob, client: &RpcClient, worker_keypair: &KeyPair, handle: &JobHandle) -> Result<(), String> {
     println!("\n🔄 Starting Recurring Worker for {}...", job.ticker);
     println!("   Target: {} updates ({} completed)", job.total_updates_required, job.completed_updates);
     println!("   Interval: {} seconds", job.interval_seconds);
     println!("   Reward: {} COMPASS per update", job.worker_reward_per_update);
-    println!("\nPress Ctrl+C to stop monitoring.");
     
     // Use proper identity
     let worker_id = worker_keypair.public_key_hex();
@@ -238,13 +344,20 @@ async fn process_recurring_job(job: &RecurringOracleJob, client: &RpcClient, wor
             println!("🎉 Job Completed! All updates finished.");
             break;
         }
+        handle.set_progress(current_completed, total_required);
+
+        // Paused from the console: hold the slot but stop submitting updates
+        if handle.is_paused() {
+            tokio::time::sleep(Duration::from_secs(5)).await;
+            continue;
+        }
 
         println!("\n⏳ Performing verification {}/{}...", current_completed + 1, total_required);
         
         // --- Layer 3 Finance Oracle Logic (Hoisted) ---
         if job.ticker == "FINANCE_ML_V1" {
             // Call the modular agent logic with CONTINUOUS duration (uses the job's interval)
-            match crate::layer3::agent::run_continuous_cycle(job, client, worker_keypair, current_completed + 1, job.interval_seconds).await {
+            match run_agent_cycle(job, client, worker_keypair, current_completed + 1, job.interval_seconds).await {
                 Ok(_) => {
                      // No sleep here, the agent function consumed the interval duration!
                      continue; 
@@ -458,7 +571,7 @@ async fn execute_ai_logic(job: &crate::layer3::compute::ComputeJob, worker_id: &
             };
             
             // Run the actual neural network training with hash computation
-            match crate::layer3::agent::run_continuous_cycle(&training_job, client, worker_keypair, 1, 15).await {
+            match run_agent_cycle(&training_job, client, worker_keypair, 1, 15).await {
                 Ok(_) => {
                     println!("\n✅ Neural Network Training Complete!");
                     println!("   Hash power utilized for AI model optimization");
@@ -579,6 +692,17 @@ async fn process_single_job(job: &OracleVerificationJob, client: &RpcClient, wor
      Ok(())
 }
 
+/// One neural-agent update. The agent runs on candle, so workers built without the `ai` feature refuse these jobs
+async fn run_agent_cycle(job: &RecurringOracleJob, client: &RpcClient, worker_keypair: &KeyPair, update_num: u32, duration_seconds: u64) -> Result<(), String> {
+    #[cfg(feature = "ai")]
+    return crate::layer3::agent::run_continuous_cycle(job, client, worker_keypair, update_num, duration_seconds).await;
+    #[cfg(not(feature = "ai"))]
+    {
+        let _ = (client, worker_keypair, update_num, duration_seconds);
+        Err(format!("{} needs the neural agent, which this worker was built without (enable the 'ai' feature)", job.ticker))
+    }
+}
+
 fn log_oracle_data(ticker: &str, price: &str, update_num: u32) -> std::io::Result<()> {
     use std::fs::OpenOptions;
     
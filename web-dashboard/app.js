@@ -125,6 +125,22 @@ class CompassRPC {
     async getMyModels(owner) {
         return this.call('getMyModels', { owner });
     }
+
+    async getAccountActivity(walletId, page, limit) {
+        return this.call('getAccountActivity', { wallet_id: walletId, page, limit });
+    }
+
+    async getModelPools() {
+        return this.call('getModelPools');
+    }
+
+    async getPoolGovernance(poolId) {
+        return this.call('getPoolGovernance', { pool_id: poolId });
+    }
+
+    async getAuditLog(count = 50) {
+        return this.call('getAuditLog', { count });
+    }
 }
 
 // Initialize RPC client
@@ -158,6 +174,7 @@ function navigateTo(page) {
         explorer: 'Block Explorer',
         trading: 'Paper Trading',
         models: 'AI Models',
+        pools: 'Model Pools',
         settings: 'Settings'
     };
     document.getElementById('pageTitle').textContent = titles[page] || page;
@@ -183,7 +200,10 @@ async function loadPageData(page) {
             }
             break;
         case 'explorer':
-            await refreshExplorerBlocks();
+            await Promise.all([
+                refreshExplorerBlocks(),
+                refreshAuditLog()
+            ]);
             break;
         case 'trading':
             await Promise.all([
@@ -199,6 +219,9 @@ async function loadPageData(page) {
                 refreshModels()
             ]);
             break;
+        case 'pools':
+            await refreshPools();
+            break;
         case 'settings':
             await loadNodeInfo();
             break;
@@ -332,7 +355,7 @@ async function loadWalletBalances() {
 async function submitTransfer(event) {
     event.preventDefault();
 
-    const to = document.getElementById('transferTo').value;
+    const input = document.getElementById('transferTo').value.trim();
     const amount = parseFloat(document.getElementById('transferAmount').value);
     const asset = document.getElementById('transferAsset').value;
 
@@ -341,6 +364,14 @@ async function submitTransfer(event) {
         return;
     }
 
+    // A contact label pays its saved address; anything unknown or unverified is confirmed first
+    const check = checkRecipient(loadAddressBook(), input);
+    const to = check.known ? check.known.address : input;
+    const warning = recipientWarning(input, check);
+    if (warning && !confirm(`${warning}\n\nSend to ${to} anyway?`)) {
+        return;
+    }
+
     try {
         const result = await rpc.submitTransaction({
             from: state.walletAddress,
@@ -359,6 +390,152 @@ async function submitTransfer(event) {
     }
 }
 
+// ===== Address Book =====
+// Same rules as the CLI's `compass contact` book (src/wallet.rs), kept in this browser
+const ADDRESS_BOOK_KEY = 'compass_address_book';
+
+function loadAddressBook() {
+    try {
+        return JSON.parse(localStorage.getItem(ADDRESS_BOOK_KEY)) || {};
+    } catch (error) {
+        return {};
+    }
+}
+
+function saveAddressBook(book) {
+    localStorage.setItem(ADDRESS_BOOK_KEY, JSON.stringify(book));
+    renderContactOptions();
+}
+
+function renderContactOptions() {
+    const list = document.getElementById('contactOptions');
+    if (!list) return;
+    list.innerHTML = Object.values(loadAddressBook()).map(contact =>
+        `<option value="${escapeHtml(contact.label)}">${escapeHtml(contact.address)}</option>`
+    ).join('');
+}
+
+// Levenshtein distance over characters
+function editDistance(a, b) {
+    const x = [...a], y = [...b];
+    let prev = Array.from({ length: y.length + 1 }, (_, j) => j);
+    x.forEach((ca, i) => {
+        const row = [i + 1];
+        y.forEach((cb, j) => {
+            row[j + 1] = Math.min(prev[j] + (ca === cb ? 0 : 1), prev[j + 1] + 1, row[j] + 1);
+        });
+        prev = row;
+    });
+    return prev[y.length];
+}
+
+// Long addresses that agree on their first 6 and last 4 characters, which is all
+// most people compare by eye
+function lookalikeAddress(a, b) {
+    return a.length >= 16 && b.length >= 16 && a.slice(0, 6) === b.slice(0, 6) && a.slice(-4) === b.slice(-4);
+}
+
+// { known: contact } for a saved label or address, otherwise { similar: [contacts it could be a typo of] }
+function checkRecipient(book, input) {
+    const contacts = Object.values(book);
+    const known = book[input] || contacts.find(c => c.address === input);
+    if (known) return { known };
+    const similar = contacts.filter(c =>
+        editDistance(c.label.toLowerCase(), input.toLowerCase()) <= 2
+        || editDistance(c.address, input) <= 2
+        || lookalikeAddress(c.address, input)
+    );
+    return { similar };
+}
+
+function recipientWarning(input, check) {
+    if (check.known) {
+        return check.known.verified
+            ? null
+            : `Contact '${check.known.label}' has not been verified against the name registry.`;
+    }
+    if (check.similar.length === 0) {
+        return `${input} is not in your address book.`;
+    }
+    const names = check.similar.map(c => `'${c.label}' (${c.address})`).join(', ');
+    return `${input} is not in your address book but closely resembles ${names}. Possible typo!`;
+}
+
+// The registry verifies a contact only if its label names the account at that address
+async function verifyContact(label, address) {
+    try {
+        const record = await rpc.call('resolveName', { name: label });
+        return Boolean(record.registered) && (record.signing_pubkey === address || record.name === address);
+    } catch (error) {
+        return false;
+    }
+}
+
+async function saveRecipientAsContact() {
+    const address = document.getElementById('transferTo').value.trim();
+    if (!address) {
+        showToast('Enter a recipient address first', 'error');
+        return;
+    }
+    const label = (prompt(`Label for ${address}`) || '').trim();
+    if (!label) return;
+
+    const book = loadAddressBook();
+    if (book[label]) {
+        showToast(`A contact named '${label}' already exists`, 'error');
+        return;
+    }
+    const existing = Object.values(book).find(c => c.address === address);
+    if (existing) {
+        showToast(`${address} is already saved as '${existing.label}'`, 'error');
+        return;
+    }
+
+    const verified = await verifyContact(label, address);
+    book[label] = { label, address, verified, added_at: Math.floor(Date.now() / 1000) };
+    saveAddressBook(book);
+    showToast(`Contact '${label}' saved${verified ? ' (verified)' : ' (unverified)'}`, 'success');
+}
+
+// Same columns as `compass wallet export-history --format csv`
+const ACTIVITY_COLUMNS = ['timestamp', 'type', 'asset', 'amount', 'counterparty', 'fee', 'block', 'block_hash'];
+
+function csvField(value) {
+    const text = String(value ?? '');
+    return /[",\r\n]/.test(text) ? `"${text.replace(/"/g, '""')}"` : text;
+}
+
+async function downloadActivityCsv() {
+    if (!state.walletAddress) {
+        showToast('Please load a wallet first', 'error');
+        return;
+    }
+
+    try {
+        const pageSize = 100;
+        let records = [];
+        for (let page = 0; ; page++) {
+            const result = await rpc.getAccountActivity(state.walletAddress, page, pageSize);
+            records = records.concat(result.records);
+            if ((page + 1) * pageSize >= result.total) break;
+        }
+        records.reverse(); // oldest first
+
+        const lines = [ACTIVITY_COLUMNS.join(',')]
+            .concat(records.map(r => ACTIVITY_COLUMNS.map(c => csvField(r[c])).join(',')));
+        const blob = new Blob([lines.join('\n') + '\n'], { type: 'text/csv' });
+        const link = document.createElement('a');
+        link.href = URL.createObjectURL(blob);
+        link.download = `compass-activity-${state.walletAddress.slice(0, 12)}.csv`;
+        link.click();
+        URL.revokeObjectURL(link.href);
+
+        showToast(`Exported ${records.length} activity rows`, 'success');
+    } catch (error) {
+        showToast(`Export failed: ${error.message}`, 'error');
+    }
+}
+
 function showCreateWallet() {
     // Generate a simple random address for demo
     const randomAddress = 'cmp_' + Array.from({ length: 40 }, () =>
@@ -380,7 +557,7 @@ async function refreshExplorerBlocks() {
             <tr onclick="showBlockDetails(${block.index})" style="cursor: pointer;">
                 <td><strong>${block.index}</strong></td>
                 <td><code>${truncateHash(block.hash)}</code></td>
-                <td>${getBlockTypeName(block.block_type)}</td>
+                <td>${getBlockSummary(block)}</td>
                 <td>${formatTimestamp(block.timestamp)}</td>
             </tr>
         `).join('');
@@ -432,12 +609,51 @@ function displayBlockDetails(block) {
         <div class="info-row"><span>Hash</span><span>${block.hash}</span></div>
         <div class="info-row"><span>Previous Hash</span><span>${block.prev_hash}</span></div>
         <div class="info-row"><span>Type</span><span>${getBlockTypeName(block.block_type)}</span></div>
+        <div class="info-row"><span>Summary</span><span>${getBlockSummary(block)}</span></div>
         <div class="info-row"><span>Proposer</span><span>${block.proposer}</span></div>
         <div class="info-row"><span>Timestamp</span><span>${new Date(block.timestamp).toLocaleString()}</span></div>
         <div class="info-row"><span>Signature</span><span>${truncateHash(block.signature_hex)}</span></div>
     `;
 }
 
+// Privileged node actions, newest first; the node re-checks the hash chain on every read
+async function refreshAuditLog() {
+    const tbody = document.getElementById('auditLogBody');
+    const status = document.getElementById('auditLogStatus');
+
+    try {
+        const log = await rpc.getAuditLog(50);
+        const entries = [...log.entries].reverse();
+
+        if (log.chain_valid) {
+            status.textContent = `${log.total} entries, hash chain intact`;
+            status.className = 'audit-status';
+        } else {
+            status.textContent = `Hash chain broken at entry ${log.first_invalid_seq}`;
+            status.className = 'audit-status broken';
+        }
+
+        if (entries.length === 0) {
+            tbody.innerHTML = '<tr><td colspan="5" class="loading">No audit entries</td></tr>';
+            return;
+        }
+
+        tbody.innerHTML = entries.map(entry => `
+            <tr>
+                <td><strong>${entry.seq}</strong></td>
+                <td>${new Date(entry.timestamp * 1000).toLocaleString()}</td>
+                <td>${escapeHtml(entry.action)}</td>
+                <td><code title="${escapeHtml(entry.actor)}">${escapeHtml(truncateHash(entry.actor))}</code></td>
+                <td>${escapeHtml(entry.details)}</td>
+            </tr>
+        `).join('');
+
+    } catch (error) {
+        status.textContent = '';
+        tbody.innerHTML = '<tr><td colspan="5" class="loading">Error loading audit log</td></tr>';
+    }
+}
+
 // ===== Trading Functions =====
 async function loadTradingStats() {
     try {
@@ -728,6 +944,68 @@ async function refreshModels() {
     }
 }
 
+// ===== Pool Functions =====
+function describePoolChange(change) {
+    switch (change.param) {
+        case 'training_cadence_epochs': return `Training every ${change.value} epochs`;
+        case 'contributor_share_bps': return `Revenue split ${(change.value / 100).toFixed(2)}%`;
+        case 'signal_price': return `Signal price ${formatNumber(change.value)} COMPASS`;
+        default: return change.param;
+    }
+}
+
+async function refreshPools() {
+    const poolsBody = document.getElementById('poolsTableBody');
+    const proposalsBody = document.getElementById('poolProposalsBody');
+
+    try {
+        const pools = await rpc.getModelPools();
+
+        if (!pools || pools.length === 0) {
+            poolsBody.innerHTML = '<tr><td colspan="7" class="loading">No pools yet</td></tr>';
+            proposalsBody.innerHTML = '<tr><td colspan="6" class="loading">No proposals</td></tr>';
+            return;
+        }
+
+        const governance = await Promise.all(pools.map(pool => rpc.getPoolGovernance(pool.pool_id)));
+
+        poolsBody.innerHTML = pools.map((pool, i) => {
+            const params = governance[i].params;
+            return `
+            <tr>
+                <td>${pool.name} <span class="hash">${pool.pool_id}</span></td>
+                <td>${pool.model_type}</td>
+                <td>${formatNumber(pool.total_staked)}</td>
+                <td>${Object.keys(pool.contributors || {}).length}</td>
+                <td>${params.training_cadence_epochs} epochs</td>
+                <td>${(params.contributor_share_bps / 100).toFixed(2)}%</td>
+                <td>${formatNumber(params.signal_price)} COMPASS</td>
+            </tr>
+        `;
+        }).join('');
+
+        const rows = governance.flatMap(gov => gov.proposals.map((p, i) => ({ gov, p, tally: gov.tallies[i] })));
+        if (rows.length === 0) {
+            proposalsBody.innerHTML = '<tr><td colspan="6" class="loading">No proposals</td></tr>';
+            return;
+        }
+        proposalsBody.innerHTML = rows.reverse().map(({ gov, p, tally }) => `
+            <tr>
+                <td>${gov.pool_id}</td>
+                <td>${p.id}</td>
+                <td>${describePoolChange(p.change)}</td>
+                <td>${p.status}</td>
+                <td>${formatNumber(tally.yes)} / ${formatNumber(tally.no)} of ${formatNumber(tally.total_staked)}</td>
+                <td>${new Date(p.closes_at * 1000).toLocaleString()}</td>
+            </tr>
+        `).join('');
+
+    } catch (error) {
+        console.error('Pools refresh error:', error);
+        poolsBody.innerHTML = '<tr><td colspan="7" class="loading">Error loading pools</td></tr>';
+    }
+}
+
 // ===== Settings Functions =====
 async function loadNodeInfo() {
     try {
@@ -795,6 +1073,21 @@ function getBlockTypeName(blockType) {
     return type || 'Unknown';
 }
 
+// Summaries carry user-chosen text (proposal titles, asset names), so they are
+// escaped before going into innerHTML
+function getBlockSummary(block) {
+    return escapeHtml(block.summary || getBlockTypeName(block.block_type));
+}
+
+function escapeHtml(text) {
+    return String(text)
+        .replace(/&/g, '&amp;')
+        .replace(/</g, '&lt;')
+        .replace(/>/g, '&gt;')
+        .replace(/"/g, '&quot;')
+        .replace(/'/g, '&#39;');
+}
+
 function getBlockTypeClass(blockType) {
     const name = getBlockTypeName(blockType).toLowerCase();
     if (name.includes('poh')) return 'primary';
@@ -855,6 +1148,7 @@ document.addEventListener('DOMContentLoaded', () => {
 
     // Setup navigation
     initNavigation();
+    renderContactOptions();
 
     // Initial load
     const savedAddress = localStorage.getItem('compass_wallet_address');
@@ -883,10 +1177,13 @@ document.addEventListener('DOMContentLoaded', () => {
 window.refreshBlocks = refreshBlocks;
 window.loadWallet = loadWallet;
 window.submitTransfer = submitTransfer;
+window.saveRecipientAsContact = saveRecipientAsContact;
 window.showCreateWallet = showCreateWallet;
 window.searchBlock = searchBlock;
 window.showBlockDetails = showBlockDetails;
 window.refreshModels = refreshModels;
+window.refreshPools = refreshPools;
+window.downloadActivityCsv = downloadActivityCsv;
 window.updateEndpoint = updateEndpoint;
 window.closeModal = closeModal;
 window.startTraining = startTraining;